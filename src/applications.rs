@@ -0,0 +1,145 @@
+// Applications assigned in IAM Identity Center besides AWS accounts, such as SAML apps,
+// as the access portal lists them
+//
+// The SSO API only covers accounts and roles, so these are read from the access portal's
+// own API with the same access token; it's the API the start page calls. The app opens
+// through the start page, which signs the user in to it.
+use crate::error::{Result, SsoError};
+use crate::network;
+use serde::Serialize;
+use serde_json::Value;
+
+/// An application on the user's start page
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Application {
+    /// Instance ID, e.g. ins-0123456789abcdef
+    pub id: String,
+    /// Name the start page shows
+    pub name: String,
+    /// What kind of app it is, e.g. "Custom SAML 2.0 application"
+    pub application_type: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Every application assigned to the user signed in with `access_token`
+pub async fn list(region: &str, access_token: &str) -> Result<Vec<Application>> {
+    let http = network::client_builder()?
+        .build()
+        .map_err(|e| SsoError::AwsSdk(format!("Failed to create HTTP client: {}", e)))?;
+    let url = format!("{}/instance/appinstances", endpoint(region));
+
+    let mut applications = Vec::new();
+    let mut pagination_token: Option<String> = None;
+    loop {
+        let mut request = http
+            .get(&url)
+            .header("x-amz-sso_bearer_token", access_token);
+        if let Some(token) = &pagination_token {
+            request = request.query(&[("paginationToken", token)]);
+        }
+        let response = network::send(request)
+            .await
+            .map_err(|e| SsoError::NetworkError(format!("Failed to list applications: {}", e)))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(SsoError::AuthExpired);
+        }
+        if !status.is_success() {
+            return Err(SsoError::AwsSdk(format!(
+                "Failed to list applications: HTTP {}",
+                status
+            )));
+        }
+        let page: Value = response
+            .json()
+            .await
+            .map_err(|e| SsoError::AwsSdk(format!("Failed to read the application list: {}", e)))?;
+
+        applications.extend(
+            page["result"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(application_from),
+        );
+        pagination_token = page["paginationToken"]
+            .as_str()
+            .filter(|token| !token.is_empty())
+            .map(str::to_string);
+        if pagination_token.is_none() {
+            break;
+        }
+    }
+
+    applications.sort_by_key(|app| app.name.to_lowercase());
+    Ok(applications)
+}
+
+fn application_from(value: &Value) -> Option<Application> {
+    let text = |key: &str| {
+        value[key]
+            .as_str()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    Some(Application {
+        id: text("id")?,
+        name: text("name")?,
+        application_type: text("applicationName"),
+        description: text("description"),
+    })
+}
+
+/// The portal API for `region`, or the endpoint override the SSO SDK client would use
+fn endpoint(region: &str) -> String {
+    ["AWS_ENDPOINT_URL_SSO", "AWS_ENDPOINT_URL"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|url| !url.is_empty())
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| format!("https://portal.sso.{}.amazonaws.com", region))
+}
+
+/// Start page link that signs in to `app`, like clicking its tile
+pub fn portal_url(start_url: &str, app: &Application) -> String {
+    let start_url = start_url.trim_end_matches('/').trim_end_matches("/#");
+    format!(
+        "{}/#/saml/default/{}/{}",
+        start_url,
+        urlencoding::encode(&app.name),
+        app.id
+    )
+}
+
+/// The application named `name`, ignoring case, or with that instance ID
+pub fn find<'a>(applications: &'a [Application], name: &str) -> Option<&'a Application> {
+    applications
+        .iter()
+        .find(|app| app.id == name || app.name.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portal_url() {
+        let app = Application {
+            id: "ins-0123456789abcdef".to_string(),
+            name: "Team Wiki".to_string(),
+            application_type: None,
+            description: None,
+        };
+        let expected =
+            "https://d-123.awsapps.com/start/#/saml/default/Team%20Wiki/ins-0123456789abcdef";
+        assert_eq!(
+            portal_url("https://d-123.awsapps.com/start", &app),
+            expected
+        );
+        assert_eq!(
+            portal_url("https://d-123.awsapps.com/start/#/", &app),
+            expected
+        );
+    }
+}
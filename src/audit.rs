@@ -0,0 +1,199 @@
+// Local append-only audit log of credential issuance
+//
+// Each event is one JSON object per line in <cache dir>/awsom/audit.jsonl. Recording is
+// opt-in via `[audit] enabled = true` in config.toml and never fails the calling command.
+use crate::config::{self, Config};
+use crate::error::{Result, SsoError};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Command that started this process (set once from the CLI dispatcher)
+static INITIATOR: OnceLock<String> = OnceLock::new();
+
+/// Record which command is issuing credentials for the rest of this process
+pub fn set_initiator(initiator: &str) {
+    let _ = INITIATOR.set(initiator.to_string());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// Role credentials were fetched from AWS SSO
+    CredentialsIssued,
+    /// A federated console sign-in URL was opened
+    ConsoleOpened,
+    /// Credentials were written to a profile in ~/.aws/credentials
+    ProfileWritten,
+}
+
+impl AuditAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            AuditAction::CredentialsIssued => "credentials_issued",
+            AuditAction::ConsoleOpened => "console_opened",
+            AuditAction::ProfileWritten => "profile_written",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub initiator: String,
+}
+
+/// Command that started this process, "unknown" before `set_initiator`
+pub fn initiator() -> String {
+    INITIATOR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Path to audit.jsonl, next to the TUI log file
+pub fn log_path() -> PathBuf {
+    config::cache_dir().join("audit.jsonl")
+}
+
+/// Append an event if auditing is enabled, and not in read-only mode
+///
+/// Failures are logged rather than returned: an unwritable audit log shouldn't stop
+/// someone from getting credentials.
+pub fn record(
+    action: AuditAction,
+    account_id: Option<&str>,
+    role_name: Option<&str>,
+    profile: Option<&str>,
+) {
+    let enabled = Config::load().map(|c| c.audit.enabled).unwrap_or(false);
+    if !enabled || config::is_read_only() {
+        return;
+    }
+
+    let event = AuditEvent {
+        timestamp: Utc::now(),
+        action,
+        account_id: account_id.map(str::to_string),
+        role_name: role_name.map(str::to_string),
+        profile: profile.map(str::to_string),
+        initiator: initiator(),
+    };
+
+    if let Err(e) = append(&event) {
+        tracing::warn!("Failed to write audit log: {}", e);
+    }
+}
+
+fn append(event: &AuditEvent) -> Result<()> {
+    let path = log_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Read events at or after `since` (all events if None), oldest first
+pub fn read_events(since: Option<DateTime<Utc>>) -> Result<Vec<AuditEvent>> {
+    let path = log_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let mut events = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<AuditEvent>(line) {
+            Ok(event) if since.map_or(true, |since| event.timestamp >= since) => events.push(event),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Skipping malformed audit log line {}: {}", index + 1, e),
+        }
+    }
+
+    Ok(events)
+}
+
+/// Parse a `--since` value: RFC 3339, a date (YYYY-MM-DD), or a relative
+/// duration such as `30m`, `24h` or `7d`
+pub fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if let Some(midnight) = date.and_hms_opt(0, 0, 0) {
+            return Ok(midnight.and_utc());
+        }
+    }
+
+    let invalid = || {
+        SsoError::InvalidConfig(format!(
+            "Invalid --since value '{}' (expected e.g. 24h, 7d or 2024-01-31)",
+            value
+        ))
+    };
+
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+
+    Ok(Utc::now() - duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since_absolute() {
+        let time = parse_since("2024-01-31").unwrap();
+        assert_eq!(time.to_rfc3339(), "2024-01-31T00:00:00+00:00");
+
+        let time = parse_since("2024-01-31T12:30:00Z").unwrap();
+        assert_eq!(time.to_rfc3339(), "2024-01-31T12:30:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_since_relative() {
+        let time = parse_since("24h").unwrap();
+        let expected = Utc::now() - Duration::hours(24);
+        assert!((time - expected).num_seconds().abs() < 5);
+
+        assert!(parse_since("7x").is_err());
+        assert!(parse_since("").is_err());
+        assert!(parse_since("h").is_err());
+    }
+}
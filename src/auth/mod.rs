@@ -1,12 +1,77 @@
 // AWS SSO OIDC authentication module
 mod oidc;
+mod pkce;
 mod token_cache;
 
 pub use oidc::{DeviceAuthorizationInfo, OidcClient};
+#[cfg(feature = "cli")]
+pub(crate) use pkce::base64_url;
 pub use token_cache::TokenCache;
 
+use crate::aws_config;
+use crate::config::{AuthFlow, Config};
 use crate::error::Result;
+use crate::hooks::{self, HookContext, HookEvent};
 use crate::models::{SsoInstance, SsoToken};
+use std::sync::OnceLock;
+
+/// Tell the `on_login` hook about a new token
+fn run_login_hook(instance: &SsoInstance, token: &SsoToken) {
+    hooks::run(
+        HookEvent::Login,
+        &HookContext {
+            session: instance.session_name.as_deref(),
+            start_url: Some(&instance.start_url),
+            region: Some(&instance.region),
+            expires_at: Some(token.expires_at),
+            ..HookContext::default()
+        },
+    );
+}
+
+/// Auth flow chosen on the command line, overriding `[login] auth_flow`
+static AUTH_FLOW_OVERRIDE: OnceLock<AuthFlow> = OnceLock::new();
+
+/// Set the auth flow override (called for `session login --auth-flow`)
+pub fn set_auth_flow_override(flow: AuthFlow) {
+    let _ = AUTH_FLOW_OVERRIDE.set(flow);
+}
+
+/// The auth flow for CLI logins, from --auth-flow or `[login] auth_flow`
+fn auth_flow() -> AuthFlow {
+    AUTH_FLOW_OVERRIDE.get().copied().unwrap_or_else(|| {
+        Config::load()
+            .map(|config| config.login.auth_flow)
+            .unwrap_or_default()
+    })
+}
+
+/// How [`AuthManager::login`] logs in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoginOptions {
+    force_refresh: bool,
+    headless: bool,
+}
+
+impl LoginOptions {
+    /// Reuse a valid cached token, and open the browser for a new login
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log in again even if the cached token is still valid
+    pub fn force_refresh(mut self, force_refresh: bool) -> Self {
+        self.force_refresh = force_refresh;
+        self
+    }
+
+    /// Don't open a browser: print the device code URL to open elsewhere, and use the
+    /// device code flow even if `[login] auth_flow = "pkce"`
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+}
 
 /// High-level authentication interface
 pub struct AuthManager {
@@ -36,14 +101,9 @@ impl AuthManager {
     }
 
     /// Start interactive SSO login flow
-    pub async fn login(
-        &self,
-        instance: &SsoInstance,
-        force_refresh: bool,
-        headless: bool,
-    ) -> Result<SsoToken> {
+    pub async fn login(&self, instance: &SsoInstance, options: LoginOptions) -> Result<SsoToken> {
         // Check cache first unless force_refresh
-        if !force_refresh {
+        if !options.force_refresh {
             if let Some(token) = self.get_cached_token(instance)? {
                 if !token.is_expired() {
                     return Ok(token);
@@ -51,14 +111,25 @@ impl AuthManager {
             }
         }
 
-        // Initiate OIDC device flow
+        // Initiate OIDC login; PKCE needs a browser on this machine
+        let scopes = aws_config::registration_scopes_for(instance);
         let oidc_client = OidcClient::new(&instance.region).await?;
-        let token = oidc_client
-            .perform_device_flow(&instance.start_url, headless)
-            .await?;
+        let token = match auth_flow() {
+            AuthFlow::Pkce if !options.headless => {
+                oidc_client
+                    .perform_pkce_flow(&instance.start_url, &scopes)
+                    .await?
+            }
+            _ => {
+                oidc_client
+                    .perform_device_flow(&instance.start_url, &scopes, options.headless)
+                    .await?
+            }
+        };
 
         // Cache the token
         self.save_token(instance, token.clone())?;
+        run_login_hook(instance, &token);
 
         Ok(token)
     }
@@ -84,13 +155,15 @@ impl AuthManager {
         }
 
         // Initiate OIDC device flow with callback
+        let scopes = aws_config::registration_scopes_for(instance);
         let oidc_client = OidcClient::new(&instance.region).await?;
         let token = oidc_client
-            .perform_device_flow_with_callback(&instance.start_url, display_callback)
+            .perform_device_flow_with_callback(&instance.start_url, &scopes, display_callback)
             .await?;
 
         // Cache the token
         self.save_token(instance, token.clone())?;
+        run_login_hook(instance, &token);
 
         Ok(token)
     }
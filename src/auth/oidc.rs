@@ -1,13 +1,34 @@
-use crate::error::{Result, SsoError};
+use super::pkce::{self, CallbackListener, Pkce};
+use super::token_cache::{ClientRegistration, TokenCache};
+use crate::config;
+use crate::error::{self, Result, SsoError};
 use crate::models::SsoToken;
+use aws_sdk_ssooidc::error::SdkError;
+use aws_sdk_ssooidc::operation::create_token::CreateTokenOutput;
+use aws_sdk_ssooidc::operation::register_client::RegisterClientOutput;
 use aws_sdk_ssooidc::Client as SsoOidcClient;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use std::time::Duration as StdDuration;
 use tokio::time::sleep;
 
 const CLIENT_NAME: &str = "awsom";
 const CLIENT_TYPE: &str = "public";
 const POLL_INTERVAL_SECONDS: u64 = 5;
+/// How long to wait for the browser to come back in the PKCE flow
+const PKCE_TIMEOUT: StdDuration = StdDuration::from_secs(300);
+
+/// Convert an SSO-OIDC API error, keeping "couldn't reach AWS" apart from AWS refusing
+fn sdk_error<E, R>(context: &str, err: SdkError<E, R>) -> SsoError
+where
+    E: std::error::Error + 'static,
+    R: std::fmt::Debug,
+{
+    if error::is_network_error(&err) {
+        SsoError::NetworkError(format!("{}: {}", context, err))
+    } else {
+        SsoError::AwsSdk(format!("{}: {}", context, err))
+    }
+}
 
 /// Device authorization information from StartDeviceAuthorization
 #[derive(Debug, Clone)]
@@ -16,9 +37,49 @@ pub struct DeviceAuthorizationInfo {
     pub user_code: String,
     pub verification_uri: String,
     pub verification_uri_complete: Option<String>,
-    #[allow(dead_code)]
     pub expires_in: i32,
     pub interval: Option<i32>,
+    /// When the user code stops working, from `expires_in`
+    pub expires_at: DateTime<Utc>,
+}
+
+impl DeviceAuthorizationInfo {
+    /// Seconds until the user code expires, 0 once it has
+    pub fn seconds_left(&self) -> i64 {
+        (self.expires_at - Utc::now()).num_seconds().max(0)
+    }
+
+    /// URL to send the user to - the complete form already has the code filled in
+    pub fn url(&self) -> &str {
+        self.verification_uri_complete
+            .as_deref()
+            .unwrap_or(&self.verification_uri)
+    }
+
+    /// Open the verification URL in a browser, unless running headless
+    ///
+    /// Shared by the CLI and TUI so `--headless`/`--no-browser` and auto-detection
+    /// behave the same everywhere. Returns whether a browser was opened.
+    pub fn open_browser(&self) -> bool {
+        if crate::env::is_headless_environment() {
+            tracing::info!("Headless environment - not opening a browser for device login");
+            return false;
+        }
+
+        match webbrowser::open(self.url()) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Could not open browser automatically: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Copy the verification URL to the clipboard
+    #[cfg(feature = "cli")]
+    pub fn copy_url(&self) -> Result<()> {
+        crate::clipboard::copy(self.url())
+    }
 }
 
 /// OIDC client for AWS SSO device flow authentication
@@ -29,10 +90,7 @@ pub struct OidcClient {
 
 impl OidcClient {
     pub async fn new(region: &str) -> Result<Self> {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
+        let config = crate::network::sdk_config(region).await?;
 
         let client = SsoOidcClient::new(&config);
 
@@ -42,31 +100,93 @@ impl OidcClient {
         })
     }
 
-    /// Register this client with AWS SSO OIDC
-    async fn register_client(&self) -> Result<(String, String)> {
-        tracing::debug!("Registering client with SSO-OIDC");
+    /// Register this client with AWS SSO OIDC, asking for `scopes` (none leaves the
+    /// choice to Identity Center)
+    async fn register_client(&self, scopes: &[String]) -> Result<ClientRegistration> {
+        tracing::debug!("Registering client with SSO-OIDC, scopes {:?}", scopes);
 
         let response = self
             .client
             .register_client()
             .client_name(CLIENT_NAME)
             .client_type(CLIENT_TYPE)
+            .set_scopes((!scopes.is_empty()).then(|| scopes.to_vec()))
             .send()
             .await
-            .map_err(|e| SsoError::AwsSdk(format!("Failed to register client: {}", e)))?;
+            .map_err(|e| sdk_error("Failed to register client", e))?;
 
-        let client_id = response
-            .client_id()
-            .ok_or_else(|| SsoError::AwsSdk("No client_id in response".to_string()))?
-            .to_string();
+        registration_from(&response, &self.region, scopes)
+    }
 
-        let client_secret = response
-            .client_secret()
-            .ok_or_else(|| SsoError::AwsSdk("No client_secret in response".to_string()))?
-            .to_string();
+    /// Start device authorization with the registration cached for this region and scopes,
+    /// registering (and caching) a new client when there is none or AWS rejects it
+    async fn start_device_flow(
+        &self,
+        start_url: &str,
+        scopes: &[String],
+    ) -> Result<(ClientRegistration, DeviceAuthorizationInfo)> {
+        let cache = TokenCache::new()?;
+        if let Some(registration) = cache.get_registration(&self.region, scopes) {
+            tracing::debug!("Reusing client registration {}", registration.client_id);
+            match self
+                .start_device_authorization(
+                    &registration.client_id,
+                    &registration.client_secret,
+                    start_url,
+                )
+                .await
+            {
+                Ok(auth_info) => return Ok((registration, auth_info)),
+                Err(SsoError::AwsSdk(e)) => {
+                    tracing::debug!("Cached client registration rejected: {}", e);
+                    if let Err(e) = cache.remove_registration(&self.region, scopes) {
+                        tracing::warn!("Failed to remove client registration: {}", e);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
 
-        tracing::debug!("Client registered successfully");
-        Ok((client_id, client_secret))
+        let registration = self.register_client(scopes).await?;
+        if !config::is_read_only() {
+            if let Err(e) = cache.save_registration(&registration) {
+                tracing::warn!("Failed to cache client registration: {}", e);
+            }
+        }
+        let auth_info = self
+            .start_device_authorization(
+                &registration.client_id,
+                &registration.client_secret,
+                start_url,
+            )
+            .await?;
+        Ok((registration, auth_info))
+    }
+
+    /// Register this client for the authorization code flow, redirecting to `redirect_uri`
+    async fn register_pkce_client(
+        &self,
+        start_url: &str,
+        scopes: &[String],
+        redirect_uri: &str,
+    ) -> Result<ClientRegistration> {
+        tracing::debug!("Registering PKCE client with SSO-OIDC, scopes {:?}", scopes);
+
+        let response = self
+            .client
+            .register_client()
+            .client_name(CLIENT_NAME)
+            .client_type(CLIENT_TYPE)
+            .set_scopes((!scopes.is_empty()).then(|| scopes.to_vec()))
+            .grant_types("authorization_code")
+            .grant_types("refresh_token")
+            .redirect_uris(redirect_uri)
+            .issuer_url(start_url)
+            .send()
+            .await
+            .map_err(|e| sdk_error("Failed to register client", e))?;
+
+        registration_from(&response, &self.region, scopes)
     }
 
     /// Start device authorization flow
@@ -86,9 +206,7 @@ impl OidcClient {
             .start_url(start_url)
             .send()
             .await
-            .map_err(|e| {
-                SsoError::AwsSdk(format!("Failed to start device authorization: {}", e))
-            })?;
+            .map_err(|e| sdk_error("Failed to start device authorization", e))?;
 
         Ok(DeviceAuthorizationInfo {
             device_code: response
@@ -106,52 +224,42 @@ impl OidcClient {
             verification_uri_complete: response.verification_uri_complete().map(|s| s.to_string()),
             expires_in: response.expires_in(),
             interval: Some(response.interval()),
+            expires_at: Utc::now() + Duration::seconds(i64::from(response.expires_in())),
         })
     }
 
-    /// Poll for token after user authorizes
+    /// Poll for token after user authorizes, until the user code expires
+    ///
+    /// The future can be dropped at any await to stop polling; nothing is left running.
     async fn poll_for_token(
         &self,
-        client_id: &str,
-        client_secret: &str,
-        device_code: &str,
-        poll_interval: u64,
+        registration: &ClientRegistration,
+        auth_info: &DeviceAuthorizationInfo,
         start_url: &str,
     ) -> Result<SsoToken> {
+        let mut poll_interval = auth_info
+            .interval
+            .filter(|&i| i > 0)
+            .map_or(POLL_INTERVAL_SECONDS, |i| i as u64);
         tracing::debug!("Polling for token with interval: {}s", poll_interval);
 
         loop {
+            let seconds_left = auth_info.seconds_left();
+            if seconds_left == 0 {
+                return Err(SsoError::AuthorizationExpired);
+            }
+
             match self
                 .client
                 .create_token()
-                .client_id(client_id)
-                .client_secret(client_secret)
+                .client_id(&registration.client_id)
+                .client_secret(&registration.client_secret)
                 .grant_type("urn:ietf:params:oauth:grant-type:device_code")
-                .device_code(device_code)
+                .device_code(&auth_info.device_code)
                 .send()
                 .await
             {
-                Ok(response) => {
-                    tracing::debug!("Token received successfully");
-
-                    let access_token = response
-                        .access_token()
-                        .ok_or_else(|| SsoError::AwsSdk("No access_token in response".to_string()))?
-                        .to_string();
-
-                    let expires_in = response.expires_in();
-                    let expires_at = Utc::now() + Duration::seconds(expires_in as i64);
-
-                    tracing::debug!("Token expires in {} seconds", expires_in);
-
-                    return Ok(SsoToken {
-                        access_token,
-                        expires_at,
-                        refresh_token: response.refresh_token().map(|s| s.to_string()),
-                        region: Some(self.region.clone()),
-                        start_url: Some(start_url.to_string()),
-                    });
-                }
+                Ok(response) => return self.token_from(&response, registration, start_url),
                 Err(err) => {
                     // Check error metadata for the error code
                     use aws_sdk_ssooidc::error::ProvideErrorMetadata;
@@ -166,14 +274,15 @@ impl OidcClient {
                         match code {
                             "AuthorizationPendingException" => {
                                 // User hasn't authorized yet, continue polling
-                                sleep(StdDuration::from_secs(poll_interval)).await;
-                                continue;
+                                tracing::debug!("Authorization pending, {}s left", seconds_left);
                             }
                             "SlowDownException" => {
-                                // We're polling too fast, slow down
-                                tracing::debug!("SlowDown requested, increasing poll interval");
-                                sleep(StdDuration::from_secs(poll_interval + 5)).await;
-                                continue;
+                                // RFC 8628: poll 5 seconds less often from now on
+                                poll_interval += 5;
+                                tracing::debug!(
+                                    "SlowDown requested, polling every {}s",
+                                    poll_interval
+                                );
                             }
                             "ExpiredTokenException" => {
                                 return Err(SsoError::AuthorizationExpired);
@@ -192,36 +301,106 @@ impl OidcClient {
                     }
                 }
             }
+
+            // No point sleeping past the code's expiry
+            let wait = poll_interval.min(auth_info.seconds_left().max(1) as u64);
+            sleep(StdDuration::from_secs(wait)).await;
         }
     }
 
     /// Perform complete device flow authentication
-    pub async fn perform_device_flow(&self, start_url: &str, headless: bool) -> Result<SsoToken> {
-        // Step 1: Register client
-        let (client_id, client_secret) = self.register_client().await?;
-
-        // Step 2: Start device authorization
-        let auth_info = self
-            .start_device_authorization(&client_id, &client_secret, start_url)
-            .await?;
+    pub async fn perform_device_flow(
+        &self,
+        start_url: &str,
+        scopes: &[String],
+        headless: bool,
+    ) -> Result<SsoToken> {
+        // Steps 1 and 2: Register client, or reuse the cached registration, and start
+        // device authorization
+        let (registration, auth_info) = self.start_device_flow(start_url, scopes).await?;
 
         // Step 3: Display authorization info to user
         self.display_authorization_prompt(&auth_info, headless)?;
 
         // Step 4: Poll for token
-        let poll_interval = auth_info
-            .interval
-            .map(|i| i as u64)
-            .unwrap_or(POLL_INTERVAL_SECONDS);
-
-        self.poll_for_token(
-            &client_id,
-            &client_secret,
-            &auth_info.device_code,
-            poll_interval,
-            start_url,
-        )
-        .await
+        self.poll_for_token(&registration, &auth_info, start_url)
+            .await
+    }
+
+    /// Log in with the authorization code flow with PKCE: the browser signs in and is
+    /// redirected to a listener on localhost, so there is no code to confirm. Falls back
+    /// to the device flow when no browser can be opened.
+    pub async fn perform_pkce_flow(&self, start_url: &str, scopes: &[String]) -> Result<SsoToken> {
+        let listener = CallbackListener::bind().await?;
+        let redirect_uri = listener.redirect_uri().to_string();
+        let registration = self
+            .register_pkce_client(start_url, scopes, &redirect_uri)
+            .await?;
+        let pkce = Pkce::new()?;
+        let url = pkce::authorize_url(
+            &self.region,
+            &registration.client_id,
+            &redirect_uri,
+            scopes,
+            &pkce,
+        );
+
+        if crate::env::is_headless_environment() || webbrowser::open(&url).is_err() {
+            eprintln!("Could not open a browser; falling back to the device code flow");
+            return self.perform_device_flow(start_url, scopes, true).await;
+        }
+        eprintln!("\n=== AWS SSO Login ===");
+        eprintln!("Opened browser to sign in. If the page didn't load, open:");
+        eprintln!();
+        eprintln!("  {}", url);
+        eprintln!();
+        eprintln!("Waiting for the browser to return...");
+
+        let code = listener.wait_for_code(&pkce.state, PKCE_TIMEOUT).await?;
+        let response = self
+            .client
+            .create_token()
+            .client_id(&registration.client_id)
+            .client_secret(&registration.client_secret)
+            .grant_type("authorization_code")
+            .code(code)
+            .redirect_uri(&redirect_uri)
+            .code_verifier(&pkce.verifier)
+            .send()
+            .await
+            .map_err(|e| sdk_error("Failed to create token", e))?;
+        self.token_from(&response, &registration, start_url)
+    }
+
+    /// The token in a CreateToken response, with what's needed to refresh it
+    fn token_from(
+        &self,
+        response: &CreateTokenOutput,
+        registration: &ClientRegistration,
+        start_url: &str,
+    ) -> Result<SsoToken> {
+        tracing::debug!("Token received successfully");
+
+        let access_token = response
+            .access_token()
+            .ok_or_else(|| SsoError::AwsSdk("No access_token in response".to_string()))?
+            .to_string();
+
+        let expires_in = response.expires_in();
+        let expires_at = Utc::now() + Duration::seconds(expires_in as i64);
+
+        tracing::debug!("Token expires in {} seconds", expires_in);
+
+        Ok(SsoToken {
+            access_token,
+            expires_at,
+            refresh_token: response.refresh_token().map(|s| s.to_string()),
+            region: Some(self.region.clone()),
+            start_url: Some(start_url.to_string()),
+            client_id: Some(registration.client_id.clone()),
+            client_secret: Some(registration.client_secret.clone()),
+            registration_expires_at: registration.expires_at,
+        })
     }
 
     /// Perform device flow authentication with callback for displaying auth info
@@ -229,36 +408,22 @@ impl OidcClient {
     pub async fn perform_device_flow_with_callback<F>(
         &self,
         start_url: &str,
+        scopes: &[String],
         display_callback: F,
     ) -> Result<SsoToken>
     where
         F: FnOnce(&DeviceAuthorizationInfo) -> Result<()>,
     {
-        // Step 1: Register client
-        let (client_id, client_secret) = self.register_client().await?;
-
-        // Step 2: Start device authorization
-        let auth_info = self
-            .start_device_authorization(&client_id, &client_secret, start_url)
-            .await?;
+        // Steps 1 and 2: Register client, or reuse the cached registration, and start
+        // device authorization
+        let (registration, auth_info) = self.start_device_flow(start_url, scopes).await?;
 
         // Step 3: Call display callback (caller controls display)
         display_callback(&auth_info)?;
 
         // Step 4: Poll for token
-        let poll_interval = auth_info
-            .interval
-            .map(|i| i as u64)
-            .unwrap_or(POLL_INTERVAL_SECONDS);
-
-        self.poll_for_token(
-            &client_id,
-            &client_secret,
-            &auth_info.device_code,
-            poll_interval,
-            start_url,
-        )
-        .await
+        self.poll_for_token(&registration, &auth_info, start_url)
+            .await
     }
 
     /// Display authorization prompt to user and optionally open browser
@@ -269,34 +434,66 @@ impl OidcClient {
     ) -> Result<()> {
         eprintln!("\n=== AWS SSO Login ===");
 
-        if headless {
-            // Headless mode - don't try to open browser, show clear instructions
-            eprintln!("Running in headless mode - please open browser manually:");
-            eprintln!();
-            eprintln!("Visit: {}", auth_info.verification_uri);
-            eprintln!("Enter code: {}", auth_info.user_code);
-            eprintln!();
+        let browser_opened = !headless && auth_info.open_browser();
+        if browser_opened {
+            eprintln!("Opened browser to: {}", auth_info.url());
+            eprintln!("\nIf the page didn't load, open the URL below instead.");
         } else {
-            // Normal mode - try to open browser
-            eprintln!("Opening browser to: {}", auth_info.verification_uri);
-            eprintln!("\nIf browser doesn't open automatically, visit:");
-            eprintln!("  {}", auth_info.verification_uri);
-            eprintln!("\nAnd enter code: {}\n", auth_info.user_code);
-
-            // Try to open browser with complete URL if available
-            let url_to_open = auth_info
-                .verification_uri_complete
-                .as_ref()
-                .unwrap_or(&auth_info.verification_uri);
-
-            if let Err(e) = webbrowser::open(url_to_open) {
-                eprintln!("Could not open browser automatically: {}", e);
-                eprintln!("Please open the URL manually.\n");
+            eprintln!("Open this URL in a browser (on any device):");
+        }
+
+        eprintln!();
+        eprintln!("  {}", auth_info.url());
+        eprintln!();
+        eprintln!(
+            "Or visit {} and enter code: {}",
+            auth_info.verification_uri, auth_info.user_code
+        );
+        eprintln!(
+            "The code expires in {} minutes.",
+            (auth_info.expires_in / 60).max(1)
+        );
+
+        #[cfg(feature = "cli")]
+        if crate::clipboard::should_copy_device_url(&config::Config::load().unwrap_or_default()) {
+            match auth_info.copy_url() {
+                Ok(()) => eprintln!("✓ Copied URL to clipboard"),
+                Err(e) => eprintln!("Could not copy URL: {}", e),
             }
         }
 
-        eprintln!("Waiting for authorization...");
+        eprintln!("\nWaiting for authorization...");
 
         Ok(())
     }
 }
+
+/// Client ID and secret from a RegisterClient response for `region` and `scopes`
+fn registration_from(
+    response: &RegisterClientOutput,
+    region: &str,
+    scopes: &[String],
+) -> Result<ClientRegistration> {
+    let client_id = response
+        .client_id()
+        .ok_or_else(|| SsoError::AwsSdk("No client_id in response".to_string()))?
+        .to_string();
+
+    let client_secret = response
+        .client_secret()
+        .ok_or_else(|| SsoError::AwsSdk("No client_secret in response".to_string()))?
+        .to_string();
+
+    let expires_at = Utc
+        .timestamp_opt(response.client_secret_expires_at(), 0)
+        .single();
+
+    tracing::debug!("Client registered successfully");
+    Ok(ClientRegistration {
+        client_id,
+        client_secret,
+        expires_at,
+        region: region.to_string(),
+        scopes: scopes.to_vec(),
+    })
+}
@@ -0,0 +1,224 @@
+// Pieces of the authorization code flow with PKCE (RFC 7636), as the AWS CLI v2 uses it
+//
+// The browser signs in at the SSO-OIDC /authorize endpoint and is redirected to
+// http://127.0.0.1:<port>/oauth/callback, where `CallbackListener` picks up the code.
+use crate::error::{Result, SsoError};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const CALLBACK_PATH: &str = "/oauth/callback";
+
+/// Verifier, challenge and state of one login
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+    pub state: String,
+}
+
+impl Pkce {
+    pub fn new() -> Result<Self> {
+        let mut random = [0u8; 32];
+        SystemRandom::new().fill(&mut random).map_err(|_| {
+            SsoError::AuthenticationFailed("No secure random numbers available".to_string())
+        })?;
+        let verifier = base64_url(&random);
+        Ok(Self {
+            challenge: code_challenge(&verifier),
+            verifier,
+            state: uuid::Uuid::new_v4().to_string(),
+        })
+    }
+}
+
+/// S256 challenge for a verifier
+fn code_challenge(verifier: &str) -> String {
+    base64_url(digest(&SHA256, verifier.as_bytes()).as_ref())
+}
+
+/// Unpadded base64url
+pub(crate) fn base64_url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::with_capacity(bytes.len() * 4 / 3 + 3);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+/// The page to send the browser to
+pub fn authorize_url(
+    region: &str,
+    client_id: &str,
+    redirect_uri: &str,
+    scopes: &[String],
+    pkce: &Pkce,
+) -> String {
+    let params = [
+        ("response_type", "code"),
+        ("client_id", client_id),
+        ("redirect_uri", redirect_uri),
+        ("state", &pkce.state),
+        ("code_challenge_method", "S256"),
+        ("scopes", &scopes.join(" ")),
+        ("code_challenge", &pkce.challenge),
+    ];
+    let query: Vec<String> = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, urlencoding::encode(value)))
+        .collect();
+    format!(
+        "https://oidc.{}.amazonaws.com/authorize?{}",
+        region,
+        query.join("&")
+    )
+}
+
+/// Localhost listener the browser is redirected to after signing in
+pub struct CallbackListener {
+    listener: TcpListener,
+    redirect_uri: String,
+}
+
+impl CallbackListener {
+    /// Listen on a free port on the loopback interface
+    pub async fn bind() -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .map_err(SsoError::Io)?;
+        let port = listener.local_addr().map_err(SsoError::Io)?.port();
+        Ok(Self {
+            listener,
+            redirect_uri: format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH),
+        })
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// Wait for the redirect carrying the authorization code for `state`, answering the
+    /// browser with a page saying whether login worked. Other requests (favicons) are
+    /// answered with 404 and ignored.
+    pub async fn wait_for_code(&self, state: &str, timeout: Duration) -> Result<String> {
+        tokio::time::timeout(timeout, self.accept_code(state))
+            .await
+            .map_err(|_| SsoError::AuthorizationExpired)?
+    }
+
+    async fn accept_code(&self, state: &str) -> Result<String> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await.map_err(SsoError::Io)?;
+            let mut buffer = vec![0u8; 8192];
+            let read = stream.read(&mut buffer).await.map_err(SsoError::Io)?;
+            let request = String::from_utf8_lossy(&buffer[..read]);
+
+            let Some(callback) = parse_callback(&request) else {
+                let _ = stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                    .await;
+                continue;
+            };
+
+            let result = match callback {
+                Callback::Code {
+                    code,
+                    state: Some(received),
+                } if received == state => Ok(code),
+                Callback::Code { .. } => Err(SsoError::AuthenticationFailed(
+                    "Login redirect doesn't belong to this login (state mismatch)".to_string(),
+                )),
+                Callback::Error(message) => Err(SsoError::AuthenticationFailed(format!(
+                    "Authorization was denied: {}",
+                    message
+                ))),
+            };
+
+            let body = match &result {
+                Ok(_) => "awsom: login complete. You can close this tab.".to_string(),
+                Err(e) => format!("awsom: login failed. {}", e),
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            return result;
+        }
+    }
+}
+
+/// What the redirect to the callback path carried
+#[derive(Debug, PartialEq)]
+enum Callback {
+    Code { code: String, state: Option<String> },
+    Error(String),
+}
+
+/// Parse the request line of a redirect to the callback path; None for other requests
+fn parse_callback(request: &str) -> Option<Callback> {
+    let target = request.lines().next()?.strip_prefix("GET ")?;
+    let target = target.split(' ').next()?;
+    let query = target.strip_prefix(CALLBACK_PATH)?.strip_prefix('?')?;
+
+    let param = |name: &str| {
+        query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            (key == name).then(|| {
+                urlencoding::decode(&value.replace('+', " "))
+                    .map(|value| value.into_owned())
+                    .unwrap_or_default()
+            })
+        })
+    };
+
+    if let Some(error) = param("error") {
+        let description = param("error_description").unwrap_or_default();
+        return Some(Callback::Error(
+            format!("{} {}", error, description).trim().to_string(),
+        ));
+    }
+    Some(Callback::Code {
+        code: param("code")?,
+        state: param("state"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_challenge_and_callback() {
+        // base64url(sha256(verifier)) without padding
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mJ92K9qzvnKL2_uK5fSBHkglqIpmrM"),
+            "TFbsE_WXvmc3haqAoYfCKdv5W2jPbLRU1RVeb0kBd3I"
+        );
+        assert_eq!(base64_url(b"ab"), "YWI");
+        assert_eq!(base64_url(&[0xfb, 0xff]), "-_8");
+
+        assert_eq!(
+            parse_callback("GET /oauth/callback?code=abc%2F1&state=s1 HTTP/1.1\r\nHost: x\r\n"),
+            Some(Callback::Code {
+                code: "abc/1".to_string(),
+                state: Some("s1".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_callback("GET /oauth/callback?error=access_denied&state=s1 HTTP/1.1\r\n"),
+            Some(Callback::Error("access_denied".to_string()))
+        );
+        assert_eq!(parse_callback("GET /favicon.ico HTTP/1.1\r\n"), None);
+    }
+}
@@ -1,13 +1,49 @@
+use crate::aws_config;
+use crate::config;
 use crate::error::{Result, SsoError};
-use crate::models::{SsoInstance, SsoToken};
+use crate::keyring_store::KeyringStore;
+use crate::models::{normalize_start_url, SsoInstance, SsoToken};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// awsom's client registrations sit next to the tokens, named apart from both the tokens
+/// and the AWS CLI's registrations
+const REGISTRATION_PREFIX: &str = "awsom-registration-";
+
+/// A cached registration is only reused with at least this long left, so it can't expire
+/// in the middle of a login
+const REGISTRATION_MARGIN_HOURS: i64 = 1;
+
+/// Result of RegisterClient, stored alongside the token so the AWS CLI can refresh it, and
+/// cached per region and scopes so logins don't register a new client every time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientRegistration {
+    pub client_id: String,
+    pub client_secret: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub region: String,
+    pub scopes: Vec<String>,
+}
+
+impl ClientRegistration {
+    /// Whether it's good for a login starting now
+    pub fn is_usable(&self) -> bool {
+        self.expires_at.map_or(true, |at| {
+            at - Utc::now() > Duration::hours(REGISTRATION_MARGIN_HOURS)
+        })
+    }
+}
 
 /// Token cache compatible with AWS CLI v2
-/// Stores tokens in ~/.aws/sso/cache/
+/// Stores tokens in ~/.aws/sso/cache/, or in the OS keychain when configured
+/// (tokens in the keychain are not visible to the AWS CLI)
 pub struct TokenCache {
     cache_dir: PathBuf,
+    keyring: Option<KeyringStore>,
 }
 
 impl TokenCache {
@@ -19,82 +55,177 @@ impl TokenCache {
             .join("cache");
 
         // Create cache directory if it doesn't exist
-        if !cache_dir.exists() {
+        if !cache_dir.exists() && !config::is_read_only() {
             fs::create_dir_all(&cache_dir)?;
         }
 
-        Ok(Self { cache_dir })
+        Ok(Self {
+            cache_dir,
+            keyring: KeyringStore::from_config()?,
+        })
     }
 
-    /// Generate cache key (compatible with AWS CLI v2)
-    /// Uses SHA1 of session_name when available (modern [sso-session] format),
-    /// otherwise falls back to SHA1 of start_url (legacy SSO format)
-    fn cache_key(&self, instance: &SsoInstance) -> String {
-        let mut hasher = Sha1::new();
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
 
-        // Use session_name if available (AWS CLI v2 with [sso-session]),
-        // otherwise use start_url (legacy format)
-        let key_material = instance
-            .session_name
-            .as_deref()
-            .unwrap_or(&instance.start_url);
+    /// Cache keys for an instance, preferred first
+    ///
+    /// AWS CLI v2 names token files after SHA1 of the session name for [sso-session]
+    /// configs and SHA1 of the start URL for legacy profiles. When the caller didn't
+    /// name a session, the one in ~/.aws/config with the same start URL is used, so
+    /// `aws sso login --sso-session <name>` and `awsom session login` share a file.
+    ///
+    /// A token for the same start URL and region is as good under any key, so the rest are
+    /// the normalized start URL, the other sessions using it, and the URL as written and
+    /// with a trailing slash, which is how files were keyed before URLs were normalized.
+    fn cache_keys(&self, instance: &SsoInstance) -> Vec<String> {
+        let start_url = normalize_start_url(&instance.start_url);
+        let sessions = equivalent_sessions(&start_url, &instance.region);
 
-        hasher.update(key_material.as_bytes());
-        format!("{:x}", hasher.finalize())
+        let mut sources = Vec::new();
+        match &instance.session_name {
+            Some(name) => sources.push(name.clone()),
+            // Several sessions share the URL - can't tell which one the AWS CLI used
+            None if sessions.len() == 1 => sources.push(sessions[0].clone()),
+            None => {}
+        }
+        sources.push(start_url.clone());
+        sources.extend(sessions);
+        sources.push(instance.start_url.clone());
+        sources.push(format!("{}/", start_url));
+
+        let mut keys: Vec<String> = Vec::new();
+        for key in sources.iter().map(|source| sha1_hex(source)) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
     }
 
-    /// Get path to cache file for given instance
-    fn cache_file_path(&self, instance: &SsoInstance) -> PathBuf {
-        self.cache_dir
-            .join(format!("{}.json", self.cache_key(instance)))
+    /// Get path to cache file for a cache key
+    fn cache_file_path(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.json", key))
+    }
+
+    /// Keychain entry name for a cache key
+    fn keyring_key(&self, key: &str) -> String {
+        format!("sso-token:{}", key)
     }
 
     /// Get cached token for SSO instance
     pub fn get_token(&self, instance: &SsoInstance) -> Result<Option<SsoToken>> {
-        let cache_file = self.cache_file_path(instance);
+        let start_url = normalize_start_url(&instance.start_url);
+        for (index, key) in self.cache_keys(instance).into_iter().enumerate() {
+            let contents = match self.read_from_keyring(&key) {
+                Some(contents) => contents,
+                None => {
+                    let cache_file = self.cache_file_path(&key);
 
-        if !cache_file.exists() {
-            return Ok(None);
-        }
+                    if !cache_file.exists() {
+                        continue;
+                    }
 
-        let contents = fs::read_to_string(&cache_file)
-            .map_err(|e| SsoError::CacheError(format!("Failed to read cache file: {}", e)))?;
+                    fs::read_to_string(&cache_file).map_err(|e| {
+                        SsoError::CacheError(format!("Failed to read cache file: {}", e))
+                    })?
+                }
+            };
 
-        let token: SsoToken = serde_json::from_str(&contents)?;
+            let token: SsoToken = serde_json::from_str(&contents)?;
 
-        // Return None if token is expired
-        if token.is_expired() {
-            return Ok(None);
+            // A session can be re-pointed at another start URL; its old token is useless
+            if token
+                .start_url
+                .as_deref()
+                .is_some_and(|url| normalize_start_url(url) != start_url)
+            {
+                continue;
+            }
+
+            // Skip expired tokens - an older key may still hold a valid one
+            if token.is_expired() {
+                continue;
+            }
+            if index > 0 && !config::is_read_only() {
+                // Copy it to the preferred key so it's found there from now on. The original
+                // stays, since the AWS CLI may look it up by its own key
+                tracing::debug!("Copying cached token {} to the preferred cache key", key);
+                if let Err(e) = self.save_token(instance, token.clone()) {
+                    tracing::warn!("Failed to copy cached token: {}", e);
+                }
+            }
+            return Ok(Some(token));
         }
 
-        Ok(Some(token))
+        Ok(None)
     }
 
     /// Save token to cache
     pub fn save_token(&self, instance: &SsoInstance, token: SsoToken) -> Result<()> {
-        let cache_file = self.cache_file_path(instance);
+        let key = &self.cache_keys(instance)[0];
+        let cache_file = self.cache_file_path(key);
+        config::ensure_writable(&cache_file)?;
 
         let json = serde_json::to_string_pretty(&token)?;
 
-        fs::write(&cache_file, json)
+        if let Some(keyring) = &self.keyring {
+            match keyring.set(&self.keyring_key(key), &json) {
+                Ok(()) => {
+                    // Don't leave a stale plaintext copy behind
+                    if cache_file.exists() {
+                        fs::remove_file(&cache_file)?;
+                    }
+                    return Ok(());
+                }
+                Err(e) => tracing::warn!("{}; falling back to file cache", e),
+            }
+        }
+
+        write_private(&cache_file, &json)
             .map_err(|e| SsoError::CacheError(format!("Failed to write cache file: {}", e)))?;
 
         Ok(())
     }
 
-    /// Remove token from cache (logout)
+    /// Remove token from cache (logout), under every equivalent key so it isn't picked up
+    /// again from one of them
     pub fn remove_token(&self, instance: &SsoInstance) -> Result<()> {
-        let cache_file = self.cache_file_path(instance);
+        config::ensure_writable(&self.cache_dir)?;
+        for key in self.cache_keys(instance) {
+            if let Some(keyring) = &self.keyring {
+                if let Err(e) = keyring.delete(&self.keyring_key(&key)) {
+                    tracing::warn!("{}", e);
+                }
+            }
+
+            let cache_file = self.cache_file_path(&key);
 
-        if cache_file.exists() {
-            fs::remove_file(&cache_file)
-                .map_err(|e| SsoError::CacheError(format!("Failed to remove cache file: {}", e)))?;
+            if cache_file.exists() {
+                fs::remove_file(&cache_file).map_err(|e| {
+                    SsoError::CacheError(format!("Failed to remove cache file: {}", e))
+                })?;
+            }
         }
 
         Ok(())
     }
 
+    /// Read token JSON from the keychain, None if not configured, missing or unreachable
+    fn read_from_keyring(&self, key: &str) -> Option<String> {
+        let keyring = self.keyring.as_ref()?;
+        match keyring.get(&self.keyring_key(key)) {
+            Ok(contents) => contents,
+            Err(e) => {
+                tracing::warn!("{}; falling back to file cache", e);
+                None
+            }
+        }
+    }
+
     /// List all cached tokens
+    /// Only covers the file cache - the keychain can't be enumerated
     pub fn list_tokens(&self) -> Result<Vec<(String, SsoToken)>> {
         let mut tokens = Vec::new();
 
@@ -119,4 +250,182 @@ impl TokenCache {
 
         Ok(tokens)
     }
+
+    /// Registration file for a region and scope list
+    fn registration_path(&self, region: &str, scopes: &[String]) -> PathBuf {
+        let key = sha1_hex(&format!("{}|{}", region, scopes.join(",")));
+        self.cache_dir
+            .join(format!("{}{}.json", REGISTRATION_PREFIX, key))
+    }
+
+    /// The cached client registration for `region` and `scopes`, if it's still usable
+    pub fn get_registration(&self, region: &str, scopes: &[String]) -> Option<ClientRegistration> {
+        let contents = fs::read_to_string(self.registration_path(region, scopes)).ok()?;
+        let registration: ClientRegistration = serde_json::from_str(&contents).ok()?;
+        registration.is_usable().then_some(registration)
+    }
+
+    /// Cache a registration for the next login in its region with the same scopes
+    pub fn save_registration(&self, registration: &ClientRegistration) -> Result<()> {
+        let path = self.registration_path(&registration.region, &registration.scopes);
+        config::ensure_writable(&path)?;
+        write_private(&path, &serde_json::to_string_pretty(registration)?).map_err(|e| {
+            SsoError::CacheError(format!("Failed to write client registration: {}", e))
+        })
+    }
+
+    /// Forget the registration for `region` and `scopes`, e.g. once AWS stops accepting it
+    pub fn remove_registration(&self, region: &str, scopes: &[String]) -> Result<()> {
+        let path = self.registration_path(region, scopes);
+        config::ensure_writable(&path)?;
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| {
+                SsoError::CacheError(format!("Failed to remove client registration: {}", e))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// awsom's cached client registrations with their files
+    pub fn list_registrations(&self) -> Result<Vec<(PathBuf, ClientRegistration)>> {
+        let mut registrations = Vec::new();
+        if !self.cache_dir.exists() {
+            return Ok(registrations);
+        }
+
+        for entry in fs::read_dir(&self.cache_dir)? {
+            let path = entry?.path();
+            let is_registration = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(REGISTRATION_PREFIX));
+            if !is_registration {
+                continue;
+            }
+            let registration = fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| serde_json::from_str(&contents).ok());
+            if let Some(registration) = registration {
+                registrations.push((path, registration));
+            }
+        }
+        Ok(registrations)
+    }
+
+    /// Delete awsom's cached client registrations, returning how many there were; the next
+    /// login registers a new client
+    pub fn clear_registrations(&self) -> Result<usize> {
+        config::ensure_writable(&self.cache_dir)?;
+        let registrations = self.list_registrations()?;
+        for (path, _) in &registrations {
+            fs::remove_file(path)?;
+        }
+        Ok(registrations.len())
+    }
+
+    /// Delete every cached token file, returning how many there were. Client registrations,
+    /// awsom's and the AWS CLI's, in the same directory are left alone.
+    /// Only covers the file cache - the keychain can't be enumerated
+    pub fn clear_all(&self) -> Result<usize> {
+        config::ensure_writable(&self.cache_dir)?;
+        let tokens = self.list_tokens()?;
+        for (key, _) in &tokens {
+            fs::remove_file(self.cache_file_path(key))?;
+        }
+        Ok(tokens.len())
+    }
+}
+
+/// Write a token or registration readable by the user only, created that way so its secret
+/// is never readable with the umask's permissions
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files; tighten one written before
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(contents.as_bytes())
+}
+
+fn sha1_hex(input: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(input.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Names of the [sso-session]s in ~/.aws/config using this normalized start URL and region
+fn equivalent_sessions(start_url: &str, region: &str) -> Vec<String> {
+    aws_config::read_all_sso_sessions()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| normalize_start_url(&s.sso_start_url) == start_url && s.sso_region == region)
+        .map(|s| s.session_name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = TokenCache {
+            cache_dir: dir.path().to_path_buf(),
+            keyring: None,
+        };
+        let scopes = vec!["sso:account:access".to_string()];
+        let registration = ClientRegistration {
+            client_id: "id".to_string(),
+            client_secret: "secret".to_string(),
+            expires_at: Some(Utc::now() + Duration::days(90)),
+            region: "eu-west-1".to_string(),
+            scopes: scopes.clone(),
+        };
+        cache.save_registration(&registration).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let path = cache.registration_path("eu-west-1", &scopes);
+            let mode = fs::metadata(path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert_eq!(
+            cache.get_registration("eu-west-1", &scopes),
+            Some(registration.clone())
+        );
+        assert_eq!(cache.get_registration("us-east-1", &scopes), None);
+        assert_eq!(cache.get_registration("eu-west-1", &[]), None);
+        // Registrations aren't mistaken for tokens
+        assert!(cache.list_tokens().unwrap().is_empty());
+
+        let expiring = ClientRegistration {
+            expires_at: Some(Utc::now() + Duration::minutes(10)),
+            region: "us-east-1".to_string(),
+            ..registration
+        };
+        cache.save_registration(&expiring).unwrap();
+        assert_eq!(cache.get_registration("us-east-1", &scopes), None);
+
+        assert_eq!(cache.clear_registrations().unwrap(), 2);
+        assert_eq!(cache.get_registration("eu-west-1", &scopes), None);
+    }
+
+    #[test]
+    fn test_sha1_hex_matches_aws_cli() {
+        // Same value botocore produces for SHA1("my-sso")
+        assert_eq!(
+            sha1_hex("my-sso"),
+            "0ad374308c5a4e22f723adf10145eafad7c4031c"
+        );
+    }
 }
@@ -1,66 +1,154 @@
 // AWS credentials and config file writer
+use crate::audit::{self, AuditAction};
+use crate::config;
 use crate::error::{Result, SsoError};
-use crate::models::{AccountRole, RoleCredentials};
+use crate::history;
+use crate::hooks::{self, HookContext, HookEvent};
+use crate::ini::{IniDocument, Section};
+use crate::models::{AccountRole, RoleCredentials, SsoInstance};
+use crate::regions::Partition;
 use chrono::{DateTime, Utc};
+use fs2::FileExt;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
-/// Check if awsom has been initialized (backups created)
-fn is_initialized() -> Result<bool> {
-    if let Some(home) = dirs::home_dir() {
-        let marker_file = home.join(".aws").join(".awsom-initialized");
-        Ok(marker_file.exists())
-    } else {
-        Err(SsoError::ConfigError(
-            "Could not determine home directory".to_string(),
-        ))
-    }
+/// How long to wait for another process to release a config/credentials lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Environment variables the AWS CLI and SDKs read the file locations from
+const CONFIG_FILE_ENV: &str = "AWS_CONFIG_FILE";
+const CREDENTIALS_FILE_ENV: &str = "AWS_SHARED_CREDENTIALS_FILE";
+
+static CONFIG_FILE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+static CREDENTIALS_FILE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Exclusive advisory lock on an AWS file, released when dropped
+///
+/// The lock lives on a sidecar `.<name>.lock` file rather than the file itself, because
+/// `write_atomic` replaces the file's inode on every write.
+pub struct FileLock {
+    _file: fs::File,
 }
 
-/// Create backups of config and credentials files on first use
-fn create_backups_if_needed() -> Result<()> {
-    // Check if already initialized
-    if is_initialized()? {
-        return Ok(());
+/// Take the write lock for `path`, retrying with backoff while another process holds it
+///
+/// Hold the lock across the whole read-modify-write so concurrent writers can't drop
+/// each other's changes. Not re-entrant: take it once per file per operation.
+pub fn lock_file(path: &Path) -> Result<FileLock> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| SsoError::ConfigError(format!("Invalid path: {}", path.display())))?;
+    let lock_path = path.with_file_name(format!(".{}.lock", file_name.to_string_lossy()));
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| SsoError::ConfigError(format!("Failed to open lock file: {}", e)))?;
+
+    let started = Instant::now();
+    let mut delay = Duration::from_millis(20);
+    loop {
+        match file.try_lock_exclusive() {
+            Ok(()) => return Ok(FileLock { _file: file }),
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                if started.elapsed() >= LOCK_TIMEOUT {
+                    return Err(SsoError::ConfigError(format!(
+                        "Timed out waiting for another process to finish writing {}",
+                        path.display()
+                    )));
+                }
+                tracing::debug!("{} is locked, retrying in {:?}", path.display(), delay);
+                std::thread::sleep(delay);
+                delay = (delay * 2).min(Duration::from_millis(500));
+            }
+            Err(e) => {
+                return Err(SsoError::ConfigError(format!(
+                    "Failed to lock {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        }
     }
+}
 
-    let home = dirs::home_dir()
-        .ok_or_else(|| SsoError::ConfigError("Could not determine home directory".to_string()))?;
-
-    let aws_dir = home.join(".aws");
-    let config_path = aws_dir.join("config");
-    let credentials_path = aws_dir.join("credentials");
-    let marker_file = aws_dir.join(".awsom-initialized");
+/// Replace `path` with `content` via a temp file and rename, so readers (including the
+/// AWS CLI) never see a half-written file
+///
+/// Follows symlinks so a dotfiles-managed `~/.aws/config` stays a symlink, and keeps the
+/// existing file's permissions; a new file is readable by the user only.
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let tmp_path = target.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let result = (|| {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp_path)?;
+        if let Ok(metadata) = fs::metadata(&target) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.write_all(content.as_ref())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &target)
+    })();
 
-    // Create ~/.aws directory if it doesn't exist
-    if !aws_dir.exists() {
-        fs::create_dir_all(&aws_dir).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to create ~/.aws directory: {}", e))
-        })?;
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
     }
+    result
+}
 
-    // Backup config file if it exists, then add header comment
-    if config_path.exists() {
-        let backup_path = aws_dir.join("config-before-awsom.bak");
-        fs::copy(&config_path, &backup_path)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to backup config file: {}", e)))?;
-        tracing::info!("Created backup: {:?}", backup_path);
+/// Marker file recording that the first-run backups were made, next to the config file
+fn initialized_marker() -> Result<PathBuf> {
+    Ok(config_file_path()?.with_file_name(".awsom-initialized"))
+}
 
-        // Add header comment to config file
-        add_header_comment(&config_path, "config-before-awsom.bak")?;
+/// Create backups of config and credentials files on first use
+///
+/// Each backup is `<file name>-before-awsom.bak` next to the file, which is
+/// ~/.aws/config-before-awsom.bak unless AWS_CONFIG_FILE or --config-file moved it.
+fn create_backups_if_needed() -> Result<()> {
+    let marker_file = initialized_marker()?;
+    if marker_file.exists() {
+        return Ok(());
     }
 
-    // Backup credentials file if it exists, then add header comment
-    if credentials_path.exists() {
-        let backup_path = aws_dir.join("credentials-before-awsom.bak");
-        fs::copy(&credentials_path, &backup_path).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to backup credentials file: {}", e))
-        })?;
-        tracing::info!("Created backup: {:?}", backup_path);
+    for (path, kind) in [
+        (config_file_path()?, "config"),
+        (credentials_file_path()?, "credentials"),
+    ] {
+        if let Some(dir) = path.parent().filter(|dir| !dir.exists()) {
+            fs::create_dir_all(dir).map_err(|e| {
+                SsoError::ConfigError(format!("Failed to create {}: {}", dir.display(), e))
+            })?;
+        }
+        if !path.exists() {
+            continue;
+        }
 
-        // Add header comment to credentials file
-        add_header_comment(&credentials_path, "credentials-before-awsom.bak")?;
+        // Back the file up, then add the header comment pointing at the backup
+        let backup_name = format!(
+            "{}-before-awsom.bak",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        let backup_path = path.with_file_name(&backup_name);
+        fs::copy(&path, &backup_path)
+            .map_err(|e| SsoError::ConfigError(format!("Failed to backup {} file: {}", kind, e)))?;
+        tracing::info!("Created backup: {:?}", backup_path);
+        add_header_comment(&path, &backup_name)?;
     }
 
     // Create marker file
@@ -94,7 +182,7 @@ fn add_header_comment(file_path: &std::path::Path, backup_filename: &str) -> Res
     let new_content = format!("{}{}", header, content);
 
     // Write updated content
-    fs::write(file_path, new_content)
+    write_atomic(file_path, new_content)
         .map_err(|e| SsoError::ConfigError(format!("Failed to write file: {}", e)))?;
 
     tracing::info!("Added header comment to {:?}", file_path);
@@ -110,6 +198,17 @@ pub const AWSOM_MANAGED_MARKER: &str =
 pub const AWSOM_MANAGED_COMMENT: &str =
     "# (sections below this line are automatically managed by awsom)";
 
+const SSO_SESSION_PREFIX: &str = "sso-session ";
+/// `sso_registration_scopes` of sessions that don't set it
+pub const DEFAULT_REGISTRATION_SCOPES: &str = "sso:account:access";
+const PROFILE_PREFIX: &str = "profile ";
+
+/// Program and subcommand of the `credential_process` awsom writes into profiles
+const CREDENTIAL_PROCESS_COMMAND: &str = "awsom credential-process";
+
+/// Comment on imported static-credentials sections in ~/.aws/credentials
+const STATIC_CREDENTIALS_COMMENT: &str = "# Static: tracked by awsom, never overwritten";
+
 /// Check if a line is any marker (user-managed or awsom-managed)
 fn is_marker_line(line: &str) -> bool {
     let trimmed = line.trim();
@@ -125,197 +224,247 @@ fn is_awsom_managed_marker(line: &str) -> bool {
     trimmed == AWSOM_MANAGED_MARKER || trimmed == AWSOM_MANAGED_COMMENT
 }
 
-/// Ensure config file has the management markers (public for import command)
-/// Adds markers if they don't exist, placing existing content below the user-managed marker
-pub fn ensure_markers(content: &str) -> String {
-    // Check if marker already exists
-    for line in content.lines() {
-        if is_marker_line(line) {
-            // Markers already exist
-            return content.to_string();
-        }
+/// Read an AWS INI file, or an empty document if it doesn't exist yet
+fn load_document(path: &Path) -> Result<IniDocument> {
+    if !path.exists() {
+        return Ok(IniDocument::default());
     }
 
-    // No marker found - need to add markers
-    // Separate header comments from actual content
-    let mut header = String::new();
-    let mut user_content = String::new();
-    let mut in_header = true;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
+    let content = fs::read_to_string(path)
+        .map_err(|e| SsoError::ConfigError(format!("Failed to read {}: {}", path.display(), e)))?;
+    Ok(IniDocument::parse(&content))
+}
 
-        // Header is any leading comment lines before first section
-        if in_header {
-            if trimmed.starts_with('#') || trimmed.is_empty() {
-                header.push_str(line);
-                header.push('\n');
-            } else {
-                // Found non-comment, non-empty content - header is done
-                in_header = false;
-                user_content.push_str(line);
-                user_content.push('\n');
-            }
-        } else {
-            user_content.push_str(line);
-            user_content.push('\n');
+/// Read-modify-write an AWS INI file under its lock, creating ~/.aws and the first-run
+/// backups if needed. The file is only rewritten when `edit` changed something.
+///
+/// In read-only mode nothing is created or written; a change is an error holding its diff.
+fn edit_document<T>(path: &Path, edit: impl FnOnce(&mut IniDocument) -> Result<T>) -> Result<T> {
+    if config::is_read_only() {
+        let mut doc = load_document(path)?;
+        let before = doc.to_string();
+        let result = edit(&mut doc)?;
+        let after = doc.to_string();
+        if after != before {
+            return Err(config::read_only_error(path, &before, &after));
         }
+        return Ok(result);
     }
 
-    // Build result: header + user marker + user content + awsom marker
-    let mut result = header;
-
-    // Add blank line after header if there's content
-    if !result.trim().is_empty() && !user_content.trim().is_empty() {
-        result.push('\n');
+    if let Some(dir) = path.parent().filter(|dir| !dir.exists()) {
+        fs::create_dir_all(dir).map_err(|e| {
+            SsoError::ConfigError(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
     }
 
-    // Add user-managed marker
-    result.push_str(USER_MANAGED_MARKER);
-    result.push('\n');
-    result.push_str(USER_MANAGED_COMMENT);
-    result.push('\n');
+    // Create backups on first write
+    create_backups_if_needed()?;
+
+    let _lock = lock_file(path)?;
+    let mut doc = load_document(path)?;
+    let before = doc.to_string();
+    let result = edit(&mut doc)?;
 
-    // Add user content below the marker
-    if !user_content.trim().is_empty() {
-        result.push('\n');
-        result.push_str(&user_content);
-        // Ensure content ends with newline
-        if !result.ends_with('\n') {
-            result.push('\n');
-        }
+    let after = doc.to_string();
+    if after != before {
+        history::record(path);
+        write_atomic(path, after).map_err(|e| {
+            SsoError::ConfigError(format!("Failed to write {}: {}", path.display(), e))
+        })?;
     }
 
-    // Add blank line before awsom marker
-    result.push('\n');
+    Ok(result)
+}
 
-    // Add awsom-managed marker
-    result.push_str(AWSOM_MANAGED_MARKER);
-    result.push('\n');
-    result.push_str(AWSOM_MANAGED_COMMENT);
-    result.push('\n');
+/// Line of the awsom-managed marker; sections below it are managed by awsom
+fn awsom_marker_line(doc: &IniDocument) -> Option<usize> {
+    doc.position(is_awsom_managed_marker)
+}
 
-    result
+fn is_awsom_managed(doc: &IniDocument, section: &Section) -> bool {
+    awsom_marker_line(doc).is_some_and(|marker| section.line() > marker)
 }
 
-/// Reconstruct config file with proper header, markers, and sections
-fn reconstruct_config(header: &str, user_section: &str, awsom_section: &str) -> String {
-    let mut result = String::new();
+/// Ensure the config has the management markers (public for import command)
+///
+/// Without markers, existing content goes below the user-managed marker (after any
+/// leading comments) and the awsom-managed marker is added at the end.
+pub fn ensure_markers(doc: &mut IniDocument) {
+    if doc.position(is_marker_line).is_none() {
+        let first_content = doc
+            .position(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with(';'))
+            .unwrap_or(doc.len());
+
+        // End marker first so `first_content` stays valid
+        doc.insert_lines(
+            doc.len(),
+            vec![
+                AWSOM_MANAGED_MARKER.to_string(),
+                AWSOM_MANAGED_COMMENT.to_string(),
+            ],
+        );
+        doc.insert_lines(
+            first_content,
+            vec![
+                USER_MANAGED_MARKER.to_string(),
+                USER_MANAGED_COMMENT.to_string(),
+            ],
+        );
+    }
 
-    // Add header if present
-    if !header.trim().is_empty() {
-        result.push_str(header);
-        // Ensure blank line after header
-        if !result.ends_with("\n\n") && !result.ends_with('\n') {
-            result.push('\n');
-        }
-        if !result.ends_with("\n\n") {
-            result.push('\n');
+    // A section header right below a marker would pull the marker into that section's
+    // leading comments, so keep them apart
+    let mut index = 0;
+    while index + 1 < doc.len() {
+        let next = doc.line(index + 1).unwrap_or_default();
+        if doc.line(index).is_some_and(is_marker_line) && next.starts_with('[') {
+            doc.insert_line(index + 1, "");
         }
+        index += 1;
     }
+}
 
-    // Add user-managed marker
-    result.push_str(USER_MANAGED_MARKER);
-    result.push('\n');
-    result.push_str(USER_MANAGED_COMMENT);
-    result.push('\n');
-
-    // Add user section if present
-    if !user_section.trim().is_empty() {
-        result.push('\n');
-        result.push_str(user_section);
-        // Ensure it ends with newline
-        if !result.ends_with('\n') {
-            result.push('\n');
-        }
+/// Sort order of the awsom-managed region: [default], sso-sessions, then profiles
+fn awsom_sort_key(section_name: &str) -> (u8, &str) {
+    if section_name == "default" {
+        (0, section_name)
+    } else if let Some(name) = section_name.strip_prefix(SSO_SESSION_PREFIX) {
+        (1, name)
+    } else {
+        (2, section_name)
     }
+}
 
-    // Add blank line before awsom marker
-    result.push('\n');
+/// Where a new section belongs in the awsom-managed region to keep it sorted
+fn awsom_insert_point(doc: &IniDocument, section_name: &str) -> usize {
+    doc.sections()
+        .filter(|s| is_awsom_managed(doc, s))
+        .find(|s| awsom_sort_key(s.name()) > awsom_sort_key(section_name))
+        .map(|s| doc.section_start(s.line()))
+        .unwrap_or(doc.len())
+}
 
-    // Add awsom-managed marker
-    result.push_str(AWSOM_MANAGED_MARKER);
-    result.push('\n');
-    result.push_str(AWSOM_MANAGED_COMMENT);
-    result.push('\n');
+/// Header line of `section_name` in the awsom-managed region, adding the section in
+/// sorted position if it isn't there yet
+fn awsom_section(doc: &mut IniDocument, section_name: &str) -> usize {
+    ensure_markers(doc);
 
-    // Add awsom section if present
-    if !awsom_section.trim().is_empty() {
-        result.push('\n');
-        result.push_str(awsom_section);
-    }
+    let existing = doc
+        .sections()
+        .find(|s| s.name() == section_name && is_awsom_managed(doc, s))
+        .map(|s| s.line());
 
-    result
+    existing.unwrap_or_else(|| {
+        let at = awsom_insert_point(doc, section_name);
+        doc.insert_section(at, section_name)
+    })
 }
 
-/// Split config content into header, user-managed and awsom-managed sections
-/// Returns (header_section, user_section, awsom_section) tuple
-/// Header is any leading comments before first section marker or profile
-fn split_into_sections(content: &str) -> (String, String, String) {
-    let mut header = String::new();
-    let mut user_section = String::new();
-    let mut awsom_section = String::new();
-    let mut in_awsom_section = false;
-    let mut in_header = true;
-    let mut found_marker = false;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Skip all marker lines - they'll be re-added during reconstruction
-        if is_marker_line(line) {
-            if is_awsom_managed_marker(line) {
-                in_awsom_section = true;
-                found_marker = true;
-            }
-            in_header = false; // No longer in header after seeing a marker
-            continue; // Skip all marker lines
-        }
-
-        // Collect header (leading comments before any section or marker)
-        if in_header {
-            if trimmed.starts_with('#') || trimmed.is_empty() {
-                header.push_str(line);
-                header.push('\n');
-                continue;
-            } else {
-                // Found non-comment content, header is done
-                in_header = false;
-            }
-        }
+/// Whether `section_name` exists above the awsom-managed marker (the whole file counts as
+/// user-managed until awsom adds its markers)
+fn in_user_region(doc: &IniDocument, section_name: &str) -> bool {
+    doc.sections()
+        .any(|s| s.name() == section_name && !is_awsom_managed(doc, &s))
+}
 
-        // Collect content
-        if in_awsom_section {
-            awsom_section.push_str(line);
-            awsom_section.push('\n');
-        } else {
-            user_section.push_str(line);
-            user_section.push('\n');
-        }
+/// Section name for a profile: `default` or `profile <name>`
+fn profile_section_name(profile_name: &str) -> String {
+    if profile_name == "default" {
+        profile_name.to_string()
+    } else {
+        format!("{}{}", PROFILE_PREFIX, profile_name)
     }
+}
 
-    // If no marker was found, treat all non-header content as user-managed
-    if !found_marker {
-        (header, content.trim_start().to_string(), String::new())
+/// Profile name of a `[default]` or `[profile <name>]` section
+fn profile_name_of(section_name: &str) -> Option<&str> {
+    if section_name == "default" {
+        Some(section_name)
     } else {
-        (header, user_section, awsom_section)
+        section_name.strip_prefix(PROFILE_PREFIX).map(str::trim)
+    }
+}
+
+/// The SSO session, account and role a profile gets its credentials from
+#[derive(Debug, Clone, PartialEq)]
+struct ProfileRole {
+    session: String,
+    account_id: String,
+    role_name: String,
+}
+
+/// The role of a profile, from its sso_* keys or an awsom `credential_process`
+fn profile_role(section: &Section) -> Option<ProfileRole> {
+    match (
+        section.get("sso_session"),
+        section.get("sso_account_id"),
+        section.get("sso_role_name"),
+    ) {
+        (Some(session), Some(account_id), Some(role_name)) => Some(ProfileRole {
+            session: session.to_string(),
+            account_id: account_id.to_string(),
+            role_name: role_name.to_string(),
+        }),
+        _ => parse_credential_process(section.get("credential_process")?),
     }
 }
 
-/// Split config content into user-managed and awsom-managed sections (public for import command)
-/// Returns (user_section, awsom_section) tuple
-/// This is a compatibility wrapper that combines header with user_section
-pub fn split_by_marker(content: &str) -> (String, String) {
-    let (header, user_section, awsom_section) = split_into_sections(content);
+/// `credential_process` value that has awsom print a role's credentials to the SDK
+fn credential_process_command(session: &str, account_id: &str, role_name: &str) -> String {
+    // The AWS CLI and SDKs split the command like a POSIX shell
+    let quote = |arg: &str| {
+        if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+            format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+        } else {
+            arg.to_string()
+        }
+    };
+    format!(
+        "{} --session-name {} --account-id {} --role-name {}",
+        CREDENTIAL_PROCESS_COMMAND,
+        quote(session),
+        quote(account_id),
+        quote(role_name)
+    )
+}
 
-    // Combine header with user section for backward compatibility
-    let mut combined_user = header;
-    if !combined_user.is_empty() && !user_section.is_empty() {
-        combined_user.push('\n');
+/// The role of a `credential_process` written by `credential_process_command`; None for
+/// any other command
+fn parse_credential_process(command: &str) -> Option<ProfileRole> {
+    let rest = command
+        .strip_prefix(CREDENTIAL_PROCESS_COMMAND)
+        .filter(|rest| rest.starts_with(char::is_whitespace))?;
+
+    // Split on whitespace outside double quotes, unescaping inside them
+    let mut args = Vec::new();
+    let mut chars = rest.trim().chars();
+    let mut arg: Option<String> = None;
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                arg.get_or_insert_with(String::new);
+            }
+            '\\' if quoted => arg.get_or_insert_with(String::new).push(chars.next()?),
+            c if c.is_whitespace() && !quoted => args.extend(arg.take()),
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
     }
-    combined_user.push_str(&user_section);
+    if quoted {
+        return None;
+    }
+    args.extend(arg);
 
-    (combined_user, awsom_section)
+    let value = |flag: &str| {
+        let index = args.iter().position(|a| a == flag)?;
+        args.get(index + 1).cloned()
+    };
+    Some(ProfileRole {
+        session: value("--session-name")?,
+        account_id: value("--account-id")?,
+        role_name: value("--role-name")?,
+    })
 }
 
 /// SSO Session configuration
@@ -327,224 +476,136 @@ pub struct SsoSession {
     pub sso_registration_scopes: String,
 }
 
-/// Get the AWS credentials file path
-pub fn credentials_file_path() -> Result<PathBuf> {
-    if let Some(home) = dirs::home_dir() {
-        Ok(home.join(".aws").join("credentials"))
-    } else {
-        Err(SsoError::ConfigError(
-            "Could not determine home directory".to_string(),
-        ))
+impl SsoSession {
+    /// Parse an `[sso-session <name>]` section; None if it isn't one or is incomplete
+    fn from_section(section: &Section) -> Option<Self> {
+        let session_name = section.name().strip_prefix(SSO_SESSION_PREFIX)?.trim();
+        Some(Self {
+            session_name: session_name.to_string(),
+            sso_start_url: section.get("sso_start_url")?.to_string(),
+            sso_region: section.get("sso_region")?.to_string(),
+            sso_registration_scopes: section
+                .get("sso_registration_scopes")
+                .unwrap_or(DEFAULT_REGISTRATION_SCOPES)
+                .to_string(),
+        })
     }
-}
 
-/// Get the AWS config file path
-pub fn config_file_path() -> Result<PathBuf> {
-    if let Some(home) = dirs::home_dir() {
-        Ok(home.join(".aws").join("config"))
-    } else {
-        Err(SsoError::ConfigError(
-            "Could not determine home directory".to_string(),
-        ))
+    /// The comma-separated `sso_registration_scopes` as a list
+    pub fn registration_scopes(&self) -> Vec<String> {
+        self.sso_registration_scopes
+            .split(',')
+            .map(str::trim)
+            .filter(|scope| !scope.is_empty())
+            .map(str::to_string)
+            .collect()
     }
 }
 
-/// Read SSO session from ~/.aws/config
-/// Returns the first sso-session found, or None if no session exists
-pub fn read_sso_session() -> Result<Option<SsoSession>> {
-    let config_path = config_file_path()?;
+/// Scopes to register the OIDC client with when logging in to `instance`: those of its
+/// [sso-session], or of a session with the same start URL when it has no session name
+pub fn registration_scopes_for(instance: &SsoInstance) -> Vec<String> {
+    let sessions = read_all_sso_sessions().unwrap_or_default();
+    let session = match &instance.session_name {
+        Some(name) => sessions.iter().find(|s| &s.session_name == name),
+        None => sessions
+            .iter()
+            .find(|s| s.sso_start_url == instance.start_url),
+    };
+    match session {
+        Some(session) => session.registration_scopes(),
+        None => vec![DEFAULT_REGISTRATION_SCOPES.to_string()],
+    }
+}
 
-    if !config_path.exists() {
-        return Ok(None);
+/// Set the files given with --config-file and --credentials-file, which take precedence
+/// over AWS_CONFIG_FILE and AWS_SHARED_CREDENTIALS_FILE
+pub fn set_file_overrides(config_file: Option<PathBuf>, credentials_file: Option<PathBuf>) {
+    if let Some(path) = config_file {
+        let _ = CONFIG_FILE_OVERRIDE.set(path);
     }
+    if let Some(path) = credentials_file {
+        let _ = CREDENTIALS_FILE_OVERRIDE.set(path);
+    }
+}
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    let mut in_sso_session = false;
-    let mut session_name: Option<String> = None;
-    let mut session_data: HashMap<String, String> = HashMap::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Check if previous section was an sso-session
-            if in_sso_session {
-                // Found a complete sso-session, return it
-                if let Some(name) = session_name.take() {
-                    if let (Some(start_url), Some(region)) = (
-                        session_data.get("sso_start_url"),
-                        session_data.get("sso_region"),
-                    ) {
-                        let scopes = session_data
-                            .get("sso_registration_scopes")
-                            .cloned()
-                            .unwrap_or_else(|| "sso:account:access".to_string());
-
-                        return Ok(Some(SsoSession {
-                            session_name: name,
-                            sso_start_url: start_url.clone(),
-                            sso_region: region.clone(),
-                            sso_registration_scopes: scopes,
-                        }));
-                    }
-                }
-            }
+/// Environment for child processes, so the AWS CLI and SDKs in them read the same files
+/// as awsom when these were given on the command line
+pub fn file_override_env() -> Vec<(&'static str, PathBuf)> {
+    [
+        (CONFIG_FILE_ENV, CONFIG_FILE_OVERRIDE.get()),
+        (CREDENTIALS_FILE_ENV, CREDENTIALS_FILE_OVERRIDE.get()),
+    ]
+    .into_iter()
+    .filter_map(|(name, path)| Some((name, path?.clone())))
+    .collect()
+}
 
-            // Parse new section header
-            let section = &trimmed[1..trimmed.len() - 1];
-            if let Some(name) = section.strip_prefix("sso-session ") {
-                in_sso_session = true;
-                session_name = Some(name.to_string());
-                session_data.clear();
-            } else {
-                in_sso_session = false;
-            }
-        } else if in_sso_session && !trimmed.is_empty() && !trimmed.starts_with('#') {
-            if let Some(eq_pos) = trimmed.find('=') {
-                let key = trimmed[..eq_pos].trim().to_string();
-                let value = trimmed[eq_pos + 1..].trim().to_string();
-                session_data.insert(key, value);
-            }
-        }
+/// Get the AWS credentials file path
+pub fn credentials_file_path() -> Result<PathBuf> {
+    aws_file_path(
+        &CREDENTIALS_FILE_OVERRIDE,
+        CREDENTIALS_FILE_ENV,
+        "credentials",
+    )
+}
+
+/// Get the AWS config file path
+pub fn config_file_path() -> Result<PathBuf> {
+    aws_file_path(&CONFIG_FILE_OVERRIDE, CONFIG_FILE_ENV, "config")
+}
+
+/// The command-line override, else the environment variable, else ~/.aws/<name>
+fn aws_file_path(override_path: &OnceLock<PathBuf>, env_var: &str, name: &str) -> Result<PathBuf> {
+    if let Some(path) = override_path.get() {
+        return Ok(path.clone());
     }
 
-    // Check last section
-    if in_sso_session {
-        if let Some(name) = session_name {
-            if let (Some(start_url), Some(region)) = (
-                session_data.get("sso_start_url"),
-                session_data.get("sso_region"),
-            ) {
-                let scopes = session_data
-                    .get("sso_registration_scopes")
-                    .cloned()
-                    .unwrap_or_else(|| "sso:account:access".to_string());
-
-                return Ok(Some(SsoSession {
-                    session_name: name,
-                    sso_start_url: start_url.clone(),
-                    sso_region: region.clone(),
-                    sso_registration_scopes: scopes,
-                }));
-            }
-        }
+    let home = dirs::home_dir();
+    let from_env = std::env::var(env_var)
+        .ok()
+        .filter(|value| !value.is_empty());
+    resolve_aws_file_path(from_env.as_deref(), home.as_deref(), name)
+        .ok_or_else(|| SsoError::ConfigError("Could not determine home directory".to_string()))
+}
+
+/// Like the AWS CLI, a leading `~/` in the environment variable is the home directory
+fn resolve_aws_file_path(
+    from_env: Option<&str>,
+    home: Option<&Path>,
+    name: &str,
+) -> Option<PathBuf> {
+    match from_env.map(|path| (path, path.strip_prefix("~/"))) {
+        Some((_, Some(rest))) => Some(home?.join(rest)),
+        Some((path, None)) => Some(PathBuf::from(path)),
+        None => Some(home?.join(".aws").join(name)),
     }
+}
 
-    Ok(None)
+/// Read SSO session from ~/.aws/config
+/// Returns the active workspace's sso-session, else the first sso-session found, or None if
+/// no session exists
+pub fn read_sso_session() -> Result<Option<SsoSession>> {
+    let doc = load_document(&config_file_path()?)?;
+    let mut sessions = doc.sections().filter_map(|s| SsoSession::from_section(&s));
+    let session = match config::active_workspace().and_then(|w| w.sso_session) {
+        Some(name) => sessions.find(|s| s.session_name == name),
+        None => sessions.next(),
+    };
+    Ok(session)
 }
 
 /// Read all SSO sessions from ~/.aws/config
 /// Returns a vector of all sso-sessions found
 pub fn read_all_sso_sessions() -> Result<Vec<SsoSession>> {
     let config_path = config_file_path()?;
-
-    if !config_path.exists() {
-        tracing::info!("Config file does not exist: {:?}", config_path);
-        return Ok(Vec::new());
-    }
-
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
     tracing::info!("Reading config file: {:?}", config_path);
-    let mut sessions = Vec::new();
-    let mut in_sso_session = false;
-    let mut session_name: Option<String> = None;
-    let mut session_data: HashMap<String, String> = HashMap::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Save previous sso-session if complete
-            if in_sso_session {
-                if let Some(name) = session_name.take() {
-                    if let (Some(start_url), Some(region)) = (
-                        session_data.get("sso_start_url"),
-                        session_data.get("sso_region"),
-                    ) {
-                        let scopes = session_data
-                            .get("sso_registration_scopes")
-                            .cloned()
-                            .unwrap_or_else(|| "sso:account:access".to_string());
-
-                        let session = SsoSession {
-                            session_name: name.clone(),
-                            sso_start_url: start_url.clone(),
-                            sso_region: region.clone(),
-                            sso_registration_scopes: scopes,
-                        };
-                        tracing::info!(
-                            "Adding session: {} ({}, {})",
-                            session.session_name,
-                            session.sso_start_url,
-                            session.sso_region
-                        );
-                        sessions.push(session);
-                    }
-                }
-                session_data.clear();
-            }
-
-            // Check if this is a new sso-session header
-            if trimmed.starts_with("[sso-session ") {
-                in_sso_session = true;
-                let name_part = &trimmed[13..trimmed.len() - 1]; // Extract name between "[sso-session " and "]"
-                session_name = Some(name_part.trim().to_string());
-                tracing::info!("Found SSO session header: {}", name_part.trim());
-            } else {
-                in_sso_session = false;
-                session_name = None;
-            }
-            continue;
-        }
-
-        if in_sso_session && trimmed.contains('=') {
-            let parts: Vec<&str> = trimmed.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().to_string();
-                let value = parts[1].trim().to_string();
-                session_data.insert(key, value);
-            }
-        }
-    }
 
-    // Handle last session if file ends without a new section
-    if in_sso_session {
-        if let Some(name) = session_name {
-            if let (Some(start_url), Some(region)) = (
-                session_data.get("sso_start_url"),
-                session_data.get("sso_region"),
-            ) {
-                let scopes = session_data
-                    .get("sso_registration_scopes")
-                    .cloned()
-                    .unwrap_or_else(|| "sso:account:access".to_string());
-
-                let session = SsoSession {
-                    session_name: name.clone(),
-                    sso_start_url: start_url.clone(),
-                    sso_region: region.clone(),
-                    sso_registration_scopes: scopes,
-                };
-                tracing::info!(
-                    "Adding last session: {} ({}, {})",
-                    session.session_name,
-                    session.sso_start_url,
-                    session.sso_region
-                );
-                sessions.push(session);
-            } else {
-                tracing::info!(
-                    "Incomplete session at end: name={:?}, data={:?}",
-                    name,
-                    session_data
-                );
-            }
-        }
-    }
+    let doc = load_document(&config_path)?;
+    let sessions: Vec<SsoSession> = doc
+        .sections()
+        .filter_map(|s| SsoSession::from_section(&s))
+        .collect();
 
     tracing::info!("Total sessions found: {}", sessions.len());
     Ok(sessions)
@@ -554,9 +615,10 @@ pub fn read_all_sso_sessions() -> Result<Vec<SsoSession>> {
 ///
 /// Priority order:
 /// 1. Explicit flags (--start-url + --region) - highest priority, for scripting
-/// 2. Session name (--session-name) - look up from config
-/// 3. Active SSO token (if only one exists) - check cache (TODO: implement)
-/// 4. Single configured session (if only one exists) - check config
+/// 2. Session name (--session-name, else the active workspace's) - look up from config
+/// 3. Current session, picked with `session switch` - from config.toml
+/// 4. Active SSO token (if only one exists) - check cache (TODO: implement)
+/// 5. Single configured session (if only one exists) - check config
 ///
 /// Returns (start_url, region) tuple or error with helpful message
 pub fn resolve_sso_session(
@@ -564,6 +626,17 @@ pub fn resolve_sso_session(
     start_url: Option<&str>,
     region: Option<&str>,
 ) -> Result<(String, String)> {
+    let (_, start_url, region) = resolve_named_sso_session(session_name, start_url, region)?;
+    Ok((start_url, region))
+}
+
+/// `resolve_sso_session` with the name of the [sso-session] it picked, None for explicit
+/// flags
+pub fn resolve_named_sso_session(
+    session_name: Option<&str>,
+    start_url: Option<&str>,
+    region: Option<&str>,
+) -> Result<(Option<String>, String, String)> {
     // Level 1: Explicit flags (both start_url and region must be provided)
     if let (Some(url), Some(reg)) = (start_url, region) {
         tracing::debug!(
@@ -571,7 +644,7 @@ pub fn resolve_sso_session(
             url,
             reg
         );
-        return Ok((url.to_string(), reg.to_string()));
+        return Ok((None, url.to_string(), reg.to_string()));
     }
 
     // If only one flag is provided, that's an error
@@ -582,7 +655,8 @@ pub fn resolve_sso_session(
     }
 
     // Level 2: Session name - look up from config
-    if let Some(name) = session_name {
+    let workspace_session = config::active_workspace().and_then(|w| w.sso_session);
+    if let Some(name) = session_name.or(workspace_session.as_deref()) {
         let sessions = read_all_sso_sessions()?;
         if let Some(session) = sessions.iter().find(|s| s.session_name == name) {
             tracing::debug!(
@@ -591,7 +665,11 @@ pub fn resolve_sso_session(
                 session.sso_start_url,
                 session.sso_region
             );
-            return Ok((session.sso_start_url.clone(), session.sso_region.clone()));
+            return Ok((
+                Some(name.to_string()),
+                session.sso_start_url.clone(),
+                session.sso_region.clone(),
+            ));
         } else {
             return Err(SsoError::ConfigError(format!(
                 "Session '{}' not found in ~/.aws/config",
@@ -600,18 +678,40 @@ pub fn resolve_sso_session(
         }
     }
 
-    // Level 3: Active SSO token (if only one exists)
+    // Level 3: The session picked with `session switch`
+    let sessions = read_all_sso_sessions()?;
+    if let Some(name) = config::current_session() {
+        match sessions.iter().find(|s| s.session_name == name) {
+            Some(session) => {
+                tracing::debug!(
+                    "Resolved SSO session from current session '{}': start_url={}, region={}",
+                    name,
+                    session.sso_start_url,
+                    session.sso_region
+                );
+                return Ok((
+                    Some(name),
+                    session.sso_start_url.clone(),
+                    session.sso_region.clone(),
+                ));
+            }
+            None => tracing::warn!(
+                "Current session '{}' is no longer in ~/.aws/config; pick another with \
+                 'awsom session switch'",
+                name
+            ),
+        }
+    }
+
+    // Level 4: Active SSO token (if only one exists)
     // TODO: Implement token cache checking
     // This would check ~/.aws/sso/cache/ for active tokens and if exactly one is found,
     // map it back to its session configuration
-    // For now, skip to level 4
+    // For now, skip to level 5
 
-    // Level 4: Single configured session
-    let sessions = read_all_sso_sessions()?;
+    // Level 5: Single configured session
     match sessions.len() {
-        0 => Err(SsoError::ConfigError(
-            "No SSO sessions configured. Add one with 'awsom session add' or provide --start-url and --region".to_string()
-        )),
+        0 => Err(SsoError::NotConfigured),
         1 => {
             let session = &sessions[0];
             tracing::debug!(
@@ -620,7 +720,11 @@ pub fn resolve_sso_session(
                 session.sso_start_url,
                 session.sso_region
             );
-            Ok((session.sso_start_url.clone(), session.sso_region.clone()))
+            Ok((
+                Some(session.session_name.clone()),
+                session.sso_start_url.clone(),
+                session.sso_region.clone(),
+            ))
         }
         _ => {
             let session_list = sessions
@@ -629,7 +733,7 @@ pub fn resolve_sso_session(
                 .collect::<Vec<_>>()
                 .join("\n");
             Err(SsoError::ConfigError(format!(
-                "Multiple SSO sessions configured. Specify one with --session-name:\n\n{}\n\nExample:\n  awsom exec --session-name {} --role-name <role> --account-name <account> -- <command>",
+                "Multiple SSO sessions configured. Specify one with --session-name, or make one the default with 'awsom session switch <name>':\n\n{}\n\nExample:\n  awsom profile exec --session-name {} --role-name <role> --account-name <account> -- <command>",
                 session_list,
                 sessions[0].session_name
             )))
@@ -644,528 +748,134 @@ pub struct DefaultConfig {
     pub output: String,
 }
 
-/// Read [default] section from ~/.aws/config
-pub fn read_default_config() -> Result<Option<DefaultConfig>> {
-    let config_path = config_file_path()?;
-
-    if !config_path.exists() {
-        return Ok(None);
-    }
-
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    let mut in_default_section = false;
-    let mut region: Option<String> = None;
-    let mut output: Option<String> = None;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Check for section headers
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            in_default_section = trimmed == "[default]";
-            continue;
+impl DefaultConfig {
+    /// Region/output of a section, if it sets either
+    fn from_section(section: &Section) -> Option<Self> {
+        let (region, output) = (section.get("region"), section.get("output"));
+        if region.is_none() && output.is_none() {
+            return None;
         }
 
-        if in_default_section && trimmed.contains('=') {
-            let parts: Vec<&str> = trimmed.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim();
-                let value = parts[1].trim();
-
-                match key {
-                    "region" => region = Some(value.to_string()),
-                    "output" => output = Some(value.to_string()),
-                    _ => {}
-                }
-            }
-        }
+        Some(Self {
+            region: region.unwrap_or("us-east-1").to_string(),
+            output: output.unwrap_or("json").to_string(),
+        })
     }
+}
 
-    if region.is_some() || output.is_some() {
-        Ok(Some(DefaultConfig {
-            region: region.unwrap_or_else(|| "us-east-1".to_string()),
-            output: output.unwrap_or_else(|| "json".to_string()),
-        }))
-    } else {
-        Ok(None)
-    }
+/// Read [default] section from ~/.aws/config
+pub fn read_default_config() -> Result<Option<DefaultConfig>> {
+    let doc = load_document(&config_file_path()?)?;
+    Ok(doc
+        .section("default")
+        .and_then(|s| DefaultConfig::from_section(&s)))
 }
 
 /// Read awsom defaults from [profile awsom-defaults] section
 pub fn read_awsom_defaults() -> Result<Option<DefaultConfig>> {
-    let config_path = config_file_path()?;
-
-    if !config_path.exists() {
-        return Ok(None);
-    }
+    let doc = load_document(&config_file_path()?)?;
+    Ok(doc
+        .section("profile awsom-defaults")
+        .and_then(|s| DefaultConfig::from_section(&s)))
+}
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
+/// Write awsom defaults to [profile awsom-defaults] in awsom-managed section
+pub fn write_awsom_defaults(config: &DefaultConfig) -> Result<()> {
+    edit_document(&config_file_path()?, |doc| {
+        let header = awsom_section(doc, "profile awsom-defaults");
+        doc.set(header, "region", &config.region);
+        doc.set(header, "output", &config.output);
+        Ok(())
+    })
+}
 
-    let mut in_awsom_defaults = false;
-    let mut region: Option<String> = None;
-    let mut output: Option<String> = None;
+/// Check if a profile is in the awsom-managed section
+pub fn is_profile_in_awsom_section(profile_name: &str) -> Result<bool> {
+    let doc = load_document(&config_file_path()?)?;
+    let section_name = profile_section_name(profile_name);
+    let found = doc
+        .sections()
+        .any(|s| s.name() == section_name && is_awsom_managed(&doc, &s));
+    Ok(found)
+}
 
-    for line in content.lines() {
-        let trimmed = line.trim();
+/// Get profile details for display (region, output, SSO info if available)
+#[derive(Debug, Clone)]
+pub struct ProfileDetails {
+    pub region: Option<String>,
+    pub output: Option<String>,
+    pub sso_session: Option<String>,
+    pub sso_account_id: Option<String>,
+    pub sso_role_name: Option<String>,
+    /// Role assumed with `source_profile`'s credentials, for `--mode role-arn` profiles
+    pub role_arn: Option<String>,
+    pub source_profile: Option<String>,
+}
 
-        // Check for section headers
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            in_awsom_defaults = trimmed == "[profile awsom-defaults]";
-            continue;
-        }
+pub fn get_profile_details(profile_name: &str) -> Result<Option<ProfileDetails>> {
+    let doc = load_document(&config_file_path()?)?;
+    let Some(section) = doc.section(&profile_section_name(profile_name)) else {
+        return Ok(None);
+    };
 
-        if in_awsom_defaults && trimmed.contains('=') {
-            let parts: Vec<&str> = trimmed.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim();
-                let value = parts[1].trim();
+    let get = |key: &str| section.get(key).map(str::to_string);
+    let role = profile_role(&section);
+    Ok(Some(ProfileDetails {
+        region: get("region"),
+        output: get("output"),
+        sso_session: role.as_ref().map(|r| r.session.clone()),
+        sso_account_id: role.as_ref().map(|r| r.account_id.clone()),
+        sso_role_name: role.map(|r| r.role_name),
+        role_arn: get("role_arn"),
+        source_profile: get("source_profile"),
+    }))
+}
 
-                match key {
-                    "region" => region = Some(value.to_string()),
-                    "output" => output = Some(value.to_string()),
-                    _ => {}
-                }
-            }
-        }
-    }
+/// Write [default] section to ~/.aws/config with marker-based organization
+pub fn write_default_config(config: &DefaultConfig) -> Result<()> {
+    edit_document(&config_file_path()?, |doc| {
+        let header = awsom_section(doc, "default");
+        doc.set(header, "region", &config.region);
+        doc.set(header, "output", &config.output);
+        Ok(())
+    })
+}
 
-    if region.is_some() || output.is_some() {
-        Ok(Some(DefaultConfig {
-            region: region.unwrap_or_else(|| "us-east-1".to_string()),
-            output: output.unwrap_or_else(|| "json".to_string()),
-        }))
-    } else {
-        Ok(None)
-    }
+/// Write SSO session to ~/.aws/config with marker-based organization
+pub fn write_sso_session(session: &SsoSession) -> Result<()> {
+    edit_document(&config_file_path()?, |doc| {
+        let section_name = format!("{}{}", SSO_SESSION_PREFIX, session.session_name);
+        let header = awsom_section(doc, &section_name);
+        doc.set(header, "sso_start_url", &session.sso_start_url);
+        doc.set(header, "sso_region", &session.sso_region);
+        doc.set(
+            header,
+            "sso_registration_scopes",
+            &session.sso_registration_scopes,
+        );
+        Ok(())
+    })
 }
 
-/// Write awsom defaults to [profile awsom-defaults] in awsom-managed section
-pub fn write_awsom_defaults(config: &DefaultConfig) -> Result<()> {
+/// Delete SSO session from ~/.aws/config with marker-based organization
+pub fn delete_sso_session(session_name: &str) -> Result<()> {
     let config_path = config_file_path()?;
-    let aws_dir = config_path
-        .parent()
-        .ok_or_else(|| SsoError::ConfigError("Invalid config path".to_string()))?;
-
-    // Create ~/.aws directory if it doesn't exist
-    if !aws_dir.exists() {
-        fs::create_dir_all(aws_dir).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to create ~/.aws directory: {}", e))
-        })?;
-    }
 
-    // Create backups on first write
-    create_backups_if_needed()?;
+    if !config_path.exists() {
+        return Ok(()); // Nothing to delete
+    }
 
-    let existing_config = if config_path.exists() {
-        fs::read_to_string(&config_path)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?
-    } else {
-        String::new()
-    };
-
-    // Ensure markers exist in the config
-    let config_with_markers = ensure_markers(&existing_config);
-
-    // Split into header, user-managed and awsom-managed sections
-    let (header, user_section, awsom_section) = split_into_sections(&config_with_markers);
-
-    // Parse existing content from awsom section
-    let sessions = parse_sso_sessions_from_content(&awsom_section);
-    let (default_config_opt, mut profiles) = parse_profiles_from_content(&awsom_section);
-
-    // Remove existing awsom-defaults profile if it exists
-    profiles.retain(|(name, _)| name != "profile awsom-defaults");
-
-    // Add awsom-defaults profile
-    profiles.push((
-        "profile awsom-defaults".to_string(),
-        vec![
-            ("region".to_string(), config.region.clone()),
-            ("output".to_string(), config.output.clone()),
-        ],
-    ));
-
-    // Sort profiles alphabetically by name
-    profiles.sort_by(|a, b| a.0.cmp(&b.0));
-
-    // Build the awsom-managed section
-    let mut new_awsom_section = String::new();
-
-    // Add [default] section if it exists
-    if let Some(default_config) = default_config_opt {
-        new_awsom_section.push_str("[default]\n");
-        for (key, value) in default_config {
-            new_awsom_section.push_str(&format!("{} = {}\n", key, value));
-        }
-        new_awsom_section.push('\n');
-    }
-
-    // Add sorted SSO sessions
-    new_awsom_section.push_str(&rebuild_sso_sessions(&sessions));
-
-    // Add sorted profiles (including awsom-defaults)
-    for (profile_name, entries) in profiles {
-        if profile_name != "default" {
-            new_awsom_section.push_str(&format!("[{}]\n", profile_name));
-            for (key, value) in entries {
-                new_awsom_section.push_str(&format!("{} = {}\n", key, value));
-            }
-            new_awsom_section.push('\n');
-        }
-    }
-
-    // Reconstruct the file using helper
-    let result = reconstruct_config(&header, &user_section, &new_awsom_section);
-
-    fs::write(&config_path, cleanup_empty_lines(&result))
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
-
-    Ok(())
-}
-
-/// Check if a profile is in the awsom-managed section
-pub fn is_profile_in_awsom_section(profile_name: &str) -> Result<bool> {
-    let config_path = config_file_path()?;
-
-    if !config_path.exists() {
-        return Ok(false);
-    }
-
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    // Ensure markers exist
-    let content_with_markers = ensure_markers(&content);
-
-    // Split into user-managed and awsom-managed sections
-    let (_, awsom_section) = split_by_marker(&content_with_markers);
-
-    // Check if profile exists in awsom section
-    let profile_section = if profile_name == "default" {
-        "[default]".to_string()
-    } else {
-        format!("[profile {}]", profile_name)
-    };
-
-    Ok(awsom_section.contains(&profile_section))
-}
-
-/// Get profile details for display (region, output, SSO info if available)
-#[derive(Debug, Clone)]
-pub struct ProfileDetails {
-    pub region: Option<String>,
-    pub output: Option<String>,
-    pub sso_session: Option<String>,
-    pub sso_account_id: Option<String>,
-    pub sso_role_name: Option<String>,
-}
-
-pub fn get_profile_details(profile_name: &str) -> Result<Option<ProfileDetails>> {
-    let config_path = config_file_path()?;
-
-    if !config_path.exists() {
-        return Ok(None);
-    }
-
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    let mut current_profile: Option<String> = None;
-    let mut profile_data: HashMap<String, String> = HashMap::new();
-
-    let target_section = if profile_name == "default" {
-        "default".to_string()
-    } else {
-        format!("profile {}", profile_name)
-    };
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Check if we found our target profile
-            if let Some(found_profile) = current_profile.take() {
-                if found_profile == target_section {
-                    // Return the profile details
-                    return Ok(Some(ProfileDetails {
-                        region: profile_data.get("region").cloned(),
-                        output: profile_data.get("output").cloned(),
-                        sso_session: profile_data.get("sso_session").cloned(),
-                        sso_account_id: profile_data.get("sso_account_id").cloned(),
-                        sso_role_name: profile_data.get("sso_role_name").cloned(),
-                    }));
-                }
-                profile_data.clear();
-            }
-
-            // Parse section header
-            let section = &trimmed[1..trimmed.len() - 1];
-            if section == "default" {
-                current_profile = Some("default".to_string());
-            } else if section.starts_with("profile ") {
-                current_profile = Some(section.to_string());
-            }
-        } else if current_profile.is_some() && trimmed.contains('=') && !trimmed.starts_with('#') {
-            let parts: Vec<&str> = trimmed.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().to_string();
-                let value = parts[1].trim().to_string();
-                profile_data.insert(key, value);
-            }
-        }
-    }
-
-    // Check last profile
-    if let Some(found_profile) = current_profile {
-        if found_profile == target_section {
-            return Ok(Some(ProfileDetails {
-                region: profile_data.get("region").cloned(),
-                output: profile_data.get("output").cloned(),
-                sso_session: profile_data.get("sso_session").cloned(),
-                sso_account_id: profile_data.get("sso_account_id").cloned(),
-                sso_role_name: profile_data.get("sso_role_name").cloned(),
-            }));
-        }
-    }
-
-    Ok(None)
-}
-
-/// Write [default] section to ~/.aws/config with marker-based organization
-pub fn write_default_config(config: &DefaultConfig) -> Result<()> {
-    let config_path = config_file_path()?;
-    let aws_dir = config_path
-        .parent()
-        .ok_or_else(|| SsoError::ConfigError("Invalid config path".to_string()))?;
-
-    // Create ~/.aws directory if it doesn't exist
-    if !aws_dir.exists() {
-        fs::create_dir_all(aws_dir).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to create ~/.aws directory: {}", e))
-        })?;
-    }
-
-    // Create backups on first write
-    create_backups_if_needed()?;
-
-    let existing_config = if config_path.exists() {
-        fs::read_to_string(&config_path)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?
-    } else {
-        String::new()
-    };
-
-    // Ensure markers exist in the config
-    let config_with_markers = ensure_markers(&existing_config);
-
-    // Split into user-managed and awsom-managed sections
-    let (header, user_section, awsom_section) = split_into_sections(&config_with_markers);
-
-    // Parse existing sessions and default config from awsom section
-    let sessions = parse_sso_sessions_from_content(&awsom_section);
-
-    // Build the awsom-managed section: [default] first, then sorted SSO sessions
-    let mut new_awsom_section = String::new();
-
-    // Add [default] section
-    new_awsom_section.push_str("[default]\n");
-    new_awsom_section.push_str(&format!("region = {}\n", config.region));
-    new_awsom_section.push_str(&format!("output = {}\n", config.output));
-    new_awsom_section.push('\n');
-
-    // Add sorted SSO sessions
-    new_awsom_section.push_str(&rebuild_sso_sessions(&sessions));
-
-    // Reconstruct the file using helper
-    let result = reconstruct_config(&header, &user_section, &new_awsom_section);
-
-    fs::write(&config_path, cleanup_empty_lines(&result))
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
-
-    Ok(())
-}
-
-/// Write SSO session to ~/.aws/config with marker-based organization
-pub fn write_sso_session(session: &SsoSession) -> Result<()> {
-    let config_path = config_file_path()?;
-    let aws_dir = config_path
-        .parent()
-        .ok_or_else(|| SsoError::ConfigError("Invalid config path".to_string()))?;
-
-    // Create ~/.aws directory if it doesn't exist
-    if !aws_dir.exists() {
-        fs::create_dir_all(aws_dir).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to create ~/.aws directory: {}", e))
-        })?;
-    }
-
-    // Create backups on first write
-    create_backups_if_needed()?;
-
-    let existing_config = if config_path.exists() {
-        fs::read_to_string(&config_path)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?
-    } else {
-        String::new()
-    };
-
-    // Ensure markers exist in the config
-    let config_with_markers = ensure_markers(&existing_config);
-
-    // Split into user-managed and awsom-managed sections
-    let (header, user_section, awsom_section) = split_into_sections(&config_with_markers);
-
-    // Parse existing SSO sessions from awsom section
-    let mut sessions = parse_sso_sessions_from_content(&awsom_section);
-
-    // Add or update the target session
-    sessions.retain(|s| s.session_name != session.session_name);
-    sessions.push(session.clone());
-
-    // Sort sessions alphabetically by name
-    sessions.sort_by(|a, b| a.session_name.cmp(&b.session_name));
-
-    // Rebuild awsom section with sorted sessions
-    let new_awsom_section = rebuild_sso_sessions(&sessions);
-
-    // Reconstruct the file using helper
-    let result = reconstruct_config(&header, &user_section, &new_awsom_section);
-
-    fs::write(&config_path, cleanup_empty_lines(&result))
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
-
-    Ok(())
-}
-
-/// Parse SSO sessions from INI content
-fn parse_sso_sessions_from_content(content: &str) -> Vec<SsoSession> {
-    let mut sessions = Vec::new();
-    let mut current_session_name: Option<String> = None;
-    let mut session_data: HashMap<String, String> = HashMap::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Save previous session if complete
-            if let Some(name) = current_session_name.take() {
-                if let (Some(start_url), Some(region)) = (
-                    session_data.get("sso_start_url"),
-                    session_data.get("sso_region"),
-                ) {
-                    let scopes = session_data
-                        .get("sso_registration_scopes")
-                        .cloned()
-                        .unwrap_or_else(|| "sso:account:access".to_string());
-
-                    sessions.push(SsoSession {
-                        session_name: name,
-                        sso_start_url: start_url.clone(),
-                        sso_region: region.clone(),
-                        sso_registration_scopes: scopes,
-                    });
-                }
-                session_data.clear();
-            }
-
-            // Check if this is an SSO session header
-            if trimmed.starts_with("[sso-session ") {
-                let name_part = &trimmed[13..trimmed.len() - 1];
-                current_session_name = Some(name_part.trim().to_string());
-            }
-        } else if current_session_name.is_some()
-            && trimmed.contains('=')
-            && !trimmed.starts_with('#')
-        {
-            let parts: Vec<&str> = trimmed.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().to_string();
-                let value = parts[1].trim().to_string();
-                session_data.insert(key, value);
-            }
+    edit_document(&config_path, |doc| {
+        let section_name = format!("{}{}", SSO_SESSION_PREFIX, session_name);
+        let header = doc
+            .sections()
+            .find(|s| s.name() == section_name && is_awsom_managed(doc, s))
+            .map(|s| s.line());
+        if let Some(header) = header {
+            doc.remove_section(header);
         }
-    }
-
-    // Handle last session
-    if let Some(name) = current_session_name {
-        if let (Some(start_url), Some(region)) = (
-            session_data.get("sso_start_url"),
-            session_data.get("sso_region"),
-        ) {
-            let scopes = session_data
-                .get("sso_registration_scopes")
-                .cloned()
-                .unwrap_or_else(|| "sso:account:access".to_string());
-
-            sessions.push(SsoSession {
-                session_name: name,
-                sso_start_url: start_url.clone(),
-                sso_region: region.clone(),
-                sso_registration_scopes: scopes,
-            });
-        }
-    }
-
-    sessions
-}
-
-/// Rebuild SSO sessions section from a sorted list
-fn rebuild_sso_sessions(sessions: &[SsoSession]) -> String {
-    let mut result = String::new();
-
-    for session in sessions {
-        result.push_str(&format!("[sso-session {}]\n", session.session_name));
-        result.push_str(&format!("sso_start_url = {}\n", session.sso_start_url));
-        result.push_str(&format!("sso_region = {}\n", session.sso_region));
-        result.push_str(&format!(
-            "sso_registration_scopes = {}\n",
-            session.sso_registration_scopes
-        ));
-        result.push('\n');
-    }
-
-    result
-}
-
-/// Delete SSO session from ~/.aws/config with marker-based organization
-pub fn delete_sso_session(session_name: &str) -> Result<()> {
-    let config_path = config_file_path()?;
-
-    if !config_path.exists() {
-        return Ok(()); // Nothing to delete
-    }
-
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    // Ensure markers exist in the config
-    let config_with_markers = ensure_markers(&content);
-
-    // Split into user-managed and awsom-managed sections
-    let (header, user_section, awsom_section) = split_into_sections(&config_with_markers);
-
-    // Parse existing SSO sessions from awsom section
-    let mut sessions = parse_sso_sessions_from_content(&awsom_section);
-
-    // Remove the target session
-    sessions.retain(|s| s.session_name != session_name);
-
-    // Sort sessions alphabetically by name
-    sessions.sort_by(|a, b| a.session_name.cmp(&b.session_name));
-
-    // Rebuild awsom section with sorted sessions
-    let new_awsom_section = rebuild_sso_sessions(&sessions);
-
-    // Reconstruct the file using helper
-    let result = reconstruct_config(&header, &user_section, &new_awsom_section);
-
-    fs::write(&config_path, cleanup_empty_lines(&result))
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
-
-    Ok(())
+        Ok(())
+    })
 }
 
 /// Write credentials to ~/.aws/credentials and config
@@ -1179,6 +889,11 @@ pub fn write_credentials(
 }
 
 /// Write credentials with optional metadata for tracking account/role
+///
+/// Profiles whose `credential_process` runs awsom get no keys in ~/.aws/credentials, only
+/// their region and output; awsom serves their credentials from its cache instead. Other
+/// profiles bound to the same role that hold live credentials get the new ones too, so
+/// e.g. `default` and `prod-admin` stay current together.
 pub fn write_credentials_with_metadata(
     profile_name: &str,
     creds: &RoleCredentials,
@@ -1186,66 +901,17 @@ pub fn write_credentials_with_metadata(
     output_format: Option<&str>,
     account_role: Option<&AccountRole>,
 ) -> Result<()> {
-    let creds_path = credentials_file_path()?;
-    let aws_dir = creds_path
-        .parent()
-        .ok_or_else(|| SsoError::ConfigError("Invalid credentials path".to_string()))?;
-
-    // Create ~/.aws directory if it doesn't exist
-    if !aws_dir.exists() {
-        fs::create_dir_all(aws_dir).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to create ~/.aws directory: {}", e))
-        })?;
-    }
-
-    // Create backups on first write
-    create_backups_if_needed()?;
-
-    // Read existing credentials file
-    let existing_content = if creds_path.exists() {
-        fs::read_to_string(&creds_path)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to read credentials file: {}", e)))?
-    } else {
-        String::new()
-    };
-
     // Build metadata comments if account_role is provided
-    let metadata = if let Some(role) = account_role {
-        vec![
+    let metadata = match account_role {
+        Some(role) => vec![
             format!("# Account: {}", role.account_id),
             format!("# Role: {}", role.role_name),
             format!("# Valid: {}", creds.expiration.to_rfc3339()),
-        ]
-    } else {
-        vec![]
-    };
-
-    let metadata = if !metadata.is_empty() {
-        Some(metadata)
-    } else {
-        None
-    };
-
-    // Parse and update credentials
-    let new_content = update_ini_section_with_comments(
-        &existing_content,
-        profile_name,
-        &[
-            ("aws_access_key_id", &creds.access_key_id),
-            ("aws_secret_access_key", &creds.secret_access_key),
-            ("aws_session_token", &creds.session_token),
         ],
-        metadata.as_deref(),
-    );
-
-    // Sort credentials profiles alphabetically
-    let sorted_content = sort_credentials_profiles(&new_content);
-
-    // Write updated credentials
-    fs::write(&creds_path, sorted_content)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write credentials file: {}", e)))?;
+        None => vec![],
+    };
 
-    // Check for profile name collision in user-managed section
+    // Check for profile name collision in user-managed section before touching either file
     if profile_exists_in_user_section(profile_name)? {
         tracing::warn!(
             "Profile '{}' already exists in user-managed section of config file. \
@@ -1253,406 +919,646 @@ pub fn write_credentials_with_metadata(
             to awsom management, or choose a different profile name.",
             profile_name
         );
-        // Return early - don't overwrite user-managed profiles
-        return Err(SsoError::ConfigError(format!(
-            "Profile '{}' exists in user-managed section. \
-            Use a different name or run 'awsom import {}' to manage it with awsom.",
-            profile_name, profile_name
-        )));
-    }
+        return Err(user_managed_conflict(profile_name));
+    }
+
+    let credential_process = load_document(&config_file_path()?)?
+        .section(&profile_section_name(profile_name))
+        .and_then(|s| {
+            s.get("credential_process")
+                .and_then(parse_credential_process)
+        })
+        .is_some();
+
+    edit_document(&credentials_file_path()?, |doc| {
+        refuse_static_credentials(doc, profile_name)?;
+        if credential_process {
+            remove_session_credentials(doc, profile_name);
+            return Ok(());
+        }
+        set_session_credentials(doc, profile_name, creds, &metadata, account_role);
+        Ok(())
+    })?;
 
-    // Also write to config file for region with marker-based organization
-    let config_path = config_file_path()?;
-    let existing_config = if config_path.exists() {
-        fs::read_to_string(&config_path)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?
-    } else {
-        String::new()
+    // Add SSO session information if account_role is provided
+    let sso_session = match account_role {
+        Some(_) => read_sso_session()?,
+        None => None,
     };
 
-    // Ensure markers exist in the config
-    let config_with_markers = ensure_markers(&existing_config);
-
-    // Split into user-managed and awsom-managed sections
-    let (header, user_section, awsom_section) = split_into_sections(&config_with_markers);
+    // Also write to config file for region with marker-based organization
+    edit_document(&config_file_path()?, |doc| {
+        let header = awsom_section(doc, &profile_section_name(profile_name));
+        doc.set(header, "region", region);
+        if let Some(output) = output_format {
+            doc.set(header, "output", output);
+        }
+        if let (Some(role), Some(session), false) = (account_role, &sso_session, credential_process)
+        {
+            doc.set(header, "sso_session", &session.session_name);
+            doc.set(header, "sso_account_id", &role.account_id);
+            doc.set(header, "sso_role_name", &role.role_name);
+        }
+        Ok(())
+    })?;
+
+    audit::record(
+        AuditAction::ProfileWritten,
+        account_role.map(|r| r.account_id.as_str()),
+        account_role.map(|r| r.role_name.as_str()),
+        Some(profile_name),
+    );
+    hooks::run(
+        HookEvent::CredentialsWritten,
+        &HookContext {
+            session: sso_session.as_ref().map(|s| s.session_name.as_str()),
+            account_id: account_role.map(|r| r.account_id.as_str()),
+            role_name: account_role.map(|r| r.role_name.as_str()),
+            profile: Some(profile_name),
+            region: Some(region),
+            expires_at: Some(creds.expiration),
+            ..HookContext::default()
+        },
+    );
 
-    // Parse existing content from awsom section
-    let sessions = parse_sso_sessions_from_content(&awsom_section);
-    let (default_config_opt, mut profiles) = parse_profiles_from_content(&awsom_section);
+    Ok(())
+}
 
-    // Build profile config entries
-    let profile_section = if profile_name == "default" {
-        profile_name.to_string()
-    } else {
-        format!("profile {}", profile_name)
+/// Put credentials and metadata into a profile's credentials section, and into the other
+/// sections whose metadata names the same role and still hold live credentials
+fn set_session_credentials(
+    doc: &mut IniDocument,
+    profile_name: &str,
+    creds: &RoleCredentials,
+    metadata: &[String],
+    account_role: Option<&AccountRole>,
+) {
+    let siblings: Vec<String> = match account_role {
+        Some(role) => doc
+            .sections()
+            .filter(|s| s.name() != profile_name && has_credentials(s))
+            .filter(|s| has_role_metadata(s, &role.account_id, &role.role_name))
+            .map(|s| s.name().to_string())
+            .collect(),
+        None => Vec::new(),
     };
-
-    let mut config_entries_owned: Vec<(String, String)> = vec![];
-    config_entries_owned.push(("region".to_string(), region.to_string()));
-
-    if let Some(output) = output_format {
-        config_entries_owned.push(("output".to_string(), output.to_string()));
-    }
-
-    // Add SSO session information if account_role is provided
-    if let Some(role) = account_role {
-        // Try to get the SSO session from config
-        if let Ok(Some(session)) = read_sso_session() {
-            config_entries_owned.push(("sso_session".to_string(), session.session_name));
-            config_entries_owned.push(("sso_account_id".to_string(), role.account_id.clone()));
-            config_entries_owned.push(("sso_role_name".to_string(), role.role_name.clone()));
+    for name in siblings.iter().map(String::as_str).chain([profile_name]) {
+        if name != profile_name {
+            tracing::debug!("Updating '{}', bound to the same role", name);
         }
+        let header = credentials_section(doc, name);
+        doc.remove_comments(header, is_metadata_comment);
+        doc.insert_comments(header, metadata);
+        doc.set(header, "aws_access_key_id", &creds.access_key_id);
+        doc.set(header, "aws_secret_access_key", &creds.secret_access_key);
+        doc.set(header, "aws_session_token", &creds.session_token);
     }
+}
 
-    // Update or add profile
-    profiles.retain(|(name, _)| name != &profile_section);
-    profiles.push((profile_section, config_entries_owned));
+/// An SSO-style profile - the AWS CLI/SDKs resolve its credentials from the sso-session
+#[derive(Debug, Clone)]
+pub struct SsoProfile {
+    pub name: String,
+    pub sso_session: String,
+    pub account_id: String,
+    pub role_name: String,
+    pub region: String,
+    pub output: Option<String>,
+    /// Resolve credentials through `awsom credential-process` instead of the sso_* keys
+    pub credential_process: bool,
+    /// Assume `role_arn(account_id, role_name, region)` with this profile's credentials instead,
+    /// for SDKs that predate SSO profiles
+    pub source_profile: Option<String>,
+}
+
+/// ARN of the IAM role named like a permission set's role, assumed by `source_profile`
+/// profiles; `region` picks the partition
+pub fn role_arn(account_id: &str, role_name: &str, region: &str) -> String {
+    format!(
+        "arn:{}:iam::{}:role/{}",
+        Partition::from_region(region).name(),
+        account_id,
+        role_name
+    )
+}
 
-    // Sort profiles alphabetically by name
-    profiles.sort_by(|a, b| a.0.cmp(&b.0));
+/// Result of adding SSO profiles to ~/.aws/config
+#[derive(Debug, Clone)]
+pub struct SsoProfilesUpdate {
+    /// Config file content before the change
+    pub before: String,
+    /// Config file content after the change
+    pub after: String,
+    /// Profiles left alone because the name is taken in the user-managed section
+    pub skipped: Vec<String>,
+}
 
-    // Build the awsom-managed section: [default] first (if exists), then sorted SSO sessions, then sorted profiles
-    let mut new_awsom_section = String::new();
+/// Add or update SSO-style profiles in the awsom-managed section of ~/.aws/config
+///
+/// A role that already has an awsom-managed profile for the same session (or the same
+/// `role_arn` and `source_profile`) keeps that profile's name, switching between the sso_*
+/// keys, `credential_process` and `role_arn` as asked. Session credentials awsom wrote to
+/// ~/.aws/credentials for `credential_process` and `role_arn` profiles are removed, since the
+/// SDKs would read them first. Nothing is written when `dry_run` is set.
+pub fn write_sso_profiles(profiles: &[SsoProfile], dry_run: bool) -> Result<SsoProfilesUpdate> {
+    let mut skipped = Vec::new();
+    let mut keyless_profiles = Vec::new();
+    let mut apply = |doc: &mut IniDocument| {
+        ensure_markers(doc);
+
+        for profile in profiles {
+            let wanted = ProfileRole {
+                session: profile.sso_session.clone(),
+                account_id: profile.account_id.clone(),
+                role_name: profile.role_name.clone(),
+            };
+            let arn = role_arn(&profile.account_id, &profile.role_name, &profile.region);
+            let assumes_role = |s: &Section| {
+                profile.source_profile.is_some()
+                    && s.get("role_arn") == Some(arn.as_str())
+                    && s.get("source_profile") == profile.source_profile.as_deref()
+            };
+            // Reuse the section of an existing profile for this role, if any
+            let section_name = doc
+                .sections()
+                .find(|s| {
+                    is_awsom_managed(doc, s)
+                        && (profile_role(s).as_ref() == Some(&wanted) || assumes_role(s))
+                })
+                .map(|s| s.name().to_string())
+                .unwrap_or_else(|| profile_section_name(&profile.name));
+
+            if in_user_region(doc, &section_name) {
+                skipped.push(profile.name.clone());
+                continue;
+            }
 
-    // Add [default] section if it exists
-    if let Some(default_config) = default_config_opt {
-        new_awsom_section.push_str("[default]\n");
-        for (key, value) in default_config {
-            new_awsom_section.push_str(&format!("{} = {}\n", key, value));
+            let header = awsom_section(doc, &section_name);
+            doc.set(header, "region", &profile.region);
+            if let Some(output) = &profile.output {
+                doc.set(header, "output", output);
+            }
+            if let Some(source_profile) = &profile.source_profile {
+                doc.set(header, "role_arn", &arn);
+                doc.set(header, "source_profile", source_profile);
+                // SDKs disagree on which of role_arn, the SSO keys and credential_process
+                // wins, so only one is kept
+                for key in ["sso_session", "sso_account_id", "sso_role_name"] {
+                    doc.remove(header, key);
+                }
+                let section = doc.section_at(header);
+                if section
+                    .get("credential_process")
+                    .and_then(parse_credential_process)
+                    .is_some()
+                {
+                    doc.remove(header, "credential_process");
+                }
+                if let Some(name) = profile_name_of(&section_name) {
+                    keyless_profiles.push(name.to_string());
+                }
+            } else if profile.credential_process {
+                let command = credential_process_command(
+                    &profile.sso_session,
+                    &profile.account_id,
+                    &profile.role_name,
+                );
+                doc.set(header, "credential_process", &command);
+                // The SDKs would pick the SSO keys or role_arn over credential_process
+                for key in [
+                    "sso_session",
+                    "sso_account_id",
+                    "sso_role_name",
+                    "role_arn",
+                    "source_profile",
+                ] {
+                    doc.remove(header, key);
+                }
+                if let Some(name) = profile_name_of(&section_name) {
+                    keyless_profiles.push(name.to_string());
+                }
+            } else {
+                doc.set(header, "sso_session", &profile.sso_session);
+                doc.set(header, "sso_account_id", &profile.account_id);
+                doc.set(header, "sso_role_name", &profile.role_name);
+                for key in ["role_arn", "source_profile"] {
+                    doc.remove(header, key);
+                }
+                let section = doc.section_at(header);
+                if section
+                    .get("credential_process")
+                    .and_then(parse_credential_process)
+                    .is_some()
+                {
+                    doc.remove(header, "credential_process");
+                }
+            }
         }
-        new_awsom_section.push('\n');
-    }
+    };
 
-    // Add sorted SSO sessions
-    new_awsom_section.push_str(&rebuild_sso_sessions(&sessions));
+    let config_path = config_file_path()?;
+    let (before, after) = if dry_run {
+        let mut doc = load_document(&config_path)?;
+        let before = doc.to_string();
+        apply(&mut doc);
+        (before, doc.to_string())
+    } else {
+        edit_document(&config_path, |doc| {
+            let before = doc.to_string();
+            apply(doc);
+            Ok((before, doc.to_string()))
+        })?
+    };
 
-    // Add sorted profiles (skipping default as it was handled above)
-    for (profile_name, entries) in profiles {
-        if profile_name != "default" {
-            new_awsom_section.push_str(&format!("[{}]\n", profile_name));
-            for (key, value) in entries {
-                new_awsom_section.push_str(&format!("{} = {}\n", key, value));
+    let credentials_path = credentials_file_path()?;
+    if !dry_run && !keyless_profiles.is_empty() && credentials_path.exists() {
+        edit_document(&credentials_path, |doc| {
+            for name in &keyless_profiles {
+                remove_session_credentials(doc, name);
+            }
+            Ok(())
+        })?;
+    }
+
+    if !dry_run && after != before {
+        for profile in profiles {
+            if !skipped.contains(&profile.name) {
+                audit::record(
+                    AuditAction::ProfileWritten,
+                    Some(&profile.account_id),
+                    Some(&profile.role_name),
+                    Some(&profile.name),
+                );
             }
-            new_awsom_section.push('\n');
         }
     }
 
-    // Reconstruct the file using helper
-    let result = reconstruct_config(&header, &user_section, &new_awsom_section);
+    Ok(SsoProfilesUpdate {
+        before,
+        after,
+        skipped,
+    })
+}
 
-    fs::write(&config_path, cleanup_empty_lines(&result))
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
+/// Write a profile with only SSO settings, for `profile_mode = "sso-native"`
+///
+/// Unlike `write_sso_profiles`, the profile gets exactly the name asked for. Session
+/// credentials awsom wrote to ~/.aws/credentials for it are removed, as the AWS CLI and SDKs
+/// would use them instead of the SSO settings.
+pub fn write_sso_native_profile(profile: &SsoProfile) -> Result<()> {
+    let credentials_path = credentials_file_path()?;
+    if credentials_path.exists() {
+        edit_document(&credentials_path, |doc| {
+            refuse_static_credentials(doc, &profile.name)?;
+            remove_session_credentials(doc, &profile.name);
+            Ok(())
+        })?;
+    }
 
+    let section_name = profile_section_name(&profile.name);
+    edit_document(&config_file_path()?, |doc| {
+        ensure_markers(doc);
+        if in_user_region(doc, &section_name) {
+            return Err(user_managed_conflict(&profile.name));
+        }
+        let header = awsom_section(doc, &section_name);
+        doc.set(header, "region", &profile.region);
+        if let Some(output) = &profile.output {
+            doc.set(header, "output", output);
+        }
+        doc.set(header, "sso_session", &profile.sso_session);
+        doc.set(header, "sso_account_id", &profile.account_id);
+        doc.set(header, "sso_role_name", &profile.role_name);
+        doc.remove(header, "credential_process");
+        Ok(())
+    })?;
+
+    audit::record(
+        AuditAction::ProfileWritten,
+        Some(&profile.account_id),
+        Some(&profile.role_name),
+        Some(&profile.name),
+    );
     Ok(())
 }
 
-/// Check if a profile exists in the user-managed section
-/// Returns true if the profile name exists above the marker
-fn profile_exists_in_user_section(profile_name: &str) -> Result<bool> {
-    let config_path = config_file_path()?;
+/// Name of the [sso-session] with this start URL, for profiles and caches keyed by session
+pub fn session_name_for_start_url(start_url: &str) -> Result<Option<String>> {
+    Ok(read_all_sso_sessions()?
+        .into_iter()
+        .find(|session| session.sso_start_url == start_url)
+        .map(|session| session.session_name))
+}
 
-    if !config_path.exists() {
-        return Ok(false);
+/// The [sso-session] an sso-native profile for `instance` refers to
+pub fn sso_native_session(instance: &SsoInstance) -> Result<String> {
+    if let Some(name) = &instance.session_name {
+        return Ok(name.clone());
     }
+    session_name_for_start_url(&instance.start_url)?.ok_or_else(|| {
+        SsoError::ConfigError(format!(
+            "profile_mode = \"sso-native\" needs an sso-session with the start URL {} in \
+             ~/.aws/config; add one with 'awsom session add'",
+            instance.start_url
+        ))
+    })
+}
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    // Ensure markers exist
-    let content_with_markers = ensure_markers(&content);
+/// Lines of a section in the user-managed part of ~/.aws/config (public for import command)
+pub fn find_user_section(section_name: &str) -> Result<Option<Vec<String>>> {
+    let doc = load_document(&config_file_path()?)?;
+    let lines = doc
+        .sections()
+        .find(|s| s.name() == section_name && !is_awsom_managed(&doc, s))
+        .map(|s| s.raw_lines().map(str::to_string).collect());
+    Ok(lines)
+}
 
-    // Split into user-managed and awsom-managed sections
-    let (user_section, _) = split_by_marker(&content_with_markers);
+/// Move a section from the user-managed to the awsom-managed part of ~/.aws/config,
+/// keeping its comments and keys as written (public for import command)
+///
+/// An awsom-managed section with the same name is replaced.
+pub fn import_section(section_name: &str) -> Result<()> {
+    edit_document(&config_file_path()?, |doc| {
+        ensure_markers(doc);
+
+        let user_header = doc
+            .sections()
+            .find(|s| s.name() == section_name && !is_awsom_managed(doc, s))
+            .map(|s| s.line())
+            .ok_or_else(|| {
+                SsoError::ConfigError(format!(
+                    "[{}] not found in user-managed section",
+                    section_name
+                ))
+            })?;
+        let lines = doc.remove_section(user_header);
+
+        let awsom_header = doc
+            .sections()
+            .find(|s| s.name() == section_name && is_awsom_managed(doc, s))
+            .map(|s| s.line());
+        if let Some(header) = awsom_header {
+            doc.remove_section(header);
+        }
 
-    // Parse profiles from user section
-    let (default_config, profiles) = parse_profiles_from_content(&user_section);
+        let at = awsom_insert_point(doc, section_name);
+        doc.insert_lines(at, lines);
+        Ok(())
+    })
+}
 
-    // Check if profile_name matches
-    let profile_section = if profile_name == "default" {
-        "default".to_string()
-    } else {
-        format!("profile {}", profile_name)
-    };
+/// A section `import --all` can hand over to awsom
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportCandidate {
+    /// `[profile x]` or `[default]` in the user-managed part of ~/.aws/config
+    Profile(String),
+    /// `[sso-session x]` in the user-managed part of ~/.aws/config
+    SsoSession(String),
+    /// Long-term access keys in ~/.aws/credentials that awsom doesn't track yet
+    StaticCredentials(String),
+}
 
-    // Check if it exists in default config
-    if profile_section == "default" && default_config.is_some() {
-        return Ok(true);
+impl ImportCandidate {
+    /// The `import --section-type` value for this kind of section
+    pub fn section_type(&self) -> &'static str {
+        match self {
+            ImportCandidate::Profile(_) => "profile",
+            ImportCandidate::SsoSession(_) => "sso-session",
+            ImportCandidate::StaticCredentials(_) => "credentials",
+        }
     }
 
-    // Check if it exists in other profiles
-    for (name, _) in profiles {
-        if name == profile_section {
-            return Ok(true);
+    pub fn name(&self) -> &str {
+        match self {
+            ImportCandidate::Profile(name)
+            | ImportCandidate::SsoSession(name)
+            | ImportCandidate::StaticCredentials(name) => name,
         }
     }
-
-    Ok(false)
 }
 
-/// Type alias for profile parsing result
-type ProfilesParseResult = (
-    Option<Vec<(String, String)>>,
-    Vec<(String, Vec<(String, String)>)>,
-);
-
-/// Sort profiles in credentials file alphabetically ([default] first, then sorted)
-fn sort_credentials_profiles(content: &str) -> String {
-    let mut profiles: Vec<(String, Vec<String>)> = Vec::new();
-    let mut current_profile: Option<String> = None;
-    let mut profile_lines: Vec<String> = Vec::new();
-    let mut header_lines: Vec<String> = Vec::new();
-    let mut in_header = true;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Collect header comments before first profile
-        if in_header && !trimmed.starts_with('[') {
-            header_lines.push(line.to_string());
-            continue;
-        }
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            in_header = false;
-
-            // Save previous profile
-            if let Some(name) = current_profile.take() {
-                profiles.push((name, profile_lines.clone()));
-                profile_lines.clear();
+/// Everything awsom doesn't manage yet: user-managed profiles and sso-sessions, then
+/// untracked static credentials
+pub fn import_candidates() -> Result<Vec<ImportCandidate>> {
+    let config = load_document(&config_file_path()?)?;
+    let mut candidates: Vec<ImportCandidate> = config
+        .sections()
+        .filter(|s| !is_awsom_managed(&config, s))
+        .filter_map(|s| {
+            if let Some(name) = s.name().strip_prefix(SSO_SESSION_PREFIX) {
+                Some(ImportCandidate::SsoSession(name.trim().to_string()))
+            } else {
+                profile_name_of(s.name()).map(|name| ImportCandidate::Profile(name.to_string()))
             }
+        })
+        .collect();
+
+    let credentials = load_document(&credentials_file_path()?)?;
+    candidates.extend(
+        credentials
+            .sections()
+            .filter(|s| is_untracked_static(s))
+            .map(|s| ImportCandidate::StaticCredentials(s.name().to_string())),
+    );
+    Ok(candidates)
+}
 
-            // Start new profile
-            let profile_name = trimmed[1..trimmed.len() - 1].to_string();
-            current_profile = Some(profile_name);
-            profile_lines.push(line.to_string());
-        } else if current_profile.is_some() {
-            profile_lines.push(line.to_string());
+/// Mark a static-credentials profile in ~/.aws/credentials as tracked by awsom, which from
+/// then on refuses to write role credentials over it
+pub fn track_static_credentials(profile_name: &str) -> Result<()> {
+    edit_document(&credentials_file_path()?, |doc| {
+        let section = doc.section(profile_name).ok_or_else(|| {
+            SsoError::ConfigError(format!(
+                "[{}] not found in ~/.aws/credentials",
+                profile_name
+            ))
+        })?;
+        if !is_untracked_static(&section) {
+            return Err(SsoError::ConfigError(format!(
+                "[{}] in ~/.aws/credentials is not an untracked static-credentials profile",
+                profile_name
+            )));
         }
-    }
 
-    // Save last profile
-    if let Some(name) = current_profile {
-        profiles.push((name, profile_lines.clone()));
-    }
-
-    // Sort profiles: [default] first, then alphabetically
-    profiles.sort_by(|a, b| match (a.0.as_str(), b.0.as_str()) {
-        ("default", "default") => std::cmp::Ordering::Equal,
-        ("default", _) => std::cmp::Ordering::Less,
-        (_, "default") => std::cmp::Ordering::Greater,
-        (x, y) => x.cmp(y),
-    });
+        let header = section.line();
+        doc.insert_comments(header, &[STATIC_CREDENTIALS_COMMENT.to_string()]);
+        Ok(())
+    })
+}
 
-    // Rebuild file
-    let mut result = String::new();
+/// Long-term keys (no session token) that awsom neither wrote nor tracks yet
+fn is_untracked_static(section: &Section) -> bool {
+    section.get("aws_access_key_id").is_some()
+        && section.get("aws_secret_access_key").is_some()
+        && section.get("aws_session_token").is_none()
+        && !section.comments().any(is_metadata_comment)
+        && !is_static_credentials(section)
+}
 
-    // Add header
-    for line in header_lines {
-        result.push_str(&line);
-        result.push('\n');
-    }
+fn is_static_credentials(section: &Section) -> bool {
+    section
+        .comments()
+        .any(|c| c.starts_with(STATIC_CREDENTIALS_COMMENT))
+}
 
-    // Add sorted profiles
-    for (_, lines) in profiles {
-        for line in lines {
-            result.push_str(&line);
-            result.push('\n');
+/// Error if `profile_name` holds tracked static credentials
+fn refuse_static_credentials(doc: &IniDocument, profile_name: &str) -> Result<()> {
+    match doc.section(profile_name) {
+        Some(section) if is_static_credentials(&section) => {
+            Err(SsoError::ProfileConflict(format!(
+                "Profile '{}' holds static credentials tracked by awsom and is never overwritten. \
+             Use a different profile name.",
+                profile_name
+            )))
         }
-        // Add blank line between profiles
-        result.push('\n');
+        _ => Ok(()),
     }
+}
+
+/// Whether ~/.aws/credentials holds session credentials for `profile_name`, as opposed to
+/// long-term keys (tracked or not) that awsom must leave alone
+pub fn has_session_credentials(profile_name: &str) -> Result<bool> {
+    let doc = load_document(&credentials_file_path()?)?;
+    Ok(doc
+        .section(profile_name)
+        .is_some_and(|s| !is_static_credentials(&s) && !is_untracked_static(&s)))
+}
 
-    cleanup_empty_lines(&result)
+/// Remove the section awsom wrote for a profile's session credentials, leaving static
+/// credentials alone
+fn remove_session_credentials(doc: &mut IniDocument, profile_name: &str) {
+    let header = doc
+        .section(profile_name)
+        .filter(|s| !is_static_credentials(s) && !is_untracked_static(s))
+        .map(|s| s.line());
+    if let Some(header) = header {
+        doc.remove_section(header);
+    }
 }
 
-/// Parse profiles from INI content
-/// Returns (default_config_option, vec of (profile_name, vec of (key, value)))
-fn parse_profiles_from_content(content: &str) -> ProfilesParseResult {
-    let mut default_config: Option<Vec<(String, String)>> = None;
-    let mut profiles = Vec::new();
-    let mut current_profile_name: Option<String> = None;
-    let mut profile_data: Vec<(String, String)> = Vec::new();
+/// Check if a profile exists in the user-managed section
+/// Returns true if the profile name exists above the marker
+pub fn profile_exists_in_user_section(profile_name: &str) -> Result<bool> {
+    let doc = load_document(&config_file_path()?)?;
+    Ok(in_user_region(&doc, &profile_section_name(profile_name)))
+}
 
-    for line in content.lines() {
-        let trimmed = line.trim();
+fn user_managed_conflict(profile_name: &str) -> SsoError {
+    SsoError::ProfileConflict(format!(
+        "Profile '{}' exists in user-managed section. \
+        Use a different name or run 'awsom import {}' to manage it with awsom.",
+        profile_name, profile_name
+    ))
+}
 
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Save previous profile if complete
-            if let Some(name) = current_profile_name.take() {
-                if name == "default" {
-                    default_config = Some(profile_data.clone());
-                } else {
-                    profiles.push((name, profile_data.clone()));
-                }
-                profile_data.clear();
-            }
+/// Appended to a user-managed profile's name by `ConflictPolicy::Suffix`
+const CONFLICT_SUFFIX: &str = "-awsom";
+
+/// What to do when the profile awsom is about to write is a user-managed section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ConflictPolicy {
+    /// Import the section into awsom's part of the file, then overwrite it
+    Import,
+    /// Write the profile as `<name>-awsom` instead
+    Suffix,
+    /// Leave the profile alone and fail
+    Abort,
+}
 
-            // Check if this is a profile section (not sso-session)
-            let section = &trimmed[1..trimmed.len() - 1];
-            if section == "default" {
-                current_profile_name = Some("default".to_string());
-            } else if section.starts_with("profile ") {
-                current_profile_name = Some(section.to_string());
-            } else if !section.starts_with("sso-session ") {
-                // Some other section that's not sso-session
-                current_profile_name = Some(section.to_string());
-            }
-        } else if current_profile_name.is_some()
-            && trimmed.contains('=')
-            && !trimmed.starts_with('#')
-        {
-            let parts: Vec<&str> = trimmed.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().to_string();
-                let value = parts[1].trim().to_string();
-                profile_data.push((key, value));
-            }
-        }
+/// The name to write `profile_name` under, settling a clash with a user-managed section
+/// by `policy`; a name awsom already manages is returned as is
+pub fn resolve_profile_conflict(profile_name: &str, policy: ConflictPolicy) -> Result<String> {
+    if !profile_exists_in_user_section(profile_name)? {
+        return Ok(profile_name.to_string());
     }
-
-    // Handle last profile
-    if let Some(name) = current_profile_name {
-        if name == "default" {
-            default_config = Some(profile_data);
-        } else {
-            profiles.push((name, profile_data));
+    match policy {
+        ConflictPolicy::Import => {
+            import_section(&profile_section_name(profile_name))?;
+            Ok(profile_name.to_string())
         }
+        ConflictPolicy::Suffix => suffixed_profile_name(profile_name),
+        ConflictPolicy::Abort => Err(user_managed_conflict(profile_name)),
     }
+}
 
-    (default_config, profiles)
-}
-
-/// Update or add a section in an INI-style file with optional comment metadata
-fn update_ini_section_with_comments(
-    content: &str,
-    section_name: &str,
-    key_values: &[(&str, &str)],
-    comments: Option<&[String]>,
-) -> String {
-    let mut result = String::new();
-    let mut in_target_section = false;
-    let mut section_found = false;
-    let mut updated_keys = std::collections::HashSet::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Check if this is a section header
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // If we were in the target section, add any missing keys
-            if in_target_section {
-                for (key, value) in key_values {
-                    if !updated_keys.contains(*key) {
-                        result.push_str(&format!("{} = {}\n", key, value));
-                    }
-                }
-                updated_keys.clear();
-            }
-
-            let section = &trimmed[1..trimmed.len() - 1];
-            in_target_section = section == section_name;
-            if in_target_section {
-                section_found = true;
-                // Skip existing comments after section header (we'll replace them)
-                result.push_str(line);
-                result.push('\n');
-                // Add metadata comments if provided
-                if let Some(comment_lines) = comments {
-                    for comment in comment_lines {
-                        result.push_str(comment);
-                        result.push('\n');
-                    }
-                }
-                continue;
-            }
+/// `<name>-awsom`, or `<name>-awsom-2` and so on when that is user-managed too
+pub fn suffixed_profile_name(profile_name: &str) -> Result<String> {
+    let doc = load_document(&config_file_path()?)?;
+    Ok(free_suffixed_name(&doc, profile_name))
+}
 
-            result.push_str(line);
-            result.push('\n');
-        } else if in_target_section {
-            // Skip old comment lines in target section (they'll be replaced)
-            if trimmed.starts_with('#') {
-                continue;
-            }
-            // Process non-comment lines
-            if !trimmed.is_empty() {
-                if let Some(eq_pos) = trimmed.find('=') {
-                    let key = trimmed[..eq_pos].trim();
-                    if let Some((_, new_value)) = key_values.iter().find(|(k, _)| *k == key) {
-                        // Update this key
-                        result.push_str(&format!("{} = {}\n", key, new_value));
-                        updated_keys.insert(key);
-                        continue;
-                    }
-                }
-            }
-            result.push_str(line);
-            result.push('\n');
-        } else {
-            result.push_str(line);
-            result.push('\n');
-        }
+fn free_suffixed_name(doc: &IniDocument, profile_name: &str) -> String {
+    let mut name = format!("{}{}", profile_name, CONFLICT_SUFFIX);
+    let mut n = 2;
+    while in_user_region(doc, &profile_section_name(&name)) {
+        name = format!("{}{}-{}", profile_name, CONFLICT_SUFFIX, n);
+        n += 1;
     }
+    name
+}
 
-    // If we were in the target section at EOF, add any missing keys
-    if in_target_section {
-        for (key, value) in key_values {
-            if !updated_keys.contains(*key) {
-                result.push_str(&format!("{} = {}\n", key, value));
-            }
-        }
+/// Header line of a profile in the credentials file, adding it in sorted position
+/// ([default] first, then alphabetical) if it isn't there yet
+fn credentials_section(doc: &mut IniDocument, profile_name: &str) -> usize {
+    if let Some(section) = doc.section(profile_name) {
+        return section.line();
     }
 
-    // If section wasn't found, add it at the end
-    if !section_found {
-        if !result.is_empty() && !result.ends_with('\n') {
-            result.push('\n');
-        }
-        // Add blank line before new section for readability
-        if !result.is_empty() {
-            result.push('\n');
-        }
-        result.push_str(&format!("[{}]\n", section_name));
-        // Add metadata comments if provided
-        if let Some(comment_lines) = comments {
-            for comment in comment_lines {
-                result.push_str(comment);
-                result.push('\n');
-            }
-        }
-        for (key, value) in key_values {
-            result.push_str(&format!("{} = {}\n", key, value));
-        }
-    }
+    let sort_key = |name: &str| (name != "default", name.to_string());
+    let at = doc
+        .sections()
+        .find(|s| sort_key(s.name()) > sort_key(profile_name))
+        .map(|s| doc.section_start(s.line()))
+        .unwrap_or(doc.len());
+    doc.insert_section(at, profile_name)
+}
+
+/// Comments awsom writes into credentials sections to track the account, role and expiry
+fn is_metadata_comment(line: &str) -> bool {
+    [
+        "# Account:",
+        "# Role:",
+        "# Valid:",
+        "# Invalidated:",
+        "# Expiration:",
+    ]
+    .iter()
+    .any(|prefix| line.starts_with(prefix))
+}
 
-    cleanup_empty_lines(&result)
+/// Whether a credentials section has `# Account:` and `# Role:` metadata for this role
+fn has_role_metadata(section: &Section, account_id: &str, role_name: &str) -> bool {
+    let account = format!("Account: {}", account_id);
+    let role = format!("Role: {}", role_name);
+    section.comments().any(|c| c.contains(&account))
+        && section.comments().any(|c| c.contains(&role))
 }
 
-/// Update or add a section in an INI-style file
-fn update_ini_section(content: &str, section_name: &str, key_values: &[(&str, &str)]) -> String {
-    update_ini_section_with_comments(content, section_name, key_values, None)
+fn has_credentials(section: &Section) -> bool {
+    [
+        "aws_access_key_id",
+        "aws_secret_access_key",
+        "aws_session_token",
+    ]
+    .iter()
+    .all(|key| section.get(key).is_some())
 }
 
 /// Get all profile names from ~/.aws/credentials
 pub fn list_profiles() -> Result<Vec<String>> {
-    let creds_path = credentials_file_path()?;
-
-    if !creds_path.exists() {
-        return Ok(Vec::new());
-    }
-
-    let content = fs::read_to_string(&creds_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read credentials file: {}", e)))?;
-
-    let mut profiles = Vec::new();
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            let profile = &trimmed[1..trimmed.len() - 1];
-            profiles.push(profile.to_string());
-        }
-    }
+    let doc = load_document(&credentials_file_path()?)?;
+    Ok(doc.sections().map(|s| s.name().to_string()).collect())
+}
 
-    Ok(profiles)
+/// Get all profile names from ~/.aws/config
+pub fn list_config_profiles() -> Result<Vec<String>> {
+    let doc = load_document(&config_file_path()?)?;
+    Ok(doc
+        .sections()
+        .filter_map(|s| profile_name_of(s.name()).map(str::to_string))
+        .collect())
 }
 
 /// Profile with credential status
@@ -1693,311 +1599,133 @@ pub fn get_profile_by_role(
     Ok(None)
 }
 
-/// Search ~/.aws/config for profile with matching sso_session, account_id, and role_name
-fn get_profile_from_config(
+/// Names of every profile bound to a role: those in ~/.aws/config whose sso_* settings (or
+/// awsom `credential_process`) name it under this session, then those in
+/// ~/.aws/credentials whose metadata does; the first is the one `get_profile_by_role` finds
+pub fn get_profiles_by_role(
     sso_session_name: &str,
     account_id: &str,
     role_name: &str,
-) -> Result<Option<ProfileInfo>> {
-    let config_path = config_file_path()?;
-
-    if !config_path.exists() {
-        return Ok(None);
-    }
-
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    let mut current_profile: Option<String> = None;
-    let mut profile_data: HashMap<String, String> = HashMap::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with("[profile ") && trimmed.ends_with(']') {
-            // Check previous profile for match
-            if let Some(profile_name) = current_profile.take() {
-                if let Some(profile_info) = check_config_profile_match(
-                    &profile_name,
-                    &profile_data,
-                    sso_session_name,
-                    account_id,
-                    role_name,
-                )? {
-                    return Ok(Some(profile_info));
-                }
-                profile_data.clear();
-            }
-
-            // Extract profile name (remove "[profile " prefix and "]" suffix)
-            current_profile = Some(trimmed[9..trimmed.len() - 1].to_string());
-        } else if trimmed.starts_with("[default]") {
-            // Handle [default] section (no "profile" prefix)
-            if let Some(profile_name) = current_profile.take() {
-                if let Some(profile_info) = check_config_profile_match(
-                    &profile_name,
-                    &profile_data,
-                    sso_session_name,
-                    account_id,
-                    role_name,
-                )? {
-                    return Ok(Some(profile_info));
-                }
-                profile_data.clear();
-            }
-            current_profile = Some("default".to_string());
-        } else if !trimmed.is_empty() && !trimmed.starts_with('#') && !trimmed.starts_with('[') {
-            // Parse key=value pairs
-            if let Some(eq_pos) = trimmed.find('=') {
-                let key = trimmed[..eq_pos].trim().to_string();
-                let value = trimmed[eq_pos + 1..].trim().to_string();
-                profile_data.insert(key, value);
-            }
+) -> Result<Vec<String>> {
+    let config = load_document(&config_file_path()?)?;
+    let mut names: Vec<String> = config
+        .sections()
+        .filter(|section| {
+            profile_role(section).is_some_and(|role| {
+                role.session == sso_session_name
+                    && role.account_id == account_id
+                    && role.role_name == role_name
+            })
+        })
+        .filter_map(|section| profile_name_of(section.name()).map(str::to_string))
+        .collect();
+    for name in find_credentials_profiles(account_id, role_name)? {
+        if !names.contains(&name) {
+            names.push(name);
         }
     }
+    Ok(names)
+}
 
-    // Check last profile
-    if let Some(profile_name) = current_profile {
-        if let Some(profile_info) = check_config_profile_match(
-            &profile_name,
-            &profile_data,
-            sso_session_name,
-            account_id,
-            role_name,
-        )? {
-            return Ok(Some(profile_info));
+/// Number of profiles in ~/.aws/config bound to a role of each SSO session
+pub fn count_profiles_by_session() -> Result<HashMap<String, usize>> {
+    let config = load_document(&config_file_path()?)?;
+    let mut counts = HashMap::new();
+    for section in config.sections() {
+        if profile_name_of(section.name()).is_none() {
+            continue;
+        }
+        if let Some(role) = profile_role(&section) {
+            *counts.entry(role.session).or_insert(0) += 1;
         }
     }
-
-    Ok(None)
+    Ok(counts)
 }
 
-/// Check if a config profile matches the criteria
-fn check_config_profile_match(
-    profile_name: &str,
-    profile_data: &HashMap<String, String>,
+/// Search ~/.aws/config for profile with matching sso_session, account_id, and role_name
+fn get_profile_from_config(
     sso_session_name: &str,
     account_id: &str,
     role_name: &str,
 ) -> Result<Option<ProfileInfo>> {
-    // Check for match on all three keys
-    let matches_session = profile_data
-        .get("sso_session")
-        .map(|s| s == sso_session_name)
-        .unwrap_or(false);
-    let matches_account = profile_data
-        .get("sso_account_id")
-        .map(|s| s == account_id)
-        .unwrap_or(false);
-    let matches_role = profile_data
-        .get("sso_role_name")
-        .map(|s| s == role_name)
-        .unwrap_or(false);
-
-    if matches_session && matches_account && matches_role {
-        // Found a match! Extract region and output
-        let region = profile_data
-            .get("region")
-            .cloned()
-            .unwrap_or_else(|| "us-east-1".to_string());
-        let output = profile_data
-            .get("output")
-            .cloned()
-            .unwrap_or_else(|| "json".to_string());
-
-        return Ok(Some(ProfileInfo {
-            name: profile_name.to_string(),
-            region,
-            output,
-        }));
-    }
+    let doc = load_document(&config_file_path()?)?;
+
+    let profile = doc.sections().find_map(|section| {
+        let name = profile_name_of(section.name())?;
+        let matches = profile_role(&section).is_some_and(|role| {
+            role.session == sso_session_name
+                && role.account_id == account_id
+                && role.role_name == role_name
+        });
 
-    Ok(None)
+        matches.then(|| ProfileInfo {
+            name: name.to_string(),
+            region: section.get("region").unwrap_or("us-east-1").to_string(),
+            output: section.get("output").unwrap_or("json").to_string(),
+        })
+    });
+    Ok(profile)
 }
 
 /// Search ~/.aws/credentials for profile with matching account_id and role_name in metadata
 fn get_profile_from_credentials(account_id: &str, role_name: &str) -> Result<Option<ProfileInfo>> {
-    let creds_path = credentials_file_path()?;
-
-    if !creds_path.exists() {
-        return Ok(None);
-    }
-
-    let content = fs::read_to_string(&creds_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read credentials file: {}", e)))?;
-
-    let mut current_profile: Option<String> = None;
-    let mut found_account_id = false;
-    let mut found_role_name = false;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Check if previous profile matched
-            if let Some(profile) = &current_profile {
-                if found_account_id && found_role_name {
-                    // Found in credentials, but we don't have region/output info
-                    // Return with defaults
-                    return Ok(Some(ProfileInfo {
-                        name: profile.clone(),
-                        region: "us-east-1".to_string(),
-                        output: "json".to_string(),
-                    }));
-                }
-            }
-
-            // Start new profile
-            current_profile = Some(trimmed[1..trimmed.len() - 1].to_string());
-            found_account_id = false;
-            found_role_name = false;
-        } else if current_profile.is_some() {
-            // Check for metadata comments
-            if trimmed.starts_with('#') {
-                if trimmed.contains(&format!("Account: {}", account_id)) {
-                    found_account_id = true;
-                } else if trimmed.contains(&format!("Role: {}", role_name)) {
-                    found_role_name = true;
-                }
-            }
-        }
-    }
-
-    // Check last profile
-    if let Some(profile) = current_profile {
-        if found_account_id && found_role_name {
-            return Ok(Some(ProfileInfo {
-                name: profile,
-                region: "us-east-1".to_string(),
-                output: "json".to_string(),
-            }));
-        }
-    }
-
-    Ok(None)
+    // Found in credentials, but we don't have region/output info - return with defaults
+    Ok(
+        find_credentials_profile(account_id, role_name)?.map(|name| ProfileInfo {
+            name,
+            region: "us-east-1".to_string(),
+            output: "json".to_string(),
+        }),
+    )
 }
 
-/// Check if a role has active credentials in AWS config
-pub fn get_profile_for_role(account: &AccountRole) -> Result<Option<ProfileStatus>> {
-    let creds_path = credentials_file_path()?;
-
-    if !creds_path.exists() {
-        return Ok(None);
-    }
-
-    let content = fs::read_to_string(&creds_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read credentials file: {}", e)))?;
-
-    let mut current_profile: Option<String> = None;
-    let mut profile_data: HashMap<String, String> = HashMap::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Check previous profile
-            if let Some(profile) = current_profile.take() {
-                if let Some(status) = check_profile_match(&profile, &profile_data, account)? {
-                    return Ok(Some(status));
-                }
-                profile_data.clear();
-            }
-
-            current_profile = Some(trimmed[1..trimmed.len() - 1].to_string());
-        } else if !trimmed.is_empty() && !trimmed.starts_with('#') {
-            if let Some(eq_pos) = trimmed.find('=') {
-                let key = trimmed[..eq_pos].trim().to_string();
-                let value = trimmed[eq_pos + 1..].trim().to_string();
-                profile_data.insert(key, value);
-            }
-        }
-    }
-
-    // Check last profile
-    if let Some(profile) = current_profile {
-        if let Some(status) = check_profile_match(&profile, &profile_data, account)? {
-            return Ok(Some(status));
-        }
-    }
+/// Name of the first credentials profile whose metadata names this account and role
+fn find_credentials_profile(account_id: &str, role_name: &str) -> Result<Option<String>> {
+    Ok(find_credentials_profiles(account_id, role_name)?
+        .into_iter()
+        .next())
+}
 
-    Ok(None)
+/// Names of the credentials profiles whose metadata names this account and role, in file
+/// order
+fn find_credentials_profiles(account_id: &str, role_name: &str) -> Result<Vec<String>> {
+    let doc = load_document(&credentials_file_path()?)?;
+    Ok(doc
+        .sections()
+        .filter(|s| has_role_metadata(s, account_id, role_name))
+        .map(|s| s.name().to_string())
+        .collect())
 }
 
-fn check_profile_match(
-    profile_name: &str,
-    data: &HashMap<String, String>,
-    _account: &AccountRole,
-) -> Result<Option<ProfileStatus>> {
-    // Check if this profile has credentials
-    let has_key = data.contains_key("aws_access_key_id");
-    let has_secret = data.contains_key("aws_secret_access_key");
-    let has_session = data.contains_key("aws_session_token");
-
-    if !has_key || !has_secret || !has_session {
-        return Ok(None);
-    }
+/// Check if a role has active credentials in AWS config
+pub fn get_profile_for_role(_account: &AccountRole) -> Result<Option<ProfileStatus>> {
+    let doc = load_document(&credentials_file_path()?)?;
 
     // For now, we can't definitively match without storing metadata
     // We'll consider any profile with credentials as potentially active
-    // A better approach would be to add comments or use AWS SSO cache structure
-
-    Ok(Some(ProfileStatus {
-        profile_name: profile_name.to_string(),
-        account_id: None,
-        role_name: None,
-        has_credentials: true,
-        expiration: None,
-    }))
+    let status = doc
+        .sections()
+        .find(|s| has_credentials(s))
+        .map(|s| ProfileStatus {
+            profile_name: s.name().to_string(),
+            account_id: None,
+            role_name: None,
+            has_credentials: true,
+            expiration: None,
+        });
+    Ok(status)
 }
 
 /// Get the existing profile name for an account/role combination
 /// Returns the profile name if found, based on matching account ID and role name in comments
 pub fn get_existing_profile_name(account: &AccountRole) -> Result<Option<String>> {
-    let creds_path = credentials_file_path()?;
-
-    if !creds_path.exists() {
-        return Ok(None);
-    }
-
-    let content = fs::read_to_string(&creds_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read credentials file: {}", e)))?;
-
-    let mut current_profile: Option<String> = None;
-    let mut found_account_id = false;
-    let mut found_role_name = false;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Check if previous profile matched
-            if current_profile.is_some() && found_account_id && found_role_name {
-                return Ok(current_profile);
-            }
-
-            // Start new profile
-            current_profile = Some(trimmed[1..trimmed.len() - 1].to_string());
-            found_account_id = false;
-            found_role_name = false;
-        } else if current_profile.is_some() {
-            // Check for metadata comments
-            if trimmed.starts_with('#') {
-                if trimmed.contains(&format!("Account: {}", account.account_id)) {
-                    found_account_id = true;
-                }
-                if trimmed.contains(&format!("Role: {}", account.role_name)) {
-                    found_role_name = true;
-                }
-            }
-        }
-    }
-
-    // Check last profile
-    if current_profile.is_some() && found_account_id && found_role_name {
-        return Ok(current_profile);
-    }
+    find_credentials_profile(&account.account_id, &account.role_name)
+}
 
-    Ok(None)
+/// Every profile with credentials for an account/role, e.g. both `prod-admin` and
+/// `default`; the first is the one `get_existing_profile_name` returns
+pub fn get_existing_profile_names(account: &AccountRole) -> Result<Vec<String>> {
+    find_credentials_profiles(&account.account_id, &account.role_name)
 }
 
 /// Rename a profile in AWS credentials and config files
@@ -2005,61 +1733,98 @@ pub fn rename_profile(old_name: &str, new_name: &str) -> Result<()> {
     // Rename in credentials file
     let creds_path = credentials_file_path()?;
     if creds_path.exists() {
-        let content = fs::read_to_string(&creds_path).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to read credentials file: {}", e))
-        })?;
-        let new_content = rename_ini_section(&content, old_name, new_name);
-        fs::write(&creds_path, new_content).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to write credentials file: {}", e))
+        edit_document(&creds_path, |doc| {
+            rename_sections(doc, old_name, new_name);
+            Ok(())
         })?;
     }
 
     // Rename in config file
     let config_path = config_file_path()?;
     if config_path.exists() {
-        let content = fs::read_to_string(&config_path)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-        let old_section = if old_name == "default" {
-            old_name.to_string()
-        } else {
-            format!("profile {}", old_name)
-        };
-
-        let new_section = if new_name == "default" {
-            new_name.to_string()
-        } else {
-            format!("profile {}", new_name)
-        };
-
-        let new_content = rename_ini_section(&content, &old_section, &new_section);
-        fs::write(&config_path, new_content)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
+        edit_document(&config_path, |doc| {
+            rename_sections(
+                doc,
+                &profile_section_name(old_name),
+                &profile_section_name(new_name),
+            );
+            Ok(())
+        })?;
     }
 
     Ok(())
 }
 
-/// Rename a section in an INI-style file
-fn rename_ini_section(content: &str, old_name: &str, new_name: &str) -> String {
-    let mut result = String::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
+/// Give every section named `old_section` the name `new_section`
+fn rename_sections(doc: &mut IniDocument, old_section: &str, new_section: &str) {
+    let headers: Vec<usize> = doc
+        .sections()
+        .filter(|s| s.name() == old_section)
+        .map(|s| s.line())
+        .collect();
+    for header in headers {
+        doc.rename_section(header, new_section);
+    }
+}
 
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            let section = &trimmed[1..trimmed.len() - 1];
-            if section == old_name {
-                result.push_str(&format!("[{}]\n", new_name));
-                continue;
+/// Change region and output of an awsom-managed profile, renaming it if `new_name` differs
+///
+/// Only the `[profile x]` section is rewritten; a rename carries the credentials section
+/// along under the new name but leaves the keys in it as they are. Each file is checked
+/// and changed under one lock, and the credentials file stays locked until the config file
+/// is written, so another process can't take the new name in between.
+pub fn update_profile_settings(
+    profile_name: &str,
+    new_name: &str,
+    region: &str,
+    output: &str,
+) -> Result<()> {
+    let renamed = new_name != profile_name;
+    let taken = || {
+        Err(SsoError::ProfileConflict(format!(
+            "Profile '{}' already exists",
+            new_name
+        )))
+    };
+    let update_config = || {
+        edit_document(&config_file_path()?, |doc| {
+            let section_name = profile_section_name(profile_name);
+            if !doc
+                .sections()
+                .any(|s| s.name() == section_name && is_awsom_managed(doc, &s))
+            {
+                return Err(SsoError::ConfigError(format!(
+                    "Profile '{}' is not managed by awsom. Run 'awsom import {}' to manage it.",
+                    profile_name, profile_name
+                )));
+            }
+            if renamed {
+                let new_section = profile_section_name(new_name);
+                if doc.section(&new_section).is_some() {
+                    return taken();
+                }
+                rename_sections(doc, &section_name, &new_section);
             }
-        }
 
-        result.push_str(line);
-        result.push('\n');
-    }
+            let header = awsom_section(doc, &profile_section_name(new_name));
+            doc.set(header, "region", region);
+            doc.set(header, "output", output);
+            Ok(())
+        })
+    };
 
-    cleanup_empty_lines(&result)
+    let creds_path = credentials_file_path()?;
+    if !renamed || !creds_path.exists() {
+        return update_config();
+    }
+    edit_document(&creds_path, |doc| {
+        if doc.section(new_name).is_some() {
+            return taken();
+        }
+        update_config()?;
+        rename_sections(doc, profile_name, new_name);
+        Ok(())
+    })
 }
 
 /// Invalidate a profile's credentials without deleting the profile structure
@@ -2071,233 +1836,388 @@ pub fn invalidate_profile(profile_name: &str) -> Result<()> {
         return Ok(()); // Nothing to invalidate
     }
 
-    let content = fs::read_to_string(&creds_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read credentials file: {}", e)))?;
-
-    // Replace credentials with dummy values and mark as invalid
-    let dummy_key = "INVALID_KEY";
-    let dummy_secret = "INVALID_SECRET";
-    let dummy_token = "INVALID_TOKEN";
-
-    let metadata = Some(vec![
-        format!("# Valid: false"),
-        format!("# Invalidated: {}", Utc::now().to_rfc3339()),
-    ]);
-
-    let new_content = update_ini_section_with_comments(
-        &content,
-        profile_name,
-        &[
-            ("aws_access_key_id", dummy_key),
-            ("aws_secret_access_key", dummy_secret),
-            ("aws_session_token", dummy_token),
-        ],
-        metadata.as_deref(),
-    );
-
-    fs::write(&creds_path, new_content)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write credentials file: {}", e)))?;
+    edit_document(&creds_path, |doc| {
+        refuse_static_credentials(doc, profile_name)?;
+        let header = credentials_section(doc, profile_name);
+        doc.remove_comments(header, is_metadata_comment);
+        doc.insert_comments(
+            header,
+            &[
+                "# Valid: false".to_string(),
+                format!("# Invalidated: {}", Utc::now().to_rfc3339()),
+            ],
+        );
 
-    Ok(())
+        // Replace credentials with dummy values and mark as invalid
+        doc.set(header, "aws_access_key_id", "INVALID_KEY");
+        doc.set(header, "aws_secret_access_key", "INVALID_SECRET");
+        doc.set(header, "aws_session_token", "INVALID_TOKEN");
+        Ok(())
+    })
 }
 
 /// Delete a profile from AWS credentials and config files
 /// NOTE: Consider using invalidate_profile() instead to preserve profile names
 pub fn delete_profile(profile_name: &str) -> Result<()> {
-    // Delete from credentials file
-    let creds_path = credentials_file_path()?;
-    if creds_path.exists() {
-        let content = fs::read_to_string(&creds_path).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to read credentials file: {}", e))
-        })?;
-        let new_content = delete_ini_section(&content, profile_name);
-        fs::write(&creds_path, new_content).map_err(|e| {
-            SsoError::ConfigError(format!("Failed to write credentials file: {}", e))
-        })?;
-    }
+    delete_profile_credentials(profile_name)?;
 
     // Delete from config file
     let config_path = config_file_path()?;
     if config_path.exists() {
-        let content = fs::read_to_string(&config_path)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
+        edit_document(&config_path, |doc| {
+            remove_sections(doc, &profile_section_name(profile_name));
+            Ok(())
+        })?;
+    }
 
-        let section_name = if profile_name == "default" {
-            profile_name.to_string()
-        } else {
-            format!("profile {}", profile_name)
-        };
+    Ok(())
+}
 
-        let new_content = delete_ini_section(&content, &section_name);
-        fs::write(&config_path, new_content)
-            .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
+/// Delete a profile's credentials, leaving its section in ~/.aws/config
+pub fn delete_profile_credentials(profile_name: &str) -> Result<()> {
+    let creds_path = credentials_file_path()?;
+    if creds_path.exists() {
+        edit_document(&creds_path, |doc| {
+            remove_sections(doc, profile_name);
+            Ok(())
+        })?;
     }
-
     Ok(())
 }
 
-/// Delete a section from an INI-style file
-fn delete_ini_section(content: &str, section_name: &str) -> String {
-    let mut result = String::new();
-    let mut in_target_section = false;
-    let mut skip_blank_line = false;
+fn remove_sections(doc: &mut IniDocument, section_name: &str) {
+    while let Some(header) = doc.section(section_name).map(|s| s.line()) {
+        doc.remove_section(header);
+    }
+}
+
+/// Get all profiles with their status
+pub fn list_profile_statuses() -> Result<Vec<ProfileStatus>> {
+    let doc = load_document(&credentials_file_path()?)?;
 
-    for line in content.lines() {
-        let trimmed = line.trim();
+    Ok(doc
+        .sections()
+        .map(|section| {
+            let mut account_id = None;
+            let mut role_name = None;
+            let mut expiration = None;
 
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            let section = &trimmed[1..trimmed.len() - 1];
-            if section == section_name {
-                in_target_section = true;
-                skip_blank_line = true;
-                continue;
-            } else {
-                in_target_section = false;
-                skip_blank_line = false;
+            // Parse metadata comments
+            for comment in section.comments() {
+                if let Some(rest) = comment.strip_prefix("# Account:") {
+                    account_id = Some(rest.trim().to_string());
+                } else if let Some(rest) = comment.strip_prefix("# Role:") {
+                    role_name = Some(rest.trim().to_string());
+                } else if let Some(rest) = comment
+                    .strip_prefix("# Valid:")
+                    // Backward compatibility: parse old format
+                    .or_else(|| comment.strip_prefix("# Expiration:"))
+                {
+                    // "false" marks an invalidated profile, which has no expiration
+                    expiration = DateTime::parse_from_rfc3339(rest.trim())
+                        .ok()
+                        .map(|dt| dt.with_timezone(&Utc));
+                }
             }
-        }
 
-        if !in_target_section {
-            // Skip one blank line after deleted section
-            if skip_blank_line && trimmed.is_empty() {
-                skip_blank_line = false;
-                continue;
+            ProfileStatus {
+                profile_name: section.name().to_string(),
+                account_id,
+                role_name,
+                has_credentials: has_credentials(&section),
+                expiration,
             }
-            result.push_str(line);
-            result.push('\n');
-        }
-    }
+        })
+        .collect())
+}
+
+/// State of the user/awsom markers in ~/.aws/config
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkerState {
+    Valid,
+    /// awsom hasn't written to the file yet; markers are added on the next write
+    Missing,
+    /// Markers are duplicated, partially missing or out of order
+    Broken(String),
+}
 
-    cleanup_empty_lines(&result)
+/// Structural problems in ~/.aws/config and ~/.aws/credentials, for `awsom doctor`
+#[derive(Debug, Clone)]
+pub struct ConfigDiagnosis {
+    pub config_exists: bool,
+    pub markers: MarkerState,
+    /// (profile, sso_session) pairs whose sso-session section doesn't exist
+    pub broken_session_refs: Vec<(String, String)>,
+    /// awsom-written credentials sections with no matching profile in ~/.aws/config
+    pub orphaned_credentials: Vec<String>,
 }
 
-/// Clean up empty lines in INI files (public for import command):
-/// - Remove leading empty lines
-/// - Ensure exactly one blank line between sections
-/// - Remove trailing empty lines
-pub fn cleanup_empty_lines(content: &str) -> String {
-    let lines: Vec<&str> = content.lines().collect();
-    let mut result = String::new();
-    let mut previous_blank = false;
-    let mut at_start = true;
+/// Inspect the AWS config and credentials files without modifying them
+pub fn diagnose() -> Result<ConfigDiagnosis> {
+    let config_path = config_file_path()?;
+    let config = load_document(&config_path)?;
+    let credentials = load_document(&credentials_file_path()?)?;
+
+    let broken_session_refs = config
+        .sections()
+        .filter_map(|section| {
+            let profile = profile_name_of(section.name())?;
+            let session = section.get("sso_session")?;
+            let exists = config
+                .section(&format!("{}{}", SSO_SESSION_PREFIX, session))
+                .is_some();
+            (!exists).then(|| (profile.to_string(), session.to_string()))
+        })
+        .collect();
+
+    let orphaned_credentials = credentials
+        .sections()
+        .filter(|section| section.comments().any(is_metadata_comment))
+        .filter(|section| {
+            config
+                .section(&profile_section_name(section.name()))
+                .is_none()
+        })
+        .map(|section| section.name().to_string())
+        .collect();
+
+    let diagnosis = ConfigDiagnosis {
+        config_exists: config_path.exists(),
+        markers: marker_state(&config),
+        broken_session_refs,
+        orphaned_credentials,
+    };
+    Ok(diagnosis)
+}
 
-    for line in lines.iter() {
-        let trimmed = line.trim();
-        let is_blank = trimmed.is_empty();
+fn marker_state(doc: &IniDocument) -> MarkerState {
+    let lines_matching = |marker: &str| -> Vec<usize> {
+        (0..doc.len())
+            .filter(|&i| doc.line(i) == Some(marker))
+            .collect()
+    };
+    let user = lines_matching(USER_MANAGED_MARKER);
+    let awsom = lines_matching(AWSOM_MANAGED_MARKER);
+
+    match (user.as_slice(), awsom.as_slice()) {
+        ([], []) => MarkerState::Missing,
+        ([user], [awsom]) if user < awsom => MarkerState::Valid,
+        ([_], [_]) => MarkerState::Broken(
+            "the awsom-managed marker comes before the user-managed marker".to_string(),
+        ),
+        _ => MarkerState::Broken(format!(
+            "expected one of each marker, found {} user-managed and {} awsom-managed",
+            user.len(),
+            awsom.len()
+        )),
+    }
+}
 
-        // Skip leading blank lines
-        if at_start && is_blank {
-            continue;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // If we encounter non-blank content, we're no longer at start
-        if !is_blank {
-            at_start = false;
-        }
+    #[test]
+    fn test_aws_file_path_from_environment() {
+        let home = Path::new("/home/me");
+        assert_eq!(
+            resolve_aws_file_path(None, Some(home), "config"),
+            Some(PathBuf::from("/home/me/.aws/config"))
+        );
+        assert_eq!(
+            resolve_aws_file_path(Some("/workspace/aws/config"), None, "config"),
+            Some(PathBuf::from("/workspace/aws/config"))
+        );
+        assert_eq!(
+            resolve_aws_file_path(Some("~/ci/credentials"), Some(home), "credentials"),
+            Some(PathBuf::from("/home/me/ci/credentials"))
+        );
+        assert_eq!(resolve_aws_file_path(None, None, "config"), None);
+    }
+
+    #[test]
+    fn test_awsom_sections_sorted_below_markers() {
+        let mut doc = IniDocument::parse("# mine\n[profile dev]\nregion = us-east-1 # keep\n");
+        let header = awsom_section(&mut doc, "profile zeta");
+        doc.set(header, "region", "eu-west-1");
+        awsom_section(&mut doc, "sso-session corp");
+        awsom_section(&mut doc, "profile alpha");
+
+        let names: Vec<_> = doc.sections().map(|s| s.name()).collect();
+        assert_eq!(
+            names,
+            [
+                "profile dev",
+                "sso-session corp",
+                "profile alpha",
+                "profile zeta"
+            ]
+        );
+        assert!(in_user_region(&doc, "profile dev"));
+        assert!(!in_user_region(&doc, "profile zeta"));
+        assert!(doc.to_string().starts_with(&format!(
+            "# mine\n\n{}\n{}\n\n[profile dev]\nregion = us-east-1 # keep\n\n{}\n",
+            USER_MANAGED_MARKER, USER_MANAGED_COMMENT, AWSOM_MANAGED_MARKER
+        )));
+    }
 
-        // Skip consecutive blank lines (keep only one)
-        if is_blank && previous_blank {
-            continue;
-        }
+    #[test]
+    fn test_suffixed_name_skips_user_managed_profiles() {
+        let mut doc = IniDocument::parse("[profile dev]\n[profile dev-awsom]\n");
+        awsom_section(&mut doc, "profile dev-awsom-2");
+        assert_eq!(free_suffixed_name(&doc, "dev"), "dev-awsom-2");
+        assert_eq!(free_suffixed_name(&doc, "prod"), "prod-awsom");
+    }
 
-        result.push_str(line);
-        result.push('\n');
-        previous_blank = is_blank;
+    #[test]
+    fn test_static_credentials_are_tracked_not_overwritten() {
+        let mut doc = IniDocument::parse(
+            "[ci]\naws_access_key_id = AKIA1\naws_secret_access_key = s\n\n\
+             [sso]\n# Account: 1\naws_access_key_id = ASIA1\naws_secret_access_key = s\n\
+             aws_session_token = t\n",
+        );
+        assert!(is_untracked_static(&doc.section("ci").unwrap()));
+        assert!(!is_untracked_static(&doc.section("sso").unwrap()));
+        assert!(refuse_static_credentials(&doc, "ci").is_ok());
+
+        let header = doc.section("ci").unwrap().line();
+        doc.insert_comments(header, &[STATIC_CREDENTIALS_COMMENT.to_string()]);
+        assert!(!is_untracked_static(&doc.section("ci").unwrap()));
+        assert!(refuse_static_credentials(&doc, "ci").is_err());
+        assert!(refuse_static_credentials(&doc, "sso").is_ok());
+    }
+
+    #[test]
+    fn test_credentials_reach_every_bound_profile() {
+        let mut doc = IniDocument::parse(
+            "[default]\n# Account: 1\n# Role: Admin\naws_access_key_id = OLD\n\
+             aws_secret_access_key = s\naws_session_token = t\n\n\
+             [other]\n# Account: 1\n# Role: ReadOnly\naws_access_key_id = OTHER\n\
+             aws_secret_access_key = s\naws_session_token = t\n\n\
+             [stopped]\n# Valid: false\naws_access_key_id = INVALID_KEY\n\
+             aws_secret_access_key = s\naws_session_token = t\n",
+        );
+        let role = AccountRole {
+            account_id: "1".to_string(),
+            account_name: "prod".to_string(),
+            role_name: "Admin".to_string(),
+        };
+        let creds = RoleCredentials {
+            access_key_id: "NEW".to_string(),
+            secret_access_key: "s2".to_string(),
+            session_token: "t2".to_string(),
+            expiration: Utc::now(),
+        };
+        let metadata = vec!["# Account: 1".to_string(), "# Role: Admin".to_string()];
+        set_session_credentials(&mut doc, "prod-admin", &creds, &metadata, Some(&role));
+
+        let key = |name: &str| doc.section(name).unwrap().get("aws_access_key_id").unwrap();
+        assert_eq!(key("prod-admin"), "NEW");
+        assert_eq!(key("default"), "NEW");
+        assert_eq!(key("other"), "OTHER");
+        assert_eq!(key("stopped"), "INVALID_KEY");
+        let bound: Vec<_> = doc
+            .sections()
+            .filter(|s| has_role_metadata(s, "1", "Admin"))
+            .map(|s| s.name().to_string())
+            .collect();
+        assert_eq!(bound, ["default", "prod-admin"]);
+    }
+
+    #[test]
+    fn test_sso_session_registration_scopes() {
+        let doc = IniDocument::parse(
+            "[sso-session corp]\nsso_start_url = https://x\nsso_region = eu-west-1\n\
+             sso_registration_scopes = sso:account:access, codewhisperer:completions,\n\n\
+             [sso-session plain]\nsso_start_url = https://y\nsso_region = eu-west-1\n",
+        );
+        let sessions: Vec<_> = doc
+            .sections()
+            .filter_map(|s| SsoSession::from_section(&s))
+            .collect();
+        assert_eq!(
+            sessions[0].registration_scopes(),
+            ["sso:account:access", "codewhisperer:completions"]
+        );
+        assert_eq!(
+            sessions[1].registration_scopes(),
+            [DEFAULT_REGISTRATION_SCOPES]
+        );
     }
 
-    // Remove trailing blank lines
-    while result.ends_with("\n\n") {
-        result.pop();
+    #[test]
+    fn test_credential_process_round_trip() {
+        let command = credential_process_command("my corp", "123456789012", "Admin");
+        assert_eq!(
+            command,
+            "awsom credential-process --session-name \"my corp\" --account-id 123456789012 \
+             --role-name Admin"
+        );
+
+        let doc = IniDocument::parse(&format!(
+            "[profile a]\ncredential_process = {}\n\n\
+             [profile b]\ncredential_process = aws-vault exec b --json\n",
+            command
+        ));
+        assert_eq!(
+            profile_role(&doc.section("profile a").unwrap()),
+            Some(ProfileRole {
+                session: "my corp".to_string(),
+                account_id: "123456789012".to_string(),
+                role_name: "Admin".to_string(),
+            })
+        );
+        assert_eq!(profile_role(&doc.section("profile b").unwrap()), None);
     }
 
-    result
-}
+    #[test]
+    fn test_marker_state() {
+        let mut doc = IniDocument::parse("[default]\nregion = us-east-1\n");
+        assert_eq!(marker_state(&doc), MarkerState::Missing);
 
-/// Get all profiles with their status
-pub fn list_profile_statuses() -> Result<Vec<ProfileStatus>> {
-    let creds_path = credentials_file_path()?;
+        ensure_markers(&mut doc);
+        assert_eq!(marker_state(&doc), MarkerState::Valid);
 
-    if !creds_path.exists() {
-        return Ok(Vec::new());
-    }
-
-    let content = fs::read_to_string(&creds_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read credentials file: {}", e)))?;
-
-    let mut profiles = Vec::new();
-    let mut current_profile: Option<String> = None;
-    let mut profile_data: HashMap<String, String> = HashMap::new();
-    let mut account_id: Option<String> = None;
-    let mut role_name: Option<String> = None;
-    let mut expiration: Option<DateTime<Utc>> = None;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Save previous profile
-            if let Some(profile) = current_profile.take() {
-                let has_creds = profile_data.contains_key("aws_access_key_id")
-                    && profile_data.contains_key("aws_secret_access_key")
-                    && profile_data.contains_key("aws_session_token");
-
-                profiles.push(ProfileStatus {
-                    profile_name: profile,
-                    account_id: account_id.take(),
-                    role_name: role_name.take(),
-                    has_credentials: has_creds,
-                    expiration: expiration.take(),
-                });
-                profile_data.clear();
-            }
+        let swapped = format!("{}\n\n{}\n", AWSOM_MANAGED_MARKER, USER_MANAGED_MARKER);
+        assert!(matches!(
+            marker_state(&IniDocument::parse(&swapped)),
+            MarkerState::Broken(_)
+        ));
 
-            current_profile = Some(trimmed[1..trimmed.len() - 1].to_string());
-        } else if trimmed.starts_with('#') {
-            // Parse metadata comments
-            if let Some(rest) = trimmed.strip_prefix("# Account:") {
-                account_id = Some(rest.trim().to_string());
-            } else if let Some(rest) = trimmed.strip_prefix("# Role:") {
-                role_name = Some(rest.trim().to_string());
-            } else if let Some(rest) = trimmed.strip_prefix("# Valid:") {
-                let value = rest.trim();
-                if value == "false" {
-                    // Profile is invalidated, no expiration
-                    expiration = None;
-                } else {
-                    // Parse ISO 8601 timestamp (expiration date)
-                    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
-                        expiration = Some(dt.with_timezone(&Utc));
-                    }
-                }
-            } else if let Some(rest) = trimmed.strip_prefix("# Expiration:") {
-                // Backward compatibility: parse old format
-                if let Ok(dt) = DateTime::parse_from_rfc3339(rest.trim()) {
-                    expiration = Some(dt.with_timezone(&Utc));
-                }
-            }
-        } else if !trimmed.is_empty() {
-            if let Some(eq_pos) = trimmed.find('=') {
-                let key = trimmed[..eq_pos].trim().to_string();
-                let value = trimmed[eq_pos + 1..].trim().to_string();
-                profile_data.insert(key, value);
-            }
-        }
+        let duplicated = format!("{}\n{}\n", doc, AWSOM_MANAGED_MARKER);
+        assert!(matches!(
+            marker_state(&IniDocument::parse(&duplicated)),
+            MarkerState::Broken(_)
+        ));
     }
 
-    // Save last profile
-    if let Some(profile) = current_profile {
-        let has_creds = profile_data.contains_key("aws_access_key_id")
-            && profile_data.contains_key("aws_secret_access_key")
-            && profile_data.contains_key("aws_session_token");
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_symlink_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
 
-        profiles.push(ProfileStatus {
-            profile_name: profile,
-            account_id,
-            role_name,
-            has_credentials: has_creds,
-            expiration,
-        });
-    }
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("credentials");
+        let link = dir.path().join("link");
+        fs::write(&target, "old").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let _lock = lock_file(&link).unwrap();
+        write_atomic(&link, "new").unwrap();
 
-    Ok(profiles)
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // A file written for the first time isn't left to the umask
+        let new = dir.path().join("config");
+        write_atomic(&new, "new").unwrap();
+        let mode = fs::metadata(&new).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
 }
@@ -0,0 +1,61 @@
+// apps command - the non-account applications on the access portal
+use crate::applications::{self, Application};
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::cli::AppsCommands;
+use crate::error::{Result, SsoError};
+use crate::models::SsoInstance;
+use crate::output::ApplicationList;
+
+pub async fn execute(command: AppsCommands) -> Result<()> {
+    match command {
+        AppsCommands::List {
+            session_name,
+            format,
+        } => {
+            let (_, applications) = list(session_name.as_deref()).await?;
+            format.print(&ApplicationList(applications))
+        }
+        AppsCommands::Open {
+            name,
+            session_name,
+            url_only,
+        } => {
+            let (instance, applications) = list(session_name.as_deref()).await?;
+            let app = applications::find(&applications, &name).ok_or_else(|| {
+                SsoError::ConfigError(format!(
+                    "No application named '{}'; see `awsom apps list`",
+                    name
+                ))
+            })?;
+            let url = applications::portal_url(&instance.start_url, app);
+            if url_only {
+                println!("{}", url);
+                return Ok(());
+            }
+            crate::console::open_url(&url)?;
+            eprintln!("✓ Opening {} in the browser", app.name);
+            Ok(())
+        }
+    }
+}
+
+/// The session's instance and its assigned applications
+async fn list(session_name: Option<&str>) -> Result<(SsoInstance, Vec<Application>)> {
+    let (start_url, region) = aws_config::resolve_sso_session(session_name, None, None)?;
+    let instance = SsoInstance {
+        session_name: session_name.map(str::to_string),
+        start_url,
+        region,
+    };
+
+    let token = AuthManager::new()?
+        .get_cached_token(&instance)?
+        .ok_or(SsoError::NoSessionFound)?;
+    if token.is_expired() {
+        return Err(SsoError::AuthExpired);
+    }
+
+    let applications = applications::list(&instance.region, &token.access_token).await?;
+    Ok((instance, applications))
+}
@@ -0,0 +1,280 @@
+// assume command - export role credentials into the calling shell
+//
+// A child process can't change its parent's environment, so `assume` only prints the
+// commands; the shell function from `awsom hook` evaluates them in the current shell.
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::cli::commands::exec::reauthenticate;
+use crate::cli::HookShell;
+use crate::config;
+use crate::credentials::CredentialManager;
+use crate::env;
+use crate::error::{Result, SsoError};
+use crate::models::{AccountRole, RoleCredentials, SsoInstance, SsoToken};
+use chrono::Duration;
+use std::io::{self, IsTerminal};
+
+/// Cached credentials expiring sooner than this are replaced, so the shell gets a usable set
+const MIN_VALIDITY_MINUTES: i64 = 15;
+
+/// Variables `assume` sets, and `assume --unset` clears
+pub const VARIABLES: &[&str] = &[
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+    "AWS_SESSION_TOKEN",
+    "AWS_CREDENTIAL_EXPIRATION",
+    "AWS_REGION",
+    "AWS_DEFAULT_REGION",
+    "AWS_PROFILE",
+];
+
+/// What to assume: a profile from ~/.aws/config, or an account (name or ID) and role
+pub struct Target {
+    /// Exported as AWS_PROFILE
+    pub profile: Option<String>,
+    /// Account name or ID; None picks the one account that has the role
+    pub account: Option<String>,
+    pub role_name: String,
+    pub session_name: Option<String>,
+    /// Region for AWS_REGION instead of the SSO region
+    pub region: Option<String>,
+}
+
+/// Credentials of an assumed role and what goes with them into the environment
+pub struct Assumed {
+    pub role: AccountRole,
+    pub creds: RoleCredentials,
+    pub region: String,
+    pub profile: Option<String>,
+}
+
+impl Assumed {
+    /// Environment variables to set, except AWS_PROFILE
+    pub fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("AWS_ACCESS_KEY_ID", self.creds.access_key_id.clone()),
+            (
+                "AWS_SECRET_ACCESS_KEY",
+                self.creds.secret_access_key.clone(),
+            ),
+            ("AWS_SESSION_TOKEN", self.creds.session_token.clone()),
+            (
+                "AWS_CREDENTIAL_EXPIRATION",
+                self.creds.expiration.to_rfc3339(),
+            ),
+            ("AWS_REGION", self.region.clone()),
+            ("AWS_DEFAULT_REGION", self.region.clone()),
+        ]
+    }
+
+    /// Shell commands that set the variables, and AWS_PROFILE or unset it
+    pub fn export_commands(&self, shell: HookShell) -> Vec<String> {
+        let mut commands: Vec<String> = self
+            .variables()
+            .iter()
+            .map(|(name, value)| export_command(shell, name, value))
+            .collect();
+        // A profile left over from before would point prompts and tools at the wrong role
+        commands.push(match &self.profile {
+            Some(profile) => export_command(shell, "AWS_PROFILE", profile),
+            None => unset_command(shell, "AWS_PROFILE"),
+        });
+        commands
+    }
+}
+
+pub async fn execute(
+    target: Option<String>,
+    session_name: Option<String>,
+    shell: HookShell,
+    unset: bool,
+    headless: bool,
+) -> Result<()> {
+    if unset {
+        super::env::unset(shell);
+        return Ok(());
+    }
+
+    let target = resolve_target(
+        target.as_deref().unwrap_or_default(),
+        session_name.as_deref(),
+    )?;
+    let assumed = assume(&target, None, None, headless).await?;
+    for command in assumed.export_commands(shell) {
+        println!("{}", command);
+    }
+
+    eprintln!(
+        "✓ Assumed {} in {} ({}), expires in {}",
+        assumed.role.role_name,
+        assumed.role.account_name,
+        assumed.role.account_id,
+        assumed.creds.expiration_display()
+    );
+    if io::stdout().is_terminal() {
+        eprintln!(
+            "⚠ The commands above were only printed. Add 'eval \"$(awsom hook {})\"' to your \
+             shell rc file so that 'assume' sets them in the current shell.",
+            shell.name()
+        );
+    }
+    Ok(())
+}
+
+/// Fetch credentials for `target`, logging in first if the SSO session has expired
+pub async fn assume(
+    target: &Target,
+    start_url: Option<&str>,
+    region: Option<&str>,
+    headless: bool,
+) -> Result<Assumed> {
+    let (start_url, sso_region) =
+        aws_config::resolve_sso_session(target.session_name.as_deref(), start_url, region)?;
+    let instance = SsoInstance {
+        start_url,
+        region: sso_region,
+        session_name: target.session_name.clone(),
+    };
+
+    let is_headless = headless || env::is_headless_environment();
+    let auth = AuthManager::new()?;
+    let token = match auth.get_cached_token(&instance)? {
+        Some(token) if !token.is_expired() => token,
+        _ => reauthenticate(&auth, &instance, is_headless).await?,
+    };
+
+    // A revoked token only shows up when it's used, so log in again and retry once
+    let (role, creds) = match fetch(&instance, &token, target).await {
+        Err(SsoError::AuthExpired) => {
+            let token = reauthenticate(&auth, &instance, is_headless).await?;
+            fetch(&instance, &token, target).await?
+        }
+        result => result?,
+    };
+
+    Ok(Assumed {
+        role,
+        creds,
+        region: target.region.clone().unwrap_or(instance.region),
+        profile: target.profile.clone(),
+    })
+}
+
+/// `account/role` names an account (name or ID) and role; anything else is a profile
+pub fn resolve_target(target: &str, session_name: Option<&str>) -> Result<Target> {
+    if let Some(details) = aws_config::get_profile_details(target)? {
+        let (Some(account_id), Some(role_name)) = (details.sso_account_id, details.sso_role_name)
+        else {
+            return Err(SsoError::ConfigError(format!(
+                "Profile '{}' has no sso_account_id/sso_role_name to assume",
+                target
+            )));
+        };
+        return Ok(Target {
+            profile: Some(target.to_string()),
+            account: Some(account_id),
+            role_name,
+            session_name: session_name.map(str::to_string).or(details.sso_session),
+            region: details.region,
+        });
+    }
+
+    match target.rsplit_once('/') {
+        Some((account, role_name)) if !account.is_empty() && !role_name.is_empty() => Ok(Target {
+            profile: None,
+            account: Some(account.to_string()),
+            role_name: role_name.to_string(),
+            session_name: session_name.map(str::to_string),
+            region: None,
+        }),
+        _ => Err(SsoError::InvalidConfig(format!(
+            "'{}' is not a profile in ~/.aws/config; use <account>/<role> to assume a role \
+             without a profile",
+            target
+        ))),
+    }
+}
+
+/// Find the role the token can use, then its credentials
+async fn fetch(
+    instance: &SsoInstance,
+    token: &SsoToken,
+    target: &Target,
+) -> Result<(AccountRole, RoleCredentials)> {
+    let manager = CredentialManager::new()?;
+    let roles = manager.list_roles(instance, &token.access_token).await?;
+    let account = target
+        .account
+        .as_ref()
+        .map(|a| config::resolve_account_alias(a).unwrap_or_else(|| a.clone()));
+    let mut matching = roles.into_iter().filter(|r| {
+        r.role_name == target.role_name
+            && account
+                .as_ref()
+                .map_or(true, |a| &r.account_id == a || &r.account_name == a)
+    });
+    let role = match (matching.next(), matching.next()) {
+        (Some(role), None) => role,
+        (None, _) => return Err(SsoError::AccountRoleNotFound),
+        (Some(_), Some(_)) => {
+            return Err(SsoError::InvalidConfig(format!(
+                "Role '{}' exists in several accounts; pick one with --account-id or \
+                 --account-name",
+                target.role_name
+            )))
+        }
+    };
+
+    let creds = manager
+        .get_credentials_valid_for(
+            instance,
+            token,
+            &role,
+            Duration::minutes(MIN_VALIDITY_MINUTES),
+        )
+        .await?;
+    Ok((role, creds))
+}
+
+pub fn export_command(shell: HookShell, name: &str, value: &str) -> String {
+    match shell {
+        HookShell::Bash | HookShell::Zsh => format!("export {}={}", name, quote(shell, value)),
+        HookShell::Fish => format!("set -gx {} {}", name, quote(shell, value)),
+    }
+}
+
+pub fn unset_command(shell: HookShell, name: &str) -> String {
+    match shell {
+        HookShell::Bash | HookShell::Zsh => format!("unset {}", name),
+        HookShell::Fish => format!("set -e {}", name),
+    }
+}
+
+/// Single-quote a value so the shell takes it literally
+pub fn quote(shell: HookShell, value: &str) -> String {
+    match shell {
+        HookShell::Bash | HookShell::Zsh => format!("'{}'", value.replace('\'', r"'\''")),
+        HookShell::Fish => format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_command_quoting() {
+        assert_eq!(
+            export_command(HookShell::Bash, "AWS_PROFILE", "it's"),
+            r"export AWS_PROFILE='it'\''s'"
+        );
+        assert_eq!(
+            export_command(HookShell::Fish, "AWS_PROFILE", r"it's\"),
+            r"set -gx AWS_PROFILE 'it\'s\\'"
+        );
+        assert_eq!(
+            unset_command(HookShell::Fish, "AWS_PROFILE"),
+            "set -e AWS_PROFILE"
+        );
+    }
+}
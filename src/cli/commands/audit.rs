@@ -0,0 +1,26 @@
+// Audit log CLI commands
+use crate::audit;
+use crate::cli::AuditCommands;
+use crate::config::Config;
+use crate::error::Result;
+use crate::output::AuditLog;
+
+pub fn execute(command: AuditCommands) -> Result<()> {
+    match command {
+        AuditCommands::Show { since, format } => {
+            let since = since.as_deref().map(audit::parse_since).transpose()?;
+            let events = audit::read_events(since)?;
+
+            // An empty log is most often because auditing was never turned on
+            if events.is_empty() && !format.is_machine_readable() && !Config::load()?.audit.enabled
+            {
+                eprintln!(
+                    "Audit logging is disabled. Enable it with `[audit] enabled = true` in {}",
+                    Config::path()?.display()
+                );
+            }
+
+            format.print(&AuditLog(events))
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Cache management CLI commands
+use crate::auth::TokenCache;
+use crate::cli::CacheCommands;
+use crate::config::{Config, TokenStore};
+use crate::credentials::{directory, CredentialCache};
+use crate::error::Result;
+use crate::output::{CacheEntry, CacheKind, CacheList, OutputFormat};
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::Path;
+
+pub fn execute(command: CacheCommands) -> Result<()> {
+    match command {
+        CacheCommands::List { format } => list(format),
+        CacheCommands::Clear {
+            tokens,
+            credentials,
+            accounts,
+            registrations,
+            all,
+        } => clear(
+            tokens || all,
+            credentials || all,
+            accounts || all,
+            registrations || all,
+        ),
+        CacheCommands::Path => {
+            println!("tokens       {}", TokenCache::new()?.cache_dir().display());
+            println!(
+                "credentials  {}",
+                CredentialCache::new()?.cache_dir().display()
+            );
+            println!("accounts     {}", directory::cache_dir().display());
+            Ok(())
+        }
+    }
+}
+
+fn list(format: OutputFormat) -> Result<()> {
+    let mut entries = Vec::new();
+
+    let token_cache = TokenCache::new()?;
+    for (key, token) in token_cache.list_tokens()? {
+        let path = token_cache.cache_dir().join(format!("{}.json", key));
+        entries.push(entry(
+            CacheKind::Tokens,
+            token.start_url.unwrap_or(key),
+            &path,
+            Some(token.expires_at),
+        ));
+    }
+
+    for (path, expiration) in CredentialCache::new()?.list_files()? {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        entries.push(entry(CacheKind::Credentials, name, &path, expiration));
+    }
+
+    let config = Config::load()?;
+    let ttl = Duration::minutes(config.cache.accounts_ttl_minutes);
+    for (path, cached) in directory::list()? {
+        entries.push(entry(
+            CacheKind::Accounts,
+            cached.start_url,
+            &path,
+            Some(cached.fetched_at + ttl),
+        ));
+    }
+
+    for (path, registration) in token_cache.list_registrations()? {
+        entries.push(entry(
+            CacheKind::Registrations,
+            registration.region,
+            &path,
+            registration.expires_at,
+        ));
+    }
+
+    format.print(&CacheList(entries))?;
+    if !format.is_machine_readable() && config.security.token_store == TokenStore::Keyring {
+        eprintln!("⚠ Tokens and credentials in the OS keychain are not listed");
+    }
+    Ok(())
+}
+
+fn entry(
+    kind: CacheKind,
+    name: String,
+    path: &Path,
+    expires_at: Option<DateTime<Utc>>,
+) -> CacheEntry {
+    CacheEntry {
+        kind,
+        name,
+        path: path.display().to_string(),
+        size_bytes: fs::metadata(path).map(|m| m.len()).unwrap_or_default(),
+        expires_at,
+    }
+}
+
+fn clear(tokens: bool, credentials: bool, accounts: bool, registrations: bool) -> Result<()> {
+    if tokens {
+        let cache = TokenCache::new()?;
+        let removed = cache.clear_all()?;
+        eprintln!(
+            "✓ Removed {} SSO token(s) from {}",
+            removed,
+            cache.cache_dir().display()
+        );
+    }
+    if credentials {
+        let cache = CredentialCache::new()?;
+        let removed = cache.clear_all()?;
+        eprintln!(
+            "✓ Removed {} cached credential file(s) from {}",
+            removed,
+            cache.cache_dir().display()
+        );
+    }
+    if accounts {
+        let removed = directory::clear()?;
+        eprintln!(
+            "✓ Removed {} cached account listing(s) from {}",
+            removed,
+            directory::cache_dir().display()
+        );
+    }
+    if registrations {
+        let cache = TokenCache::new()?;
+        let removed = cache.clear_registrations()?;
+        eprintln!(
+            "✓ Removed {} client registration(s) from {}",
+            removed,
+            cache.cache_dir().display()
+        );
+    }
+    Ok(())
+}
@@ -1,19 +1,77 @@
+use crate::aws_config;
 use crate::cli::{Cli, Shell};
+use crate::credentials::directory;
+use crate::error::{Result, SsoError};
 use clap::CommandFactory;
 use clap_complete::{generate, Shell as ClapShell};
-use std::io::{self, IsTerminal};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+pub fn execute(
+    shell: Option<Shell>,
+    show_install: bool,
+    install: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None => detect_shell()?,
+    };
+
+    if install {
+        return install_script(shell, dry_run);
+    }
 
-pub fn execute(shell: Shell, show_install: bool) {
     if show_install {
         // Just show installation instructions
         print_installation_instructions(&shell);
-        return;
+        return Ok(());
+    }
+
+    // Generate completions to stdout; a reader that stops early (`| head`) isn't an error
+    match io::stdout().write_all(&script(shell)) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+        result => result?,
+    }
+
+    // Only show hint when running interactively (not when being eval'd or piped)
+    // When stdout is captured (not a terminal), we're being piped/eval'd - don't show hints
+    if io::stdout().is_terminal() {
+        eprintln!();
+        eprintln!("# Completion script generated successfully!");
+        eprintln!("# To see installation instructions, run:");
+        eprintln!("#   awsom completions {} --show-install", name(shell));
     }
+    Ok(())
+}
 
-    // Generate the completion script
-    let mut cmd = Cli::command();
-    let bin_name = "awsom";
+fn name(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => "bash",
+        Shell::Zsh => "zsh",
+        Shell::Fish => "fish",
+        Shell::PowerShell => "powershell",
+        Shell::Elvish => "elvish",
+    }
+}
 
+/// Options whose values the bash, zsh and fish scripts complete with `awsom _complete`
+const VALUE_OPTIONS: &[(&str, &str)] = &[
+    ("session-name", "sessions"),
+    ("profile", "profiles"),
+    ("account-name", "accounts"),
+    ("account-id", "account-ids"),
+];
+
+/// `profile` subcommands whose one argument is an existing profile's name
+const PROFILE_SUBCOMMANDS: &[&str] = &["start", "show", "stop", "delete"];
+
+/// The completion script for `shell`
+///
+/// clap's scripts only complete subcommands and flags; for bash, zsh and fish the values
+/// in `VALUE_OPTIONS`, and the profile name of `PROFILE_SUBCOMMANDS`, are completed from
+/// `awsom _complete` when Tab is pressed.
+fn script(shell: Shell) -> Vec<u8> {
     let clap_shell = match shell {
         Shell::Bash => ClapShell::Bash,
         Shell::Zsh => ClapShell::Zsh,
@@ -21,28 +79,272 @@ pub fn execute(shell: Shell, show_install: bool) {
         Shell::PowerShell => ClapShell::PowerShell,
         Shell::Elvish => ClapShell::Elvish,
     };
+    let mut script = Vec::new();
+    generate(clap_shell, &mut Cli::command(), "awsom", &mut script);
+
+    let script = String::from_utf8_lossy(&script);
+    match shell {
+        Shell::Bash => bash_values(&script),
+        Shell::Zsh => zsh_values(&script),
+        Shell::Fish => fish_values(&script),
+        Shell::PowerShell | Shell::Elvish => script.into_owned(),
+    }
+    .into_bytes()
+}
 
-    // Generate completions to stdout
-    generate(clap_shell, &mut cmd, bin_name, &mut io::stdout());
+/// Route Tab through `_awsom_values`, which answers value positions and hands the rest
+/// to clap's `_awsom`
+fn bash_values(script: &str) -> String {
+    let options: String = VALUE_OPTIONS
+        .iter()
+        .map(|(option, kind)| format!("        --{}) kind={} ;;\n", option, kind))
+        .collect();
+    let function = format!(
+        r#"_awsom_values() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}" kind=
+    case "${{prev}}" in
+{options}    esac
+    if [[ -z "${{kind}}" && "${{COMP_CWORD}}" -eq 3 && "${{COMP_WORDS[1]}}" == profile && "${{cur}}" != -* ]]; then
+        case "${{COMP_WORDS[2]}}" in
+            {subcommands}) kind=profiles ;;
+        esac
+    fi
+    if [[ -n "${{kind}}" ]]; then
+        local IFS=$'\n'
+        COMPREPLY=( $(compgen -W "$(awsom _complete "${{kind}}" 2>/dev/null)" -- "${{cur}}") )
+        return 0
+    fi
+    _awsom "$@"
+}}
+
+"#,
+        options = options,
+        subcommands = PROFILE_SUBCOMMANDS.join("|"),
+    );
+    let script = script.replace("complete -F _awsom ", "complete -F _awsom_values ");
+    insert_before_last(&script, "if [[ \"${BASH_VERSINFO[0]}\"", &function)
+}
 
-    // Only show hint when running interactively (not when being eval'd or piped)
-    // When stdout is captured (not a terminal), we're being piped/eval'd - don't show hints
-    if io::stdout().is_terminal() {
-        let shell_name = match shell {
-            Shell::Bash => "bash",
-            Shell::Zsh => "zsh",
-            Shell::Fish => "fish",
-            Shell::PowerShell => "powershell",
-            Shell::Elvish => "elvish",
-        };
+/// Swap `_default` for `_awsom_values <kind>` in the value specs of `_arguments`
+fn zsh_values(script: &str) -> String {
+    let mut out = String::with_capacity(script.len());
+    for line in script.lines() {
+        let kind = VALUE_OPTIONS
+            .iter()
+            .find(|(option, _)| line.starts_with(&format!("'--{}=", option)))
+            .map(|(_, kind)| *kind)
+            .or_else(|| {
+                // `::` when the name is optional, as with `profile start --all-expired`
+                let positional = line.starts_with("':profile_name -- ")
+                    || line.starts_with("'::profile_name -- ");
+                positional.then_some("profiles")
+            });
+        match kind {
+            Some(kind) => {
+                out.push_str(&line.replacen(":_default'", &format!(":_awsom_values {}'", kind), 1))
+            }
+            None => out.push_str(line),
+        }
+        out.push('\n');
+    }
 
-        eprintln!();
-        eprintln!("# Completion script generated successfully!");
-        eprintln!("# To see installation instructions, run:");
-        eprintln!("#   awsom completions {} --show-install", shell_name);
+    let function = r#"(( $+functions[_awsom_values] )) ||
+_awsom_values() {
+    local -a values
+    values=(${(f)"$(awsom _complete $1 2>/dev/null)"})
+    compadd -a values
+}
+
+"#;
+    insert_before_last(&out, "if [ \"$funcstack[1]\" = \"_awsom\" ]", function)
+}
+
+/// Give the value options a candidate list, and add the profile subcommands' argument
+fn fish_values(script: &str) -> String {
+    let mut out = String::with_capacity(script.len());
+    for line in script.lines() {
+        out.push_str(line);
+        if let Some((_, kind)) = VALUE_OPTIONS
+            .iter()
+            .find(|(option, _)| line.contains(&format!(" -l {} ", option)))
+        {
+            out.push_str(&format!(" -f -a \"(awsom _complete {})\"", kind));
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "complete -c awsom -n \"__fish_awsom_using_subcommand profile; and \
+         __fish_seen_subcommand_from {}\" -f -a \"(awsom _complete profiles)\"\n",
+        PROFILE_SUBCOMMANDS.join(" ")
+    ));
+    out
+}
+
+/// `script` with `text` inserted before the last line starting with `marker`, or at the end
+fn insert_before_last(script: &str, marker: &str, text: &str) -> String {
+    let at = script
+        .rfind(&format!("\n{}", marker))
+        .map_or(script.len(), |i| i + 1);
+    format!("{}{}{}", &script[..at], text, &script[at..])
+}
+
+const COMPLETE_COMMAND: &str = "_complete";
+
+/// The kind in `awsom _complete <kind>`, which the completion scripts run on Tab
+///
+/// This is handled before clap parses the command line: clap_complete lists even hidden
+/// subcommands, so as a subcommand it would show up as a completion itself.
+pub fn complete_request() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    match args.next() {
+        Some(command) if command == COMPLETE_COMMAND => Some(args.next().unwrap_or_default()),
+        _ => None,
+    }
+}
+
+/// Print the candidates for a `VALUE_OPTIONS` kind, reading only local files
+///
+/// Errors and unknown kinds print nothing; a completion script has no way to show them.
+pub fn complete(kind: &str) {
+    let cached_roles = || {
+        directory::list()
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|(_, cached)| cached.roles)
+    };
+    let mut values: Vec<String> = match kind {
+        "profiles" => {
+            let mut profiles = aws_config::list_config_profiles().unwrap_or_default();
+            profiles.extend(aws_config::list_profiles().unwrap_or_default());
+            profiles
+        }
+        "sessions" => aws_config::read_all_sso_sessions()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|session| session.session_name)
+            .collect(),
+        "accounts" => cached_roles().map(|role| role.account_name).collect(),
+        "account-ids" => cached_roles().map(|role| role.account_id).collect(),
+        _ => Vec::new(),
+    };
+    values.sort();
+    values.dedup();
+
+    let mut stdout = io::stdout().lock();
+    for value in values {
+        if writeln!(stdout, "{}", value).is_err() {
+            break;
+        }
     }
 }
 
+/// The shell named by $SHELL, for `--install` without a shell argument
+fn detect_shell() -> Result<Shell> {
+    let program = std::env::var_os("SHELL").map(PathBuf::from);
+    let shell = match program.as_deref().and_then(Path::file_stem) {
+        Some(stem) if stem == "bash" => Some(Shell::Bash),
+        Some(stem) if stem == "zsh" => Some(Shell::Zsh),
+        Some(stem) if stem == "fish" => Some(Shell::Fish),
+        Some(stem) if stem == "elvish" => Some(Shell::Elvish),
+        Some(stem) if stem == "pwsh" || stem == "powershell" => Some(Shell::PowerShell),
+        _ => None,
+    };
+    shell.ok_or_else(|| {
+        SsoError::InvalidConfig(
+            "Couldn't tell your shell from $SHELL; name it, e.g. \
+             'awsom completions zsh --install'"
+                .to_string(),
+        )
+    })
+}
+
+/// Where `--install` puts the script: a directory the shell loads completions from
+/// without changes to its startup files, except zsh's ~/.zfunc which must be on $fpath
+fn install_path(
+    shell: Shell,
+    home: &Path,
+    data_home: Option<PathBuf>,
+    config_home: Option<PathBuf>,
+) -> Option<PathBuf> {
+    match shell {
+        Shell::Bash => Some(
+            data_home
+                .unwrap_or_else(|| home.join(".local/share"))
+                .join("bash-completion/completions/awsom"),
+        ),
+        Shell::Zsh => Some(home.join(".zfunc/_awsom")),
+        Shell::Fish => Some(
+            config_home
+                .unwrap_or_else(|| home.join(".config"))
+                .join("fish/completions/awsom.fish"),
+        ),
+        Shell::PowerShell | Shell::Elvish => None,
+    }
+}
+
+/// An absolute path from an XDG base directory variable
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+fn install_script(shell: Shell, dry_run: bool) -> Result<()> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| SsoError::ConfigError("Could not find home directory".to_string()))?;
+    let path = install_path(
+        shell,
+        &home,
+        xdg_dir("XDG_DATA_HOME"),
+        xdg_dir("XDG_CONFIG_HOME"),
+    )
+    .ok_or_else(|| {
+        SsoError::InvalidConfig(format!(
+            "--install supports bash, zsh and fish; for {} see \
+             'awsom completions {} --show-install'",
+            name(shell),
+            name(shell)
+        ))
+    })?;
+    let script = script(shell);
+
+    if dry_run {
+        println!(
+            "Would write {} completions to {} ({} bytes)",
+            name(shell),
+            path.display(),
+            script.len()
+        );
+    } else {
+        crate::config::ensure_writable(&path)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, &script)?;
+        println!("✓ Wrote {} completions to {}", name(shell), path.display());
+    }
+
+    match shell {
+        Shell::Zsh if !zshrc_has_zfunc(&home) => {
+            println!();
+            println!("~/.zfunc isn't on your fpath yet; add this to ~/.zshrc before compinit:");
+            println!("  fpath=(~/.zfunc $fpath)");
+            println!("  autoload -Uz compinit && compinit");
+        }
+        Shell::Bash => {
+            println!("New shells load it through the bash-completion package (version 2 or later).")
+        }
+        _ => println!("New shells load it automatically."),
+    }
+    Ok(())
+}
+
+/// Whether ~/.zshrc (in $ZDOTDIR if set) already mentions ~/.zfunc
+fn zshrc_has_zfunc(home: &Path) -> bool {
+    let dir = std::env::var_os("ZDOTDIR").map_or_else(|| home.to_path_buf(), PathBuf::from);
+    std::fs::read_to_string(dir.join(".zshrc")).is_ok_and(|zshrc| zshrc.contains(".zfunc"))
+}
+
 fn print_installation_instructions(shell: &Shell) {
     let instructions = match shell {
         Shell::Bash => {
@@ -141,3 +443,52 @@ Note: rc.elv location may vary:
 
     println!("{}", instructions);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_complete_values() {
+        // Every option in the table exists, so each script gets its hooks
+        let zsh = String::from_utf8(script(Shell::Zsh)).unwrap();
+        let fish = String::from_utf8(script(Shell::Fish)).unwrap();
+        for (option, kind) in VALUE_OPTIONS {
+            assert!(
+                zsh.contains(&format!(":_awsom_values {}'", kind)),
+                "{}",
+                option
+            );
+            assert!(fish.contains(&format!(" -l {} ", option)), "{}", option);
+        }
+        assert_eq!(
+            zsh.matches(":_awsom_values profiles'").count(),
+            zsh.matches("'--profile=").count() + PROFILE_SUBCOMMANDS.len()
+        );
+        assert!(zsh.contains("_awsom_values() {\n"));
+
+        let bash = String::from_utf8(script(Shell::Bash)).unwrap();
+        assert!(bash.contains("_awsom_values() {\n"));
+        assert!(!bash.contains("complete -F _awsom "));
+    }
+
+    #[test]
+    fn test_install_path() {
+        let home = Path::new("/home/me");
+        assert_eq!(
+            install_path(Shell::Bash, home, None, None),
+            Some(PathBuf::from(
+                "/home/me/.local/share/bash-completion/completions/awsom"
+            ))
+        );
+        assert_eq!(
+            install_path(Shell::Fish, home, None, Some(PathBuf::from("/xdg/config"))),
+            Some(PathBuf::from("/xdg/config/fish/completions/awsom.fish"))
+        );
+        assert_eq!(
+            install_path(Shell::Zsh, home, Some(PathBuf::from("/xdg/data")), None),
+            Some(PathBuf::from("/home/me/.zfunc/_awsom"))
+        );
+        assert_eq!(install_path(Shell::Elvish, home, None, None), None);
+    }
+}
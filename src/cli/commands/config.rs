@@ -0,0 +1,106 @@
+// config command - read and change ~/.config/awsom/config.toml without editing TOML by hand
+use crate::cli::ConfigCommands;
+use crate::config::{self, Config};
+use crate::error::{Result, SsoError};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+
+pub fn execute(command: ConfigCommands) -> Result<()> {
+    match command {
+        ConfigCommands::Get { key } => {
+            let value = Config::load()?.get_key(&key)?;
+            match value {
+                toml::Value::String(text) => println!("{}", text),
+                toml::Value::Table(table) => {
+                    print!("{}", toml::to_string_pretty(&table)?)
+                }
+                value => println!("{}", value),
+            }
+            Ok(())
+        }
+        ConfigCommands::Set { key, value } => {
+            let mut config = Config::load()?;
+            config.set_key(&key, &value)?;
+            config.save()?;
+            println!("✓ {} = {}", key, config.get_key(&key)?);
+            Ok(())
+        }
+        ConfigCommands::List => {
+            for (key, value) in Config::load()?.settings()? {
+                println!("{} = {}", key, value);
+            }
+            Ok(())
+        }
+        ConfigCommands::Edit => edit(),
+        ConfigCommands::Path => {
+            println!("{}", Config::path()?.display());
+            Ok(())
+        }
+    }
+}
+
+/// Open config.toml in the user's editor, written out with the defaults if it doesn't exist
+/// yet, and offer to go back to it until it parses
+fn edit() -> Result<()> {
+    let path = Config::path()?;
+    config::ensure_writable(&path)?;
+    if !path.exists() {
+        Config::default().save()?;
+    }
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+
+    loop {
+        let status = editor_command(&editor, &path)
+            .status()
+            .map_err(|e| SsoError::ConfigError(format!("Failed to start {}: {}", editor, e)))?;
+        if !status.success() {
+            return Err(SsoError::ConfigError(format!(
+                "{} exited with {}",
+                editor, status
+            )));
+        }
+
+        let error = match Config::load() {
+            Ok(_) => {
+                println!("✓ {} is valid", path.display());
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+        if !io::stdin().is_terminal() {
+            return Err(error);
+        }
+        eprint!("{}\nEdit again? (Y/n): ", error);
+        io::stderr().flush().map_err(SsoError::Io)?;
+        let mut response = String::new();
+        io::stdin().read_line(&mut response).map_err(SsoError::Io)?;
+        if response.trim().eq_ignore_ascii_case("n") {
+            return Err(error);
+        }
+    }
+}
+
+/// The editor setting may carry arguments (`code --wait`), so it goes through the shell
+fn editor_command(editor: &str, path: &Path) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command
+            .arg("/C")
+            .arg(format!("{} \"{}\"", editor, path.display()));
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("sh")
+            .arg(path);
+        command
+    }
+}
@@ -1,9 +1,42 @@
+use crate::audit::{self, AuditAction};
 use crate::auth::AuthManager;
 use crate::aws_config;
+use crate::cli::commands::select::{self, Pick, RolePattern};
+use crate::clipboard;
+use crate::config::Config;
+use crate::console::SignIn;
 use crate::credentials::CredentialManager;
 use crate::error::{Result, SsoError};
+use crate::hooks::{self, HookContext, HookEvent};
+use crate::models::AccountRole;
 use crate::models::SsoInstance;
+use crate::usage;
+use chrono::Duration;
+use std::io::{self, IsTerminal, Write};
 
+/// What `profile console` does with the sign-in URL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UrlAction {
+    /// Open it in the default browser
+    #[default]
+    Open,
+    /// Print it to stdout (--url-only), e.g. to paste into a browser on another machine
+    Print,
+    /// Copy it to the clipboard (--copy)
+    Copy,
+}
+
+impl UrlAction {
+    pub fn from_flags(url_only: bool, copy: bool) -> Self {
+        match (url_only, copy) {
+            (true, _) => UrlAction::Print,
+            (_, true) => UrlAction::Copy,
+            _ => UrlAction::Open,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn execute(
     account_id: Option<String>,
     account_name: Option<String>,
@@ -12,6 +45,10 @@ pub async fn execute(
     sso_start_url: Option<String>,
     sso_region: Option<String>,
     console_region: Option<String>,
+    duration: Option<Duration>,
+    console_duration: Option<Duration>,
+    pick: Pick,
+    url_action: UrlAction,
 ) -> Result<()> {
     // Resolve SSO session using the new 4-level priority logic
     let (start_url, sso_region) = aws_config::resolve_sso_session(
@@ -33,55 +70,98 @@ pub async fn execute(
         .ok_or(SsoError::NoSessionFound)?;
 
     if token.is_expired() {
-        return Err(SsoError::TokenExpired);
+        return Err(SsoError::AuthExpired);
     }
 
-    // Determine account ID
-    let account_id = if let Some(id) = account_id {
-        id
-    } else if let Some(name) = account_name {
-        // Look up account ID by name
-        let cred_manager = CredentialManager::new()?;
-        let accounts = cred_manager
-            .list_accounts(&instance.region, &token.access_token)
-            .await?;
-
-        accounts
-            .into_iter()
-            .find(|(_, acc_name)| acc_name == &name)
-            .map(|(id, _)| id)
-            .ok_or_else(|| SsoError::InvalidConfig(format!("Account '{}' not found", name)))?
-    } else {
-        return Err(SsoError::InvalidConfig(
-            "Either --account-id or --account-name is required".to_string(),
-        ));
+    let pattern = RolePattern {
+        account_id: account_id.as_deref(),
+        account_name: account_name.as_deref(),
+        role_name: &role_name,
     };
-
-    // Get credentials
-    let cred_manager = CredentialManager::new()?;
-    let creds = cred_manager
-        .get_role_credentials(
-            &instance.region,
-            &token.access_token,
-            &account_id,
-            &role_name,
-        )
-        .await?;
+    let roles = select::resolve(&instance, &token.access_token, &pattern, pick).await?;
 
     // Determine which region to use for console (use SSO region as default)
     let console_region_resolved = console_region.as_deref().or(Some(instance.region.as_str()));
 
-    eprintln!("Opening AWS Console in browser...");
-    eprintln!("  Account: {}", account_id);
-    eprintln!("  Role: {}", role_name);
-    if let Some(r) = console_region_resolved {
-        eprintln!("  Region: {}", r);
-    }
+    let config = Config::load()?;
+    let console_config = config.console;
+    let sign_in = SignIn::from_config(&console_config, console_duration);
+    let cred_manager = CredentialManager::new()?;
+    for role in &roles {
+        if console_config.require_confirmation && !confirm_sign_in(role)? {
+            eprintln!("Console sign-in cancelled");
+            continue;
+        }
+        let creds = cred_manager
+            .get_role_credentials(&instance, &token.access_token, role, duration)
+            .await?;
 
-    // Open console in browser
-    crate::console::open_console(&creds, console_region_resolved)?;
+        if url_action == UrlAction::Open {
+            eprintln!("Opening AWS Console in browser...");
+            eprintln!("  Account: {}", role.account_id);
+            eprintln!("  Role: {}", role.role_name);
+            if let Some(r) = console_region_resolved {
+                eprintln!("  Region: {}", r);
+            }
+        }
 
-    eprintln!("✓ Console opened successfully");
+        let url =
+            crate::console::generate_console_url(&creds, console_region_resolved, &sign_in).await?;
+        match url_action {
+            UrlAction::Open => crate::console::open_url(&url)?,
+            UrlAction::Print => println!("{}", url),
+            UrlAction::Copy => {
+                clipboard::copy(&url)?;
+                eprintln!(
+                    "✓ Copied the console sign-in URL for {} in {} (valid for 15 minutes)",
+                    role.role_name, role.account_id
+                );
+            }
+        }
+        audit::record(
+            AuditAction::ConsoleOpened,
+            Some(&role.account_id),
+            Some(&role.role_name),
+            None,
+        );
+        usage::record(&config.stats, &role.account_id, &role.role_name, None);
+        if url_action != UrlAction::Open {
+            continue;
+        }
+        hooks::run(
+            HookEvent::ConsoleOpen,
+            &HookContext {
+                session: session_name.as_deref(),
+                start_url: Some(&instance.start_url),
+                account_id: Some(&role.account_id),
+                role_name: Some(&role.role_name),
+                region: console_region_resolved,
+                ..HookContext::default()
+            },
+        );
+
+        eprintln!("✓ Console opened successfully");
+    }
 
     Ok(())
 }
+
+/// `[console] require_confirmation`: ask on the terminal before signing in as `role`;
+/// without a terminal to ask on, nothing is signed in
+fn confirm_sign_in(role: &AccountRole) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Err(SsoError::InvalidConfig(
+            "[console] require_confirmation is set, but there is no terminal to confirm on"
+                .to_string(),
+        ));
+    }
+    eprint!(
+        "Sign in to the AWS Console as {} in {} ({})? (y/N): ",
+        role.role_name, role.account_name, role.account_id
+    );
+    io::stderr().flush().map_err(SsoError::Io)?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response).map_err(SsoError::Io)?;
+    Ok(response.trim().eq_ignore_ascii_case("y"))
+}
@@ -0,0 +1,205 @@
+// profile create command - write a named profile for an account/role
+use crate::auth::AuthManager;
+use crate::aws_config::{self, ConflictPolicy};
+use crate::config::{self, Config, ProfileMode};
+use crate::credentials::CredentialManager;
+use crate::error::{Result, SsoError};
+use crate::models::{AccountRole, SsoInstance};
+use crate::profile_template::{self, ProfileNameContext};
+use crate::usage;
+use std::io::{self, IsTerminal, Write};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    account_id: Option<String>,
+    account_name: Option<String>,
+    role_name: String,
+    session_name: Option<String>,
+    start_url: Option<String>,
+    region: Option<String>,
+    name: Option<String>,
+    profile_region: Option<String>,
+    output: Option<String>,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<()> {
+    if account_id.is_none() && account_name.is_none() {
+        return Err(SsoError::InvalidConfig(
+            "Either --account-id or --account-name is required".to_string(),
+        ));
+    }
+
+    // Resolve SSO session using the new 4-level priority logic
+    let (start_url, region) = aws_config::resolve_sso_session(
+        session_name.as_deref(),
+        start_url.as_deref(),
+        region.as_deref(),
+    )?;
+
+    let instance = SsoInstance {
+        start_url,
+        region,
+        session_name: None,
+    };
+
+    // Get SSO token
+    let auth = AuthManager::new()?;
+    let token = auth
+        .get_cached_token(&instance)?
+        .ok_or(SsoError::NoSessionFound)?;
+
+    if token.is_expired() {
+        return Err(SsoError::AuthExpired);
+    }
+
+    // The name template may need either the account name or ID, so always look both up
+    let account_id = account_id.or_else(|| {
+        account_name
+            .as_deref()
+            .and_then(config::resolve_account_alias)
+    });
+    let cred_manager = CredentialManager::new()?;
+    let accounts = cred_manager
+        .list_accounts(&instance.region, &token.access_token)
+        .await?;
+    let (account_id, account_name) = accounts
+        .into_iter()
+        .find(|(id, acc_name)| {
+            account_id.as_deref() == Some(id.as_str())
+                || account_name.as_deref() == Some(acc_name.as_str())
+        })
+        .ok_or_else(|| {
+            SsoError::InvalidConfig(format!(
+                "Account '{}' not found",
+                account_id.or(account_name).unwrap_or_default()
+            ))
+        })?;
+
+    let role = AccountRole {
+        account_id,
+        account_name,
+        role_name,
+    };
+
+    let profile_name = match name {
+        Some(name) => name,
+        None => {
+            let session_name = session_name.or_else(|| {
+                aws_config::read_sso_session()
+                    .ok()
+                    .flatten()
+                    .map(|s| s.session_name)
+            });
+            profile_template::render(
+                &Config::load()?.profile_defaults.name_template,
+                &ProfileNameContext {
+                    account_name: &role.account_name,
+                    account_id: &role.account_id,
+                    role_name: &role.role_name,
+                    session_name: session_name.as_deref(),
+                },
+            )?
+        }
+    };
+    let profile_name = resolve_conflict(&profile_name, on_conflict)?;
+
+    let creds = cred_manager
+        .get_role_credentials(&instance, &token.access_token, &role, None)
+        .await?;
+
+    // Fall back to [profile awsom-defaults], then the SSO region
+    let defaults = aws_config::read_awsom_defaults()?;
+    let profile_region = profile_region
+        .or_else(|| defaults.as_ref().map(|d| d.region.clone()))
+        .unwrap_or_else(|| instance.region.clone());
+    let output = output.or_else(|| defaults.map(|d| d.output));
+
+    let sso_native = Config::load()?.profile_defaults.profile_mode == ProfileMode::SsoNative;
+    if sso_native {
+        aws_config::write_sso_native_profile(&aws_config::SsoProfile {
+            name: profile_name.clone(),
+            sso_session: aws_config::sso_native_session(&instance)?,
+            account_id: role.account_id.clone(),
+            role_name: role.role_name.clone(),
+            region: profile_region.clone(),
+            output: output.clone(),
+            credential_process: false,
+            source_profile: None,
+        })?;
+    } else {
+        aws_config::write_credentials_with_metadata(
+            &profile_name,
+            &creds,
+            &profile_region,
+            output.as_deref(),
+            Some(&role),
+        )?;
+    }
+
+    usage::record(
+        &Config::load().unwrap_or_default().stats,
+        &role.account_id,
+        &role.role_name,
+        Some(&profile_name),
+    );
+
+    println!("✓ Created profile '{}'", profile_name);
+    println!("  Account: {} ({})", role.account_name, role.account_id);
+    println!("  Role: {}", role.role_name);
+    println!("  Region: {}", profile_region);
+    if let Some(output) = &output {
+        println!("  Output format: {}", output);
+    }
+    if sso_native {
+        println!("  Credentials: from the SSO session (profile_mode = \"sso-native\")");
+    } else {
+        println!("  Expires: {}", creds.expiration_display());
+    }
+    println!("\nUse with: aws s3 ls --profile {}", profile_name);
+
+    Ok(())
+}
+
+/// The name to write `profile_name` under when it is a user-managed profile: settled by
+/// `--on-conflict`, else asked on the terminal; with no terminal to ask on it isn't touched
+pub fn resolve_conflict(profile_name: &str, on_conflict: Option<ConflictPolicy>) -> Result<String> {
+    if !aws_config::profile_exists_in_user_section(profile_name)? {
+        return Ok(profile_name.to_string());
+    }
+    let policy = match on_conflict {
+        Some(policy) => policy,
+        None if !io::stdin().is_terminal() => ConflictPolicy::Abort,
+        None => ask_conflict_policy(profile_name)?,
+    };
+
+    let resolved = aws_config::resolve_profile_conflict(profile_name, policy)?;
+    if resolved == profile_name {
+        eprintln!("Imported profile '{}' into awsom management", profile_name);
+    } else {
+        eprintln!(
+            "Profile '{}' is user-managed; writing '{}' instead",
+            profile_name, resolved
+        );
+    }
+    Ok(resolved)
+}
+
+fn ask_conflict_policy(profile_name: &str) -> Result<ConflictPolicy> {
+    eprint!(
+        "Profile '{}' is user-managed in ~/.aws/config.\n  \
+         i  import it and overwrite it\n  \
+         s  write '{}' instead\n  \
+         a  abort\n\
+         Choice (i/s/A): ",
+        profile_name,
+        aws_config::suffixed_profile_name(profile_name)?
+    );
+    io::stderr().flush().map_err(SsoError::Io)?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response).map_err(SsoError::Io)?;
+    Ok(match response.trim().to_lowercase().as_str() {
+        "i" | "import" => ConflictPolicy::Import,
+        "s" | "suffix" => ConflictPolicy::Suffix,
+        _ => ConflictPolicy::Abort,
+    })
+}
@@ -0,0 +1,66 @@
+// credential-process command - credentials for a profile's `credential_process`
+//
+// The AWS CLI and SDKs run this and read the JSON it prints on stdout, so it must never
+// prompt or print anything else there. Credentials come from awsom's cache while they
+// last long enough for the SDK not to ask again right away.
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::credentials::CredentialManager;
+use crate::error::{Result, SsoError};
+use crate::models::{AccountRole, SsoInstance};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// The SDKs refresh process credentials 15 minutes before they expire; cached ones that
+/// expire sooner would have them call again on every request
+const MIN_VALIDITY_MINUTES: i64 = 15;
+
+/// The output format the SDKs expect
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ProcessCredentials {
+    version: u8,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: String,
+    expiration: DateTime<Utc>,
+}
+
+pub async fn execute(session_name: String, account_id: String, role_name: String) -> Result<()> {
+    let (start_url, region) =
+        aws_config::resolve_sso_session(Some(session_name.as_str()), None, None)?;
+    let instance = SsoInstance {
+        start_url,
+        region,
+        session_name: Some(session_name),
+    };
+
+    let token = AuthManager::new()?
+        .get_cached_token(&instance)?
+        .filter(|token| !token.is_expired())
+        .ok_or(SsoError::AuthExpired)?;
+
+    let role = AccountRole {
+        account_name: account_id.clone(),
+        account_id,
+        role_name,
+    };
+    let creds = CredentialManager::new()?
+        .get_credentials_valid_for(
+            &instance,
+            &token,
+            &role,
+            Duration::minutes(MIN_VALIDITY_MINUTES),
+        )
+        .await?;
+
+    let output = ProcessCredentials {
+        version: 1,
+        access_key_id: creds.access_key_id,
+        secret_access_key: creds.secret_access_key,
+        session_token: creds.session_token,
+        expiration: creds.expiration,
+    };
+    println!("{}", serde_json::to_string(&output)?);
+    Ok(())
+}
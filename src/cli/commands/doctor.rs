@@ -0,0 +1,285 @@
+// doctor command - diagnose the local AWS/awsom setup
+use crate::auth::{AuthManager, TokenCache};
+use crate::aws_config::{self, MarkerState, SsoSession};
+use crate::env;
+use crate::error::{Result, SsoError};
+use crate::models::SsoInstance;
+use crate::network;
+use crate::output::{CheckStatus, DoctorCheck, DoctorReport, OutputFormat};
+use std::time::Duration;
+
+/// How long to wait for an SSO endpoint before calling it unreachable
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub async fn execute(format: OutputFormat) -> Result<()> {
+    let diagnosis = aws_config::diagnose()?;
+    let sessions = aws_config::read_all_sso_sessions()?;
+
+    let mut checks = vec![config_check(&diagnosis)];
+    if diagnosis.config_exists {
+        checks.push(session_refs_check(&diagnosis.broken_session_refs));
+    }
+    checks.push(orphaned_credentials_check(&diagnosis.orphaned_credentials));
+    checks.push(token_cache_check()?);
+    checks.push(sessions_check(&sessions)?);
+    checks.extend(endpoint_checks(&sessions).await);
+    checks.push(browser_check());
+
+    let report = DoctorReport(checks);
+    format.print(&report)?;
+    std::process::exit(if report.has_errors() { 1 } else { 0 });
+}
+
+fn check(name: &str, status: CheckStatus, message: String, fix: Option<&str>) -> DoctorCheck {
+    DoctorCheck {
+        check: name.to_string(),
+        status,
+        message,
+        fix: fix.map(str::to_string),
+    }
+}
+
+fn config_check(diagnosis: &aws_config::ConfigDiagnosis) -> DoctorCheck {
+    const NAME: &str = "config markers";
+
+    if !diagnosis.config_exists {
+        return check(
+            NAME,
+            CheckStatus::Warning,
+            "~/.aws/config does not exist".to_string(),
+            Some("Add an SSO session: awsom session add --name <name> --start-url <url> --region <region>"),
+        );
+    }
+
+    match &diagnosis.markers {
+        MarkerState::Valid => check(
+            NAME,
+            CheckStatus::Ok,
+            "user-managed and awsom-managed markers are in place".to_string(),
+            None,
+        ),
+        MarkerState::Missing => check(
+            NAME,
+            CheckStatus::Warning,
+            "~/.aws/config has no awsom markers yet".to_string(),
+            Some(
+                "They are added on the first write, e.g. awsom session add or awsom profile start",
+            ),
+        ),
+        MarkerState::Broken(reason) => check(
+            NAME,
+            CheckStatus::Error,
+            format!("~/.aws/config markers are broken: {}", reason),
+            Some(
+                "Edit ~/.aws/config to keep one user-managed marker above one awsom-managed \
+                 marker (the original file is in ~/.aws/config-before-awsom.bak)",
+            ),
+        ),
+    }
+}
+
+fn session_refs_check(broken: &[(String, String)]) -> DoctorCheck {
+    const NAME: &str = "sso-session references";
+
+    if broken.is_empty() {
+        return check(
+            NAME,
+            CheckStatus::Ok,
+            "every sso_session in ~/.aws/config exists".to_string(),
+            None,
+        );
+    }
+
+    let list: Vec<String> = broken
+        .iter()
+        .map(|(profile, session)| format!("{} → {}", profile, session))
+        .collect();
+    check(
+        NAME,
+        CheckStatus::Error,
+        format!(
+            "profile(s) refer to missing sso-session sections: {}",
+            list.join(", ")
+        ),
+        Some("Add the session with awsom session add, or fix sso_session in the profile"),
+    )
+}
+
+fn orphaned_credentials_check(orphaned: &[String]) -> DoctorCheck {
+    const NAME: &str = "orphaned credentials";
+
+    if orphaned.is_empty() {
+        return check(
+            NAME,
+            CheckStatus::Ok,
+            "all awsom credentials have a matching profile".to_string(),
+            None,
+        );
+    }
+
+    check(
+        NAME,
+        CheckStatus::Warning,
+        format!(
+            "~/.aws/credentials has awsom credentials without a profile in ~/.aws/config: {}",
+            orphaned.join(", ")
+        ),
+        Some("Re-run awsom profile start for them, or delete the sections from ~/.aws/credentials"),
+    )
+}
+
+fn token_cache_check() -> Result<DoctorCheck> {
+    const NAME: &str = "token cache";
+
+    let tokens = TokenCache::new()?.list_tokens()?;
+    let expired = tokens.iter().filter(|(_, t)| t.is_expired()).count();
+
+    let check = if expired == 0 {
+        check(
+            NAME,
+            CheckStatus::Ok,
+            format!(
+                "{} token(s) in ~/.aws/sso/cache, none expired",
+                tokens.len()
+            ),
+            None,
+        )
+    } else {
+        check(
+            NAME,
+            CheckStatus::Warning,
+            format!(
+                "{} of {} token(s) in ~/.aws/sso/cache are expired",
+                expired,
+                tokens.len()
+            ),
+            Some("Log in again with awsom session login; stale cache files can be deleted safely"),
+        )
+    };
+    Ok(check)
+}
+
+fn sessions_check(sessions: &[SsoSession]) -> Result<DoctorCheck> {
+    const NAME: &str = "sso sessions";
+
+    if sessions.is_empty() {
+        return Ok(check(
+            NAME,
+            CheckStatus::Warning,
+            "no [sso-session] sections in ~/.aws/config".to_string(),
+            Some("Add one: awsom session add --name <name> --start-url <url> --region <region>"),
+        ));
+    }
+
+    let auth = AuthManager::new()?;
+    let mut logged_out = Vec::new();
+    for session in sessions {
+        let instance = SsoInstance {
+            start_url: session.sso_start_url.clone(),
+            region: session.sso_region.clone(),
+            session_name: Some(session.session_name.clone()),
+        };
+        if auth.get_cached_token(&instance)?.is_none() {
+            logged_out.push(session.session_name.as_str());
+        }
+    }
+
+    let check = if logged_out.is_empty() {
+        check(
+            NAME,
+            CheckStatus::Ok,
+            format!("logged in to all {} session(s)", sessions.len()),
+            None,
+        )
+    } else {
+        check(
+            NAME,
+            CheckStatus::Warning,
+            format!("not logged in to: {}", logged_out.join(", ")),
+            Some("Run awsom session login --session-name <name>"),
+        )
+    };
+    Ok(check)
+}
+
+/// Check that the OIDC endpoint and start URL of every session answer over HTTPS
+///
+/// Any HTTP response counts as reachable - only connection and TLS failures or
+/// timeouts are reported.
+async fn endpoint_checks(sessions: &[SsoSession]) -> Vec<DoctorCheck> {
+    let client = network::client_builder().and_then(|builder| {
+        builder
+            .timeout(ENDPOINT_TIMEOUT)
+            .build()
+            .map_err(|e| SsoError::NetworkError(e.to_string()))
+    });
+    let client = match client {
+        Ok(client) => client,
+        Err(e) => {
+            return vec![check(
+                "sso endpoints",
+                CheckStatus::Error,
+                format!("Failed to create HTTP client: {}", e),
+                None,
+            )]
+        }
+    };
+
+    let mut checks = Vec::new();
+    for session in sessions {
+        let oidc_url = format!("https://oidc.{}.amazonaws.com", session.sso_region);
+        let mut unreachable = Vec::new();
+        for url in [&oidc_url, &session.sso_start_url] {
+            if let Err(e) = client.get(url.as_str()).send().await {
+                unreachable.push(format!("{} ({})", url, root_cause(&e)));
+            }
+        }
+
+        let name = format!("sso endpoints ({})", session.session_name);
+        checks.push(if unreachable.is_empty() {
+            check(
+                &name,
+                CheckStatus::Ok,
+                format!("{} and the start URL are reachable", oidc_url),
+                None,
+            )
+        } else {
+            check(
+                &name,
+                CheckStatus::Error,
+                format!("cannot reach {}", unreachable.join(", ")),
+                Some("Check the network, VPN and proxy ([network] or HTTPS_PROXY), and the session's region and start URL"),
+            )
+        });
+    }
+    checks
+}
+
+/// reqwest's own message is just "error sending request"; the cause says why
+fn root_cause(error: &dyn std::error::Error) -> String {
+    let mut cause = error;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
+
+fn browser_check() -> DoctorCheck {
+    const NAME: &str = "browser";
+
+    if env::is_headless_environment() {
+        check(
+            NAME,
+            CheckStatus::Warning,
+            "no browser available (headless environment detected)".to_string(),
+            Some("Use awsom session login --no-browser and open the printed URL on another device"),
+        )
+    } else {
+        check(
+            NAME,
+            CheckStatus::Ok,
+            "login will open the default browser".to_string(),
+            None,
+        )
+    }
+}
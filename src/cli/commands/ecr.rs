@@ -0,0 +1,136 @@
+// ecr command - docker logins to ECR registries as an SSO role
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::cli::commands::select::{self, Pick, RolePattern};
+use crate::cli::EcrCommands;
+use crate::credentials::CredentialManager;
+use crate::ecr::{self, RegistryLogin};
+use crate::error::{Result, SsoError};
+use crate::models::SsoInstance;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+pub async fn execute(command: EcrCommands) -> Result<()> {
+    match command {
+        EcrCommands::Login {
+            account_id,
+            account_name,
+            role_name,
+            session_name,
+            region,
+            registry_ids,
+            print,
+            first,
+        } => {
+            let pattern = RolePattern {
+                account_id: account_id.as_deref(),
+                account_name: account_name.as_deref(),
+                role_name: &role_name,
+            };
+            let logins = get_logins(
+                &pattern,
+                session_name.as_deref(),
+                region,
+                &registry_ids,
+                Pick::from_flags(first, false),
+            )
+            .await?;
+
+            if print {
+                // Every registry the role can reach shares the one password
+                if let Some(login) = logins.first() {
+                    println!("{}", login.password);
+                }
+                return Ok(());
+            }
+            docker_login(&logins)
+        }
+    }
+}
+
+async fn get_logins(
+    pattern: &RolePattern<'_>,
+    session_name: Option<&str>,
+    region: Option<String>,
+    registry_ids: &[String],
+    pick: Pick,
+) -> Result<Vec<RegistryLogin>> {
+    let (start_url, sso_region) = aws_config::resolve_sso_session(session_name, None, None)?;
+    let instance = SsoInstance {
+        start_url,
+        region: sso_region,
+        session_name: session_name.map(str::to_string),
+    };
+
+    let token = AuthManager::new()?
+        .get_cached_token(&instance)?
+        .ok_or(SsoError::NoSessionFound)?;
+    if token.is_expired() {
+        return Err(SsoError::AuthExpired);
+    }
+
+    let role = select::resolve(&instance, &token.access_token, pattern, pick)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| SsoError::ConfigError("No matching role".to_string()))?;
+    let region = region
+        .or_else(|| {
+            aws_config::read_awsom_defaults()
+                .ok()
+                .flatten()
+                .map(|defaults| defaults.region)
+        })
+        .unwrap_or_else(|| instance.region.clone());
+
+    let creds = CredentialManager::new()?
+        .get_role_credentials(&instance, &token.access_token, &role, None)
+        .await?;
+    let logins = ecr::get_authorization(&creds, &region, registry_ids).await?;
+    if logins.is_empty() {
+        return Err(SsoError::AwsSdk(
+            "GetAuthorizationToken returned no registries".to_string(),
+        ));
+    }
+    Ok(logins)
+}
+
+/// Run `docker login` for each registry, passing the password on stdin
+fn docker_login(logins: &[RegistryLogin]) -> Result<()> {
+    let mut exit_code = 0;
+    for login in logins {
+        let mut child = Command::new("docker")
+            .args(["login", "--username", &login.username, "--password-stdin"])
+            .arg(&login.endpoint)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SsoError::ConfigError(
+                    "docker not found on PATH; use --print to pipe the password to another \
+                     client"
+                        .to_string(),
+                ),
+                _ => SsoError::Io(e),
+            })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(login.password.as_bytes())?;
+        }
+        let status = child.wait()?;
+
+        if status.success() {
+            let expires = login
+                .expires_at
+                .map(|at| format!(" until {}", at.format("%Y-%m-%d %H:%M UTC")))
+                .unwrap_or_default();
+            eprintln!("✓ Logged in to {}{}", login.endpoint, expires);
+        } else {
+            exit_code = exit_code.max(status.code().unwrap_or(1));
+        }
+    }
+
+    // Exit with docker's code, the highest one across registries
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+    Ok(())
+}
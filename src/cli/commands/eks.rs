@@ -0,0 +1,195 @@
+// eks command - kubeconfig entries and cluster tokens for SSO roles
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::cli::commands::select::{self, Pick, RolePattern};
+use crate::cli::EksCommands;
+use crate::credentials::CredentialManager;
+use crate::eks;
+use crate::error::{Result, SsoError};
+use crate::models::{AccountRole, SsoInstance};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// Tokens are accepted for 15 minutes; kubectl asks again a minute before that
+const TOKEN_LIFETIME_MINUTES: i64 = 14;
+
+/// What kubectl's exec plugin reads on stdout
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecCredential {
+    kind: &'static str,
+    api_version: &'static str,
+    spec: serde_json::Value,
+    status: ExecCredentialStatus,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecCredentialStatus {
+    expiration_timestamp: DateTime<Utc>,
+    token: String,
+}
+
+pub async fn execute(command: EksCommands) -> Result<()> {
+    match command {
+        EksCommands::UpdateKubeconfig {
+            cluster,
+            account_id,
+            account_name,
+            role_name,
+            session_name,
+            region,
+            alias,
+            kubeconfig,
+            first,
+        } => {
+            let pattern = RolePattern {
+                account_id: account_id.as_deref(),
+                account_name: account_name.as_deref(),
+                role_name: &role_name,
+            };
+            update_kubeconfig(
+                &cluster,
+                &pattern,
+                session_name.as_deref(),
+                region,
+                alias,
+                kubeconfig,
+                Pick::from_flags(first, false),
+            )
+            .await
+        }
+        EksCommands::GetToken {
+            cluster,
+            region,
+            session_name,
+            account_id,
+            role_name,
+        } => get_token(&cluster, &region, session_name, account_id, role_name).await,
+    }
+}
+
+async fn update_kubeconfig(
+    cluster_name: &str,
+    pattern: &RolePattern<'_>,
+    session_name: Option<&str>,
+    region: Option<String>,
+    alias: Option<String>,
+    kubeconfig: Option<std::path::PathBuf>,
+    pick: Pick,
+) -> Result<()> {
+    let (start_url, sso_region) = aws_config::resolve_sso_session(session_name, None, None)?;
+    // get-token finds the session again by name
+    let session_name = aws_config::read_all_sso_sessions()?
+        .into_iter()
+        .find(|session| session.sso_start_url == start_url)
+        .map(|session| session.session_name)
+        .ok_or_else(|| {
+            SsoError::ConfigError(format!(
+                "No sso-session in ~/.aws/config has the start URL {}; add one with \
+                 'awsom session add'",
+                start_url
+            ))
+        })?;
+    let instance = SsoInstance {
+        start_url,
+        region: sso_region,
+        session_name: Some(session_name.clone()),
+    };
+
+    let token = AuthManager::new()?
+        .get_cached_token(&instance)?
+        .ok_or(SsoError::NoSessionFound)?;
+    if token.is_expired() {
+        return Err(SsoError::AuthExpired);
+    }
+
+    let role = select::resolve(&instance, &token.access_token, pattern, pick)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| SsoError::ConfigError("No matching role".to_string()))?;
+    let region = region
+        .or_else(|| {
+            aws_config::read_awsom_defaults()
+                .ok()
+                .flatten()
+                .map(|defaults| defaults.region)
+        })
+        .unwrap_or_else(|| instance.region.clone());
+
+    let creds = CredentialManager::new()?
+        .get_role_credentials(&instance, &token.access_token, &role, None)
+        .await?;
+    let cluster = eks::describe_cluster(&creds, &region, cluster_name).await?;
+
+    let context = alias.unwrap_or_else(|| cluster.arn.clone());
+    let path = match kubeconfig {
+        Some(path) => path,
+        None => eks::kubeconfig_path()?,
+    };
+    let dropped_comments = eks::update_kubeconfig(
+        &path,
+        &cluster,
+        &context,
+        &eks::get_token_args(&cluster.name, &region, &session_name, &role),
+    )?;
+
+    println!("✓ Switched to context {} in {}", context, path.display());
+    if dropped_comments {
+        eprintln!("  Comments in {} were not kept", path.display());
+    }
+    println!(
+        "  kubectl authenticates to {} as {} / {} through awsom",
+        cluster.name, role.account_name, role.role_name
+    );
+    Ok(())
+}
+
+async fn get_token(
+    cluster: &str,
+    region: &str,
+    session_name: String,
+    account_id: String,
+    role_name: String,
+) -> Result<()> {
+    let (start_url, sso_region) =
+        aws_config::resolve_sso_session(Some(session_name.as_str()), None, None)?;
+    let instance = SsoInstance {
+        start_url,
+        region: sso_region,
+        session_name: Some(session_name),
+    };
+
+    let token = AuthManager::new()?
+        .get_cached_token(&instance)?
+        .filter(|token| !token.is_expired())
+        .ok_or(SsoError::AuthExpired)?;
+
+    let role = AccountRole {
+        account_name: account_id.clone(),
+        account_id,
+        role_name,
+    };
+    // The presigned URL is only good while the credentials behind it are
+    let creds = CredentialManager::new()?
+        .get_credentials_valid_for(
+            &instance,
+            &token,
+            &role,
+            Duration::minutes(TOKEN_LIFETIME_MINUTES + 1),
+        )
+        .await?;
+
+    let output = ExecCredential {
+        kind: "ExecCredential",
+        api_version: eks::EXEC_API_VERSION,
+        spec: serde_json::json!({}),
+        status: ExecCredentialStatus {
+            expiration_timestamp: Utc::now() + Duration::minutes(TOKEN_LIFETIME_MINUTES),
+            token: eks::token(&creds, region, cluster)?,
+        },
+    };
+    println!("{}", serde_json::to_string(&output)?);
+    Ok(())
+}
@@ -0,0 +1,234 @@
+// emit command - provider and client configuration snippets that use an awsom profile
+use crate::aws_config;
+use crate::cli::EmitFormat;
+use crate::error::{Result, SsoError};
+
+/// What a snippet needs to know about the profile
+#[derive(Debug, Clone, PartialEq)]
+struct Target {
+    profile: String,
+    region: String,
+    account_id: Option<String>,
+    /// (source profile, role ARN) of a `--mode role-arn` profile
+    chain: Option<(String, String)>,
+}
+
+pub fn execute(format: EmitFormat, profile: &str, region: Option<String>) -> Result<()> {
+    let target = target(profile, region)?;
+    print!("{}", snippet(format, &target));
+    Ok(())
+}
+
+fn target(profile: &str, region: Option<String>) -> Result<Target> {
+    let details = aws_config::get_profile_details(profile)?.ok_or_else(|| {
+        SsoError::ConfigError(format!("Profile '{}' not found in ~/.aws/config", profile))
+    })?;
+
+    let region = match region.or(details.region.clone()) {
+        Some(region) => region,
+        None => default_region(&details)?.ok_or_else(|| {
+            SsoError::ConfigError(format!(
+                "Profile '{}' has no region; pass --region",
+                profile
+            ))
+        })?,
+    };
+
+    let chain = details.source_profile.zip(details.role_arn);
+    let account_id = details.sso_account_id.or_else(|| {
+        // arn:<partition>:iam::<account>:role/<name>
+        let (_, arn) = chain.as_ref()?;
+        arn.split(':').nth(4).map(str::to_string)
+    });
+    Ok(Target {
+        profile: profile.to_string(),
+        region,
+        account_id,
+        chain,
+    })
+}
+
+/// Region for a profile without one: its source profile's, awsom's default, or its SSO
+/// region, as the SDKs would fall back
+fn default_region(details: &aws_config::ProfileDetails) -> Result<Option<String>> {
+    if let Some(source) = &details.source_profile {
+        let source_region = aws_config::get_profile_details(source)?.and_then(|d| d.region);
+        if source_region.is_some() {
+            return Ok(source_region);
+        }
+    }
+    if let Some(defaults) = aws_config::read_awsom_defaults()? {
+        return Ok(Some(defaults.region));
+    }
+    match &details.sso_session {
+        Some(session) => Ok(Some(
+            aws_config::resolve_sso_session(Some(session), None, None)?.1,
+        )),
+        None => Ok(None),
+    }
+}
+
+fn snippet(format: EmitFormat, target: &Target) -> String {
+    match format {
+        EmitFormat::Terraform => terraform(target),
+        EmitFormat::Cdk => cdk(target),
+        EmitFormat::SdkGo => sdk_go(target),
+        EmitFormat::Boto3 => boto3(target),
+    }
+}
+
+/// A double-quoted string literal; these escapes mean the same in HCL, TypeScript, Go and
+/// Python, unlike Rust's Debug escapes such as `\u{..}`
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The provider assumes the role itself when the profile is a role-arn one, so the chain
+/// shows up in `terraform plan`
+fn terraform(target: &Target) -> String {
+    let mut out = String::from("provider \"aws\" {\n");
+    match &target.chain {
+        Some((source_profile, role_arn)) => {
+            out += &format!("  profile = {}\n", quote(source_profile));
+            out += &format!("  region  = {}\n", quote(&target.region));
+            out += "\n  assume_role {\n";
+            out += &format!("    role_arn     = {}\n", quote(role_arn));
+            out += "    session_name = \"awsom\"\n";
+            out += "  }\n";
+        }
+        None => {
+            out += &format!("  profile = {}\n", quote(&target.profile));
+            out += &format!("  region  = {}\n", quote(&target.region));
+        }
+    }
+    out += "}\n";
+    out
+}
+
+/// The CDK CLI reads credentials from `--profile`, including role_arn chains; the app only
+/// pins the stack's environment
+fn cdk(target: &Target) -> String {
+    let mut out = format!("// Deploy with: cdk deploy --profile {}\n", target.profile);
+    if let Some((source_profile, role_arn)) = &target.chain {
+        out += &format!(
+            "// The profile assumes {} with {}'s credentials\n",
+            role_arn, source_profile
+        );
+    }
+    let account = target
+        .account_id
+        .as_deref()
+        .map_or_else(|| "process.env.CDK_DEFAULT_ACCOUNT".to_string(), quote);
+    out += "const app = new cdk.App();\n";
+    out += "new MyStack(app, \"MyStack\", {\n";
+    out += &format!(
+        "  env: {{ account: {}, region: {} }},\n",
+        account,
+        quote(&target.region)
+    );
+    out += "});\n";
+    out
+}
+
+fn sdk_go(target: &Target) -> String {
+    let (profile, role_arn) = match &target.chain {
+        Some((source_profile, role_arn)) => (source_profile, Some(role_arn)),
+        None => (&target.profile, None),
+    };
+
+    let mut out = String::from("import (\n");
+    if role_arn.is_some() {
+        out += "\t\"github.com/aws/aws-sdk-go-v2/aws\"\n";
+    }
+    out += "\t\"github.com/aws/aws-sdk-go-v2/config\"\n";
+    if role_arn.is_some() {
+        out += "\t\"github.com/aws/aws-sdk-go-v2/credentials/stscreds\"\n";
+        out += "\t\"github.com/aws/aws-sdk-go-v2/service/sts\"\n";
+    }
+    out += ")\n\n";
+    out += "cfg, err := config.LoadDefaultConfig(ctx,\n";
+    out += &format!("\tconfig.WithSharedConfigProfile({}),\n", quote(profile));
+    out += &format!("\tconfig.WithRegion({}),\n", quote(&target.region));
+    out += ")\nif err != nil {\n\treturn err\n}\n";
+    if let Some(role_arn) = role_arn {
+        out += "cfg.Credentials = aws.NewCredentialsCache(\n";
+        out += &format!(
+            "\tstscreds.NewAssumeRoleProvider(sts.NewFromConfig(cfg), {}),\n",
+            quote(role_arn)
+        );
+        out += ")\n";
+    }
+    out
+}
+
+/// botocore resolves role_arn/source_profile chains from the profile, refreshing the
+/// assumed credentials as they expire, so the session only names the profile
+fn boto3(target: &Target) -> String {
+    let mut out = String::from("import boto3\n\n");
+    if let Some((source_profile, role_arn)) = &target.chain {
+        out += &format!(
+            "# The profile assumes {} with {}'s credentials\n",
+            role_arn, source_profile
+        );
+    }
+    out += &format!(
+        "session = boto3.Session(profile_name={}, region_name={})\n",
+        quote(&target.profile),
+        quote(&target.region)
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippets_follow_role_arn_chains() {
+        let mut target = Target {
+            profile: "prod-admin".to_string(),
+            region: "eu-west-1".to_string(),
+            account_id: Some("111111111111".to_string()),
+            chain: None,
+        };
+        assert_eq!(
+            terraform(&target),
+            "provider \"aws\" {\n  profile = \"prod-admin\"\n  region  = \"eu-west-1\"\n}\n"
+        );
+        assert!(!sdk_go(&target).contains("stscreds"));
+
+        target.chain = Some((
+            "hub".to_string(),
+            "arn:aws:iam::111111111111:role/Admin".to_string(),
+        ));
+        let hcl = terraform(&target);
+        assert!(hcl.contains("  profile = \"hub\"\n"), "{}", hcl);
+        assert!(
+            hcl.contains("    role_arn     = \"arn:aws:iam::111111111111:role/Admin\"\n"),
+            "{}",
+            hcl
+        );
+        let go = sdk_go(&target);
+        assert!(
+            go.contains("config.WithSharedConfigProfile(\"hub\")") && go.contains("stscreds"),
+            "{}",
+            go
+        );
+        assert!(boto3(&target).contains("profile_name=\"prod-admin\""));
+
+        assert_eq!(quote("a\"b\\c\td\n"), r#""a\"b\\c\td\n""#);
+        assert_eq!(quote("prod-é"), "\"prod-é\"");
+    }
+}
@@ -0,0 +1,112 @@
+// env command - print exports for a role's credentials, or unsets for every AWS_* variable
+// that could get in their way
+use crate::cli::commands::assume::{self, unset_command, Target, VARIABLES};
+use crate::cli::HookShell;
+use crate::error::Result;
+
+/// Variables awsom never sets that still make AWS tools pick other credentials or an old
+/// session token
+pub const CONFLICTING_VARIABLES: &[&str] = &[
+    "AWS_SECURITY_TOKEN",
+    "AWS_ACCESS_KEY",
+    "AWS_SECRET_KEY",
+    "AWS_DEFAULT_PROFILE",
+    "AWS_ROLE_ARN",
+    "AWS_ROLE_SESSION_NAME",
+    "AWS_WEB_IDENTITY_TOKEN_FILE",
+    "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI",
+    "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+    "AWS_CONTAINER_AUTHORIZATION_TOKEN",
+];
+
+pub struct EnvArgs {
+    pub profile: Option<String>,
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub role_name: Option<String>,
+    pub session_name: Option<String>,
+    pub region: Option<String>,
+}
+
+pub async fn execute(args: EnvArgs, shell: HookShell, unset: bool, headless: bool) -> Result<()> {
+    if unset {
+        self::unset(shell);
+        return Ok(());
+    }
+
+    let mut target = match &args.profile {
+        Some(profile) => assume::resolve_target(profile, args.session_name.as_deref())?,
+        None => Target {
+            profile: None,
+            account: args.account_id.or(args.account_name),
+            // clap requires a role name without --profile or --unset
+            role_name: args.role_name.unwrap_or_default(),
+            session_name: args.session_name,
+            region: None,
+        },
+    };
+    target.region = args.region.or(target.region);
+
+    let assumed = assume::assume(&target, None, None, headless).await?;
+    for command in assumed.export_commands(shell) {
+        println!("{}", command);
+    }
+    eprintln!(
+        "✓ Credentials for {} in {} ({}), expire in {}",
+        assumed.role.role_name,
+        assumed.role.account_name,
+        assumed.role.account_id,
+        assumed.creds.expiration_display()
+    );
+    warn_about_conflicts(&[]);
+    Ok(())
+}
+
+/// Print commands clearing the variables `assume` sets and the conflicting ones
+pub fn unset(shell: HookShell) {
+    for name in VARIABLES.iter().chain(CONFLICTING_VARIABLES) {
+        println!("{}", unset_command(shell, name));
+    }
+    eprintln!("✓ Cleared AWS credentials from the environment");
+}
+
+/// Conflicting variables set in this process's environment, except those in `overridden`
+pub fn conflicting_variables(overridden: &[&str]) -> Vec<&'static str> {
+    CONFLICTING_VARIABLES
+        .iter()
+        .copied()
+        .filter(|name| !overridden.contains(name) && std::env::var_os(name).is_some())
+        .collect()
+}
+
+/// Warn on stderr about conflicting variables the shell or a command would keep
+pub fn warn_about_conflicts(overridden: &[&str]) {
+    let conflicts = conflicting_variables(overridden);
+    if !conflicts.is_empty() {
+        eprintln!(
+            "⚠ {} {} set and may make AWS tools use other credentials; clear {} with: eval \
+             \"$(awsom env --unset)\"",
+            conflicts.join(", "),
+            if conflicts.len() == 1 { "is" } else { "are" },
+            if conflicts.len() == 1 { "it" } else { "them" },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflicting_variables() {
+        // Names no other test sets, so the process environment can be changed safely
+        std::env::set_var("AWS_CONTAINER_AUTHORIZATION_TOKEN", "token");
+        std::env::set_var("AWS_ROLE_SESSION_NAME", "ci");
+        assert_eq!(
+            conflicting_variables(&["AWS_ROLE_SESSION_NAME"]),
+            vec!["AWS_CONTAINER_AUTHORIZATION_TOKEN"]
+        );
+        std::env::remove_var("AWS_CONTAINER_AUTHORIZATION_TOKEN");
+        std::env::remove_var("AWS_ROLE_SESSION_NAME");
+    }
+}
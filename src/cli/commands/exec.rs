@@ -1,10 +1,32 @@
-use crate::auth::AuthManager;
+// exec command - runs a command with a role's credentials in its environment
+use crate::auth::{AuthManager, LoginOptions};
 use crate::aws_config;
+use crate::cli::commands::assume::Assumed;
+use crate::cli::commands::select::{self, Pick, RolePattern};
+use crate::config::Config;
 use crate::credentials::CredentialManager;
+use crate::env;
 use crate::error::{Result, SsoError};
-use crate::models::SsoInstance;
-use std::process::Command;
+use crate::models::{AccountRole, RoleCredentials, SsoInstance, SsoToken};
+use crate::usage;
+use chrono::Duration;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 
+/// What `--redact` prints in place of a secret
+const REDACTED: &[u8] = b"****";
+
+/// Pid of the command awsom is waiting for. Locked from writing the --via-file credentials
+/// until the command has started, and again while they're removed, so a signal never ends
+/// awsom with them on disk.
+type Running = Arc<Mutex<Option<u32>>>;
+
+#[allow(clippy::too_many_arguments)]
 pub async fn execute(
     account_id: Option<String>,
     account_name: Option<String>,
@@ -12,12 +34,23 @@ pub async fn execute(
     session_name: Option<String>,
     start_url: Option<String>,
     region: Option<String>,
+    aws_region: Option<String>,
+    env_vars: Vec<(String, String)>,
+    duration: Option<Duration>,
+    pick: Pick,
     command: Vec<String>,
+    via_file: bool,
+    redact: bool,
+    headless: bool,
 ) -> Result<()> {
     if command.is_empty() {
         return Err(SsoError::InvalidConfig("No command specified".to_string()));
     }
 
+    // The command inherits these, and tools may prefer them to the role's credentials
+    let overridden: Vec<&str> = env_vars.iter().map(|(name, _)| name.as_str()).collect();
+    super::env::warn_about_conflicts(&overridden);
+
     // Resolve SSO session using the new 4-level priority logic
     let (start_url, region) = aws_config::resolve_sso_session(
         session_name.as_deref(),
@@ -31,63 +64,428 @@ pub async fn execute(
         session_name: None,
     };
 
-    // Get SSO token
+    // Determine if running in headless mode (explicit flag or auto-detect)
+    let is_headless = headless || env::is_headless_environment();
+
+    // Get SSO token, logging in if there is no valid one
     let auth = AuthManager::new()?;
-    let token = auth
-        .get_cached_token(&instance)?
-        .ok_or(SsoError::NoSessionFound)?;
+    let mut token = match auth.get_cached_token(&instance)? {
+        Some(token) if !token.is_expired() => token,
+        _ => reauthenticate(&auth, &instance, is_headless).await?,
+    };
 
-    if token.is_expired() {
-        return Err(SsoError::TokenExpired);
+    // The cached token can still be rejected (e.g. revoked or signed out elsewhere),
+    // so re-authenticate and retry once
+    let pattern = RolePattern {
+        account_id: account_id.as_deref(),
+        account_name: account_name.as_deref(),
+        role_name: &role_name,
+    };
+    let roles = match select::resolve(&instance, &token.access_token, &pattern, pick).await {
+        Err(SsoError::AuthExpired) => {
+            token = reauthenticate(&auth, &instance, is_headless).await?;
+            select::resolve(&instance, &token.access_token, &pattern, pick).await?
+        }
+        result => result?,
+    };
+
+    // Ctrl+C reaches the running command through the terminal; awsom stays up to pass on
+    // its exit code (and remove the --via-file credentials), skipping the roles still to come.
+    // SIGTERM and SIGHUP are passed on to the command, to the same end.
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let running: Running = Arc::default();
+    if roles.len() > 1 || via_file || redact || cfg!(not(unix)) {
+        let ctrl_c = Arc::clone(&interrupted);
+        tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                ctrl_c.store(true, Ordering::Relaxed);
+            }
+        });
+        #[cfg(unix)]
+        forward_signals(&running, &interrupted)?;
     }
 
-    // Determine account ID
-    let account_id = if let Some(id) = account_id {
-        id
-    } else if let Some(name) = account_name {
-        // Look up account ID by name
-        let cred_manager = CredentialManager::new()?;
-        let accounts = cred_manager
-            .list_accounts(&instance.region, &token.access_token)
-            .await?;
-
-        accounts
-            .into_iter()
-            .find(|(_, acc_name)| acc_name == &name)
-            .map(|(id, _)| id)
-            .ok_or_else(|| SsoError::InvalidConfig(format!("Account '{}' not found", name)))?
-    } else {
-        return Err(SsoError::InvalidConfig(
-            "Either --account-id or --account-name is required".to_string(),
-        ));
-    };
+    let stats = Config::load().unwrap_or_default().stats;
+    let mut exit_code = 0;
+    for role in &roles {
+        if interrupted.load(Ordering::Relaxed) {
+            break;
+        }
+        let creds = match fetch_credentials(&instance, &token, role, duration).await {
+            Err(SsoError::AuthExpired) => {
+                token = reauthenticate(&auth, &instance, is_headless).await?;
+                fetch_credentials(&instance, &token, role, duration).await?
+            }
+            result => result?,
+        };
+
+        let profile = aws_config::get_existing_profile_name(role)?;
+        usage::record(
+            &stats,
+            &role.account_id,
+            &role.role_name,
+            profile.as_deref(),
+        );
+        let profile_region = match &profile {
+            Some(profile) => aws_config::get_profile_details(profile)?.and_then(|d| d.region),
+            None => None,
+        };
+        let assumed = Assumed {
+            role: role.clone(),
+            creds,
+            region: aws_region
+                .clone()
+                .or(profile_region)
+                .unwrap_or_else(|| instance.region.clone()),
+            profile,
+        };
+
+        // A single command takes awsom's place, so it gets signals and its exit code
+        // reaches the caller as they are
+        #[cfg(unix)]
+        if roles.len() == 1 && !via_file && !redact {
+            use std::os::unix::process::CommandExt;
+            let mut child = child_command(&command, &assumed, None, &env_vars);
+            return Err(SsoError::Io(child.exec()));
+        }
+
+        if roles.len() > 1 {
+            eprintln!("==> {} ({})", role.display_name(), role.account_id);
+        }
+        let mut pid = running.lock().unwrap_or_else(|e| e.into_inner());
+        let credentials_file = if via_file {
+            Some(CredentialsFile::create(&assumed)?)
+        } else {
+            None
+        };
+        let child = child_command(
+            &command,
+            &assumed,
+            credentials_file.as_ref().map(|file| file.path.as_path()),
+            &env_vars,
+        );
+        let process = spawn(child, redact.then_some(&assumed.creds));
+        *pid = process.as_ref().ok().map(|process| process.child.id());
+        drop(pid);
+
+        let status = process.and_then(Process::wait);
+        let mut pid = running.lock().unwrap_or_else(|e| e.into_inner());
+        *pid = None;
+        drop(credentials_file);
+        drop(pid);
+        let status = status.map_err(SsoError::Io)?;
+        if !status.success() {
+            exit_code = exit_code.max(exit_code_of(status));
+        }
+    }
+
+    // Exit with the command's code, the highest one with --all
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
 
-    // Get credentials
-    let cred_manager = CredentialManager::new()?;
-    let creds = cred_manager
-        .get_role_credentials(
-            &instance.region,
-            &token.access_token,
-            &account_id,
-            &role_name,
-        )
-        .await?;
-
-    // Execute command with credentials in environment
-    let status = Command::new(&command[0])
+    Ok(())
+}
+
+/// The command with the role's credentials, region and profile in its environment; an
+/// AWS_PROFILE inherited from the shell is removed when the role has no profile, and
+/// `env_vars` are set last so they can override any of them
+pub(super) fn child_command(
+    command: &[String],
+    assumed: &Assumed,
+    credentials_file: Option<&Path>,
+    env_vars: &[(String, String)],
+) -> Command {
+    let mut child = Command::new(&command[0]);
+    child
         .args(&command[1..])
-        .env("AWS_ACCESS_KEY_ID", &creds.access_key_id)
-        .env("AWS_SECRET_ACCESS_KEY", &creds.secret_access_key)
-        .env("AWS_SESSION_TOKEN", &creds.session_token)
-        .env("AWS_REGION", &instance.region)
-        .env("AWS_DEFAULT_REGION", &instance.region)
-        .status()
-        .map_err(SsoError::Io)?;
-
-    // Exit with same code as the command
-    if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        .envs(aws_config::file_override_env())
+        .envs(assumed.variables());
+    if let Some(path) = credentials_file {
+        child.env("AWS_SHARED_CREDENTIALS_FILE", path);
     }
+    match &assumed.profile {
+        Some(profile) => child.env("AWS_PROFILE", profile),
+        None => child.env_remove("AWS_PROFILE"),
+    };
+    child.envs(env_vars.iter().map(|(name, value)| (name, value)));
+    child
+}
 
+/// Pass SIGTERM and SIGHUP on to the running command, and skip the roles still to come;
+/// between commands they end awsom as they would have
+#[cfg(unix)]
+fn forward_signals(running: &Running, interrupted: &Arc<AtomicBool>) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    for kind in [SignalKind::terminate(), SignalKind::hangup()] {
+        let mut signals = signal(kind).map_err(SsoError::Io)?;
+        let running = Arc::clone(running);
+        let interrupted = Arc::clone(interrupted);
+        tokio::spawn(async move {
+            while signals.recv().await.is_some() {
+                interrupted.store(true, Ordering::Relaxed);
+                let pid = running.lock().unwrap_or_else(|e| e.into_inner());
+                match *pid {
+                    // SAFETY: kill has no memory safety requirements; the pid is a child
+                    // awsom hasn't reaped yet, so it can't have been reused
+                    Some(pid) => unsafe {
+                        libc::kill(pid as libc::pid_t, kind.as_raw_value());
+                    },
+                    None => std::process::exit(128 + kind.as_raw_value()),
+                }
+            }
+        });
+    }
     Ok(())
 }
+
+/// A started command, with the threads passing on its output for --redact
+struct Process {
+    child: Child,
+    forwarders: Vec<thread::JoinHandle<io::Result<()>>>,
+}
+
+/// Start the command; with `redact`, its stdout and stderr go through a `Redactor` each,
+/// streamed rather than collected until the command ends
+fn spawn(mut command: Command, redact: Option<&RoleCredentials>) -> io::Result<Process> {
+    let Some(creds) = redact else {
+        return Ok(Process {
+            child: command.spawn()?,
+            forwarders: Vec::new(),
+        });
+    };
+
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().map(|out| {
+        let redactor = Redactor::new(creds);
+        thread::spawn(move || forward_redacted(out, io::stdout(), redactor))
+    });
+    let stderr = child.stderr.take().map(|err| {
+        let redactor = Redactor::new(creds);
+        thread::spawn(move || forward_redacted(err, io::stderr(), redactor))
+    });
+    Ok(Process {
+        child,
+        forwarders: [stdout, stderr].into_iter().flatten().collect(),
+    })
+}
+
+impl Process {
+    fn wait(mut self) -> io::Result<ExitStatus> {
+        let status = self.child.wait();
+        // Output still in the pipes is printed before the exit code is passed on
+        for handle in self.forwarders {
+            if let Ok(Err(e)) = handle.join() {
+                tracing::warn!("Failed to pass on the command's output: {}", e);
+            }
+        }
+        status
+    }
+}
+
+/// Copy a stream of the command's output to ours as it arrives, masking secrets
+fn forward_redacted(
+    mut from: impl Read,
+    mut to: impl Write,
+    mut redactor: Redactor,
+) -> io::Result<()> {
+    let mut buf = [0; 8192];
+    loop {
+        let read = match from.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        to.write_all(&redactor.push(&buf[..read]))?;
+        to.flush()?;
+    }
+    to.write_all(&redactor.finish())?;
+    to.flush()
+}
+
+/// Replaces the role's secret access key and session token in output with `REDACTED`
+///
+/// A secret can be split across reads, so output that ends in the start of one is held
+/// back until the next read shows whether the rest follows.
+struct Redactor {
+    /// Longest first, so a secret containing another is masked whole
+    secrets: Vec<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl Redactor {
+    fn new(creds: &RoleCredentials) -> Self {
+        let mut secrets: Vec<Vec<u8>> = [&creds.secret_access_key, &creds.session_token]
+            .into_iter()
+            .filter(|secret| !secret.is_empty())
+            .map(|secret| secret.as_bytes().to_vec())
+            .collect();
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        Self {
+            secrets,
+            pending: Vec::new(),
+        }
+    }
+
+    /// The output to print for the next chunk read
+    fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+        let mut out = Vec::with_capacity(self.pending.len());
+        let mut at = 0;
+        while at < self.pending.len() {
+            let rest = &self.pending[at..];
+            if let Some(secret) = self.secrets.iter().find(|s| rest.starts_with(s)) {
+                out.extend_from_slice(REDACTED);
+                at += secret.len();
+            } else if self.secrets.iter().any(|s| s.starts_with(rest)) {
+                break;
+            } else {
+                out.push(rest[0]);
+                at += 1;
+            }
+        }
+        self.pending.drain(..at);
+        out
+    }
+
+    /// Whatever was held back, once the stream has ended without completing a secret
+    fn finish(self) -> Vec<u8> {
+        self.pending
+    }
+}
+
+/// Credentials file for a command run with --via-file, for tools that only read one: a
+/// private copy holding the role's credentials, so ~/.aws/credentials isn't touched. It's
+/// overwritten and removed when dropped.
+struct CredentialsFile {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl CredentialsFile {
+    fn create(assumed: &Assumed) -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!("awsom-exec-{}", uuid::Uuid::new_v4()));
+        fs::create_dir(&dir)?;
+        let file = Self {
+            path: dir.join("credentials"),
+            dir,
+        };
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            fs::set_permissions(&file.dir, fs::Permissions::from_mode(0o700))?;
+            options.mode(0o600);
+        }
+        options
+            .open(&file.path)?
+            .write_all(credentials_ini(assumed).as_bytes())?;
+        Ok(file)
+    }
+}
+
+impl Drop for CredentialsFile {
+    fn drop(&mut self) {
+        // Zero the keys in place before unlinking, rather than leave them in freed blocks
+        if let Ok(mut file) = OpenOptions::new().write(true).open(&self.path) {
+            if let Ok(metadata) = file.metadata() {
+                let _ = file.write_all(&vec![0; metadata.len() as usize]);
+                let _ = file.sync_all();
+            }
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// The role's credentials as the default profile, and under its own profile name too so an
+/// AWS_PROFILE pointing at it finds them
+fn credentials_ini(assumed: &Assumed) -> String {
+    let mut profiles = vec!["default"];
+    profiles.extend(assumed.profile.as_deref().filter(|p| *p != "default"));
+    profiles
+        .iter()
+        .map(|profile| {
+            format!(
+                "[{}]\naws_access_key_id = {}\naws_secret_access_key = {}\n\
+                 aws_session_token = {}\n",
+                profile,
+                assumed.creds.access_key_id,
+                assumed.creds.secret_access_key,
+                assumed.creds.session_token
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Exit code to pass on for a command's status: its own, or 128 + the signal that killed
+/// it, as shells report it
+pub(super) fn exit_code_of(status: std::process::ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+/// Run the device flow for a fresh token
+pub async fn reauthenticate(
+    auth: &AuthManager,
+    instance: &SsoInstance,
+    headless: bool,
+) -> Result<SsoToken> {
+    eprintln!("SSO session expired or missing - starting login...");
+    let options = LoginOptions::new().force_refresh(true).headless(headless);
+    let token = auth.login(instance, options).await?;
+    eprintln!("✓ Login successful!");
+    Ok(token)
+}
+
+/// Fetch role credentials with the given token
+pub(super) async fn fetch_credentials(
+    instance: &SsoInstance,
+    token: &SsoToken,
+    role: &AccountRole,
+    duration: Option<Duration>,
+) -> Result<RoleCredentials> {
+    CredentialManager::new()?
+        .get_role_credentials(instance, &token.access_token, role, duration)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_redactor_masks_secrets_split_across_reads() {
+        let creds = RoleCredentials {
+            access_key_id: "ASIAEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI".to_string(),
+            session_token: "IQoJb3JpZ2luX2VjE".to_string(),
+            expiration: Utc::now(),
+        };
+        let mut redactor = Redactor::new(&creds);
+        let mut out = redactor.push(b"key=ASIAEXAMPLE secret=wJalr");
+        // The start of the secret waits for the next read
+        assert_eq!(out, b"key=ASIAEXAMPLE secret=");
+        out.extend(redactor.push(b"XUtnFEMI token=IQoJb3JpZ2luX2VjE\nwJal"));
+        out.extend(redactor.push(b"\n"));
+        out.extend(redactor.push(b"IQoJ"));
+        out.extend(redactor.finish());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "key=ASIAEXAMPLE secret=**** token=****\nwJal\nIQoJ"
+        );
+    }
+}
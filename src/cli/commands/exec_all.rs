@@ -0,0 +1,246 @@
+// exec-all command - run a command once per account with that account's credentials
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::cli::commands::exec::{exit_code_of, reauthenticate};
+use crate::credentials::CredentialManager;
+use crate::env;
+use crate::error::{Result, SsoError};
+use crate::glob::glob_match;
+use crate::models::{AccountRole, SsoInstance};
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// What happened in a single account
+enum Outcome {
+    Succeeded,
+    Failed(i32),
+    /// The account doesn't grant the requested role
+    Skipped,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    role_name: String,
+    filter: Option<String>,
+    session_name: Option<String>,
+    start_url: Option<String>,
+    region: Option<String>,
+    concurrency: usize,
+    command: Vec<String>,
+    headless: bool,
+) -> Result<()> {
+    if command.is_empty() {
+        return Err(SsoError::InvalidConfig("No command specified".to_string()));
+    }
+
+    // Resolve SSO session using the new 4-level priority logic
+    let (start_url, region) = aws_config::resolve_sso_session(
+        session_name.as_deref(),
+        start_url.as_deref(),
+        region.as_deref(),
+    )?;
+
+    let instance = SsoInstance {
+        start_url,
+        region,
+        session_name: None,
+    };
+
+    let is_headless = headless || env::is_headless_environment();
+
+    let auth = AuthManager::new()?;
+    let mut token = match auth.get_cached_token(&instance)? {
+        Some(token) if !token.is_expired() => token,
+        _ => reauthenticate(&auth, &instance, is_headless).await?,
+    };
+
+    let cred_manager = CredentialManager::new()?;
+    let accounts = match cred_manager
+        .list_accounts(&instance.region, &token.access_token)
+        .await
+    {
+        Err(SsoError::AuthExpired) => {
+            token = reauthenticate(&auth, &instance, is_headless).await?;
+            cred_manager
+                .list_accounts(&instance.region, &token.access_token)
+                .await?
+        }
+        result => result?,
+    };
+
+    let mut accounts: Vec<(String, String)> = accounts
+        .into_iter()
+        .filter(|(id, name)| match &filter {
+            Some(pattern) => glob_match(pattern, name) || glob_match(pattern, id),
+            None => true,
+        })
+        .collect();
+    accounts.sort_by(|a, b| a.1.cmp(&b.1));
+
+    if accounts.is_empty() {
+        return Err(SsoError::InvalidConfig(match filter {
+            Some(pattern) => format!("No accounts match '{}'", pattern),
+            None => "No accounts available".to_string(),
+        }));
+    }
+
+    eprintln!(
+        "Running in {} account(s) as {} (concurrency {})...",
+        accounts.len(),
+        role_name,
+        concurrency
+    );
+
+    // Pad prefixes so output from different accounts lines up
+    let width = accounts
+        .iter()
+        .map(|(_, name)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let command = Arc::new(command);
+    let access_token = Arc::new(token.access_token);
+    let mut tasks = JoinSet::new();
+
+    for (account_id, account_name) in accounts {
+        let semaphore = Arc::clone(&semaphore);
+        let command = Arc::clone(&command);
+        let access_token = Arc::clone(&access_token);
+        let instance = instance.clone();
+        let role = AccountRole {
+            account_id,
+            account_name: account_name.clone(),
+            role_name: role_name.clone(),
+        };
+        let prefix = format!("{:<width$}", account_name, width = width);
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let outcome = run_in_account(&instance, &access_token, &role, &command, &prefix).await;
+            (account_name, outcome)
+        });
+    }
+
+    let mut succeeded = 0;
+    let mut skipped = 0;
+    let mut failed: Vec<(String, i32)> = Vec::new();
+
+    while let Some(joined) = tasks.join_next().await {
+        let (account_name, outcome) =
+            joined.map_err(|e| SsoError::ConfigError(format!("Account task panicked: {}", e)))?;
+
+        match outcome {
+            Ok(Outcome::Succeeded) => succeeded += 1,
+            Ok(Outcome::Skipped) => skipped += 1,
+            Ok(Outcome::Failed(code)) => failed.push((account_name, code)),
+            Err(e) => {
+                eprintln!("{} | error: {}", account_name, e);
+                failed.push((account_name, 1));
+            }
+        }
+    }
+
+    eprintln!();
+    eprintln!(
+        "✓ {} succeeded, ✗ {} failed, {} skipped (no {} role)",
+        succeeded,
+        failed.len(),
+        skipped,
+        role_name
+    );
+    for (account_name, code) in &failed {
+        eprintln!("  ✗ {} (exit code {})", account_name, code);
+    }
+
+    // Exit with the highest exit code seen so any failure fails the whole run
+    if let Some(code) = failed.iter().map(|(_, code)| *code).max() {
+        std::process::exit(code);
+    }
+
+    Ok(())
+}
+
+/// Fetch credentials for one account and run the command with prefixed output
+async fn run_in_account(
+    instance: &SsoInstance,
+    access_token: &str,
+    role: &AccountRole,
+    command: &[String],
+    prefix: &str,
+) -> Result<Outcome> {
+    let cred_manager = CredentialManager::new()?;
+    let region = &instance.region;
+
+    let roles = cred_manager
+        .list_account_roles(region, access_token, &role.account_id)
+        .await?;
+    if !roles.iter().any(|r| r == &role.role_name) {
+        return Ok(Outcome::Skipped);
+    }
+
+    let creds = cred_manager
+        .get_role_credentials(instance, access_token, role, None)
+        .await?;
+
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .env("AWS_ACCESS_KEY_ID", &creds.access_key_id)
+        .env("AWS_SECRET_ACCESS_KEY", &creds.secret_access_key)
+        .env("AWS_SESSION_TOKEN", &creds.session_token)
+        .env("AWS_REGION", region)
+        .env("AWS_DEFAULT_REGION", region)
+        // A profile from awsom's own environment would name another account's credentials
+        .env_remove("AWS_PROFILE")
+        .envs(crate::aws_config::file_override_env())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(SsoError::Io)?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| tokio::spawn(forward_lines(out, prefix.to_string(), false)));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| tokio::spawn(forward_lines(err, prefix.to_string(), true)));
+
+    let status = child.wait().await.map_err(SsoError::Io)?;
+
+    // Drain remaining output before reporting the result
+    for handle in [stdout, stderr].into_iter().flatten() {
+        let _ = handle.await;
+    }
+
+    if status.success() {
+        Ok(Outcome::Succeeded)
+    } else {
+        Ok(Outcome::Failed(exit_code_of(status)))
+    }
+}
+
+/// Copy lines from a child stream to our stdout/stderr with an account prefix
+///
+/// Bytes that aren't UTF-8 are replaced rather than ending the output early.
+async fn forward_lines<R: AsyncRead + Unpin>(reader: R, prefix: String, to_stderr: bool) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    while matches!(reader.read_until(b'\n', &mut buf).await, Ok(n) if n > 0) {
+        let bytes = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        let line = String::from_utf8_lossy(bytes);
+        if to_stderr {
+            eprintln!("{} | {}", prefix, line);
+        } else {
+            println!("{} | {}", prefix, line);
+        }
+        buf.clear();
+    }
+}
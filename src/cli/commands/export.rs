@@ -1,10 +1,17 @@
 use crate::auth::AuthManager;
 use crate::aws_config;
-use crate::credentials::CredentialManager;
+use crate::cli::commands::select::{self, Pick, RolePattern};
+use crate::cli::ExportFormat;
+use crate::config::{Config, ProfileMode};
+use crate::credentials::{CredentialCache, CredentialManager};
 use crate::error::{Result, SsoError};
 use crate::models::SsoInstance;
+use crate::output::{ExportedCredentials, OutputFormat};
 use crate::sso_config;
+use crate::usage;
+use chrono::Duration;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn execute(
     account_id: Option<String>,
     account_name: Option<String>,
@@ -13,6 +20,9 @@ pub async fn execute(
     start_url: Option<String>,
     region: Option<String>,
     profile_name: Option<String>,
+    duration: Option<Duration>,
+    pick: Pick,
+    format: ExportFormat,
 ) -> Result<()> {
     // Resolve SSO session using the new 4-level priority logic
     let (start_url, region) = aws_config::resolve_sso_session(
@@ -34,70 +44,121 @@ pub async fn execute(
         .ok_or(SsoError::NoSessionFound)?;
 
     if token.is_expired() {
-        return Err(SsoError::TokenExpired);
+        return Err(SsoError::AuthExpired);
     }
 
-    // Determine account ID
-    let account_id = if let Some(id) = account_id {
-        id
-    } else if let Some(name) = account_name {
-        // Look up account ID by name
-        let cred_manager = CredentialManager::new()?;
-        let accounts = cred_manager
-            .list_accounts(&instance.region, &token.access_token)
-            .await?;
-
-        accounts
-            .into_iter()
-            .find(|(_, acc_name)| acc_name == &name)
-            .map(|(id, _)| id)
-            .ok_or_else(|| SsoError::InvalidConfig(format!("Account '{}' not found", name)))?
-    } else {
-        return Err(SsoError::InvalidConfig(
-            "Either --account-id or --account-name is required".to_string(),
-        ));
+    let pattern = RolePattern {
+        account_id: account_id.as_deref(),
+        account_name: account_name.as_deref(),
+        role_name: &role_name,
     };
+    let role = select::resolve(&instance, &token.access_token, &pattern, pick)
+        .await?
+        .swap_remove(0);
 
     // Get credentials
     let cred_manager = CredentialManager::new()?;
     let creds = cred_manager
-        .get_role_credentials(
-            &instance.region,
-            &token.access_token,
+        .get_role_credentials(&instance, &token.access_token, &role, duration)
+        .await?;
+    let (account_id, role_name) = (role.account_id, role.role_name);
+    usage::record(
+        &Config::load().unwrap_or_default().stats,
+        &account_id,
+        &role_name,
+        profile_name.as_deref(),
+    );
+
+    let powershell = format == ExportFormat::Powershell;
+    let Some(format) = format.output_format() else {
+        // The AWS CLI keys the cache by sso-session name, or by start URL for legacy profiles
+        let session_name = match session_name {
+            Some(name) => Some(name),
+            None => aws_config::read_all_sso_sessions()?
+                .into_iter()
+                .find(|s| s.sso_start_url == instance.start_url)
+                .map(|s| s.session_name),
+        };
+        let path = CredentialCache::new()?.export_for_aws_cli(
+            session_name.as_deref(),
+            &instance.start_url,
             &account_id,
             &role_name,
-        )
-        .await?;
+            &creds,
+        )?;
+
+        eprintln!("✓ Wrote credentials to the AWS CLI cache");
+        eprintln!(
+            "  Used by profiles with sso_account_id = {}, sso_role_name = {} and {}",
+            account_id,
+            role_name,
+            match &session_name {
+                Some(name) => format!("sso_session = {}", name),
+                None => format!("sso_start_url = {}", instance.start_url),
+            }
+        );
+        eprintln!("  Expires: {}", creds.expiration_display());
+        println!("{}", path.display());
+        return Ok(());
+    };
 
     // If profile name specified, write to AWS credentials file
-    if let Some(profile) = profile_name {
+    if let Some(profile) = &profile_name {
         // Use SSO region as default
         let profile_region = &instance.region;
         let output_format = sso_config::get_default_output_format();
 
-        crate::aws_config::write_credentials(&profile, &creds, profile_region, output_format)?;
-        eprintln!("✓ Wrote credentials to ~/.aws/credentials");
-        eprintln!("  Profile: {}", profile);
+        let sso_native = Config::load()?.profile_defaults.profile_mode == ProfileMode::SsoNative;
+        if sso_native {
+            let sso_session = aws_config::sso_native_session(&instance)?;
+            aws_config::write_sso_native_profile(&aws_config::SsoProfile {
+                name: profile.clone(),
+                sso_session: sso_session.clone(),
+                account_id: account_id.clone(),
+                role_name: role_name.clone(),
+                region: profile_region.clone(),
+                output: output_format.map(str::to_string),
+                credential_process: false,
+                source_profile: None,
+            })?;
+            eprintln!("✓ Wrote SSO profile to ~/.aws/config");
+            eprintln!("  Profile: {}", profile);
+            eprintln!(
+                "  Session: {} (the AWS CLI and SDKs fetch credentials with its token)",
+                sso_session
+            );
+        } else {
+            aws_config::write_credentials(profile, &creds, profile_region, output_format)?;
+            eprintln!("✓ Wrote credentials to ~/.aws/credentials");
+            eprintln!("  Profile: {}", profile);
+        }
         eprintln!("  Region: {}", profile_region);
         if let Some(output) = output_format {
             eprintln!("  Output format: {}", output);
         }
-        eprintln!("  Expires: {}", creds.expiration_display());
+        if !sso_native {
+            eprintln!("  Expires: {}", creds.expiration_display());
+        }
         eprintln!("\nUse with: aws s3 ls --profile {}", profile);
-    } else {
-        // Output as shell export commands
-        println!("export AWS_ACCESS_KEY_ID=\"{}\"", creds.access_key_id);
-        println!(
-            "export AWS_SECRET_ACCESS_KEY=\"{}\"",
-            creds.secret_access_key
-        );
-        println!("export AWS_SESSION_TOKEN=\"{}\"", creds.session_token);
-        println!("export AWS_REGION=\"{}\"", instance.region);
-        println!(
-            "# Credentials expire at: {}",
-            creds.expiration.format("%Y-%m-%d %H:%M:%S UTC")
-        );
+
+        // The summary above already covers the text format
+        if format == OutputFormat::Text {
+            return Ok(());
+        }
+    }
+
+    let exported = ExportedCredentials::new(
+        &account_id,
+        &role_name,
+        &instance.region,
+        &creds,
+        profile_name,
+    );
+    if powershell {
+        println!("{}", exported.powershell());
+        return Ok(());
     }
 
-    Ok(())
+    // Text format prints shell export commands
+    format.print(&exported)
 }
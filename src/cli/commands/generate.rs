@@ -0,0 +1,232 @@
+// profile generate command - write SSO profiles for every account/role in the directory
+use crate::auth::AuthManager;
+use crate::aws_config::{self, SsoProfile};
+use crate::cli::ProfileMode;
+use crate::config::Config;
+use crate::credentials::CredentialManager;
+use crate::error::{Result, SsoError};
+use crate::glob::glob_match;
+use crate::models::SsoInstance;
+use crate::profile_template::{self, ProfileNameContext};
+use similar::TextDiff;
+
+pub async fn execute(
+    session_name: Option<String>,
+    filters: Vec<String>,
+    profile_region: Option<String>,
+    output: Option<String>,
+    mode: ProfileMode,
+    source_profile: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let source = match (mode, source_profile) {
+        (ProfileMode::RoleArn, Some(name)) => Some(check_source_profile(name)?),
+        (ProfileMode::RoleArn, None) => {
+            return Err(SsoError::InvalidConfig(
+                "--mode role-arn needs --source-profile".to_string(),
+            ))
+        }
+        (_, Some(_)) => {
+            return Err(SsoError::InvalidConfig(
+                "--source-profile only applies to --mode role-arn".to_string(),
+            ))
+        }
+        (_, None) => None,
+    };
+
+    // Generated profiles point at an [sso-session], so explicit --start-url isn't enough
+    let (start_url, region) = aws_config::resolve_sso_session(session_name.as_deref(), None, None)?;
+    let session = aws_config::read_all_sso_sessions()?
+        .into_iter()
+        .find(|s| match &session_name {
+            Some(name) => &s.session_name == name,
+            None => s.sso_start_url == start_url,
+        })
+        .ok_or_else(|| SsoError::ConfigError("Could not resolve SSO session".to_string()))?;
+
+    let instance = SsoInstance {
+        start_url,
+        region,
+        session_name: Some(session.session_name.clone()),
+    };
+
+    // Get SSO token
+    let auth = AuthManager::new()?;
+    let token = auth
+        .get_cached_token(&instance)?
+        .ok_or(SsoError::NoSessionFound)?;
+
+    if token.is_expired() {
+        return Err(SsoError::AuthExpired);
+    }
+
+    let roles = CredentialManager::new()?
+        .discover_roles(&instance, &token.access_token, |_, done, total| {
+            eprint!("\rDiscovering roles: {}/{} account(s)", done, total)
+        })
+        .await?;
+    eprintln!();
+
+    let defaults = aws_config::read_awsom_defaults()?;
+    let profile_region = profile_region
+        .or_else(|| defaults.as_ref().map(|d| d.region.clone()))
+        .unwrap_or_else(|| instance.region.clone());
+    let output = output.or_else(|| defaults.map(|d| d.output));
+    let name_template = Config::load()?.profile_defaults.name_template;
+
+    let mut profiles = Vec::new();
+    for role in roles {
+        if !matches_filters(
+            &filters,
+            &role.account_id,
+            &role.account_name,
+            &role.role_name,
+        ) {
+            continue;
+        }
+
+        let name = profile_template::render(
+            &name_template,
+            &ProfileNameContext {
+                account_name: &role.account_name,
+                account_id: &role.account_id,
+                role_name: &role.role_name,
+                session_name: Some(&session.session_name),
+            },
+        )?;
+
+        // The source profile can't assume its own role through itself
+        if let Some(source) = &source {
+            if name == source.name
+                || (role.account_id == source.account_id && role.role_name == source.role_name)
+            {
+                eprintln!(
+                    "⚠ Skipped '{}': source profile '{}' already holds this role",
+                    name, source.name
+                );
+                continue;
+            }
+        }
+
+        profiles.push(SsoProfile {
+            name,
+            sso_session: session.session_name.clone(),
+            account_id: role.account_id,
+            role_name: role.role_name,
+            region: profile_region.clone(),
+            output: output.clone(),
+            credential_process: mode == ProfileMode::CredentialProcess,
+            source_profile: source.as_ref().map(|s| s.name.clone()),
+        });
+    }
+
+    if profiles.is_empty() {
+        return Err(SsoError::InvalidConfig(
+            "No accounts/roles match the given filters".to_string(),
+        ));
+    }
+
+    let update = aws_config::write_sso_profiles(&profiles, dry_run)?;
+
+    for name in &update.skipped {
+        eprintln!(
+            "⚠ Skipped '{}': a profile with this name exists in the user-managed section",
+            name
+        );
+    }
+
+    if dry_run {
+        if update.before == update.after {
+            eprintln!("No changes to ~/.aws/config");
+        } else {
+            print!(
+                "{}",
+                TextDiff::from_lines(&update.before, &update.after)
+                    .unified_diff()
+                    .context_radius(2)
+                    .header("~/.aws/config", "~/.aws/config (generated)")
+            );
+        }
+        return Ok(());
+    }
+
+    let kind = match mode {
+        ProfileMode::Sso => "SSO",
+        ProfileMode::CredentialProcess => "credential_process",
+        ProfileMode::RoleArn => "role_arn",
+    };
+    println!(
+        "✓ Wrote {} {} profile(s) for session '{}' to ~/.aws/config",
+        profiles.len() - update.skipped.len(),
+        kind,
+        session.session_name
+    );
+
+    Ok(())
+}
+
+/// The profile `--mode role-arn` profiles take their credentials from
+struct SourceProfile {
+    name: String,
+    account_id: String,
+    role_name: String,
+}
+
+/// The source profile must be one awsom keeps credentials current for
+fn check_source_profile(name: String) -> Result<SourceProfile> {
+    let details = aws_config::get_profile_details(&name)?;
+    let role = details.and_then(|d| d.sso_account_id.zip(d.sso_role_name));
+    let (account_id, role_name) = match role {
+        Some(role) if aws_config::is_profile_in_awsom_section(&name)? => role,
+        _ => {
+            return Err(SsoError::InvalidConfig(format!(
+                "Source profile '{}' is not an awsom-managed SSO profile; create it with \
+                 'awsom profile create' first",
+                name
+            )))
+        }
+    };
+    if !aws_config::has_session_credentials(&name)? {
+        eprintln!(
+            "⚠ '{}' has no credentials in ~/.aws/credentials yet; run 'awsom profile start {}' \
+             so the generated profiles can assume their roles",
+            name, name
+        );
+    }
+    Ok(SourceProfile {
+        name,
+        account_id,
+        role_name,
+    })
+}
+
+/// A role is kept if there are no filters or any filter matches
+///
+/// `account/role` patterns match the two names separately; a pattern without a slash
+/// matches the account name or ID.
+fn matches_filters(filters: &[String], account_id: &str, account_name: &str, role: &str) -> bool {
+    filters.is_empty()
+        || filters.iter().any(|filter| match filter.split_once('/') {
+            Some((account_pattern, role_pattern)) => {
+                (glob_match(account_pattern, account_name)
+                    || glob_match(account_pattern, account_id))
+                    && glob_match(role_pattern, role)
+            }
+            None => glob_match(filter, account_name) || glob_match(filter, account_id),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_filters() {
+        let filters = vec!["prod-*/ReadOnly".to_string(), "sandbox".to_string()];
+        assert!(matches_filters(&filters, "1", "prod-payments", "ReadOnly"));
+        assert!(!matches_filters(&filters, "1", "prod-payments", "Admin"));
+        assert!(matches_filters(&filters, "2", "sandbox", "Admin"));
+        assert!(!matches_filters(&filters, "3", "staging", "ReadOnly"));
+        assert!(matches_filters(&[], "3", "staging", "ReadOnly"));
+    }
+}
@@ -0,0 +1,23 @@
+// hook command - shell function that lets `assume` change the current shell's environment
+use crate::cli::HookShell;
+
+const POSIX_HOOK: &str = r#"assume() {
+  local awsom_exports
+  awsom_exports="$(command awsom assume --shell SHELL "$@")" || return $?
+  eval "$awsom_exports"
+}
+"#;
+
+const FISH_HOOK: &str = r#"function assume --description 'Assume an AWS role with awsom'
+    set -l awsom_exports (command awsom assume --shell fish $argv)
+    or return $status
+    string join \n -- $awsom_exports | source
+end
+"#;
+
+pub fn execute(shell: HookShell) {
+    match shell {
+        HookShell::Bash | HookShell::Zsh => print!("{}", POSIX_HOOK.replace("SHELL", shell.name())),
+        HookShell::Fish => print!("{}", FISH_HOOK),
+    }
+}
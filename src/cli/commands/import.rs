@@ -1,328 +1,225 @@
 // Import command - moves sections from user-managed to awsom-managed area
-use crate::aws_config;
+use crate::aws_config::{self, ImportCandidate};
 use crate::error::{Result, SsoError};
+use crate::output::{ImportResult, ImportResults, OutputFormat};
 use std::io::{self, Write};
 
-pub async fn execute(name: String, section_type: String, force: bool) -> Result<()> {
+pub async fn execute(
+    name: String,
+    section_type: String,
+    force: bool,
+    format: OutputFormat,
+) -> Result<()> {
     // Validate section type
     let section_type = section_type.to_lowercase();
-    if section_type != "profile" && section_type != "sso-session" {
-        return Err(SsoError::ConfigError(
-            "Invalid section type. Must be 'profile' or 'sso-session'".to_string(),
-        ));
-    }
-
-    // Read the config file
-    let config_path = aws_config::config_file_path()?;
-    if !config_path.exists() {
-        return Err(SsoError::ConfigError(
-            "Config file does not exist. Nothing to import.".to_string(),
-        ));
-    }
-
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    // Check if the section exists in user-managed area
-    let (user_section, _awsom_section) = find_section_in_user_area(&content, &name, &section_type)?;
+    let candidate = match section_type.as_str() {
+        "profile" => ImportCandidate::Profile(name.clone()),
+        "sso-session" => ImportCandidate::SsoSession(name.clone()),
+        "credentials" => ImportCandidate::StaticCredentials(name.clone()),
+        _ => {
+            return Err(SsoError::ConfigError(
+                "Invalid section type. Must be 'profile', 'sso-session' or 'credentials'"
+                    .to_string(),
+            ))
+        }
+    };
 
-    if user_section.is_none() {
-        return Err(SsoError::ConfigError(format!(
-            "{} '{}' not found in user-managed section. Nothing to import.",
-            if section_type == "profile" {
-                "Profile"
-            } else {
-                "SSO session"
-            },
-            name
-        )));
-    }
+    let section_lines = match &candidate {
+        ImportCandidate::StaticCredentials(_) => {
+            if !aws_config::import_candidates()?.contains(&candidate) {
+                return Err(SsoError::ConfigError(format!(
+                    "No untracked static credentials for '{}' in ~/.aws/credentials. \
+                     Nothing to import.",
+                    name
+                )));
+            }
+            // Never echo the secret key
+            vec![format!("[{}] (static access keys)", name)]
+        }
+        _ => {
+            // Read the config file
+            let config_path = aws_config::config_file_path()?;
+            if !config_path.exists() {
+                return Err(SsoError::ConfigError(
+                    "Config file does not exist. Nothing to import.".to_string(),
+                ));
+            }
 
-    let (section_name, section_content) = user_section.unwrap();
+            // Check if the section exists in user-managed area
+            let Some(section_lines) = aws_config::find_user_section(&section_name(&candidate))?
+            else {
+                return Err(SsoError::ConfigError(format!(
+                    "{} '{}' not found in user-managed section. Nothing to import.",
+                    if section_type == "profile" {
+                        "Profile"
+                    } else {
+                        "SSO session"
+                    },
+                    name
+                )));
+            };
+            section_lines
+        }
+    };
 
     // Confirm import unless --force is used
     if !force {
-        println!("Found {} to import:", section_type);
-        println!("\n[{}]", section_name);
-        for line in section_content.lines() {
-            if !line.trim().is_empty() {
-                println!("{}", line);
-            }
+        let mut prompt = format!("Found {} to import:\n\n", section_type);
+        for line in section_lines.iter().filter(|line| !line.trim().is_empty()) {
+            prompt.push_str(line);
+            prompt.push('\n');
         }
-        println!();
-        print!("Move this {} to awsom management? (y/N): ", section_type);
-        io::stdout().flush().map_err(SsoError::Io)?;
-
-        let mut response = String::new();
-        io::stdin().read_line(&mut response).map_err(SsoError::Io)?;
-
-        if !response.trim().eq_ignore_ascii_case("y") {
-            println!("Import cancelled.");
-            return Ok(());
+        prompt.push_str(&match candidate {
+            ImportCandidate::StaticCredentials(_) => {
+                "\nTrack these static credentials with awsom? (y/N): ".to_string()
+            }
+            _ => format!("\nMove this {} to awsom management? (y/N): ", section_type),
+        });
+
+        if !ask(&prompt, format)?.eq_ignore_ascii_case("y") {
+            return format.print(&ImportResult {
+                name,
+                section_type,
+                imported: false,
+                error: None,
+            });
         }
     }
 
-    // Perform the import based on section type
-    if section_type == "sso-session" {
-        import_sso_session(&name, &section_content)?;
-        println!("✓ Imported SSO session '{}' to awsom management", name);
-    } else {
-        import_profile(&name, &section_name, &section_content)?;
-        println!("✓ Imported profile '{}' to awsom management", name);
-    }
-
-    println!();
-    println!(
-        "The {} has been moved from user-managed to awsom-managed section.",
-        section_type
-    );
-    println!("It will now be automatically organized and sorted by awsom.");
+    import(&candidate)?;
 
-    Ok(())
+    format.print(&ImportResult {
+        name,
+        section_type,
+        imported: true,
+        error: None,
+    })
 }
 
-/// Find a section in the user-managed area
-/// Returns (Some((section_name, section_content)), awsom_section) if found, (None, awsom_section) if not found
-fn find_section_in_user_area(
-    content: &str,
-    name: &str,
-    section_type: &str,
-) -> Result<(Option<(String, String)>, String)> {
-    use crate::aws_config::{ensure_markers, split_by_marker};
-
-    let content_with_markers = ensure_markers(content);
-    let (user_section, awsom_section) = split_by_marker(&content_with_markers);
+/// Import every user-managed section and untracked static-credentials profile, after
+/// letting the user review the list unless `force` is set
+///
+/// A section that fails to import doesn't stop the others; the failures are listed with
+/// the rest and make the command fail after printing.
+pub async fn execute_all(force: bool, format: OutputFormat) -> Result<()> {
+    let candidates = aws_config::import_candidates()?;
 
-    // Determine the section header to look for
-    let section_header = if section_type == "sso-session" {
-        format!("[sso-session {}]", name)
-    } else if name == "default" {
-        "[default]".to_string()
+    let selected: Vec<bool> = if force || candidates.is_empty() {
+        vec![true; candidates.len()]
     } else {
-        format!("[profile {}]", name)
+        let mut prompt = String::from("Found these sections to import:\n\n");
+        for (i, candidate) in candidates.iter().enumerate() {
+            prompt.push_str(&format!(
+                "  {:>2}. {:<12} {}\n",
+                i + 1,
+                candidate.section_type(),
+                candidate.name()
+            ));
+        }
+        prompt.push_str("\nImport all (y), none (N), or only the listed numbers (e.g. 1 3): ");
+
+        let response = ask(&prompt, format)?;
+        parse_selection(&response, candidates.len()).ok_or_else(|| {
+            SsoError::InvalidConfig(format!(
+                "Invalid selection '{}': expected y, n or numbers from 1 to {}",
+                response,
+                candidates.len()
+            ))
+        })?
     };
 
-    // Parse the user section to find the target
-    let mut found_section: Option<(String, String)> = None;
-    let mut in_target_section = false;
-    let mut section_content = String::new();
-
-    for line in user_section.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // If we were in the target section, save it
-            if in_target_section {
-                found_section = Some((section_header.clone(), section_content.clone()));
-                break;
-            }
-
-            // Check if this is our target section
-            if trimmed == section_header {
-                in_target_section = true;
-                section_content.clear();
-            }
-        } else if in_target_section && !trimmed.is_empty() {
-            section_content.push_str(line);
-            section_content.push('\n');
-        }
+    let mut results = Vec::new();
+    for (candidate, import_it) in candidates.iter().zip(selected) {
+        let error = if import_it {
+            import(candidate).err().map(|e| e.to_string())
+        } else {
+            None
+        };
+        results.push(ImportResult {
+            name: candidate.name().to_string(),
+            section_type: candidate.section_type().to_string(),
+            imported: import_it && error.is_none(),
+            error,
+        });
     }
 
-    // Handle case where target section is the last one
-    if in_target_section && !section_content.is_empty() {
-        found_section = Some((section_header, section_content));
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    format.print(&ImportResults(results))?;
+    if failed > 0 {
+        return Err(SsoError::ConfigError(format!(
+            "{} section(s) could not be imported",
+            failed
+        )));
     }
-
-    Ok((found_section, awsom_section))
+    Ok(())
 }
 
-/// Import an SSO session by parsing its content and calling write_sso_session
-fn import_sso_session(name: &str, content: &str) -> Result<()> {
-    use std::collections::HashMap;
-
-    // Parse the section content
-    let mut properties: HashMap<String, String> = HashMap::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.contains('=') && !trimmed.starts_with('#') {
-            let parts: Vec<&str> = trimmed.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().to_string();
-                let value = parts[1].trim().to_string();
-                properties.insert(key, value);
-            }
-        }
+fn section_name(candidate: &ImportCandidate) -> String {
+    match candidate {
+        ImportCandidate::SsoSession(name) => format!("sso-session {}", name),
+        ImportCandidate::Profile(name) if name == "default" => name.clone(),
+        ImportCandidate::Profile(name) => format!("profile {}", name),
+        // Sections in ~/.aws/credentials are named after the profile
+        ImportCandidate::StaticCredentials(name) => name.clone(),
     }
-
-    // Extract required fields
-    let start_url = properties
-        .get("sso_start_url")
-        .ok_or_else(|| SsoError::ConfigError("SSO session missing sso_start_url".to_string()))?
-        .clone();
-
-    let region = properties
-        .get("sso_region")
-        .ok_or_else(|| SsoError::ConfigError("SSO session missing sso_region".to_string()))?
-        .clone();
-
-    let scopes = properties
-        .get("sso_registration_scopes")
-        .cloned()
-        .unwrap_or_else(|| "sso:account:access".to_string());
-
-    // Create SsoSession and write it (which will place it in awsom-managed section)
-    let session = aws_config::SsoSession {
-        session_name: name.to_string(),
-        sso_start_url: start_url,
-        sso_region: region,
-        sso_registration_scopes: scopes,
-    };
-
-    // Remove from user-managed section first
-    remove_section_from_user_area(name, "sso-session")?;
-
-    // Write to awsom-managed section
-    aws_config::write_sso_session(&session)?;
-
-    Ok(())
 }
 
-/// Import a profile by parsing its content and calling write_credentials_with_metadata
-fn import_profile(profile_name: &str, section_name: &str, content: &str) -> Result<()> {
-    use std::collections::HashMap;
-
-    // Parse the section content
-    let mut properties: HashMap<String, String> = HashMap::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.contains('=') && !trimmed.starts_with('#') {
-            let parts: Vec<&str> = trimmed.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().to_string();
-                let value = parts[1].trim().to_string();
-                properties.insert(key, value);
-            }
-        }
+/// Move a config section as written, comments and all; static credentials stay where they
+/// are and are only marked as tracked (a `[profile x]` for them is imported on its own)
+fn import(candidate: &ImportCandidate) -> Result<()> {
+    match candidate {
+        ImportCandidate::StaticCredentials(name) => aws_config::track_static_credentials(name),
+        _ => aws_config::import_section(&section_name(candidate)),
     }
+}
 
-    // Remove from user-managed section first
-    remove_section_from_user_area(profile_name, "profile")?;
-
-    // Re-write the profile to awsom-managed section
-    // We'll use a simple INI update approach
-    let config_path = aws_config::config_file_path()?;
-    let existing_content = std::fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    use crate::aws_config::{ensure_markers, split_by_marker};
-    let content_with_markers = ensure_markers(&existing_content);
-    let (user_section, awsom_section) = split_by_marker(&content_with_markers);
-
-    // Add this profile to awsom section
-    let mut new_awsom_section = awsom_section;
-    new_awsom_section.push('\n');
-    // Extract the section name without brackets if present
-    let clean_section_name = if section_name.starts_with('[') && section_name.ends_with(']') {
-        &section_name[1..section_name.len() - 1]
+/// Print a prompt (to stderr for machine-readable formats, keeping stdout clean) and read
+/// one line of input
+fn ask(prompt: &str, format: OutputFormat) -> Result<String> {
+    if format.is_machine_readable() {
+        eprint!("{}", prompt);
+        io::stderr().flush().map_err(SsoError::Io)?;
     } else {
-        section_name
-    };
-    new_awsom_section.push_str(&format!("[{}]\n", clean_section_name));
-    for (key, value) in properties {
-        new_awsom_section.push_str(&format!("{} = {}\n", key, value));
-    }
-
-    // Reconstruct the file
-    use crate::aws_config::{
-        cleanup_empty_lines, AWSOM_MANAGED_COMMENT, AWSOM_MANAGED_MARKER, USER_MANAGED_COMMENT,
-        USER_MANAGED_MARKER,
-    };
-    let mut result = user_section;
-    result.push_str(USER_MANAGED_MARKER);
-    result.push('\n');
-    result.push_str(USER_MANAGED_COMMENT);
-    result.push_str("\n\n");
-    result.push_str(AWSOM_MANAGED_MARKER);
-    result.push('\n');
-    result.push_str(AWSOM_MANAGED_COMMENT);
-    result.push('\n');
-    if !new_awsom_section.trim().is_empty() {
-        result.push('\n');
-        result.push_str(&new_awsom_section);
+        print!("{}", prompt);
+        io::stdout().flush().map_err(SsoError::Io)?;
     }
 
-    std::fs::write(&config_path, cleanup_empty_lines(&result))
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
-
-    Ok(())
+    let mut response = String::new();
+    io::stdin().read_line(&mut response).map_err(SsoError::Io)?;
+    Ok(response.trim().to_string())
 }
 
-/// Remove a section from the user-managed area
-fn remove_section_from_user_area(name: &str, section_type: &str) -> Result<()> {
-    use crate::aws_config::{ensure_markers, split_by_marker};
-
-    let config_path = aws_config::config_file_path()?;
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| SsoError::ConfigError(format!("Failed to read config file: {}", e)))?;
-
-    let content_with_markers = ensure_markers(&content);
-    let (user_section, awsom_section) = split_by_marker(&content_with_markers);
-
-    // Determine the section header to remove
-    let section_header = if section_type == "sso-session" {
-        format!("[sso-session {}]", name)
-    } else if name == "default" {
-        "[default]".to_string()
-    } else {
-        format!("[profile {}]", name)
-    };
-
-    // Remove the section from user_section
-    let mut new_user_section = String::new();
-    let mut in_target_section = false;
-
-    for line in user_section.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            // Check if we're entering or leaving the target section
-            if trimmed == section_header {
-                in_target_section = true;
-                continue; // Skip this line
-            } else {
-                in_target_section = false;
-            }
-        }
+/// Which of `count` items to import: "y" for all, "n" or nothing for none, or 1-based
+/// numbers separated by spaces or commas; None if the input is none of these
+fn parse_selection(response: &str, count: usize) -> Option<Vec<bool>> {
+    match response.to_lowercase().as_str() {
+        "y" | "yes" => return Some(vec![true; count]),
+        "" | "n" | "no" => return Some(vec![false; count]),
+        _ => {}
+    }
 
-        if !in_target_section {
-            new_user_section.push_str(line);
-            new_user_section.push('\n');
+    let mut selected = vec![false; count];
+    for part in response.split(|c: char| c == ',' || c.is_whitespace()) {
+        if part.is_empty() {
+            continue;
         }
+        let number: usize = part.parse().ok()?;
+        *selected.get_mut(number.checked_sub(1)?)? = true;
     }
+    Some(selected)
+}
 
-    // Reconstruct the file without the removed section
-    use crate::aws_config::{
-        AWSOM_MANAGED_COMMENT, AWSOM_MANAGED_MARKER, USER_MANAGED_COMMENT, USER_MANAGED_MARKER,
-    };
-    let mut result = new_user_section;
-    result.push_str(USER_MANAGED_MARKER);
-    result.push('\n');
-    result.push_str(USER_MANAGED_COMMENT);
-    result.push_str("\n\n");
-    result.push_str(AWSOM_MANAGED_MARKER);
-    result.push('\n');
-    result.push_str(AWSOM_MANAGED_COMMENT);
-    result.push('\n');
-    if !awsom_section.trim().is_empty() {
-        result.push('\n');
-        result.push_str(&awsom_section);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("Y", 2), Some(vec![true, true]));
+        assert_eq!(parse_selection("", 2), Some(vec![false, false]));
+        assert_eq!(parse_selection("1, 3", 3), Some(vec![true, false, true]));
+        assert_eq!(parse_selection("4", 3), None);
+        assert_eq!(parse_selection("0", 3), None);
+        assert_eq!(parse_selection("maybe", 3), None);
     }
-
-    use crate::aws_config::cleanup_empty_lines;
-    std::fs::write(&config_path, cleanup_empty_lines(&result))
-        .map_err(|e| SsoError::ConfigError(format!("Failed to write config file: {}", e)))?;
-
-    Ok(())
 }
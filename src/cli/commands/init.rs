@@ -0,0 +1,224 @@
+// init command - guided first-run setup in the terminal
+use crate::aws_config::{self, DefaultConfig, SsoSession};
+use crate::cli::commands::{generate, login};
+use crate::cli::ProfileMode;
+use crate::error::{Result, SsoError};
+use crate::regions;
+use std::io::{self, IsTerminal, Write};
+
+/// Walk through adding an SSO session, profile defaults, the first login and, optionally,
+/// a profile for every role; `start_url`/`region` pre-fill the first answers
+pub async fn execute(
+    start_url: Option<String>,
+    region: Option<String>,
+    headless: bool,
+) -> Result<()> {
+    println!("=== awsom setup ===");
+
+    let existing = aws_config::read_all_sso_sessions()?;
+    if !existing.is_empty() {
+        println!("\nSSO sessions already in ~/.aws/config:");
+        for session in &existing {
+            println!("  - {} ({})", session.session_name, session.sso_start_url);
+        }
+        if !confirm("\nAdd another SSO session?", false)? {
+            println!("Nothing changed. Run 'awsom session login' to sign in.");
+            return Ok(());
+        }
+    }
+
+    println!("\n[1/4] SSO session");
+    let start_url = loop {
+        let url = ask(
+            "SSO start URL (e.g. https://my-org.awsapps.com/start)",
+            start_url.as_deref(),
+        )?;
+        if url.starts_with("https://") {
+            break url;
+        }
+        println!("  The start URL must begin with https://");
+    };
+    let sso_region = ask_region("SSO region", region.as_deref().unwrap_or("us-east-1"))?;
+    let session_name = loop {
+        let name = ask("Session name", Some(&session_name_from_url(&start_url)))?;
+        if name.contains(char::is_whitespace) {
+            println!("  Session names can't contain spaces");
+        } else if existing.iter().any(|s| s.session_name == name) {
+            println!("  A session named '{}' already exists", name);
+        } else {
+            break name;
+        }
+    };
+
+    aws_config::write_sso_session(&SsoSession {
+        session_name: session_name.clone(),
+        sso_start_url: start_url.clone(),
+        sso_region: sso_region.clone(),
+        sso_registration_scopes: aws_config::DEFAULT_REGISTRATION_SCOPES.to_string(),
+    })?;
+    println!("✓ Added [sso-session {}] to ~/.aws/config", session_name);
+
+    println!("\n[2/4] Defaults for new profiles");
+    let defaults = aws_config::read_awsom_defaults()?;
+    let profile_region = ask_region(
+        "Region",
+        defaults.as_ref().map_or(&sso_region, |d| &d.region),
+    )?;
+    let output = loop {
+        let output = ask(
+            "Output format (json, yaml, yaml-stream, text, table)",
+            Some(defaults.as_ref().map_or("json", |d| &d.output)),
+        )?;
+        match regions::check_output(&output) {
+            Ok(()) => break output,
+            Err(e) => println!("  {}", capitalize(&e)),
+        }
+    };
+    aws_config::write_awsom_defaults(&DefaultConfig {
+        region: profile_region,
+        output,
+    })?;
+    println!("✓ Saved profile defaults");
+
+    println!("\n[3/4] Sign in");
+    if !confirm("Log in to this session now?", true)? {
+        println!(
+            "\nSetup saved. Run 'awsom session login --session-name {}' when ready.",
+            session_name
+        );
+        return Ok(());
+    }
+    login::execute(Some(start_url), Some(sso_region), false, headless).await?;
+
+    println!("\n[4/4] Profiles");
+    if confirm(
+        "Write a profile to ~/.aws/config for every account and role?",
+        false,
+    )? {
+        generate::execute(
+            Some(session_name),
+            Vec::new(),
+            None,
+            None,
+            ProfileMode::Sso,
+            None,
+            false,
+        )
+        .await?;
+    }
+
+    println!(
+        "\n✓ Setup complete. Run 'awsom' to open the TUI or 'awsom profile list' to see roles."
+    );
+    Ok(())
+}
+
+/// Offered when a command fails because no SSO session is configured: run the wizard if
+/// the user agrees, otherwise hand back the original error
+pub async fn offer(headless: bool) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(SsoError::NotConfigured);
+    }
+
+    eprintln!("No SSO session is configured yet.");
+    if !confirm("Set one up now with 'awsom init'?", true)? {
+        return Err(SsoError::NotConfigured);
+    }
+
+    execute(None, None, headless).await?;
+    eprintln!("\nRun your command again to use the new session.");
+    Ok(())
+}
+
+/// A session name from the start URL's subdomain: https://my-org.awsapps.com/start → my-org
+fn session_name_from_url(start_url: &str) -> String {
+    start_url
+        .trim_start_matches("https://")
+        .split(['.', '/'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("default-sso")
+        .to_string()
+}
+
+/// Ask for a value, returning `default` for an empty answer; an empty answer without a
+/// default asks again
+fn ask(question: &str, default: Option<&str>) -> Result<String> {
+    loop {
+        let answer = match default {
+            Some(default) => read_answer(&format!("{} [{}]: ", question, default))?,
+            None => read_answer(&format!("{}: ", question))?,
+        };
+
+        match (answer.is_empty(), default) {
+            (true, Some(default)) => return Ok(default.to_string()),
+            (true, None) => continue,
+            (false, _) => return Ok(answer),
+        }
+    }
+}
+
+/// Ask until the answer looks like an AWS region, warning about one awsom doesn't know
+fn ask_region(question: &str, default: &str) -> Result<String> {
+    loop {
+        let region = ask(question, Some(default))?;
+        match regions::check_region(&region) {
+            Ok(()) => {
+                if let Some(warning) = regions::region_warning(&region) {
+                    println!("  ⚠ {}", warning);
+                }
+                return Ok(region);
+            }
+            Err(e) => println!("  {}", capitalize(&e)),
+        }
+    }
+}
+
+fn capitalize(message: &str) -> String {
+    let mut chars = message.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+fn confirm(question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "Y/n" } else { "y/N" };
+    let answer = read_answer(&format!("{} ({}): ", question, hint))?.to_lowercase();
+    Ok(match answer.as_str() {
+        "" => default,
+        answer => answer == "y" || answer == "yes",
+    })
+}
+
+/// Print a prompt and read one trimmed line; end of input cancels the setup rather than
+/// looping on empty answers
+fn read_answer(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush().map_err(SsoError::Io)?;
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).map_err(SsoError::Io)? == 0 {
+        return Err(SsoError::InvalidConfig(
+            "Setup cancelled: no more input".to_string(),
+        ));
+    }
+    Ok(answer.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_name_from_url() {
+        assert_eq!(
+            session_name_from_url("https://my-org.awsapps.com/start"),
+            "my-org"
+        );
+        assert_eq!(
+            session_name_from_url("https://d-1234567890.awsapps.com/start/"),
+            "d-1234567890"
+        );
+        assert_eq!(session_name_from_url("https://"), "default-sso");
+    }
+}
@@ -1,14 +1,17 @@
 use crate::auth::AuthManager;
 use crate::aws_config;
-use crate::credentials::CredentialFetcher;
+use crate::config::Config;
+use crate::credentials::CredentialManager;
 use crate::error::{Result, SsoError};
-use crate::models::{AccountRole, SsoInstance};
+use crate::models::SsoInstance;
+use crate::output::{OutputFormat, RoleList};
 
 pub async fn execute(
     session_name: Option<String>,
     start_url: Option<String>,
     region: Option<String>,
-    format: String,
+    tags: Vec<String>,
+    format: OutputFormat,
 ) -> Result<()> {
     // Resolve SSO session using the new 4-level priority logic
     let (start_url, region) = aws_config::resolve_sso_session(
@@ -30,40 +33,21 @@ pub async fn execute(
         .ok_or(SsoError::NoSessionFound)?;
 
     if token.is_expired() {
-        return Err(SsoError::TokenExpired);
+        return Err(SsoError::AuthExpired);
     }
 
     // List accounts and roles
-    let fetcher = CredentialFetcher::new(&region).await?;
-    let accounts = fetcher.list_accounts(&token.access_token).await?;
-
-    let mut roles = Vec::new();
-    for (account_id, account_name) in accounts {
-        let account_roles = fetcher
-            .list_account_roles(&token.access_token, &account_id)
-            .await?;
-
-        for role_name in account_roles {
-            roles.push(AccountRole {
-                account_id: account_id.clone(),
-                account_name: account_name.clone(),
-                role_name,
-            });
-        }
-    }
-
-    // Output
-    if format == "json" {
-        println!("{}", serde_json::to_string_pretty(&roles)?);
-    } else {
-        println!("Available accounts and roles:\n");
-        for role in roles {
-            println!(
-                "  {} ({}): {}",
-                role.account_name, role.account_id, role.role_name
-            );
-        }
+    let mut roles = CredentialManager::new()?
+        .list_roles(&instance, &token.access_token)
+        .await?;
+
+    if !tags.is_empty() {
+        let config = Config::load()?;
+        roles.retain(|role| {
+            tags.iter()
+                .all(|tag| config.has_tag(&role.account_id, &role.role_name, tag))
+        });
     }
 
-    Ok(())
+    format.print(&RoleList(roles))
 }
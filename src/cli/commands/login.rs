@@ -1,7 +1,8 @@
-use crate::auth::AuthManager;
+use crate::auth::{AuthManager, LoginOptions};
 use crate::env;
 use crate::error::Result;
 use crate::models::SsoInstance;
+use crate::prefetch;
 use crate::sso_config;
 
 pub async fn execute(
@@ -23,10 +24,45 @@ pub async fn execute(
     let is_headless = headless || env::is_headless_environment();
 
     let auth = AuthManager::new()?;
-    let token = auth.login(&instance, force, is_headless).await?;
+    let options = LoginOptions::new()
+        .force_refresh(force)
+        .headless(is_headless);
+    let token = auth.login(&instance, options).await?;
 
     println!("✓ Login successful!");
     println!("  Token expires in: {}", token.expiration_display());
 
+    prefetch_favorites(&instance, &token.access_token).await;
     Ok(())
 }
+
+/// Write credentials for the favorites picked for prefetching; failures don't fail the login
+async fn prefetch_favorites(instance: &SsoInstance, access_token: &str) {
+    let roles = match prefetch::roles(instance, access_token).await {
+        Ok(roles) if roles.is_empty() => return,
+        Ok(roles) => roles,
+        Err(e) => {
+            eprintln!("⚠ Could not list favorite roles to prefetch: {}", e);
+            return;
+        }
+    };
+
+    println!("  Prefetching {} favorite role(s):", roles.len());
+    let result = prefetch::fetch(instance, access_token, roles, |role, result, _| {
+        let written = result.and_then(|creds| {
+            prefetch::write_profile(instance, &role, &creds).map(|profile| (profile, creds))
+        });
+        match written {
+            Ok((profile, creds)) => println!(
+                "  ✓ {} (expires in {})",
+                profile,
+                creds.expiration_display()
+            ),
+            Err(e) => println!("  ✗ {} / {}: {}", role.account_name, role.role_name, e),
+        }
+    })
+    .await;
+    if let Err(e) = result {
+        eprintln!("⚠ Prefetching stopped: {}", e);
+    }
+}
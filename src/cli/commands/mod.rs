@@ -1,11 +1,35 @@
+pub mod apps;
+pub mod assume;
+pub mod audit;
+pub mod cache;
 pub mod completions;
+pub mod config;
 pub mod console;
+pub mod create;
+pub mod credential_process;
+pub mod doctor;
+pub mod ecr;
+pub mod eks;
+pub mod emit;
+pub mod env;
 pub mod exec;
+pub mod exec_all;
 pub mod export;
+pub mod generate;
+pub mod hook;
 pub mod import;
+pub mod init;
 pub mod list;
 pub mod login;
 pub mod logout;
+pub mod pick;
 pub mod profile;
+pub mod prompt;
+pub mod rollback;
+pub mod run;
+pub mod select;
+pub mod serve;
 pub mod session;
+pub mod shell;
+pub mod stats;
 pub mod status;
@@ -0,0 +1,336 @@
+// pick command - inline fuzzy selector for an account/role
+//
+// Draws a query line and a short list under the cursor on stderr instead of taking over
+// the screen, so stdout stays free for `assume $(awsom pick)`. The chosen role is printed
+// as <account>/<role>, or handed to `profile exec`, `profile export` or `profile console`.
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::cli::commands::exec::reauthenticate;
+use crate::cli::commands::select::Pick;
+use crate::cli::commands::{console, exec, export};
+use crate::cli::ExportFormat;
+use crate::credentials::CredentialManager;
+use crate::env;
+use crate::error::{Result, SsoError};
+use crate::models::{AccountRole, SsoInstance};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType},
+};
+use std::io::{self, IsTerminal, Write};
+
+/// Matches listed under the query line
+const VISIBLE_ROWS: usize = 10;
+
+/// What to do with the chosen role
+pub enum PickAction {
+    Print,
+    Exec(Vec<String>),
+    Export,
+    Console,
+}
+
+pub async fn execute(
+    session_name: Option<String>,
+    start_url: Option<String>,
+    region: Option<String>,
+    action: PickAction,
+    headless: bool,
+) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(SsoError::InvalidConfig(
+            "awsom pick needs a terminal; use 'awsom profile list' in scripts".to_string(),
+        ));
+    }
+
+    let roles = load_roles(
+        session_name.as_deref(),
+        start_url.as_deref(),
+        region.as_deref(),
+        headless,
+    )
+    .await?;
+    if roles.is_empty() {
+        return Err(SsoError::AccountRoleNotFound);
+    }
+
+    let Some(role) = select(&roles)? else {
+        // Cancelled: nothing chosen, nothing to do
+        std::process::exit(130);
+    };
+
+    match action {
+        PickAction::Print => {
+            println!("{}", role.display_name());
+            Ok(())
+        }
+        PickAction::Exec(command) => {
+            exec::execute(
+                Some(role.account_id.clone()),
+                None,
+                role.role_name.clone(),
+                session_name,
+                start_url,
+                region,
+                None,
+                Vec::new(),
+                None,
+                Pick::First,
+                command,
+                false,
+                false,
+                headless,
+            )
+            .await
+        }
+        PickAction::Export => {
+            export::execute(
+                Some(role.account_id.clone()),
+                None,
+                role.role_name.clone(),
+                session_name,
+                start_url,
+                region,
+                None,
+                None,
+                Pick::First,
+                ExportFormat::default(),
+            )
+            .await
+        }
+        PickAction::Console => {
+            console::execute(
+                Some(role.account_id.clone()),
+                None,
+                role.role_name.clone(),
+                session_name,
+                start_url,
+                region,
+                None,
+                None,
+                None,
+                Pick::First,
+                console::UrlAction::Open,
+            )
+            .await
+        }
+    }
+}
+
+/// Every role of the session, logging in first if needed
+async fn load_roles(
+    session_name: Option<&str>,
+    start_url: Option<&str>,
+    region: Option<&str>,
+    headless: bool,
+) -> Result<Vec<AccountRole>> {
+    let (start_url, region) = aws_config::resolve_sso_session(session_name, start_url, region)?;
+    let instance = SsoInstance {
+        start_url,
+        region,
+        session_name: session_name.map(str::to_string),
+    };
+
+    let is_headless = headless || env::is_headless_environment();
+    let auth = AuthManager::new()?;
+    let token = match auth.get_cached_token(&instance)? {
+        Some(token) if !token.is_expired() => token,
+        _ => reauthenticate(&auth, &instance, is_headless).await?,
+    };
+
+    let manager = CredentialManager::new()?;
+    match manager.list_roles(&instance, &token.access_token).await {
+        Err(SsoError::AuthExpired) => {
+            let token = reauthenticate(&auth, &instance, is_headless).await?;
+            manager.list_roles(&instance, &token.access_token).await
+        }
+        result => result,
+    }
+}
+
+/// Restores the terminal however the picker ends
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        terminal::enable_raw_mode().map_err(SsoError::Io)?;
+        Ok(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let mut stderr = io::stderr();
+        let _ = queue!(
+            stderr,
+            cursor::MoveToColumn(0),
+            Clear(ClearType::FromCursorDown)
+        );
+        let _ = stderr.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Run the picker; None if the user cancelled
+fn select(roles: &[AccountRole]) -> Result<Option<&AccountRole>> {
+    let _raw = RawMode::enable()?;
+    let mut stderr = io::stderr();
+    let mut query = String::new();
+    let mut selected = 0;
+
+    loop {
+        let matches = filter(roles, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+        draw(&mut stderr, &query, roles, &matches, selected)?;
+
+        let Event::Key(key) = event::read().map_err(SsoError::Io)? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') | KeyCode::Char('d') if ctrl => return Ok(None),
+            KeyCode::Enter => return Ok(matches.get(selected).map(|&i| &roles[i])),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => selected += 1,
+            KeyCode::Char('n') if ctrl => selected += 1,
+            KeyCode::Char('u') if ctrl => query.clear(),
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Redraw the query line and the visible matches below it, leaving the cursor at the end
+/// of the query
+fn draw(
+    out: &mut impl Write,
+    query: &str,
+    roles: &[AccountRole],
+    matches: &[usize],
+    selected: usize,
+) -> Result<()> {
+    let width = match terminal::size() {
+        Ok((width, _)) if width > 0 => width as usize,
+        _ => 80,
+    };
+    // Scroll the window along with the selection
+    let first = selected.saturating_sub(VISIBLE_ROWS - 1);
+    let shown = matches.iter().enumerate().skip(first).take(VISIBLE_ROWS);
+
+    let prompt = format!("pick> {}", query);
+    queue!(
+        out,
+        cursor::MoveToColumn(0),
+        Clear(ClearType::FromCursorDown),
+        Print(&prompt),
+        SetAttribute(Attribute::Dim),
+        Print(format!("  {}/{}", matches.len(), roles.len())),
+        SetAttribute(Attribute::Reset)
+    )
+    .map_err(SsoError::Io)?;
+
+    let mut rows = 0;
+    for (position, &index) in shown {
+        let line: String = label(&roles[index])
+            .chars()
+            .take(width.saturating_sub(3))
+            .collect();
+        queue!(out, Print("\r\n")).map_err(SsoError::Io)?;
+        if position == selected {
+            queue!(
+                out,
+                SetAttribute(Attribute::Reverse),
+                Print(format!("> {}", line)),
+                SetAttribute(Attribute::Reset)
+            )
+        } else {
+            queue!(out, Print(format!("  {}", line)))
+        }
+        .map_err(SsoError::Io)?;
+        rows += 1;
+    }
+
+    if rows > 0 {
+        queue!(out, cursor::MoveUp(rows)).map_err(SsoError::Io)?;
+    }
+    queue!(out, cursor::MoveToColumn(prompt.chars().count() as u16)).map_err(SsoError::Io)?;
+    out.flush().map_err(SsoError::Io)
+}
+
+fn label(role: &AccountRole) -> String {
+    format!(
+        "{} / {}  ({})",
+        role.account_name, role.role_name, role.account_id
+    )
+}
+
+/// Indices of the roles matching `query`, best match first
+fn filter(roles: &[AccountRole], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = roles
+        .iter()
+        .enumerate()
+        .filter_map(|(i, role)| fuzzy_score(query, &label(role)).map(|score| (score, i)))
+        .collect();
+    // Stable, so equal scores keep the account/role order
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Score `text` against each whitespace-separated term of `query`, which must all appear
+/// in it as subsequences (ignoring case); None if one doesn't. Runs of consecutive
+/// characters and characters starting a word score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    for term in query.to_lowercase().split_whitespace() {
+        let mut next = 0;
+        let mut previous: Option<usize> = None;
+        for c in term.chars() {
+            let i = next + text[next..].iter().position(|&t| t == c)?;
+            score += 1;
+            if previous.is_some_and(|p| p + 1 == i) {
+                score += 5;
+            }
+            if i == 0 || !text[i - 1].is_alphanumeric() {
+                score += 3;
+            }
+            previous = Some(i);
+            next = i + 1;
+        }
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("prdadm", "production / AdminAccess").is_some());
+        assert!(fuzzy_score("prod admin", "production / AdminAccess").is_some());
+        assert!(fuzzy_score("admin prod", "production / AdminAccess").is_some());
+        assert!(fuzzy_score("xyz", "production / AdminAccess").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+
+        // A contiguous match beats a scattered one
+        assert!(
+            fuzzy_score("dev", "dev / ReadOnly").unwrap()
+                > fuzzy_score("dev", "data-eng / View").unwrap()
+        );
+    }
+}
@@ -1,23 +1,103 @@
 // Profile management CLI commands
+use crate::aws_config::ConflictPolicy;
+use crate::cli::commands::console::UrlAction;
+use crate::cli::commands::create;
+use crate::cli::commands::select::Pick;
 use crate::cli::ProfileCommands;
-use crate::error::Result;
+use crate::error::{Result, SsoError};
+use crate::output::{CredentialState, OutputFormat, ProfileShow};
+use std::io::{self, Write};
 
 pub async fn execute(
     command: ProfileCommands,
     start_url: Option<String>,
     region: Option<String>,
+    headless: bool,
 ) -> Result<()> {
     match command {
         ProfileCommands::List {
             session_name,
+            tag,
             format,
-        } => crate::cli::commands::list::execute(session_name, start_url, region, format).await,
-        ProfileCommands::Start { profile_name } => profile_start(profile_name).await,
+        } => {
+            crate::cli::commands::list::execute(session_name, start_url, region, tag, format).await
+        }
+        ProfileCommands::Create {
+            account_id,
+            account_name,
+            role_name,
+            session_name,
+            name,
+            region: profile_region,
+            output,
+            on_conflict,
+        } => {
+            crate::cli::commands::create::execute(
+                account_id,
+                account_name,
+                role_name,
+                session_name,
+                start_url,
+                region,
+                name,
+                profile_region,
+                output,
+                on_conflict,
+            )
+            .await
+        }
+        ProfileCommands::Generate {
+            all: _,
+            filter,
+            session_name,
+            region: profile_region,
+            output,
+            mode,
+            source_profile,
+            dry_run,
+        } => {
+            crate::cli::commands::generate::execute(
+                session_name,
+                filter,
+                profile_region,
+                output,
+                mode,
+                source_profile,
+                dry_run,
+            )
+            .await
+        }
+        ProfileCommands::Start {
+            profile_name: Some(profile_name),
+            on_conflict,
+            ..
+        } => profile_start(profile_name, on_conflict).await,
+        ProfileCommands::Start { .. } => profile_start_all_expired().await,
+        ProfileCommands::Show {
+            profile_name,
+            format,
+        } => profile_show(profile_name, format),
+        ProfileCommands::Stop {
+            profile_name,
+            force,
+        } => profile_stop(profile_name, force),
+        ProfileCommands::Delete {
+            profile_name,
+            purge_config,
+            force,
+        } => profile_delete(profile_name, purge_config, force),
         ProfileCommands::Exec {
             account_id,
             account_name,
             role_name,
             session_name,
+            region: aws_region,
+            env_vars,
+            duration,
+            first,
+            all,
+            via_file,
+            redact,
             command,
         } => {
             crate::cli::commands::exec::execute(
@@ -27,7 +107,14 @@ pub async fn execute(
                 session_name,
                 start_url,
                 region,
+                aws_region,
+                env_vars,
+                duration,
+                Pick::from_flags(first, all),
                 command,
+                via_file,
+                redact,
+                headless,
             )
             .await
         }
@@ -37,6 +124,9 @@ pub async fn execute(
             role_name,
             session_name,
             profile,
+            duration,
+            first,
+            format,
         } => {
             crate::cli::commands::export::execute(
                 account_id,
@@ -46,6 +136,9 @@ pub async fn execute(
                 start_url,
                 region,
                 profile,
+                duration,
+                Pick::from_flags(first, false),
+                format,
             )
             .await
         }
@@ -55,6 +148,12 @@ pub async fn execute(
             role_name,
             session_name,
             region: console_region,
+            duration,
+            console_duration,
+            first,
+            all,
+            url_only,
+            copy,
         } => {
             crate::cli::commands::console::execute(
                 account_id,
@@ -64,16 +163,19 @@ pub async fn execute(
                 start_url,
                 region,
                 console_region,
+                duration,
+                console_duration,
+                Pick::from_flags(first, all),
+                UrlAction::from_flags(url_only, copy),
             )
             .await
         }
     }
 }
 
-async fn profile_start(profile_name: String) -> Result<()> {
+async fn profile_start(profile_name: String, on_conflict: Option<ConflictPolicy>) -> Result<()> {
     use crate::aws_config;
     use crate::credentials::CredentialManager;
-    use crate::error::SsoError;
     use crate::models::AccountRole;
 
     println!("Refreshing credentials for profile '{}'...", profile_name);
@@ -149,21 +251,22 @@ async fn profile_start(profile_name: String) -> Result<()> {
 
     println!("✓ Found valid SSO token");
 
+    let account_role = AccountRole {
+        account_id: account_id.clone(),
+        account_name: account_id.clone(), // We don't have the friendly name here
+        role_name: role_name.clone(),
+    };
+
     // Step 5: Fetch fresh credentials
     let credential_manager = CredentialManager::new()?;
     let credentials = credential_manager
-        .get_role_credentials(&sso_region, &token.access_token, &account_id, &role_name)
+        .refresh_credentials(&sso_instance, &token.access_token, &account_role, None)
         .await?;
 
     println!("✓ Fetched temporary credentials");
 
-    // Step 6: Write credentials to file
-    let account_role = AccountRole {
-        account_id: account_id.clone(),
-        account_name: account_id.clone(), // We don't have the friendly name here
-        role_name: role_name.clone(),
-    };
-
+    // Step 6: Write credentials to file, under another name if the user chose one
+    let profile_name = create::resolve_conflict(&profile_name, on_conflict)?;
     aws_config::write_credentials_with_metadata(
         &profile_name,
         &credentials,
@@ -171,6 +274,12 @@ async fn profile_start(profile_name: String) -> Result<()> {
         profile_details.output.as_deref(),
         Some(&account_role),
     )?;
+    crate::usage::record(
+        &crate::config::Config::load().unwrap_or_default().stats,
+        &account_id,
+        &role_name,
+        Some(&profile_name),
+    );
 
     println!("✓ Updated credentials in ~/.aws/credentials");
     println!();
@@ -179,3 +288,282 @@ async fn profile_start(profile_name: String) -> Result<()> {
 
     Ok(())
 }
+
+/// Refresh every awsom-managed profile whose credentials have expired or were stopped.
+/// Profiles of sessions that aren't logged in are skipped rather than starting a login, so
+/// this can run unattended.
+async fn profile_start_all_expired() -> Result<()> {
+    use crate::aws_config;
+    use crate::credentials::CredentialManager;
+
+    // Session credentials (stopped ones keep placeholder keys) past their expiry, or with
+    // none, of profiles in awsom's part of ~/.aws/config
+    let mut expired = Vec::new();
+    for status in aws_config::list_profile_statuses()? {
+        if status.has_credentials
+            && status
+                .expiration
+                .map_or(true, |at| crate::expiry::is_expired(&at))
+            && aws_config::is_profile_in_awsom_section(&status.profile_name)?
+        {
+            expired.push(status.profile_name);
+        }
+    }
+    if expired.is_empty() {
+        println!("No expired profiles.");
+        return Ok(());
+    }
+
+    let token_cache = crate::auth::TokenCache::new()?;
+    let credential_manager = CredentialManager::new()?;
+    let (mut refreshed, mut skipped, mut failed) = (0, 0, 0);
+    for profile_name in &expired {
+        match refresh_expired_profile(profile_name, &token_cache, &credential_manager).await {
+            Ok(Refresh::Done(expiration)) => {
+                println!("✓ {}: valid until {}", profile_name, expiration);
+                refreshed += 1;
+            }
+            Ok(Refresh::Skipped(reason)) => {
+                println!("- {}: skipped, {}", profile_name, reason);
+                skipped += 1;
+            }
+            Err(e) => {
+                println!("✗ {}: {}", profile_name, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "✓ {} refreshed, ✗ {} failed, {} skipped",
+        refreshed, failed, skipped
+    );
+    if failed > 0 {
+        return Err(SsoError::ConfigError(format!(
+            "{} of {} expired profile(s) could not be refreshed",
+            failed,
+            expired.len()
+        )));
+    }
+    Ok(())
+}
+
+/// What happened to one profile of `profile start --all-expired`
+enum Refresh {
+    /// New credentials, valid until this time
+    Done(chrono::DateTime<chrono::Utc>),
+    Skipped(String),
+}
+
+/// Fetch and write new credentials for one expired profile, if its session is logged in
+async fn refresh_expired_profile(
+    profile_name: &str,
+    token_cache: &crate::auth::TokenCache,
+    credential_manager: &crate::credentials::CredentialManager,
+) -> Result<Refresh> {
+    use crate::aws_config;
+
+    let Some(details) = aws_config::get_profile_details(profile_name)? else {
+        return Ok(Refresh::Skipped("not in ~/.aws/config".to_string()));
+    };
+    let (Some(sso_session), Some(account_id), Some(role_name)) = (
+        details.sso_session,
+        details.sso_account_id,
+        details.sso_role_name,
+    ) else {
+        return Ok(Refresh::Skipped("not an SSO profile".to_string()));
+    };
+
+    let (start_url, sso_region) = aws_config::resolve_sso_session(Some(&sso_session), None, None)?;
+    let instance = crate::models::SsoInstance {
+        session_name: Some(sso_session.clone()),
+        start_url,
+        region: sso_region.clone(),
+    };
+    let token = match token_cache.get_token(&instance)? {
+        Some(token) if !token.is_expired() => token,
+        _ => {
+            return Ok(Refresh::Skipped(format!(
+                "session '{}' is not logged in",
+                sso_session
+            )))
+        }
+    };
+
+    let account_role = crate::models::AccountRole {
+        account_name: account_id.clone(),
+        account_id,
+        role_name,
+    };
+    let credentials = credential_manager
+        .refresh_credentials(&instance, &token.access_token, &account_role, None)
+        .await?;
+    aws_config::write_credentials_with_metadata(
+        profile_name,
+        &credentials,
+        details.region.as_deref().unwrap_or(&sso_region),
+        details.output.as_deref(),
+        Some(&account_role),
+    )?;
+    Ok(Refresh::Done(credentials.expiration))
+}
+
+/// Print a profile's ~/.aws/config settings and whether its credentials are usable
+fn profile_show(profile_name: String, format: OutputFormat) -> Result<()> {
+    use crate::aws_config;
+    use chrono::Utc;
+
+    let details = aws_config::get_profile_details(&profile_name)?;
+    let status = aws_config::list_profile_statuses()?
+        .into_iter()
+        .find(|status| status.profile_name == profile_name);
+    if details.is_none() && status.is_none() {
+        return Err(SsoError::ConfigError(format!(
+            "Profile '{}' not found in ~/.aws/credentials or ~/.aws/config",
+            profile_name
+        )));
+    }
+
+    let now = Utc::now();
+    let credentials = match &status {
+        None => CredentialState::Missing,
+        Some(_) if !aws_config::has_session_credentials(&profile_name)? => CredentialState::Static,
+        Some(status) => match status.expiration {
+            Some(expiration) if !crate::expiry::is_expired(&expiration) => CredentialState::Valid,
+            Some(_) => CredentialState::Expired,
+            None => CredentialState::Invalidated,
+        },
+    };
+    let expiration = status.as_ref().and_then(|status| status.expiration);
+    // The role from ~/.aws/config, else from the metadata comments awsom writes above
+    // the credentials
+    let (account_id, role_name) = match &details {
+        Some(details) if details.sso_account_id.is_some() => (
+            details.sso_account_id.clone(),
+            details.sso_role_name.clone(),
+        ),
+        _ => status
+            .as_ref()
+            .map(|status| (status.account_id.clone(), status.role_name.clone()))
+            .unwrap_or_default(),
+    };
+
+    let show = ProfileShow {
+        managed_by_awsom: aws_config::is_profile_in_awsom_section(&profile_name)?,
+        in_config: details.is_some(),
+        region: details.as_ref().and_then(|d| d.region.clone()),
+        output: details.as_ref().and_then(|d| d.output.clone()),
+        sso_session: details.and_then(|d| d.sso_session),
+        sso_account_id: account_id,
+        sso_role_name: role_name,
+        credentials,
+        expires_at: expiration,
+        expires_in_minutes: expiration.map(|at| (at - now).num_minutes().max(0)),
+        name: profile_name,
+    };
+    format.print(&show)
+}
+
+/// Invalidate a profile's credentials, leaving the profile in place for `profile start`
+fn profile_stop(profile_name: String, force: bool) -> Result<()> {
+    use crate::aws_config;
+
+    if !aws_config::has_session_credentials(&profile_name)? {
+        return Err(unmanaged_credentials(&profile_name));
+    }
+
+    if !force
+        && !confirm(&format!(
+            "Invalidate the credentials of profile '{}'?",
+            profile_name
+        ))?
+    {
+        println!("Stop cancelled.");
+        return Ok(());
+    }
+
+    aws_config::invalidate_profile(&profile_name)?;
+    println!("✓ Invalidated credentials of profile '{}'", profile_name);
+    println!(
+        "  Run 'awsom profile start {}' to get new ones",
+        profile_name
+    );
+    Ok(())
+}
+
+/// Delete a profile's credentials, and with `purge_config` its ~/.aws/config section
+fn profile_delete(profile_name: String, purge_config: bool, force: bool) -> Result<()> {
+    use crate::aws_config;
+
+    let has_credentials = aws_config::has_session_credentials(&profile_name)?;
+    let has_static_credentials = !has_credentials
+        && aws_config::list_profile_statuses()?
+            .iter()
+            .any(|status| status.profile_name == profile_name);
+    if has_static_credentials {
+        return Err(unmanaged_credentials(&profile_name));
+    }
+    let in_config = aws_config::get_profile_details(&profile_name)?.is_some();
+    match (has_credentials, in_config, purge_config) {
+        (false, false, _) => {
+            return Err(SsoError::ConfigError(format!(
+                "Profile '{}' not found in ~/.aws/credentials or ~/.aws/config",
+                profile_name
+            )))
+        }
+        (false, true, false) => {
+            return Err(SsoError::ConfigError(format!(
+                "Profile '{}' has no credentials in ~/.aws/credentials; \
+                 pass --purge-config to delete it from ~/.aws/config",
+                profile_name
+            )))
+        }
+        _ => {}
+    }
+
+    let question = if purge_config {
+        format!(
+            "Delete profile '{}' from ~/.aws/credentials and ~/.aws/config?",
+            profile_name
+        )
+    } else {
+        format!(
+            "Delete the credentials of profile '{}' from ~/.aws/credentials?",
+            profile_name
+        )
+    };
+    if !force && !confirm(&question)? {
+        println!("Deletion cancelled.");
+        return Ok(());
+    }
+
+    if purge_config {
+        aws_config::delete_profile(&profile_name)?;
+        println!("✓ Deleted profile '{}'", profile_name);
+    } else {
+        aws_config::delete_profile_credentials(&profile_name)?;
+        println!("✓ Deleted credentials of profile '{}'", profile_name);
+    }
+    Ok(())
+}
+
+/// Error for a profile without session credentials, which also covers long-term keys
+/// in ~/.aws/credentials that awsom never touches
+fn unmanaged_credentials(profile_name: &str) -> SsoError {
+    SsoError::ConfigError(format!(
+        "Profile '{}' has no session credentials in ~/.aws/credentials \
+         (static access keys are left alone)",
+        profile_name
+    ))
+}
+
+/// Ask a yes/no question on stdout; anything but "y" is no
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} (y/N): ", question);
+    io::stdout().flush().map_err(SsoError::Io)?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response).map_err(SsoError::Io)?;
+    Ok(response.trim().eq_ignore_ascii_case("y"))
+}
@@ -0,0 +1,149 @@
+// prompt command - one-line auth summary for PS1/starship, from local caches only
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::error::{Result, SsoError};
+use crate::expiry::format_compact_remaining;
+use crate::models::SsoInstance;
+
+const PROFILE_TEMPLATE: &str = "{profile} {expires}";
+const SESSION_TEMPLATE: &str = "{session} {session_expires}";
+
+/// Values available to a prompt template; missing ones render as empty strings
+#[derive(Debug, Default)]
+struct PromptValues {
+    profile: Option<String>,
+    account: Option<String>,
+    role: Option<String>,
+    region: Option<String>,
+    expires: Option<String>,
+    session: Option<String>,
+    session_expires: Option<String>,
+}
+
+pub fn execute(
+    profile: Option<String>,
+    session_name: Option<String>,
+    template: Option<String>,
+) -> Result<()> {
+    let profile = profile.filter(|p| !p.is_empty());
+    let mut values = PromptValues::default();
+
+    if let Some(profile) = &profile {
+        let details = aws_config::get_profile_details(profile)?;
+        let status = aws_config::list_profile_statuses()?
+            .into_iter()
+            .find(|s| &s.profile_name == profile);
+
+        values.profile = Some(profile.clone());
+        if let Some(details) = details {
+            values.account = details.sso_account_id;
+            values.role = details.sso_role_name;
+            values.region = details.region;
+            values.session = details.sso_session;
+        }
+        if let Some(status) = status {
+            values.account = values.account.or(status.account_id);
+            values.role = values.role.or(status.role_name);
+            values.expires = status.expiration.as_ref().map(format_compact_remaining);
+        }
+    }
+
+    // An explicit session wins over the profile's sso_session
+    if session_name.is_some() {
+        values.session = session_name;
+    }
+    if let Some(name) = &values.session {
+        values.session_expires = session_expiry(name)?;
+    }
+
+    let template = match (template, &profile, &values.session) {
+        (Some(template), _, _) => template,
+        (None, Some(_), _) => PROFILE_TEMPLATE.to_string(),
+        (None, None, Some(_)) => SESSION_TEMPLATE.to_string(),
+        // Nothing selected: print nothing so the prompt stays clean
+        (None, None, None) => return Ok(()),
+    };
+
+    println!("{}", render(&template, &values)?);
+    Ok(())
+}
+
+/// Remaining lifetime of a session's cached token, None if not logged in
+fn session_expiry(session_name: &str) -> Result<Option<String>> {
+    let Some(session) = aws_config::read_all_sso_sessions()?
+        .into_iter()
+        .find(|s| s.session_name == session_name)
+    else {
+        return Ok(None);
+    };
+
+    let instance = SsoInstance {
+        start_url: session.sso_start_url,
+        region: session.sso_region,
+        session_name: Some(session.session_name),
+    };
+    let token = AuthManager::new()?.get_cached_token(&instance)?;
+    Ok(token.map(|t| format_compact_remaining(&t.expires_at)))
+}
+
+fn render(template: &str, values: &PromptValues) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find('}').ok_or_else(|| {
+            SsoError::InvalidConfig(format!(
+                "Invalid prompt template '{}': unclosed '{{'",
+                template
+            ))
+        })?;
+
+        let value = match after[..end].trim() {
+            "profile" => &values.profile,
+            "account" => &values.account,
+            "role" => &values.role,
+            "region" => &values.region,
+            "expires" => &values.expires,
+            "session" => &values.session,
+            "session_expires" => &values.session_expires,
+            other => {
+                return Err(SsoError::InvalidConfig(format!(
+                    "Invalid prompt template '{}': unknown field '{}'",
+                    template, other
+                )))
+            }
+        };
+        out.push_str(value.as_deref().unwrap_or_default());
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+
+    // Empty fields at the end would leave a dangling separator
+    Ok(out.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let values = PromptValues {
+            profile: Some("prod-admin".to_string()),
+            role: Some("Admin".to_string()),
+            expires: Some("23m".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(render(PROFILE_TEMPLATE, &values).unwrap(), "prod-admin 23m");
+        assert_eq!(
+            render("aws:{ role }@{account}", &values).unwrap(),
+            "aws:Admin@"
+        );
+        assert_eq!(render("{session} {session_expires}", &values).unwrap(), "");
+        assert!(render("{profile", &values).is_err());
+        assert!(render("{color}", &values).is_err());
+    }
+}
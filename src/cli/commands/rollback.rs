@@ -0,0 +1,45 @@
+// rollback command - restore the AWS config files from awsom's write history
+use crate::error::{Result, SsoError};
+use crate::history;
+use crate::output::{OutputFormat, SnapshotList};
+use std::io::{self, Write};
+
+pub async fn execute(
+    list: bool,
+    to: Option<String>,
+    force: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    if list {
+        return format.print(&SnapshotList(history::list()?));
+    }
+
+    let snapshot = history::find(to.as_deref())?;
+
+    if !force {
+        eprint!(
+            "Restore ~/.aws/config and ~/.aws/credentials to how they were before '{}' \
+             changed ~/.aws/{} at {}? (y/N): ",
+            snapshot.command,
+            snapshot.changed,
+            snapshot
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+        );
+        io::stderr().flush().map_err(SsoError::Io)?;
+
+        let mut response = String::new();
+        io::stdin().read_line(&mut response).map_err(SsoError::Io)?;
+        if !response.trim().eq_ignore_ascii_case("y") {
+            eprintln!("Rollback cancelled");
+            return Ok(());
+        }
+    }
+
+    for path in history::restore(&snapshot)? {
+        eprintln!("✓ Restored {}", path.display());
+    }
+    eprintln!("  The replaced files were saved first; run 'awsom rollback' again to undo this");
+    Ok(())
+}
@@ -0,0 +1,103 @@
+// run command - log in if needed, fetch a role's credentials and run a command with them,
+// all in one step and without reading or writing any profile
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::cli::commands::assume::Assumed;
+use crate::cli::commands::exec::{child_command, fetch_credentials, reauthenticate};
+use crate::cli::commands::select::{self, Pick, RolePattern};
+use crate::config::Config;
+use crate::env;
+use crate::error::{Result, SsoError};
+use crate::models::SsoInstance;
+use crate::usage;
+use chrono::Duration;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    account_id: Option<String>,
+    account_name: Option<String>,
+    role_name: String,
+    session_name: Option<String>,
+    start_url: Option<String>,
+    region: Option<String>,
+    aws_region: Option<String>,
+    env_vars: Vec<(String, String)>,
+    duration: Option<Duration>,
+    first: bool,
+    command: Vec<String>,
+    headless: bool,
+) -> Result<()> {
+    if command.is_empty() {
+        return Err(SsoError::InvalidConfig("No command specified".to_string()));
+    }
+
+    let (start_url, region) = aws_config::resolve_sso_session(
+        session_name.as_deref(),
+        start_url.as_deref(),
+        region.as_deref(),
+    )?;
+    let instance = SsoInstance {
+        start_url,
+        region,
+        session_name: None,
+    };
+    let is_headless = headless || env::is_headless_environment();
+
+    let auth = AuthManager::new()?;
+    let mut token = match auth.get_cached_token(&instance)? {
+        Some(token) if !token.is_expired() => token,
+        _ => reauthenticate(&auth, &instance, is_headless).await?,
+    };
+
+    // Exactly one role: several matches need --first (or a choice at a terminal)
+    let pattern = RolePattern {
+        account_id: account_id.as_deref(),
+        account_name: account_name.as_deref(),
+        role_name: &role_name,
+    };
+    let pick = Pick::from_flags(first, false);
+    let role = match select::resolve(&instance, &token.access_token, &pattern, pick).await {
+        Err(SsoError::AuthExpired) => {
+            token = reauthenticate(&auth, &instance, is_headless).await?;
+            select::resolve(&instance, &token.access_token, &pattern, pick).await?
+        }
+        result => result?,
+    }
+    .swap_remove(0);
+
+    let creds = match fetch_credentials(&instance, &token, &role, duration).await {
+        Err(SsoError::AuthExpired) => {
+            token = reauthenticate(&auth, &instance, is_headless).await?;
+            fetch_credentials(&instance, &token, &role, duration).await?
+        }
+        result => result?,
+    };
+    usage::record(
+        &Config::load().unwrap_or_default().stats,
+        &role.account_id,
+        &role.role_name,
+        None,
+    );
+
+    let assumed = Assumed {
+        region: aws_region.unwrap_or_else(|| instance.region.clone()),
+        role,
+        creds,
+        profile: None,
+    };
+    let mut child = child_command(&command, &assumed, None, &env_vars);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        Err(SsoError::Io(child.exec()))
+    }
+    #[cfg(not(unix))]
+    {
+        let status = child.status().map_err(SsoError::Io)?;
+        if !status.success() {
+            std::process::exit(super::exec::exit_code_of(status));
+        }
+        Ok(())
+    }
+}
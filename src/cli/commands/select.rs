@@ -0,0 +1,186 @@
+// Account/role selection for `profile exec`, `profile export` and `profile console`
+//
+// --account-id, --account-name and --role-name take * and ? wildcards, matched against the
+// account/role list (the cached one while it is fresh). --account-name also takes the aliases
+// from config.toml's `[account_aliases]`. When several roles match, --first
+// takes the first, --all (where offered) takes every one, and otherwise the user is asked.
+use crate::config::Config;
+use crate::credentials::CredentialManager;
+use crate::error::{Result, SsoError};
+use crate::glob::glob_match;
+use crate::models::{AccountRole, SsoInstance};
+use std::io::{self, IsTerminal, Write};
+
+/// What to do when several roles match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    Ask,
+    First,
+    All,
+}
+
+impl Pick {
+    pub fn from_flags(first: bool, all: bool) -> Self {
+        match (first, all) {
+            (true, _) => Pick::First,
+            (_, true) => Pick::All,
+            _ => Pick::Ask,
+        }
+    }
+}
+
+/// Account and role as given on the command line; no account means any account
+pub struct RolePattern<'a> {
+    pub account_id: Option<&'a str>,
+    pub account_name: Option<&'a str>,
+    pub role_name: &'a str,
+}
+
+impl RolePattern<'_> {
+    /// Whether `role` matches; `alias` is its account's alias, which the account name
+    /// pattern may match instead of the real name
+    fn matches(&self, role: &AccountRole, alias: Option<&str>) -> bool {
+        self.account_id
+            .map_or(true, |id| glob_match(id, &role.account_id))
+            && self.account_name.map_or(true, |name| {
+                glob_match(name, &role.account_name)
+                    || alias.is_some_and(|alias| glob_match(name, alias))
+            })
+            && glob_match(self.role_name, &role.role_name)
+    }
+
+    /// A literal account ID and role need no lookup
+    fn exact(&self) -> Option<AccountRole> {
+        let id = self.account_id?;
+        if has_wildcard(id) || has_wildcard(self.role_name) || self.account_name.is_some() {
+            return None;
+        }
+        Some(AccountRole {
+            account_id: id.to_string(),
+            account_name: id.to_string(),
+            role_name: self.role_name.to_string(),
+        })
+    }
+}
+
+fn has_wildcard(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// The roles `pattern` selects; `Pick::All` can return several, the others exactly one
+pub async fn resolve(
+    instance: &SsoInstance,
+    access_token: &str,
+    pattern: &RolePattern<'_>,
+    pick: Pick,
+) -> Result<Vec<AccountRole>> {
+    let config = Config::load().unwrap_or_default();
+
+    // An alias names a single account, so it needs no lookup either
+    let alias_id = match (pattern.account_id, pattern.account_name) {
+        (None, Some(name)) if !has_wildcard(name) => config.alias_account_id(name),
+        _ => None,
+    };
+    let aliased = alias_id.map(|id| RolePattern {
+        account_id: Some(id),
+        account_name: None,
+        role_name: pattern.role_name,
+    });
+    let pattern = aliased.as_ref().unwrap_or(pattern);
+
+    if let Some(role) = pattern.exact() {
+        return Ok(vec![role]);
+    }
+
+    let mut matching: Vec<AccountRole> = CredentialManager::new()?
+        .list_roles(instance, access_token)
+        .await?
+        .into_iter()
+        .filter(|role| pattern.matches(role, config.account_alias(&role.account_id)))
+        .collect();
+
+    match (matching.len(), pick) {
+        (0, _) => Err(SsoError::AccountRoleNotFound),
+        (1, _) | (_, Pick::All) => Ok(matching),
+        (_, Pick::First) => {
+            matching.truncate(1);
+            Ok(matching)
+        }
+        (_, Pick::Ask) => {
+            let index = ask(&matching)?;
+            Ok(vec![matching.swap_remove(index)])
+        }
+    }
+}
+
+/// Let the user choose one of `roles` on the terminal
+fn ask(roles: &[AccountRole]) -> Result<usize> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        let names: Vec<String> = roles.iter().map(AccountRole::display_name).collect();
+        return Err(SsoError::InvalidConfig(format!(
+            "{} roles match: {}. Narrow the patterns or pass --first",
+            roles.len(),
+            names.join(", ")
+        )));
+    }
+
+    eprintln!("Several roles match:");
+    for (i, role) in roles.iter().enumerate() {
+        eprintln!(
+            "  {:>2}) {} ({})",
+            i + 1,
+            role.display_name(),
+            role.account_id
+        );
+    }
+    loop {
+        eprint!("Pick one [1-{}]: ", roles.len());
+        io::stderr().flush().map_err(SsoError::Io)?;
+
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).map_err(SsoError::Io)? == 0 {
+            return Err(SsoError::InvalidConfig("No role picked".to_string()));
+        }
+        match answer.trim().parse::<usize>() {
+            Ok(n) if (1..=roles.len()).contains(&n) => return Ok(n - 1),
+            _ => eprintln!("  Enter a number between 1 and {}", roles.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn role(account_id: &str, account_name: &str, role_name: &str) -> AccountRole {
+        AccountRole {
+            account_id: account_id.to_string(),
+            account_name: account_name.to_string(),
+            role_name: role_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_role_pattern() {
+        let pattern = RolePattern {
+            account_id: None,
+            account_name: Some("prod-*"),
+            role_name: "Admin*",
+        };
+        assert!(pattern.matches(&role("111111111111", "prod-payments", "AdminAccess"), None));
+        assert!(!pattern.matches(&role("111111111111", "staging", "AdminAccess"), None));
+        assert!(!pattern.matches(&role("111111111111", "prod-payments", "ReadOnly"), None));
+        assert!(pattern.matches(
+            &role("111111111111", "payments", "AdminAccess"),
+            Some("prod-payments")
+        ));
+        assert!(pattern.exact().is_none());
+
+        let pattern = RolePattern {
+            account_id: Some("111111111111"),
+            account_name: None,
+            role_name: "ReadOnly",
+        };
+        assert_eq!(pattern.exact().unwrap().account_id, "111111111111");
+    }
+}
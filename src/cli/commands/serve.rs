@@ -0,0 +1,185 @@
+// serve command - local credential endpoint for SDKs in containers
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::cli::commands::exec::reauthenticate;
+use crate::config;
+use crate::credentials::CredentialManager;
+use crate::env;
+use crate::error::{Result, SsoError};
+use crate::hooks::{ExpiringHook, HookContext};
+use crate::metadata_server::{MetadataServer, CREDENTIALS_PATH};
+use crate::metrics::METRICS_PATH;
+use crate::models::{AccountRole, SsoInstance};
+use crate::notify::ExpiryNotifier;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// How often the SSO session's expiry is checked while serving
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    account_id: Option<String>,
+    account_name: Option<String>,
+    role_name: String,
+    session_name: Option<String>,
+    start_url: Option<String>,
+    region: Option<String>,
+    imds: bool,
+    bind: String,
+    allow_remote: bool,
+    metrics: bool,
+    headless: bool,
+) -> Result<()> {
+    check_bind(&bind, imds, allow_remote).await?;
+
+    let (start_url, region) = aws_config::resolve_sso_session(
+        session_name.as_deref(),
+        start_url.as_deref(),
+        region.as_deref(),
+    )?;
+
+    let instance = SsoInstance {
+        start_url,
+        region,
+        session_name: None,
+    };
+
+    // Log in up front; once serving, an expired SSO session is reported to the SDK instead
+    let auth = AuthManager::new()?;
+    let token = match auth.get_cached_token(&instance)? {
+        Some(token) if !token.is_expired() => token,
+        _ => reauthenticate(&auth, &instance, headless || env::is_headless_environment()).await?,
+    };
+
+    let account_id = account_id.or_else(|| {
+        account_name
+            .as_deref()
+            .and_then(config::resolve_account_alias)
+    });
+    let accounts = CredentialManager::new()?
+        .list_accounts(&instance.region, &token.access_token)
+        .await?;
+    let (account_id, account_name) = accounts
+        .into_iter()
+        .find(|(id, name)| match (&account_id, &account_name) {
+            (Some(wanted), _) => id == wanted,
+            (None, Some(wanted)) => name == wanted,
+            (None, None) => false,
+        })
+        .ok_or_else(|| match (account_id, account_name) {
+            (None, None) => SsoError::InvalidConfig(
+                "Either --account-id or --account-name is required".to_string(),
+            ),
+            (id, name) => SsoError::InvalidConfig(format!(
+                "Account '{}' not found",
+                id.or(name).unwrap_or_default()
+            )),
+        })?;
+
+    let role = AccountRole {
+        account_id,
+        account_name,
+        role_name,
+    };
+    let display_name = role.display_name();
+
+    let listener = TcpListener::bind(&bind)
+        .await
+        .map_err(|e| SsoError::ConfigError(format!("Failed to listen on {}: {}", bind, e)))?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(watch_expiry(instance.clone(), session_name));
+    let server = MetadataServer::new(instance, role, imds, metrics)?;
+
+    eprintln!(
+        "✓ Serving credentials for {} on http://{}",
+        display_name, addr
+    );
+    if metrics {
+        eprintln!("  Metrics: http://{}{}", addr, METRICS_PATH);
+    }
+    eprintln!("  Set these variables for the SDK (Ctrl+C to stop):");
+    println!(
+        "export AWS_CONTAINER_CREDENTIALS_FULL_URI=http://{}{}",
+        addr, CREDENTIALS_PATH
+    );
+    println!(
+        "export AWS_CONTAINER_AUTHORIZATION_TOKEN={}",
+        server.auth_token()
+    );
+    if imds {
+        println!("export AWS_EC2_METADATA_SERVICE_ENDPOINT=http://{}", addr);
+    }
+
+    tokio::select! {
+        result = server.run(listener) => result,
+        _ = tokio::signal::ctrl_c() => {
+            eprintln!("Stopped serving credentials");
+            Ok(())
+        }
+    }
+}
+
+/// Warn before the SSO session the credentials come from runs out, with a desktop
+/// notification and `on_token_expiring`, as the TUI does
+async fn watch_expiry(instance: SsoInstance, session_name: Option<String>) {
+    let config = config::Config::load().unwrap_or_default();
+    let mut notifier = ExpiryNotifier::new(config.ui.notify_before_minutes);
+    let mut hook = ExpiringHook::new(config.hooks.token_expiring_minutes);
+    let label = format!(
+        "SSO session {}",
+        session_name.as_deref().unwrap_or(&instance.start_url)
+    );
+
+    let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        // Read each time, as a login elsewhere replaces the cached token
+        let token = AuthManager::new().and_then(|auth| auth.get_cached_token(&instance));
+        let Ok(Some(token)) = token else {
+            continue;
+        };
+        notifier.check(&label, &token.expires_at);
+        hook.check(
+            &label,
+            &HookContext {
+                session: session_name.as_deref(),
+                start_url: Some(&instance.start_url),
+                region: Some(&instance.region),
+                expires_at: Some(token.expires_at),
+                ..HookContext::default()
+            },
+        );
+    }
+}
+
+/// Refuse an address other hosts can reach unless --allow-remote is given, and warn when it
+/// is: the IMDS token route hands out its token to any caller, so with --imds anyone who can
+/// connect can read the credentials
+async fn check_bind(bind: &str, imds: bool, allow_remote: bool) -> Result<()> {
+    let addrs: Vec<_> = tokio::net::lookup_host(bind)
+        .await
+        .map_err(|e| SsoError::ConfigError(format!("Invalid --bind address {}: {}", bind, e)))?
+        .collect();
+    if addrs.iter().all(|addr| addr.ip().is_loopback()) {
+        return Ok(());
+    }
+    if !allow_remote {
+        return Err(SsoError::ConfigError(format!(
+            "{} can be reached from other hosts; bind to a loopback address such as \
+             127.0.0.1:9911, or pass --allow-remote if that is intended",
+            bind
+        )));
+    }
+    eprintln!(
+        "⚠ Listening on {}, which other hosts can reach: anyone who {} can read the role's \
+         credentials",
+        bind,
+        if imds {
+            "can connect"
+        } else {
+            "can connect and has the authorization token"
+        }
+    );
+    Ok(())
+}
@@ -1,7 +1,12 @@
 // Session management CLI commands
+use crate::auth::AuthManager;
 use crate::aws_config::{self, SsoSession};
+use crate::cli::commands::status;
 use crate::cli::SessionCommands;
+use crate::config::{self, Config};
 use crate::error::{Result, SsoError};
+use crate::models::SsoInstance;
+use crate::output::{OutputFormat, SessionEntry, SessionList, SessionStatusOutput};
 use std::io::{self, Write};
 
 pub async fn execute(command: SessionCommands, headless: bool) -> Result<()> {
@@ -10,29 +15,58 @@ pub async fn execute(command: SessionCommands, headless: bool) -> Result<()> {
             name,
             start_url,
             region,
-        } => add_session(name, start_url, region).await,
+            sso_scopes,
+        } => add_session(name, start_url, region, sso_scopes).await,
         SessionCommands::List { format } => list_sessions(format).await,
         SessionCommands::Delete { name, force } => delete_session(name, force).await,
         SessionCommands::Edit {
             name,
             start_url,
             region,
-        } => edit_session(name, start_url, region).await,
-        SessionCommands::Switch { name } => switch_session(name).await,
+            sso_scopes,
+        } => edit_session(name, start_url, region, sso_scopes).await,
+        SessionCommands::Switch { name, clear: _ } => switch_session(name).await,
         SessionCommands::Login {
             session_name,
             force,
-        } => session_login(session_name, force, headless).await,
+            no_browser,
+            copy,
+            sso_scopes,
+            auth_flow,
+        } => {
+            if no_browser {
+                crate::env::set_headless_override(true);
+            }
+            crate::clipboard::set_copy_device_url_override(copy);
+            if let Some(flow) = auth_flow {
+                crate::auth::set_auth_flow_override(flow);
+            }
+            session_login(session_name, force, sso_scopes, headless || no_browser).await
+        }
         SessionCommands::Logout { session_name } => session_logout(session_name).await,
-        SessionCommands::Status { session_name, json } => session_status(session_name, json).await,
+        SessionCommands::Status {
+            session_name,
+            all,
+            exit_code,
+            format,
+            json,
+        } => {
+            let format = if json { OutputFormat::Json } else { format };
+            session_status(session_name, all, format, exit_code).await
+        }
     }
 }
 
-async fn add_session(name: String, start_url: String, region: String) -> Result<()> {
+async fn add_session(
+    name: String,
+    start_url: String,
+    region: String,
+    sso_scopes: Vec<String>,
+) -> Result<()> {
     // Check if session already exists
     let existing_sessions = aws_config::read_all_sso_sessions()?;
     if existing_sessions.iter().any(|s| s.session_name == name) {
-        return Err(SsoError::ConfigError(format!(
+        return Err(SsoError::ProfileConflict(format!(
             "Session '{}' already exists. Use 'session edit' to modify it.",
             name
         )));
@@ -43,7 +77,11 @@ async fn add_session(name: String, start_url: String, region: String) -> Result<
         session_name: name.clone(),
         sso_start_url: start_url.clone(),
         sso_region: region.clone(),
-        sso_registration_scopes: "sso:account:access".to_string(),
+        sso_registration_scopes: if sso_scopes.is_empty() {
+            aws_config::DEFAULT_REGISTRATION_SCOPES.to_string()
+        } else {
+            sso_scopes.join(",")
+        },
     };
 
     // Write to config
@@ -52,54 +90,36 @@ async fn add_session(name: String, start_url: String, region: String) -> Result<
     println!("✓ Added SSO session '{}' to ~/.aws/config", name);
     println!("  Start URL: {}", start_url);
     println!("  Region: {}", region);
+    println!("  Scopes: {}", session.sso_registration_scopes);
     println!();
     println!("Run 'awsom login' or launch the TUI to authenticate with this session.");
 
     Ok(())
 }
 
-async fn list_sessions(format: String) -> Result<()> {
+async fn list_sessions(format: OutputFormat) -> Result<()> {
     let sessions = aws_config::read_all_sso_sessions()?;
+    let auth = AuthManager::new()?;
+    let current = config::current_session();
+    let profiles = aws_config::count_profiles_by_session()?;
 
-    if sessions.is_empty() {
-        if format == "json" {
-            println!("[]");
-        } else {
-            println!("No SSO sessions configured.");
-            println!();
-            println!("Add a session with: awsom session add --name <name> --start-url <url> --region <region>");
-        }
-        return Ok(());
-    }
-
-    match format.as_str() {
-        "json" => {
-            let json_sessions: Vec<_> = sessions
-                .iter()
-                .map(|s| {
-                    serde_json::json!({
-                        "name": s.session_name,
-                        "start_url": s.sso_start_url,
-                        "region": s.sso_region,
-                        "registration_scopes": s.sso_registration_scopes,
-                    })
-                })
-                .collect();
-            println!("{}", serde_json::to_string_pretty(&json_sessions)?);
-        }
-        _ => {
-            println!("SSO Sessions ({}):", sessions.len());
-            println!();
-            for session in sessions {
-                println!("  {}", session.session_name);
-                println!("    Start URL: {}", session.sso_start_url);
-                println!("    Region: {}", session.sso_region);
-                println!();
-            }
-        }
-    }
-
-    Ok(())
+    let entries = sessions
+        .iter()
+        .map(|session| {
+            let instance = SsoInstance {
+                start_url: session.sso_start_url.clone(),
+                region: session.sso_region.clone(),
+                session_name: Some(session.session_name.clone()),
+            };
+            let status = SessionStatusOutput {
+                current: current.as_ref() == Some(&session.session_name),
+                ..status::token_status(&auth, &instance)?
+            };
+            let bound = profiles.get(&session.session_name).copied().unwrap_or(0);
+            Ok(SessionEntry::new(session, status, bound))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    format.print(&SessionList(entries))
 }
 
 async fn delete_session(name: String, force: bool) -> Result<()> {
@@ -134,6 +154,11 @@ async fn delete_session(name: String, force: bool) -> Result<()> {
 
     // Delete the session
     aws_config::delete_sso_session(&name)?;
+    let mut config = Config::load()?;
+    if config.current_session.as_deref() == Some(name.as_str()) {
+        config.current_session = None;
+        config.save()?;
+    }
 
     println!("✓ Deleted SSO session '{}'", name);
     println!("  Start URL was: {}", session.sso_start_url);
@@ -146,6 +171,7 @@ async fn edit_session(
     name: String,
     start_url: Option<String>,
     region: Option<String>,
+    sso_scopes: Vec<String>,
 ) -> Result<()> {
     // Check if session exists
     let existing_sessions = aws_config::read_all_sso_sessions()?;
@@ -161,9 +187,9 @@ async fn edit_session(
         })?;
 
     // Check if at least one field is being updated
-    if start_url.is_none() && region.is_none() {
+    if start_url.is_none() && region.is_none() && sso_scopes.is_empty() {
         return Err(SsoError::ConfigError(
-            "No changes specified. Use --start-url and/or --region to update the session."
+            "No changes specified. Use --start-url, --region or --sso-scope to update the session."
                 .to_string(),
         ));
     }
@@ -181,6 +207,14 @@ async fn edit_session(
         changes.push(format!("Region: {} → {}", session.sso_region, new_region));
         session.sso_region = new_region;
     }
+    if !sso_scopes.is_empty() {
+        let new_scopes = sso_scopes.join(",");
+        changes.push(format!(
+            "Scopes: {} → {}",
+            session.sso_registration_scopes, new_scopes
+        ));
+        session.sso_registration_scopes = new_scopes;
+    }
 
     // Write updated session
     aws_config::write_sso_session(&session)?;
@@ -197,29 +231,39 @@ async fn edit_session(
     Ok(())
 }
 
-async fn switch_session(name: String) -> Result<()> {
-    // Check if session exists
-    let existing_sessions = aws_config::read_all_sso_sessions()?;
-    let _session = existing_sessions
-        .iter()
-        .find(|s| s.session_name == name)
-        .ok_or_else(|| {
-            SsoError::ConfigError(format!(
+/// Save `name` as the current session in config.toml, or forget it for None (--clear)
+async fn switch_session(name: Option<String>) -> Result<()> {
+    if let Some(name) = &name {
+        // Check if session exists
+        let existing_sessions = aws_config::read_all_sso_sessions()?;
+        if !existing_sessions.iter().any(|s| &s.session_name == name) {
+            return Err(SsoError::ConfigError(format!(
                 "Session '{}' not found. Use 'awsom session list' to see available sessions.",
                 name
-            ))
-        })?;
+            )));
+        }
+    }
 
-    // For now, this is a placeholder for future multi-session support
-    println!("✓ Session '{}' selected", name);
-    println!();
-    println!("Note: Multi-session switching is not yet fully implemented.");
-    println!("For now, use the TUI (just run 'awsom') to switch between sessions interactively.");
+    let mut config = Config::load()?;
+    config.current_session = name.clone();
+    config.save()?;
 
+    match name {
+        Some(name) => {
+            println!("✓ Current session is now '{}'", name);
+            println!("  Commands without --session-name use it until you switch again.");
+        }
+        None => println!("✓ Cleared the current session"),
+    }
     Ok(())
 }
 
-async fn session_login(session_name: Option<String>, force: bool, headless: bool) -> Result<()> {
+async fn session_login(
+    session_name: Option<String>,
+    force: bool,
+    sso_scopes: Vec<String>,
+    headless: bool,
+) -> Result<()> {
     // Resolve session using the new resolution logic
     let (start_url, region) = aws_config::resolve_sso_session(
         session_name.as_deref(),
@@ -227,6 +271,26 @@ async fn session_login(session_name: Option<String>, force: bool, headless: bool
         None, // No explicit region
     )?;
 
+    // Login reads the scopes from the session, so save them first; a token
+    // cached under the old scopes doesn't count
+    let force = force || !sso_scopes.is_empty();
+    if !sso_scopes.is_empty() {
+        let mut session = aws_config::read_all_sso_sessions()?
+            .into_iter()
+            .find(|s| match &session_name {
+                Some(name) => &s.session_name == name,
+                None => s.sso_start_url == start_url,
+            })
+            .ok_or_else(|| {
+                SsoError::InvalidConfig(
+                    "--sso-scope needs an [sso-session]; add one with 'awsom session add'"
+                        .to_string(),
+                )
+            })?;
+        session.sso_registration_scopes = sso_scopes.join(",");
+        aws_config::write_sso_session(&session)?;
+    }
+
     // Call the existing login command implementation
     crate::cli::commands::login::execute(Some(start_url), Some(region), force, headless).await
 }
@@ -243,15 +307,28 @@ async fn session_logout(session_name: Option<String>) -> Result<()> {
     crate::cli::commands::logout::execute(Some(start_url), Some(region)).await
 }
 
-async fn session_status(session_name: Option<String>, json: bool) -> Result<()> {
+async fn session_status(
+    session_name: Option<String>,
+    all: bool,
+    format: OutputFormat,
+    exit_code: bool,
+) -> Result<()> {
+    if all {
+        return crate::cli::commands::status::execute_all(format, exit_code);
+    }
+
     // Resolve session using the new resolution logic
-    let (_start_url, _region) = aws_config::resolve_sso_session(
+    let resolved = aws_config::resolve_named_sso_session(
         session_name.as_deref(),
         None, // No explicit start_url
         None, // No explicit region
-    )?;
+    );
+    let (session_name, start_url, region) = match resolved {
+        Ok(resolved) => resolved,
+        // No session to check counts as not logged in
+        Err(_) if exit_code => std::process::exit(1),
+        Err(e) => return Err(e),
+    };
 
-    // Call the existing status command implementation
-    // Note: status command currently doesn't use session info, but we resolve it for consistency
-    crate::cli::commands::status::execute(json).await
+    crate::cli::commands::status::execute(session_name, start_url, region, format, exit_code).await
 }
@@ -0,0 +1,263 @@
+// shell command - interactive subshell with a role's credentials in its environment
+//
+// bash, zsh and fish get a prompt prefix and a prompt hook that fetches new credentials
+// shortly before the current ones expire, by running `awsom shell --refresh` and
+// evaluating its output. Other shells only get the variables.
+use crate::aws_config;
+use crate::cli::commands::assume::{self, export_command, quote, Assumed, Target};
+use crate::cli::HookShell;
+use crate::error::{Result, SsoError};
+use crate::expiry;
+use chrono::Duration;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How long before expiry the prompt hook fetches new credentials
+const REFRESH_BEFORE_MINUTES: i64 = 5;
+
+/// What the subshell was started with, repeated by the refresh command
+pub struct ShellArgs {
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub role_name: String,
+    pub session_name: Option<String>,
+    pub start_url: Option<String>,
+    pub region: Option<String>,
+}
+
+pub async fn execute(args: ShellArgs, refresh: Option<HookShell>, headless: bool) -> Result<()> {
+    let target = Target {
+        profile: None,
+        account: args.account_id.clone().or(args.account_name.clone()),
+        role_name: args.role_name.clone(),
+        session_name: args.session_name.clone(),
+        region: None,
+    };
+    let mut assumed = assume::assume(
+        &target,
+        args.start_url.as_deref(),
+        args.region.as_deref(),
+        headless,
+    )
+    .await?;
+    assumed.profile = aws_config::get_existing_profile_name(&assumed.role)?;
+
+    if let Some(shell) = refresh {
+        for command in assumed.export_commands(shell) {
+            println!("{}", command);
+        }
+        println!(
+            "{}",
+            export_command(shell, "AWSOM_REFRESH_AT", &refresh_at(&assumed))
+        );
+        return Ok(());
+    }
+
+    if std::env::var_os("AWSOM_ROLE").is_some() {
+        eprintln!("⚠ Already in an awsom shell; exit it first to avoid nesting");
+    }
+
+    let program = std::env::var("SHELL").unwrap_or_else(|_| default_shell());
+    let shell = match Path::new(&program).file_name().and_then(|n| n.to_str()) {
+        Some("bash") => Some(HookShell::Bash),
+        Some("zsh") => Some(HookShell::Zsh),
+        Some("fish") => Some(HookShell::Fish),
+        _ => None,
+    };
+
+    let mut command = Command::new(&program);
+    command.envs(crate::aws_config::file_override_env());
+    for (name, value) in assumed.variables() {
+        command.env(name, value);
+    }
+    match &assumed.profile {
+        Some(profile) => command.env("AWS_PROFILE", profile),
+        None => command.env_remove("AWS_PROFILE"),
+    };
+    command
+        .env(
+            "AWSOM_ROLE",
+            format!("{}@{}", assumed.role.role_name, assumed.role.account_name),
+        )
+        .env("AWSOM_REFRESH_AT", refresh_at(&assumed));
+
+    let init_dir = match shell {
+        Some(shell) => add_hooks(&mut command, shell, &refresh_command(&args, shell))?,
+        None => {
+            eprintln!(
+                "⚠ {} gets no prompt or refresh hooks (bash, zsh and fish do), so the \
+                 credentials won't be renewed",
+                program
+            );
+            None
+        }
+    };
+
+    eprintln!(
+        "✓ Starting {} as {} in {} ({}), expires in {}. Type 'exit' to leave.",
+        program,
+        assumed.role.role_name,
+        assumed.role.account_name,
+        assumed.role.account_id,
+        assumed.creds.expiration_display()
+    );
+    let status = command.status();
+    if let Some(dir) = init_dir {
+        let _ = fs::remove_dir_all(dir);
+    }
+    let status = status.map_err(SsoError::Io)?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Unix time at which the prompt hook should fetch new credentials
+fn refresh_at(assumed: &Assumed) -> String {
+    (assumed.creds.expiration - expiry::margin() - Duration::minutes(REFRESH_BEFORE_MINUTES))
+        .timestamp()
+        .to_string()
+}
+
+/// `awsom shell --refresh` with the same session, account and role, quoted for `shell`
+fn refresh_command(args: &ShellArgs, shell: HookShell) -> String {
+    let mut parts = vec!["command awsom".to_string()];
+    let mut flag = |name: &str, value: Option<&String>| {
+        if let Some(value) = value {
+            parts.push(format!("{} {}", name, quote(shell, value)));
+        }
+    };
+    flag("--start-url", args.start_url.as_ref());
+    flag("--region", args.region.as_ref());
+    flag("shell --refresh", Some(&shell.name().to_string()));
+    flag("--account-id", args.account_id.as_ref());
+    flag("--account-name", args.account_name.as_ref());
+    flag("--role-name", Some(&args.role_name));
+    flag("--session-name", args.session_name.as_ref());
+    parts.join(" ")
+}
+
+/// The shell to start when $SHELL isn't set
+#[cfg(windows)]
+fn default_shell() -> String {
+    std::env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".to_string())
+}
+
+#[cfg(not(windows))]
+fn default_shell() -> String {
+    "/bin/sh".to_string()
+}
+
+const POSIX_REFRESH: &str = r#"__awsom_refresh() {
+  if [ "$(date +%s)" -ge "${AWSOM_REFRESH_AT:-0}" ]; then
+    local awsom_exports
+    awsom_exports="$(REFRESH_COMMAND)" && eval "$awsom_exports"
+  fi
+}
+"#;
+
+const BASH_RC: &str = r#"[ -f ~/.bashrc ] && . ~/.bashrc
+REFRESH_FUNCTION
+PROMPT_COMMAND="__awsom_refresh${PROMPT_COMMAND:+; $PROMPT_COMMAND}"
+PS1='(${AWSOM_ROLE}) '"$PS1"
+"#;
+
+// zsh only reads startup files from $ZDOTDIR, so point it at the init directory and
+// source the user's own files from there
+const ZSH_ENV: &str = r#"AWSOM_SHELL_DIR="$ZDOTDIR"
+ZDOTDIR="$AWSOM_ZDOTDIR"
+[ -f "$ZDOTDIR/.zshenv" ] && . "$ZDOTDIR/.zshenv"
+AWSOM_ZDOTDIR="$ZDOTDIR"
+ZDOTDIR="$AWSOM_SHELL_DIR"
+"#;
+
+const ZSH_RC: &str = r#"ZDOTDIR="$AWSOM_ZDOTDIR"
+unset AWSOM_SHELL_DIR AWSOM_ZDOTDIR
+[ -f "$ZDOTDIR/.zshrc" ] && . "$ZDOTDIR/.zshrc"
+REFRESH_FUNCTION
+autoload -Uz add-zsh-hook
+add-zsh-hook precmd __awsom_refresh
+setopt prompt_subst
+PROMPT='(${AWSOM_ROLE}) '"$PROMPT"
+"#;
+
+const FISH_INIT: &str = r#"function __awsom_refresh --on-event fish_prompt
+    if test (date +%s) -ge "$AWSOM_REFRESH_AT"
+        REFRESH_COMMAND | source
+    end
+end
+functions -q fish_prompt; and functions -c fish_prompt __awsom_fish_prompt
+function fish_prompt
+    echo -n "($AWSOM_ROLE) "
+    functions -q __awsom_fish_prompt; and __awsom_fish_prompt
+end
+"#;
+
+/// Set up the prompt prefix and refresh hook; returns the directory of generated startup
+/// files to delete once the shell exits
+fn add_hooks(command: &mut Command, shell: HookShell, refresh: &str) -> Result<Option<PathBuf>> {
+    let refresh_function = POSIX_REFRESH.replace("REFRESH_COMMAND", refresh);
+    match shell {
+        HookShell::Bash => {
+            let dir = init_dir()?;
+            let rc = dir.join("bashrc");
+            fs::write(&rc, BASH_RC.replace("REFRESH_FUNCTION", &refresh_function))?;
+            command.arg("--rcfile").arg(&rc).arg("-i");
+            Ok(Some(dir))
+        }
+        HookShell::Zsh => {
+            let dir = init_dir()?;
+            fs::write(dir.join(".zshenv"), ZSH_ENV)?;
+            fs::write(
+                dir.join(".zshrc"),
+                ZSH_RC.replace("REFRESH_FUNCTION", &refresh_function),
+            )?;
+            let original = std::env::var_os("ZDOTDIR")
+                .or_else(|| dirs::home_dir().map(PathBuf::into_os_string))
+                .unwrap_or_default();
+            command.env("AWSOM_ZDOTDIR", original).env("ZDOTDIR", &dir);
+            Ok(Some(dir))
+        }
+        HookShell::Fish => {
+            command
+                .arg("--init-command")
+                .arg(FISH_INIT.replace("REFRESH_COMMAND", refresh));
+            Ok(None)
+        }
+    }
+}
+
+/// Private directory for the generated startup files
+fn init_dir() -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("awsom-shell-{}", uuid::Uuid::new_v4()));
+    fs::create_dir(&dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_command() {
+        let args = ShellArgs {
+            account_id: None,
+            account_name: Some("it's prod".to_string()),
+            role_name: "ReadOnly".to_string(),
+            session_name: None,
+            start_url: None,
+            region: Some("eu-west-1".to_string()),
+        };
+        assert_eq!(
+            refresh_command(&args, HookShell::Bash),
+            r"command awsom --region 'eu-west-1' shell --refresh 'bash' --account-name 'it'\''s prod' --role-name 'ReadOnly'"
+        );
+    }
+}
@@ -0,0 +1,25 @@
+// stats command - local counts of role activations
+use crate::config::Config;
+use crate::error::Result;
+use crate::output::{OutputFormat, UsageStats};
+use crate::usage;
+
+pub fn execute(clear: bool, format: OutputFormat) -> Result<()> {
+    if clear {
+        if usage::clear()? {
+            println!("✓ Cleared usage stats");
+        } else {
+            println!("No usage stats to clear");
+        }
+        return Ok(());
+    }
+
+    let stats = usage::load()?;
+    if stats.is_empty() && !format.is_machine_readable() && !Config::load()?.stats.enabled {
+        eprintln!(
+            "Usage stats are disabled. Enable them with `[stats] enabled = true` in {}",
+            Config::path()?.display()
+        );
+    }
+    format.print(&UsageStats(stats))
+}
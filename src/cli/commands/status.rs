@@ -1,63 +1,80 @@
 use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::config;
 use crate::error::Result;
 use crate::models::SsoInstance;
-use crate::sso_config;
-
-pub async fn execute(json: bool) -> Result<()> {
-    // Check if SSO config is available
-    if !sso_config::has_sso_config(None, None) {
-        if json {
-            println!("{{\"active\":false,\"reason\":\"not_configured\"}}");
-        } else {
-            println!("SSO not configured");
-        }
-        std::process::exit(1);
-    }
-
-    // Get SSO config from env vars or ~/.aws/config
-    let (start_url, region) = sso_config::get_sso_config(None, None)?;
+use crate::output::{OutputFormat, SessionStatusList, SessionStatusOutput};
 
+pub async fn execute(
+    session_name: Option<String>,
+    start_url: String,
+    region: String,
+    format: OutputFormat,
+    exit_code: bool,
+) -> Result<()> {
     let instance = SsoInstance {
-        start_url,
+        start_url: start_url.clone(),
         region,
-        session_name: None,
+        session_name: session_name.clone(),
     };
 
     // Check for cached token
     let auth = AuthManager::new()?;
+    let status = SessionStatusOutput {
+        current: session_name.is_some() && session_name == config::current_session(),
+        session_name,
+        start_url: Some(start_url),
+        ..token_status(&auth, &instance)?
+    };
+
+    if !exit_code {
+        format.print(&status)?;
+    }
+    std::process::exit(if status.active { 0 } else { 1 });
+}
+
+/// Status of every configured sso-session; exits 0 only if all of them are logged in
+pub fn execute_all(format: OutputFormat, exit_code: bool) -> Result<()> {
+    let auth = AuthManager::new()?;
+    let current = config::current_session();
+    let statuses = aws_config::read_all_sso_sessions()?
+        .into_iter()
+        .map(|session| {
+            let instance = SsoInstance {
+                start_url: session.sso_start_url.clone(),
+                region: session.sso_region,
+                session_name: Some(session.session_name.clone()),
+            };
+            Ok(SessionStatusOutput {
+                current: current.as_ref() == Some(&session.session_name),
+                session_name: Some(session.session_name),
+                start_url: Some(session.sso_start_url),
+                ..token_status(&auth, &instance)?
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
 
-    match auth.get_cached_token(&instance)? {
-        Some(token) => {
-            if token.is_expired() {
-                if json {
-                    println!("{{\"active\":false,\"reason\":\"expired\"}}");
-                } else {
-                    println!("SSO session expired");
-                }
-                std::process::exit(1);
-            } else {
-                let expires_in_minutes = token.expires_in_minutes();
-                if json {
-                    println!(
-                        "{{\"active\":true,\"expires_in_minutes\":{}}}",
-                        expires_in_minutes
-                    );
-                } else {
-                    println!(
-                        "SSO session active (expires in {} minutes)",
-                        expires_in_minutes
-                    );
-                }
-                std::process::exit(0);
-            }
-        }
-        None => {
-            if json {
-                println!("{{\"active\":false,\"reason\":\"no_session\"}}");
-            } else {
-                println!("No SSO session found");
-            }
-            std::process::exit(1);
-        }
+    let all_active = !statuses.is_empty() && statuses.iter().all(|s| s.active);
+    if !exit_code {
+        format.print(&SessionStatusList(statuses))?;
     }
+    std::process::exit(if all_active { 0 } else { 1 });
+}
+
+/// Token state from the cache only - no network calls
+pub fn token_status(auth: &AuthManager, instance: &SsoInstance) -> Result<SessionStatusOutput> {
+    let status = match auth.get_cached_token(instance)? {
+        Some(token) if token.is_expired() => SessionStatusOutput::inactive("expired"),
+        Some(token) => SessionStatusOutput {
+            active: true,
+            reason: None,
+            session_name: None,
+            current: false,
+            start_url: None,
+            expires_at: Some(token.expires_at),
+            expires_in_minutes: Some(token.expires_in_minutes()),
+        },
+        None => SessionStatusOutput::inactive("no_session"),
+    };
+    Ok(status)
 }
@@ -1,8 +1,12 @@
 // CLI interface
 pub mod commands;
 
-use crate::error::Result;
-use clap::{Parser, Subcommand, ValueEnum};
+use crate::aws_config::ConflictPolicy;
+use crate::config::AuthFlow;
+use crate::error::{Result, SsoError};
+use crate::output::OutputFormat;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "awsom")]
@@ -17,20 +21,59 @@ pub struct Cli {
     pub start_url: Option<String>,
 
     /// SSO region
-    #[arg(long, env = "AWS_SSO_REGION")]
+    #[arg(long, env = "AWS_SSO_REGION", value_parser = parse_region)]
     pub region: Option<String>,
 
     /// Enable verbose/debug logging
     #[arg(short, long, global = true)]
     pub verbose: bool,
 
+    /// Print errors without hints and only log warnings; exit codes are unchanged
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
     /// Headless mode - don't try to open browser (auto-detected in SSH/Docker)
     #[arg(long, global = true)]
     pub headless: bool,
+
+    /// Ask AWS for the account/role list instead of using the cached one
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Scope sessions, accounts and favorites to a workspace from config.toml
+    #[arg(long, global = true, env = "AWSOM_WORKSPACE")]
+    pub workspace: Option<String>,
+
+    /// AWS config file to read and write instead of $AWS_CONFIG_FILE or ~/.aws/config
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config_file: Option<PathBuf>,
+
+    /// AWS credentials file instead of $AWS_SHARED_CREDENTIALS_FILE or ~/.aws/credentials
+    #[arg(long, global = true, value_name = "PATH")]
+    pub credentials_file: Option<PathBuf>,
+
+    /// Write no files: changes to ~/.aws files fail with their diff, caches aren't updated
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
+    /// Log line format (default: `[log] format` in config.toml, else text)
+    #[arg(long, global = true, value_enum, value_name = "FORMAT")]
+    pub log_format: Option<crate::config::LogFormat>,
+
+    /// Log to this file, rotated like the TUI's log, instead of stderr
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Restore a terminal left in raw mode or on the alternate screen, then exit
+    #[arg(long)]
+    pub reset_terminal: bool,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    /// Set up awsom step by step: SSO session, profile defaults, first login and profiles
+    Init,
+
     /// Manage SSO sessions
     Session {
         #[command(subcommand)]
@@ -48,16 +91,455 @@ pub enum Commands {
     /// Moves sections from above the "Managed by awsom" marker to below it,
     /// allowing awsom to manage them with automatic sorting and organization.
     Import {
-        /// Profile or SSO session name to import
-        name: String,
+        /// Profile, SSO session or credentials profile name to import
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
 
-        /// Type of section to import (profile or sso-session)
+        /// Type of section to import (profile, sso-session, or credentials for static
+        /// access keys in ~/.aws/credentials, which are tracked but never overwritten)
         #[arg(short, long, default_value = "profile")]
         section_type: String,
 
+        /// Import every user-managed profile and sso-session and every untracked
+        /// static-credentials profile, after reviewing the list
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+
         /// Force import without confirmation
         #[arg(short, long)]
         force: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Restore ~/.aws/config and ~/.aws/credentials from the write history
+    ///
+    /// awsom saves both files before each change it makes (the newest 20 by default,
+    /// see `[history] keep` in config.toml). Without --to, the newest snapshot is
+    /// restored, which undoes the last change. The files being replaced are saved
+    /// first, so a rollback can be undone the same way.
+    Rollback {
+        /// List saved snapshots instead of restoring one
+        #[arg(long, conflicts_with = "to")]
+        list: bool,
+
+        /// Snapshot to restore: an ID from --list (a unique prefix is enough) or an
+        /// RFC 3339 time, which picks the last snapshot taken at or before it
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Restore without confirmation
+        #[arg(short, long)]
+        force: bool,
+
+        /// Output format for --list
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Manage awsom's caches
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+
+    /// Read and change awsom's own settings in config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Print export commands for a role's credentials, for the `assume` shell function
+    ///
+    /// TARGET is a profile from ~/.aws/config or <account>/<role>, where the account is
+    /// a name or ID. Install the shell function once with `awsom hook`, then `assume`
+    /// sets the credentials in the current shell instead of a subshell.
+    ///
+    /// Example:
+    ///   eval "$(awsom hook bash)"
+    ///   assume prod-admin
+    ///   assume Production/ReadOnly
+    ///   assume --unset
+    Assume {
+        /// Profile name or <account>/<role>
+        #[arg(required_unless_present = "unset")]
+        target: Option<String>,
+
+        /// SSO session (default: the profile's, else the usual session resolution)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Shell syntax of the printed commands
+        #[arg(long, value_enum, default_value_t)]
+        shell: HookShell,
+
+        /// Print commands that clear the credentials instead
+        #[arg(short, long, conflicts_with = "target")]
+        unset: bool,
+    },
+
+    /// Print the `assume` shell function to eval in your shell rc file
+    ///
+    /// Example:
+    ///   echo 'eval "$(awsom hook zsh)"' >> ~/.zshrc
+    ///   echo 'awsom hook fish | source' >> ~/.config/fish/config.fish
+    Hook {
+        #[arg(value_enum)]
+        shell: HookShell,
+    },
+
+    /// Print commands that put a role's credentials in the environment, or clear them
+    ///
+    /// Like `assume`, but the role is given with flags and the syntax follows $SHELL.
+    /// --unset also clears AWS_* variables that make tools use other credentials.
+    ///
+    /// Example:
+    ///   eval "$(awsom env --account-name prod --role-name ReadOnly)"
+    ///   eval "$(awsom env --unset)"
+    Env {
+        /// Profile to take the account, role, session and region from
+        #[arg(long, conflicts_with_all = ["account_id", "account_name", "role_name"])]
+        profile: Option<String>,
+
+        /// Account ID
+        #[arg(long)]
+        account_id: Option<String>,
+
+        /// Account name (alternative to account-id; not needed if only one account has the role)
+        #[arg(long, conflicts_with = "account_id")]
+        account_name: Option<String>,
+
+        /// Role name
+        #[arg(long, required_unless_present_any = ["profile", "unset"])]
+        role_name: Option<String>,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Region for AWS_REGION (default: the profile's, else the SSO region)
+        #[arg(long, value_parser = parse_region)]
+        region: Option<String>,
+
+        /// Shell syntax of the printed commands (default: from $SHELL)
+        #[arg(long, value_enum)]
+        shell: Option<HookShell>,
+
+        /// Print commands that clear AWS credentials and conflicting AWS_* variables instead
+        #[arg(
+            short,
+            long,
+            conflicts_with_all = ["profile", "account_id", "account_name", "role_name"]
+        )]
+        unset: bool,
+    },
+
+    /// Log in if needed and run a command with a role's credentials, in one step
+    ///
+    /// Resolves the SSO session, starts a device login when there's no valid token, fetches
+    /// the role's credentials and runs the command with them in its environment. No profile
+    /// is read or written, so it needs nothing in ~/.aws beyond an SSO session (or
+    /// --start-url and --region). Exits with the command's exit code.
+    ///
+    /// Example:
+    ///   awsom run --account-name prod --role-name Deploy -- terraform apply
+    Run {
+        /// Account ID (* and ? wildcards allowed, as long as one role matches)
+        #[arg(long)]
+        account_id: Option<String>,
+
+        /// Account name (alternative to account-id; * and ? wildcards allowed)
+        #[arg(long, conflicts_with = "account_id")]
+        account_name: Option<String>,
+
+        /// Role name (* and ? wildcards allowed)
+        #[arg(long)]
+        role_name: String,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Region for the command (default: the SSO region)
+        #[arg(long, value_parser = parse_region)]
+        region: Option<String>,
+
+        /// Extra environment variable for the command, as KEY=VALUE (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+        env_vars: Vec<(String, String)>,
+
+        /// Fail unless the credentials last at least this long, e.g. 8h or 90m
+        #[arg(long, value_parser = parse_duration)]
+        duration: Option<chrono::Duration>,
+
+        /// Take the first match when the patterns match several roles
+        #[arg(long)]
+        first: bool,
+
+        /// Command to run, after `--`
+        #[arg(required = true, last = true)]
+        command: Vec<String>,
+    },
+
+    /// Start your $SHELL with a role's credentials in its environment
+    ///
+    /// Nothing is written to ~/.aws. In bash, zsh and fish the prompt shows the role
+    /// (also in $AWSOM_ROLE) and the credentials are renewed at a prompt shortly before
+    /// they expire. Type `exit` to leave.
+    ///
+    /// Example:
+    ///   awsom shell --account-name prod --role-name ReadOnly
+    Shell {
+        /// Account ID
+        #[arg(long)]
+        account_id: Option<String>,
+
+        /// Account name (alternative to account-id; not needed if only one account has the role)
+        #[arg(long)]
+        account_name: Option<String>,
+
+        /// Role name
+        #[arg(long)]
+        role_name: String,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Print the commands that renew the credentials (used by the prompt hook)
+        #[arg(long, value_enum, hide = true)]
+        refresh: Option<HookShell>,
+    },
+
+    /// Pick an account/role by typing part of its name
+    ///
+    /// Opens a small fuzzy finder below the prompt: type to narrow the list, Up/Down to
+    /// move, Enter to choose, Esc to cancel. Prints `<account>/<role>` unless an action
+    /// is given.
+    ///
+    /// Example:
+    ///   awsom pick --exec -- aws s3 ls
+    ///   eval "$(awsom pick --export)"
+    #[command(group(ArgGroup::new("action")))]
+    Pick {
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Run the command after `--` with the chosen role's credentials
+        #[arg(long, group = "action")]
+        exec: bool,
+
+        /// Print export commands for the chosen role's credentials
+        #[arg(long, group = "action")]
+        export: bool,
+
+        /// Open the AWS Console as the chosen role
+        #[arg(long, group = "action")]
+        console: bool,
+
+        /// Command to run with --exec
+        #[arg(requires = "exec")]
+        command: Vec<String>,
+    },
+
+    /// Run a command in every account with the given role
+    ///
+    /// Credentials for each account are injected into the command's environment.
+    /// Output lines are prefixed with the account name, and the exit code is the
+    /// highest exit code of any account.
+    ///
+    /// Example:
+    ///   awsom exec-all --role-name ReadOnly --filter 'prod-*' -- aws s3 ls
+    ExecAll {
+        /// Role name to assume in each account (accounts without it are skipped)
+        #[arg(long)]
+        role_name: String,
+
+        /// Only run in accounts whose name or ID matches this pattern (* and ? wildcards)
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Maximum number of accounts to run in parallel
+        #[arg(short = 'j', long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+        concurrency: u16,
+
+        /// Command to execute
+        command: Vec<String>,
+    },
+
+    /// Serve a role's credentials to AWS SDKs over HTTP
+    ///
+    /// Exposes a container credentials endpoint, so SDKs (for example inside a local
+    /// Docker container) pick up credentials from `AWS_CONTAINER_CREDENTIALS_FULL_URI`
+    /// without any files being written. Credentials are refreshed from the SSO session
+    /// on demand. The export lines for the SDK environment are printed on startup.
+    ///
+    /// Example:
+    ///   awsom serve --account-name prod --role-name ReadOnly --imds
+    ///   docker run --network host -e AWS_CONTAINER_CREDENTIALS_FULL_URI \
+    ///     -e AWS_CONTAINER_AUTHORIZATION_TOKEN amazon/aws-cli sts get-caller-identity
+    Serve {
+        /// Account ID
+        #[arg(long)]
+        account_id: Option<String>,
+
+        /// Account name (alternative to account-id)
+        #[arg(long)]
+        account_name: Option<String>,
+
+        /// Role name
+        #[arg(long)]
+        role_name: String,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Also serve the EC2 instance metadata (IMDSv2) credential paths, for
+        /// AWS_EC2_METADATA_SERVICE_ENDPOINT
+        #[arg(long)]
+        imds: bool,
+
+        /// Address to listen on (SDKs only accept plain HTTP on loopback addresses)
+        #[arg(long, default_value = "127.0.0.1:9911")]
+        bind: String,
+
+        /// Allow a --bind address other hosts can reach; anyone who can connect and
+        /// learns the token (or, with --imds, anyone at all) gets the role's credentials
+        #[arg(long)]
+        allow_remote: bool,
+
+        /// Also serve Prometheus metrics on /metrics: token and credential expiry and
+        /// refresh counts (no authorization needed)
+        #[arg(long)]
+        metrics: bool,
+    },
+
+    /// Give kubectl access to EKS clusters as an SSO role
+    Eks {
+        #[command(subcommand)]
+        command: EksCommands,
+    },
+
+    /// Log docker in to ECR registries as an SSO role
+    Ecr {
+        #[command(subcommand)]
+        command: EcrCommands,
+    },
+
+    /// List and open the applications (SAML and other apps) on your access portal
+    Apps {
+        #[command(subcommand)]
+        command: AppsCommands,
+    },
+
+    /// Print provider or client configuration that uses a profile, ready to paste
+    ///
+    /// The snippet names the profile and its region, and the role it assumes when the
+    /// profile was generated with `--mode role-arn`.
+    ///
+    /// Example:
+    ///   awsom emit terraform --profile prod-admin >> providers.tf
+    ///   awsom emit boto3 --profile prod-admin
+    Emit {
+        /// What to write the configuration for
+        #[arg(value_enum)]
+        format: EmitFormat,
+
+        /// Profile in ~/.aws/config
+        #[arg(long)]
+        profile: String,
+
+        /// Region to configure (default: the profile's, else awsom's default or the SSO
+        /// region)
+        #[arg(long, value_parser = parse_region)]
+        region: Option<String>,
+    },
+
+    /// Query the local audit log
+    ///
+    /// Events are only recorded when `[audit] enabled = true` is set in
+    /// ~/.config/awsom/config.toml.
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
+
+    /// Show how often and when each role was activated
+    ///
+    /// Counted locally in ~/.cache/awsom/usage.json; set `[stats] enabled = false` in
+    /// ~/.config/awsom/config.toml to stop counting.
+    Stats {
+        /// Forget all recorded usage
+        #[arg(long)]
+        clear: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Check the local AWS/awsom setup for problems
+    ///
+    /// Checks the ~/.aws/config markers, sso-session references, orphaned
+    /// credentials, the SSO token cache, SSO endpoint reachability and browser
+    /// availability. Exits with status 1 if any check fails.
+    Doctor {
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Print a role's credentials for an AWS profile's `credential_process`
+    ///
+    /// Profiles written by `profile generate --mode credential-process` run this, so the
+    /// AWS CLI and SDKs get credentials from awsom's cache and no keys are stored in
+    /// ~/.aws/credentials. It never starts a login; run `awsom session login` when the
+    /// SSO session has expired.
+    CredentialProcess {
+        /// SSO session name
+        #[arg(long)]
+        session_name: String,
+
+        /// Account ID
+        #[arg(long)]
+        account_id: String,
+
+        /// Role name
+        #[arg(long)]
+        role_name: String,
+    },
+
+    /// Print a one-line auth summary for shell prompts
+    ///
+    /// Reads only ~/.aws and the local token cache, so it is fast enough to run on every
+    /// prompt. Prints nothing if no profile or session is selected.
+    ///
+    /// Template fields: {profile} {account} {role} {region} {expires} {session}
+    /// {session_expires}
+    ///
+    /// Example (bash):
+    ///   PS1='[$(awsom prompt)] \w $ '
+    Prompt {
+        /// Profile to show
+        #[arg(long, env = "AWS_PROFILE")]
+        profile: Option<String>,
+
+        /// SSO session to show (defaults to the profile's sso_session)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Output template, e.g. '{role}@{account} {expires}'
+        /// [default: '{profile} {expires}', or '{session} {session_expires}' without a profile]
+        #[arg(long)]
+        template: Option<String>,
     },
 
     /// Generate shell completion scripts
@@ -73,6 +555,9 @@ pub enum Commands {
     /// Show installation instructions:
     ///   awsom completions bash --show-install
     ///
+    /// Install for the shell in $SHELL:
+    ///   awsom completions --install
+    ///
     /// QUICK INSTALL:
     ///
     /// Bash:
@@ -90,13 +575,22 @@ pub enum Commands {
     /// Elvish:
     ///   eval (awsom completions elvish | slurp)
     Completions {
-        /// Shell type to generate completions for (bash, zsh, fish, powershell, elvish)
-        #[arg(value_enum)]
-        shell: Shell,
+        /// Shell type to generate completions for (bash, zsh, fish, powershell, elvish);
+        /// --install defaults to $SHELL
+        #[arg(value_enum, required_unless_present = "install")]
+        shell: Option<Shell>,
 
         /// Show installation instructions instead of generating completion script
-        #[arg(long)]
+        #[arg(long, conflicts_with = "install")]
         show_install: bool,
+
+        /// Write the completion script where the shell loads it from (bash, zsh and fish)
+        #[arg(long)]
+        install: bool,
+
+        /// With --install, print where the script would go without writing it
+        #[arg(long, requires = "install")]
+        dry_run: bool,
     },
 }
 
@@ -113,15 +607,20 @@ pub enum SessionCommands {
         start_url: String,
 
         /// SSO region
-        #[arg(long)]
+        #[arg(long, value_parser = parse_region)]
         region: String,
+
+        /// OIDC registration scope; repeat or comma-separate for several
+        /// (default: sso:account:access)
+        #[arg(long = "sso-scope", value_name = "SCOPE", value_delimiter = ',')]
+        sso_scopes: Vec<String>,
     },
 
     /// List all SSO sessions
     List {
-        /// Output format (text, json)
-        #[arg(short, long, default_value = "text")]
-        format: String,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
     },
 
     /// Delete an SSO session
@@ -144,14 +643,23 @@ pub enum SessionCommands {
         start_url: Option<String>,
 
         /// New SSO region (optional)
-        #[arg(long)]
+        #[arg(long, value_parser = parse_region)]
         region: Option<String>,
+
+        /// Replace the OIDC registration scopes; repeat or comma-separate for several
+        #[arg(long = "sso-scope", value_name = "SCOPE", value_delimiter = ',')]
+        sso_scopes: Vec<String>,
     },
 
-    /// Switch to a different SSO session (for multi-session support)
+    /// Make a session the current one, used by commands not given --session-name
     Switch {
         /// Session name to switch to
-        name: String,
+        #[arg(required_unless_present = "clear")]
+        name: Option<String>,
+
+        /// Forget the current session instead
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
     },
 
     /// Authenticate with AWS SSO
@@ -163,6 +671,24 @@ pub enum SessionCommands {
         /// Force re-authentication even if token is valid
         #[arg(short, long)]
         force: bool,
+
+        /// Don't open a browser; print the login URL and code instead (same as --headless)
+        #[arg(long)]
+        no_browser: bool,
+
+        /// Copy the login URL to the clipboard (or set `[login] copy_to_clipboard`)
+        #[arg(long)]
+        copy: bool,
+
+        /// Log in with these OIDC registration scopes and save them to the session;
+        /// repeat or comma-separate for several
+        #[arg(long = "sso-scope", value_name = "SCOPE", value_delimiter = ',')]
+        sso_scopes: Vec<String>,
+
+        /// How to authorize (or set `[login] auth_flow`); pkce falls back to device when
+        /// headless
+        #[arg(long, value_enum)]
+        auth_flow: Option<AuthFlow>,
     },
 
     /// End SSO session
@@ -178,8 +704,20 @@ pub enum SessionCommands {
         #[arg(long)]
         session_name: Option<String>,
 
-        /// Output in JSON format for scripting
+        /// Report every configured SSO session
+        #[arg(long, conflicts_with = "session_name")]
+        all: bool,
+
+        /// Print nothing; exit 0 only if the token is valid (every token with --all)
         #[arg(long)]
+        exit_code: bool,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+
+        /// Shorthand for --format json (kept for existing scripts)
+        #[arg(long, hide = true)]
         json: bool,
     },
 }
@@ -192,19 +730,20 @@ pub enum ProfileCommands {
         #[arg(long)]
         session_name: Option<String>,
 
-        /// Output format (text, json)
-        #[arg(short, long, default_value = "text")]
-        format: String,
-    },
+        /// Only roles with this local tag (repeatable; all must match)
+        #[arg(long)]
+        tag: Vec<String>,
 
-    /// Refresh credentials for an existing profile
-    Start {
-        /// Profile name to refresh
-        profile_name: String,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
     },
 
-    /// Execute a command with AWS credentials
-    Exec {
+    /// Create a profile for an account/role
+    ///
+    /// The profile name comes from `[profile_defaults] name_template` in
+    /// ~/.config/awsom/config.toml unless --name is given.
+    Create {
         /// Account ID
         #[arg(long)]
         account_id: Option<String>,
@@ -221,21 +760,198 @@ pub enum ProfileCommands {
         #[arg(long)]
         session_name: Option<String>,
 
-        /// Command to execute
-        command: Vec<String>,
+        /// Profile name (overrides the name template)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Default region for the profile (defaults to awsom-defaults or the SSO region)
+        #[arg(long, value_parser = parse_region)]
+        region: Option<String>,
+
+        /// Default output format for the profile (e.g. json, yaml, text, table)
+        #[arg(long, value_parser = parse_output)]
+        output: Option<String>,
+
+        /// What to do if the profile is user-managed in ~/.aws/config (asked on a terminal,
+        /// abort otherwise)
+        #[arg(long, value_enum)]
+        on_conflict: Option<ConflictPolicy>,
     },
 
-    /// Export credentials as environment variables or AWS profile
-    Export {
-        /// Account ID
+    /// Generate SSO profiles for every account and role in the SSO directory
+    ///
+    /// Writes sso_session/sso_account_id/sso_role_name profiles to the awsom-managed
+    /// section of ~/.aws/config, named by `[profile_defaults] name_template`. With
+    /// `--mode credential-process` the profiles run `awsom credential-process` instead, and
+    /// with `--mode role-arn` they assume arn:aws:iam::<account>:role/<role> using the
+    /// credentials of an awsom-managed --source-profile.
+    ///
+    /// Example:
+    ///   awsom profile generate --filter 'prod-*/ReadOnly' --dry-run
+    ///   awsom profile generate --all --mode credential-process
+    ///   awsom profile generate --all --mode role-arn --source-profile hub-admin
+    Generate {
+        /// Generate profiles for all accounts and roles
+        #[arg(long, required_unless_present = "filter", conflicts_with = "filter")]
+        all: bool,
+
+        /// Only roles matching this pattern: 'account/role' or just 'account' (* and ? wildcards, repeatable)
         #[arg(long)]
-        account_id: Option<String>,
+        filter: Vec<String>,
 
-        /// Account name (alternative to account-id)
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Default region for the profiles (defaults to awsom-defaults or the SSO region)
+        #[arg(long, value_parser = parse_region)]
+        region: Option<String>,
+
+        /// Default output format for the profiles
+        #[arg(long, value_parser = parse_output)]
+        output: Option<String>,
+
+        /// How the profiles get their credentials
+        #[arg(long, value_enum, default_value_t)]
+        mode: ProfileMode,
+
+        /// awsom-managed profile whose credentials assume the roles with `--mode role-arn`
+        #[arg(long, required_if_eq("mode", "role-arn"))]
+        source_profile: Option<String>,
+
+        /// Print the diff of ~/.aws/config instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Refresh credentials for an existing profile
+    ///
+    /// Example:
+    ///   awsom profile start prod-admin
+    ///   awsom session login && awsom profile start --all-expired
+    Start {
+        /// Profile name to refresh
+        #[arg(required_unless_present = "all_expired")]
+        profile_name: Option<String>,
+
+        /// Refresh every awsom-managed profile whose credentials are expired or stopped,
+        /// where its SSO session is logged in
+        #[arg(long, conflicts_with_all = ["profile_name", "on_conflict"])]
+        all_expired: bool,
+
+        /// What to do if the profile is user-managed in ~/.aws/config (asked on a terminal,
+        /// abort otherwise)
+        #[arg(long, value_enum)]
+        on_conflict: Option<ConflictPolicy>,
+    },
+
+    /// Show a profile's settings and the state of its credentials
+    Show {
+        /// Profile name to show
+        profile_name: String,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Invalidate a profile's credentials, keeping the profile
+    ///
+    /// Replaces the keys in ~/.aws/credentials with placeholders, like deactivating a role
+    /// in the TUI; `profile start` fetches new ones.
+    Stop {
+        /// Profile name to stop
+        profile_name: String,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Delete a profile's credentials from ~/.aws/credentials
+    Delete {
+        /// Profile name to delete
+        profile_name: String,
+
+        /// Also delete the profile from ~/.aws/config
+        #[arg(long)]
+        purge_config: bool,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Execute a command with AWS credentials
+    ///
+    /// Starts the SSO login flow automatically if the session has expired. The command
+    /// gets AWS_REGION and AWS_DEFAULT_REGION, and AWS_PROFILE when the role has a profile;
+    /// its exit code is awsom's, 128 + the signal number if a signal stopped it.
+    Exec {
+        /// Account ID (* and ? wildcards match several)
+        #[arg(long)]
+        account_id: Option<String>,
+
+        /// Account name (alternative to account-id; * and ? wildcards match several)
         #[arg(long)]
         account_name: Option<String>,
 
-        /// Role name
+        /// Role name (* and ? wildcards match several)
+        #[arg(long)]
+        role_name: String,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Region for the command (defaults to the role's profile region, else the SSO
+        /// region)
+        #[arg(long, value_parser = parse_region)]
+        region: Option<String>,
+
+        /// Extra environment variable for the command, as KEY=VALUE (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+        env_vars: Vec<(String, String)>,
+
+        /// Fail unless the credentials last at least this long, e.g. 8h or 90m (up to the
+        /// permission set's session duration, at most 12h)
+        #[arg(long, value_parser = parse_duration)]
+        duration: Option<chrono::Duration>,
+
+        /// Take the first match when the patterns match several roles
+        #[arg(long)]
+        first: bool,
+
+        /// Take every match when the patterns match several roles
+        #[arg(long, conflicts_with = "first")]
+        all: bool,
+
+        /// Also write the credentials to a private temporary file and point
+        /// AWS_SHARED_CREDENTIALS_FILE at it, for tools that only read a credentials file;
+        /// the file is wiped and removed when the command exits
+        #[arg(long)]
+        via_file: bool,
+
+        /// Mask the secret access key and session token wherever the command prints them,
+        /// e.g. in CI logs; its output goes through pipes instead of the terminal
+        #[arg(long)]
+        redact: bool,
+
+        /// Command to execute
+        command: Vec<String>,
+    },
+
+    /// Export credentials as environment variables or AWS profile
+    Export {
+        /// Account ID (* and ? wildcards match several)
+        #[arg(long)]
+        account_id: Option<String>,
+
+        /// Account name (alternative to account-id; * and ? wildcards match several)
+        #[arg(long)]
+        account_name: Option<String>,
+
+        /// Role name (* and ? wildcards match several)
         #[arg(long)]
         role_name: String,
 
@@ -243,22 +959,37 @@ pub enum ProfileCommands {
         #[arg(long)]
         session_name: Option<String>,
 
-        /// Write to ~/.aws/credentials as this profile name (instead of exporting to env)
+        /// Write to ~/.aws/credentials as this profile name (instead of exporting to env);
+        /// with `profile_mode = "sso-native"`, only an SSO profile in ~/.aws/config
         #[arg(long)]
         profile: Option<String>,
+
+        /// Fail unless the credentials last at least this long, e.g. 8h or 90m (up to the
+        /// permission set's session duration, at most 12h)
+        #[arg(long, value_parser = parse_duration)]
+        duration: Option<chrono::Duration>,
+
+        /// Take the first match when the patterns match several roles
+        #[arg(long)]
+        first: bool,
+
+        /// Output format (text prints shell export commands; aws-sdk-cache writes the
+        /// credentials to the AWS CLI cache in ~/.aws/cli/cache)
+        #[arg(long, value_enum, default_value_t)]
+        format: ExportFormat,
     },
 
     /// Open AWS Console in browser for a role
     Console {
-        /// Account ID
+        /// Account ID (* and ? wildcards match several)
         #[arg(long)]
         account_id: Option<String>,
 
-        /// Account name (alternative to account-id)
+        /// Account name (alternative to account-id; * and ? wildcards match several)
         #[arg(long)]
         account_name: Option<String>,
 
-        /// Role name
+        /// Role name (* and ? wildcards match several)
         #[arg(long)]
         role_name: String,
 
@@ -267,12 +998,329 @@ pub enum ProfileCommands {
         session_name: Option<String>,
 
         /// AWS region to open console in (defaults to profile default or SSO region)
+        #[arg(long, value_parser = parse_region)]
+        region: Option<String>,
+
+        /// Fail unless the credentials last at least this long, e.g. 8h or 90m (up to the
+        /// permission set's session duration, at most 12h)
+        #[arg(long, value_parser = parse_duration)]
+        duration: Option<chrono::Duration>,
+
+        /// How long the console session lasts, e.g. 1h (15m to 12h, no longer than the
+        /// credentials; defaults to `[console] duration_minutes` or 12h)
+        #[arg(long, value_parser = parse_console_duration)]
+        console_duration: Option<chrono::Duration>,
+
+        /// Take the first match when the patterns match several roles
+        #[arg(long)]
+        first: bool,
+
+        /// Take every match when the patterns match several roles
+        #[arg(long, conflicts_with = "first")]
+        all: bool,
+
+        /// Print the sign-in URL to stdout instead of opening a browser, e.g. over SSH
+        #[arg(long)]
+        url_only: bool,
+
+        /// Copy the sign-in URL to the clipboard instead of opening a browser
+        #[arg(long, conflicts_with_all = ["url_only", "all"])]
+        copy: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EksCommands {
+    /// Add a cluster to the kubeconfig, authenticating as a role through awsom
+    ///
+    /// Looks the cluster up with eks:DescribeCluster and writes a cluster, a context and a
+    /// user whose exec plugin runs `awsom eks get-token`, then makes the context current.
+    /// Running it again with another role switches the context to that role.
+    ///
+    /// Example:
+    ///   awsom eks update-kubeconfig --cluster prod --account-name prod --role-name Admin
+    UpdateKubeconfig {
+        /// Cluster name
+        #[arg(long)]
+        cluster: String,
+
+        /// Account ID (* and ? wildcards)
+        #[arg(long)]
+        account_id: Option<String>,
+
+        /// Account name (alternative to account-id; * and ? wildcards)
+        #[arg(long)]
+        account_name: Option<String>,
+
+        /// Role name (* and ? wildcards)
+        #[arg(long)]
+        role_name: String,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Cluster region (defaults to the awsom profile defaults, then the SSO region)
+        #[arg(long, value_parser = parse_region)]
+        region: Option<String>,
+
+        /// Context and user name (defaults to the cluster ARN)
+        #[arg(long)]
+        alias: Option<String>,
+
+        /// Kubeconfig to update (defaults to the first file in $KUBECONFIG, then
+        /// ~/.kube/config)
+        #[arg(long, value_name = "PATH")]
+        kubeconfig: Option<PathBuf>,
+
+        /// Take the first match when the patterns match several roles
+        #[arg(long)]
+        first: bool,
+    },
+
+    /// Print an ExecCredential with a cluster token, for kubectl's exec plugin
+    ///
+    /// Written into the kubeconfig by `eks update-kubeconfig`. Credentials come from
+    /// awsom's cache; it never starts a login.
+    GetToken {
+        /// Cluster name
+        #[arg(long)]
+        cluster: String,
+
+        /// Cluster region
+        #[arg(long, value_parser = parse_region)]
+        region: String,
+
+        /// SSO session name
+        #[arg(long)]
+        session_name: String,
+
+        /// Account ID
+        #[arg(long)]
+        account_id: String,
+
+        /// Role name
+        #[arg(long)]
+        role_name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AppsCommands {
+    /// Show the applications assigned to you besides AWS accounts
+    ///
+    /// Example:
+    ///   awsom apps list --format table
+    List {
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Sign in to an application in the browser, through the access portal
+    ///
+    /// Example:
+    ///   awsom apps open Salesforce
+    Open {
+        /// Application name (any case) or instance ID
+        name: String,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Print the URL instead of opening it
+        #[arg(long)]
+        url_only: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EcrCommands {
+    /// Log docker in to the role's ECR registry with ecr:GetAuthorizationToken
+    ///
+    /// The password is passed to `docker login --password-stdin` and lasts 12 hours.
+    ///
+    /// Example:
+    ///   awsom ecr login --account-name prod --role-name Developer
+    ///   awsom ecr login --account-name prod --role-name Developer --registry-ids 222222222222
+    Login {
+        /// Account ID (* and ? wildcards)
+        #[arg(long)]
+        account_id: Option<String>,
+
+        /// Account name (alternative to account-id; * and ? wildcards)
+        #[arg(long)]
+        account_name: Option<String>,
+
+        /// Role name (* and ? wildcards)
         #[arg(long)]
+        role_name: String,
+
+        /// SSO session name (auto-resolved if only one exists)
+        #[arg(long)]
+        session_name: Option<String>,
+
+        /// Registry region (defaults to the awsom profile defaults, then the SSO region)
+        #[arg(long, value_parser = parse_region)]
         region: Option<String>,
+
+        /// Log in to these registries (account IDs) instead of the role's own account's
+        #[arg(long, value_delimiter = ',', value_name = "IDS")]
+        registry_ids: Vec<String>,
+
+        /// Print the password to stdout instead of running docker login
+        #[arg(long)]
+        print: bool,
+
+        /// Take the first match when the patterns match several roles
+        #[arg(long)]
+        first: bool,
     },
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Subcommand, Debug)]
+pub enum AuditCommands {
+    /// Show recorded credential issuance, console sign-ins and profile writes
+    Show {
+        /// Only show events since this time (e.g. 24h, 7d, 2024-01-31 or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommands {
+    /// Show cached SSO tokens, role credentials, account listings and client registrations
+    /// with sizes and expiry
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Delete cached entries
+    ///
+    /// Example:
+    ///   awsom cache clear --credentials --accounts
+    #[command(group(ArgGroup::new("caches").required(true).multiple(true)))]
+    Clear {
+        /// SSO tokens in ~/.aws/sso/cache (signs you out)
+        #[arg(long, group = "caches")]
+        tokens: bool,
+
+        /// Role credentials in ~/.aws/cli/cache
+        #[arg(long, group = "caches")]
+        credentials: bool,
+
+        /// awsom's account/role listings
+        #[arg(long, group = "caches")]
+        accounts: bool,
+
+        /// awsom's OIDC client registrations in ~/.aws/sso/cache (the next login registers
+        /// a new client)
+        #[arg(long, group = "caches")]
+        registrations: bool,
+
+        /// All of the above
+        #[arg(long, group = "caches")]
+        all: bool,
+    },
+
+    /// Print the cache directories
+    Path,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Print a setting, by its dotted key
+    ///
+    /// Example:
+    ///   awsom config get ui.sort_by
+    Get {
+        /// Dotted key, e.g. ui.sort_by or console.issuer
+        key: String,
+    },
+
+    /// Change a setting, checking the key and the value's type first
+    ///
+    /// The value is read as TOML (5, true, "text", ["a", "b"]); a bare word is a string.
+    ///
+    /// Example:
+    ///   awsom config set ui.notify_before_minutes 5
+    ///   awsom config set ui.sort_by expiry
+    Set {
+        /// Dotted key, e.g. ui.sort_by or console.issuer
+        key: String,
+
+        /// New value
+        value: String,
+    },
+
+    /// Print every setting with its current value
+    List,
+
+    /// Open config.toml in $VISUAL or $EDITOR, then check that it still parses
+    Edit,
+
+    /// Print the path of config.toml
+    Path,
+}
+
+/// `profile export --format`: the usual output formats plus the AWS CLI cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Shell export commands (default)
+    #[default]
+    Text,
+    /// PowerShell `$Env:` assignments, for `| Invoke-Expression`
+    Powershell,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// Aligned columns
+    Table,
+    /// Write to ~/.aws/cli/cache where the AWS CLI, Terraform and SDKs sharing the CLI
+    /// cache pick the credentials up for matching SSO profiles
+    AwsSdkCache,
+}
+
+impl ExportFormat {
+    /// The format for printing, None for the cache file
+    pub fn output_format(self) -> Option<OutputFormat> {
+        match self {
+            ExportFormat::Text | ExportFormat::Powershell => Some(OutputFormat::Text),
+            ExportFormat::Json => Some(OutputFormat::Json),
+            ExportFormat::Yaml => Some(OutputFormat::Yaml),
+            ExportFormat::Table => Some(OutputFormat::Table),
+            ExportFormat::AwsSdkCache => None,
+        }
+    }
+}
+
+/// `profile generate --mode`: where the AWS CLI and SDKs get a profile's credentials
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ProfileMode {
+    /// sso_session, sso_account_id and sso_role_name keys (default)
+    #[default]
+    Sso,
+    /// A `credential_process` running awsom, which keeps keys off disk
+    CredentialProcess,
+    /// `role_arn` and `source_profile`, for older SDKs without SSO or credential_process
+    /// support; needs --source-profile
+    RoleArn,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
 #[allow(clippy::enum_variant_names)]
 pub enum Shell {
     Bash,
@@ -282,26 +1330,450 @@ pub enum Shell {
     Elvish,
 }
 
+/// Tools `awsom emit` writes configuration for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EmitFormat {
+    /// Terraform AWS provider block
+    Terraform,
+    /// AWS CDK app (TypeScript) stack environment
+    Cdk,
+    /// AWS SDK for Go v2 config loading
+    SdkGo,
+    /// boto3 session (Python)
+    Boto3,
+}
+
+/// Shells `awsom hook` and `assume` support
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum HookShell {
+    #[default]
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl HookShell {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookShell::Bash => "bash",
+            HookShell::Zsh => "zsh",
+            HookShell::Fish => "fish",
+        }
+    }
+
+    /// The shell named by $SHELL, bash for any other
+    pub fn detect() -> Self {
+        let shell = std::env::var_os("SHELL").unwrap_or_default();
+        match std::path::Path::new(&shell).file_name() {
+            Some(name) if name == "zsh" => HookShell::Zsh,
+            Some(name) if name == "fish" => HookShell::Fish,
+            _ => HookShell::Bash,
+        }
+    }
+}
+
+/// Parse a `--duration` value: minutes or hours such as `90m` or `12h`
+fn parse_duration(value: &str) -> std::result::Result<chrono::Duration, String> {
+    let duration = parse_console_duration(value)?;
+    if duration < chrono::Duration::minutes(15) || duration > chrono::Duration::hours(12) {
+        return Err("role credentials last between 15m and 12h".to_string());
+    }
+    Ok(duration)
+}
+
+/// Parse a `--console-duration` value like `--duration`, leaving the range to the console
+/// module, which clamps it to what AWS accepts
+fn parse_console_duration(value: &str) -> std::result::Result<chrono::Duration, String> {
+    let invalid = || format!("'{}' is not a duration like 90m or 12h", value);
+    let split = value.char_indices().last().map_or(0, |(index, _)| index);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        "m" => chrono::Duration::minutes(amount),
+        "h" => chrono::Duration::hours(amount),
+        _ => return Err(invalid()),
+    };
+    if duration <= chrono::Duration::zero() {
+        return Err(invalid());
+    }
+    Ok(duration)
+}
+
+/// Parse a `--region` value, which must look like an AWS region; an unknown one is used
+/// with a warning
+fn parse_region(value: &str) -> std::result::Result<String, String> {
+    crate::regions::check_region(value)?;
+    if let Some(warning) = crate::regions::region_warning(value) {
+        eprintln!("⚠ {}", warning);
+    }
+    Ok(value.to_string())
+}
+
+/// Parse an `--output` value, which must be a format the AWS CLI knows
+fn parse_output(value: &str) -> std::result::Result<String, String> {
+    crate::regions::check_output(value).map(|()| value.to_string())
+}
+
+/// Parse an `--env` value: `KEY=VALUE`, where the value may be empty or contain `=`
+fn parse_env_var(value: &str) -> std::result::Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("'{}' is not KEY=VALUE", value)),
+    }
+}
+
+impl Commands {
+    /// Commands whose stderr must stay quiet unless --verbose is given
+    pub fn is_quiet(&self) -> bool {
+        matches!(
+            self,
+            Commands::Prompt { .. }
+                | Commands::CredentialProcess { .. }
+                | Commands::Eks {
+                    command: EksCommands::GetToken { .. }
+                }
+                | Commands::Ecr {
+                    command: EcrCommands::Login { print: true, .. }
+                }
+                | Commands::Assume { .. }
+                | Commands::Env { .. }
+                | Commands::Pick { .. }
+                | Commands::Shell {
+                    refresh: Some(_),
+                    ..
+                }
+        )
+    }
+
+    /// Command name recorded as the initiator in the audit log
+    fn audit_name(&self) -> &'static str {
+        match self {
+            Commands::Init => "init",
+            Commands::Session { command } => match command {
+                SessionCommands::Login { .. } => "session login",
+                _ => "session",
+            },
+            Commands::Profile { command } => match command {
+                ProfileCommands::List { .. } => "profile list",
+                ProfileCommands::Create { .. } => "profile create",
+                ProfileCommands::Generate { .. } => "profile generate",
+                ProfileCommands::Start { .. } => "profile start",
+                ProfileCommands::Show { .. } => "profile show",
+                ProfileCommands::Stop { .. } => "profile stop",
+                ProfileCommands::Delete { .. } => "profile delete",
+                ProfileCommands::Exec { .. } => "profile exec",
+                ProfileCommands::Export { .. } => "profile export",
+                ProfileCommands::Console { .. } => "profile console",
+            },
+            Commands::Import { .. } => "import",
+            Commands::Rollback { .. } => "rollback",
+            Commands::Cache { .. } => "cache",
+            Commands::Config { .. } => "config",
+            Commands::Assume { .. } => "assume",
+            Commands::Hook { .. } => "hook",
+            Commands::Env { .. } => "env",
+            Commands::Shell { .. } => "shell",
+            Commands::Run { .. } => "run",
+            Commands::Pick { .. } => "pick",
+            Commands::ExecAll { .. } => "exec-all",
+            Commands::Serve { .. } => "serve",
+            Commands::Eks { command } => match command {
+                EksCommands::UpdateKubeconfig { .. } => "eks update-kubeconfig",
+                EksCommands::GetToken { .. } => "eks get-token",
+            },
+            Commands::Apps { command } => match command {
+                AppsCommands::List { .. } => "apps list",
+                AppsCommands::Open { .. } => "apps open",
+            },
+            Commands::Emit { .. } => "emit",
+            Commands::Ecr { command } => match command {
+                EcrCommands::Login { .. } => "ecr login",
+            },
+            Commands::Audit { .. } => "audit",
+            Commands::Stats { .. } => "stats",
+            Commands::Doctor { .. } => "doctor",
+            Commands::CredentialProcess { .. } => "credential-process",
+            Commands::Prompt { .. } => "prompt",
+            Commands::Completions { .. } => "completions",
+        }
+    }
+}
+
+/// Everything the awsom binary does: parse the arguments, set up logging and run the
+/// command (or the TUI), exiting with the command's exit code on errors
+pub async fn main() {
+    if let Some(kind) = commands::completions::complete_request() {
+        commands::completions::complete(&kind);
+        return;
+    }
+
+    // Parse CLI arguments first to get verbose flag
+    let args = Cli::parse();
+
+    if args.reset_terminal {
+        if let Err(e) = crate::ui::terminal::reset() {
+            eprintln!("Error: {}", e);
+            std::process::exit(e.exit_code());
+        }
+        return;
+    }
+
+    // Set headless mode override if --headless flag is set
+    if args.headless {
+        crate::env::set_headless_override(true);
+    }
+    if args.read_only {
+        crate::config::set_read_only_override(true);
+    }
+    // Resolved once here rather than by reading config.toml on every write
+    crate::config::is_read_only();
+    crate::credentials::directory::set_bypass(args.no_cache);
+
+    // Initialize tracing based on verbose flag
+    let log_level = if args.verbose {
+        tracing::Level::DEBUG
+    } else if args.quiet || args.command.as_ref().is_some_and(Commands::is_quiet) {
+        // Runs on every shell prompt, where INFO lines would clutter the terminal
+        tracing::Level::WARN
+    } else {
+        tracing::Level::INFO
+    };
+
+    // The TUI logs to a file so log lines don't break the UI
+    let is_tui_mode = args.command.is_none();
+    if let Err(e) = crate::logging::init(
+        log_level,
+        args.log_format,
+        args.log_file.clone(),
+        is_tui_mode,
+    ) {
+        eprintln!("Error: {}", e);
+        std::process::exit(e.exit_code());
+    }
+
+    let quiet = args.quiet;
+    let headless = args.headless;
+
+    // Execute the appropriate command, offering the setup wizard to first-time users
+    let result = match execute(args).await {
+        Err(SsoError::NotConfigured) if !quiet => commands::init::offer(headless).await,
+        result => result,
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        if let SsoError::ReadOnly { diff, .. } = &e {
+            eprint!("{}", diff);
+        }
+        if let Some(hint) = e.hint().filter(|_| !quiet) {
+            eprintln!("Hint: {}", hint);
+        }
+        std::process::exit(e.exit_code());
+    }
+}
+
 pub async fn execute(args: Cli) -> Result<()> {
+    crate::audit::set_initiator(args.command.as_ref().map_or("tui", Commands::audit_name));
+    crate::config::select_workspace(args.workspace)?;
+    crate::aws_config::set_file_overrides(args.config_file, args.credentials_file);
+
     match args.command {
+        Some(Commands::Init) => {
+            commands::init::execute(args.start_url, args.region, args.headless).await
+        }
         Some(Commands::Session { command }) => {
             commands::session::execute(command, args.headless).await
         }
         Some(Commands::Profile { command }) => {
-            commands::profile::execute(command, args.start_url, args.region).await
+            commands::profile::execute(command, args.start_url, args.region, args.headless).await
         }
         Some(Commands::Import {
             name,
             section_type,
+            all,
             force,
-        }) => commands::import::execute(name, section_type, force).await,
-        Some(Commands::Completions {
+            format,
+        }) => match name {
+            Some(name) if !all => {
+                commands::import::execute(name, section_type, force, format).await
+            }
+            _ => commands::import::execute_all(force, format).await,
+        },
+        Some(Commands::Rollback {
+            list,
+            to,
+            force,
+            format,
+        }) => commands::rollback::execute(list, to, force, format).await,
+        Some(Commands::Cache { command }) => commands::cache::execute(command),
+        Some(Commands::Config { command }) => commands::config::execute(command),
+        Some(Commands::Assume {
+            target,
+            session_name,
             shell,
-            show_install,
-        }) => {
-            commands::completions::execute(shell, show_install);
+            unset,
+        }) => commands::assume::execute(target, session_name, shell, unset, args.headless).await,
+        Some(Commands::Hook { shell }) => {
+            commands::hook::execute(shell);
             Ok(())
         }
+        Some(Commands::Env {
+            profile,
+            account_id,
+            account_name,
+            role_name,
+            session_name,
+            region,
+            shell,
+            unset,
+        }) => {
+            let env_args = commands::env::EnvArgs {
+                profile,
+                account_id,
+                account_name,
+                role_name,
+                session_name,
+                region,
+            };
+            let shell = shell.unwrap_or_else(HookShell::detect);
+            commands::env::execute(env_args, shell, unset, args.headless).await
+        }
+        Some(Commands::Run {
+            account_id,
+            account_name,
+            role_name,
+            session_name,
+            region: aws_region,
+            env_vars,
+            duration,
+            first,
+            command,
+        }) => {
+            commands::run::execute(
+                account_id,
+                account_name,
+                role_name,
+                session_name,
+                args.start_url,
+                args.region,
+                aws_region,
+                env_vars,
+                duration,
+                first,
+                command,
+                args.headless,
+            )
+            .await
+        }
+        Some(Commands::Shell {
+            account_id,
+            account_name,
+            role_name,
+            session_name,
+            refresh,
+        }) => {
+            let shell_args = commands::shell::ShellArgs {
+                account_id,
+                account_name,
+                role_name,
+                session_name,
+                start_url: args.start_url,
+                region: args.region,
+            };
+            commands::shell::execute(shell_args, refresh, args.headless).await
+        }
+        Some(Commands::Pick {
+            session_name,
+            exec,
+            export,
+            console,
+            command,
+        }) => {
+            let action = match (exec, export, console) {
+                (true, _, _) => commands::pick::PickAction::Exec(command),
+                (_, true, _) => commands::pick::PickAction::Export,
+                (_, _, true) => commands::pick::PickAction::Console,
+                _ => commands::pick::PickAction::Print,
+            };
+            commands::pick::execute(
+                session_name,
+                args.start_url,
+                args.region,
+                action,
+                args.headless,
+            )
+            .await
+        }
+        Some(Commands::ExecAll {
+            role_name,
+            filter,
+            session_name,
+            concurrency,
+            command,
+        }) => {
+            commands::exec_all::execute(
+                role_name,
+                filter,
+                session_name,
+                args.start_url,
+                args.region,
+                usize::from(concurrency),
+                command,
+                args.headless,
+            )
+            .await
+        }
+        Some(Commands::Serve {
+            account_id,
+            account_name,
+            role_name,
+            session_name,
+            imds,
+            bind,
+            allow_remote,
+            metrics,
+        }) => {
+            commands::serve::execute(
+                account_id,
+                account_name,
+                role_name,
+                session_name,
+                args.start_url,
+                args.region,
+                imds,
+                bind,
+                allow_remote,
+                metrics,
+                args.headless,
+            )
+            .await
+        }
+        Some(Commands::Eks { command }) => commands::eks::execute(command).await,
+        Some(Commands::Ecr { command }) => commands::ecr::execute(command).await,
+        Some(Commands::Apps { command }) => commands::apps::execute(command).await,
+        Some(Commands::Emit {
+            format,
+            profile,
+            region,
+        }) => commands::emit::execute(format, &profile, region),
+        Some(Commands::Audit { command }) => commands::audit::execute(command),
+        Some(Commands::Stats { clear, format }) => commands::stats::execute(clear, format),
+        Some(Commands::Doctor { format }) => commands::doctor::execute(format).await,
+        Some(Commands::CredentialProcess {
+            session_name,
+            account_id,
+            role_name,
+        }) => commands::credential_process::execute(session_name, account_id, role_name).await,
+        Some(Commands::Prompt {
+            profile,
+            session_name,
+            template,
+        }) => commands::prompt::execute(profile, session_name, template),
+        Some(Commands::Completions {
+            shell,
+            show_install,
+            install,
+            dry_run,
+        }) => commands::completions::execute(shell, show_install, install, dry_run),
         None => {
             // No command specified, launch TUI
             use crate::ui::App;
@@ -310,3 +1782,37 @@ pub async fn execute(args: Cli) -> Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(
+            parse_duration("90m").unwrap(),
+            chrono::Duration::minutes(90)
+        );
+        assert_eq!(parse_duration("12h").unwrap(), chrono::Duration::hours(12));
+        assert!(parse_duration("13h").is_err());
+        assert!(parse_duration("5m").is_err());
+        assert!(parse_duration("2d").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn test_parse_env_var() {
+        assert_eq!(
+            parse_env_var("STAGE=prod").unwrap(),
+            ("STAGE".to_string(), "prod".to_string())
+        );
+        assert_eq!(
+            parse_env_var("OPTS=a=b").unwrap(),
+            ("OPTS".to_string(), "a=b".to_string())
+        );
+        assert_eq!(parse_env_var("EMPTY=").unwrap().1, "");
+        assert!(parse_env_var("=value").is_err());
+        assert!(parse_env_var("STAGE").is_err());
+    }
+}
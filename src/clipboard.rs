@@ -0,0 +1,40 @@
+// System clipboard access
+use crate::config::Config;
+use crate::error::{Result, SsoError};
+use arboard::Clipboard;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Global flag to copy the device login URL (set by `session login --copy`)
+static FORCE_COPY_DEVICE_URL: AtomicBool = AtomicBool::new(false);
+
+/// Kept open for the life of the process: on X11/Wayland the copied text is served by
+/// the process that owns the clipboard, so dropping the handle would empty it
+static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+/// Set the copy-device-URL override (called for `session login --copy`)
+pub fn set_copy_device_url_override(copy: bool) {
+    FORCE_COPY_DEVICE_URL.store(copy, Ordering::Relaxed);
+}
+
+/// Whether the device login URL should be copied, from --copy or `[login] copy_to_clipboard`
+pub fn should_copy_device_url(config: &Config) -> bool {
+    FORCE_COPY_DEVICE_URL.load(Ordering::Relaxed) || config.login.copy_to_clipboard
+}
+
+/// Replace the clipboard contents with `text`
+pub fn copy(text: &str) -> Result<()> {
+    let mut guard = CLIPBOARD
+        .lock()
+        .map_err(|_| SsoError::ClipboardFailed("clipboard lock poisoned".to_string()))?;
+
+    if guard.is_none() {
+        *guard = Some(Clipboard::new().map_err(|e| SsoError::ClipboardFailed(e.to_string()))?);
+    }
+
+    guard
+        .as_mut()
+        .expect("clipboard initialized above")
+        .set_text(text)
+        .map_err(|e| SsoError::ClipboardFailed(e.to_string()))
+}
@@ -1,14 +1,141 @@
 // AWS Console federation and URL generation
+use crate::config::ConsoleConfig;
 use crate::error::{Result, SsoError};
 use crate::models::RoleCredentials;
+use crate::regions::Partition;
 use serde_json::json;
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// Commercial regions offered by the TUI's console region picker; any other region can be
+/// typed in
+pub const COMMON_REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
+    "ca-central-1",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "eu-central-1",
+    "eu-north-1",
+    "ap-south-1",
+    "ap-northeast-1",
+    "ap-northeast-2",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "sa-east-1",
+];
+
+/// Regions the TUI's console region picker offers for a session in `sso_region`: the
+/// common commercial ones, or every GovCloud or China region
+pub fn picker_regions(sso_region: &str) -> Vec<&'static str> {
+    match Partition::from_region(sso_region) {
+        Partition::Aws => COMMON_REGIONS.to_vec(),
+        partition => partition.regions().collect(),
+    }
+}
+
+/// The federation endpoint of `partition`, which trades role credentials for a console
+/// sign-in token
+fn federation_url(partition: Partition) -> &'static str {
+    match partition {
+        Partition::Aws => "https://signin.aws.amazon.com/federation",
+        Partition::AwsUsGov => "https://signin.amazonaws-us-gov.com/federation",
+        Partition::AwsCn => "https://signin.amazonaws.cn/federation",
+    }
+}
+
+/// The console of `partition`
+fn console_url(partition: Partition) -> &'static str {
+    match partition {
+        Partition::Aws => "https://console.aws.amazon.com/",
+        Partition::AwsUsGov => "https://console.amazonaws-us-gov.com/",
+        Partition::AwsCn => "https://console.amazonaws.cn/",
+    }
+}
+
+/// How long one call to the federation endpoint may take
+const FEDERATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Calls made for a sign-in token before giving up on timeouts and server errors
+const FEDERATION_ATTEMPTS: u32 = 3;
+
+/// Shortest console session the federation endpoint accepts
+const MIN_SESSION_DURATION: i64 = 15 * 60;
+
+/// Longest console session the federation endpoint accepts, also used when none is asked for
+const MAX_SESSION_DURATION: i64 = 12 * 3600;
+
+/// Console session length and Issuer of a sign-in URL
+#[derive(Debug, Clone)]
+pub struct SignIn {
+    /// How long the console session lasts; None asks for the maximum
+    pub duration: Option<chrono::Duration>,
+    pub issuer: String,
+}
+
+impl SignIn {
+    /// `[console]` settings, with `duration` (--console-duration) taking precedence
+    pub fn from_config(config: &ConsoleConfig, duration: Option<chrono::Duration>) -> Self {
+        let issuer = config.issuer.trim();
+        Self {
+            duration: duration.or(config.duration_minutes.map(chrono::Duration::minutes)),
+            issuer: if issuer.is_empty() {
+                ConsoleConfig::default().issuer
+            } else {
+                issuer.to_string()
+            },
+        }
+    }
+
+    /// SessionDuration in seconds, clamped to what AWS accepts
+    ///
+    /// A console session can't outlive the role credentials it's made from, so asking for
+    /// more than they have left is an error rather than a session that ends early.
+    fn session_duration(&self, creds: &RoleCredentials) -> Result<i64> {
+        let Some(duration) = self.duration else {
+            return Ok(MAX_SESSION_DURATION);
+        };
+        let seconds = duration
+            .num_seconds()
+            .clamp(MIN_SESSION_DURATION, MAX_SESSION_DURATION);
+        if seconds != duration.num_seconds() {
+            tracing::warn!(
+                "Console session duration of {} minutes is outside AWS's 15 to 720, using {}",
+                duration.num_minutes(),
+                seconds / 60
+            );
+        }
+        if seconds > creds.expires_in_seconds() {
+            return Err(SsoError::InvalidConfig(format!(
+                "A {} minute console session can't outlast the role credentials, which expire \
+                 in {}; ask for a shorter --console-duration, or for longer credentials with \
+                 --duration",
+                seconds / 60,
+                creds.expiration_display()
+            )));
+        }
+        Ok(seconds)
+    }
+}
 
 /// Generate an AWS Console sign-in URL using temporary credentials
 ///
 /// This uses the AWS Federation endpoint to create a sign-in token
 /// that allows accessing the AWS Console with temporary credentials.
-pub fn generate_console_url(creds: &RoleCredentials, region: Option<&str>) -> Result<String> {
+/// The region decides the partition, so its sign-in and console domains are used; the
+/// credentials must come from an account in that partition.
+pub async fn generate_console_url(
+    creds: &RoleCredentials,
+    region: Option<&str>,
+    sign_in: &SignIn,
+) -> Result<String> {
+    let session_duration = sign_in.session_duration(creds)?;
+    let console_region = region.unwrap_or(Partition::Aws.default_region());
+    let federation_url = federation_url(Partition::from_region(console_region));
+
     // Create the session credentials JSON
     let session_json = json!({
         "sessionId": creds.access_key_id,
@@ -20,50 +147,170 @@ pub fn generate_console_url(creds: &RoleCredentials, region: Option<&str>) -> Re
     let session_string = session_json.to_string();
     let encoded_session = urlencoding::encode(&session_string);
 
-    // AWS Federation endpoint
-    let federation_url = "https://signin.aws.amazon.com/federation";
-
     // Step 1: Get the sign-in token
     let token_url = format!(
         "{}?Action=getSigninToken&SessionDuration={}&Session={}",
-        federation_url,
-        43200, // 12 hours (max for federated users)
-        encoded_session
+        federation_url, session_duration, encoded_session
     );
-
-    // Make HTTP request to get the token
-    tracing::debug!("Requesting sign-in token from AWS federation endpoint");
-    let response = reqwest::blocking::get(&token_url).map_err(|e| {
-        SsoError::AuthenticationFailed(format!("Failed to get sign-in token: {}", e))
-    })?;
-
-    let token_response: HashMap<String, String> = response.json().map_err(|e| {
-        SsoError::AuthenticationFailed(format!("Failed to parse token response: {}", e))
-    })?;
-
-    let signin_token = token_response
-        .get("SigninToken")
-        .ok_or_else(|| SsoError::AuthenticationFailed("No SigninToken in response".to_string()))?;
+    let signin_token = get_signin_token(federation_url, &token_url).await?;
 
     // Step 2: Build the console URL
-    let console_region = region.unwrap_or("us-east-1");
-    let destination = format!("https://console.aws.amazon.com/?region={}", console_region);
+    let destination = format!(
+        "{}?region={}",
+        console_url(Partition::from_region(console_region)),
+        console_region
+    );
     let encoded_destination = urlencoding::encode(&destination);
 
     let console_url = format!(
-        "{}?Action=login&Issuer=awsom&Destination={}&SigninToken={}",
-        federation_url, encoded_destination, signin_token
+        "{}?Action=login&Issuer={}&Destination={}&SigninToken={}",
+        federation_url,
+        urlencoding::encode(&sign_in.issuer),
+        encoded_destination,
+        signin_token
     );
 
     Ok(console_url)
 }
 
-/// Open the AWS Console in the default browser
-pub fn open_console(creds: &RoleCredentials, region: Option<&str>) -> Result<()> {
-    let url = generate_console_url(creds, region)?;
+/// Outcome of one call for a sign-in token
+enum Attempt {
+    Token(String),
+    /// A timeout, connection failure, throttling or server error, worth another call
+    Retry(String),
+    Fail(SsoError),
+}
+
+/// Ask the federation endpoint for a sign-in token, retrying transient failures with
+/// backoff
+async fn get_signin_token(federation_url: &str, token_url: &str) -> Result<String> {
+    let client = crate::network::client_builder()?
+        .timeout(FEDERATION_TIMEOUT)
+        .build()
+        .map_err(|e| SsoError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
 
+    tracing::debug!("Requesting sign-in token from AWS federation endpoint");
+    let mut attempt = 1;
+    loop {
+        match request_signin_token(&client, token_url).await {
+            Attempt::Token(token) => return Ok(token),
+            Attempt::Fail(e) => return Err(e),
+            Attempt::Retry(reason) if attempt < FEDERATION_ATTEMPTS => {
+                let delay = crate::network::backoff(attempt);
+                tracing::debug!(
+                    "Sign-in token request failed ({}), retrying in {:?}",
+                    reason,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Attempt::Retry(reason) => {
+                return Err(SsoError::NetworkError(format!(
+                    "Failed to get a console sign-in token from {} after {} attempts: {}",
+                    federation_url, FEDERATION_ATTEMPTS, reason
+                )))
+            }
+        }
+    }
+}
+
+async fn request_signin_token(client: &reqwest::Client, token_url: &str) -> Attempt {
+    // The URL carries the credentials, so it's kept out of every error message
+    let response = match client.get(token_url).send().await {
+        Ok(response) => response,
+        Err(e) if e.is_timeout() => {
+            return Attempt::Retry(format!(
+                "no response within {}s",
+                FEDERATION_TIMEOUT.as_secs()
+            ))
+        }
+        Err(e) => return Attempt::Retry(root_cause(&e.without_url())),
+    };
+
+    let status = response.status();
+    if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Attempt::Retry(format!("HTTP {}", status));
+    }
+    if !status.is_success() {
+        // Expired or otherwise invalid role credentials are turned away with a 400
+        let body = response.text().await.unwrap_or_default();
+        return Attempt::Fail(SsoError::AuthenticationFailed(format!(
+            "The federation endpoint rejected the role credentials (HTTP {}): {}",
+            status,
+            body.trim()
+        )));
+    }
+
+    let token_response: HashMap<String, String> = match response.json().await {
+        Ok(body) => body,
+        Err(e) => {
+            return Attempt::Fail(SsoError::AuthenticationFailed(format!(
+                "Failed to parse token response: {}",
+                e.without_url()
+            )))
+        }
+    };
+    match token_response.get("SigninToken") {
+        Some(token) => Attempt::Token(token.clone()),
+        None => Attempt::Fail(SsoError::AuthenticationFailed(
+            "No SigninToken in response".to_string(),
+        )),
+    }
+}
+
+/// reqwest's own message is just "error sending request"; the cause says why
+fn root_cause(error: &dyn std::error::Error) -> String {
+    let mut cause = error;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
+
+/// Open a sign-in URL from `generate_console_url` in the default browser
+pub fn open_url(url: &str) -> Result<()> {
     tracing::info!("Opening AWS Console in browser");
-    webbrowser::open(&url).map_err(|e| SsoError::BrowserLaunchFailed(format!("{}", e)))?;
+    webbrowser::open(url).map_err(|e| SsoError::BrowserUnavailable(format!("{}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn creds_expiring_in(minutes: i64) -> RoleCredentials {
+        RoleCredentials {
+            access_key_id: "ASIA".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: "token".to_string(),
+            expiration: Utc::now() + chrono::Duration::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn test_session_duration() {
+        let config = ConsoleConfig {
+            issuer: " ".to_string(),
+            duration_minutes: Some(60),
+            ..ConsoleConfig::default()
+        };
+        let creds = creds_expiring_in(120);
+
+        let sign_in = SignIn::from_config(&config, None);
+        assert_eq!(sign_in.issuer, "awsom");
+        assert_eq!(sign_in.session_duration(&creds).unwrap(), 3600);
+
+        // --console-duration wins over the config, and is clamped to AWS's range
+        let sign_in = SignIn::from_config(&config, Some(chrono::Duration::minutes(5)));
+        assert_eq!(sign_in.session_duration(&creds).unwrap(), 900);
+
+        let sign_in = SignIn::from_config(&config, Some(chrono::Duration::hours(4)));
+        let error = sign_in.session_duration(&creds).unwrap_err().to_string();
+        assert!(error.contains("240 minute console session"), "{}", error);
 
-    Ok(())
+        // Without a duration the maximum is asked for, whatever the credentials have left
+        let sign_in = SignIn::from_config(&ConsoleConfig::default(), None);
+        assert_eq!(sign_in.session_duration(&creds).unwrap(), 43200);
+    }
 }
@@ -1,13 +1,41 @@
+use super::encryption::CacheCipher;
+use crate::config::{self, Config};
 use crate::error::{Result, SsoError};
+use crate::keyring_store::KeyringStore;
 use crate::models::{AccountRole, RoleCredentials, SsoInstance};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sha1::{Digest, Sha1};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Credential cache compatible with AWS CLI v2
-/// Stores credentials in ~/.aws/cli/cache/
+/// Stores credentials in ~/.aws/cli/cache/, or in the OS keychain when configured
+///
+/// Entries are keyed by start URL, account and role. A requested minimum lifetime is not
+/// part of the key: SSO hands out the same credentials whatever it is, so callers check
+/// the expiry of what they get instead.
 pub struct CredentialCache {
     cache_dir: PathBuf,
+    keyring: Option<KeyringStore>,
+    encryption: FileEncryption,
+}
+
+/// How role credentials written to cache files are protected
+enum FileEncryption {
+    Off,
+    On(Box<CacheCipher>),
+    /// Encryption is configured but the keychain holding the key can't be reached, so
+    /// credentials are not written to files at all
+    Unavailable,
+}
+
+/// A cache file written with encryption on; the expiry stays readable for `cache list`
+#[derive(Serialize, Deserialize)]
+struct SealedFile {
+    expiration: DateTime<Utc>,
+    sealed: String,
 }
 
 impl CredentialCache {
@@ -19,11 +47,31 @@ impl CredentialCache {
             .join("cache");
 
         // Create cache directory if it doesn't exist
-        if !cache_dir.exists() {
+        if !cache_dir.exists() && !config::is_read_only() {
             fs::create_dir_all(&cache_dir)?;
         }
 
-        Ok(Self { cache_dir })
+        let encryption = if Config::load()?.security.encrypt_credentials {
+            match CacheCipher::from_keyring(&KeyringStore) {
+                Ok(cipher) => FileEncryption::On(Box::new(cipher)),
+                Err(e) => {
+                    tracing::warn!("{}; role credentials won't be cached on disk", e);
+                    FileEncryption::Unavailable
+                }
+            }
+        } else {
+            FileEncryption::Off
+        };
+
+        Ok(Self {
+            cache_dir,
+            keyring: KeyringStore::from_config()?,
+            encryption,
+        })
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
     }
 
     /// Generate cache key for a role
@@ -43,20 +91,47 @@ impl CredentialCache {
             .join(format!("{}.json", self.cache_key(instance, role)))
     }
 
+    /// Keychain entry name for a role
+    fn keyring_key(&self, instance: &SsoInstance, role: &AccountRole) -> String {
+        format!("role-credentials:{}", self.cache_key(instance, role))
+    }
+
+    /// Read credentials JSON from the keychain, None if not configured, missing or unreachable
+    fn read_from_keyring(&self, instance: &SsoInstance, role: &AccountRole) -> Option<String> {
+        let keyring = self.keyring.as_ref()?;
+        match keyring.get(&self.keyring_key(instance, role)) {
+            Ok(contents) => contents,
+            Err(e) => {
+                tracing::warn!("{}; falling back to file cache", e);
+                None
+            }
+        }
+    }
+
     /// Get cached credentials
     pub fn get_credentials(
         &self,
         instance: &SsoInstance,
         role: &AccountRole,
     ) -> Result<Option<RoleCredentials>> {
-        let cache_file = self.cache_file_path(instance, role);
+        let contents = match self.read_from_keyring(instance, role) {
+            Some(contents) => contents,
+            None => {
+                let cache_file = self.cache_file_path(instance, role);
 
-        if !cache_file.exists() {
-            return Ok(None);
-        }
+                if !cache_file.exists() {
+                    return Ok(None);
+                }
 
-        let contents = fs::read_to_string(&cache_file)
-            .map_err(|e| SsoError::CacheError(format!("Failed to read cache file: {}", e)))?;
+                let contents = fs::read_to_string(&cache_file).map_err(|e| {
+                    SsoError::CacheError(format!("Failed to read cache file: {}", e))
+                })?;
+                match self.open_file(instance, role, contents) {
+                    Some(contents) => contents,
+                    None => return Ok(None),
+                }
+            }
+        };
 
         let creds: RoleCredentials = serde_json::from_str(&contents)?;
 
@@ -68,25 +143,131 @@ impl CredentialCache {
         Ok(Some(creds))
     }
 
-    /// Save credentials to cache
+    /// When the role's credentials were written to their cache file, from its mtime; None
+    /// without a file (e.g. kept in the keychain)
+    pub fn written_at(&self, instance: &SsoInstance, role: &AccountRole) -> Option<DateTime<Utc>> {
+        fs::metadata(self.cache_file_path(instance, role))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(DateTime::from)
+    }
+
+    /// Save credentials to cache; skipped in read-only mode, as they're only a cache
     pub fn save_credentials(
         &self,
         instance: &SsoInstance,
         role: &AccountRole,
         creds: &RoleCredentials,
     ) -> Result<()> {
+        if config::is_read_only() {
+            tracing::debug!("Read-only mode: not caching credentials");
+            return Ok(());
+        }
         let cache_file = self.cache_file_path(instance, role);
 
         let json = serde_json::to_string_pretty(creds)?;
 
-        fs::write(&cache_file, json)
+        if let Some(keyring) = &self.keyring {
+            match keyring.set(&self.keyring_key(instance, role), &json) {
+                Ok(()) => {
+                    // Don't leave a stale plaintext copy behind
+                    if cache_file.exists() {
+                        fs::remove_file(&cache_file)?;
+                    }
+                    return Ok(());
+                }
+                Err(e) => tracing::warn!("{}; falling back to file cache", e),
+            }
+        }
+
+        let contents = match &self.encryption {
+            FileEncryption::Off => json,
+            FileEncryption::On(cipher) => serde_json::to_string_pretty(&SealedFile {
+                expiration: creds.expiration,
+                sealed: cipher.seal(&json, &self.cache_key(instance, role))?,
+            })?,
+            FileEncryption::Unavailable => return Ok(()),
+        };
+        fs::write(&cache_file, contents)
             .map_err(|e| SsoError::CacheError(format!("Failed to write cache file: {}", e)))?;
 
         Ok(())
     }
 
+    /// Credentials JSON of a cache file, decrypting it if it was written encrypted; None if
+    /// it can't be decrypted. Plain files are still read with encryption on, so credentials
+    /// cached before it was turned on stay usable until they expire.
+    fn open_file(
+        &self,
+        instance: &SsoInstance,
+        role: &AccountRole,
+        contents: String,
+    ) -> Option<String> {
+        let Ok(file) = serde_json::from_str::<SealedFile>(&contents) else {
+            return Some(contents);
+        };
+        let FileEncryption::On(cipher) = &self.encryption else {
+            tracing::debug!("Cached credentials are encrypted but encryption is off");
+            return None;
+        };
+        match cipher.open(&file.sealed, &self.cache_key(instance, role)) {
+            Ok(json) => Some(json),
+            Err(e) => {
+                tracing::warn!("{}", e);
+                None
+            }
+        }
+    }
+
+    /// Write credentials under the name and in the format the AWS CLI uses when it resolves
+    /// an SSO profile itself, so the CLI and tools that read its cache (Terraform, SDKs)
+    /// use them instead of calling GetRoleCredentials. Returns the file written.
+    ///
+    /// Profiles with `sso_session` are keyed by session name; legacy profiles with only
+    /// `sso_start_url` by start URL. Always a file, even with the keychain store, since the
+    /// point is for other tools to read it.
+    pub fn export_for_aws_cli(
+        &self,
+        session_name: Option<&str>,
+        start_url: &str,
+        account_id: &str,
+        role_name: &str,
+        creds: &RoleCredentials,
+    ) -> Result<PathBuf> {
+        let path = self.cache_dir.join(format!(
+            "{}.json",
+            aws_cli_cache_key(session_name, start_url, account_id, role_name)
+        ));
+        config::ensure_writable(&path)?;
+        let contents = json!({
+            "ProviderType": "sso",
+            "Credentials": {
+                "AccessKeyId": creds.access_key_id,
+                "SecretAccessKey": creds.secret_access_key,
+                "SessionToken": creds.session_token,
+                "Expiration": creds.expiration.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            },
+        });
+
+        fs::write(&path, serde_json::to_string_pretty(&contents)?)
+            .map_err(|e| SsoError::CacheError(format!("Failed to write cache file: {}", e)))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(path)
+    }
+
     /// Remove credentials from cache
     pub fn remove_credentials(&self, instance: &SsoInstance, role: &AccountRole) -> Result<()> {
+        config::ensure_writable(&self.cache_dir)?;
+        if let Some(keyring) = &self.keyring {
+            if let Err(e) = keyring.delete(&self.keyring_key(instance, role)) {
+                tracing::warn!("{}", e);
+            }
+        }
+
         let cache_file = self.cache_file_path(instance, role);
 
         if cache_file.exists() {
@@ -97,17 +278,102 @@ impl CredentialCache {
         Ok(())
     }
 
-    /// Clear all cached credentials
-    pub fn clear_all(&self) -> Result<()> {
-        if self.cache_dir.exists() {
-            for entry in fs::read_dir(&self.cache_dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-                    fs::remove_file(path)?;
-                }
+    /// Cache files with their expiry, which is None when the file isn't in a format awsom or
+    /// the AWS CLI writes. Only covers the file cache - the keychain can't be enumerated
+    pub fn list_files(&self) -> Result<Vec<(PathBuf, Option<DateTime<Utc>>)>> {
+        let mut files = Vec::new();
+        if !self.cache_dir.exists() {
+            return Ok(files);
+        }
+
+        for entry in fs::read_dir(&self.cache_dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
+                let expiration = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|contents| cached_expiration(&contents));
+                files.push((path, expiration));
             }
         }
-        Ok(())
+        Ok(files)
+    }
+
+    /// Clear all cached credentials, returning how many files were removed
+    /// Only covers the file cache - the keychain can't be enumerated
+    pub fn clear_all(&self) -> Result<usize> {
+        config::ensure_writable(&self.cache_dir)?;
+        let files = self.list_files()?;
+        for (path, _) in &files {
+            fs::remove_file(path)?;
+        }
+        Ok(files.len())
+    }
+}
+
+/// Expiry of a cache file in awsom's format (plain or encrypted) or the AWS CLI's
+/// {"Credentials": {...}} one
+fn cached_expiration(contents: &str) -> Option<DateTime<Utc>> {
+    if let Ok(creds) = serde_json::from_str::<RoleCredentials>(contents) {
+        return Some(creds.expiration);
+    }
+
+    let value: serde_json::Value = serde_json::from_str(contents).ok()?;
+    let expiration = value["Credentials"]["Expiration"]
+        .as_str()
+        .or_else(|| value["expiration"].as_str())?;
+    DateTime::parse_from_rfc3339(expiration)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// SHA1 of the request arguments as botocore serializes them: compact JSON with sorted keys
+fn aws_cli_cache_key(
+    session_name: Option<&str>,
+    start_url: &str,
+    account_id: &str,
+    role_name: &str,
+) -> String {
+    let mut args = json!({ "accountId": account_id, "roleName": role_name });
+    match session_name {
+        Some(session) => args["sessionName"] = json!(session),
+        None => args["startUrl"] = json!(start_url),
+    }
+
+    let mut hasher = Sha1::new();
+    hasher.update(args.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aws_cli_cache_key() {
+        // sha1('{"accountId":"123456789012","roleName":"ReadOnly","sessionName":"corp"}')
+        assert_eq!(
+            aws_cli_cache_key(Some("corp"), "https://x", "123456789012", "ReadOnly"),
+            "999fa299c534d8ffbe1215f572353203fbe5c4b7"
+        );
+        // sha1('{"accountId":"123456789012","roleName":"ReadOnly","startUrl":"https://x"}')
+        assert_eq!(
+            aws_cli_cache_key(None, "https://x", "123456789012", "ReadOnly"),
+            "d5f845f071eee737777c1fa2697d03b07b02d478"
+        );
+    }
+
+    #[test]
+    fn test_cached_expiration() {
+        let awsom = r#"{"access_key_id":"AKIA","secret_access_key":"s","session_token":"t",
+            "expiration":"2026-03-01T12:00:00Z"}"#;
+        let aws_cli = r#"{"ProviderType":"sso","Credentials":{"AccessKeyId":"AKIA",
+            "Expiration":"2026-03-01T12:00:00Z"}}"#;
+        let expected = "2026-03-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(cached_expiration(awsom), Some(expected));
+        assert_eq!(cached_expiration(aws_cli), Some(expected));
+        let sealed = r#"{"expiration":"2026-03-01T12:00:00Z","sealed":"00ff"}"#;
+        assert_eq!(cached_expiration(sealed), Some(expected));
+        assert_eq!(cached_expiration(r#"{"Credentials":{}}"#), None);
     }
 }
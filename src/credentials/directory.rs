@@ -0,0 +1,117 @@
+// On-disk cache of the account/role directory
+//
+// Listing every account and role takes one API call per account, so the result is kept in
+// <cache dir>/awsom/accounts/<sha1 of start URL>.json. Listings younger than
+// `[cache] accounts_ttl_minutes` are used as they are; older ones are still shown by the
+// TUI while a fresh listing loads.
+use crate::config;
+use crate::error::Result;
+use crate::models::AccountRole;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by --no-cache: never read the directory cache (fresh listings are still saved)
+static BYPASS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_bypass(bypass: bool) {
+    BYPASS.store(bypass, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedDirectory {
+    pub start_url: String,
+    pub fetched_at: DateTime<Utc>,
+    pub roles: Vec<AccountRole>,
+}
+
+impl CachedDirectory {
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        Utc::now() - self.fetched_at < ttl
+    }
+}
+
+pub fn cache_dir() -> PathBuf {
+    config::cache_dir().join("accounts")
+}
+
+fn cache_file(start_url: &str) -> PathBuf {
+    let mut hasher = Sha1::new();
+    hasher.update(start_url.as_bytes());
+    cache_dir().join(format!("{:x}.json", hasher.finalize()))
+}
+
+/// The cached listing for an SSO instance, None if there is none, it can't be read, or
+/// --no-cache was given
+pub fn load(start_url: &str) -> Option<CachedDirectory> {
+    if BYPASS.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let contents = fs::read_to_string(cache_file(start_url)).ok()?;
+    match serde_json::from_str::<CachedDirectory>(&contents) {
+        Ok(cached) if cached.start_url == start_url => Some(cached),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::debug!("Ignoring unreadable account cache: {}", e);
+            None
+        }
+    }
+}
+
+pub fn save(start_url: &str, roles: &[AccountRole]) -> Result<()> {
+    if config::is_read_only() {
+        tracing::debug!("Read-only mode: not caching the account list");
+        return Ok(());
+    }
+    fs::create_dir_all(cache_dir())?;
+    let cached = CachedDirectory {
+        start_url: start_url.to_string(),
+        fetched_at: Utc::now(),
+        roles: roles.to_vec(),
+    };
+    fs::write(cache_file(start_url), serde_json::to_string(&cached)?)?;
+    Ok(())
+}
+
+/// Every cached listing with its file, for `awsom cache list`
+pub fn list() -> Result<Vec<(PathBuf, CachedDirectory)>> {
+    let dir = cache_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut listings = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            match serde_json::from_str::<CachedDirectory>(&fs::read_to_string(&path)?) {
+                Ok(cached) => listings.push((path, cached)),
+                Err(e) => tracing::debug!("Skipping unreadable account cache: {}", e),
+            }
+        }
+    }
+    Ok(listings)
+}
+
+/// Delete every cached listing, returning how many there were
+pub fn clear() -> Result<usize> {
+    let dir = cache_dir();
+    if !dir.exists() {
+        return Ok(0);
+    }
+    config::ensure_writable(&dir)?;
+
+    let mut removed = 0;
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
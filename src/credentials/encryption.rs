@@ -0,0 +1,125 @@
+// At-rest encryption for the role-credentials file cache
+//
+// With `[security] encrypt_credentials = true`, cache files hold AES-256-GCM ciphertext
+// under a random key that lives in the OS keychain, so a copied ~/.aws/cli/cache is of no
+// use without the keychain it came from.
+use crate::error::{Result, SsoError};
+use crate::keyring_store::KeyringStore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fmt::Write;
+
+/// Keychain entry holding the hex-encoded cache key
+const KEY_ENTRY: &str = "credential-cache-key";
+
+pub struct CacheCipher {
+    key: LessSafeKey,
+}
+
+impl CacheCipher {
+    /// The cache key from the keychain, created on first use
+    pub fn from_keyring(keyring: &KeyringStore) -> Result<Self> {
+        let key = match keyring.get(KEY_ENTRY)? {
+            Some(stored) => decode_hex(&stored).ok_or_else(|| {
+                SsoError::CacheError("Credential cache key in the keychain is corrupt".to_string())
+            })?,
+            None => {
+                let mut key = [0u8; 32];
+                fill_random(&mut key)?;
+                // In read-only mode the key only lives for this run; nothing reaches the cache
+                if !crate::config::is_read_only() {
+                    keyring.set(KEY_ENTRY, &encode_hex(&key))?;
+                }
+                key.to_vec()
+            }
+        };
+        Self::new(&key)
+    }
+
+    fn new(key: &[u8]) -> Result<Self> {
+        let key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| SsoError::CacheError("Invalid credential cache key".to_string()))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    /// Encrypt `plaintext`, bound to `context` so the result can't be moved to another
+    /// entry; hex of a random nonce followed by the ciphertext
+    pub fn seal(&self, plaintext: &str, context: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        fill_random(&mut nonce)?;
+
+        let mut data = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(context.as_bytes()),
+                &mut data,
+            )
+            .map_err(|_| SsoError::CacheError("Failed to encrypt credentials".to_string()))?;
+        Ok(encode_hex(&[&nonce[..], &data].concat()))
+    }
+
+    /// Decrypt what `seal` returned for the same `context`
+    pub fn open(&self, sealed: &str, context: &str) -> Result<String> {
+        let failed = || SsoError::CacheError("Failed to decrypt cached credentials".to_string());
+        let bytes = decode_hex(sealed)
+            .filter(|bytes| bytes.len() > NONCE_LEN)
+            .ok_or_else(failed)?;
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| failed())?;
+
+        let mut data = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(context.as_bytes()), &mut data)
+            .map_err(|_| failed())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| failed())
+    }
+}
+
+fn fill_random(bytes: &mut [u8]) -> Result<()> {
+    SystemRandom::new()
+        .fill(bytes)
+        .map_err(|_| SsoError::CacheError("No secure random numbers available".to_string()))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut out, byte| {
+        let _ = write!(out, "{:02x}", byte);
+        out
+    })
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let cipher = CacheCipher::new(&[7u8; 32]).unwrap();
+        let sealed = cipher.seal("{\"secret\":1}", "role-a").unwrap();
+        assert!(!sealed.contains("secret"));
+        assert_eq!(cipher.open(&sealed, "role-a").unwrap(), "{\"secret\":1}");
+
+        // Another entry's context, another key or a tampered byte all fail
+        assert!(cipher.open(&sealed, "role-b").is_err());
+        let other = CacheCipher::new(&[8u8; 32]).unwrap();
+        assert!(other.open(&sealed, "role-a").is_err());
+        let mut tampered = sealed.clone();
+        let last = if tampered.ends_with('0') { "1" } else { "0" };
+        tampered.replace_range(tampered.len() - 1.., last);
+        assert!(cipher.open(&tampered, "role-a").is_err());
+    }
+}
@@ -1,19 +1,44 @@
-use crate::error::{Result, SsoError};
+use crate::audit::{self, AuditAction};
+use crate::error::{self, Result, SsoError};
 use crate::models::RoleCredentials;
+use aws_sdk_sso::error::{ProvideErrorMetadata, SdkError};
 use aws_sdk_sso::Client as SsoClient;
-use chrono::{TimeZone, Utc};
+use chrono::{Duration, TimeZone, Utc};
+
+/// Convert an SSO API error, surfacing a rejected access token as `SsoError::AuthExpired`
+/// so callers can re-authenticate instead of failing outright, and an unreachable endpoint
+/// as `SsoError::NetworkError`
+fn sdk_error<E, R>(context: &str, err: SdkError<E, R>) -> SsoError
+where
+    E: ProvideErrorMetadata + std::error::Error + 'static,
+    R: std::fmt::Debug,
+{
+    if err.code() == Some("UnauthorizedException") {
+        return SsoError::AuthExpired;
+    }
+    if error::is_network_error(&err) {
+        return SsoError::NetworkError(format!("{}: {}", context, err));
+    }
+    SsoError::AwsSdk(format!("{}: {}", context, err))
+}
+
+fn hours_and_minutes(duration: Duration) -> String {
+    match (duration.num_hours(), duration.num_minutes() % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
 
 /// Fetches role credentials from AWS SSO
+#[derive(Clone)]
 pub struct CredentialFetcher {
     client: SsoClient,
 }
 
 impl CredentialFetcher {
     pub async fn new(region: &str) -> Result<Self> {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
+        let config = crate::network::sdk_config(region).await?;
 
         let client = SsoClient::new(&config);
 
@@ -21,11 +46,16 @@ impl CredentialFetcher {
     }
 
     /// Fetch credentials for a specific account/role
+    ///
+    /// SSO always issues credentials for the permission set's session duration, so a
+    /// requested `duration` can't lengthen them; credentials that expire sooner than it
+    /// are an error rather than a surprise halfway through a long job.
     pub async fn fetch_credentials(
         &self,
         access_token: &str,
         account_id: &str,
         role_name: &str,
+        duration: Option<Duration>,
     ) -> Result<RoleCredentials> {
         let response = self
             .client
@@ -35,7 +65,7 @@ impl CredentialFetcher {
             .role_name(role_name)
             .send()
             .await
-            .map_err(|e| SsoError::AwsSdk(format!("Failed to get role credentials: {}", e)))?;
+            .map_err(|e| sdk_error("Failed to get role credentials", e))?;
 
         let role_creds = response
             .role_credentials()
@@ -62,6 +92,26 @@ impl CredentialFetcher {
             .single()
             .ok_or_else(|| SsoError::AwsSdk("Invalid expiration timestamp".to_string()))?;
 
+        audit::record(
+            AuditAction::CredentialsIssued,
+            Some(account_id),
+            Some(role_name),
+            None,
+        );
+
+        // Allow for the time the request took
+        let valid_for = expiration - Utc::now() + Duration::minutes(1);
+        if let Some(duration) = duration.filter(|d| valid_for < *d) {
+            return Err(SsoError::InvalidConfig(format!(
+                "{} in account {} only gets credentials for {} (its permission set's session \
+                 duration), less than the requested {}",
+                role_name,
+                account_id,
+                hours_and_minutes(valid_for),
+                hours_and_minutes(duration)
+            )));
+        }
+
         Ok(RoleCredentials {
             access_key_id,
             secret_access_key,
@@ -85,7 +135,7 @@ impl CredentialFetcher {
             let response = request
                 .send()
                 .await
-                .map_err(|e| SsoError::AwsSdk(format!("Failed to list accounts: {}", e)))?;
+                .map_err(|e| sdk_error("Failed to list accounts", e))?;
 
             for account in response.account_list() {
                 let account_id = account.account_id().unwrap_or("").to_string();
@@ -125,7 +175,7 @@ impl CredentialFetcher {
             let response = request
                 .send()
                 .await
-                .map_err(|e| SsoError::AwsSdk(format!("Failed to list account roles: {}", e)))?;
+                .map_err(|e| sdk_error("Failed to list account roles", e))?;
 
             for role in response.role_list() {
                 if let Some(role_name) = role.role_name() {
@@ -1,12 +1,23 @@
 // Credential fetching and caching
 mod cache;
+pub mod directory;
+mod encryption;
 mod fetcher;
 
 pub use cache::CredentialCache;
 pub use fetcher::CredentialFetcher;
 
-use crate::error::Result;
+use crate::config::Config;
+use crate::error::{Result, SsoError};
+use crate::expiry;
 use crate::models::{AccountRole, RoleCredentials, SsoInstance, SsoToken};
+use chrono::Duration;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Accounts whose roles are listed at the same time by `discover_roles`
+const DISCOVERY_CONCURRENCY: usize = 8;
 
 /// High-level credential management
 pub struct CredentialManager {
@@ -26,22 +37,67 @@ impl CredentialManager {
         instance: &SsoInstance,
         token: &SsoToken,
         role: &AccountRole,
+    ) -> Result<RoleCredentials> {
+        self.get_credentials_valid_for(instance, token, role, Duration::zero())
+            .await
+    }
+
+    /// Get credentials for a role that stay valid for at least `min_validity`, fetching
+    /// new ones if the cached credentials expire sooner
+    pub async fn get_credentials_valid_for(
+        &self,
+        instance: &SsoInstance,
+        token: &SsoToken,
+        role: &AccountRole,
+        min_validity: Duration,
     ) -> Result<RoleCredentials> {
         // Check cache first
         if let Some(creds) = self.cache.get_credentials(instance, role)? {
-            if !creds.is_expired() {
+            if expiry::usable_for(&creds.expiration) > min_validity {
+                return Ok(creds);
+            }
+        }
+
+        self.refresh_credentials(instance, &token.access_token, role, None)
+            .await
+    }
+
+    /// Get credentials for a role from the cache, or fetch them; with `duration`, cached
+    /// credentials must last that long and fetching fails if new ones don't
+    pub async fn get_role_credentials(
+        &self,
+        instance: &SsoInstance,
+        access_token: &str,
+        role: &AccountRole,
+        duration: Option<Duration>,
+    ) -> Result<RoleCredentials> {
+        if let Some(creds) = self.cache.get_credentials(instance, role)? {
+            if expiry::usable_for(&creds.expiration) > duration.unwrap_or_else(Duration::zero) {
                 return Ok(creds);
             }
         }
 
-        // Fetch fresh credentials
+        self.refresh_credentials(instance, access_token, role, duration)
+            .await
+    }
+
+    /// Fetch new credentials for a role even if cached ones are still valid, and cache them
+    pub async fn refresh_credentials(
+        &self,
+        instance: &SsoInstance,
+        access_token: &str,
+        role: &AccountRole,
+        duration: Option<Duration>,
+    ) -> Result<RoleCredentials> {
         let fetcher = CredentialFetcher::new(&instance.region).await?;
         let creds = fetcher
-            .fetch_credentials(&token.access_token, &role.account_id, &role.role_name)
+            .fetch_credentials(access_token, &role.account_id, &role.role_name, duration)
             .await?;
 
-        // Cache for future use
-        self.cache.save_credentials(instance, role, &creds)?;
+        // A cache that can't be written only costs a fetch next time
+        if let Err(e) = self.cache.save_credentials(instance, role, &creds) {
+            tracing::warn!("Failed to cache credentials: {}", e);
+        }
 
         Ok(creds)
     }
@@ -67,18 +123,113 @@ impl CredentialManager {
         fetcher.list_account_roles(access_token, account_id).await
     }
 
-    /// Get role credentials directly (without instance/caching)
-    pub async fn get_role_credentials(
+    /// Every role in every account the token can access
+    ///
+    /// Roles are listed for up to `DISCOVERY_CONCURRENCY` accounts at once. Each account's
+    /// roles go to `on_account` as soon as they arrive, with the number of accounts done
+    /// and the total; the full list is returned sorted by account name and role. Accounts
+    /// outside the active workspace's filter are left out of both. The first failure stops
+    /// the discovery.
+    pub async fn discover_roles(
         &self,
-        region: &str,
+        instance: &SsoInstance,
         access_token: &str,
-        account_id: &str,
-        role_name: &str,
-    ) -> Result<RoleCredentials> {
-        let fetcher = CredentialFetcher::new(region).await?;
-        fetcher
-            .fetch_credentials(access_token, account_id, role_name)
-            .await
+        mut on_account: impl FnMut(&[AccountRole], usize, usize),
+    ) -> Result<Vec<AccountRole>> {
+        let fetcher = CredentialFetcher::new(&instance.region).await?;
+        let accounts = fetcher.list_accounts(access_token).await?;
+        let total = accounts.len();
+
+        let semaphore = Arc::new(Semaphore::new(DISCOVERY_CONCURRENCY));
+        let access_token = Arc::new(access_token.to_string());
+        let mut tasks = JoinSet::new();
+        for (account_id, account_name) in accounts {
+            let fetcher = fetcher.clone();
+            let semaphore = Arc::clone(&semaphore);
+            let access_token = Arc::clone(&access_token);
+
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let roles = fetcher
+                    .list_account_roles(&access_token, &account_id)
+                    .await?;
+                Ok::<_, SsoError>(
+                    roles
+                        .into_iter()
+                        .map(|role_name| AccountRole {
+                            account_id: account_id.clone(),
+                            account_name: account_name.clone(),
+                            role_name,
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            });
+        }
+
+        let config = Config::load().unwrap_or_default();
+        let session = filter_session(instance);
+        let mut all_roles = Vec::new();
+        let mut done = 0;
+        while let Some(joined) = tasks.join_next().await {
+            let roles = joined
+                .map_err(|e| SsoError::AwsSdk(format!("Failed to list account roles: {}", e)))??;
+            done += 1;
+            let shown: Vec<_> = roles
+                .iter()
+                .filter(|role| config.shows_account(session.as_deref(), role))
+                .cloned()
+                .collect();
+            on_account(&shown, done, total);
+            all_roles.extend(roles);
+        }
+
+        all_roles.sort_by(|a, b| {
+            (&a.account_name, &a.account_id, &a.role_name).cmp(&(
+                &b.account_name,
+                &b.account_id,
+                &b.role_name,
+            ))
+        });
+
+        // The cache holds every role so that switching workspaces or editing filters doesn't
+        // need a reload
+        if let Err(e) = directory::save(&instance.start_url, &all_roles) {
+            tracing::warn!("Failed to cache account list: {}", e);
+        }
+        all_roles.retain(|role| config.shows_account(session.as_deref(), role));
+        Ok(all_roles)
+    }
+
+    /// Every role the token can access, from the directory cache while it is younger than
+    /// `[cache] accounts_ttl_minutes`
+    pub async fn list_roles(
+        &self,
+        instance: &SsoInstance,
+        access_token: &str,
+    ) -> Result<Vec<AccountRole>> {
+        let config = Config::load()?;
+        let ttl = Duration::minutes(config.cache.accounts_ttl_minutes);
+        match directory::load(&instance.start_url) {
+            Some(mut cached) if cached.is_fresh(ttl) => {
+                let session = filter_session(instance);
+                cached
+                    .roles
+                    .retain(|role| config.shows_account(session.as_deref(), role));
+                Ok(cached.roles)
+            }
+            _ => {
+                self.discover_roles(instance, access_token, |_, _, _| {})
+                    .await
+            }
+        }
+    }
+
+    /// How long the role's cached credentials were issued for, from when they were cached to
+    /// their expiry; None without live credentials in a cache file
+    pub fn cached_lifetime(&self, instance: &SsoInstance, role: &AccountRole) -> Option<Duration> {
+        let creds = self.cache.get_credentials(instance, role).ok()??;
+        let written_at = self.cache.written_at(instance, role)?;
+        Some(creds.expiration - written_at)
     }
 
     /// Clear cached credentials for a role
@@ -88,10 +239,20 @@ impl CredentialManager {
 
     /// Clear all cached credentials
     pub fn clear_all(&self) -> Result<()> {
-        self.cache.clear_all()
+        self.cache.clear_all().map(|_| ())
     }
 }
 
+/// The instance's [sso-session] name, which picks its `[account_filters]` entry; commands
+/// given only a start URL find it in ~/.aws/config
+fn filter_session(instance: &SsoInstance) -> Option<String> {
+    instance.session_name.clone().or_else(|| {
+        crate::aws_config::session_name_for_start_url(&instance.start_url)
+            .ok()
+            .flatten()
+    })
+}
+
 impl Default for CredentialManager {
     fn default() -> Self {
         Self::new().expect("Failed to initialize CredentialManager")
@@ -0,0 +1,112 @@
+// ECR registry passwords for SSO roles
+//
+// GetAuthorizationToken is called on the ECR JSON API of the region's partition through
+// sigv4.rs. Its token is base64 "AWS:<password>", good for `docker login` for 12 hours.
+use crate::error::{Result, SsoError};
+use crate::models::RoleCredentials;
+use crate::sigv4::{self, Client};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+const TARGET: &str = "AmazonEC2ContainerRegistry_V20150921.GetAuthorizationToken";
+
+/// Credentials for one registry
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryLogin {
+    /// e.g. https://111111111111.dkr.ecr.eu-west-1.amazonaws.com
+    pub endpoint: String,
+    pub username: String,
+    pub password: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Logins for the role's own registry, or for each of `registry_ids`
+pub async fn get_authorization(
+    creds: &RoleCredentials,
+    region: &str,
+    registry_ids: &[String],
+) -> Result<Vec<RegistryLogin>> {
+    let request = if registry_ids.is_empty() {
+        json!({})
+    } else {
+        json!({ "registryIds": registry_ids })
+    };
+    let value = Client::new(creds, "ecr", region, sigv4::endpoint("api.ecr", region))?
+        .call_json(TARGET, &request)
+        .await?;
+
+    value["authorizationData"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|data| {
+            let (username, password) =
+                decode_token(data["authorizationToken"].as_str().unwrap_or_default())?;
+            Ok(RegistryLogin {
+                endpoint: data["proxyEndpoint"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                username,
+                password,
+                expires_at: data["expiresAt"]
+                    .as_f64()
+                    .and_then(|secs| DateTime::from_timestamp(secs as i64, 0)),
+            })
+        })
+        .collect()
+}
+
+/// Split a base64 "user:password" authorization token
+fn decode_token(token: &str) -> Result<(String, String)> {
+    let invalid = || SsoError::AwsSdk("Unreadable ECR authorization token".to_string());
+    let decoded = base64_decode(token).ok_or_else(invalid)?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (username, password) = decoded.split_once(':').ok_or_else(invalid)?;
+    Ok((username.to_string(), password.to_string()))
+}
+
+/// Standard, padded base64
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let text = text.trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            n |= u32::from(value(c)?) << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_token() {
+        // base64 of "AWS:pass/word+="
+        assert_eq!(
+            decode_token("QVdTOnBhc3Mvd29yZCs9").unwrap(),
+            ("AWS".to_string(), "pass/word+=".to_string())
+        );
+        // Padded
+        assert_eq!(base64_decode("QVdTOmE=").unwrap(), b"AWS:a");
+        assert!(decode_token("not base64!").is_err());
+        assert!(decode_token("bm9jb2xvbg==").is_err());
+    }
+}
@@ -0,0 +1,361 @@
+// EKS cluster access with SSO role credentials
+//
+// `update_kubeconfig` adds a cluster to the kubeconfig with a user whose exec plugin runs
+// `awsom eks get-token`, so kubectl authenticates as the chosen role through awsom's
+// credential cache. DescribeCluster is called on the REST API through sigv4.rs' client;
+// the token is the aws-iam-authenticator format, a presigned STS GetCallerIdentity URL.
+use crate::auth::base64_url;
+use crate::aws_config;
+use crate::config;
+use crate::error::{Result, SsoError};
+use crate::models::{AccountRole, RoleCredentials};
+use crate::sigv4::{self, Client};
+use aws_sigv4::http_request::{SignatureLocation, SigningSettings};
+use serde_json::Value;
+use serde_yaml::{Mapping, Value as Yaml};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const TOKEN_PREFIX: &str = "k8s-aws-v1.";
+/// How long the presigned URL in a token is accepted; EKS allows up to 15 minutes
+const TOKEN_URL_EXPIRY: Duration = Duration::from_secs(60);
+/// Header binding a token to its cluster
+const CLUSTER_ID_HEADER: &str = "x-k8s-aws-id";
+pub const EXEC_API_VERSION: &str = "client.authentication.k8s.io/v1beta1";
+
+/// What the kubeconfig needs from DescribeCluster
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub name: String,
+    pub arn: String,
+    pub endpoint: String,
+    /// Base64 PEM of the cluster's CA, as kubeconfig's certificate-authority-data wants it
+    pub certificate_authority: String,
+}
+
+/// Look up a cluster's endpoint and CA with eks:DescribeCluster
+pub async fn describe_cluster(
+    creds: &RoleCredentials,
+    region: &str,
+    name: &str,
+) -> Result<Cluster> {
+    let path = format!("/clusters/{}", urlencoding::encode(name));
+    let value = Client::new(creds, "eks", region, sigv4::endpoint("eks", region))?
+        .get("DescribeCluster", &path)
+        .await?;
+
+    let cluster = &value["cluster"];
+    let field = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    let cluster = Cluster {
+        name: field(&cluster["name"]),
+        arn: field(&cluster["arn"]),
+        endpoint: field(&cluster["endpoint"]),
+        certificate_authority: field(&cluster["certificateAuthority"]["data"]),
+    };
+    if cluster.endpoint.is_empty() {
+        return Err(SsoError::AwsSdk(format!(
+            "Cluster '{}' has no endpoint yet; is it still being created?",
+            name
+        )));
+    }
+    Ok(cluster)
+}
+
+/// A bearer token for the cluster, valid for about 15 minutes
+pub fn token(creds: &RoleCredentials, region: &str, cluster: &str) -> Result<String> {
+    let url = format!(
+        "{}/?Action=GetCallerIdentity&Version=2011-06-15",
+        sigv4::endpoint("sts", region)
+    );
+    let mut settings = SigningSettings::default();
+    settings.signature_location = SignatureLocation::QueryParams;
+    settings.expires_in = Some(TOKEN_URL_EXPIRY);
+    let request = sigv4::Request {
+        headers: &[(CLUSTER_ID_HEADER, cluster)],
+        ..sigv4::Request::get(&url)
+    };
+    let (_headers, params) = sigv4::sign_request(creds, region, "sts", &request, settings)?;
+
+    let presigned = params.iter().fold(url, |url, (name, value)| {
+        format!("{}&{}={}", url, name, urlencoding::encode(value))
+    });
+    Ok(format!(
+        "{}{}",
+        TOKEN_PREFIX,
+        base64_url(presigned.as_bytes())
+    ))
+}
+
+/// Arguments of the `awsom eks get-token` the kubeconfig user runs
+pub fn get_token_args(
+    cluster: &str,
+    region: &str,
+    session: &str,
+    role: &AccountRole,
+) -> Vec<String> {
+    [
+        "eks",
+        "get-token",
+        "--cluster",
+        cluster,
+        "--region",
+        region,
+        "--session-name",
+        session,
+        "--account-id",
+        &role.account_id,
+        "--role-name",
+        &role.role_name,
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect()
+}
+
+/// The kubeconfig kubectl uses: the first file in $KUBECONFIG, else ~/.kube/config
+pub fn kubeconfig_path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os("KUBECONFIG")
+        .and_then(|paths| std::env::split_paths(&paths).find(|p| !p.as_os_str().is_empty()))
+    {
+        return Ok(path);
+    }
+    dirs::home_dir()
+        .map(|home| home.join(".kube").join("config"))
+        .ok_or_else(|| SsoError::ConfigError("Could not find home directory".to_string()))
+}
+
+/// Add or replace the cluster, and a context and user named `context`, and make the
+/// context current
+///
+/// The user's exec plugin runs `awsom` with `exec_args`. The file is parsed and written
+/// back whole: entries and key order are kept, but comments and formatting are not. Returns
+/// whether the old file had comments, so callers can say they were dropped.
+pub fn update_kubeconfig(
+    path: &Path,
+    cluster: &Cluster,
+    context: &str,
+    exec_args: &[String],
+) -> Result<bool> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(SsoError::ConfigError(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    let updated = merge_kubeconfig(existing.as_deref(), cluster, context, exec_args)
+        .map_err(|e| SsoError::ConfigError(format!("{}: {}", path.display(), e)))?;
+    if config::is_read_only() {
+        return Err(config::read_only_error(
+            path,
+            existing.as_deref().unwrap_or_default(),
+            &updated,
+        ));
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    aws_config::write_atomic(path, updated)?;
+    // It holds cluster endpoints; kubectl warns about group/world-readable files
+    #[cfg(unix)]
+    if existing.is_none() {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(existing.as_deref().is_some_and(has_comments))
+}
+
+/// Whether YAML has `#` comment lines; comments after a value on the same line aren't
+/// looked for, as `#` can be part of an unquoted value there
+fn has_comments(yaml: &str) -> bool {
+    yaml.lines().any(|line| line.trim_start().starts_with('#'))
+}
+
+/// The kubeconfig `existing` with the entries of `update_kubeconfig`
+fn merge_kubeconfig(
+    existing: Option<&str>,
+    cluster: &Cluster,
+    context: &str,
+    exec_args: &[String],
+) -> std::result::Result<String, String> {
+    let mut config = match existing.map(serde_yaml::from_str::<Yaml>) {
+        Some(Ok(Yaml::Mapping(config))) => config,
+        Some(Ok(Yaml::Null)) | None => Mapping::new(),
+        Some(Ok(_)) => return Err("not a kubeconfig mapping".to_string()),
+        Some(Err(e)) => return Err(e.to_string()),
+    };
+
+    let entry = |name: &str, key: &str, value: Yaml| {
+        let mut entry = Mapping::new();
+        entry.insert("name".into(), name.into());
+        entry.insert(key.into(), value);
+        Yaml::Mapping(entry)
+    };
+    let mapping = |pairs: Vec<(&str, Yaml)>| {
+        Yaml::Mapping(pairs.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    };
+
+    let cluster_entry = mapping(vec![
+        ("server", cluster.endpoint.as_str().into()),
+        (
+            "certificate-authority-data",
+            cluster.certificate_authority.as_str().into(),
+        ),
+    ]);
+    let context_entry = mapping(vec![
+        ("cluster", cluster.arn.as_str().into()),
+        ("user", context.into()),
+    ]);
+    let user_entry = mapping(vec![(
+        "exec",
+        mapping(vec![
+            ("apiVersion", EXEC_API_VERSION.into()),
+            ("command", "awsom".into()),
+            (
+                "args",
+                Yaml::Sequence(exec_args.iter().map(|a| a.as_str().into()).collect()),
+            ),
+            ("interactiveMode", "Never".into()),
+        ]),
+    )]);
+
+    for (list, name, key, value) in [
+        ("clusters", cluster.arn.as_str(), "cluster", cluster_entry),
+        ("contexts", context, "context", context_entry),
+        ("users", context, "user", user_entry),
+    ] {
+        let list = config
+            .entry(list.into())
+            .or_insert_with(|| Yaml::Sequence(Vec::new()));
+        if list.is_null() {
+            *list = Yaml::Sequence(Vec::new());
+        }
+        let Yaml::Sequence(entries) = list else {
+            return Err(format!("'{}' is not a list", key));
+        };
+        let new = entry(name, key, value);
+        match entries
+            .iter_mut()
+            .find(|e| e.get("name").and_then(Yaml::as_str) == Some(name))
+        {
+            Some(existing) => *existing = new,
+            None => entries.push(new),
+        }
+    }
+
+    config
+        .entry("apiVersion".into())
+        .or_insert_with(|| "v1".into());
+    config
+        .entry("kind".into())
+        .or_insert_with(|| "Config".into());
+    config.insert("current-context".into(), context.into());
+
+    serde_yaml::to_string(&config).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn cluster() -> Cluster {
+        Cluster {
+            name: "prod".to_string(),
+            arn: "arn:aws:eks:eu-west-1:111111111111:cluster/prod".to_string(),
+            endpoint: "https://ABC.gr7.eu-west-1.eks.amazonaws.com".to_string(),
+            certificate_authority: "LS0tLS1CRUdJTg==".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_kubeconfig_replaces_own_entries() {
+        let existing = r#"apiVersion: v1
+kind: Config
+current-context: other
+clusters:
+- name: other
+  cluster:
+    server: https://other
+- name: arn:aws:eks:eu-west-1:111111111111:cluster/prod
+  cluster:
+    server: https://old
+contexts:
+- name: other
+  context:
+    cluster: other
+    user: other
+users:
+- name: other
+  user:
+    token: abc
+"#;
+        let args = vec!["eks".to_string(), "get-token".to_string()];
+        let merged = merge_kubeconfig(Some(existing), &cluster(), "prod-admin", &args).unwrap();
+        let merged: Yaml = serde_yaml::from_str(&merged).unwrap();
+
+        assert_eq!(merged["current-context"], "prod-admin");
+        let clusters = merged["clusters"].as_sequence().unwrap();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(
+            clusters[1]["cluster"]["server"],
+            "https://ABC.gr7.eu-west-1.eks.amazonaws.com"
+        );
+        assert_eq!(merged["contexts"].as_sequence().unwrap().len(), 2);
+        assert_eq!(merged["contexts"][1]["context"]["user"], "prod-admin");
+        assert_eq!(merged["users"][0]["user"]["token"], "abc");
+        assert_eq!(merged["users"][1]["user"]["exec"]["command"], "awsom");
+        assert_eq!(merged["users"][1]["user"]["exec"]["args"][1], "get-token");
+
+        // A new file gets the header fields
+        let fresh = merge_kubeconfig(None, &cluster(), "prod-admin", &args).unwrap();
+        assert!(fresh.contains("kind: Config"));
+
+        assert!(!has_comments(existing));
+        assert!(has_comments("  # managed by hand\nkind: Config\n"));
+    }
+
+    #[test]
+    fn test_token_is_presigned_get_caller_identity() {
+        let creds = RoleCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: "session+token/=".to_string(),
+            expiration: Utc::now(),
+        };
+        let token = token(&creds, "eu-west-1", "prod").unwrap();
+        let encoded = token.strip_prefix(TOKEN_PREFIX).unwrap();
+        assert!(!encoded.contains('='));
+
+        // Decode the unpadded base64url back to the URL
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        let bits: Vec<u8> = encoded
+            .bytes()
+            .map(|c| ALPHABET.iter().position(|&a| a == c).unwrap() as u8)
+            .collect();
+        let url: Vec<u8> = bits
+            .chunks(4)
+            .flat_map(|chunk| {
+                let n = chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u32, |n, (i, &b)| n | u32::from(b) << (18 - 6 * i));
+                (0..chunk.len() - 1).map(move |i| (n >> (16 - 8 * i)) as u8)
+            })
+            .collect();
+        let url = String::from_utf8(url).unwrap();
+
+        assert!(url.starts_with(
+            "https://sts.eu-west-1.amazonaws.com/?Action=GetCallerIdentity&Version=2011-06-15&"
+        ));
+        assert!(url.contains("X-Amz-Expires=60"));
+        assert!(url.contains("X-Amz-SignedHeaders=host%3Bx-k8s-aws-id"));
+        assert!(url.contains("X-Amz-Security-Token=session%2Btoken%2F%3D"));
+        assert!(url.contains("X-Amz-Signature="));
+    }
+}
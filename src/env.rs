@@ -16,10 +16,12 @@ pub fn set_headless_override(headless: bool) {
 /// - --headless CLI flag is set (highest priority)
 /// - SSH_TTY or SSH_CONNECTION environment variables are set (SSH session)
 /// - TERM is set to "dumb" or is empty
-/// - On Linux: DISPLAY environment variable is not set (no X11)
+/// - On Linux: neither DISPLAY (X11) nor WAYLAND_DISPLAY is set, except under WSL, where
+///   the browser is opened on the Windows side
 /// - CI environment is detected
 ///
-/// Note: macOS doesn't use DISPLAY, so we don't check it on Darwin
+/// macOS and Windows always have a desktop to open the browser on, so they only get the
+/// SSH, CI and TERM checks
 ///
 /// Returns true if running in headless mode
 pub fn is_headless_environment() -> bool {
@@ -29,41 +31,82 @@ pub fn is_headless_environment() -> bool {
         return true;
     }
 
+    detect_headless(
+        |name| std::env::var(name).ok(),
+        cfg!(all(unix, not(target_os = "macos"))),
+    )
+}
+
+/// The environment checks of `is_headless_environment`, reading variables through `var`;
+/// `needs_display` is set where a browser needs an X11 or Wayland display
+fn detect_headless(var: impl Fn(&str) -> Option<String>, needs_display: bool) -> bool {
     // Check SSH session (most reliable auto-detection indicator)
-    if std::env::var("SSH_TTY").is_ok() {
+    if var("SSH_TTY").is_some() {
         tracing::debug!("Headless detected: SSH_TTY set");
         return true;
     }
 
-    if std::env::var("SSH_CONNECTION").is_ok() {
+    if var("SSH_CONNECTION").is_some() {
         tracing::debug!("Headless detected: SSH_CONNECTION set");
         return true;
     }
 
     // Check for CI environment
-    if std::env::var("CI").is_ok() {
+    if var("CI").is_some() {
         tracing::debug!("Headless detected: CI environment");
         return true;
     }
 
     // Check TERM
-    if let Ok(term) = std::env::var("TERM") {
+    if let Some(term) = var("TERM") {
         if term == "dumb" || term.is_empty() {
             tracing::debug!("Headless detected: TERM is '{}'", term);
             return true;
         }
     }
 
-    // On Linux (and other non-macOS Unix), check DISPLAY for X11
-    // macOS doesn't use X11/DISPLAY, so skip this check on Darwin
-    #[cfg(not(target_os = "macos"))]
+    if needs_display
+        && var("DISPLAY").is_none()
+        && var("WAYLAND_DISPLAY").is_none()
+        && var("WSL_DISTRO_NAME").is_none()
     {
-        if std::env::var("DISPLAY").is_err() {
-            tracing::debug!("Headless detected: DISPLAY not set (non-macOS)");
-            return true;
-        }
+        tracing::debug!("Headless detected: no DISPLAY or WAYLAND_DISPLAY");
+        return true;
     }
 
     tracing::debug!("Not headless: detected graphical environment");
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headless(vars: &[(&str, &str)], needs_display: bool) -> bool {
+        detect_headless(
+            |name| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            },
+            needs_display,
+        )
+    }
+
+    #[test]
+    fn test_detect_headless() {
+        // Windows and macOS: no display variables needed
+        assert!(!headless(&[], false));
+        assert!(headless(
+            &[("SSH_CONNECTION", "10.0.0.1 22 10.0.0.2 22")],
+            false
+        ));
+
+        // Linux
+        assert!(headless(&[], true));
+        assert!(!headless(&[("DISPLAY", ":0")], true));
+        assert!(!headless(&[("WAYLAND_DISPLAY", "wayland-0")], true));
+        assert!(!headless(&[("WSL_DISTRO_NAME", "Ubuntu")], true));
+        assert!(headless(&[("DISPLAY", ":0"), ("TERM", "dumb")], true));
+    }
+}
@@ -1,6 +1,10 @@
+use aws_sdk_sso::error::SdkError;
+use std::fmt;
 use thiserror::Error;
 
+/// Errors of the library and the CLI; new variants may be added in minor releases
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum SsoError {
     #[error("AWS SDK error: {0}")]
     AwsSdk(String),
@@ -14,8 +18,9 @@ pub enum SsoError {
     #[error("Authorization expired - user took too long to complete device flow")]
     AuthorizationExpired,
 
-    #[error("Token expired or invalid")]
-    TokenExpired,
+    /// The SSO access token is missing, expired or was rejected by AWS
+    #[error("SSO session expired or not logged in")]
+    AuthExpired,
 
     #[error("Invalid SSO configuration: {0}")]
     InvalidConfig(String),
@@ -23,6 +28,14 @@ pub enum SsoError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    /// A profile or session name is already taken by a section awsom won't touch
+    #[error("Profile conflict: {0}")]
+    ProfileConflict(String),
+
+    /// AWS or the federation endpoint couldn't be reached at all
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
     #[error("Cache error: {0}")]
     CacheError(String),
 
@@ -33,16 +46,172 @@ pub enum SsoError {
     Json(#[from] serde_json::Error),
 
     #[error("TOML serialization error: {0}")]
-    Toml(#[from] toml::de::Error),
+    Toml(FormatError),
+
+    #[error("TOML serialization error: {0}")]
+    TomlSerialize(FormatError),
+
+    #[error("YAML serialization error: {0}")]
+    Yaml(FormatError),
 
     #[error("No SSO session found")]
     NoSessionFound,
 
+    /// Nothing to log in to: no [sso-session] and no --start-url/--region
+    #[error("No SSO sessions configured")]
+    NotConfigured,
+
     #[error("Account or role not found")]
     AccountRoleNotFound,
 
-    #[error("Browser launch failed: {0}")]
-    BrowserLaunchFailed(String),
+    #[error("Browser unavailable: {0}")]
+    BrowserUnavailable(String),
+
+    #[error("Clipboard unavailable: {0}")]
+    ClipboardFailed(String),
+
+    /// A write refused by --read-only or `[security] read_only`; `diff` is the change to a
+    /// text file that was kept from happening, empty for other files
+    #[error("Read-only mode: not writing {path}")]
+    ReadOnly { path: String, diff: String },
 }
 
 pub type Result<T> = std::result::Result<T, SsoError>;
+
+/// The parser's message for a TOML or YAML file, kept apart from the parser crate's error
+/// types so a new version of one isn't a breaking change to awsom's API
+#[derive(Debug)]
+pub struct FormatError(String);
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<toml::de::Error> for SsoError {
+    fn from(e: toml::de::Error) -> Self {
+        SsoError::Toml(FormatError(e.to_string()))
+    }
+}
+
+impl From<toml::ser::Error> for SsoError {
+    fn from(e: toml::ser::Error) -> Self {
+        SsoError::TomlSerialize(FormatError(e.to_string()))
+    }
+}
+
+impl From<serde_yaml::Error> for SsoError {
+    fn from(e: serde_yaml::Error) -> Self {
+        SsoError::Yaml(FormatError(e.to_string()))
+    }
+}
+
+/// Process exit codes, documented in the README. 2 is also what clap uses for bad arguments.
+pub mod exit_code {
+    pub const GENERAL: i32 = 1;
+    pub const USAGE: i32 = 2;
+    pub const CONFIG: i32 = 3;
+    pub const NOT_LOGGED_IN: i32 = 4;
+    pub const AUTH_FAILED: i32 = 5;
+    pub const PROFILE_CONFLICT: i32 = 6;
+    pub const NOT_FOUND: i32 = 7;
+    pub const NETWORK: i32 = 8;
+    pub const BROWSER_UNAVAILABLE: i32 = 9;
+    pub const READ_ONLY: i32 = 10;
+}
+
+impl SsoError {
+    /// Exit code for scripts to branch on
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            SsoError::InvalidConfig(_) => exit_code::USAGE,
+            SsoError::ConfigError(_)
+            | SsoError::NotConfigured
+            | SsoError::Toml(_)
+            | SsoError::TomlSerialize(_) => exit_code::CONFIG,
+            SsoError::AuthExpired | SsoError::NoSessionFound => exit_code::NOT_LOGGED_IN,
+            SsoError::AuthenticationFailed(_)
+            | SsoError::AuthorizationPending
+            | SsoError::AuthorizationExpired => exit_code::AUTH_FAILED,
+            SsoError::ProfileConflict(_) => exit_code::PROFILE_CONFLICT,
+            SsoError::AccountRoleNotFound => exit_code::NOT_FOUND,
+            SsoError::NetworkError(_) => exit_code::NETWORK,
+            SsoError::BrowserUnavailable(_) => exit_code::BROWSER_UNAVAILABLE,
+            SsoError::ReadOnly { .. } => exit_code::READ_ONLY,
+            SsoError::AwsSdk(_)
+            | SsoError::CacheError(_)
+            | SsoError::Io(_)
+            | SsoError::Json(_)
+            | SsoError::Yaml(_)
+            | SsoError::ClipboardFailed(_) => exit_code::GENERAL,
+        }
+    }
+
+    /// What the user can do about it, printed under the error unless --quiet is given
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            SsoError::AuthExpired | SsoError::NoSessionFound => {
+                Some("Run 'awsom session login' to sign in again")
+            }
+            SsoError::AuthorizationExpired | SsoError::AuthorizationPending => {
+                Some("Run 'awsom session login' and approve the request in the browser sooner")
+            }
+            SsoError::NotConfigured => {
+                Some("Run 'awsom init' to set one up, or pass --start-url and --region")
+            }
+            SsoError::ProfileConflict(_) => {
+                Some("Use another name, or 'awsom import <name>' to let awsom manage it")
+            }
+            SsoError::NetworkError(_) => Some(
+                "Check your network, VPN and proxy ([network] or HTTPS_PROXY); 'awsom doctor' runs the checks",
+            ),
+            SsoError::BrowserUnavailable(_) => {
+                Some("Use --headless to print the URL instead of opening a browser")
+            }
+            SsoError::Toml(_) => Some("Run 'awsom doctor' to check the config files"),
+            SsoError::ReadOnly { .. } => Some(
+                "Run without --read-only, and without [security] read_only in config.toml, to \
+                 make changes",
+            ),
+            SsoError::AccountRoleNotFound => {
+                Some("'awsom profile list' shows the accounts and roles you can use")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A request that never got a response: DNS, connection refused, TLS or timeout
+pub fn is_network_error<E, R>(err: &SdkError<E, R>) -> bool {
+    matches!(
+        err,
+        SdkError::DispatchFailure(_) | SdkError::TimeoutError(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_and_hints() {
+        assert_eq!(SsoError::AuthExpired.exit_code(), 4);
+        assert_eq!(SsoError::ProfileConflict("dev".to_string()).exit_code(), 6);
+        assert_eq!(SsoError::NetworkError("dns".to_string()).exit_code(), 8);
+        assert_eq!(SsoError::AwsSdk("boom".to_string()).exit_code(), 1);
+        let read_only = SsoError::ReadOnly {
+            path: "~/.aws/config".to_string(),
+            diff: String::new(),
+        };
+        assert_eq!(read_only.exit_code(), 10);
+
+        assert!(SsoError::AuthExpired
+            .hint()
+            .unwrap()
+            .contains("session login"));
+        assert!(SsoError::AwsSdk("boom".to_string()).hint().is_none());
+    }
+}
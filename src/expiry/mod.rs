@@ -1,9 +1,60 @@
 // Expiry tracking and notifications
-use chrono::{DateTime, Utc};
+//
+// Tokens and credentials count as expired `[security] expiry_margin_seconds` before their
+// expiry time, so one that looks valid doesn't run out halfway through a command or an
+// SDK call, or when this machine's clock is a little behind AWS's.
+use chrono::{DateTime, Duration, Utc};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::OnceLock;
+
+/// Upper bound on the margin, so 15-minute role sessions stay usable for most of their life
+pub const MAX_MARGIN_SECONDS: u64 = 300;
+
+/// Margin from `set_margin_seconds`, or -1 to use the config's
+static MARGIN_OVERRIDE: AtomicI64 = AtomicI64::new(-1);
+
+/// `[security] expiry_margin_seconds`, read by the first `margin` call without an override
+static CONFIG_MARGIN: OnceLock<i64> = OnceLock::new();
+
+/// Treat tokens and credentials as expired `seconds` early, up to `MAX_MARGIN_SECONDS`,
+/// whatever config.toml says
+pub fn set_margin_seconds(seconds: u64) {
+    let seconds = seconds.min(MAX_MARGIN_SECONDS) as i64;
+    MARGIN_OVERRIDE.store(seconds, Ordering::Relaxed);
+}
+
+/// The early-expiry margin; config.toml is read once per process, like `config::is_read_only`
+pub fn margin() -> Duration {
+    let seconds = match MARGIN_OVERRIDE.load(Ordering::Relaxed) {
+        -1 => *CONFIG_MARGIN.get_or_init(|| {
+            let config = crate::config::Config::load().unwrap_or_default();
+            config
+                .security
+                .expiry_margin_seconds
+                .min(MAX_MARGIN_SECONDS) as i64
+        }),
+        seconds => seconds,
+    };
+    Duration::seconds(seconds)
+}
+
+/// Whether something expiring at `expires_at` should no longer be used
+pub fn is_expired(expires_at: &DateTime<Utc>) -> bool {
+    is_within(expires_at, margin())
+}
+
+fn is_within(expires_at: &DateTime<Utc>, margin: Duration) -> bool {
+    Utc::now() + margin >= *expires_at
+}
+
+/// How much longer it can be used, negative once `is_expired`
+pub fn usable_for(expires_at: &DateTime<Utc>) -> Duration {
+    *expires_at - Utc::now() - margin()
+}
 
 pub fn format_time_remaining(expires_at: &DateTime<Utc>) -> String {
     let now = Utc::now();
-    if *expires_at <= now {
+    if is_expired(expires_at) {
         return "EXPIRED".to_string();
     }
 
@@ -22,7 +73,73 @@ pub fn format_time_remaining(expires_at: &DateTime<Utc>) -> String {
 }
 
 pub fn is_expiring_soon(expires_at: &DateTime<Utc>, threshold_minutes: i64) -> bool {
-    let now = Utc::now();
-    let duration = (*expires_at - now).num_minutes();
-    duration > 0 && duration < threshold_minutes
+    !is_expired(expires_at) && *expires_at - Utc::now() < Duration::minutes(threshold_minutes)
+}
+
+/// How close a token or credentials are to expiring, for coloring them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryState {
+    Valid,
+    /// Less than the warning threshold left
+    ExpiringSoon,
+    Expired,
+}
+
+impl ExpiryState {
+    pub fn of(expires_at: &DateTime<Utc>, threshold_minutes: i64) -> Self {
+        if is_expired(expires_at) {
+            ExpiryState::Expired
+        } else if is_expiring_soon(expires_at, threshold_minutes) {
+            ExpiryState::ExpiringSoon
+        } else {
+            ExpiryState::Valid
+        }
+    }
+}
+
+/// Short form for tight spaces like shell prompts: "1h5m", "23m", "<1m" or "expired"
+pub fn format_compact_remaining(expires_at: &DateTime<Utc>) -> String {
+    let remaining = *expires_at - Utc::now();
+    let minutes = remaining.num_minutes();
+    if is_expired(expires_at) {
+        "expired".to_string()
+    } else if minutes == 0 {
+        "<1m".to_string()
+    } else if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h{}m", minutes / 60, minutes % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_state() {
+        let in_minutes = |minutes| Utc::now() + Duration::minutes(minutes);
+        assert_eq!(ExpiryState::of(&in_minutes(45), 30), ExpiryState::Valid);
+        assert_eq!(
+            ExpiryState::of(&in_minutes(20), 30),
+            ExpiryState::ExpiringSoon
+        );
+        assert_eq!(ExpiryState::of(&in_minutes(-1), 30), ExpiryState::Expired);
+
+        // The last usable minute still counts as expiring, not as valid
+        let seconds_left = Utc::now() + margin() + Duration::seconds(30);
+        assert_eq!(
+            ExpiryState::of(&seconds_left, 30),
+            ExpiryState::ExpiringSoon
+        );
+    }
+
+    #[test]
+    fn test_margin_expires_early() {
+        let in_seconds = |seconds| Utc::now() + Duration::seconds(seconds);
+        assert!(is_within(&in_seconds(30), Duration::seconds(60)));
+        assert!(!is_within(&in_seconds(90), Duration::seconds(60)));
+        assert!(!is_within(&in_seconds(30), Duration::zero()));
+        assert!(is_within(&in_seconds(-1), Duration::zero()));
+    }
 }
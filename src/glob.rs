@@ -0,0 +1,45 @@
+// Shell-style wildcard matching for account and role filters
+
+/// Match text against a shell-style pattern where `*` matches any run of
+/// characters and `?` matches exactly one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last `*` swallow one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("prod-*", "prod-payments"));
+        assert!(glob_match("*-prod", "payments-prod"));
+        assert!(glob_match("team-?-dev", "team-a-dev"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("prod-*", "staging-prod"));
+        assert!(!glob_match("team-?-dev", "team-ab-dev"));
+    }
+}
@@ -0,0 +1,260 @@
+// Rolling history of ~/.aws/config and ~/.aws/credentials
+//
+// Before awsom rewrites either file, both are copied into <cache dir>/awsom/history/<id>/,
+// where <id> is the UTC time of the write. `awsom rollback` copies a snapshot back. Only
+// the newest `[history] keep` snapshots are kept; the first-run `*-before-awsom.bak`
+// backups are separate and never pruned.
+use crate::audit;
+use crate::aws_config;
+use crate::config::{self, Config};
+use crate::error::{Result, SsoError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Snapshot directory names: sortable and safe in file names
+const ID_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+const META_FILE: &str = "snapshot.json";
+const CONFIG_FILE: &str = "config";
+const CREDENTIALS_FILE: &str = "credentials";
+
+/// The state of both files just before one awsom write
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    /// File the write was about to change ("config" or "credentials")
+    pub changed: String,
+    /// awsom command that made the write
+    pub command: String,
+}
+
+pub fn history_dir() -> PathBuf {
+    config::cache_dir().join("history")
+}
+
+/// Snapshot the AWS files before `changed` is rewritten
+///
+/// Failures are logged rather than returned, so a full disk in the cache dir doesn't stop
+/// awsom from writing credentials.
+pub fn record(changed: &Path) {
+    let keep = Config::load().map(|c| c.history.keep).unwrap_or(0);
+    if keep == 0 {
+        return;
+    }
+
+    if let Err(e) = snapshot(changed).and_then(|_| prune(keep)) {
+        tracing::warn!("Failed to save config history: {}", e);
+    }
+}
+
+fn snapshot(changed: &Path) -> Result<Snapshot> {
+    let config_path = aws_config::config_file_path()?;
+    let credentials_path = aws_config::credentials_file_path()?;
+
+    let timestamp = Utc::now();
+    let base = timestamp.format(ID_FORMAT).to_string();
+    // Two writes in the same millisecond (credentials, then config) get -1, -2, ...
+    let mut id = base.clone();
+    let mut n = 0;
+    while history_dir().join(&id).exists() {
+        n += 1;
+        id = format!("{}-{}", base, n);
+    }
+    let dir = history_dir().join(&id);
+    create_private_dir(&dir)?;
+
+    for (source, name) in [
+        (&config_path, CONFIG_FILE),
+        (&credentials_path, CREDENTIALS_FILE),
+    ] {
+        if source.exists() {
+            copy_private(source, &dir.join(name))?;
+        }
+    }
+
+    let snapshot = Snapshot {
+        id,
+        timestamp,
+        changed: if changed == credentials_path {
+            CREDENTIALS_FILE
+        } else {
+            CONFIG_FILE
+        }
+        .to_string(),
+        command: audit::initiator(),
+    };
+    fs::write(
+        dir.join(META_FILE),
+        serde_json::to_string_pretty(&snapshot)?,
+    )?;
+    Ok(snapshot)
+}
+
+/// Delete all but the newest `keep` snapshots
+fn prune(keep: usize) -> Result<()> {
+    for snapshot in list()?.into_iter().skip(keep) {
+        fs::remove_dir_all(history_dir().join(&snapshot.id))?;
+    }
+    Ok(())
+}
+
+/// Saved snapshots, newest first
+pub fn list() -> Result<Vec<Snapshot>> {
+    let dir = history_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let meta = path.join(META_FILE);
+        if !meta.exists() {
+            continue;
+        }
+        match serde_json::from_str::<Snapshot>(&fs::read_to_string(&meta)?) {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(e) => tracing::warn!("Skipping unreadable snapshot {}: {}", path.display(), e),
+        }
+    }
+
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(snapshots)
+}
+
+/// The snapshot whose id starts with `prefix`, or the newest one if `prefix` is None
+pub fn find(prefix: Option<&str>) -> Result<Snapshot> {
+    select(list()?, prefix)
+}
+
+/// `find` over `snapshots` (newest first); an RFC 3339 time picks the last snapshot taken
+/// at or before it
+fn select(snapshots: Vec<Snapshot>, prefix: Option<&str>) -> Result<Snapshot> {
+    let Some(prefix) = prefix else {
+        return snapshots.into_iter().next().ok_or_else(|| {
+            SsoError::ConfigError("No config history saved yet. Nothing to roll back.".to_string())
+        });
+    };
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(prefix) {
+        let time = time.with_timezone(&Utc);
+        return snapshots
+            .into_iter()
+            .find(|s| s.timestamp <= time)
+            .ok_or_else(|| SsoError::ConfigError(format!("No snapshot at or before {}", prefix)));
+    }
+
+    let mut matches = snapshots.into_iter().filter(|s| s.id.starts_with(prefix));
+    match (matches.next(), matches.next()) {
+        (Some(snapshot), None) => Ok(snapshot),
+        (None, _) => Err(SsoError::ConfigError(format!(
+            "No snapshot '{}' (see awsom rollback --list)",
+            prefix
+        ))),
+        (Some(_), Some(_)) => Err(SsoError::ConfigError(format!(
+            "'{}' matches several snapshots; give more of the timestamp",
+            prefix
+        ))),
+    }
+}
+
+/// Copy a snapshot back over ~/.aws/config and ~/.aws/credentials, first snapshotting the
+/// current state so the rollback can itself be undone. Returns the files restored.
+pub fn restore(snapshot: &Snapshot) -> Result<Vec<PathBuf>> {
+    let dir = history_dir().join(&snapshot.id);
+    let targets = [
+        (aws_config::config_file_path()?, CONFIG_FILE),
+        (aws_config::credentials_file_path()?, CREDENTIALS_FILE),
+    ];
+    config::ensure_writable(&targets[0].0)?;
+
+    snapshot_before_rollback()?;
+
+    let mut restored = Vec::new();
+    for (target, name) in targets {
+        let source = dir.join(name);
+        if !source.exists() {
+            continue;
+        }
+
+        let content = fs::read(&source)?;
+        let _lock = aws_config::lock_file(&target)?;
+        aws_config::write_atomic(&target, content).map_err(|e| {
+            SsoError::ConfigError(format!("Failed to restore {}: {}", target.display(), e))
+        })?;
+        restored.push(target);
+    }
+    Ok(restored)
+}
+
+fn snapshot_before_rollback() -> Result<()> {
+    let keep = Config::load()?.history.keep;
+    if keep > 0 {
+        snapshot(&aws_config::config_file_path()?)?;
+        prune(keep)?;
+    }
+    Ok(())
+}
+
+/// Snapshots hold credentials, so keep them readable by the owner only
+fn create_private_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+fn copy_private(source: &Path, dest: &Path) -> Result<()> {
+    fs::copy(source, dest)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dest, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn taken_at(time: &str) -> Snapshot {
+        let timestamp = DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Utc);
+        Snapshot {
+            id: timestamp.format(ID_FORMAT).to_string(),
+            timestamp,
+            changed: CONFIG_FILE.to_string(),
+            command: "profile start".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let snapshots = vec![
+            taken_at("2026-01-31T10:00:00.500Z"),
+            taken_at("2026-01-31T09:30:00Z"),
+            taken_at("2026-01-30T12:00:00Z"),
+        ];
+        assert_eq!(snapshots[0].id, "20260131T100000.500Z");
+
+        let id = |prefix: Option<&str>| select(snapshots.clone(), prefix).map(|s| s.id);
+        assert_eq!(id(None).unwrap(), "20260131T100000.500Z");
+        assert_eq!(id(Some("20260130")).unwrap(), "20260130T120000.000Z");
+        assert!(id(Some("20260131")).is_err());
+        assert!(id(Some("2025")).is_err());
+        assert_eq!(
+            id(Some("2026-01-31T09:45:00Z")).unwrap(),
+            "20260131T093000.000Z"
+        );
+        assert!(id(Some("2026-01-01T00:00:00Z")).is_err());
+        assert!(select(Vec::new(), None).is_err());
+    }
+}
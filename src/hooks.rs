@@ -0,0 +1,207 @@
+// User-defined commands run on login, credential writes, expiry warnings and console
+// sign-ins
+//
+// Commands come from `[hooks]` in config.toml and run through the shell with the event's
+// details in AWSOM_* variables. They run in the background: a slow or failing hook is
+// logged but never holds up or fails the command that triggered it.
+use crate::config::{Config, HooksConfig};
+use crate::expiry::is_expiring_soon;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::process::{Child, Command, Stdio};
+
+/// Set for hook commands to the event's name; awsom runs no hooks while it is set, so a
+/// hook that calls awsom can't trigger itself
+const HOOK_ENV: &str = "AWSOM_HOOK";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Login,
+    CredentialsWritten,
+    TokenExpiring,
+    ConsoleOpen,
+}
+
+impl HookEvent {
+    /// Value of AWSOM_HOOK
+    pub fn label(self) -> &'static str {
+        match self {
+            HookEvent::Login => "login",
+            HookEvent::CredentialsWritten => "credentials_written",
+            HookEvent::TokenExpiring => "token_expiring",
+            HookEvent::ConsoleOpen => "console_open",
+        }
+    }
+
+    fn command(self, hooks: &HooksConfig) -> Option<&str> {
+        match self {
+            HookEvent::Login => hooks.on_login.as_deref(),
+            HookEvent::CredentialsWritten => hooks.on_credentials_written.as_deref(),
+            HookEvent::TokenExpiring => hooks.on_token_expiring.as_deref(),
+            HookEvent::ConsoleOpen => hooks.on_console_open.as_deref(),
+        }
+    }
+}
+
+/// What the event was about; unset fields are left out of the environment
+#[derive(Debug, Clone, Default)]
+pub struct HookContext<'a> {
+    pub session: Option<&'a str>,
+    pub start_url: Option<&'a str>,
+    pub account_id: Option<&'a str>,
+    pub role_name: Option<&'a str>,
+    pub profile: Option<&'a str>,
+    pub region: Option<&'a str>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl HookContext<'_> {
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        let expires_at = self.expires_at.map(|at| at.to_rfc3339());
+        [
+            ("AWSOM_SESSION", self.session.map(str::to_string)),
+            ("AWSOM_START_URL", self.start_url.map(str::to_string)),
+            ("AWSOM_ACCOUNT_ID", self.account_id.map(str::to_string)),
+            ("AWSOM_ROLE_NAME", self.role_name.map(str::to_string)),
+            ("AWSOM_PROFILE", self.profile.map(str::to_string)),
+            ("AWSOM_REGION", self.region.map(str::to_string)),
+            ("AWSOM_EXPIRES_AT", expires_at),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect()
+    }
+}
+
+/// Run the command configured for `event`, if any
+pub fn run(event: HookEvent, context: &HookContext) {
+    if std::env::var_os(HOOK_ENV).is_some() {
+        tracing::debug!("Not running the {} hook from inside a hook", event.label());
+        return;
+    }
+    let Ok(config) = Config::load() else {
+        return;
+    };
+    let Some(command) = event.command(&config.hooks) else {
+        return;
+    };
+
+    match spawn(command, event, context) {
+        Ok(child) => {
+            // Reap the child off the caller's thread and log how it went
+            std::thread::spawn(move || match child.wait_with_output() {
+                Ok(output) if output.status.success() => {
+                    tracing::debug!("{} hook finished", event.label());
+                }
+                Ok(output) => tracing::warn!(
+                    "{} hook failed ({}): {}",
+                    event.label(),
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                Err(e) => tracing::warn!("Failed to wait for the {} hook: {}", event.label(), e),
+            });
+        }
+        Err(e) => tracing::warn!("Failed to run the {} hook: {}", event.label(), e),
+    }
+}
+
+/// Runs `on_token_expiring` once per expiring token or set of credentials, whether or not
+/// desktop notifications are on
+///
+/// Like `notify::ExpiryNotifier`, callers pass everything they know about on each check,
+/// and refreshed credentials can fire the hook again.
+pub struct ExpiringHook {
+    threshold_minutes: i64,
+    fired: HashSet<(String, DateTime<Utc>)>,
+}
+
+impl ExpiringHook {
+    /// `threshold_minutes` is `[hooks] token_expiring_minutes`; 0 never runs the hook
+    pub fn new(threshold_minutes: u32) -> Self {
+        Self {
+            threshold_minutes: i64::from(threshold_minutes),
+            fired: HashSet::new(),
+        }
+    }
+
+    /// Run the hook if the item `label` names expires within the threshold, the first time
+    /// it's seen to
+    pub fn check(&mut self, label: &str, context: &HookContext) {
+        if context.expires_at.is_some_and(|at| self.is_due(label, &at)) {
+            run(HookEvent::TokenExpiring, context);
+        }
+    }
+
+    fn is_due(&mut self, label: &str, expires_at: &DateTime<Utc>) -> bool {
+        self.threshold_minutes > 0
+            && is_expiring_soon(expires_at, self.threshold_minutes)
+            && self.fired.insert((label.to_string(), *expires_at))
+    }
+}
+
+/// Start `command` through the platform shell with the event in its environment
+fn spawn(command: &str, event: HookEvent, context: &HookContext) -> std::io::Result<Child> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .env(HOOK_ENV, event.label())
+        .envs(context.variables())
+        // Output would end up in the middle of the TUI
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_expiring_hook_is_due_once() {
+        let mut hook = ExpiringHook::new(10);
+        let soon = Utc::now() + Duration::minutes(5);
+        let later = Utc::now() + Duration::minutes(30);
+        assert!(!hook.is_due("dev", &later));
+        assert!(hook.is_due("dev", &soon));
+        assert!(!hook.is_due("dev", &soon));
+        assert!(hook.is_due("prod", &soon));
+        // Refreshed credentials fire it again
+        assert!(hook.is_due("dev", &(soon + Duration::seconds(1))));
+
+        assert!(!ExpiringHook::new(0).is_due("dev", &soon));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_gets_event_in_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let command = format!(
+            "echo \"$AWSOM_HOOK $AWSOM_PROFILE $AWSOM_ACCOUNT_ID ${{AWSOM_SESSION:-none}}\" > {}",
+            out.display()
+        );
+        let context = HookContext {
+            account_id: Some("111111111111"),
+            profile: Some("dev"),
+            ..HookContext::default()
+        };
+
+        let child = spawn(&command, HookEvent::CredentialsWritten, &context).unwrap();
+        assert!(child.wait_with_output().unwrap().status.success());
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "credentials_written dev 111111111111 none\n"
+        );
+    }
+}
@@ -0,0 +1,399 @@
+// Lossless INI document model for ~/.aws/config and ~/.aws/credentials
+//
+// Every line is kept verbatim - comments, blank lines, unknown keys and indented
+// sub-sections like `s3 =` blocks - so parsing and printing a file reproduces it byte
+// for byte, and edits only touch the lines they change.
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Section(String),
+    Entry {
+        key: String,
+        value: String,
+    },
+    Comment,
+    Blank,
+    /// Indented continuation/sub-section lines and anything unparseable
+    Other,
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    raw: String,
+    kind: Kind,
+}
+
+impl Line {
+    fn parse(raw: &str) -> Self {
+        let trimmed = raw.trim();
+        let kind = if trimmed.is_empty() {
+            Kind::Blank
+        } else if trimmed.starts_with('#') || trimmed.starts_with(';') {
+            Kind::Comment
+        } else if raw.starts_with([' ', '\t']) {
+            // The AWS CLI treats indented lines as belonging to the previous key
+            Kind::Other
+        } else if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            Kind::Section(trimmed[1..trimmed.len() - 1].trim().to_string())
+        } else if let Some((key, value)) = trimmed.split_once('=') {
+            Kind::Entry {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+            }
+        } else {
+            Kind::Other
+        };
+
+        Self {
+            raw: raw.to_string(),
+            kind,
+        }
+    }
+}
+
+/// A parsed INI file
+#[derive(Debug, Clone)]
+pub struct IniDocument {
+    lines: Vec<Line>,
+    trailing_newline: bool,
+}
+
+impl Default for IniDocument {
+    fn default() -> Self {
+        Self {
+            lines: Vec::new(),
+            trailing_newline: true,
+        }
+    }
+}
+
+/// A section of an [`IniDocument`], addressed by the line index of its header
+#[derive(Debug, Clone, Copy)]
+pub struct Section<'a> {
+    doc: &'a IniDocument,
+    header: usize,
+    /// One past the last entry (or header comment)
+    content_end: usize,
+    /// `content_end` plus a trailing blank separator line, if any
+    end: usize,
+}
+
+impl<'a> Section<'a> {
+    /// Section name without brackets, e.g. `profile dev`
+    pub fn name(&self) -> &'a str {
+        match &self.doc.lines[self.header].kind {
+            Kind::Section(name) => name,
+            _ => unreachable!("section header is always a Section line"),
+        }
+    }
+
+    /// Line index of the `[name]` header
+    pub fn line(&self) -> usize {
+        self.header
+    }
+
+    /// Value of `key`; the first occurrence wins, like the AWS CLI
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.entries().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.body().iter().filter_map(|line| match &line.kind {
+            Kind::Entry { key, value } => Some((key.as_str(), value.as_str())),
+            _ => None,
+        })
+    }
+
+    /// Comment lines inside the section body, trimmed
+    pub fn comments(&self) -> impl Iterator<Item = &'a str> {
+        self.body()
+            .iter()
+            .filter(|line| line.kind == Kind::Comment)
+            .map(|line| line.raw.trim())
+    }
+
+    /// The section's lines as written, including its header and leading comments
+    pub fn raw_lines(&self) -> impl Iterator<Item = &'a str> {
+        self.doc.lines[self.doc.section_start(self.header)..self.end]
+            .iter()
+            .map(|line| line.raw.as_str())
+    }
+
+    fn body(&self) -> &'a [Line] {
+        &self.doc.lines[self.header + 1..self.end]
+    }
+}
+
+impl IniDocument {
+    pub fn parse(content: &str) -> Self {
+        let trailing_newline = content.is_empty() || content.ends_with('\n');
+        let content = content.strip_suffix('\n').unwrap_or(content);
+        let lines = if content.is_empty() && trailing_newline {
+            Vec::new()
+        } else {
+            content.split('\n').map(Line::parse).collect()
+        };
+
+        Self {
+            lines,
+            trailing_newline,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Index of the first line whose trimmed text satisfies `pred`
+    pub fn position(&self, pred: impl Fn(&str) -> bool) -> Option<usize> {
+        self.lines.iter().position(|line| pred(line.raw.trim()))
+    }
+
+    /// Trimmed text of line `index`
+    pub fn line(&self, index: usize) -> Option<&str> {
+        self.lines.get(index).map(|line| line.raw.trim())
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = Section<'_>> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| matches!(line.kind, Kind::Section(_)))
+            .map(|(header, _)| self.section_at(header))
+    }
+
+    /// First section called `name`
+    pub fn section(&self, name: &str) -> Option<Section<'_>> {
+        self.sections().find(|s| s.name() == name)
+    }
+
+    /// The section whose header is on line `header`
+    ///
+    /// A section runs to its last entry plus one blank separator line. Comment blocks
+    /// detached from it by a blank line (like the awsom markers) belong to no section.
+    pub fn section_at(&self, header: usize) -> Section<'_> {
+        let limit = self.lines[header + 1..]
+            .iter()
+            .position(|line| matches!(line.kind, Kind::Section(_)))
+            .map(|offset| self.section_start(header + 1 + offset))
+            .unwrap_or(self.lines.len());
+        let body = &self.lines[header + 1..limit];
+
+        let content_end = match body
+            .iter()
+            .rposition(|line| matches!(line.kind, Kind::Entry { .. } | Kind::Other))
+        {
+            Some(offset) => header + 2 + offset,
+            // No entries: just the comments right below the header
+            None => {
+                header
+                    + 1
+                    + body
+                        .iter()
+                        .take_while(|line| line.kind == Kind::Comment)
+                        .count()
+            }
+        };
+        let end = match self.lines.get(content_end) {
+            Some(line) if content_end < limit && line.kind == Kind::Blank => content_end + 1,
+            _ => content_end,
+        };
+
+        Section {
+            doc: self,
+            header,
+            content_end,
+            end,
+        }
+    }
+
+    /// Where a section begins once comment lines directly above its header are included
+    pub fn section_start(&self, header: usize) -> usize {
+        let mut start = header;
+        while start > 0 && self.lines[start - 1].kind == Kind::Comment {
+            start -= 1;
+        }
+        start
+    }
+
+    /// Set `key` in the section on line `header`, updating the first existing entry in
+    /// place or adding it after the section's last entry
+    pub fn set(&mut self, header: usize, key: &str, value: &str) {
+        let section = self.section_at(header);
+        let existing = (header + 1..section.content_end)
+            .find(|&i| matches!(&self.lines[i].kind, Kind::Entry { key: k, .. } if k == key));
+
+        let line = Line::parse(&format!("{} = {}", key, value));
+        match existing {
+            Some(index) => self.lines[index] = line,
+            // After the last entry and its indented lines, or below the header comments
+            None => self.lines.insert(section.content_end, line),
+        }
+    }
+
+    /// Remove every `key` entry, with its indented lines, from the section on line `header`
+    pub fn remove(&mut self, header: usize, key: &str) {
+        let mut end = self.section_at(header).content_end;
+        let mut index = header + 1;
+        while index < end {
+            if matches!(&self.lines[index].kind, Kind::Entry { key: k, .. } if k == key) {
+                self.lines.remove(index);
+                end -= 1;
+                while index < end && self.lines[index].kind == Kind::Other {
+                    self.lines.remove(index);
+                    end -= 1;
+                }
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Remove every comment line in the section on line `header` that satisfies `pred`
+    pub fn remove_comments(&mut self, header: usize, pred: impl Fn(&str) -> bool) {
+        let mut end = self.section_at(header).end;
+        let mut index = header + 1;
+        while index < end {
+            if self.lines[index].kind == Kind::Comment && pred(self.lines[index].raw.trim()) {
+                self.lines.remove(index);
+                end -= 1;
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Insert comment lines directly below the header on line `header`
+    pub fn insert_comments(&mut self, header: usize, comments: &[String]) {
+        for (offset, comment) in comments.iter().enumerate() {
+            self.lines.insert(header + 1 + offset, Line::parse(comment));
+        }
+    }
+
+    /// Insert a single raw line at `at`
+    pub fn insert_line(&mut self, at: usize, raw: &str) {
+        self.lines.insert(at, Line::parse(raw));
+    }
+
+    /// Insert a new `[name]` section so that it starts at line `at` (a section start or
+    /// the end of the file). Returns the line index of its header.
+    pub fn insert_section(&mut self, at: usize, name: &str) -> usize {
+        self.insert_lines(at, vec![format!("[{}]", name)])
+    }
+
+    /// Insert raw lines as a block starting at `at`, separated from their neighbours by a
+    /// blank line. Returns the index of the first inserted line.
+    pub fn insert_lines(&mut self, at: usize, lines: Vec<String>) -> usize {
+        let mut block: Vec<Line> = lines.iter().map(|raw| Line::parse(raw)).collect();
+        while block.last().is_some_and(|line| line.kind == Kind::Blank) {
+            block.pop();
+        }
+
+        let mut first = at;
+        if at > 0 && self.lines[at - 1].kind != Kind::Blank {
+            block.insert(0, Line::parse(""));
+            first += 1;
+        }
+        if at < self.lines.len() {
+            block.push(Line::parse(""));
+        }
+
+        self.lines.splice(at..at, block);
+        first
+    }
+
+    /// Remove the section on line `header` with its leading comments, returning its lines
+    pub fn remove_section(&mut self, header: usize) -> Vec<String> {
+        let section = self.section_at(header);
+        let (start, end) = (self.section_start(header), section.end);
+        let removed = self.lines.drain(start..end).map(|line| line.raw).collect();
+
+        // Don't leave a run of blank lines behind at the end of the file
+        if start == self.lines.len() {
+            while self
+                .lines
+                .last()
+                .is_some_and(|line| line.kind == Kind::Blank)
+            {
+                self.lines.pop();
+            }
+        }
+
+        removed
+    }
+
+    pub fn rename_section(&mut self, header: usize, name: &str) {
+        self.lines[header] = Line::parse(&format!("[{}]", name));
+    }
+}
+
+impl fmt::Display for IniDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, line) in self.lines.iter().enumerate() {
+            f.write_str(&line.raw)?;
+            if index + 1 < self.lines.len() || self.trailing_newline {
+                f.write_str("\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "# my aws config\n\n[default]\nregion = us-east-1\n\n; staging\n[profile staging]\nregion=eu-west-1  \ns3 =\n  max_concurrent_requests = 20\n\n[sso-session corp]\nsso_start_url = https://corp.awsapps.com/start\n";
+
+    #[test]
+    fn test_round_trip_is_lossless() {
+        assert_eq!(IniDocument::parse(SAMPLE).to_string(), SAMPLE);
+        assert_eq!(IniDocument::parse("[a]\nk = v").to_string(), "[a]\nk = v");
+        assert_eq!(IniDocument::parse("").to_string(), "");
+    }
+
+    #[test]
+    fn test_sections_and_entries() {
+        let doc = IniDocument::parse(SAMPLE);
+        let names: Vec<_> = doc.sections().map(|s| s.name()).collect();
+        assert_eq!(names, ["default", "profile staging", "sso-session corp"]);
+
+        let staging = doc.section("profile staging").unwrap();
+        assert_eq!(staging.get("region"), Some("eu-west-1"));
+        assert_eq!(staging.get("s3"), Some(""));
+        // Indented sub-section keys aren't top-level entries
+        assert_eq!(staging.get("max_concurrent_requests"), None);
+        // "; staging" belongs to the section below it, not [default]
+        assert_eq!(doc.section("default").unwrap().comments().count(), 0);
+        assert_eq!(staging.raw_lines().next(), Some("; staging"));
+    }
+
+    #[test]
+    fn test_edits_keep_surrounding_lines() {
+        let mut doc = IniDocument::parse(SAMPLE);
+        let staging = doc.section("profile staging").unwrap().line();
+        doc.set(staging, "region", "eu-central-1");
+        doc.set(staging, "output", "json");
+        assert!(doc.to_string().contains(
+            "[profile staging]\nregion = eu-central-1\ns3 =\n  max_concurrent_requests = 20\noutput = json\n"
+        ));
+        doc.remove(staging, "s3");
+        assert!(doc
+            .to_string()
+            .contains("[profile staging]\nregion = eu-central-1\noutput = json\n"));
+
+        let default = doc.section("default").unwrap().line();
+        doc.remove_section(default);
+        assert!(doc
+            .to_string()
+            .starts_with("# my aws config\n\n; staging\n"));
+
+        let end = doc.len();
+        let header = doc.insert_section(end, "profile zeta");
+        doc.set(header, "region", "us-west-2");
+        assert!(doc
+            .to_string()
+            .ends_with("/start\n\n[profile zeta]\nregion = us-west-2\n"));
+    }
+}
@@ -0,0 +1,86 @@
+// OS keychain access for cached secrets
+//
+// Used by the token and credential caches when `[security] token_store = "keyring"`.
+// Callers treat every error as "keychain unavailable" and fall back to the file cache,
+// which is what keeps headless machines without a Secret Service working. Builds without
+// the `keyring` feature have no keychain and always use the file cache.
+use crate::config::{Config, TokenStore};
+use crate::error::{Result, SsoError};
+#[cfg(feature = "keyring")]
+use keyring::Entry;
+
+#[cfg(feature = "keyring")]
+const SERVICE: &str = "awsom";
+
+pub struct KeyringStore;
+
+#[cfg(feature = "keyring")]
+impl KeyringStore {
+    /// Returns a store if config.toml asks for keychain storage
+    pub fn from_config() -> Result<Option<Self>> {
+        let config = Config::load()?;
+        Ok(match config.security.token_store {
+            TokenStore::Keyring => Some(Self),
+            TokenStore::File => None,
+        })
+    }
+
+    fn entry(&self, key: &str) -> Result<Entry> {
+        Entry::new(SERVICE, key).map_err(keyring_error)
+    }
+
+    /// Read a secret, None if no entry exists
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        match self.entry(key)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(keyring_error(e)),
+        }
+    }
+
+    /// Create or overwrite a secret
+    pub fn set(&self, key: &str, secret: &str) -> Result<()> {
+        self.entry(key)?.set_password(secret).map_err(keyring_error)
+    }
+
+    /// Delete a secret, succeeding if it does not exist
+    pub fn delete(&self, key: &str) -> Result<()> {
+        match self.entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(keyring_error(e)),
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_error(e: keyring::Error) -> SsoError {
+    SsoError::CacheError(format!("Keyring error: {}", e))
+}
+
+#[cfg(not(feature = "keyring"))]
+impl KeyringStore {
+    /// Never a store: `token_store = "keyring"` is ignored with a warning
+    pub fn from_config() -> Result<Option<Self>> {
+        if Config::load()?.security.token_store == TokenStore::Keyring {
+            tracing::warn!("Built without the keyring feature; caching in files instead");
+        }
+        Ok(None)
+    }
+
+    pub fn get(&self, _key: &str) -> Result<Option<String>> {
+        Err(unavailable())
+    }
+
+    pub fn set(&self, _key: &str, _secret: &str) -> Result<()> {
+        Err(unavailable())
+    }
+
+    pub fn delete(&self, _key: &str) -> Result<()> {
+        Err(unavailable())
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn unavailable() -> SsoError {
+    SsoError::CacheError("Keyring support is not built in".to_string())
+}
@@ -0,0 +1,109 @@
+//! awsom as a library: AWS SSO login, role credentials, and profiles in ~/.aws/config and
+//! ~/.aws/credentials, shared with the `awsom` CLI and TUI
+//!
+//! Tokens and credentials are cached where the CLI caches them, so a login made through the
+//! library is visible to `awsom` and the AWS CLI, and the other way around. awsom's own
+//! config.toml (workspaces, `[security]`, `[profile]` templates) applies here as well.
+//!
+//! ```no_run
+//! use awsom::{AccountRole, AuthManager, AwsConfigStore, CredentialManager, LoginOptions};
+//!
+//! # async fn example() -> awsom::Result<()> {
+//! let store = AwsConfigStore::new();
+//! let instance = store.sso_instance(Some("my-sso"))?;
+//!
+//! // Uses the cached token if it's still valid, else starts a device code login
+//! let token = AuthManager::new()?
+//!     .login(&instance, LoginOptions::new().headless(true))
+//!     .await?;
+//!
+//! let role = AccountRole {
+//!     account_id: "111111111111".to_string(),
+//!     account_name: "prod".to_string(),
+//!     role_name: "ReadOnly".to_string(),
+//! };
+//! let creds = CredentialManager::new()?
+//!     .get_credentials(&instance, &token, &role)
+//!     .await?;
+//! store.write_credentials("prod-readonly", &creds, &instance.region, None, &role)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only the items re-exported here are part of the API; everything else is the CLI's. The
+//! CLI and TUI are built by the default `cli` feature; `default-features = false` leaves them
+//! and their terminal, clipboard and notification dependencies out.
+
+// Much of the shared code is only called by the CLI
+#![cfg_attr(not(feature = "cli"), allow(dead_code))]
+
+#[cfg(feature = "cli")]
+mod applications;
+mod audit;
+mod auth;
+mod aws_config;
+#[cfg(feature = "cli")]
+mod cli;
+#[cfg(feature = "cli")]
+mod clipboard;
+mod config;
+#[cfg(feature = "cli")]
+mod console;
+mod credentials;
+#[cfg(feature = "cli")]
+mod ecr;
+#[cfg(feature = "cli")]
+mod eks;
+mod env;
+mod error;
+mod expiry;
+mod glob;
+mod history;
+mod hooks;
+mod ini;
+mod keyring_store;
+#[cfg(feature = "cli")]
+mod logging;
+#[cfg(feature = "cli")]
+mod metadata_server;
+#[cfg(feature = "cli")]
+mod metrics;
+mod models;
+mod network;
+#[cfg(feature = "cli")]
+mod notify;
+#[cfg(feature = "cli")]
+mod orgs;
+#[cfg(feature = "cli")]
+mod output;
+#[cfg(feature = "cli")]
+mod permission_sets;
+#[cfg(feature = "cli")]
+mod prefetch;
+mod profile_template;
+mod regions;
+#[cfg(feature = "cli")]
+mod session;
+#[cfg(feature = "cli")]
+mod sigv4;
+#[cfg(feature = "cli")]
+mod sso_config;
+mod store;
+#[cfg(feature = "cli")]
+mod ui;
+#[cfg(feature = "cli")]
+mod usage;
+#[cfg(feature = "cli")]
+mod watch;
+
+pub use auth::{AuthManager, DeviceAuthorizationInfo, LoginOptions};
+pub use aws_config::{ProfileDetails, ProfileStatus, SsoProfile, SsoSession};
+pub use credentials::CredentialManager;
+pub use error::{FormatError, Result, SsoError};
+pub use models::{AccountRole, RoleCredentials, SsoInstance, SsoToken};
+pub use store::AwsConfigStore;
+
+/// Entry point of the `awsom` binary
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub use cli::main as run_cli;
@@ -0,0 +1,344 @@
+// Tracing setup: text or JSON lines, to stderr or a rotating log file
+//
+// CLI commands log to stderr so stdout stays parseable; the TUI, and any command run with
+// --log-file, log to a file instead. The file is rotated by size and optionally by day as set
+// under `[log]` in config.toml, keeping a few older files as awsom.log.1, awsom.log.2, ...
+use crate::config::{self, Config, LogConfig, LogFormat};
+use crate::error::{Result, SsoError};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Log file picked with --log-file, for this process only
+static LOG_FILE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// The open log file, when logging to one
+static LOG_FILE: OnceLock<RotatingFile> = OnceLock::new();
+
+/// The log file: --log-file, or awsom.log in the cache directory
+pub fn log_file_path() -> PathBuf {
+    LOG_FILE_OVERRIDE
+        .get()
+        .cloned()
+        .unwrap_or_else(|| config::cache_dir().join("awsom.log"))
+}
+
+/// Install the global subscriber; logs go to the log file for the TUI or with `log_file`,
+/// and to stderr otherwise
+pub fn init(
+    level: Level,
+    format: Option<LogFormat>,
+    log_file: Option<PathBuf>,
+    tui: bool,
+) -> Result<()> {
+    let settings = Config::load().map(|c| c.log).unwrap_or_default();
+    let format = format.unwrap_or(settings.format);
+    let to_file = tui || log_file.is_some();
+    if let Some(path) = log_file {
+        let _ = LOG_FILE_OVERRIDE.set(path);
+    }
+    let filter = EnvFilter::from_default_env().add_directive(level.into());
+
+    if to_file && config::is_read_only() {
+        // Read-only mode writes no log file, and stderr would break the UI
+        install(filter, format, io::sink, false);
+    } else if to_file {
+        let path = log_file_path();
+        let file = RotatingFile::open(&path, &settings).map_err(|e| {
+            SsoError::ConfigError(format!("Can't open log file {}: {}", path.display(), e))
+        })?;
+        let file = LOG_FILE.get_or_init(|| file);
+        install(filter, format, move || file, false);
+    } else {
+        install(filter, format, io::stderr, true);
+    }
+    Ok(())
+}
+
+/// Get logged lines onto disk, before the process exits on a panic or signal
+pub fn flush() {
+    if let Some(mut file) = LOG_FILE.get() {
+        let _ = file.flush();
+        let _ = file
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .file
+            .sync_data();
+    }
+}
+
+fn install<W>(filter: EnvFilter, format: LogFormat, writer: W, ansi: bool)
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(ansi);
+    match format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.event_format(JsonFormat).init(),
+    }
+}
+
+/// Events as one JSON object per line: timestamp, level, target, message, other fields and
+/// the names of the enclosing spans
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = JsonFields(Map::new());
+        event.record(&mut fields);
+        let mut fields = fields.0;
+
+        let mut line = Map::new();
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true);
+        line.insert("timestamp".into(), now.into());
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert("target".into(), metadata.target().into());
+        if let Some(message) = fields.remove("message") {
+            line.insert("message".into(), message);
+        }
+        if !fields.is_empty() {
+            line.insert("fields".into(), Value::Object(fields));
+        }
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope.from_root().map(|span| span.name().into()).collect();
+            line.insert("spans".into(), spans.into());
+        }
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Event fields as JSON values, keeping numbers and booleans as they are
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{:?}", value).into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+}
+
+/// A log file that moves itself aside when it gets too big or a new day starts
+struct RotatingFile {
+    path: PathBuf,
+    /// 0 for no size limit
+    max_bytes: u64,
+    daily: bool,
+    keep: usize,
+    state: Mutex<OpenLog>,
+}
+
+struct OpenLog {
+    file: File,
+    size: u64,
+    /// Day the file was started on, as far as its modification time tells
+    day: NaiveDate,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, settings: &LogConfig) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = open_append(path)?;
+        let metadata = file.metadata()?;
+        let day = metadata
+            .modified()
+            .ok()
+            .filter(|_| metadata.len() > 0)
+            .map_or_else(Utc::now, DateTime::<Utc>::from)
+            .date_naive();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes: settings.max_size_mb.saturating_mul(1024 * 1024),
+            daily: settings.rotate_daily,
+            keep: settings.keep,
+            state: Mutex::new(OpenLog {
+                file,
+                size: metadata.len(),
+                day,
+            }),
+        })
+    }
+
+    /// Whether writing `len` more bytes should go to a new file
+    fn is_due(&self, log: &OpenLog, len: usize, today: NaiveDate) -> bool {
+        if log.size == 0 {
+            return false;
+        }
+        let too_big = self.max_bytes > 0 && log.size + len as u64 > self.max_bytes;
+        too_big || (self.daily && log.day != today)
+    }
+
+    /// Shift awsom.log.N up by one, dropping the oldest, and start an empty log file
+    fn rotate(&self, log: &mut OpenLog, today: NaiveDate) -> io::Result<()> {
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                let _ = fs::rename(self.rotated(n), self.rotated(n + 1));
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+        }
+        log.file = open_append(&self.path)?;
+        log.size = 0;
+        log.day = today;
+        Ok(())
+    }
+
+    /// awsom.log.N
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+}
+
+impl Write for &RotatingFile {
+    // Each event arrives as one write, so a line is never split across files
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut log = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let today = Utc::now().date_naive();
+        if self.is_due(&log, buf.len(), today) {
+            self.rotate(&mut log, today)?;
+        }
+        let written = log.file.write(buf)?;
+        log.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .file
+            .flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("awsom.log");
+        let settings = LogConfig {
+            keep: 2,
+            ..LogConfig::default()
+        };
+        let file = RotatingFile::open(&path, &settings).unwrap();
+        let file = RotatingFile {
+            max_bytes: 8,
+            ..file
+        };
+
+        for line in ["one\n", "two\n", "three\n", "four\n", "five\n"] {
+            (&file).write_all(line.as_bytes()).unwrap();
+        }
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        assert_eq!(read(path.clone()), "five\n");
+        assert_eq!(read(file.rotated(1)), "four\n");
+        assert_eq!(read(file.rotated(2)), "three\n");
+        assert!(!file.rotated(3).exists());
+
+        // A new day starts a new file however small the old one is
+        let file = RotatingFile {
+            max_bytes: 0,
+            daily: true,
+            ..file
+        };
+        let yesterday = Utc::now().date_naive().pred_opt().unwrap();
+        file.state.lock().unwrap().day = yesterday;
+        (&file).write_all(b"six\n").unwrap();
+        assert_eq!(read(path), "six\n");
+        assert_eq!(read(file.rotated(1)), "five\n");
+    }
+
+    #[test]
+    fn test_json_format() {
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .event_format(JsonFormat)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("login").entered();
+            tracing::warn!(account = "111111111111", attempts = 3, "Token expired");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim_end()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["target"], "awsom::logging::tests");
+        assert_eq!(line["message"], "Token expired");
+        assert_eq!(line["fields"]["account"], "111111111111");
+        assert_eq!(line["fields"]["attempts"], 3);
+        assert_eq!(line["spans"], serde_json::json!(["login"]));
+        assert!(line["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+}
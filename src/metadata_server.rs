@@ -0,0 +1,394 @@
+// Local credential server for AWS SDKs
+//
+// Serves one role's credentials in the format of the ECS container credentials endpoint
+// (`AWS_CONTAINER_CREDENTIALS_FULL_URI` + `AWS_CONTAINER_AUTHORIZATION_TOKEN`), and
+// optionally under the EC2 instance metadata (IMDSv2) credential paths. Credentials are
+// looked up through `CredentialManager` on every request, so nothing is written to
+// ~/.aws/credentials and expiring credentials are replaced from the SSO token. With
+// metrics enabled, GET /metrics answers without authorization; it exposes no secrets.
+use crate::auth::AuthManager;
+use crate::credentials::CredentialManager;
+use crate::error::{Result, SsoError};
+use crate::metrics::{Metrics, METRICS_PATH};
+use crate::models::{AccountRole, RoleCredentials, SsoInstance};
+use chrono::Utc;
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Path of the container credentials endpoint
+pub const CREDENTIALS_PATH: &str = "/credentials";
+
+const IMDS_TOKEN_PATH: &str = "/latest/api/token";
+const IMDS_CREDENTIALS_PATH: &str = "/latest/meta-data/iam/security-credentials/";
+
+/// SDKs refresh container credentials 5-15 minutes before they expire, so never hand out
+/// credentials that are closer to expiry than that
+const MIN_VALIDITY_MINUTES: i64 = 15;
+
+/// How long a client gets to send its request headers
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Upper bound on request headers, to keep a misbehaving client from growing memory
+const MAX_HEADERS: usize = 100;
+
+pub struct MetadataServer {
+    instance: SsoInstance,
+    role: AccountRole,
+    auth_token: String,
+    /// IMDSv2 session token; None when the IMDS paths are disabled
+    imds_token: Option<String>,
+    auth: AuthManager,
+    cred_manager: CredentialManager,
+    /// None when the metrics endpoint is disabled
+    metrics: Option<Metrics>,
+}
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Route {
+    Credentials,
+    ImdsToken,
+    ImdsRoles,
+    ImdsCredentials,
+    Unauthorized,
+    NotFound,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, code: &str, message: &str) -> Self {
+        Self::json(status, json!({ "code": code, "message": message }))
+    }
+}
+
+impl MetadataServer {
+    /// A server for `role`, with a fresh random authorization token
+    pub fn new(
+        instance: SsoInstance,
+        role: AccountRole,
+        imds: bool,
+        metrics: bool,
+    ) -> Result<Self> {
+        Ok(Self {
+            instance,
+            role,
+            auth_token: uuid::Uuid::new_v4().simple().to_string(),
+            imds_token: imds.then(|| uuid::Uuid::new_v4().simple().to_string()),
+            auth: AuthManager::new()?,
+            cred_manager: CredentialManager::new()?,
+            metrics: metrics.then(Metrics::default),
+        })
+    }
+
+    /// Value clients must send in the `Authorization` header
+    pub fn auth_token(&self) -> &str {
+        &self.auth_token
+    }
+
+    /// Serve requests until the listener fails
+    ///
+    /// Requests are handled one at a time; SDKs only call the endpoint when their
+    /// credentials are about to expire.
+    pub async fn run(&self, listener: TcpListener) -> Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            if let Err(e) = self.handle(stream).await {
+                tracing::warn!("Failed to handle request from {}: {}", peer, e);
+            }
+        }
+    }
+
+    async fn handle(&self, stream: TcpStream) -> std::io::Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let request = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(reader)).await {
+            Ok(Ok(Some(request))) => request,
+            Ok(Ok(None)) => return Ok(()),
+            Ok(Err(e)) => return Err(e),
+            Err(_) => {
+                tracing::debug!("Timed out waiting for request headers");
+                return Ok(());
+            }
+        };
+
+        let response = self.respond(&request).await;
+        tracing::info!("{} {} -> {}", request.method, request.path, response.status);
+
+        let head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            response.status,
+            reason_phrase(response.status),
+            response.content_type,
+            response.body.len()
+        );
+        writer.write_all(head.as_bytes()).await?;
+        writer.write_all(response.body.as_bytes()).await?;
+        writer.shutdown().await
+    }
+
+    async fn respond(&self, request: &Request) -> Response {
+        if let Some(metrics) = &self.metrics {
+            if request.method == "GET" && request.path == METRICS_PATH {
+                return Response {
+                    status: 200,
+                    content_type: "text/plain; version=0.0.4",
+                    body: metrics.render(&self.role),
+                };
+            }
+        }
+
+        let route = route(
+            request,
+            &self.auth_token,
+            self.imds_token.as_deref(),
+            &self.role.role_name,
+        );
+
+        match route {
+            Route::Credentials | Route::ImdsCredentials => match self.credentials().await {
+                Ok(creds) => {
+                    if let Some(metrics) = &self.metrics {
+                        metrics.served(creds.expiration);
+                    }
+                    Response::json(
+                        200,
+                        credentials_json(&creds, route == Route::ImdsCredentials),
+                    )
+                }
+                Err(e) => {
+                    if let Some(metrics) = &self.metrics {
+                        metrics.refresh_failed();
+                    }
+                    tracing::warn!("Failed to get credentials: {}", e);
+                    Response::error(500, "CredentialsUnavailable", &e.to_string())
+                }
+            },
+            Route::ImdsToken => Response::text(200, self.imds_token.as_deref().unwrap_or("")),
+            Route::ImdsRoles => Response::text(200, &self.role.role_name),
+            Route::Unauthorized => Response::error(
+                401,
+                "Unauthorized",
+                "Missing or invalid authorization token",
+            ),
+            Route::NotFound => Response::error(404, "NotFound", "Not found"),
+        }
+    }
+
+    async fn credentials(&self) -> Result<RoleCredentials> {
+        let token = self.auth.get_cached_token(&self.instance)?.ok_or_else(|| {
+            SsoError::AuthenticationFailed(
+                "SSO session expired - run 'awsom session login' to renew it".to_string(),
+            )
+        })?;
+
+        self.cred_manager
+            .get_credentials_valid_for(
+                &self.instance,
+                &token,
+                &self.role,
+                chrono::Duration::minutes(MIN_VALIDITY_MINUTES),
+            )
+            .await
+    }
+}
+
+/// Read the request line and headers; None if the client closed the connection first
+async fn read_request(stream: impl AsyncRead + Unpin) -> std::io::Result<Option<Request>> {
+    let mut lines = BufReader::new(stream).lines();
+    let Some(request_line) = lines.next_line().await? else {
+        return Ok(None);
+    };
+
+    let mut parts = request_line.split_whitespace();
+    let mut request = Request {
+        method: parts.next().unwrap_or_default().to_string(),
+        // Query strings aren't used by any of the endpoints
+        path: parts
+            .next()
+            .unwrap_or_default()
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        headers: Vec::new(),
+    };
+
+    while let Some(line) = lines.next_line().await? {
+        if line.is_empty() || request.headers.len() >= MAX_HEADERS {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    Ok(Some(request))
+}
+
+fn route(request: &Request, auth_token: &str, imds_token: Option<&str>, role_name: &str) -> Route {
+    let method = request.method.as_str();
+    let path = request.path.as_str();
+
+    if path == CREDENTIALS_PATH {
+        return match (method, request.header("Authorization")) {
+            ("GET", Some(token)) if token == auth_token => Route::Credentials,
+            ("GET", _) => Route::Unauthorized,
+            _ => Route::NotFound,
+        };
+    }
+
+    let Some(imds_token) = imds_token else {
+        return Route::NotFound;
+    };
+
+    match method {
+        "PUT" if path == IMDS_TOKEN_PATH => Route::ImdsToken,
+        "GET" => match path.strip_prefix(IMDS_CREDENTIALS_PATH) {
+            // IMDSv2 only: every metadata read needs the session token
+            Some(_) if request.header("X-aws-ec2-metadata-token") != Some(imds_token) => {
+                Route::Unauthorized
+            }
+            Some("") => Route::ImdsRoles,
+            Some(name) if name == role_name => Route::ImdsCredentials,
+            _ => Route::NotFound,
+        },
+        _ => Route::NotFound,
+    }
+}
+
+/// Credentials in the container endpoint format, or the EC2 IMDS format with `imds`
+fn credentials_json(creds: &RoleCredentials, imds: bool) -> serde_json::Value {
+    let expiration = creds
+        .expiration
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let mut value = json!({
+        "AccessKeyId": creds.access_key_id,
+        "SecretAccessKey": creds.secret_access_key,
+        "Token": creds.session_token,
+        "Expiration": expiration,
+    });
+
+    if imds {
+        value["Code"] = json!("Success");
+        value["Type"] = json!("AWS-HMAC");
+        value["LastUpdated"] = json!(Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    }
+    value
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, headers: &[(&str, &str)]) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_route_checks_tokens() {
+        let container = |req: &Request| route(req, "secret", None, "ReadOnly");
+        assert_eq!(
+            container(&request(
+                "GET",
+                "/credentials",
+                &[("authorization", "secret")]
+            )),
+            Route::Credentials
+        );
+        assert_eq!(
+            container(&request(
+                "GET",
+                "/credentials",
+                &[("Authorization", "nope")]
+            )),
+            Route::Unauthorized
+        );
+        // IMDS paths are off unless enabled
+        assert_eq!(
+            container(&request("PUT", IMDS_TOKEN_PATH, &[])),
+            Route::NotFound
+        );
+
+        let imds = |req: &Request| route(req, "secret", Some("session"), "ReadOnly");
+        let token = [("X-aws-ec2-metadata-token", "session")];
+        assert_eq!(
+            imds(&request("PUT", IMDS_TOKEN_PATH, &[])),
+            Route::ImdsToken
+        );
+        assert_eq!(
+            imds(&request("GET", IMDS_CREDENTIALS_PATH, &token)),
+            Route::ImdsRoles
+        );
+        assert_eq!(
+            imds(&request(
+                "GET",
+                "/latest/meta-data/iam/security-credentials/ReadOnly",
+                &token
+            )),
+            Route::ImdsCredentials
+        );
+        assert_eq!(
+            imds(&request(
+                "GET",
+                "/latest/meta-data/iam/security-credentials/ReadOnly",
+                &[]
+            )),
+            Route::Unauthorized
+        );
+    }
+}
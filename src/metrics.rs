@@ -0,0 +1,179 @@
+// Prometheus metrics for `awsom serve --metrics`
+//
+// Rendered in the text exposition format on every scrape: seconds until each SSO session's
+// token and each profile's credentials expire (read from the caches and ~/.aws/credentials
+// at scrape time), plus counters of credential refreshes made by the server.
+use crate::auth::AuthManager;
+use crate::aws_config;
+use crate::models::{AccountRole, SsoInstance};
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Path of the metrics endpoint
+pub const METRICS_PATH: &str = "/metrics";
+
+#[derive(Default)]
+pub struct Metrics {
+    refresh_successes: AtomicU64,
+    refresh_failures: AtomicU64,
+    /// Expiration of the credentials last served, to tell refreshes from cache hits
+    served_expiration: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl Metrics {
+    /// Record credentials handed to a client; new ones count as a refresh
+    pub fn served(&self, expiration: DateTime<Utc>) {
+        let mut served = self
+            .served_expiration
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if *served != Some(expiration) {
+            self.refresh_successes.fetch_add(1, Ordering::Relaxed);
+            *served = Some(expiration);
+        }
+    }
+
+    pub fn refresh_failed(&self) {
+        self.refresh_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The metrics page for a server serving `role`
+    pub fn render(&self, role: &AccountRole) -> String {
+        let now = Utc::now();
+        let mut out = String::new();
+
+        gauge_header(
+            &mut out,
+            "awsom_sso_token_expiry_seconds",
+            "Seconds until the SSO session's access token expires (negative once expired)",
+        );
+        for (session, expires_at) in token_expirations() {
+            sample(
+                &mut out,
+                "awsom_sso_token_expiry_seconds",
+                &[("session", &session)],
+                (expires_at - now).num_seconds(),
+            );
+        }
+
+        gauge_header(
+            &mut out,
+            "awsom_credentials_expiry_seconds",
+            "Seconds until the profile's credentials in ~/.aws/credentials expire",
+        );
+        for status in aws_config::list_profile_statuses().unwrap_or_default() {
+            if let Some(expiration) = status.expiration.filter(|_| status.has_credentials) {
+                sample(
+                    &mut out,
+                    "awsom_credentials_expiry_seconds",
+                    &[("profile", &status.profile_name)],
+                    (expiration - now).num_seconds(),
+                );
+            }
+        }
+
+        gauge_header(
+            &mut out,
+            "awsom_served_credentials_expiry_seconds",
+            "Seconds until the credentials last served by this server expire",
+        );
+        let served = *self
+            .served_expiration
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(expiration) = served {
+            sample(
+                &mut out,
+                "awsom_served_credentials_expiry_seconds",
+                &[
+                    ("account_id", &role.account_id),
+                    ("account_name", &role.account_name),
+                    ("role", &role.role_name),
+                ],
+                (expiration - now).num_seconds(),
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP awsom_credential_refreshes_total Credentials fetched by this server, by result"
+        );
+        let _ = writeln!(out, "# TYPE awsom_credential_refreshes_total counter");
+        for (result, counter) in [
+            ("success", &self.refresh_successes),
+            ("failure", &self.refresh_failures),
+        ] {
+            sample(
+                &mut out,
+                "awsom_credential_refreshes_total",
+                &[("result", result)],
+                counter.load(Ordering::Relaxed) as i64,
+            );
+        }
+        out
+    }
+}
+
+/// Cached token expiry of every [sso-session] in ~/.aws/config that has a token
+fn token_expirations() -> Vec<(String, DateTime<Utc>)> {
+    let sessions = aws_config::read_all_sso_sessions().unwrap_or_default();
+    let Ok(auth) = AuthManager::new() else {
+        return Vec::new();
+    };
+
+    sessions
+        .into_iter()
+        .filter_map(|session| {
+            let instance = SsoInstance {
+                start_url: session.sso_start_url,
+                region: session.sso_region,
+                session_name: Some(session.session_name.clone()),
+            };
+            let token = auth.get_cached_token(&instance).ok()??;
+            Some((session.session_name, token.expires_at))
+        })
+        .collect()
+}
+
+fn gauge_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: i64) {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+        .collect();
+    let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+}
+
+/// Escape a label value as the exposition format requires
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_and_refresh_counting() {
+        let mut out = String::new();
+        sample(&mut out, "m", &[("profile", "a\"b\\c")], -5);
+        assert_eq!(out, "m{profile=\"a\\\"b\\\\c\"} -5\n");
+
+        let metrics = Metrics::default();
+        let expiration = Utc::now();
+        metrics.served(expiration);
+        metrics.served(expiration);
+        metrics.refresh_failed();
+        assert_eq!(metrics.refresh_successes.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.refresh_failures.load(Ordering::Relaxed), 1);
+    }
+}
@@ -12,6 +12,31 @@ pub struct SsoInstance {
     pub session_name: Option<String>,
 }
 
+/// A start URL without what doesn't change where it points: surrounding whitespace, a
+/// trailing `/` or `#/`, and the case of the scheme and host
+pub fn normalize_start_url(url: &str) -> String {
+    let mut url = url.trim();
+    loop {
+        let trimmed = url.trim_end_matches('/').trim_end_matches('#');
+        if trimmed == url {
+            break;
+        }
+        url = trimmed;
+    }
+    match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            format!(
+                "{}://{}{}",
+                scheme.to_ascii_lowercase(),
+                host.to_ascii_lowercase(),
+                path
+            )
+        }
+        None => url.to_string(),
+    }
+}
+
 /// Cached SSO-OIDC token (AWS CLI v2 compatible format)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SsoToken {
@@ -42,11 +67,26 @@ pub struct SsoToken {
         skip_serializing_if = "Option::is_none"
     )]
     pub start_url: Option<String>,
+
+    /// OIDC client registration used to obtain the token. AWS CLI v2 needs these
+    /// to refresh a token that awsom wrote
+    #[serde(rename = "clientId", skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    #[serde(rename = "clientSecret", skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+
+    #[serde(
+        rename = "registrationExpiresAt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub registration_expires_at: Option<DateTime<Utc>>,
 }
 
 impl SsoToken {
+    /// Expired, or within the `[security] expiry_margin_seconds` of it
     pub fn is_expired(&self) -> bool {
-        Utc::now() >= self.expires_at
+        crate::expiry::is_expired(&self.expires_at)
     }
 
     pub fn expires_in_seconds(&self) -> i64 {
@@ -61,7 +101,9 @@ impl SsoToken {
     pub fn expiration_display(&self) -> String {
         let mins = self.expires_in_minutes();
 
-        if mins >= 60 {
+        if self.is_expired() {
+            "EXPIRED".to_string()
+        } else if mins >= 60 {
             let hours = mins / 60;
             let remaining_mins = mins % 60;
             if remaining_mins > 0 {
@@ -115,8 +157,9 @@ pub struct RoleCredentials {
 }
 
 impl RoleCredentials {
+    /// Expired, or within the `[security] expiry_margin_seconds` of it
     pub fn is_expired(&self) -> bool {
-        Utc::now() >= self.expiration
+        crate::expiry::is_expired(&self.expiration)
     }
 
     pub fn expires_in_seconds(&self) -> i64 {
@@ -132,7 +175,9 @@ impl RoleCredentials {
         let mins = self.expires_in_minutes();
         let secs = self.expires_in_seconds() % 60;
 
-        if mins > 60 {
+        if self.is_expired() {
+            "EXPIRED".to_string()
+        } else if mins > 60 {
             let hours = mins / 60;
             let remaining_mins = mins % 60;
             format!("{}h {}m", hours, remaining_mins)
@@ -213,6 +258,24 @@ mod tests {
         assert_eq!(instance1, instance2);
     }
 
+    #[test]
+    fn test_normalize_start_url() {
+        let normalized = "https://example.awsapps.com/start";
+        for url in [
+            normalized,
+            "https://example.awsapps.com/start/",
+            "https://example.awsapps.com/start#/",
+            " HTTPS://Example.AWSAPPS.com/start/#/ ",
+        ] {
+            assert_eq!(normalize_start_url(url), normalized, "{}", url);
+        }
+        // Paths keep their case
+        assert_eq!(
+            normalize_start_url("https://d-123.awsapps.com/Start"),
+            "https://d-123.awsapps.com/Start"
+        );
+    }
+
     #[test]
     fn test_sso_token_is_expired() {
         let expired_token = SsoToken {
@@ -221,6 +284,9 @@ mod tests {
             refresh_token: None,
             region: None,
             start_url: None,
+            client_id: None,
+            client_secret: None,
+            registration_expires_at: None,
         };
         assert!(expired_token.is_expired());
 
@@ -230,6 +296,9 @@ mod tests {
             refresh_token: None,
             region: None,
             start_url: None,
+            client_id: None,
+            client_secret: None,
+            registration_expires_at: None,
         };
         assert!(!valid_token.is_expired());
     }
@@ -242,6 +311,9 @@ mod tests {
             refresh_token: None,
             region: None,
             start_url: None,
+            client_id: None,
+            client_secret: None,
+            registration_expires_at: None,
         };
         let display = token.expiration_display();
         assert!(display.contains("1h"));
@@ -252,6 +324,9 @@ mod tests {
             refresh_token: None,
             region: None,
             start_url: None,
+            client_id: None,
+            client_secret: None,
+            registration_expires_at: None,
         };
         assert_eq!(expired.expiration_display(), "EXPIRED");
     }
@@ -0,0 +1,279 @@
+// Proxy, custom CA and retry settings for every HTTP client awsom builds
+//
+// The SSO and SSO-OIDC SDK clients and the reqwest clients (console federation, EKS, ECR,
+// Organizations, doctor) are all made here, so `[network]` reaches each of them. Without
+// `[network]` settings the usual environment variables apply: HTTPS_PROXY, HTTP_PROXY,
+// ALL_PROXY and NO_PROXY for the proxy, and AWS_CA_BUNDLE for extra root certificates.
+//
+// Throttled calls, server errors and dropped connections are retried with exponential
+// backoff and jitter: by the SDK's standard retry mode for the SDK clients, and by `send`
+// for the AWS APIs awsom signs itself.
+use crate::config::{Config, NetworkConfig};
+use crate::error::{Result, SsoError};
+use aws_smithy_http_client::proxy::ProxyConfig;
+use aws_smithy_http_client::tls::{self, rustls_provider::CryptoMode, TlsContext, TrustStore};
+use aws_smithy_http_client::Connector;
+use aws_smithy_runtime_api::client::http::{http_client_fn, SharedHttpClient, SharedHttpConnector};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Calls made for one AWS API request before a throttling or transient error is returned
+const API_ATTEMPTS: u32 = 4;
+
+/// Wait before the first retry; it doubles with each one
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest wait between two calls
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Proxy and root certificates in effect
+#[derive(Debug, Clone, Default)]
+struct Network {
+    /// `[network] proxy`; None leaves the proxy to the environment variables
+    proxy: Option<String>,
+    no_proxy: Option<String>,
+    /// PEM certificates trusted next to the system's roots
+    ca_bundle: Option<Vec<u8>>,
+}
+
+impl Network {
+    fn load() -> Result<Self> {
+        Self::from_config(&Config::load()?.network)
+    }
+
+    fn from_config(config: &NetworkConfig) -> Result<Self> {
+        let ca_bundle = config.ca_bundle.clone().or_else(|| {
+            std::env::var_os("AWS_CA_BUNDLE")
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+        });
+        let ca_bundle = match ca_bundle {
+            Some(path) => {
+                let pem = fs::read(&path).map_err(|e| {
+                    SsoError::ConfigError(format!(
+                        "Failed to read CA bundle {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                // The SDK's TLS setup panics on a bad certificate, so it's checked up front
+                match reqwest::Certificate::from_pem_bundle(&pem) {
+                    Ok(certs) if !certs.is_empty() => {}
+                    Ok(_) => {
+                        return Err(SsoError::ConfigError(format!(
+                            "CA bundle {} holds no PEM certificates",
+                            path.display()
+                        )))
+                    }
+                    Err(e) => {
+                        return Err(SsoError::ConfigError(format!(
+                            "CA bundle {} is not valid PEM: {}",
+                            path.display(),
+                            e
+                        )))
+                    }
+                }
+                Some(pem)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            proxy: config
+                .proxy
+                .as_deref()
+                .map(str::trim)
+                .filter(|proxy| !proxy.is_empty())
+                .map(str::to_string),
+            no_proxy: config.no_proxy.clone(),
+            ca_bundle,
+        })
+    }
+
+    fn proxy_config(&self) -> Result<ProxyConfig> {
+        let Some(proxy) = &self.proxy else {
+            return Ok(ProxyConfig::from_env());
+        };
+        let config = ProxyConfig::all(proxy.as_str())
+            .map_err(|e| SsoError::ConfigError(format!("Invalid [network] proxy: {}", e)))?;
+        Ok(match &self.no_proxy {
+            Some(no_proxy) => config.no_proxy(no_proxy),
+            None => config,
+        })
+    }
+}
+
+/// A reqwest client builder going through the configured proxy and trusting the CA bundle
+pub fn client_builder() -> Result<reqwest::ClientBuilder> {
+    let network = Network::load()?;
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = &network.proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| SsoError::ConfigError(format!("Invalid [network] proxy: {}", e)))?
+            .no_proxy(
+                network
+                    .no_proxy
+                    .as_deref()
+                    .and_then(reqwest::NoProxy::from_string),
+            );
+        builder = builder.proxy(proxy);
+    }
+    if let Some(pem) = &network.ca_bundle {
+        for cert in reqwest::Certificate::from_pem_bundle(pem)
+            .map_err(|e| SsoError::ConfigError(format!("Invalid CA bundle: {}", e)))?
+        {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    Ok(builder)
+}
+
+/// The AWS SDK config for clients in `region`, with awsom's proxy and CA bundle
+pub async fn sdk_config(region: &str) -> Result<aws_types::SdkConfig> {
+    Ok(aws_config::defaults(aws_config::BehaviorVersion::latest())
+        .region(aws_config::Region::new(region.to_string()))
+        .http_client(sdk_http_client(&Network::load()?)?)
+        .retry_config(
+            aws_config::retry::RetryConfig::standard()
+                .with_max_attempts(API_ATTEMPTS)
+                .with_initial_backoff(INITIAL_BACKOFF)
+                .with_max_backoff(MAX_BACKOFF),
+        )
+        .load()
+        .await)
+}
+
+/// Send an AWS API request, retrying throttling, server errors, timeouts and failed
+/// connections with backoff; the last response or error is returned as is
+pub async fn send(request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    let mut attempt = 1;
+    loop {
+        // Streamed bodies can't be sent twice
+        let Some(call) = request.try_clone() else {
+            return request.send().await;
+        };
+        let result = call.send().await;
+        let transient = match &result {
+            Ok(response) => is_throttled(response),
+            Err(e) => e.is_timeout() || e.is_connect(),
+        };
+        if !transient || attempt >= API_ATTEMPTS {
+            return result;
+        }
+        let delay = backoff(attempt);
+        tracing::debug!("AWS API call failed, retry {} in {:?}", attempt, delay);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// A response worth retrying: throttled (429, or a throttling error code some APIs send
+/// with a 400) or a server error
+fn is_throttled(response: &reqwest::Response) -> bool {
+    let status = response.status();
+    let code = response
+        .headers()
+        .get("x-amzn-errortype")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+        || code.starts_with("Throttling")
+        || code.starts_with("TooManyRequests")
+}
+
+/// Wait before retry number `attempt` (from 1): exponential, capped at `MAX_BACKOFF`, and
+/// somewhere in its upper half so clients that failed together don't retry together
+pub fn backoff(attempt: u32) -> Duration {
+    let ceiling = INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_BACKOFF);
+    let jitter = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.subsec_nanos());
+    ceiling / 2 + ceiling.mul_f64(f64::from(jitter % 1000) / 2000.0)
+}
+
+/// The SDK's default rustls client, plus the proxy and CA bundle
+fn sdk_http_client(network: &Network) -> Result<SharedHttpClient> {
+    let proxy = network.proxy_config()?;
+    let mut trust_store = TrustStore::default();
+    if let Some(pem) = &network.ca_bundle {
+        trust_store.add_pem_certificate(pem.clone());
+    }
+    let tls_context = TlsContext::builder()
+        .with_trust_store(trust_store)
+        .build()
+        .map_err(|e| SsoError::ConfigError(format!("Invalid CA bundle: {}", e)))?;
+
+    // Each client asks for a connector per request; building one loads the root
+    // certificates, so the first is kept
+    let connector: Mutex<Option<SharedHttpConnector>> = Mutex::new(None);
+    Ok(http_client_fn(move |settings, components| {
+        connector
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| {
+                let mut builder = Connector::builder();
+                builder
+                    .set_connector_settings(Some(settings.clone()))
+                    .set_sleep_impl(components.sleep_impl());
+                SharedHttpConnector::new(
+                    builder
+                        .proxy_config(proxy.clone())
+                        .tls_provider(tls::Provider::Rustls(CryptoMode::AwsLc))
+                        .tls_context(tls_context.clone())
+                        .build(),
+                )
+            })
+            .clone()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = NetworkConfig {
+            proxy: Some(" http://proxy.example.com:3128 ".to_string()),
+            no_proxy: Some("localhost,.internal".to_string()),
+            ca_bundle: None,
+        };
+        let network = Network::from_config(&config).unwrap();
+        assert_eq!(
+            network.proxy.as_deref(),
+            Some("http://proxy.example.com:3128")
+        );
+        assert!(network.proxy_config().is_ok());
+
+        let bundle = dir.path().join("ca.pem");
+        fs::write(&bundle, "not a certificate").unwrap();
+        let config = NetworkConfig {
+            ca_bundle: Some(bundle),
+            ..NetworkConfig::default()
+        };
+        let error = Network::from_config(&config).unwrap_err().to_string();
+        assert!(error.contains("holds no PEM certificates"), "{}", error);
+
+        let config = NetworkConfig {
+            ca_bundle: Some(dir.path().join("missing.pem")),
+            ..NetworkConfig::default()
+        };
+        assert!(Network::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_backoff() {
+        for attempt in 1..=3 {
+            let ceiling = INITIAL_BACKOFF * 2u32.pow(attempt - 1);
+            let delay = backoff(attempt);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{:?}", delay);
+        }
+        assert!(backoff(30) <= MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,60 @@
+// Desktop notifications before SSO tokens and role credentials expire
+use crate::expiry::{format_time_remaining, is_expiring_soon};
+use chrono::{DateTime, Utc};
+use notify_rust::Notification;
+use std::collections::HashSet;
+
+/// Fires one desktop notification per expiring item
+///
+/// Callers pass every token/credential they know about on each check; an item is
+/// notified once per expiry time, so refreshed credentials can notify again later.
+pub struct ExpiryNotifier {
+    threshold_minutes: i64,
+    notified: HashSet<(String, DateTime<Utc>)>,
+}
+
+impl ExpiryNotifier {
+    /// `threshold_minutes` of 0 disables notifications
+    pub fn new(threshold_minutes: u32) -> Self {
+        Self {
+            threshold_minutes: i64::from(threshold_minutes),
+            notified: HashSet::new(),
+        }
+    }
+
+    /// Notify if `expires_at` is within the threshold and this item hasn't been notified yet
+    ///
+    /// Returns whether a notification was sent.
+    pub fn check(&mut self, label: &str, expires_at: &DateTime<Utc>) -> bool {
+        if self.threshold_minutes == 0 || !is_expiring_soon(expires_at, self.threshold_minutes) {
+            return false;
+        }
+
+        let first = self.notified.insert((label.to_string(), *expires_at));
+        if first {
+            send(
+                &format!("{} expiring soon", label),
+                &format!("Expires in {}", format_time_remaining(expires_at)),
+            );
+        }
+        first
+    }
+}
+
+/// Show a notification without blocking the caller
+fn send(summary: &str, body: &str) {
+    let summary = summary.to_string();
+    let body = body.to_string();
+
+    // D-Bus/OS calls can stall, so keep them off the TUI event loop
+    std::thread::spawn(move || {
+        if let Err(e) = Notification::new()
+            .appname("awsom")
+            .summary(&summary)
+            .body(&body)
+            .show()
+        {
+            tracing::warn!("Failed to show desktop notification: {}", e);
+        }
+    });
+}
@@ -0,0 +1,218 @@
+// AWS Organizations structure for the TUI tree view
+//
+// Reads the OU hierarchy with the credentials of a role in the management (or delegated
+// administrator) account, configured under `[organizations]` in config.toml. Requests go to
+// the Organizations JSON API through sigv4.rs.
+use crate::error::Result;
+use crate::models::RoleCredentials;
+use crate::regions::Partition;
+use crate::sigv4::{self, Client};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const TARGET_PREFIX: &str = "AWSOrganizationsV20161128";
+
+/// OU path of every account in the organization, e.g. ["Root", "Workloads", "Prod"]
+#[derive(Debug, Clone, Default)]
+pub struct OrgTree {
+    paths: HashMap<String, Vec<String>>,
+}
+
+impl OrgTree {
+    /// Walk the organization from its root, one ListOrganizationalUnitsForParent and
+    /// ListAccountsForParent call per OU; `region` picks the partition
+    pub async fn fetch(creds: &RoleCredentials, region: &str) -> Result<Self> {
+        let client = client(creds, region)?;
+        let mut tree = Self::default();
+
+        let mut parents: Vec<(String, Vec<String>)> =
+            list_all(&client, "ListRoots", json!({}), "Roots")
+                .await?
+                .into_iter()
+                .map(|root| (root.id, vec![root.name]))
+                .collect();
+
+        while let Some((parent_id, path)) = parents.pop() {
+            let parent = json!({ "ParentId": parent_id });
+
+            for account in
+                list_all(&client, "ListAccountsForParent", parent.clone(), "Accounts").await?
+            {
+                tree.paths.insert(account.id, path.clone());
+            }
+
+            for unit in list_all(
+                &client,
+                "ListOrganizationalUnitsForParent",
+                parent,
+                "OrganizationalUnits",
+            )
+            .await?
+            {
+                let mut unit_path = path.clone();
+                unit_path.push(unit.name);
+                parents.push((unit.id, unit_path));
+            }
+        }
+
+        Ok(tree)
+    }
+
+    pub fn ou_path(&self, account_id: &str) -> Option<&[String]> {
+        self.paths.get(account_id).map(Vec::as_slice)
+    }
+
+    pub fn account_count(&self) -> usize {
+        self.paths.len()
+    }
+}
+
+/// A row of the tree view: an OU header or the item at an index of the flat list
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeRow {
+    Unit {
+        path: Vec<String>,
+        depth: usize,
+        /// Items anywhere below this OU
+        items: usize,
+        collapsed: bool,
+    },
+    Item(usize),
+}
+
+/// Lay out `(path, item)` entries as a tree
+///
+/// OUs are listed depth-first and alphabetically, each followed by its own items in entry
+/// order and then its child OUs. Everything below a collapsed OU is hidden. Any grouping
+/// works as a path, and an item listed under several paths appears once under each.
+pub fn tree_rows(entries: &[(&[String], usize)], collapsed: &HashSet<Vec<String>>) -> Vec<TreeRow> {
+    // Every OU that has items somewhere below it, with its item count
+    let mut units: BTreeMap<&[String], usize> = BTreeMap::new();
+    for (path, _) in entries {
+        for depth in 1..=path.len() {
+            *units.entry(&path[..depth]).or_default() += 1;
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (unit, items) in units {
+        let hidden = (1..unit.len()).any(|depth| collapsed.contains(&unit[..depth]));
+        if hidden {
+            continue;
+        }
+
+        let is_collapsed = collapsed.contains(unit);
+        rows.push(TreeRow::Unit {
+            path: unit.to_vec(),
+            depth: unit.len() - 1,
+            items,
+            collapsed: is_collapsed,
+        });
+        if !is_collapsed {
+            rows.extend(
+                entries
+                    .iter()
+                    .filter(|(path, _)| *path == unit)
+                    .map(|(_, index)| TreeRow::Item(*index)),
+            );
+        }
+    }
+    rows
+}
+
+/// An account, OU or root - the fields awsom needs are the same for all three
+struct Node {
+    id: String,
+    name: String,
+}
+
+/// Organizations is a global service, served from one region of each partition
+fn client(creds: &RoleCredentials, region: &str) -> Result<Client> {
+    let home = match Partition::from_region(region) {
+        Partition::Aws => "us-east-1",
+        Partition::AwsUsGov => "us-gov-west-1",
+        Partition::AwsCn => "cn-northwest-1",
+    };
+    Client::new(
+        creds,
+        "organizations",
+        home,
+        sigv4::endpoint("organizations", home),
+    )
+}
+
+/// Call a paginated List* operation and collect `key` from every page
+async fn list_all(
+    client: &Client,
+    operation: &str,
+    mut request: Value,
+    key: &str,
+) -> Result<Vec<Node>> {
+    let target = format!("{}.{}", TARGET_PREFIX, operation);
+    let mut nodes = Vec::new();
+    loop {
+        let page = client.call_json(&target, &request).await?;
+        for item in page[key].as_array().into_iter().flatten() {
+            let field = |name: &str| item[name].as_str().unwrap_or_default().to_string();
+            nodes.push(Node {
+                id: field("Id"),
+                name: field("Name"),
+            });
+        }
+
+        match page["NextToken"].as_str() {
+            Some(token) => request["NextToken"] = json!(token),
+            None => return Ok(nodes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tree_rows() {
+        let prod = path(&["Root", "Prod"]);
+        let dev = path(&["Root", "Dev"]);
+        let root = path(&["Root"]);
+        let entries: Vec<(&[String], usize)> = vec![(&prod, 0), (&root, 1), (&dev, 2), (&prod, 3)];
+
+        let unit = |parts: &[&str], items, collapsed| TreeRow::Unit {
+            path: path(parts),
+            depth: parts.len() - 1,
+            items,
+            collapsed,
+        };
+
+        assert_eq!(
+            tree_rows(&entries, &HashSet::new()),
+            vec![
+                unit(&["Root"], 4, false),
+                TreeRow::Item(1),
+                unit(&["Root", "Dev"], 1, false),
+                TreeRow::Item(2),
+                unit(&["Root", "Prod"], 2, false),
+                TreeRow::Item(0),
+                TreeRow::Item(3),
+            ]
+        );
+
+        let collapsed = HashSet::from([path(&["Root", "Dev"])]);
+        assert_eq!(
+            tree_rows(&entries, &collapsed)[2],
+            unit(&["Root", "Dev"], 1, true)
+        );
+        assert_eq!(tree_rows(&entries, &collapsed).len(), 6);
+
+        let collapsed = HashSet::from([root.clone()]);
+        assert_eq!(
+            tree_rows(&entries, &collapsed),
+            vec![unit(&["Root"], 4, true)]
+        );
+    }
+}
@@ -0,0 +1,1134 @@
+// Shared output layer for CLI commands
+//
+// Every command that prints data builds one of the serde structs below and hands it to
+// `OutputFormat::print`. The structs are the public schema for `--format json|yaml`, so
+// fields may be added but must not be renamed or removed.
+use crate::applications::Application;
+use crate::audit::AuditEvent;
+use crate::aws_config::SsoSession;
+use crate::error::Result;
+use crate::history::Snapshot;
+use crate::models::{AccountRole, RoleCredentials};
+use crate::usage::RoleUsage;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Output format selectable with `--format`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text (default)
+    #[default]
+    Text,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// Aligned columns
+    Table,
+}
+
+impl OutputFormat {
+    /// Whether this format is meant to be parsed by scripts
+    pub fn is_machine_readable(self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Yaml)
+    }
+
+    /// Render a value in this format
+    pub fn render<T: Render>(self, value: &T) -> Result<String> {
+        match self {
+            OutputFormat::Text => TextRenderer.render(value),
+            OutputFormat::Json => JsonRenderer.render(value),
+            OutputFormat::Yaml => YamlRenderer.render(value),
+            OutputFormat::Table => TableRenderer.render(value),
+        }
+    }
+
+    /// Render a value and print it to stdout
+    pub fn print<T: Render>(self, value: &T) -> Result<()> {
+        let rendered = self.render(value)?;
+        println!("{}", rendered.trim_end_matches('\n'));
+        Ok(())
+    }
+}
+
+/// A value that can be printed by the CLI in any output format
+pub trait Render: Serialize {
+    /// Human-readable representation used by `--format text`
+    fn text(&self) -> String;
+
+    /// Column headers used by `--format table`
+    fn headers(&self) -> Vec<&'static str>;
+
+    /// Rows used by `--format table`, one cell per header
+    fn rows(&self) -> Vec<Vec<String>>;
+}
+
+/// Turns a renderable value into a string
+pub trait Renderer {
+    fn render<T: Render>(&self, value: &T) -> Result<String>;
+}
+
+pub struct TextRenderer;
+
+impl Renderer for TextRenderer {
+    fn render<T: Render>(&self, value: &T) -> Result<String> {
+        Ok(value.text())
+    }
+}
+
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn render<T: Render>(&self, value: &T) -> Result<String> {
+        Ok(serde_json::to_string_pretty(value)?)
+    }
+}
+
+pub struct YamlRenderer;
+
+impl Renderer for YamlRenderer {
+    fn render<T: Render>(&self, value: &T) -> Result<String> {
+        Ok(serde_yaml::to_string(value)?)
+    }
+}
+
+pub struct TableRenderer;
+
+impl Renderer for TableRenderer {
+    fn render<T: Render>(&self, value: &T) -> Result<String> {
+        Ok(format_table(&value.headers(), &value.rows()))
+    }
+}
+
+/// Lay out headers and rows as left-aligned columns separated by two spaces
+fn format_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if let Some(width) = widths.get_mut(i) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| -> String {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        line.trim_end().to_string()
+    };
+
+    let mut out = format_row(headers.to_vec());
+    out.push('\n');
+    for row in rows {
+        out.push_str(&format_row(row.iter().map(String::as_str).collect()));
+        out.push('\n');
+    }
+    out
+}
+
+/// One SSO session as printed by `session list`
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEntry {
+    pub name: String,
+    pub start_url: String,
+    pub region: String,
+    pub registration_scopes: String,
+    /// The session picked with `session switch`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub current: bool,
+    /// Whether a cached token is still valid
+    pub active: bool,
+    /// Why the session is inactive: "expired" or "no_session"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in_minutes: Option<i64>,
+    /// Profiles in ~/.aws/config bound to one of the session's roles
+    pub profiles: usize,
+}
+
+impl SessionEntry {
+    /// Combine a session's config with its token state from `session status`
+    pub fn new(session: &SsoSession, status: SessionStatusOutput, profiles: usize) -> Self {
+        Self {
+            name: session.session_name.clone(),
+            start_url: session.sso_start_url.clone(),
+            region: session.sso_region.clone(),
+            registration_scopes: session.sso_registration_scopes.clone(),
+            current: status.current,
+            active: status.active,
+            reason: status.reason,
+            expires_at: status.expires_at,
+            expires_in_minutes: status.expires_in_minutes,
+            profiles,
+        }
+    }
+
+    fn name(&self) -> String {
+        if self.current {
+            format!("{} (current)", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    fn state(&self) -> String {
+        match (self.active, self.reason.as_deref()) {
+            (true, _) => format!(
+                "active, expires in {} minutes",
+                self.expires_in_minutes.unwrap_or_default()
+            ),
+            (false, Some("expired")) => "expired".to_string(),
+            (false, _) => "not logged in".to_string(),
+        }
+    }
+}
+
+/// Output of `session list`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct SessionList(pub Vec<SessionEntry>);
+
+impl Render for SessionList {
+    fn text(&self) -> String {
+        if self.0.is_empty() {
+            return "No SSO sessions configured.\n\n\
+                    Add a session with: awsom session add --name <name> --start-url <url> --region <region>"
+                .to_string();
+        }
+
+        let mut out = format!("SSO Sessions ({}):\n\n", self.0.len());
+        for session in &self.0 {
+            out.push_str(&format!("  {}\n", session.name()));
+            out.push_str(&format!("    Start URL: {}\n", session.start_url));
+            out.push_str(&format!("    Region: {}\n", session.region));
+            out.push_str(&format!("    Status: {}\n", session.state()));
+            out.push_str(&format!("    Profiles: {}\n\n", session.profiles));
+        }
+        out
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "NAME",
+            "START URL",
+            "REGION",
+            "ACTIVE",
+            "EXPIRES AT",
+            "PROFILES",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|s| {
+                vec![
+                    s.name(),
+                    s.start_url.clone(),
+                    s.region.clone(),
+                    s.active.to_string(),
+                    s.expires_at
+                        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    s.profiles.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Output of `session status`
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStatusOutput {
+    pub active: bool,
+    /// Why the session is inactive: "not_configured", "expired" or "no_session"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_name: Option<String>,
+    /// The session picked with `session switch`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in_minutes: Option<i64>,
+}
+
+impl SessionStatusOutput {
+    pub fn inactive(reason: &str) -> Self {
+        Self {
+            active: false,
+            reason: Some(reason.to_string()),
+            session_name: None,
+            current: false,
+            start_url: None,
+            expires_at: None,
+            expires_in_minutes: None,
+        }
+    }
+}
+
+impl SessionStatusOutput {
+    /// The session's name, marked if it's the current one
+    fn name(&self) -> String {
+        let name = self.session_name.as_deref().unwrap_or("-");
+        if self.current {
+            format!("{} (current)", name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    fn summary(&self) -> String {
+        match (self.active, self.reason.as_deref()) {
+            (true, _) => format!(
+                "SSO session active (expires in {} minutes)",
+                self.expires_in_minutes.unwrap_or_default()
+            ),
+            (false, Some("not_configured")) => "SSO not configured".to_string(),
+            (false, Some("expired")) => "SSO session expired".to_string(),
+            (false, _) => "No SSO session found".to_string(),
+        }
+    }
+}
+
+impl Render for SessionStatusOutput {
+    fn text(&self) -> String {
+        match self.session_name {
+            Some(_) => format!("{}: {}", self.name(), self.summary()),
+            None => self.summary(),
+        }
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["SESSION", "ACTIVE", "EXPIRES AT", "REASON"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.name(),
+            self.active.to_string(),
+            self.expires_at
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            self.reason.clone().unwrap_or_else(|| "-".to_string()),
+        ]]
+    }
+}
+
+/// Output of `session status --all`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct SessionStatusList(pub Vec<SessionStatusOutput>);
+
+impl Render for SessionStatusList {
+    fn text(&self) -> String {
+        if self.0.is_empty() {
+            return "No SSO sessions configured".to_string();
+        }
+
+        self.0
+            .iter()
+            .map(|status| {
+                format!(
+                    "{} {}: {}\n",
+                    if status.active { "✓" } else { "✗" },
+                    status.name(),
+                    status.summary()
+                )
+            })
+            .collect()
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["SESSION", "ACTIVE", "EXPIRES AT", "REASON"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0.iter().flat_map(|status| status.rows()).collect()
+    }
+}
+
+/// Output of `profile list`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct RoleList(pub Vec<AccountRole>);
+
+impl Render for RoleList {
+    fn text(&self) -> String {
+        let mut out = String::from("Available accounts and roles:\n\n");
+        for role in &self.0 {
+            out.push_str(&format!(
+                "  {} ({}): {}\n",
+                role.account_name, role.account_id, role.role_name
+            ));
+        }
+        out
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["ACCOUNT", "ACCOUNT ID", "ROLE"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|r| {
+                vec![
+                    r.account_name.clone(),
+                    r.account_id.clone(),
+                    r.role_name.clone(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Output of `apps list`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct ApplicationList(pub Vec<Application>);
+
+impl Render for ApplicationList {
+    fn text(&self) -> String {
+        if self.0.is_empty() {
+            return "No applications assigned.".to_string();
+        }
+        let mut out = String::from("Assigned applications:\n\n");
+        for app in &self.0 {
+            match &app.application_type {
+                Some(kind) => out.push_str(&format!("  {} ({})\n", app.name, kind)),
+                None => out.push_str(&format!("  {}\n", app.name)),
+            }
+        }
+        out
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["NAME", "TYPE", "ID", "DESCRIPTION"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+        self.0
+            .iter()
+            .map(|app| {
+                vec![
+                    app.name.clone(),
+                    value(&app.application_type),
+                    app.id.clone(),
+                    value(&app.description),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// What ~/.aws/credentials holds for a profile, as reported by `profile show`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialState {
+    /// Session credentials that haven't expired
+    Valid,
+    Expired,
+    /// Replaced with placeholders by `profile stop` or the TUI
+    Invalidated,
+    /// Long-term access keys, which awsom never touches
+    Static,
+    /// No section for the profile
+    Missing,
+}
+
+impl CredentialState {
+    fn description(self) -> &'static str {
+        match self {
+            CredentialState::Valid => "valid",
+            CredentialState::Expired => "expired",
+            CredentialState::Invalidated => "invalidated",
+            CredentialState::Static => "static access keys",
+            CredentialState::Missing => "missing",
+        }
+    }
+}
+
+/// Output of `profile show`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileShow {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sso_session: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sso_account_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sso_role_name: Option<String>,
+    /// The profile is in ~/.aws/config at all
+    pub in_config: bool,
+    /// Its ~/.aws/config section is below the awsom-managed marker
+    pub managed_by_awsom: bool,
+    pub credentials: CredentialState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in_minutes: Option<i64>,
+}
+
+impl Render for ProfileShow {
+    fn text(&self) -> String {
+        let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+        let config = match (self.in_config, self.managed_by_awsom) {
+            (true, true) => "~/.aws/config (managed by awsom)",
+            (true, false) => "~/.aws/config (user-managed)",
+            (false, _) => "not in ~/.aws/config",
+        };
+        let credentials = match (self.credentials, self.expires_at) {
+            (CredentialState::Valid, Some(at)) => format!(
+                "valid until {} ({} minutes left)",
+                at.format("%Y-%m-%d %H:%M:%S UTC"),
+                self.expires_in_minutes.unwrap_or_default()
+            ),
+            (CredentialState::Expired, Some(at)) => {
+                format!("expired at {}", at.format("%Y-%m-%d %H:%M:%S UTC"))
+            }
+            (state, _) => state.description().to_string(),
+        };
+
+        format!(
+            "Profile: {}\n  \
+             Region: {}\n  \
+             Output: {}\n  \
+             SSO session: {}\n  \
+             Account ID: {}\n  \
+             Role: {}\n  \
+             Config: {}\n  \
+             Credentials: {}",
+            self.name,
+            value(&self.region),
+            value(&self.output),
+            value(&self.sso_session),
+            value(&self.sso_account_id),
+            value(&self.sso_role_name),
+            config,
+            credentials
+        )
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "PROFILE",
+            "REGION",
+            "OUTPUT",
+            "SSO SESSION",
+            "ACCOUNT ID",
+            "ROLE",
+            "MANAGED",
+            "CREDENTIALS",
+            "EXPIRES AT",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+        vec![vec![
+            self.name.clone(),
+            value(&self.region),
+            value(&self.output),
+            value(&self.sso_session),
+            value(&self.sso_account_id),
+            value(&self.sso_role_name),
+            self.managed_by_awsom.to_string(),
+            self.credentials.description().to_string(),
+            self.expires_at
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        ]]
+    }
+}
+
+/// Output of `profile export`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedCredentials {
+    pub account_id: String,
+    pub role_name: String,
+    pub region: String,
+    /// Profile written to ~/.aws/credentials, if `--profile` was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Omitted when credentials were written to a profile instead of exported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+    pub expiration: DateTime<Utc>,
+}
+
+impl ExportedCredentials {
+    pub fn new(
+        account_id: &str,
+        role_name: &str,
+        region: &str,
+        creds: &RoleCredentials,
+        profile: Option<String>,
+    ) -> Self {
+        let include_secrets = profile.is_none();
+        Self {
+            account_id: account_id.to_string(),
+            role_name: role_name.to_string(),
+            region: region.to_string(),
+            profile,
+            access_key_id: include_secrets.then(|| creds.access_key_id.clone()),
+            secret_access_key: include_secrets.then(|| creds.secret_access_key.clone()),
+            session_token: include_secrets.then(|| creds.session_token.clone()),
+            expiration: creds.expiration,
+        }
+    }
+}
+
+impl ExportedCredentials {
+    /// `$Env:` assignments for PowerShell, which has no `export`
+    pub fn powershell(&self) -> String {
+        let quote =
+            |value: &Option<String>| value.as_deref().unwrap_or_default().replace('\'', "''");
+        format!(
+            "$Env:AWS_ACCESS_KEY_ID = '{}'\n\
+             $Env:AWS_SECRET_ACCESS_KEY = '{}'\n\
+             $Env:AWS_SESSION_TOKEN = '{}'\n\
+             $Env:AWS_REGION = '{}'\n\
+             # Credentials expire at: {}",
+            quote(&self.access_key_id),
+            quote(&self.secret_access_key),
+            quote(&self.session_token),
+            self.region.replace('\'', "''"),
+            self.expiration.format("%Y-%m-%d %H:%M:%S UTC")
+        )
+    }
+}
+
+impl Render for ExportedCredentials {
+    fn text(&self) -> String {
+        if let Some(profile) = &self.profile {
+            return format!(
+                "Profile '{}' written to ~/.aws/credentials (expires {})",
+                profile,
+                self.expiration.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+        }
+
+        format!(
+            "export AWS_ACCESS_KEY_ID=\"{}\"\n\
+             export AWS_SECRET_ACCESS_KEY=\"{}\"\n\
+             export AWS_SESSION_TOKEN=\"{}\"\n\
+             export AWS_REGION=\"{}\"\n\
+             # Credentials expire at: {}",
+            self.access_key_id.as_deref().unwrap_or_default(),
+            self.secret_access_key.as_deref().unwrap_or_default(),
+            self.session_token.as_deref().unwrap_or_default(),
+            self.region,
+            self.expiration.format("%Y-%m-%d %H:%M:%S UTC")
+        )
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["ACCOUNT ID", "ROLE", "REGION", "PROFILE", "EXPIRES AT"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.account_id.clone(),
+            self.role_name.clone(),
+            self.region.clone(),
+            self.profile.clone().unwrap_or_else(|| "-".to_string()),
+            self.expiration.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        ]]
+    }
+}
+
+/// Output of `import`
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportResult {
+    pub name: String,
+    pub section_type: String,
+    pub imported: bool,
+    /// Why `import --all` couldn't import it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ImportResult {
+    fn label(&self) -> &'static str {
+        match self.section_type.as_str() {
+            "sso-session" => "SSO session",
+            "credentials" => "static credentials",
+            _ => "profile",
+        }
+    }
+}
+
+impl Render for ImportResult {
+    fn text(&self) -> String {
+        if !self.imported {
+            return "Import cancelled.".to_string();
+        }
+
+        if self.section_type == "credentials" {
+            return format!(
+                "✓ Now tracking static credentials '{}'\n\n\
+                 The keys stay in ~/.aws/credentials; awsom will never overwrite them.",
+                self.name
+            );
+        }
+        format!(
+            "✓ Imported {} '{}' to awsom management\n\n\
+             The {} has been moved from user-managed to awsom-managed section.\n\
+             It will now be automatically organized and sorted by awsom.",
+            self.label(),
+            self.name,
+            self.section_type
+        )
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["NAME", "TYPE", "IMPORTED", "ERROR"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.name.clone(),
+            self.section_type.clone(),
+            self.imported.to_string(),
+            self.error.clone().unwrap_or_default(),
+        ]]
+    }
+}
+
+/// Output of `import --all`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct ImportResults(pub Vec<ImportResult>);
+
+impl Render for ImportResults {
+    fn text(&self) -> String {
+        if self.0.is_empty() {
+            return "Nothing to import - awsom already manages every section.".to_string();
+        }
+
+        self.0
+            .iter()
+            .map(|r| match (&r.error, r.imported) {
+                (Some(error), _) => format!("✗ Failed   {} '{}': {}", r.label(), r.name, error),
+                (None, true) => format!("✓ Imported {} '{}'", r.label(), r.name),
+                (None, false) => format!("- Skipped  {} '{}'", r.label(), r.name),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["NAME", "TYPE", "IMPORTED", "ERROR"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0.iter().flat_map(Render::rows).collect()
+    }
+}
+
+/// Output of `audit show`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct AuditLog(pub Vec<AuditEvent>);
+
+impl Render for AuditLog {
+    fn text(&self) -> String {
+        if self.0.is_empty() {
+            return "No audit events found.".to_string();
+        }
+        format_table(&self.headers(), &self.rows())
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["TIME", "ACTION", "ACCOUNT", "ROLE", "PROFILE", "INITIATOR"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let dash = || "-".to_string();
+        self.0
+            .iter()
+            .map(|e| {
+                vec![
+                    e.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                    e.action.label().to_string(),
+                    e.account_id.clone().unwrap_or_else(dash),
+                    e.role_name.clone().unwrap_or_else(dash),
+                    e.profile.clone().unwrap_or_else(dash),
+                    e.initiator.clone(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Output of `stats`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct UsageStats(pub Vec<RoleUsage>);
+
+impl Render for UsageStats {
+    fn text(&self) -> String {
+        if self.0.is_empty() {
+            return "No role activations recorded yet.".to_string();
+        }
+        format_table(&self.headers(), &self.rows())
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "ACCOUNT",
+            "ROLE",
+            "PROFILE",
+            "COUNT",
+            "LAST USED",
+            "FIRST USED",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|u| {
+                vec![
+                    u.account_id.clone(),
+                    u.role_name.clone(),
+                    u.profile.clone().unwrap_or_else(|| "-".to_string()),
+                    u.count.to_string(),
+                    u.last_used.format("%Y-%m-%d %H:%M UTC").to_string(),
+                    u.first_used.format("%Y-%m-%d %H:%M UTC").to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Output of `rollback --list`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct SnapshotList(pub Vec<Snapshot>);
+
+impl Render for SnapshotList {
+    fn text(&self) -> String {
+        if self.0.is_empty() {
+            return "No config history saved yet.".to_string();
+        }
+        format_table(&self.headers(), &self.rows())
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["ID", "TIME", "COMMAND", "BEFORE CHANGE TO"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|s| {
+                vec![
+                    s.id.clone(),
+                    s.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                    s.command.clone(),
+                    format!("~/.aws/{}", s.changed),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Which cache a `cache list` entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    /// SSO access tokens in ~/.aws/sso/cache
+    Tokens,
+    /// Role credentials in ~/.aws/cli/cache
+    Credentials,
+    /// awsom's account/role listings
+    Accounts,
+    /// awsom's OIDC client registrations in ~/.aws/sso/cache
+    Registrations,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntry {
+    pub kind: CacheKind,
+    /// Start URL for tokens and listings, file name for credentials, region for
+    /// registrations
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    /// When the token, credentials or registration expire, or the listing goes stale
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Output of `cache list`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct CacheList(pub Vec<CacheEntry>);
+
+impl Render for CacheList {
+    fn text(&self) -> String {
+        if self.0.is_empty() {
+            return "Nothing cached.".to_string();
+        }
+        format_table(&self.headers(), &self.rows())
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["KIND", "NAME", "SIZE", "EXPIRES", "PATH"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let now = Utc::now();
+        self.0
+            .iter()
+            .map(|e| {
+                let expires = match e.expires_at {
+                    Some(at) if at <= now => {
+                        format!("{} (expired)", at.format("%Y-%m-%d %H:%M UTC"))
+                    }
+                    Some(at) => at.format("%Y-%m-%d %H:%M UTC").to_string(),
+                    None => "-".to_string(),
+                };
+                vec![
+                    format!("{:?}", e.kind).to_lowercase(),
+                    e.name.clone(),
+                    format_size(e.size_bytes),
+                    expires,
+                    e.path.clone(),
+                ]
+            })
+            .collect()
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Outcome of a single `doctor` check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl CheckStatus {
+    fn symbol(self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "✓",
+            CheckStatus::Warning => "⚠",
+            CheckStatus::Error => "✗",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    pub check: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// Suggested fix, for warnings and errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+/// Output of `doctor`
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct DoctorReport(pub Vec<DoctorCheck>);
+
+impl DoctorReport {
+    pub fn has_errors(&self) -> bool {
+        self.0.iter().any(|c| c.status == CheckStatus::Error)
+    }
+}
+
+impl Render for DoctorReport {
+    fn text(&self) -> String {
+        let mut out = String::new();
+        for check in &self.0 {
+            out.push_str(&format!(
+                "{} {}: {}\n",
+                check.status.symbol(),
+                check.check,
+                check.message
+            ));
+            if let Some(fix) = &check.fix {
+                out.push_str(&format!("    → {}\n", fix));
+            }
+        }
+        out
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["CHECK", "STATUS", "MESSAGE", "FIX"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|c| {
+                vec![
+                    c.check.clone(),
+                    format!("{:?}", c.status).to_lowercase(),
+                    c.message.clone(),
+                    c.fix.clone().unwrap_or_else(|| "-".to_string()),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table_aligns_columns() {
+        let table = format_table(
+            &["NAME", "REGION"],
+            &[
+                vec!["prod".to_string(), "us-east-1".to_string()],
+                vec!["development".to_string(), "eu-west-1".to_string()],
+            ],
+        );
+
+        assert_eq!(
+            table,
+            "NAME         REGION\n\
+             prod         us-east-1\n\
+             development  eu-west-1\n"
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1_048_576), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_session_status_json_schema() {
+        let status = SessionStatusOutput::inactive("expired");
+        let json = OutputFormat::Json.render(&status).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["active"], false);
+        assert_eq!(value["reason"], "expired");
+        assert!(value.get("expires_in_minutes").is_none());
+        assert!(value.get("current").is_none());
+
+        let status = SessionStatusOutput {
+            session_name: Some("corp".to_string()),
+            current: true,
+            ..status
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&OutputFormat::Json.render(&status).unwrap()).unwrap();
+        assert_eq!(value["current"], true);
+        assert_eq!(status.text(), "corp (current): SSO session expired");
+    }
+
+    #[test]
+    fn test_session_list_merges_token_state() {
+        let session = SsoSession {
+            session_name: "corp".to_string(),
+            sso_start_url: "https://corp.awsapps.com/start".to_string(),
+            sso_region: "us-east-1".to_string(),
+            sso_registration_scopes: "sso:account:access".to_string(),
+        };
+        let list = SessionList(vec![SessionEntry::new(
+            &session,
+            SessionStatusOutput {
+                current: true,
+                ..SessionStatusOutput::inactive("expired")
+            },
+            3,
+        )]);
+
+        let value: serde_json::Value =
+            serde_json::from_str(&OutputFormat::Json.render(&list).unwrap()).unwrap();
+        assert_eq!(value[0]["active"], false);
+        assert_eq!(value[0]["reason"], "expired");
+        assert_eq!(value[0]["current"], true);
+        assert_eq!(value[0]["profiles"], 3);
+        assert!(value[0].get("expires_at").is_none());
+
+        let text = list.text();
+        assert!(text.contains("  corp (current)\n"), "{}", text);
+        assert!(text.contains("Status: expired"), "{}", text);
+        assert!(text.contains("Profiles: 3"), "{}", text);
+    }
+
+    #[test]
+    fn test_profile_show_json_schema() {
+        let show = ProfileShow {
+            name: "dev".to_string(),
+            region: Some("eu-west-1".to_string()),
+            output: None,
+            sso_session: Some("corp".to_string()),
+            sso_account_id: Some("111111111111".to_string()),
+            sso_role_name: Some("Developer".to_string()),
+            in_config: true,
+            managed_by_awsom: true,
+            credentials: CredentialState::Invalidated,
+            expires_at: None,
+            expires_in_minutes: None,
+        };
+        let json = OutputFormat::Json.render(&show).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["sso_account_id"], "111111111111");
+        assert_eq!(value["managed_by_awsom"], true);
+        assert_eq!(value["credentials"], "invalidated");
+        assert!(value.get("output").is_none());
+        assert!(show.text().contains("Credentials: invalidated"));
+    }
+
+    #[test]
+    fn test_exported_credentials_powershell() {
+        let creds = RoleCredentials {
+            access_key_id: "AKIA".to_string(),
+            secret_access_key: "it's".to_string(),
+            session_token: "token".to_string(),
+            expiration: DateTime::parse_from_rfc3339("2026-01-31T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        };
+        let exported = ExportedCredentials::new("111111111111", "Dev", "eu-west-1", &creds, None);
+        let powershell = exported.powershell();
+        let lines: Vec<&str> = powershell.lines().collect();
+        assert_eq!(lines[0], "$Env:AWS_ACCESS_KEY_ID = 'AKIA'");
+        assert_eq!(lines[1], "$Env:AWS_SECRET_ACCESS_KEY = 'it''s'");
+        assert_eq!(lines[3], "$Env:AWS_REGION = 'eu-west-1'");
+    }
+}
@@ -0,0 +1,151 @@
+// IAM Identity Center permission sets, for the TUI's role details popup
+//
+// An SSO role is named after the permission set it was provisioned from. Permission sets
+// can only be read through the sso-admin API, from the management or delegated
+// administrator account, so awsom reads them with the `[organizations]` role when that role
+// has sso:ListInstances, sso:ListPermissionSets and sso:DescribePermissionSet. Requests go
+// to the sso-admin JSON API through sigv4.rs.
+use crate::error::{Result, SsoError};
+use crate::models::RoleCredentials;
+use crate::sigv4::{self, Client};
+use chrono::Duration;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+const TARGET_PREFIX: &str = "SWBExternalService";
+const SIGNING_NAME: &str = "sso";
+/// DescribePermissionSet calls in flight at once
+const DESCRIBE_CONCURRENCY: usize = 8;
+
+/// What a permission set grants, as far as its metadata says
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermissionSet {
+    pub name: String,
+    pub description: Option<String>,
+    /// How long credentials for its roles last
+    pub session_duration: Option<Duration>,
+}
+
+/// Every permission set of the Identity Center instance in `region`: one ListInstances
+/// call, ListPermissionSets pages, and a DescribePermissionSet per set, up to
+/// `DESCRIBE_CONCURRENCY` at a time
+pub async fn list(creds: &RoleCredentials, region: &str) -> Result<Vec<PermissionSet>> {
+    let client = Arc::new(Client::new(
+        creds,
+        SIGNING_NAME,
+        region,
+        sigv4::endpoint("sso", region),
+    )?);
+
+    let instances = call(&client, "ListInstances", &json!({})).await?;
+    let Some(instance_arn) = instances["Instances"][0]["InstanceArn"].as_str() else {
+        return Err(SsoError::AwsSdk(format!(
+            "No IAM Identity Center instance in {}",
+            region
+        )));
+    };
+
+    let mut arns = Vec::new();
+    let mut request = json!({ "InstanceArn": instance_arn });
+    loop {
+        let page = call(&client, "ListPermissionSets", &request).await?;
+        arns.extend(
+            page["PermissionSets"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string),
+        );
+        match page["NextToken"].as_str() {
+            Some(token) => request["NextToken"] = json!(token),
+            None => break,
+        }
+    }
+
+    let permits = Arc::new(Semaphore::new(DESCRIBE_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (index, arn) in arns.into_iter().enumerate() {
+        let client = Arc::clone(&client);
+        let permits = Arc::clone(&permits);
+        let request = json!({ "InstanceArn": instance_arn, "PermissionSetArn": arn });
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let response = call(&client, "DescribePermissionSet", &request).await?;
+            Ok::<_, SsoError>((index, permission_set(&response["PermissionSet"])))
+        });
+    }
+
+    let mut permission_sets = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        let described = joined
+            .map_err(|e| SsoError::AwsSdk(format!("DescribePermissionSet task failed: {}", e)))?;
+        // The first failure ends the listing; dropping `tasks` cancels the rest
+        permission_sets.push(described?);
+    }
+    // In ListPermissionSets order
+    permission_sets.sort_by_key(|(index, _)| *index);
+    Ok(permission_sets.into_iter().map(|(_, set)| set).collect())
+}
+
+async fn call(client: &Client, operation: &str, request: &Value) -> Result<Value> {
+    client
+        .call_json(&format!("{}.{}", TARGET_PREFIX, operation), request)
+        .await
+}
+
+/// A PermissionSet of a DescribePermissionSet response
+fn permission_set(set: &Value) -> PermissionSet {
+    PermissionSet {
+        name: set["Name"].as_str().unwrap_or_default().to_string(),
+        description: set["Description"]
+            .as_str()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string),
+        session_duration: set["SessionDuration"]
+            .as_str()
+            .and_then(parse_session_duration),
+    }
+}
+
+/// An ISO 8601 duration as sso-admin returns it, e.g. PT1H or PT2H30M
+fn parse_session_duration(text: &str) -> Option<Duration> {
+    let mut rest = text.strip_prefix("PT")?;
+    let mut total = Duration::zero();
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let value: i64 = rest[..digits].parse().ok()?;
+        total += match rest[digits..].chars().next()? {
+            'H' => Duration::hours(value),
+            'M' => Duration::minutes(value),
+            'S' => Duration::seconds(value),
+            _ => return None,
+        };
+        rest = &rest[digits + 1..];
+    }
+    (total > Duration::zero()).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_session_duration() {
+        assert_eq!(parse_session_duration("PT1H"), Some(Duration::hours(1)));
+        assert_eq!(
+            parse_session_duration("PT2H30M"),
+            Some(Duration::minutes(150))
+        );
+        assert_eq!(
+            parse_session_duration("PT900S"),
+            Some(Duration::minutes(15))
+        );
+        assert_eq!(parse_session_duration("P1D"), None);
+        assert_eq!(parse_session_duration("PT"), None);
+        assert_eq!(parse_session_duration("PTH"), None);
+    }
+}
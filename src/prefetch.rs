@@ -0,0 +1,147 @@
+// Credentials for favorite roles, written to their profiles right after an SSO login
+//
+// The roles are the session's favorites picked by `Config::prefetch_favorites`. They are
+// fetched a few at a time; the caller writes each one as it arrives, so the credentials file
+// is only ever written from one place.
+use crate::aws_config::{self, SsoProfile};
+use crate::config::{Config, ProfileMode};
+use crate::credentials::CredentialManager;
+use crate::error::{Result, SsoError};
+use crate::models::{AccountRole, RoleCredentials, SsoInstance};
+use crate::profile_template::{self, ProfileNameContext};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Roles whose credentials are fetched at the same time
+const PREFETCH_CONCURRENCY: usize = 4;
+
+/// The session's roles that are favorites to prefetch; empty without any, in which case
+/// nothing is listed
+pub async fn roles(instance: &SsoInstance, access_token: &str) -> Result<Vec<AccountRole>> {
+    let config = Config::load()?;
+    let favorites = config.prefetch_favorites();
+    if favorites.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut roles = CredentialManager::new()?
+        .list_roles(instance, access_token)
+        .await?;
+    roles.retain(|role| {
+        favorites
+            .iter()
+            .any(|f| f.account_id == role.account_id && f.role_name == role.role_name)
+    });
+    Ok(roles)
+}
+
+/// Fetch fresh credentials for each role, handing each result to `on_fetched` with the
+/// number done so far
+pub async fn fetch(
+    instance: &SsoInstance,
+    access_token: &str,
+    roles: Vec<AccountRole>,
+    mut on_fetched: impl FnMut(AccountRole, Result<RoleCredentials>, usize),
+) -> Result<()> {
+    let manager = Arc::new(CredentialManager::new()?);
+    let semaphore = Arc::new(Semaphore::new(PREFETCH_CONCURRENCY));
+    let access_token = Arc::new(access_token.to_string());
+    let mut tasks = JoinSet::new();
+    for role in roles {
+        let manager = Arc::clone(&manager);
+        let semaphore = Arc::clone(&semaphore);
+        let access_token = Arc::clone(&access_token);
+        let instance = instance.clone();
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = manager
+                .refresh_credentials(&instance, &access_token, &role, None)
+                .await;
+            (role, result)
+        });
+    }
+
+    let mut done = 0;
+    while let Some(joined) = tasks.join_next().await {
+        let (role, result) =
+            joined.map_err(|e| SsoError::AwsSdk(format!("Failed to fetch credentials: {}", e)))?;
+        done += 1;
+        on_fetched(role, result, done);
+    }
+    Ok(())
+}
+
+/// Write a role's credentials to the profile already holding its credentials, or to a new
+/// one named by the profile name template; returns the profile name
+///
+/// With `profile_mode = "sso-native"` only the profile's SSO settings are written.
+pub fn write_profile(
+    instance: &SsoInstance,
+    role: &AccountRole,
+    creds: &RoleCredentials,
+) -> Result<String> {
+    let config = Config::load()?;
+    let sso_native = config.profile_defaults.profile_mode == ProfileMode::SsoNative;
+    if let Some(profile_name) = aws_config::get_existing_profile_name(role)?.filter(|_| !sso_native)
+    {
+        let details = aws_config::get_profile_details(&profile_name)?;
+        let region = details
+            .as_ref()
+            .and_then(|d| d.region.clone())
+            .unwrap_or_else(|| instance.region.clone());
+        let output = details.and_then(|d| d.output);
+        aws_config::write_credentials_with_metadata(
+            &profile_name,
+            creds,
+            &region,
+            output.as_deref(),
+            Some(role),
+        )?;
+        return Ok(profile_name);
+    }
+
+    // Profiles created by the TUI and `create` are named after the session too
+    let session_name = match &instance.session_name {
+        Some(name) => Some(name.clone()),
+        None => aws_config::session_name_for_start_url(&instance.start_url)?,
+    };
+    let profile_name = profile_template::render(
+        &config.profile_defaults.name_template,
+        &ProfileNameContext {
+            account_name: &role.account_name,
+            account_id: &role.account_id,
+            role_name: &role.role_name,
+            session_name: session_name.as_deref(),
+        },
+    )?;
+
+    let defaults = aws_config::read_awsom_defaults()?;
+    let region = defaults
+        .as_ref()
+        .map(|d| d.region.clone())
+        .unwrap_or_else(|| instance.region.clone());
+    let output = defaults.map(|d| d.output);
+    if sso_native {
+        aws_config::write_sso_native_profile(&SsoProfile {
+            name: profile_name.clone(),
+            sso_session: aws_config::sso_native_session(instance)?,
+            account_id: role.account_id.clone(),
+            role_name: role.role_name.clone(),
+            region,
+            output,
+            credential_process: false,
+            source_profile: None,
+        })?;
+        return Ok(profile_name);
+    }
+    aws_config::write_credentials_with_metadata(
+        &profile_name,
+        creds,
+        &region,
+        output.as_deref(),
+        Some(role),
+    )?;
+    Ok(profile_name)
+}
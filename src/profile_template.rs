@@ -0,0 +1,134 @@
+// Profile name templates - `[profile_defaults] name_template` in config.toml
+//
+// A template is literal text with `{placeholder}` fields. Each placeholder can be followed
+// by `|transform` steps applied left to right, e.g. `{account_name|short|lower}-{role_name}`.
+use crate::error::{Result, SsoError};
+
+/// Reproduces the historical `account-name_role-name` naming
+pub const DEFAULT_TEMPLATE: &str = "{account_name|kebab}_{role_name|kebab}";
+
+/// Values available to a template
+pub struct ProfileNameContext<'a> {
+    pub account_name: &'a str,
+    pub account_id: &'a str,
+    pub role_name: &'a str,
+    pub session_name: Option<&'a str>,
+}
+
+/// Render `template` into a profile name
+pub fn render(template: &str, ctx: &ProfileNameContext) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| invalid(template, "unclosed '{'"))?;
+
+        let mut parts = after[..end].split('|').map(str::trim);
+        let field = parts.next().unwrap_or_default();
+        let mut value = match field {
+            "account_name" => ctx.account_name.to_string(),
+            "account_id" => ctx.account_id.to_string(),
+            "role_name" => ctx.role_name.to_string(),
+            "session_name" => ctx.session_name.unwrap_or_default().to_string(),
+            other => return Err(invalid(template, &format!("unknown field '{}'", other))),
+        };
+
+        for transform in parts {
+            value = apply_transform(transform, &value)
+                .ok_or_else(|| invalid(template, &format!("unknown transform '{}'", transform)))?;
+        }
+
+        out.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+
+    // Whitespace would break `--profile <name>` and the INI section header
+    let name: String = out.split_whitespace().collect::<Vec<_>>().join("-");
+    if name.is_empty() {
+        return Err(invalid(template, "produces an empty profile name"));
+    }
+
+    Ok(name)
+}
+
+fn apply_transform(transform: &str, value: &str) -> Option<String> {
+    Some(match transform {
+        "lower" => value.to_lowercase(),
+        "upper" => value.to_uppercase(),
+        // Spaces and underscores become dashes, matching the old default naming
+        "kebab" => value.replace([' ', '_'], "-").to_lowercase(),
+        "snake" => value.replace([' ', '-'], "_").to_lowercase(),
+        // First word, e.g. "Production Payments" -> "Production"
+        "short" => value
+            .split([' ', '-', '_', '.'])
+            .find(|word| !word.is_empty())
+            .unwrap_or_default()
+            .to_string(),
+        _ => return None,
+    })
+}
+
+fn invalid(template: &str, reason: &str) -> SsoError {
+    SsoError::InvalidConfig(format!(
+        "Invalid profile name template '{}': {}",
+        template, reason
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> ProfileNameContext<'static> {
+        ProfileNameContext {
+            account_name: "Production Payments",
+            account_id: "123456789012",
+            role_name: "Admin_Access",
+            session_name: Some("corp"),
+        }
+    }
+
+    #[test]
+    fn test_default_template_matches_legacy_naming() {
+        assert_eq!(
+            render(DEFAULT_TEMPLATE, &ctx()).unwrap(),
+            "production-payments_admin-access"
+        );
+    }
+
+    #[test]
+    fn test_transforms_and_fields() {
+        assert_eq!(
+            render(
+                "{session_name}-{account_name|short|lower}-{role_name|snake}",
+                &ctx()
+            )
+            .unwrap(),
+            "corp-production-admin_access"
+        );
+        assert_eq!(
+            render("{account_id}/{role_name|upper}", &ctx()).unwrap(),
+            "123456789012/ADMIN_ACCESS"
+        );
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(render("{account}", &ctx()).is_err());
+        assert!(render("{role_name|camel}", &ctx()).is_err());
+        assert!(render("{role_name", &ctx()).is_err());
+        assert!(render(
+            "{session_name}",
+            &ProfileNameContext {
+                session_name: None,
+                ..ctx()
+            }
+        )
+        .is_err());
+    }
+}
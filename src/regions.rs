@@ -0,0 +1,263 @@
+// AWS regions and AWS CLI output formats, to catch typos before they reach ~/.aws/config
+//
+// Both lists are checked by the clap value parsers of --region/--output flags and by the
+// TUI's input dialogs. An unknown output format is rejected with the closest known one
+// suggested. Regions launch more often than awsom is released, so any name shaped like a
+// region is accepted and an unknown one only draws a warning with the suggestion.
+
+/// Every AWS region: the commercial partition, then GovCloud (US) and China
+pub const REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
+    "af-south-1",
+    "ap-east-1",
+    "ap-east-2",
+    "ap-northeast-1",
+    "ap-northeast-2",
+    "ap-northeast-3",
+    "ap-south-1",
+    "ap-south-2",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ap-southeast-3",
+    "ap-southeast-4",
+    "ap-southeast-5",
+    "ap-southeast-6",
+    "ap-southeast-7",
+    "ca-central-1",
+    "ca-west-1",
+    "eu-central-1",
+    "eu-central-2",
+    "eu-north-1",
+    "eu-south-1",
+    "eu-south-2",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "il-central-1",
+    "me-central-1",
+    "me-south-1",
+    "mx-central-1",
+    "sa-east-1",
+    "us-gov-east-1",
+    "us-gov-west-1",
+    "cn-north-1",
+    "cn-northwest-1",
+];
+
+/// The AWS partition a region belongs to, which decides ARNs and sign-in/console domains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Partition {
+    Aws,
+    /// AWS GovCloud (US), us-gov-* regions
+    AwsUsGov,
+    /// AWS China, cn-* regions
+    AwsCn,
+}
+
+impl Partition {
+    /// The partition of `region`; anything not GovCloud or China is the commercial one
+    pub fn from_region(region: &str) -> Self {
+        if region.starts_with("us-gov-") {
+            Partition::AwsUsGov
+        } else if region.starts_with("cn-") {
+            Partition::AwsCn
+        } else {
+            Partition::Aws
+        }
+    }
+
+    /// Partition name as used in ARNs
+    pub fn name(self) -> &'static str {
+        match self {
+            Partition::Aws => "aws",
+            Partition::AwsUsGov => "aws-us-gov",
+            Partition::AwsCn => "aws-cn",
+        }
+    }
+
+    /// Domain of the partition's service endpoints, e.g. `ecr.<region>.amazonaws.com.cn`
+    pub fn dns_suffix(self) -> &'static str {
+        match self {
+            Partition::Aws | Partition::AwsUsGov => "amazonaws.com",
+            Partition::AwsCn => "amazonaws.com.cn",
+        }
+    }
+
+    /// Region to use when none is given
+    pub fn default_region(self) -> &'static str {
+        match self {
+            Partition::Aws => "us-east-1",
+            Partition::AwsUsGov => "us-gov-west-1",
+            Partition::AwsCn => "cn-north-1",
+        }
+    }
+
+    /// Every known region of this partition
+    pub fn regions(self) -> impl Iterator<Item = &'static str> {
+        REGIONS
+            .iter()
+            .copied()
+            .filter(move |region| Partition::from_region(region) == self)
+    }
+}
+
+/// Values the AWS CLI accepts for `output`
+pub const OUTPUT_FORMATS: &[&str] = &["json", "yaml", "yaml-stream", "text", "table"];
+
+/// Typos further than this many edits from every known value get no suggestion
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// Ok for anything shaped like a region, known or not (see `region_warning`); otherwise an
+/// error naming the closest known one
+pub fn check_region(region: &str) -> Result<(), String> {
+    if REGIONS.contains(&region) || is_region_shaped(region) {
+        return Ok(());
+    }
+    Err(match closest(region, REGIONS) {
+        Some(suggestion) => format!(
+            "unknown AWS region '{}'; did you mean '{}'?",
+            region, suggestion
+        ),
+        None => format!(
+            "unknown AWS region '{}' (regions look like us-east-1 or eu-west-2)",
+            region
+        ),
+    })
+}
+
+/// A warning for a region `check_region` accepts but awsom doesn't know: a typo, or one
+/// newer than this release
+pub fn region_warning(region: &str) -> Option<String> {
+    if REGIONS.contains(&region) {
+        return None;
+    }
+    Some(match closest(region, REGIONS) {
+        Some(suggestion) => format!(
+            "Unknown AWS region '{}'; did you mean '{}'?",
+            region, suggestion
+        ),
+        None => format!("AWS region '{}' is new to awsom; using it as given", region),
+    })
+}
+
+/// Whether `region` reads like `<two letters>-<words>-<number>`, e.g. `ap-southeast-7`
+fn is_region_shaped(region: &str) -> bool {
+    let parts: Vec<&str> = region.split('-').collect();
+    let is_word = |part: &&str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_lowercase());
+    match parts.as_slice() {
+        [country, words @ .., number] if !words.is_empty() => {
+            country.len() == 2
+                && is_word(country)
+                && words.iter().all(is_word)
+                && !number.is_empty()
+                && number.bytes().all(|b| b.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
+
+/// Ok for an output format the AWS CLI knows; otherwise an error listing them
+pub fn check_output(output: &str) -> Result<(), String> {
+    if OUTPUT_FORMATS.contains(&output) {
+        return Ok(());
+    }
+    let suggestion = closest(output, OUTPUT_FORMATS)
+        .map(|s| format!(" (did you mean '{}'?)", s))
+        .unwrap_or_default();
+    Err(format!(
+        "unknown output format '{}'{}; expected one of {}",
+        output,
+        suggestion,
+        OUTPUT_FORMATS.join(", ")
+    ))
+}
+
+/// The candidate fewest edits away from `value`, if any is close enough
+fn closest<'a>(value: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let value = value.trim().to_lowercase();
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&value, candidate), *candidate))
+        .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Insertions, deletions, substitutions and swaps of neighbouring letters needed to turn
+/// `a` into `b`, so `us-eats-1` is one edit from `us-east-1`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_region() {
+        assert!(check_region("eu-west-1").is_ok());
+        assert!(check_region("us-gov-west-1").is_ok());
+        assert_eq!(region_warning("eu-west-1"), None);
+        // Shaped like a region: accepted, with the suggestion as a warning
+        assert!(check_region("us-eats-1").is_ok());
+        assert_eq!(
+            region_warning("us-eats-1").unwrap(),
+            "Unknown AWS region 'us-eats-1'; did you mean 'us-east-1'?"
+        );
+        assert!(check_region("eu-polar-north-1").is_ok());
+        assert!(region_warning("eu-polar-north-1")
+            .unwrap()
+            .contains("new to awsom"));
+        assert!(check_region("euwest1").unwrap_err().contains("'eu-west-1'"));
+        assert!(check_region("us-east-").is_err());
+        assert!(check_region("US-EAST-1").is_err());
+        assert!(check_region("mars-1")
+            .unwrap_err()
+            .contains("regions look like"));
+        assert!(check_region("").is_err());
+    }
+
+    #[test]
+    fn test_partition() {
+        assert_eq!(Partition::from_region("eu-west-1"), Partition::Aws);
+        assert_eq!(Partition::from_region("us-gov-east-1").name(), "aws-us-gov");
+        assert_eq!(
+            Partition::from_region("cn-northwest-1").default_region(),
+            "cn-north-1"
+        );
+        assert_eq!(
+            Partition::AwsUsGov.regions().collect::<Vec<_>>(),
+            vec!["us-gov-east-1", "us-gov-west-1"]
+        );
+    }
+
+    #[test]
+    fn test_check_output() {
+        assert!(check_output("yaml-stream").is_ok());
+        let error = check_output("jsno").unwrap_err();
+        assert!(error.contains("(did you mean 'json'?)"), "{}", error);
+        assert!(check_output("csv").unwrap_err().contains("expected one of"));
+    }
+}
@@ -1,5 +1,5 @@
 // Session management module
-use crate::auth::AuthManager;
+use crate::auth::{AuthManager, LoginOptions};
 use crate::credentials::CredentialManager;
 use crate::error::Result;
 use crate::models::{AccountRole, ProfileSession, SsoInstance, SsoToken};
@@ -23,7 +23,8 @@ impl SessionManager {
         force: bool,
         headless: bool,
     ) -> Result<SsoToken> {
-        self.auth.login(instance, force, headless).await
+        let options = LoginOptions::new().force_refresh(force).headless(headless);
+        self.auth.login(instance, options).await
     }
 
     pub async fn activate_session(
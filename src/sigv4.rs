@@ -0,0 +1,249 @@
+// SigV4 signing for the AWS APIs awsom calls without an SDK crate
+//
+// Organizations, sso-admin, EKS, ECR and STS presigning only need a request or two each, so
+// they go through reqwest: `Client` signs each call with the headers (or query parameters)
+// computed here and turns AWS error responses into `SsoError`s the same way for all of them.
+use crate::error::{Result, SsoError};
+use crate::models::RoleCredentials;
+use crate::network;
+use crate::regions::Partition;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+use serde_json::Value;
+use std::time::SystemTime;
+
+/// Header or query parameter name/value pairs
+pub type Pairs = Vec<(String, String)>;
+
+/// What gets signed; `headers` are sent as given, so they must be sent with the request
+pub struct Request<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub headers: &'a [(&'a str, &'a str)],
+    pub body: &'a [u8],
+}
+
+impl<'a> Request<'a> {
+    pub fn get(url: &'a str) -> Self {
+        Self {
+            method: "GET",
+            url,
+            headers: &[],
+            body: &[],
+        }
+    }
+}
+
+/// The request's headers with the signature headers added, and the signature query
+/// parameters when `settings` asks for a presigned URL
+pub fn sign_request(
+    creds: &RoleCredentials,
+    region: &str,
+    service: &str,
+    request: &Request,
+    settings: SigningSettings,
+) -> Result<(Pairs, Pairs)> {
+    let identity = aws_credential_types::Credentials::new(
+        &creds.access_key_id,
+        &creds.secret_access_key,
+        Some(creds.session_token.clone()),
+        None,
+        "awsom",
+    )
+    .into();
+    let params = v4::SigningParams::builder()
+        .identity(&identity)
+        .region(region)
+        .name(service)
+        .time(SystemTime::now())
+        .settings(settings)
+        .build()
+        .map_err(|e| SsoError::AwsSdk(format!("Failed to sign {} request: {}", service, e)))?
+        .into();
+    let signable = SignableRequest::new(
+        request.method,
+        request.url,
+        request.headers.iter().copied(),
+        SignableBody::Bytes(request.body),
+    )
+    .and_then(|signable| sign(signable, &params))
+    .map_err(|e| SsoError::AwsSdk(format!("Failed to sign {} request: {}", service, e)))?;
+    let (instructions, _signature) = signable.into_parts();
+
+    let headers = request
+        .headers
+        .iter()
+        .copied()
+        .chain(instructions.headers())
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    let params = instructions
+        .params()
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    Ok((headers, params))
+}
+
+/// Endpoint of a regional service in `region`'s partition, e.g.
+/// `https://api.ecr.cn-north-1.amazonaws.com.cn`
+pub fn endpoint(prefix: &str, region: &str) -> String {
+    format!(
+        "https://{}.{}.{}",
+        prefix,
+        region,
+        Partition::from_region(region).dns_suffix()
+    )
+}
+
+/// Signed calls to one AWS service, answered with JSON
+pub struct Client {
+    http: reqwest::Client,
+    creds: RoleCredentials,
+    /// Region the requests are signed for
+    region: String,
+    /// Signing name, e.g. "ecr"; not always the endpoint prefix ("sso" for sso-admin)
+    service: &'static str,
+    /// Scheme and host, without a trailing `/`
+    endpoint: String,
+}
+
+impl Client {
+    pub fn new(
+        creds: &RoleCredentials,
+        service: &'static str,
+        region: &str,
+        endpoint: String,
+    ) -> Result<Self> {
+        let http = network::client_builder()?
+            .build()
+            .map_err(|e| SsoError::AwsSdk(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            http,
+            creds: creds.clone(),
+            region: region.to_string(),
+            service,
+            endpoint,
+        })
+    }
+
+    /// Call an operation of a JSON 1.1 API, e.g. `AWSOrganizationsV20161128.ListRoots`
+    pub async fn call_json(&self, target: &str, request: &Value) -> Result<Value> {
+        let operation = target.rsplit('.').next().unwrap_or(target);
+        let body = request.to_string();
+        let headers = [
+            ("content-type", "application/x-amz-json-1.1"),
+            ("x-amz-target", target),
+        ];
+        self.send(operation, "POST", "/", &headers, body.into_bytes())
+            .await
+    }
+
+    /// GET a path of a REST JSON API, e.g. `/clusters/prod`
+    pub async fn get(&self, operation: &str, path: &str) -> Result<Value> {
+        self.send(operation, "GET", path, &[], Vec::new()).await
+    }
+
+    async fn send(
+        &self,
+        operation: &str,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<Value> {
+        let url = format!("{}{}", self.endpoint, path);
+        let request = Request {
+            method,
+            url: &url,
+            headers,
+            body: &body,
+        };
+        let (headers, _params) = sign_request(
+            &self.creds,
+            &self.region,
+            self.service,
+            &request,
+            SigningSettings::default(),
+        )?;
+
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|e| SsoError::AwsSdk(format!("Invalid method for {}: {}", operation, e)))?;
+        let mut http_request = self.http.request(method, &url).body(body);
+        for (name, value) in &headers {
+            http_request = http_request.header(name.as_str(), value.as_str());
+        }
+        let response = network::send(http_request)
+            .await
+            .map_err(|e| SsoError::NetworkError(format!("Failed to call {}: {}", operation, e)))?;
+
+        let status = response.status();
+        let error_type = response
+            .headers()
+            .get("x-amzn-errortype")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let value: Value = response.json().await.map_err(|e| {
+            SsoError::AwsSdk(format!("Failed to read {} response: {}", operation, e))
+        })?;
+        if !status.is_success() {
+            return Err(SsoError::AwsSdk(format!(
+                "{} failed: {}",
+                operation,
+                error_message(error_type.as_deref(), &value)
+            )));
+        }
+        Ok(value)
+    }
+}
+
+/// "<code> <message>" of an AWS JSON error, whose code is in the x-amzn-errortype header
+/// or the `__type` field, e.g. `com.amazonaws.ecr#RepositoryNotFoundException` or
+/// `AccessDeniedException:http://internal.amazon.com/coral/`
+fn error_message(error_type: Option<&str>, body: &Value) -> String {
+    let code = error_type
+        .or_else(|| body["__type"].as_str())
+        .unwrap_or("UnknownError");
+    let code = code.split(':').next().unwrap_or(code);
+    let code = code.rsplit('#').next().unwrap_or(code);
+    let message = body["Message"]
+        .as_str()
+        .or_else(|| body["message"].as_str())
+        .unwrap_or_default();
+    format!("{} {}", code, message).trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_endpoint_and_error_message() {
+        assert_eq!(
+            endpoint("api.ecr", "cn-north-1"),
+            "https://api.ecr.cn-north-1.amazonaws.com.cn"
+        );
+        assert_eq!(
+            endpoint("eks", "us-gov-west-1"),
+            "https://eks.us-gov-west-1.amazonaws.com"
+        );
+
+        let body = json!({
+            "__type": "com.amazonaws.ecr#RepositoryNotFoundException",
+            "message": "not found"
+        });
+        assert_eq!(
+            error_message(None, &body),
+            "RepositoryNotFoundException not found"
+        );
+        assert_eq!(
+            error_message(
+                Some("ResourceNotFoundException:http://internal.amazon.com/coral/"),
+                &json!({ "message": "No cluster found" })
+            ),
+            "ResourceNotFoundException No cluster found"
+        );
+        assert_eq!(error_message(None, &json!({})), "UnknownError");
+    }
+}
@@ -1,5 +1,7 @@
 // SSO Configuration - reads from ~/.aws/config instead of custom config file
-use crate::aws_config::{read_sso_session, write_sso_session, SsoSession};
+use crate::aws_config::{
+    read_sso_session, write_sso_session, SsoSession, DEFAULT_REGISTRATION_SCOPES,
+};
 use crate::error::{Result, SsoError};
 
 /// Get SSO configuration from ~/.aws/config or environment variables
@@ -30,13 +32,9 @@ pub fn get_sso_config(
     }
 
     // Check if we have partial config from different sources
-    let start_url = start_url_arg.or(env_start_url).ok_or_else(|| {
-        SsoError::ConfigError(
-            "SSO start URL not configured. Set AWS_SSO_START_URL environment variable, \
-                 use --start-url flag, or configure [sso-session] in ~/.aws/config"
-                .to_string(),
-        )
-    })?;
+    let start_url = start_url_arg
+        .or(env_start_url)
+        .ok_or(SsoError::NotConfigured)?;
 
     let region = region_arg.or(env_region).ok_or_else(|| {
         SsoError::ConfigError(
@@ -112,7 +110,7 @@ pub fn prompt_sso_config() -> Result<(String, String, String)> {
         session_name: session_name.clone(),
         sso_start_url: start_url.clone(),
         sso_region: region.clone(),
-        sso_registration_scopes: "sso:account:access".to_string(),
+        sso_registration_scopes: DEFAULT_REGISTRATION_SCOPES.to_string(),
     };
 
     write_sso_session(&session)?;
@@ -0,0 +1,120 @@
+// Library handle on ~/.aws/config and ~/.aws/credentials
+//
+// A thin, stable front for `aws_config`: the CLI and TUI call its functions directly, library
+// users go through `AwsConfigStore` so the module can keep changing underneath.
+use crate::aws_config::{self, ProfileDetails, ProfileStatus, SsoProfile, SsoSession};
+use crate::error::Result;
+use crate::models::{AccountRole, RoleCredentials, SsoInstance};
+use std::path::PathBuf;
+
+/// The AWS config and credentials files awsom reads and writes
+///
+/// Profiles awsom writes go between its markers in the files, leaving the rest alone, and
+/// every write is atomic and locked against other awsom processes. Writes fail with
+/// [`SsoError::ReadOnly`](crate::SsoError::ReadOnly) when `[security] read_only` is set in
+/// awsom's config.toml.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AwsConfigStore {
+    _private: (),
+}
+
+impl AwsConfigStore {
+    /// The files the AWS CLI uses: AWS_CONFIG_FILE and AWS_SHARED_CREDENTIALS_FILE, else
+    /// ~/.aws/config and ~/.aws/credentials
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use these files instead, like `--config-file` and `--credentials-file`
+    ///
+    /// The paths apply to the whole process, and the first ones set stay in effect: a later
+    /// call can't point the store somewhere else.
+    pub fn with_files(config_file: Option<PathBuf>, credentials_file: Option<PathBuf>) -> Self {
+        aws_config::set_file_overrides(config_file, credentials_file);
+        Self::default()
+    }
+
+    /// Path of the config file (~/.aws/config)
+    pub fn config_file(&self) -> Result<PathBuf> {
+        aws_config::config_file_path()
+    }
+
+    /// Path of the credentials file (~/.aws/credentials)
+    pub fn credentials_file(&self) -> Result<PathBuf> {
+        aws_config::credentials_file_path()
+    }
+
+    /// Every `[sso-session]` in the config file
+    pub fn sso_sessions(&self) -> Result<Vec<SsoSession>> {
+        aws_config::read_all_sso_sessions()
+    }
+
+    /// The SSO instance of the named `[sso-session]`, or without a name the one awsom would
+    /// pick: the current session, else the only one configured
+    pub fn sso_instance(&self, session_name: Option<&str>) -> Result<SsoInstance> {
+        let (session_name, start_url, region) =
+            aws_config::resolve_named_sso_session(session_name, None, None)?;
+        Ok(SsoInstance {
+            start_url,
+            region,
+            session_name,
+        })
+    }
+
+    /// Add or update an `[sso-session]`
+    pub fn write_sso_session(&self, session: &SsoSession) -> Result<()> {
+        aws_config::write_sso_session(session)
+    }
+
+    /// Every profile in the credentials file, with the role and expiry awsom noted for it
+    pub fn profiles(&self) -> Result<Vec<ProfileStatus>> {
+        aws_config::list_profile_statuses()
+    }
+
+    /// A profile's settings in the config file, None if it has none
+    pub fn profile(&self, name: &str) -> Result<Option<ProfileDetails>> {
+        aws_config::get_profile_details(name)
+    }
+
+    /// Name of the profile holding credentials for a role, if any
+    pub fn profile_for_role(&self, role: &AccountRole) -> Result<Option<String>> {
+        aws_config::get_existing_profile_name(role)
+    }
+
+    /// Write a role's credentials to a profile, with its region and output in the config
+    /// file
+    ///
+    /// Other profiles holding live credentials for the same role get the new ones too.
+    pub fn write_credentials(
+        &self,
+        profile_name: &str,
+        creds: &RoleCredentials,
+        region: &str,
+        output_format: Option<&str>,
+        role: &AccountRole,
+    ) -> Result<()> {
+        aws_config::write_credentials_with_metadata(
+            profile_name,
+            creds,
+            region,
+            output_format,
+            Some(role),
+        )
+    }
+
+    /// Write a profile the AWS CLI and SDKs resolve through its `sso_session`, removing
+    /// session credentials awsom wrote for it
+    pub fn write_sso_profile(&self, profile: &SsoProfile) -> Result<()> {
+        aws_config::write_sso_native_profile(profile)
+    }
+
+    /// Replace a profile's credentials with invalid ones, keeping the profile
+    pub fn invalidate_profile(&self, name: &str) -> Result<()> {
+        aws_config::invalidate_profile(name)
+    }
+
+    /// Remove a profile from both files
+    pub fn delete_profile(&self, name: &str) -> Result<()> {
+        aws_config::delete_profile(name)
+    }
+}
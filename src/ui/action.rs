@@ -0,0 +1,127 @@
+// What a key press on the main screen asks the app to do
+//
+// Keys are translated here, and by the pane that has the focus, into an `Action` that
+// `App::dispatch` carries out, so key bindings can be checked without a terminal.
+use super::app::ActivePane;
+use super::widgets::accounts_pane::AccountsPane;
+use super::widgets::sessions_pane::SessionsPane;
+use crossterm::event::KeyCode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    ShowHelp,
+    /// Open the `:` command line and its command palette
+    CommandLine,
+    CycleTheme,
+    ShowMessages,
+    /// Move the focus to the other pane
+    SwitchPane,
+    /// List accounts and roles again, skipping the directory cache
+    RefreshAccounts,
+    /// Fetch new credentials for every active profile
+    RefreshAllProfiles,
+    SwitchWorkspace,
+    /// List the roles of every logged-in session, or only the selected session's
+    ToggleAllSessions,
+    /// Pause or resume the automatic reload of the Accounts pane
+    ToggleAutoRefresh,
+    /// The session's applications besides AWS accounts, to open in the browser
+    ShowApplications,
+
+    // Sessions pane
+    /// Log in to the selected session (palette only, like `Logout`)
+    Login,
+    Logout,
+    NextSession,
+    PreviousSession,
+    /// Log in to the selected session, or out of it
+    ToggleSession,
+    AddSession,
+    EditSession,
+    DeleteSession,
+
+    // Accounts pane
+    NextRole,
+    PreviousRole,
+    /// Fold or unfold the selected OU, or activate or deactivate the selected role
+    ToggleRole,
+    EditProfile,
+    MakeDefault,
+    OpenConsole,
+    ToggleFavorite,
+    CycleSort,
+    ReverseSort,
+    CycleGrouping,
+    EditTags,
+    ProfileSettings,
+    /// Add an EKS cluster to the kubeconfig with the selected role
+    UpdateKubeconfig,
+    /// Copy the selected role's credentials or profile name, per `[ui] copy_format`
+    CopyCredentials,
+    /// Permission set description and session duration of the selected role
+    ShowRoleDetails,
+    /// Mark or unmark the selected role for a bulk action
+    ToggleMark,
+    /// Menu of actions on every marked role
+    BulkActions,
+}
+
+/// The action for a key on the main screen: the focused pane's binding, else a global one
+pub fn main_action(key: KeyCode, pane: &ActivePane) -> Option<Action> {
+    let pane_action = match pane {
+        ActivePane::Sessions => SessionsPane::action(key),
+        ActivePane::Accounts => AccountsPane::action(key),
+    };
+    pane_action.or(match key {
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        KeyCode::Char('?') | KeyCode::F(1) => Some(Action::ShowHelp),
+        KeyCode::Char(':') => Some(Action::CommandLine),
+        KeyCode::Char('T') => Some(Action::CycleTheme),
+        KeyCode::Char('m') => Some(Action::ShowMessages),
+        KeyCode::Tab => Some(Action::SwitchPane),
+        KeyCode::Char('r') => Some(Action::RefreshAccounts),
+        KeyCode::Char('R') => Some(Action::RefreshAllProfiles),
+        KeyCode::Char('w') => Some(Action::SwitchWorkspace),
+        KeyCode::Char('A') => Some(Action::ToggleAllSessions),
+        KeyCode::Char('P') => Some(Action::ToggleAutoRefresh),
+        KeyCode::Char('p') => Some(Action::ShowApplications),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_depend_on_the_focused_pane() {
+        let sessions = ActivePane::Sessions;
+        let accounts = ActivePane::Accounts;
+
+        assert_eq!(
+            main_action(KeyCode::Enter, &sessions),
+            Some(Action::ToggleSession)
+        );
+        assert_eq!(
+            main_action(KeyCode::Enter, &accounts),
+            Some(Action::ToggleRole)
+        );
+        assert_eq!(
+            main_action(KeyCode::Char('d'), &sessions),
+            Some(Action::DeleteSession)
+        );
+        assert_eq!(
+            main_action(KeyCode::Char('d'), &accounts),
+            Some(Action::MakeDefault)
+        );
+
+        // Role-only keys do nothing in the Sessions pane; global ones work in both
+        assert_eq!(main_action(KeyCode::Char('c'), &sessions), None);
+        assert_eq!(main_action(KeyCode::Char('a'), &accounts), None);
+        for pane in [&sessions, &accounts] {
+            assert_eq!(main_action(KeyCode::Tab, pane), Some(Action::SwitchPane));
+            assert_eq!(main_action(KeyCode::Char('q'), pane), Some(Action::Quit));
+        }
+    }
+}
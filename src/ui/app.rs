@@ -1,145 +1,184 @@
 // Main TUI application
+use super::action::{main_action, Action};
+use super::event::{AppEvent, Discovery, DiscoveryEvent, LoginResult};
+use super::screens::StartTarget;
+use super::terminal::TerminalGuard;
+use super::theme::Palette;
+use super::widgets::accounts_pane::{
+    sort_accounts, sort_description, AccountRoleWithStatus, AccountsPane,
+};
+use super::widgets::sessions_pane::{SessionsPane, SsoSessionInfo};
+use crate::applications::Application;
 use crate::auth::{AuthManager, DeviceAuthorizationInfo};
-use crate::credentials::CredentialManager;
+use crate::config::{self, Config, CopyFormat, GroupBy, ProfileMode};
+use crate::console::SignIn;
+use crate::credentials::{directory, CredentialManager};
 use crate::error::{Result, SsoError};
-use crate::models::{AccountRole, SsoInstance, SsoToken};
+use crate::hooks::{self, ExpiringHook, HookContext, HookEvent};
+use crate::models::{AccountRole, RoleCredentials, SsoInstance, SsoToken};
+use crate::notify::ExpiryNotifier;
+use crate::orgs::OrgTree;
+use crate::output::{ExportedCredentials, OutputFormat};
+use crate::permission_sets::{self, PermissionSet};
+use crate::prefetch;
+use crate::profile_template::{self, ProfileNameContext};
 use crate::sso_config;
-use catppuccin::Flavor;
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    text::{Line, Span, Text},
-    widgets::{
-        Block, Borders, Cell, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
-        Table, TableState,
-    },
-    Frame, Terminal,
-};
+use crate::watch::FileWatcher;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{backend::CrosstermBackend, Frame, Terminal};
 use std::collections::HashMap;
 use std::io;
 use tokio::sync::mpsc;
 
-/// Message type for background login tasks
-enum LoginResult {
-    Success {
-        session_index: usize,
-        token: SsoToken,
-        instance: SsoInstance,
-        session_name: String,
-    },
-    Error {
-        message: String,
-    },
-    Cancelled,
-}
+/// Status bar messages kept for the messages panel
+const MESSAGE_HISTORY: usize = 100;
 
-/// Convert Catppuccin color to Ratatui Color
-fn catppuccin_color(color: catppuccin::Color) -> Color {
-    Color::Rgb(color.rgb.r, color.rgb.g, color.rgb.b)
+/// Palette of the theme in effect: the active workspace's, else `[ui] theme`
+fn config_palette(config: &Config) -> Palette {
+    Palette::new(config.theme(), &config.ui.custom_theme)
 }
 
-/// Wrapper for AccountRole with active status
-#[derive(Debug, Clone)]
-struct AccountRoleWithStatus {
-    account_role: AccountRole,
-    is_active: bool,
-    expiration: Option<chrono::DateTime<chrono::Utc>>,
-    is_default: bool,
-    profile_name: Option<String>,
+/// Permission sets of the session's Identity Center instance, read with `admin_role`
+async fn read_permission_sets(
+    instance: &SsoInstance,
+    token: &SsoToken,
+    admin_role: Result<AccountRole>,
+) -> Result<Vec<PermissionSet>> {
+    let creds = CredentialManager::new()?
+        .get_credentials(instance, token, &admin_role?)
+        .await?;
+    permission_sets::list(&creds, &instance.region).await
 }
 
-/// SSO Session with its status
-#[derive(Debug, Clone)]
-struct SsoSessionInfo {
-    session_name: String,
-    start_url: String,
-    region: String,
-    is_active: bool,
-    token_expiration: Option<chrono::DateTime<chrono::Utc>>,
-    instance: SsoInstance,
-    token: Option<SsoToken>,
+/// How long `role`'s sessions last, from the lifetime of its cached credentials; None
+/// unless the role has live ones
+fn cached_session_duration(instance: &SsoInstance, role: &AccountRole) -> Option<chrono::Duration> {
+    let seconds = CredentialManager::new()
+        .ok()?
+        .cached_lifetime(instance, role)?
+        .num_seconds();
+    Some(chrono::Duration::minutes((seconds + 30) / 60))
 }
 
 /// Active pane in two-pane layout
 #[derive(Debug, Clone, PartialEq)]
-enum ActivePane {
+pub enum ActivePane {
     Sessions,
     Accounts,
 }
 
 pub struct App {
     /// Whether the app should quit
-    should_quit: bool,
+    pub(crate) should_quit: bool,
     /// Current screen/state
-    state: AppState,
+    pub(crate) state: AppState,
     /// Active pane (Sessions or Accounts)
-    active_pane: ActivePane,
-    /// List of SSO sessions with their status
-    sso_sessions: Vec<SsoSessionInfo>,
-    /// SSO sessions table selection state
-    sessions_list_state: TableState,
-    /// List of accounts and roles with their active status (filtered by selected session)
-    accounts: Vec<AccountRoleWithStatus>,
-    /// Accounts table selection state
-    accounts_list_state: TableState,
+    pub(crate) active_pane: ActivePane,
+    /// SSO sessions pane
+    pub(crate) sessions: SessionsPane,
+    /// Accounts and roles of the selected session
+    pub(crate) accounts: AccountsPane,
+    /// Comma-separated tags being edited in the tag popup
+    pub(crate) tag_input: String,
+    /// Cluster name being typed in the EKS popup
+    pub(crate) cluster_input: String,
+    /// Text typed after `:`
+    pub(crate) command_input: String,
+    /// Command palette selection (index into `palette::search(&command_input)`)
+    pub(crate) palette_state: ratatui::widgets::ListState,
     /// Authentication manager
-    auth_manager: AuthManager,
+    pub(crate) auth_manager: AuthManager,
     /// Credential manager
-    credential_manager: CredentialManager,
+    pub(crate) credential_manager: CredentialManager,
     /// Current SSO instance (from selected session)
-    sso_instance: Option<SsoInstance>,
+    pub(crate) sso_instance: Option<SsoInstance>,
     /// Current SSO token (from selected session)
-    sso_token: Option<SsoToken>,
+    pub(crate) sso_token: Option<SsoToken>,
     /// Status message to display
-    status_message: Option<String>,
+    pub(crate) status_message: Option<String>,
+    /// Earlier status messages with the time they were shown, oldest first
+    pub(crate) messages: Vec<(chrono::DateTime<chrono::Local>, String)>,
+    /// Lines the messages panel is scrolled up from the bottom
+    pub(crate) messages_scroll: usize,
+    /// Credential expiry of every profile in ~/.aws/credentials, for the status bar countdown
+    pub(crate) profile_expirations: Vec<(String, chrono::DateTime<chrono::Utc>)>,
     /// Profile name input buffer
-    profile_input: String,
+    pub(crate) profile_input: String,
     /// Cursor position in profile input (0-based index)
-    profile_input_cursor: usize,
+    pub(crate) profile_input_cursor: usize,
     /// Account/role being configured
-    pending_role: Option<AccountRole>,
+    pub(crate) pending_role: Option<AccountRole>,
     /// Existing profile name for pending role (if found)
-    existing_profile_name: Option<String>,
+    pub(crate) existing_profile_name: Option<String>,
     /// Device authorization info during login
-    device_auth_info: Option<DeviceAuthorizationInfo>,
+    pub(crate) device_auth_info: Option<DeviceAuthorizationInfo>,
     /// Shared device authorization info from background task
-    device_auth_info_arc: Option<std::sync::Arc<std::sync::Mutex<Option<DeviceAuthorizationInfo>>>>,
+    pub(crate) device_auth_info_arc:
+        Option<std::sync::Arc<std::sync::Mutex<Option<DeviceAuthorizationInfo>>>>,
+    /// Result of copying the device login URL, shown on the loading screen
+    pub(crate) device_url_copy_status: Option<String>,
+    /// Background login task, aborted when the login is cancelled
+    pub(crate) login_task: Option<tokio::task::AbortHandle>,
     /// Last Ctrl+C press time for double-press detection
-    last_ctrl_c_time: Option<std::time::Instant>,
+    pub(crate) last_ctrl_c_time: Option<std::time::Instant>,
     /// Pending confirmation action (for modal dialog)
-    pending_confirm_action: Option<ConfirmAction>,
+    pub(crate) pending_confirm_action: Option<ConfirmAction>,
     /// SSO configuration input buffers
-    sso_start_url_input: String,
-    sso_region_input: String,
-    sso_session_name_input: String,
-    sso_input_cursor: usize,
+    pub(crate) sso_start_url_input: String,
+    pub(crate) sso_region_input: String,
+    pub(crate) sso_session_name_input: String,
+    pub(crate) sso_input_cursor: usize,
     /// Default configuration input buffers
-    default_region_input: String,
-    default_output_input: String,
-    default_input_cursor: usize,
+    pub(crate) default_region_input: String,
+    pub(crate) default_output_input: String,
+    pub(crate) default_input_cursor: usize,
+    /// Console region picker filter/free-text input
+    pub(crate) region_input: String,
+    /// Console region picker selection (index into `region_choices()`)
+    pub(crate) region_list_state: ratatui::widgets::ListState,
+    /// Region remembered for the role being opened, listed first in the picker
+    pub(crate) remembered_region: Option<String>,
+    /// Workspace switcher selection (index into `workspace_choices()`)
+    pub(crate) workspace_list_state: ratatui::widgets::ListState,
     /// New profile configuration input buffers
-    new_profile_name_input: String,
-    new_profile_region_input: String,
-    new_profile_output_input: String,
-    new_profile_input_cursor: usize,
+    pub(crate) new_profile_name_input: String,
+    pub(crate) new_profile_region_input: String,
+    pub(crate) new_profile_output_input: String,
+    pub(crate) new_profile_input_cursor: usize,
     /// Last automatic refresh time
-    last_auto_refresh: Option<std::time::Instant>,
-    /// Catppuccin theme flavor
-    theme: Flavor,
-    /// Channel for receiving login results from background tasks
-    login_rx: mpsc::UnboundedReceiver<LoginResult>,
-    /// Sender for login tasks (kept to create clones for background tasks)
-    login_tx: mpsc::UnboundedSender<LoginResult>,
+    pub(crate) last_auto_refresh: Option<std::time::Instant>,
+    /// Colors of the theme in effect
+    pub(crate) theme: Palette,
+    /// awsom settings (config.toml), including favorites
+    pub(crate) config: Config,
+    /// Desktop notifications for expiring tokens and credentials
+    pub(crate) notifier: ExpiryNotifier,
+    /// `on_token_expiring` runs for them, apart from the notifications
+    pub(crate) expiring_hook: ExpiringHook,
+    /// Results of logins and discoveries running in the background
+    pub(crate) events_rx: mpsc::UnboundedReceiver<AppEvent>,
+    /// Sender for background tasks (kept to create clones for them)
+    pub(crate) events_tx: mpsc::UnboundedSender<AppEvent>,
+    /// Account/role discovery running in the background
+    pub(crate) discovery: Option<Discovery>,
+    /// Id of the next discovery started
+    pub(crate) next_discovery_id: u64,
+    /// The last account listing couldn't reach AWS, so the rows are cached ones
+    pub(crate) offline: bool,
+    /// "account / role" of favorites the running prefetch couldn't write
+    pub(crate) prefetch_failed: Vec<String>,
+    /// Summary lines of the marked roles being started, None for those still fetching
+    pub(crate) bulk_start: Option<Vec<Option<String>>>,
+    /// Summary lines of the profiles being refreshed, None for those still fetching
+    pub(crate) refresh_all: Option<Vec<Option<String>>>,
+    /// Notices other tools and editors changing ~/.aws/config and ~/.aws/credentials
+    pub(crate) aws_files: FileWatcher,
+    /// Permission sets read for the details popup, by SSO start URL
+    pub(crate) permission_sets: HashMap<String, Vec<PermissionSet>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-enum AppState {
+pub enum AppState {
     /// Main screen showing account/role list
     Main,
     /// Help screen
@@ -158,23 +197,76 @@ enum AppState {
     NewProfileConfigInput { step: NewProfileConfigStep },
     /// Confirmation dialog
     ConfirmationDialog { title: String, message: Vec<String> },
+    /// Per-role results of refreshing all active profiles or of a bulk action
+    Summary { title: String, lines: Vec<String> },
+    /// Console region picker for a role
+    RegionPicker { account: AccountRole },
+    /// Tag editor for a role
+    TagInput { account: AccountRole },
+    /// EKS cluster name for a role's kubeconfig entry, in `region` unless given as
+    /// name@region
+    ClusterInput {
+        account: AccountRole,
+        region: String,
+    },
+    /// Workspace switcher
+    WorkspacePicker,
+    /// Name, region and output of an existing awsom-managed profile
+    ProfileSettings {
+        profile: String,
+        field: NewProfileConfigStep,
+    },
+    /// `:` command line in the status bar
+    CommandLine,
+    /// Status message history and the tail of the log file, read when opened
+    Messages { log: Vec<String> },
+    /// Actions on the roles marked with Space
+    BulkMenu,
+    /// The profile being saved is user-managed: import it, write `suffixed` instead, or
+    /// leave it alone
+    ProfileConflict {
+        account: AccountRole,
+        profile_name: String,
+        suffixed: String,
+    },
+    /// What a role grants; `details` is None while they're read
+    RoleDetails {
+        account: AccountRole,
+        details: Option<RoleDetails>,
+    },
+    /// Applications assigned besides AWS accounts; None while they're read
+    Applications {
+        applications: Option<std::result::Result<Vec<Application>, String>>,
+        selected: usize,
+    },
 }
 
+/// The details popup's view of a role
 #[derive(Debug, Clone, PartialEq)]
-enum SsoConfigStep {
+pub struct RoleDetails {
+    /// How long the role's credentials last
+    pub session_duration: Option<chrono::Duration>,
+    /// The permission set's description
+    pub description: Option<String>,
+    /// Why the permission set couldn't be read
+    pub permission_set_error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SsoConfigStep {
     StartUrl,
     Region,
     SessionName,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-enum DefaultsConfigStep {
+pub enum DefaultsConfigStep {
     Region,
     Output,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-enum NewProfileConfigStep {
+pub enum NewProfileConfigStep {
     ProfileName,
     Region,
     Output,
@@ -182,7 +274,7 @@ enum NewProfileConfigStep {
 
 /// Action to perform when user confirms in confirmation dialog
 #[derive(Debug, Clone)]
-enum ConfirmAction {
+pub enum ConfirmAction {
     /// Make a profile the default profile
     MakeProfileDefault {
         from_profile: String,
@@ -199,35 +291,68 @@ enum ConfirmAction {
         session_index: usize,
         session_name: String,
     },
+    /// Sign in to the console, with `[console] require_confirmation` set
+    OpenConsole {
+        account: AccountRole,
+        region: String,
+        copy_url: bool,
+    },
+    /// Open the console for each marked role in its region, with `[console]
+    /// require_confirmation` set
+    OpenConsoles { targets: Vec<(AccountRole, String)> },
 }
 
 impl App {
     pub fn new() -> Result<Self> {
+        Self::with_config(Config::load()?)
+    }
+
+    /// An app with no sessions or accounts loaded yet
+    pub(crate) fn with_config(config: Config) -> Result<Self> {
         let auth_manager = AuthManager::new()?;
         let credential_manager = CredentialManager::new()?;
 
-        // Create channel for background login tasks
-        let (login_tx, login_rx) = mpsc::unbounded_channel();
+        // Create channel for background tasks
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+
+        let notifier = ExpiryNotifier::new(config.ui.notify_before_minutes);
+        let expiring_hook = ExpiringHook::new(config.hooks.token_expiring_minutes);
+        let aws_files = FileWatcher::new(
+            [
+                crate::aws_config::config_file_path(),
+                crate::aws_config::credentials_file_path(),
+            ]
+            .into_iter()
+            .filter_map(|path| path.ok())
+            .collect(),
+        );
 
         Ok(Self {
             should_quit: false,
             state: AppState::Main,
             active_pane: ActivePane::Sessions,
-            sso_sessions: Vec::new(),
-            sessions_list_state: TableState::default(),
-            accounts: Vec::new(),
-            accounts_list_state: TableState::default(),
+            sessions: SessionsPane::default(),
+            accounts: AccountsPane::default(),
+            tag_input: String::new(),
+            cluster_input: String::new(),
+            command_input: String::new(),
+            palette_state: ratatui::widgets::ListState::default(),
             auth_manager,
             credential_manager,
             sso_instance: None,
             sso_token: None,
             status_message: None,
+            messages: Vec::new(),
+            messages_scroll: 0,
+            profile_expirations: Vec::new(),
             profile_input: String::new(),
             profile_input_cursor: 0,
             pending_role: None,
             existing_profile_name: None,
             device_auth_info: None,
             device_auth_info_arc: None,
+            device_url_copy_status: None,
+            login_task: None,
             last_ctrl_c_time: None,
             pending_confirm_action: None,
             sso_start_url_input: String::new(),
@@ -237,90 +362,163 @@ impl App {
             default_region_input: String::new(),
             default_output_input: String::new(),
             default_input_cursor: 0,
+            region_input: String::new(),
+            region_list_state: ratatui::widgets::ListState::default(),
+            workspace_list_state: ratatui::widgets::ListState::default(),
+            remembered_region: None,
             new_profile_name_input: String::new(),
             new_profile_region_input: String::new(),
             new_profile_output_input: String::new(),
             new_profile_input_cursor: 0,
             last_auto_refresh: None,
-            theme: catppuccin::PALETTE.mocha,
-            login_rx,
-            login_tx,
+            theme: config_palette(&config),
+            config,
+            notifier,
+            expiring_hook,
+            events_rx,
+            events_tx,
+            discovery: None,
+            next_discovery_id: 0,
+            offline: false,
+            prefetch_failed: Vec::new(),
+            bulk_start: None,
+            refresh_all: None,
+            aws_files,
+            permission_sets: HashMap::new(),
         })
     }
 
-    /// Get the currently selected SSO session
-    fn get_selected_session(&self) -> Option<&SsoSessionInfo> {
-        self.sessions_list_state
-            .selected()
-            .and_then(|idx| self.sso_sessions.get(idx))
-    }
-
     /// Get the currently selected SSO session's token
     fn get_current_token(&self) -> Option<&SsoToken> {
-        self.get_selected_session()
+        self.sessions
+            .selected()
             .and_then(|session| session.token.as_ref())
     }
 
     /// Get the currently selected SSO session's instance
     fn get_current_instance(&self) -> Option<&SsoInstance> {
-        self.get_selected_session().map(|session| &session.instance)
+        self.sessions.selected().map(|session| &session.instance)
     }
 
     pub async fn run(&mut self) -> Result<()> {
-        // Setup terminal
-        enable_raw_mode().map_err(SsoError::Io)?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen).map_err(SsoError::Io)?;
-        let backend = CrosstermBackend::new(stdout);
+        // Setup terminal; dropping the guard restores it
+        let _guard = TerminalGuard::enter()?;
+        let backend = CrosstermBackend::new(io::stdout());
         let mut terminal = Terminal::new(backend).map_err(SsoError::Io)?;
 
         // Load all SSO sessions
         self.load_all_sso_sessions().await;
+        self.load_profile_expirations();
 
         // Load accounts for selected session if active
+        // (the Accounts pane takes focus once the first rows arrive)
         if self.sso_token.is_some() {
-            let _ = self.load_accounts().await;
-
-            // If we successfully loaded accounts from an active session,
-            // default to Accounts pane for better UX
-            if !self.accounts.is_empty() {
-                self.active_pane = ActivePane::Accounts;
-                // Select first account
-                self.accounts_list_state.select(Some(0));
-            }
+            self.load_accounts();
         }
 
         // Main event loop
-        let result = self.run_event_loop(&mut terminal).await;
-
-        // Restore terminal
-        disable_raw_mode().map_err(SsoError::Io)?;
-        execute!(terminal.backend_mut(), LeaveAlternateScreen).map_err(SsoError::Io)?;
-        terminal.show_cursor().map_err(SsoError::Io)?;
-
-        result
+        self.run_event_loop(&mut terminal).await
     }
 
     async fn run_event_loop(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> Result<()> {
-        // Refresh interval: 1 minute
-        const AUTO_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
-
         loop {
+            self.record_status();
+            self.poll_device_auth();
             terminal.draw(|f| self.ui(f)).map_err(SsoError::Io)?;
 
-            // Check for login results from background tasks
-            while let Ok(result) = self.login_rx.try_recv() {
-                self.handle_login_result(result).await?;
+            // Apply results of background logins and discoveries
+            let mut discovery_events = Vec::new();
+            while let Ok(event) = self.events_rx.try_recv() {
+                match event {
+                    AppEvent::Login(result) => {
+                        let before = self.aws_files.stamps();
+                        self.handle_login_result(result).await?;
+                        self.aws_files.absorb(before);
+                    }
+                    AppEvent::Discovery { id, event } => discovery_events.push((id, event)),
+                    AppEvent::Prefetched {
+                        instance,
+                        role,
+                        result,
+                        done,
+                        total,
+                    } => {
+                        let before = self.aws_files.stamps();
+                        self.apply_prefetched(&instance, &role, result, done, total);
+                        self.aws_files.absorb(before);
+                    }
+                    AppEvent::ConsoleUrl {
+                        instance,
+                        account,
+                        region,
+                        copy_url,
+                        result,
+                    } => self.apply_console_url(&instance, &account, &region, copy_url, result),
+                    AppEvent::RoleDetails {
+                        instance,
+                        account,
+                        details,
+                        permission_sets,
+                    } => self.apply_role_details(instance, account, details, permission_sets),
+                    AppEvent::Applications { instance, result } => {
+                        // The popup may have been closed, and another session selected
+                        if self.sso_instance.as_ref() == Some(&instance) {
+                            self.apply_applications(result);
+                        }
+                    }
+                    AppEvent::BulkStarted {
+                        index,
+                        target,
+                        result,
+                    } => {
+                        let before = self.aws_files.stamps();
+                        self.apply_bulk_started(index, target, result);
+                        self.aws_files.absorb(before);
+                    }
+                    AppEvent::ProfileRefreshed {
+                        index,
+                        target,
+                        result,
+                    } => {
+                        let before = self.aws_files.stamps();
+                        self.apply_profile_refreshed(index, target, result);
+                        self.aws_files.absorb(before);
+                    }
+                    AppEvent::ExportedCredentials {
+                        account,
+                        format,
+                        result,
+                    } => self.apply_exported_credentials(&account, format, result),
+                    AppEvent::OrgTree {
+                        instance,
+                        group,
+                        result,
+                    } => self.apply_org_tree(&instance, group, result),
+                    AppEvent::Kubeconfig { account, result } => {
+                        self.apply_kubeconfig(&account, result)
+                    }
+                }
+            }
+            self.apply_discovery_events(discovery_events);
+
+            // Dialogs may hold session indices, so reload only on the main screen
+            if self.state == AppState::Main && self.aws_files.poll() {
+                self.reload_aws_files().await;
             }
 
-            // Check if we need to auto-refresh (every 1 minute)
+            self.check_expiry_notifications();
+
+            // Check if we need to auto-refresh (every `[ui] refresh_interval` seconds)
             let now = std::time::Instant::now();
-            let should_auto_refresh = match self.last_auto_refresh {
-                Some(last_refresh) => now.duration_since(last_refresh) >= AUTO_REFRESH_INTERVAL,
-                None => {
+            let should_auto_refresh = match (self.auto_refresh_interval(), self.last_auto_refresh) {
+                (None, _) => false,
+                (Some(interval), Some(last_refresh)) => {
+                    now.duration_since(last_refresh) >= interval
+                }
+                (Some(_), None) => {
                     // First time - set the timer but don't refresh yet
                     self.last_auto_refresh = Some(now);
                     false
@@ -329,14 +527,15 @@ impl App {
 
             if should_auto_refresh
                 && self.state == AppState::Main
-                && self.sso_token.is_some()
-                && !self.accounts.is_empty()
+                && !self.discovery_targets().is_empty()
+                && !self.accounts.items.is_empty()
             {
-                tracing::debug!("Auto-refreshing account list (1 minute interval)");
+                tracing::debug!(
+                    "Auto-refreshing account list ({}s interval)",
+                    self.config.ui.refresh_interval
+                );
                 self.last_auto_refresh = Some(now);
-                if let Err(e) = self.load_accounts().await {
-                    tracing::warn!("Auto-refresh failed: {}", e);
-                }
+                self.load_accounts();
             }
 
             if event::poll(std::time::Duration::from_millis(250)).map_err(SsoError::Io)? {
@@ -348,8 +547,18 @@ impl App {
                             && key.code == KeyCode::Char('c')
                         {
                             self.handle_ctrl_c();
+                        } else if key.modifiers.contains(KeyModifiers::CONTROL)
+                            && key.code == KeyCode::Char('p')
+                        {
+                            if self.state == AppState::Main {
+                                let before = self.aws_files.stamps();
+                                self.dispatch(Action::CommandLine).await?;
+                                self.aws_files.absorb(before);
+                            }
                         } else {
+                            let before = self.aws_files.stamps();
                             self.handle_key(key.code).await?;
+                            self.aws_files.absorb(before);
                         }
                     }
                 }
@@ -362,6 +571,138 @@ impl App {
         Ok(())
     }
 
+    /// Keep a new status bar message for the messages panel; role discovery progress is
+    /// left out
+    fn record_status(&mut self) {
+        let Some(message) = &self.status_message else {
+            return;
+        };
+        let is_progress = self
+            .discovery
+            .as_ref()
+            .is_some_and(|discovery| &discovery.status == message);
+        if is_progress || self.messages.last().map(|(_, last)| last) == Some(message) {
+            return;
+        }
+
+        self.messages.push((chrono::Local::now(), message.clone()));
+        if self.messages.len() > MESSAGE_HISTORY {
+            self.messages.remove(0);
+        }
+    }
+
+    /// Notify about SSO sessions and role credentials that are about to expire, and run the
+    /// `on_token_expiring` hook for them, each at its own threshold
+    fn check_expiry_notifications(&mut self) {
+        for session in &self.sessions.items {
+            if let (true, Some(expiration)) = (session.is_active, session.token_expiration) {
+                let label = format!("SSO session {}", session.session_name);
+                self.notifier.check(&label, &expiration);
+                self.expiring_hook.check(
+                    &label,
+                    &HookContext {
+                        session: Some(&session.session_name),
+                        start_url: Some(&session.start_url),
+                        region: Some(&session.region),
+                        expires_at: Some(expiration),
+                        ..HookContext::default()
+                    },
+                );
+            }
+        }
+
+        for account in &self.accounts.items {
+            if let (true, Some(expiration)) = (account.is_active, account.expiration) {
+                let label = format!(
+                    "{} / {}",
+                    account.account_role.account_name, account.account_role.role_name
+                );
+                self.notifier.check(&label, &expiration);
+                self.expiring_hook.check(
+                    &label,
+                    &HookContext {
+                        session: account.session_name.as_deref(),
+                        account_id: Some(&account.account_role.account_id),
+                        role_name: Some(&account.account_role.role_name),
+                        profile: account.profile_name.as_deref(),
+                        expires_at: Some(expiration),
+                        ..HookContext::default()
+                    },
+                );
+            }
+        }
+    }
+
+    /// Fetch credentials for the session's favorites to prefetch in the background
+    fn start_prefetch(&self, instance: SsoInstance, access_token: String) {
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            let roles = match prefetch::roles(&instance, &access_token).await {
+                Ok(roles) => roles,
+                Err(e) => {
+                    tracing::warn!("Failed to list favorite roles to prefetch: {}", e);
+                    return;
+                }
+            };
+            let total = roles.len();
+            let result = prefetch::fetch(&instance, &access_token, roles, |role, result, done| {
+                let _ = events.send(AppEvent::Prefetched {
+                    instance: instance.clone(),
+                    role,
+                    result,
+                    done,
+                    total,
+                });
+            })
+            .await;
+            if let Err(e) = result {
+                tracing::warn!("Prefetching favorite roles stopped: {}", e);
+            }
+        });
+    }
+
+    /// Write a prefetched role's credentials and show the prefetch's progress
+    fn apply_prefetched(
+        &mut self,
+        instance: &SsoInstance,
+        role: &AccountRole,
+        result: Result<RoleCredentials>,
+        done: usize,
+        total: usize,
+    ) {
+        let written = result.and_then(|creds| prefetch::write_profile(instance, role, &creds));
+        if let Err(e) = written {
+            tracing::warn!(
+                "Failed to prefetch {} / {}: {}",
+                role.account_name,
+                role.role_name,
+                e
+            );
+            self.prefetch_failed
+                .push(format!("{} / {}", role.account_name, role.role_name));
+        }
+        self.load_profile_expirations();
+        self.restatus_rows();
+
+        let session = instance.session_name.as_deref().unwrap_or("SSO");
+        self.status_message = Some(if done < total {
+            format!("Prefetching favorites of {}: {}/{}", session, done, total)
+        } else {
+            let failed = std::mem::take(&mut self.prefetch_failed);
+            if failed.is_empty() {
+                format!("✓ Prefetched {} favorite profile(s) of {}", total, session)
+            } else {
+                format!(
+                    "Prefetched {} of {} favorite profile(s) of {}; failed: {}",
+                    total - failed.len(),
+                    total,
+                    session,
+                    failed.join(", ")
+                )
+            }
+        });
+    }
+
     /// Handle login result from background task
     async fn handle_login_result(&mut self, result: LoginResult) -> Result<()> {
         match result {
@@ -371,40 +712,37 @@ impl App {
                 instance,
                 session_name,
             } => {
-                self.device_auth_info = None;
-                self.device_auth_info_arc = None;
+                self.end_login();
 
                 // Update session in list
-                if let Some(session_mut) = self.sso_sessions.get_mut(session_index) {
+                if let Some(session_mut) = self.sessions.items.get_mut(session_index) {
                     session_mut.is_active = true;
-                    session_mut.token = Some(token.clone());
+                    session_mut.token = Some((*token).clone());
                     session_mut.token_expiration = Some(token.expires_at);
                 }
 
+                self.start_prefetch(instance.clone(), token.access_token.clone());
+
                 // Update current session
                 self.sso_instance = Some(instance);
-                self.sso_token = Some(token);
+                self.sso_token = Some(*token);
                 self.state = AppState::Main;
                 self.status_message = Some(format!("✓ Logged in to {}", session_name));
 
-                // Load accounts for this session
-                self.load_accounts().await?;
-
-                // Switch to Accounts pane for better UX
-                if !self.accounts.is_empty() {
-                    self.active_pane = ActivePane::Accounts;
-                    self.accounts_list_state.select(Some(0));
+                // Load accounts for this session, replacing any from the previous one; the
+                // all-sessions view keeps the other sessions' rows until the new list is in
+                if !self.config.ui.all_sessions {
+                    self.accounts.items.clear();
                 }
+                self.load_accounts();
             }
             LoginResult::Error { message } => {
-                self.device_auth_info = None;
-                self.device_auth_info_arc = None;
+                self.end_login();
                 self.state = AppState::Main;
                 self.status_message = Some(format!("Login failed: {}", message));
             }
             LoginResult::Cancelled => {
-                self.device_auth_info = None;
-                self.device_auth_info_arc = None;
+                self.end_login();
                 self.state = AppState::Main;
                 self.status_message = Some("Login cancelled".to_string());
             }
@@ -412,6 +750,15 @@ impl App {
         Ok(())
     }
 
+    /// Forget the device code of a finished login, stopping its task if still polling
+    fn end_login(&mut self) {
+        if let Some(task) = self.login_task.take() {
+            task.abort();
+        }
+        self.device_auth_info = None;
+        self.device_auth_info_arc = None;
+    }
+
     async fn handle_key(&mut self, key: KeyCode) -> Result<()> {
         match self.state {
             AppState::Main => self.handle_main_key(key).await?,
@@ -422,20 +769,21 @@ impl App {
             AppState::Loading => {
                 // Allow cancelling login with q or Esc
                 match key {
+                    KeyCode::Char('c') if self.device_auth_info.is_some() => {
+                        self.copy_device_url();
+                    }
                     KeyCode::Char('q') | KeyCode::Esc => {
                         // Cancel the login attempt
                         tracing::info!("User cancelled login");
-                        self.device_auth_info = None;
-                        self.device_auth_info_arc = None;
+                        self.end_login();
                         self.state = AppState::Main;
                         self.status_message = Some("Login cancelled".to_string());
-                        // Note: The background task will still complete, but we ignore its result
                     }
                     _ => {}
                 }
             }
-            AppState::Error(_) => {
-                // Any key clears error and returns to main
+            AppState::Error(_) | AppState::Summary { .. } | AppState::RoleDetails { .. } => {
+                // Any key closes the error, summary or details and returns to main
                 self.state = AppState::Main;
             }
             AppState::ProfileInput => {
@@ -453,6 +801,28 @@ impl App {
             AppState::ConfirmationDialog { .. } => {
                 self.handle_confirmation_dialog_key(key).await?;
             }
+            AppState::RegionPicker { .. } => {
+                self.handle_region_picker_key(key).await?;
+            }
+            AppState::TagInput { .. } => {
+                self.handle_tag_input_key(key);
+            }
+            AppState::ClusterInput { .. } => {
+                self.handle_cluster_input_key(key);
+            }
+            AppState::WorkspacePicker => {
+                self.handle_workspace_picker_key(key).await;
+            }
+            AppState::ProfileSettings { .. } => {
+                self.handle_profile_settings_key(key).await;
+            }
+            AppState::CommandLine => {
+                self.handle_command_line_key(key).await;
+            }
+            AppState::Messages { .. } => self.handle_messages_key(key),
+            AppState::BulkMenu => self.handle_bulk_menu_key(key),
+            AppState::ProfileConflict { .. } => self.handle_profile_conflict_key(key).await?,
+            AppState::Applications { .. } => self.handle_applications_key(key),
         }
         Ok(())
     }
@@ -476,14 +846,37 @@ impl App {
     }
 
     async fn handle_main_key(&mut self, key: KeyCode) -> Result<()> {
-        match key {
-            KeyCode::Char('q') | KeyCode::Esc => {
+        // Esc stops a running account load before it quits
+        if key == KeyCode::Esc && self.discovery.is_some() {
+            self.cancel_discovery();
+            return Ok(());
+        }
+        match main_action(key, &self.active_pane) {
+            Some(action) => self.dispatch(action).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Carry out what a key on the main screen or the command palette asked for
+    pub(crate) async fn dispatch(&mut self, action: Action) -> Result<()> {
+        if self.active_pane == ActivePane::Accounts {
+            self.follow_selected_role();
+        }
+        match action {
+            Action::Quit => {
                 self.should_quit = true;
             }
-            KeyCode::Char('?') | KeyCode::F(1) => {
+            Action::ShowHelp => {
                 self.state = AppState::Help;
             }
-            KeyCode::Tab => {
+            Action::CommandLine => {
+                self.command_input.clear();
+                self.palette_state.select(Some(0));
+                self.state = AppState::CommandLine;
+            }
+            Action::CycleTheme => self.cycle_theme(),
+            Action::ShowMessages => self.open_messages(),
+            Action::SwitchPane => {
                 // Switch between Sessions and Accounts panes
                 self.active_pane = match self.active_pane {
                     ActivePane::Sessions => ActivePane::Accounts,
@@ -497,10 +890,10 @@ impl App {
                     }
                 ));
             }
-            KeyCode::Char('r') => {
-                // Refresh account list
-                if self.sso_token.is_some() {
-                    self.load_accounts().await?;
+            Action::RefreshAccounts => {
+                // Refresh account list, skipping the directory cache
+                if !self.discovery_targets().is_empty() {
+                    self.refresh_accounts();
                     // Reset auto-refresh timer after manual refresh
                     self.last_auto_refresh = Some(std::time::Instant::now());
                 } else {
@@ -510,152 +903,405 @@ impl App {
                     );
                 }
             }
-            KeyCode::Down | KeyCode::Char('j') => match self.active_pane {
-                ActivePane::Sessions => self.next_session(),
-                ActivePane::Accounts => self.next_item(),
-            },
-            KeyCode::Up | KeyCode::Char('k') => match self.active_pane {
-                ActivePane::Sessions => self.previous_session(),
-                ActivePane::Accounts => self.previous_item(),
-            },
-            KeyCode::Enter => {
-                match self.active_pane {
-                    ActivePane::Sessions => {
-                        // Start or stop SSO session
-                        self.toggle_sso_session().await?;
-                    }
-                    ActivePane::Accounts => {
-                        // Start or stop role session
-                        self.toggle_role_session().await?;
-                    }
-                }
-            }
-            KeyCode::Char('a') => {
-                if self.active_pane == ActivePane::Sessions {
-                    self.add_sso_session().await?;
-                }
-            }
-            KeyCode::Char('e') => {
-                match self.active_pane {
-                    ActivePane::Sessions => {
-                        self.edit_sso_session().await?;
-                    }
-                    ActivePane::Accounts => {
-                        // Edit profile (name, region, output)
-                        self.edit_profile().await?;
-                    }
-                }
-            }
-            KeyCode::Char('d') => {
-                match self.active_pane {
-                    ActivePane::Sessions => {
-                        self.delete_sso_session().await?;
-                    }
-                    ActivePane::Accounts => {
-                        // Set as default profile
-                        self.set_as_default().await?;
+            Action::RefreshAllProfiles => self.refresh_all_profiles(),
+            Action::SwitchWorkspace => self.open_workspace_picker(),
+            Action::ToggleAllSessions => self.toggle_all_sessions(),
+            Action::ToggleAutoRefresh => self.toggle_auto_refresh(),
+            Action::Login | Action::Logout => {
+                let login = action == Action::Login;
+                match self.sessions.selected() {
+                    None => self.status_message = Some("No session selected".to_string()),
+                    Some(session) if session.is_active == login => {
+                        self.status_message = Some(format!(
+                            "Already logged {} {}",
+                            if login { "in to" } else { "out of" },
+                            session.session_name
+                        ));
                     }
-                }
-            }
-            KeyCode::Char('c') => {
-                if self.active_pane == ActivePane::Accounts {
-                    // Open AWS Console in browser
-                    self.open_console().await?;
-                }
-            }
-            _ => {}
+                    Some(_) => self.toggle_sso_session().await?,
+                }
+            }
+            Action::NextSession => {
+                self.sessions.select_next();
+                self.session_selected();
+            }
+            Action::PreviousSession => {
+                self.sessions.select_previous();
+                self.session_selected();
+            }
+            // Start or stop SSO session
+            Action::ToggleSession => self.toggle_sso_session().await?,
+            Action::AddSession => self.add_sso_session().await?,
+            Action::EditSession => self.edit_sso_session().await?,
+            Action::DeleteSession => self.delete_sso_session().await?,
+            Action::NextRole => {
+                self.accounts.select_next(&self.config);
+                self.follow_selected_role();
+            }
+            Action::PreviousRole => {
+                self.accounts.select_previous(&self.config);
+                self.follow_selected_role();
+            }
+            Action::ToggleRole => {
+                // Fold/unfold an OU, or start or stop role session
+                if !self.accounts.toggle_selected_unit(&self.config) {
+                    self.toggle_role_session().await?;
+                }
+            }
+            // Edit profile (name, region, output)
+            Action::EditProfile => self.edit_profile().await?,
+            // Set as default profile
+            Action::MakeDefault => self.set_as_default().await?,
+            // Pick a region, then open the AWS Console in the browser
+            Action::OpenConsole => self.open_region_picker(),
+            Action::ToggleFavorite => self.toggle_favorite(),
+            Action::CycleSort => {
+                self.config.ui.sort_by = self.config.ui.sort_by.next();
+                self.apply_sort();
+            }
+            Action::ReverseSort => {
+                self.config.ui.sort_descending = !self.config.ui.sort_descending;
+                self.apply_sort();
+            }
+            Action::CycleGrouping => self.cycle_grouping(),
+            Action::EditTags => self.open_tag_editor(),
+            Action::ProfileSettings => self.open_profile_settings(),
+            Action::UpdateKubeconfig => self.open_cluster_input(),
+            Action::ShowRoleDetails => self.show_role_details(),
+            Action::ToggleMark => {
+                if !self.accounts.toggle_mark(&self.config) {
+                    self.status_message = Some("No role selected".to_string());
+                }
+            }
+            Action::BulkActions => self.open_bulk_menu(),
+            Action::ShowApplications => self.show_applications(),
+            Action::CopyCredentials => self.copy_credentials(),
         }
         Ok(())
     }
 
-    fn next_item(&mut self) {
-        if self.accounts.is_empty() {
+    /// Make another workspace active and reload the sessions and accounts it scopes
+    pub(crate) async fn switch_workspace(&mut self, name: Option<String>) {
+        if let Err(e) = config::select_workspace(name.clone()) {
+            self.status_message = Some(e.to_string());
             return;
         }
-        let i = match self.accounts_list_state.selected() {
-            Some(i) => {
-                if i >= self.accounts.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+        self.config.active_workspace = name;
+        self.theme = config_palette(&self.config);
+
+        self.discovery = None;
+        self.sessions.items.clear();
+        self.sessions.state.select(None);
+        self.sso_token = None;
+        self.sso_instance = None;
+        self.accounts.items.clear();
+        self.accounts.state.select(None);
+        self.active_pane = ActivePane::Sessions;
+
+        self.load_all_sso_sessions().await;
+        if self.sso_token.is_some() {
+            self.load_accounts();
+        } else {
+            self.status_message = Some(match &self.config.active_workspace {
+                Some(name) => format!("Switched to workspace '{}'", name),
+                None => "Showing all sessions and accounts".to_string(),
+            });
+        }
+    }
+
+    /// Pin or unpin the selected role, keeping it selected after re-sorting
+    fn toggle_favorite(&mut self) {
+        let Some(index) = self.accounts.selected_index(&self.config) else {
+            self.status_message = Some("No role selected".to_string());
+            return;
+        };
+        let Some(account_with_status) = self.accounts.items.get_mut(index) else {
+            return;
+        };
+
+        let account = account_with_status.account_role.clone();
+        let is_favorite = self
+            .config
+            .toggle_favorite(&account.account_id, &account.role_name);
+        account_with_status.is_favorite = is_favorite;
+
+        if let Err(e) = self.config.save() {
+            self.status_message = Some(format!("Error saving favorites: {}", e));
+            return;
+        }
+
+        sort_accounts(&mut self.accounts.items, &self.config.ui);
+        let new_index = self
+            .accounts
+            .items
+            .iter()
+            .position(|a| a.account_role == account)
+            .unwrap_or(0);
+        self.accounts.select(new_index, &self.config);
+
+        self.status_message = Some(format!(
+            "{} {} / {}",
+            if is_favorite {
+                "★ Pinned"
+            } else {
+                "Unpinned"
+            },
+            account.account_name,
+            account.role_name
+        ));
+    }
+
+    /// Re-sort the accounts pane after a sort change, keeping the selection and saving the preference
+    fn apply_sort(&mut self) {
+        self.resort_accounts();
+
+        self.status_message = Some(match self.config.save() {
+            Ok(()) => format!("Sorted by {}", sort_description(&self.config.ui)),
+            Err(e) => format!("Error saving sort preference: {}", e),
+        });
+    }
+
+    /// Switch to the next theme and save it as the default (or the workspace's theme)
+    fn cycle_theme(&mut self) {
+        let theme = self.config.theme().next();
+        self.config.set_theme(theme);
+        self.theme = config_palette(&self.config);
+
+        self.status_message = Some(match self.config.save() {
+            Ok(()) => format!("Theme: {}", theme.name()),
+            Err(e) => format!("Error saving theme: {}", e),
+        });
+    }
+
+    /// Switch the accounts pane to the next grouping, skipping OUs without `[organizations]`
+    fn cycle_grouping(&mut self) {
+        let organizations = &self.config.organizations;
+        let has_org = organizations.account_id.is_some() && organizations.role_name.is_some();
+        let group_by = match self.config.ui.group_by {
+            GroupBy::None if has_org => GroupBy::Ou,
+            GroupBy::None | GroupBy::Ou => GroupBy::Tag,
+            GroupBy::Tag => GroupBy::None,
         };
-        self.accounts_list_state.select(Some(i));
+        self.set_grouping(group_by);
     }
 
-    fn previous_item(&mut self) {
-        if self.accounts.is_empty() {
+    /// Group the accounts pane, keeping the selected role selected and saving the choice
+    ///
+    /// Grouping by OU waits for the organization to be read, if it wasn't yet.
+    pub(crate) fn set_grouping(&mut self, group_by: GroupBy) {
+        let selected = self.accounts.selected_index(&self.config);
+        if group_by == GroupBy::Ou && self.accounts.org_tree.is_none() {
+            self.status_message = Some(match self.load_org_tree(true) {
+                Ok(()) => "Loading organization...".to_string(),
+                Err(e) => format!("Failed to load organization: {}", e),
+            });
             return;
         }
-        let i = match self.accounts_list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.accounts.len() - 1
-                } else {
-                    i - 1
-                }
+
+        self.config.ui.group_by = group_by;
+        match selected {
+            Some(index) => self.accounts.select(index, &self.config),
+            None => self.accounts.state.select(Some(0)),
+        }
+
+        let org_accounts = self
+            .accounts
+            .org_tree
+            .as_ref()
+            .map_or(0, OrgTree::account_count);
+        self.status_message = Some(match (self.config.save(), group_by) {
+            (Ok(()), GroupBy::Ou) => format!(
+                "Grouped by OU, {} accounts in the organization (Enter folds an OU)",
+                org_accounts
+            ),
+            (Ok(()), GroupBy::Tag) => {
+                "Grouped by tag (t edits the selected role's tags)".to_string()
             }
-            None => 0,
+            (Ok(()), GroupBy::None) => "Flat account list".to_string(),
+            (Err(e), _) => format!("Error saving view preference: {}", e),
+        });
+    }
+
+    /// Read the OU structure in the background with the role configured under
+    /// [organizations]; with `group`, the accounts pane is grouped by OU once it's read
+    fn load_org_tree(&mut self, group: bool) -> Result<()> {
+        let (Some(token), Some(instance)) = (self.sso_token.clone(), self.sso_instance.clone())
+        else {
+            return Err(SsoError::NoSessionFound);
         };
-        self.accounts_list_state.select(Some(i));
+        let role = self.organizations_role()?;
+
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = async {
+                let creds = CredentialManager::new()?
+                    .get_credentials(&instance, &token, &role)
+                    .await?;
+                OrgTree::fetch(&creds, &instance.region).await
+            }
+            .await;
+            let _ = events.send(AppEvent::OrgTree {
+                instance,
+                group,
+                result,
+            });
+        });
+        Ok(())
     }
 
-    fn next_session(&mut self) {
-        if self.sso_sessions.is_empty() {
+    /// Show the organization read by `load_org_tree`, unless another session was picked
+    /// in the meantime
+    pub(crate) fn apply_org_tree(
+        &mut self,
+        instance: &SsoInstance,
+        group: bool,
+        result: Result<OrgTree>,
+    ) {
+        if self.sso_instance.as_ref() != Some(instance) {
             return;
         }
-        let i = match self.sessions_list_state.selected() {
-            Some(i) => {
-                if i >= self.sso_sessions.len() - 1 {
-                    0
-                } else {
-                    i + 1
+        match result {
+            Ok(tree) if group => {
+                self.accounts.org_tree = Some(tree);
+                self.set_grouping(GroupBy::Ou);
+            }
+            Ok(tree) => {
+                let selected = self.accounts.selected_index(&self.config);
+                self.accounts.org_tree = Some(tree);
+                if let Some(index) = selected {
+                    self.accounts.select(index, &self.config);
                 }
             }
-            None => 0,
+            Err(e) if group => {
+                self.status_message = Some(format!("Failed to load organization: {}", e));
+            }
+            Err(e) => self.status_message = Some(format!("OU tree unavailable: {}", e)),
+        }
+    }
+
+    /// The management or delegated administrator role configured under [organizations]
+    fn organizations_role(&self) -> Result<AccountRole> {
+        let organizations = &self.config.organizations;
+        let (Some(account_id), Some(role_name)) = (
+            organizations.account_id.clone(),
+            organizations.role_name.clone(),
+        ) else {
+            return Err(SsoError::InvalidConfig(
+                "set account_id and role_name under [organizations] in config.toml".to_string(),
+            ));
         };
-        self.sessions_list_state.select(Some(i));
-        // Update current session
-        self.update_current_session_from_selection();
 
-        // Show which session is now selected
-        if let Some(session) = self.sso_sessions.get(i) {
-            if session.is_active {
-                self.status_message = Some(format!(
-                    "Selected session '{}' - press 'r' in Accounts pane to load accounts",
-                    session.session_name
-                ));
-            } else {
-                self.status_message = Some(format!(
-                    "Selected session '{}' (inactive - press Enter to login)",
-                    session.session_name
-                ));
+        let account_name = self
+            .accounts
+            .items
+            .iter()
+            .find(|a| a.account_role.account_id == account_id)
+            .map_or_else(
+                || account_id.clone(),
+                |a| a.account_role.account_name.clone(),
+            );
+        Ok(AccountRole {
+            account_id,
+            account_name,
+            role_name,
+        })
+    }
+
+    /// Switch between the selected session's roles and those of every logged-in session
+    fn toggle_all_sessions(&mut self) {
+        let all_sessions = !self.config.ui.all_sessions;
+        self.config.ui.all_sessions = all_sessions;
+        if !all_sessions {
+            // The selected session's rows are already there; the reload only adds state
+            let current = self.sessions.selected().map(|s| s.session_name.clone());
+            self.accounts
+                .items
+                .retain(|account| account.session_name == current);
+            self.accounts.state.select(Some(0));
+        }
+        self.load_accounts();
+
+        self.status_message = Some(match (self.config.save(), all_sessions) {
+            (Err(e), _) => format!("Error saving view preference: {}", e),
+            (Ok(()), true) => {
+                let active = self.sessions.items.iter().filter(|s| s.is_active).count();
+                format!(
+                    "Listing the roles of all {} logged-in session(s) (A for the selected one)",
+                    active
+                )
             }
+            (Ok(()), false) => "Listing the selected session's roles".to_string(),
+        });
+    }
+
+    /// How often the Accounts pane reloads itself, None when `[ui] auto_refresh` is off or
+    /// `refresh_interval` is 0
+    pub(crate) fn auto_refresh_interval(&self) -> Option<std::time::Duration> {
+        let ui = &self.config.ui;
+        (ui.auto_refresh && ui.refresh_interval > 0)
+            .then(|| std::time::Duration::from_secs(ui.refresh_interval))
+    }
+
+    /// Time left until the next automatic reload
+    pub(crate) fn next_auto_refresh(&self) -> Option<std::time::Duration> {
+        let interval = self.auto_refresh_interval()?;
+        let elapsed = self
+            .last_auto_refresh
+            .map_or(std::time::Duration::ZERO, |last| last.elapsed());
+        Some(interval.saturating_sub(elapsed))
+    }
+
+    /// Pause or resume the automatic reload, saving the choice as `[ui] auto_refresh`
+    fn toggle_auto_refresh(&mut self) {
+        if self.config.ui.refresh_interval == 0 {
+            self.status_message = Some(
+                "Auto refresh is disabled by [ui] refresh_interval = 0 in config.toml".to_string(),
+            );
+            return;
         }
+        self.config.ui.auto_refresh = !self.config.ui.auto_refresh;
+        self.last_auto_refresh = Some(std::time::Instant::now());
+        self.status_message = Some(match (self.config.save(), self.config.ui.auto_refresh) {
+            (Err(e), _) => format!("Error saving auto refresh preference: {}", e),
+            (Ok(()), true) => format!(
+                "Auto refresh every {}s (P to pause)",
+                self.config.ui.refresh_interval
+            ),
+            (Ok(()), false) => "Auto refresh paused (P to resume)".to_string(),
+        });
     }
 
-    fn previous_session(&mut self) {
-        if self.sso_sessions.is_empty() {
+    /// In the all-sessions view, make the selected role's session the current one, so
+    /// its credentials are fetched with that session's token
+    fn follow_selected_role(&mut self) {
+        if !self.config.ui.all_sessions {
             return;
         }
-        let i = match self.sessions_list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.sso_sessions.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
+        let Some(session_name) = self
+            .accounts
+            .selected_index(&self.config)
+            .and_then(|index| self.accounts.items[index].session_name.clone())
+        else {
+            return;
         };
-        self.sessions_list_state.select(Some(i));
+        let index = self
+            .sessions
+            .items
+            .iter()
+            .position(|session| session.session_name == session_name);
+        if index.is_some() && index != self.sessions.state.selected() {
+            self.sessions.state.select(index);
+            self.update_current_session_from_selection();
+        }
+    }
+
+    /// Make the newly selected session current and say whether it's logged in
+    fn session_selected(&mut self) {
         // Update current session
         self.update_current_session_from_selection();
 
         // Show which session is now selected
-        if let Some(session) = self.sso_sessions.get(i) {
+        if let Some(session) = self.sessions.selected() {
             if session.is_active {
                 self.status_message = Some(format!(
                     "Selected session '{}' - press 'r' in Accounts pane to load accounts",
@@ -672,9 +1318,9 @@ impl App {
 
     /// Update current sso_instance and sso_token based on selected session
     fn update_current_session_from_selection(&mut self) {
-        let selected_idx = self.sessions_list_state.selected();
+        let selected_idx = self.sessions.state.selected();
         if let Some(idx) = selected_idx {
-            if let Some(session) = self.sso_sessions.get(idx) {
+            if let Some(session) = self.sessions.items.get(idx) {
                 self.sso_instance = Some(session.instance.clone());
                 self.sso_token = session.token.clone();
                 return;
@@ -686,8 +1332,8 @@ impl App {
 
     /// Toggle SSO session: if active, logout; if inactive, login
     async fn toggle_sso_session(&mut self) -> Result<()> {
-        if let Some(index) = self.sessions_list_state.selected() {
-            if let Some(session) = self.sso_sessions.get(index).cloned() {
+        if let Some(index) = self.sessions.state.selected() {
+            if let Some(session) = self.sessions.items.get(index).cloned() {
                 if session.is_active {
                     // Session is active, logout
                     self.logout_session(index).await?;
@@ -704,27 +1350,29 @@ impl App {
 
     /// Login to a specific SSO session by index
     async fn login_session(&mut self, index: usize) -> Result<()> {
-        if let Some(session) = self.sso_sessions.get(index).cloned() {
+        if let Some(session) = self.sessions.items.get(index).cloned() {
             self.status_message = Some(format!("Logging in to {}...", session.session_name));
             self.state = AppState::Loading;
 
             let instance = session.instance.clone();
             let session_name = session.session_name.clone();
-            let tx = self.login_tx.clone();
+            let tx = self.events_tx.clone();
+
+            self.device_url_copy_status = None;
 
             // Clone device_auth_info Arc for sharing with background task
             let device_auth_info = std::sync::Arc::new(std::sync::Mutex::new(None));
             let device_auth_info_clone = device_auth_info.clone();
 
             // Spawn background task for login
-            tokio::spawn(async move {
+            let task = tokio::spawn(async move {
                 // Create new AuthManager for this task
                 let auth_manager = match AuthManager::new() {
                     Ok(am) => am,
                     Err(e) => {
-                        let _ = tx.send(LoginResult::Error {
+                        let _ = tx.send(AppEvent::Login(LoginResult::Error {
                             message: format!("Failed to create auth manager: {}", e),
-                        });
+                        }));
                         return;
                     }
                 };
@@ -737,19 +1385,8 @@ impl App {
                             *guard = Some(auth_info.clone());
                         }
 
-                        // Only try to open browser if not in headless environment
-                        if !crate::env::is_headless_environment() {
-                            let url_to_open = auth_info
-                                .verification_uri_complete
-                                .as_ref()
-                                .unwrap_or(&auth_info.verification_uri);
-
-                            if let Err(e) = webbrowser::open(url_to_open) {
-                                tracing::warn!("Could not open browser automatically: {}", e);
-                            }
-                        } else {
-                            tracing::info!("Headless environment detected - skipping browser launch, showing URL in TUI");
-                        }
+                        // Skipped when headless - the URL is shown in the TUI instead
+                        auth_info.open_browser();
 
                         Ok(())
                     })
@@ -759,7 +1396,7 @@ impl App {
                 let message = match result {
                     Ok(token) => LoginResult::Success {
                         session_index: index,
-                        token,
+                        token: Box::new(token),
                         instance,
                         session_name,
                     },
@@ -768,18 +1405,19 @@ impl App {
                     },
                 };
 
-                let _ = tx.send(message);
+                let _ = tx.send(AppEvent::Login(message));
             });
 
             // Store the device_auth_info Arc so we can poll it during rendering
             self.device_auth_info_arc = Some(device_auth_info);
+            self.login_task = Some(task.abort_handle());
         }
         Ok(())
     }
 
     /// Logout from a specific SSO session by index
-    async fn logout_session(&mut self, index: usize) -> Result<()> {
-        if let Some(session) = self.sso_sessions.get_mut(index) {
+    pub(crate) async fn logout_session(&mut self, index: usize) -> Result<()> {
+        if let Some(session) = self.sessions.items.get_mut(index) {
             self.status_message = Some(format!("Logging out from {}...", session.session_name));
 
             // Remove cached token
@@ -797,11 +1435,22 @@ impl App {
                 if current_instance.start_url == session.start_url {
                     self.sso_instance = None;
                     self.sso_token = None;
-                    self.accounts.clear();
-                    self.accounts_list_state.select(None);
+                    if !self.config.ui.all_sessions {
+                        self.accounts.items.clear();
+                        self.accounts.state.select(None);
+                    }
                 }
             }
 
+            // The all-sessions view keeps the roles of the sessions still logged in
+            if self.config.ui.all_sessions {
+                let session_name = Some(session.session_name.clone());
+                self.accounts
+                    .items
+                    .retain(|account| account.session_name != session_name);
+                self.accounts.state.select(Some(0));
+            }
+
             self.status_message = Some(format!("✓ Logged out from {}", session.session_name));
         }
         Ok(())
@@ -825,8 +1474,8 @@ impl App {
 
     /// Edit the selected SSO session
     async fn edit_sso_session(&mut self) -> Result<()> {
-        if let Some(index) = self.sessions_list_state.selected() {
-            if let Some(session) = self.sso_sessions.get(index) {
+        if let Some(index) = self.sessions.state.selected() {
+            if let Some(session) = self.sessions.items.get(index) {
                 // Pre-fill input buffers with current session values
                 self.sso_start_url_input = session.start_url.clone();
                 self.sso_region_input = session.region.clone();
@@ -847,8 +1496,8 @@ impl App {
 
     /// Delete the selected SSO session (requires confirmation via modal dialog)
     async fn delete_sso_session(&mut self) -> Result<()> {
-        if let Some(index) = self.sessions_list_state.selected() {
-            if let Some(session) = self.sso_sessions.get(index) {
+        if let Some(index) = self.sessions.state.selected() {
+            if let Some(session) = self.sessions.items.get(index) {
                 let message = vec![
                     format!(
                         "Are you sure you want to delete SSO session '{}'?",
@@ -884,39 +1533,45 @@ impl App {
 
     /// Toggle role session: if active, delete it; if inactive, create it
     async fn toggle_role_session(&mut self) -> Result<()> {
-        if let Some(index) = self.accounts_list_state.selected() {
-            if let Some(account_with_status) = self.accounts.get(index).cloned() {
+        let sso_native = self.config.profile_defaults.profile_mode == ProfileMode::SsoNative;
+        if let Some(index) = self.accounts.selected_index(&self.config) {
+            if let Some(account_with_status) = self.accounts.items.get(index).cloned() {
                 let account = account_with_status.account_role;
 
-                if account_with_status.is_active {
-                    // Role is active, stop it (delete credentials)
-                    if let Some(existing_profile) =
-                        crate::aws_config::get_existing_profile_name(&account)?
-                    {
-                        self.status_message = Some(format!(
-                            "Stopping session for profile '{}'...",
-                            existing_profile
-                        ));
-                        if let Err(e) = crate::aws_config::invalidate_profile(&existing_profile) {
+                if account_with_status.is_active && sso_native {
+                    self.remove_sso_native_profile(account_with_status.profile_name.as_deref());
+                } else if account_with_status.is_active {
+                    // Role is active, stop it (delete credentials) in every profile bound to it
+                    let profiles = crate::aws_config::get_existing_profile_names(&account)?;
+                    if !profiles.is_empty() {
+                        let names = profiles
+                            .iter()
+                            .map(|name| format!("'{}'", name))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        let stopped = profiles
+                            .iter()
+                            .try_for_each(|profile| crate::aws_config::invalidate_profile(profile));
+                        if let Err(e) = stopped {
                             self.status_message = Some(format!("Error stopping session: {}", e));
                         } else {
+                            let noun = if profiles.len() == 1 {
+                                "profile"
+                            } else {
+                                "profiles"
+                            };
                             self.status_message = Some(format!(
-                                "✓ Stopped session for profile '{}' (profile preserved)",
-                                existing_profile
+                                "✓ Stopped session for {} {} ({} preserved)",
+                                noun, names, noun
                             ));
                             // Reload accounts to update indicators
-                            if let Err(e) = self.load_accounts().await {
-                                tracing::warn!(
-                                    "Failed to reload accounts after stopping session: {}",
-                                    e
-                                );
-                            }
+                            self.load_accounts();
                         }
                     }
                 } else {
                     // Role is inactive, start it (get credentials)
                     // Get current session name for unified profile lookup
-                    let session_name = if let Some(selected_session) = self.get_selected_session() {
+                    let session_name = if let Some(selected_session) = self.sessions.selected() {
                         selected_session.session_name.clone()
                     } else {
                         self.status_message = Some("No SSO session selected".to_string());
@@ -941,20 +1596,7 @@ impl App {
                         match crate::aws_config::read_awsom_defaults()? {
                             Some(defaults) => {
                                 // Defaults exist, show new profile config dialog
-                                let default_profile_name = format!(
-                                    "{}_{}",
-                                    account
-                                        .account_name
-                                        .replace(" ", "-")
-                                        .replace("_", "-")
-                                        .to_lowercase(),
-                                    account
-                                        .role_name
-                                        .replace(" ", "-")
-                                        .replace("_", "-")
-                                        .to_lowercase()
-                                );
-                                self.new_profile_name_input = default_profile_name;
+                                self.new_profile_name_input = self.default_profile_name(&account);
                                 self.new_profile_region_input = defaults.region.clone();
                                 self.new_profile_output_input = defaults.output.clone();
                                 self.new_profile_input_cursor = self.new_profile_name_input.len();
@@ -984,11 +1626,34 @@ impl App {
         Ok(())
     }
 
-    /// Set the selected role's profile as the default profile
-    async fn set_as_default(&mut self) -> Result<()> {
-        if let Some(index) = self.accounts_list_state.selected() {
-            if let Some(account_with_status) = self.accounts.get(index).cloned() {
-                let account = account_with_status.account_role;
+    /// Stop an sso-native role by removing its profile, which is all there is of it
+    fn remove_sso_native_profile(&mut self, profile_name: Option<&str>) {
+        let Some(profile_name) = profile_name else {
+            return;
+        };
+        self.status_message = Some(
+            match crate::aws_config::is_profile_in_awsom_section(profile_name) {
+                Ok(true) => match crate::aws_config::delete_profile(profile_name) {
+                    Ok(()) => {
+                        self.load_accounts();
+                        format!("✓ Removed SSO profile '{}'", profile_name)
+                    }
+                    Err(e) => format!("Error removing profile: {}", e),
+                },
+                Ok(false) => format!(
+                    "Profile '{}' is user-managed; awsom leaves it alone",
+                    profile_name
+                ),
+                Err(e) => format!("Error removing profile: {}", e),
+            },
+        );
+    }
+
+    /// Set the selected role's profile as the default profile
+    async fn set_as_default(&mut self) -> Result<()> {
+        if let Some(index) = self.accounts.selected_index(&self.config) {
+            if let Some(account_with_status) = self.accounts.items.get(index).cloned() {
+                let account = account_with_status.account_role;
 
                 // Check if there's an existing profile for this role
                 if let Some(existing_profile) =
@@ -1082,12 +1747,7 @@ impl App {
                                             existing_profile
                                         ));
                                         // Reload accounts to update indicators
-                                        if let Err(e) = self.load_accounts().await {
-                                            tracing::warn!(
-                                                "Failed to reload accounts after setting default: {}",
-                                                e
-                                            );
-                                        }
+                                        self.load_accounts();
                                     }
                                     Err(e) => {
                                         self.status_message =
@@ -1111,12 +1771,12 @@ impl App {
 
     /// Open profile editor for selected role (name, region, output)
     async fn edit_profile(&mut self) -> Result<()> {
-        if let Some(index) = self.accounts_list_state.selected() {
-            if let Some(account_with_status) = self.accounts.get(index).cloned() {
+        if let Some(index) = self.accounts.selected_index(&self.config) {
+            if let Some(account_with_status) = self.accounts.items.get(index).cloned() {
                 let account = account_with_status.account_role;
 
                 // Get current session name for unified profile lookup
-                let session_name = if let Some(selected_session) = self.get_selected_session() {
+                let session_name = if let Some(selected_session) = self.sessions.selected() {
                     selected_session.session_name.clone()
                 } else {
                     self.status_message = Some("No SSO session selected".to_string());
@@ -1139,20 +1799,7 @@ impl App {
                     self.existing_profile_name = Some(profile_info.name);
                 } else {
                     // Create new profile - use defaults
-                    let default_profile_name = format!(
-                        "{}_{}",
-                        account
-                            .account_name
-                            .replace(" ", "-")
-                            .replace("_", "-")
-                            .to_lowercase(),
-                        account
-                            .role_name
-                            .replace(" ", "-")
-                            .replace("_", "-")
-                            .to_lowercase()
-                    );
-                    self.new_profile_name_input = default_profile_name;
+                    self.new_profile_name_input = self.default_profile_name(&account);
 
                     // Try to get defaults from awsom-defaults
                     match crate::aws_config::read_awsom_defaults()? {
@@ -1181,757 +1828,22 @@ impl App {
         Ok(())
     }
 
-    async fn handle_profile_input_key(&mut self, key: KeyCode) -> Result<()> {
-        match key {
-            KeyCode::Enter => {
-                // Save profile with entered name
-                if let Some(account) = self.pending_role.take() {
-                    self.state = AppState::Loading;
-                    self.save_profile_credentials(&account, &self.profile_input.clone())
-                        .await?;
-                }
-            }
-            KeyCode::Esc => {
-                // Cancel
-                self.state = AppState::Main;
-                self.profile_input.clear();
-                self.profile_input_cursor = 0;
-                self.pending_role = None;
-                self.existing_profile_name = None;
-            }
-            KeyCode::Left => {
-                // Move cursor left
-                if self.profile_input_cursor > 0 {
-                    self.profile_input_cursor -= 1;
-                }
-            }
-            KeyCode::Right => {
-                // Move cursor right
-                if self.profile_input_cursor < self.profile_input.len() {
-                    self.profile_input_cursor += 1;
-                }
-            }
-            KeyCode::Home => {
-                // Move cursor to beginning
-                self.profile_input_cursor = 0;
-            }
-            KeyCode::End => {
-                // Move cursor to end
-                self.profile_input_cursor = self.profile_input.len();
-            }
-            KeyCode::Backspace => {
-                // Delete character before cursor
-                if self.profile_input_cursor > 0 {
-                    self.profile_input.remove(self.profile_input_cursor - 1);
-                    self.profile_input_cursor -= 1;
-                }
-            }
-            KeyCode::Delete => {
-                // Delete character at cursor
-                if self.profile_input_cursor < self.profile_input.len() {
-                    self.profile_input.remove(self.profile_input_cursor);
-                }
-            }
-            KeyCode::Char(c) => {
-                // Only allow alphanumeric, dash, and underscore
-                if c.is_alphanumeric() || c == '-' || c == '_' {
-                    self.profile_input.insert(self.profile_input_cursor, c);
-                    self.profile_input_cursor += 1;
-                }
-            }
-            _ => {}
-        }
-        Ok(())
-    }
-
-    async fn handle_sso_config_input_key(&mut self, key: KeyCode) -> Result<()> {
-        let current_step = if let AppState::SsoConfigInput { step } = &self.state {
-            step.clone()
-        } else {
-            return Ok(());
-        };
-
-        match key {
-            KeyCode::Enter => {
-                // Move to next step or save configuration
-                match current_step {
-                    SsoConfigStep::StartUrl => {
-                        if self.sso_start_url_input.trim().is_empty() {
-                            self.status_message = Some("SSO Start URL is required".to_string());
-                        } else {
-                            self.state = AppState::SsoConfigInput {
-                                step: SsoConfigStep::Region,
-                            };
-                            self.sso_input_cursor = self.sso_region_input.len();
-                        }
-                    }
-                    SsoConfigStep::Region => {
-                        if self.sso_region_input.trim().is_empty() {
-                            self.status_message = Some("SSO Region is required".to_string());
-                        } else {
-                            self.state = AppState::SsoConfigInput {
-                                step: SsoConfigStep::SessionName,
-                            };
-                            self.sso_input_cursor = self.sso_session_name_input.len();
-                        }
-                    }
-                    SsoConfigStep::SessionName => {
-                        // Save configuration to ~/.aws/config
-                        let session_name = if self.sso_session_name_input.trim().is_empty() {
-                            "default-sso".to_string()
-                        } else {
-                            self.sso_session_name_input.trim().to_string()
-                        };
-
-                        let session = crate::aws_config::SsoSession {
-                            session_name: session_name.clone(),
-                            sso_start_url: self.sso_start_url_input.trim().to_string(),
-                            sso_region: self.sso_region_input.trim().to_string(),
-                            sso_registration_scopes: "sso:account:access".to_string(),
-                        };
-
-                        match crate::aws_config::write_sso_session(&session) {
-                            Ok(()) => {
-                                self.status_message = Some(format!(
-                                    "✓ SSO session '{}' saved to ~/.aws/config",
-                                    session_name
-                                ));
-                                self.state = AppState::Main;
-
-                                // Clear input buffers
-                                self.sso_start_url_input.clear();
-                                self.sso_region_input.clear();
-                                self.sso_session_name_input = "default-sso".to_string();
-                                self.sso_input_cursor = 0;
-
-                                // Reload sessions list to show the new session
-                                self.load_all_sso_sessions().await;
-                            }
-                            Err(e) => {
-                                self.status_message =
-                                    Some(format!("Error saving configuration: {}", e));
-                            }
-                        }
-                    }
-                }
-            }
-            KeyCode::Esc => {
-                // Cancel configuration
-                self.state = AppState::Main;
-                self.sso_start_url_input.clear();
-                self.sso_region_input.clear();
-                self.sso_session_name_input = "default-sso".to_string();
-                self.sso_input_cursor = 0;
-                self.status_message = Some("Configuration cancelled".to_string());
-            }
-            KeyCode::Left => {
-                if self.sso_input_cursor > 0 {
-                    self.sso_input_cursor -= 1;
-                }
-            }
-            KeyCode::Right => {
-                let max_len = match current_step {
-                    SsoConfigStep::StartUrl => self.sso_start_url_input.len(),
-                    SsoConfigStep::Region => self.sso_region_input.len(),
-                    SsoConfigStep::SessionName => self.sso_session_name_input.len(),
-                };
-                if self.sso_input_cursor < max_len {
-                    self.sso_input_cursor += 1;
-                }
-            }
-            KeyCode::Home => {
-                self.sso_input_cursor = 0;
-            }
-            KeyCode::End => {
-                self.sso_input_cursor = match current_step {
-                    SsoConfigStep::StartUrl => self.sso_start_url_input.len(),
-                    SsoConfigStep::Region => self.sso_region_input.len(),
-                    SsoConfigStep::SessionName => self.sso_session_name_input.len(),
-                };
-            }
-            KeyCode::Backspace => {
-                if self.sso_input_cursor > 0 {
-                    match current_step {
-                        SsoConfigStep::StartUrl => {
-                            self.sso_start_url_input.remove(self.sso_input_cursor - 1);
-                        }
-                        SsoConfigStep::Region => {
-                            self.sso_region_input.remove(self.sso_input_cursor - 1);
-                        }
-                        SsoConfigStep::SessionName => {
-                            self.sso_session_name_input
-                                .remove(self.sso_input_cursor - 1);
-                        }
-                    }
-                    self.sso_input_cursor -= 1;
-                }
-            }
-            KeyCode::Delete => match current_step {
-                SsoConfigStep::StartUrl => {
-                    if self.sso_input_cursor < self.sso_start_url_input.len() {
-                        self.sso_start_url_input.remove(self.sso_input_cursor);
-                    }
-                }
-                SsoConfigStep::Region => {
-                    if self.sso_input_cursor < self.sso_region_input.len() {
-                        self.sso_region_input.remove(self.sso_input_cursor);
-                    }
-                }
-                SsoConfigStep::SessionName => {
-                    if self.sso_input_cursor < self.sso_session_name_input.len() {
-                        self.sso_session_name_input.remove(self.sso_input_cursor);
-                    }
-                }
-            },
-            KeyCode::Char(c) => {
-                // Allow reasonable characters for URLs and region names
-                match current_step {
-                    SsoConfigStep::StartUrl => {
-                        self.sso_start_url_input.insert(self.sso_input_cursor, c);
-                        self.sso_input_cursor += 1;
-                    }
-                    SsoConfigStep::Region => {
-                        // Only allow lowercase letters, digits, and hyphens for region
-                        if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' {
-                            self.sso_region_input.insert(self.sso_input_cursor, c);
-                            self.sso_input_cursor += 1;
-                        }
-                    }
-                    SsoConfigStep::SessionName => {
-                        // Allow alphanumeric, dash, and underscore
-                        if c.is_alphanumeric() || c == '-' || c == '_' {
-                            self.sso_session_name_input.insert(self.sso_input_cursor, c);
-                            self.sso_input_cursor += 1;
-                        }
-                    }
-                }
-            }
-            _ => {}
-        }
-        Ok(())
-    }
-
-    async fn handle_defaults_config_input_key(&mut self, key: KeyCode) -> Result<()> {
-        let current_step = if let AppState::DefaultsConfigInput { step } = &self.state {
-            step.clone()
-        } else {
-            return Ok(());
-        };
-
-        match key {
-            KeyCode::Enter => {
-                match current_step {
-                    DefaultsConfigStep::Region => {
-                        if self.default_region_input.trim().is_empty() {
-                            self.status_message = Some("Region is required".to_string());
-                        } else {
-                            self.state = AppState::DefaultsConfigInput {
-                                step: DefaultsConfigStep::Output,
-                            };
-                            self.default_input_cursor = self.default_output_input.len();
-                        }
-                    }
-                    DefaultsConfigStep::Output => {
-                        // Save default configuration to [profile awsom-defaults]
-                        let config = crate::aws_config::DefaultConfig {
-                            region: self.default_region_input.trim().to_string(),
-                            output: self.default_output_input.trim().to_string(),
-                        };
-
-                        match crate::aws_config::write_awsom_defaults(&config) {
-                            Ok(()) => {
-                                self.status_message = Some(
-                                    "✓ Default settings saved to [profile awsom-defaults]"
-                                        .to_string(),
-                                );
-
-                                // Now proceed to new profile configuration
-                                if let Some(account) = &self.pending_role {
-                                    let default_profile_name = format!(
-                                        "{}_{}",
-                                        account
-                                            .account_name
-                                            .replace(" ", "-")
-                                            .replace("_", "-")
-                                            .to_lowercase(),
-                                        account
-                                            .role_name
-                                            .replace(" ", "-")
-                                            .replace("_", "-")
-                                            .to_lowercase()
-                                    );
-                                    self.new_profile_name_input = default_profile_name;
-                                    self.new_profile_region_input = config.region.clone();
-                                    self.new_profile_output_input = config.output.clone();
-                                    self.new_profile_input_cursor =
-                                        self.new_profile_name_input.len();
-                                    self.state = AppState::NewProfileConfigInput {
-                                        step: NewProfileConfigStep::ProfileName,
-                                    };
-                                }
-
-                                // Clear input buffers
-                                self.default_region_input = String::new();
-                                self.default_output_input = String::new();
-                                self.default_input_cursor = 0;
-                            }
-                            Err(e) => {
-                                self.status_message = Some(format!("Error saving defaults: {}", e));
-                            }
-                        }
-                    }
-                }
-            }
-            KeyCode::Esc => {
-                self.state = AppState::Main;
-                self.default_region_input = String::new();
-                self.default_output_input = String::new();
-                self.default_input_cursor = 0;
-                self.pending_role = None;
-                self.status_message = Some("Configuration cancelled".to_string());
-            }
-            KeyCode::Left => {
-                if self.default_input_cursor > 0 {
-                    self.default_input_cursor -= 1;
-                }
-            }
-            KeyCode::Right => {
-                let max_len = match current_step {
-                    DefaultsConfigStep::Region => self.default_region_input.len(),
-                    DefaultsConfigStep::Output => self.default_output_input.len(),
-                };
-                if self.default_input_cursor < max_len {
-                    self.default_input_cursor += 1;
-                }
-            }
-            KeyCode::Home => {
-                self.default_input_cursor = 0;
-            }
-            KeyCode::End => {
-                self.default_input_cursor = match current_step {
-                    DefaultsConfigStep::Region => self.default_region_input.len(),
-                    DefaultsConfigStep::Output => self.default_output_input.len(),
-                };
-            }
-            KeyCode::Backspace => {
-                if self.default_input_cursor > 0 {
-                    match current_step {
-                        DefaultsConfigStep::Region => {
-                            self.default_region_input
-                                .remove(self.default_input_cursor - 1);
-                        }
-                        DefaultsConfigStep::Output => {
-                            self.default_output_input
-                                .remove(self.default_input_cursor - 1);
-                        }
-                    }
-                    self.default_input_cursor -= 1;
-                }
-            }
-            KeyCode::Delete => match current_step {
-                DefaultsConfigStep::Region => {
-                    if self.default_input_cursor < self.default_region_input.len() {
-                        self.default_region_input.remove(self.default_input_cursor);
-                    }
-                }
-                DefaultsConfigStep::Output => {
-                    if self.default_input_cursor < self.default_output_input.len() {
-                        self.default_output_input.remove(self.default_input_cursor);
-                    }
-                }
-            },
-            KeyCode::Char(c) => match current_step {
-                DefaultsConfigStep::Region => {
-                    if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' {
-                        self.default_region_input
-                            .insert(self.default_input_cursor, c);
-                        self.default_input_cursor += 1;
-                    }
-                }
-                DefaultsConfigStep::Output => {
-                    if c.is_alphanumeric() || c == '-' {
-                        self.default_output_input
-                            .insert(self.default_input_cursor, c);
-                        self.default_input_cursor += 1;
-                    }
-                }
-            },
-            _ => {}
-        }
-        Ok(())
-    }
-
-    async fn handle_new_profile_config_input_key(&mut self, key: KeyCode) -> Result<()> {
-        let current_step = if let AppState::NewProfileConfigInput { step } = &self.state {
-            step.clone()
-        } else {
-            return Ok(());
-        };
-
-        match key {
-            KeyCode::Enter => {
-                match current_step {
-                    NewProfileConfigStep::ProfileName => {
-                        if self.new_profile_name_input.trim().is_empty() {
-                            self.status_message = Some("Profile name is required".to_string());
-                        } else {
-                            self.state = AppState::NewProfileConfigInput {
-                                step: NewProfileConfigStep::Region,
-                            };
-                            self.new_profile_input_cursor = self.new_profile_region_input.len();
-                        }
-                    }
-                    NewProfileConfigStep::Region => {
-                        if self.new_profile_region_input.trim().is_empty() {
-                            self.status_message = Some("Region is required".to_string());
-                        } else {
-                            self.state = AppState::NewProfileConfigInput {
-                                step: NewProfileConfigStep::Output,
-                            };
-                            self.new_profile_input_cursor = self.new_profile_output_input.len();
-                        }
-                    }
-                    NewProfileConfigStep::Output => {
-                        // Save the profile with credentials
-                        if let Some(account) = self.pending_role.take() {
-                            let profile_name = self.new_profile_name_input.trim().to_string();
-                            self.state = AppState::Loading;
-                            self.save_profile_credentials(&account, &profile_name)
-                                .await?;
-
-                            // Clear input buffers
-                            self.new_profile_name_input.clear();
-                            self.new_profile_region_input.clear();
-                            self.new_profile_output_input.clear();
-                            self.new_profile_input_cursor = 0;
-                        }
-                    }
-                }
-            }
-            KeyCode::Esc => {
-                self.state = AppState::Main;
-                self.new_profile_name_input.clear();
-                self.new_profile_region_input.clear();
-                self.new_profile_output_input.clear();
-                self.new_profile_input_cursor = 0;
-                self.pending_role = None;
-                self.status_message = Some("Profile configuration cancelled".to_string());
-            }
-            KeyCode::Left => {
-                if self.new_profile_input_cursor > 0 {
-                    self.new_profile_input_cursor -= 1;
-                }
-            }
-            KeyCode::Right => {
-                let max_len = match current_step {
-                    NewProfileConfigStep::ProfileName => self.new_profile_name_input.len(),
-                    NewProfileConfigStep::Region => self.new_profile_region_input.len(),
-                    NewProfileConfigStep::Output => self.new_profile_output_input.len(),
-                };
-                if self.new_profile_input_cursor < max_len {
-                    self.new_profile_input_cursor += 1;
-                }
-            }
-            KeyCode::Home => {
-                self.new_profile_input_cursor = 0;
-            }
-            KeyCode::End => {
-                self.new_profile_input_cursor = match current_step {
-                    NewProfileConfigStep::ProfileName => self.new_profile_name_input.len(),
-                    NewProfileConfigStep::Region => self.new_profile_region_input.len(),
-                    NewProfileConfigStep::Output => self.new_profile_output_input.len(),
-                };
-            }
-            KeyCode::Backspace => {
-                if self.new_profile_input_cursor > 0 {
-                    match current_step {
-                        NewProfileConfigStep::ProfileName => {
-                            self.new_profile_name_input
-                                .remove(self.new_profile_input_cursor - 1);
-                        }
-                        NewProfileConfigStep::Region => {
-                            self.new_profile_region_input
-                                .remove(self.new_profile_input_cursor - 1);
-                        }
-                        NewProfileConfigStep::Output => {
-                            self.new_profile_output_input
-                                .remove(self.new_profile_input_cursor - 1);
-                        }
-                    }
-                    self.new_profile_input_cursor -= 1;
-                }
-            }
-            KeyCode::Delete => match current_step {
-                NewProfileConfigStep::ProfileName => {
-                    if self.new_profile_input_cursor < self.new_profile_name_input.len() {
-                        self.new_profile_name_input
-                            .remove(self.new_profile_input_cursor);
-                    }
-                }
-                NewProfileConfigStep::Region => {
-                    if self.new_profile_input_cursor < self.new_profile_region_input.len() {
-                        self.new_profile_region_input
-                            .remove(self.new_profile_input_cursor);
-                    }
-                }
-                NewProfileConfigStep::Output => {
-                    if self.new_profile_input_cursor < self.new_profile_output_input.len() {
-                        self.new_profile_output_input
-                            .remove(self.new_profile_input_cursor);
-                    }
-                }
-            },
-            KeyCode::Char(c) => match current_step {
-                NewProfileConfigStep::ProfileName => {
-                    if c.is_alphanumeric() || c == '-' || c == '_' {
-                        self.new_profile_name_input
-                            .insert(self.new_profile_input_cursor, c);
-                        self.new_profile_input_cursor += 1;
-                    }
-                }
-                NewProfileConfigStep::Region => {
-                    if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' {
-                        self.new_profile_region_input
-                            .insert(self.new_profile_input_cursor, c);
-                        self.new_profile_input_cursor += 1;
-                    }
-                }
-                NewProfileConfigStep::Output => {
-                    if c.is_alphanumeric() || c == '-' {
-                        self.new_profile_output_input
-                            .insert(self.new_profile_input_cursor, c);
-                        self.new_profile_input_cursor += 1;
-                    }
-                }
-            },
-            _ => {}
-        }
-        Ok(())
-    }
-
-    async fn handle_confirmation_dialog_key(&mut self, key: KeyCode) -> Result<()> {
-        match key {
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                // User confirmed - execute the pending action
-                if let Some(action) = self.pending_confirm_action.take() {
-                    match action {
-                        ConfirmAction::MakeProfileDefault {
-                            from_profile,
-                            account: _,
-                        } => {
-                            // Delete existing default profile
-                            tracing::info!("Deleting existing default profile");
-                            if let Err(e) = crate::aws_config::delete_profile("default") {
-                                tracing::debug!(
-                                    "No existing default profile to delete (or error): {}",
-                                    e
-                                );
-                            }
-
-                            // Rename the profile to default
-                            match crate::aws_config::rename_profile(&from_profile, "default") {
-                                Ok(()) => {
-                                    self.status_message = Some(format!(
-                                        "✓ Set '{}' as default profile",
-                                        from_profile
-                                    ));
-                                    // Reload accounts to update indicators
-                                    if let Err(e) = self.load_accounts().await {
-                                        tracing::warn!(
-                                            "Failed to reload accounts after setting default: {}",
-                                            e
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    self.status_message =
-                                        Some(format!("Error setting default profile: {}", e));
-                                }
-                            }
-                        }
-                        ConfirmAction::RenameProfile {
-                            old_name,
-                            new_name,
-                            account,
-                        } => {
-                            // Delete old profile if names differ
-                            if old_name != new_name {
-                                if let Err(e) = crate::aws_config::delete_profile(&old_name) {
-                                    tracing::warn!(
-                                        "Failed to delete old profile '{}': {}",
-                                        old_name,
-                                        e
-                                    );
-                                }
-                            }
-
-                            // Save the profile with new name and credentials
-                            self.state = AppState::Loading;
-                            self.save_profile_credentials(&account, &new_name).await?;
-                        }
-                        ConfirmAction::DeleteSession {
-                            session_index,
-                            session_name,
-                        } => {
-                            // Delete the session
-                            if let Some(session) = self.sso_sessions.get(session_index).cloned() {
-                                // Logout if active
-                                if session.is_active {
-                                    self.logout_session(session_index).await?;
-                                }
-
-                                // Delete from config
-                                if let Err(e) = crate::aws_config::delete_sso_session(&session_name)
-                                {
-                                    self.status_message =
-                                        Some(format!("Error deleting session: {}", e));
-                                    self.state = AppState::Main;
-                                    return Ok(());
-                                }
-
-                                // Remove from list
-                                self.sso_sessions.remove(session_index);
-
-                                // Update selection
-                                if self.sso_sessions.is_empty() {
-                                    self.sessions_list_state.select(None);
-                                } else if session_index >= self.sso_sessions.len() {
-                                    self.sessions_list_state
-                                        .select(Some(self.sso_sessions.len() - 1));
-                                }
-
-                                self.status_message =
-                                    Some(format!("✓ Deleted session '{}'", session_name));
-                            }
-                        }
-                    }
-                }
-                self.state = AppState::Main;
-            }
-            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                // User cancelled - just return to main screen
-                self.pending_confirm_action = None;
-                self.state = AppState::Main;
-                self.status_message = Some("Action cancelled".to_string());
-            }
-            _ => {
-                // Ignore other keys
-            }
-        }
-        Ok(())
-    }
-
-    fn draw_confirmation_dialog(&self, f: &mut Frame, title: String, message: Vec<String>) {
-        // Calculate dialog size with dynamic height
-        let dialog_width = 60;
-
-        // CRITICAL: Reserve space for essential elements
-        // - borders: 2 lines
-        // - title: 1 line
-        // - empty after title: 1 line
-        // - empty before buttons: 1 line
-        // - buttons (Y/N): 1 line
-        // MINIMUM dialog: 8 lines (6 fixed + at least 2 message lines)
-        let min_essential_height = 8u16;
-
-        // Get terminal dimensions
-        let area = f.area();
-
-        // Use most of the terminal height, leaving small margin
-        let max_height = area.height.saturating_sub(2);
-
-        // Calculate desired height
-        let content_height = message.len() as u16;
-        let desired_height = content_height + 6; // message + fixed elements
-
-        // Final dialog height (ensure minimum)
-        let dialog_height = std::cmp::max(
-            min_essential_height,
-            std::cmp::min(desired_height, max_height),
-        );
-
-        // Center the dialog
-        let dialog_x = (area.width.saturating_sub(dialog_width)) / 2;
-        let dialog_y = (area.height.saturating_sub(dialog_height)) / 2;
-
-        let dialog_area = ratatui::layout::Rect {
-            x: dialog_x,
-            y: dialog_y,
-            width: dialog_width,
-            height: dialog_height,
-        };
-
-        // Calculate available space for message content
-        // dialog_height - borders(2) - title(1) - empty(1) - empty(1) - buttons(1) = available
-        let available_message_lines = (dialog_height as usize).saturating_sub(6).max(1);
-
-        // Truncate message if needed - ALWAYS ensure Y/N buttons can be shown
-        let message_to_show = if message.len() > available_message_lines {
-            // Leave room for truncation indicator
-            let truncate_at = available_message_lines.saturating_sub(1).max(1);
-            let mut truncated = message[..truncate_at].to_vec();
-            truncated.push("...".to_string());
-            truncated
-        } else {
-            message
-        };
-
-        // Build dialog content
-        let mut dialog_text = vec![];
-        dialog_text.push(Line::from(Span::styled(
-            title,
-            Style::default()
-                .fg(catppuccin_color(self.theme.colors.yellow))
-                .add_modifier(Modifier::BOLD),
-        )));
-        dialog_text.push(Line::from(""));
-
-        for msg in message_to_show {
-            dialog_text.push(Line::from(msg));
-        }
-
-        dialog_text.push(Line::from(""));
-        dialog_text.push(Line::from(vec![
-            Span::styled(
-                "Y",
-                Style::default()
-                    .fg(catppuccin_color(self.theme.colors.green))
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(": Confirm | "),
-            Span::styled(
-                "N",
-                Style::default()
-                    .fg(catppuccin_color(self.theme.colors.red))
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(": Cancel"),
-        ]));
-
-        let dialog = Paragraph::new(dialog_text)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(catppuccin_color(self.theme.colors.yellow)))
-                    .title("Confirmation"),
-            )
-            .wrap(ratatui::widgets::Wrap { trim: false });
-
-        // Clear the background by rendering a clear block first
-        let clear_block =
-            Block::default().style(Style::default().bg(catppuccin_color(self.theme.colors.base)));
-        f.render_widget(clear_block, dialog_area);
-
-        // Render the dialog
-        f.render_widget(dialog, dialog_area);
-    }
-
-    async fn save_profile_credentials(
+    pub(crate) async fn save_profile_credentials(
         &mut self,
         account: &AccountRole,
         profile_name: &str,
     ) -> Result<()> {
         if let (Some(ref token), Some(ref instance)) = (&self.sso_token, &self.sso_instance) {
+            // awsom doesn't write to user-managed profiles without being told how
+            if crate::aws_config::profile_exists_in_user_section(profile_name)? {
+                self.state = AppState::ProfileConflict {
+                    account: account.clone(),
+                    profile_name: profile_name.to_string(),
+                    suffixed: crate::aws_config::suffixed_profile_name(profile_name)?,
+                };
+                return Ok(());
+            }
+
             // Check if target profile already exists (and is not the one being renamed)
             let target_exists = match crate::aws_config::get_profile_details(profile_name) {
                 Ok(Some(_)) => {
@@ -2026,14 +1938,55 @@ impl App {
                 }
             }
 
+            if self.config.profile_defaults.profile_mode == ProfileMode::SsoNative {
+                let profile = crate::aws_config::sso_native_session(instance).map(|sso_session| {
+                    crate::aws_config::SsoProfile {
+                        name: profile_name.to_string(),
+                        sso_session,
+                        account_id: account.account_id.clone(),
+                        role_name: account.role_name.clone(),
+                        region: if self.new_profile_region_input.is_empty() {
+                            instance.region.clone()
+                        } else {
+                            self.new_profile_region_input.clone()
+                        },
+                        output: if self.new_profile_output_input.is_empty() {
+                            sso_config::get_default_output_format().map(str::to_string)
+                        } else {
+                            Some(self.new_profile_output_input.clone())
+                        },
+                        credential_process: false,
+                        source_profile: None,
+                    }
+                });
+                match profile.and_then(|p| crate::aws_config::write_sso_native_profile(&p)) {
+                    Ok(()) => {
+                        crate::usage::record(
+                            &self.config.stats,
+                            &account.account_id,
+                            &account.role_name,
+                            Some(profile_name),
+                        );
+                        self.state = AppState::Main;
+                        self.status_message = Some(format!(
+                            "✓ Saved SSO profile '{}' (credentials come from the SSO session)",
+                            profile_name
+                        ));
+                        self.load_accounts();
+                    }
+                    Err(e) => {
+                        self.state = AppState::Error(format!("Failed to write profile: {}", e));
+                    }
+                }
+                self.profile_input.clear();
+                self.profile_input_cursor = 0;
+                self.existing_profile_name = None;
+                return Ok(());
+            }
+
             match self
                 .credential_manager
-                .get_role_credentials(
-                    &instance.region,
-                    &token.access_token,
-                    &account.account_id,
-                    &account.role_name,
-                )
+                .get_role_credentials(instance, &token.access_token, account, None)
                 .await
             {
                 Ok(creds) => {
@@ -2059,6 +2012,12 @@ impl App {
                         Some(account),
                     ) {
                         Ok(()) => {
+                            crate::usage::record(
+                                &self.config.stats,
+                                &account.account_id,
+                                &account.role_name,
+                                Some(profile_name),
+                            );
                             self.state = AppState::Main;
                             let mut status_msg = format!(
                                 "✓ Saved profile '{}' (expires in {})",
@@ -2071,12 +2030,7 @@ impl App {
                             self.status_message = Some(status_msg);
 
                             // Reload accounts to update active status indicators
-                            if let Err(e) = self.load_accounts().await {
-                                tracing::warn!(
-                                    "Failed to reload accounts after saving profile: {}",
-                                    e
-                                );
-                            }
+                            self.load_accounts();
                         }
                         Err(e) => {
                             self.state =
@@ -2134,19 +2088,8 @@ impl App {
                 // Store device auth info for display in loading screen
                 self.device_auth_info = Some(auth_info.clone());
 
-                // Only try to open browser if not in headless environment
-                if !crate::env::is_headless_environment() {
-                    let url_to_open = auth_info
-                        .verification_uri_complete
-                        .as_ref()
-                        .unwrap_or(&auth_info.verification_uri);
-
-                    if let Err(e) = webbrowser::open(url_to_open) {
-                        tracing::warn!("Could not open browser automatically: {}", e);
-                    }
-                } else {
-                    tracing::info!("Headless environment detected - skipping browser launch, showing URL in TUI");
-                }
+                // Skipped when headless - the URL is shown in the TUI instead
+                auth_info.open_browser();
 
                 Ok(())
             })
@@ -2164,12 +2107,8 @@ impl App {
                 self.status_message = Some("Login successful! Loading accounts...".to_string());
 
                 // Load accounts after successful login
-                if let Err(e) = self.load_accounts().await {
-                    self.status_message = Some(format!(
-                        "Login succeeded but failed to load accounts: {}",
-                        e
-                    ));
-                }
+                self.accounts.items.clear();
+                self.load_accounts();
             }
             Err(e) => {
                 tracing::error!("Login failed: {}", e);
@@ -2192,8 +2131,8 @@ impl App {
         // Clear session data
         self.sso_token = None;
         self.sso_instance = None;
-        self.accounts.clear();
-        self.accounts_list_state.select(None);
+        self.accounts.items.clear();
+        self.accounts.state.select(None);
         self.status_message = Some(
             "Logged out successfully. Switch to Sessions pane (Tab) and press Enter to login."
                 .to_string(),
@@ -2202,10 +2141,63 @@ impl App {
         Ok(())
     }
 
+    /// Pick up changes another tool or an editor made to ~/.aws/config or credentials,
+    /// keeping the selected session selected if it still exists
+    async fn reload_aws_files(&mut self) {
+        let selected = self
+            .sessions
+            .selected()
+            .map(|session| session.session_name.clone());
+        let previous = self.sso_instance.clone();
+
+        self.load_all_sso_sessions().await;
+        let index = selected
+            .and_then(|name| {
+                self.sessions
+                    .items
+                    .iter()
+                    .position(|session| session.session_name == name)
+            })
+            .or_else(|| (!self.sessions.items.is_empty()).then_some(0));
+        self.sessions.state.select(index);
+        self.update_current_session_from_selection();
+        self.load_profile_expirations();
+
+        if self.sso_instance == previous {
+            // Same session: only profile names, defaults and credentials can have changed
+            self.restatus_rows();
+        } else {
+            self.accounts.items.clear();
+            self.accounts.state.select(None);
+            self.accounts.org_tree = None;
+            self.load_accounts();
+        }
+
+        self.status_message = Some(
+            "~/.aws/config or ~/.aws/credentials changed outside awsom - reloaded".to_string(),
+        );
+    }
+
+    /// Re-read the profile and credential state of the listed roles
+    pub(crate) fn restatus_rows(&mut self) {
+        let roles = self
+            .accounts
+            .items
+            .iter()
+            .map(|account| (account.session_name.clone(), account.account_role.clone()))
+            .collect();
+        self.accounts.items = self.with_status(roles);
+        self.resort_accounts();
+    }
+
     /// Load all SSO sessions from ~/.aws/config and check their token status
-    async fn load_all_sso_sessions(&mut self) {
+    pub(crate) async fn load_all_sso_sessions(&mut self) {
+        let workspace_session = self.config.workspace().and_then(|w| w.sso_session.clone());
         match crate::aws_config::read_all_sso_sessions() {
-            Ok(sessions) => {
+            Ok(mut sessions) => {
+                if let Some(name) = &workspace_session {
+                    sessions.retain(|session| &session.session_name == name);
+                }
                 tracing::info!("Loaded {} raw sessions from config", sessions.len());
                 let mut sso_session_infos = Vec::new();
 
@@ -2242,21 +2234,22 @@ impl App {
                     });
                 }
 
-                self.sso_sessions = sso_session_infos;
+                self.sessions.items = sso_session_infos;
 
                 // Select first active session if available, otherwise select first session
-                if !self.sso_sessions.is_empty() && self.sessions_list_state.selected().is_none() {
+                if !self.sessions.items.is_empty() && self.sessions.state.selected().is_none() {
                     // Find first active session
                     let first_active_idx = self
-                        .sso_sessions
+                        .sessions
+                        .items
                         .iter()
                         .position(|session| session.is_active);
 
                     let selected_idx = first_active_idx.unwrap_or(0);
-                    self.sessions_list_state.select(Some(selected_idx));
+                    self.sessions.state.select(Some(selected_idx));
 
                     // Set current session to the selected one if it's active
-                    if let Some(selected_session) = self.sso_sessions.get(selected_idx) {
+                    if let Some(selected_session) = self.sessions.items.get(selected_idx) {
                         if selected_session.is_active {
                             self.sso_instance = Some(selected_session.instance.clone());
                             self.sso_token = selected_session.token.clone();
@@ -2264,8 +2257,10 @@ impl App {
                     }
                 }
 
-                self.status_message =
-                    Some(format!("Loaded {} SSO session(s)", self.sso_sessions.len()));
+                self.status_message = Some(format!(
+                    "Loaded {} SSO session(s)",
+                    self.sessions.items.len()
+                ));
             }
             Err(e) => {
                 tracing::warn!("Error loading SSO sessions: {}", e);
@@ -2311,9 +2306,7 @@ impl App {
                     self.status_message = Some("Loaded valid SSO session from cache".to_string());
 
                     // Auto-load accounts
-                    if let Err(e) = self.load_accounts().await {
-                        self.status_message = Some(format!("Failed to load accounts: {}", e));
-                    }
+                    self.load_accounts();
                 } else {
                     tracing::info!("Cached SSO token has expired");
                     self.status_message =
@@ -2334,1055 +2327,1148 @@ impl App {
         }
     }
 
-    async fn load_accounts(&mut self) -> Result<()> {
-        if let (Some(ref token), Some(ref instance)) = (&self.sso_token, &self.sso_instance) {
-            self.state = AppState::Loading;
-            self.status_message = Some("Loading accounts and roles...".to_string());
+    /// Show the current session's accounts and roles, from the directory cache while it
+    /// is fresh
+    pub(crate) fn load_accounts(&mut self) {
+        self.start_discovery(false);
+    }
 
-            match self
-                .credential_manager
-                .list_accounts(&instance.region, &token.access_token)
-                .await
-            {
-                Ok(account_list) => {
-                    // Now fetch roles for each account
-                    let mut all_roles = Vec::new();
-                    for (account_id, account_name) in account_list {
-                        match self
-                            .credential_manager
-                            .list_account_roles(&instance.region, &token.access_token, &account_id)
-                            .await
-                        {
-                            Ok(roles) => {
-                                for role_name in roles {
-                                    all_roles.push(AccountRole {
-                                        account_id: account_id.clone(),
-                                        account_name: account_name.clone(),
-                                        role_name,
-                                    });
-                                }
-                            }
-                            Err(e) => {
-                                tracing::warn!(
-                                    "Failed to list roles for account {}: {}",
-                                    account_id,
-                                    e
-                                );
-                            }
-                        }
-                    }
+    /// List accounts and roles from AWS even if the directory cache is fresh (`r`)
+    fn refresh_accounts(&mut self) {
+        self.start_discovery(true);
+    }
 
-                    // Load credential statuses from AWS config
-                    let statuses = crate::aws_config::list_profile_statuses().unwrap_or_default();
-
-                    // Build a map from (account_id, role_name) to (is_active, expiration, is_default)
-                    #[allow(clippy::type_complexity)]
-                    let mut profile_map: HashMap<
-                        (String, String),
-                        (bool, Option<chrono::DateTime<chrono::Utc>>, bool),
-                    > = HashMap::new();
-
-                    for status in statuses {
-                        if status.has_credentials {
-                            if let (Some(account_id), Some(role_name)) =
-                                (status.account_id, status.role_name)
-                            {
-                                // Check if this is the default profile
-                                let is_default = status.profile_name == "default";
-
-                                // Check if credentials are expired
-                                let is_active = if let Some(expiration) = status.expiration {
-                                    chrono::Utc::now() < expiration
-                                } else {
-                                    // No expiration info means credentials exist but we can't verify validity
-                                    true
-                                };
+    /// Sessions whose roles the Accounts pane lists: every logged-in one in the
+    /// all-sessions view, else the selected one
+    fn discovery_targets(&self) -> Vec<(Option<String>, SsoInstance, SsoToken)> {
+        if self.config.ui.all_sessions {
+            return self
+                .sessions
+                .items
+                .iter()
+                .filter(|session| session.is_active)
+                .filter_map(|session| {
+                    let token = session.token.clone()?;
+                    Some((
+                        Some(session.session_name.clone()),
+                        session.instance.clone(),
+                        token,
+                    ))
+                })
+                .collect();
+        }
 
-                                // Match by account ID and role name from metadata
-                                profile_map.insert(
-                                    (account_id, role_name),
-                                    (is_active, status.expiration, is_default),
-                                );
-                            }
-                        }
-                    }
+        let (Some(token), Some(instance)) = (self.sso_token.clone(), self.sso_instance.clone())
+        else {
+            return Vec::new();
+        };
+        let session_name = self.sessions.selected().map(|s| s.session_name.clone());
+        vec![(session_name, instance, token)]
+    }
 
-                    // Get current session name for profile lookup
-                    let session_name = self
-                        .get_selected_session()
-                        .map(|selected_session| selected_session.session_name.clone());
-
-                    // Wrap roles with status
-                    let mut accounts_with_status: Vec<AccountRoleWithStatus> = all_roles
-                        .into_iter()
-                        .map(|account_role| {
-                            // Match by account ID and role name
-                            let key = (
-                                account_role.account_id.clone(),
-                                account_role.role_name.clone(),
-                            );
-                            let (is_active, expiration, is_default) = profile_map
-                                .get(&key)
-                                .cloned()
-                                .unwrap_or((false, None, false));
-
-                            // Look up profile name using unified lookup
-                            let profile_name = if let Some(ref sess_name) = session_name {
-                                crate::aws_config::get_profile_by_role(
-                                    sess_name,
-                                    &account_role.account_id,
-                                    &account_role.role_name,
-                                )
-                                .ok()
-                                .flatten()
-                                .map(|p| p.name)
-                            } else {
-                                None
-                            };
+    /// Start listing accounts and roles in the background
+    ///
+    /// On a first load rows appear as each account's roles arrive, or straight away from a
+    /// stale cache. A reload keeps the current rows on screen and swaps in the new list
+    /// once discovery is done, so the list doesn't empty and refill after every profile
+    /// change. In the all-sessions view the sessions are listed one after another, and
+    /// one that fails is reported without dropping the others.
+    fn start_discovery(&mut self, bypass_cache: bool) {
+        let targets = self.discovery_targets();
+        if targets.is_empty() {
+            return;
+        }
 
-                            AccountRoleWithStatus {
-                                account_role,
-                                is_active,
-                                expiration,
-                                is_default,
-                                profile_name,
-                            }
-                        })
-                        .collect();
-
-                    // Sort by account name, then by role name
-                    accounts_with_status.sort_by(|a, b| {
-                        a.account_role
-                            .account_name
-                            .cmp(&b.account_role.account_name)
-                            .then_with(|| a.account_role.role_name.cmp(&b.account_role.role_name))
+        let ttl = chrono::Duration::minutes(self.config.cache.accounts_ttl_minutes);
+        let all_sessions = self.config.ui.all_sessions;
+        let id = self.next_discovery_id;
+        self.next_discovery_id += 1;
+        let events = self.events_tx.clone();
+        let tx = move |event| {
+            let _ = events.send(AppEvent::Discovery { id, event });
+        };
+        let task = tokio::spawn(async move {
+            for (session, instance, token) in targets {
+                let cached = if bypass_cache {
+                    None
+                } else {
+                    directory::load(&instance.start_url)
+                };
+                if let Some(cached) = cached {
+                    let fresh = cached.is_fresh(ttl);
+                    tx(DiscoveryEvent::Cached {
+                        session: session.clone(),
+                        roles: cached.roles,
+                        fresh,
                     });
-
-                    self.accounts = accounts_with_status;
-                    self.state = AppState::Main;
-                    self.status_message = Some(format!(
-                        "Loaded {} account/role combinations",
-                        self.accounts.len()
-                    ));
-
-                    // Select first item if none selected
-                    if self.accounts_list_state.selected().is_none() && !self.accounts.is_empty() {
-                        self.accounts_list_state.select(Some(0));
+                    if fresh {
+                        continue;
                     }
                 }
-                Err(e) => {
-                    self.state = AppState::Error(format!("Failed to load accounts: {}", e));
+
+                let result = match CredentialManager::new() {
+                    Ok(manager) => manager
+                        .discover_roles(&instance, &token.access_token, |roles, done, total| {
+                            tx(DiscoveryEvent::Roles {
+                                session: session.clone(),
+                                roles: roles.to_vec(),
+                                done,
+                                total,
+                            });
+                        })
+                        .await
+                        .map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                match (result, session) {
+                    (Err(e), Some(session)) if all_sessions => {
+                        tracing::warn!("Failed to list accounts of session {}: {}", session, e);
+                        tx(DiscoveryEvent::SessionFailed {
+                            session,
+                            offline: matches!(e, SsoError::NetworkError(_)),
+                            error: e.to_string(),
+                        });
+                    }
+                    (Err(e), _) => {
+                        tx(DiscoveryEvent::Finished(Err(e)));
+                        return;
+                    }
+                    (Ok(()), _) => {}
                 }
             }
+            tx(DiscoveryEvent::Finished(Ok(())));
+        });
+
+        let status = "Loading accounts and roles...".to_string();
+        self.status_message = Some(status.clone());
+        if self.state == AppState::Loading {
+            self.state = AppState::Main;
         }
-        Ok(())
+        // Replacing a running discovery aborts it
+        self.discovery = Some(Discovery {
+            id,
+            task,
+            roles: Vec::new(),
+            live: self.accounts.items.is_empty(),
+            from_cache: false,
+            status,
+            progress: None,
+            failed: Vec::new(),
+            offline: false,
+        });
     }
 
-    async fn get_credentials_for_role(&mut self, account: &AccountRole) -> Result<()> {
-        if let (Some(ref token), Some(ref instance)) = (&self.sso_token, &self.sso_instance) {
-            self.status_message = Some(format!(
-                "Getting credentials for {} / {}...",
-                account.account_name, account.role_name
-            ));
+    /// Apply role discovery results that arrived since the last frame
+    fn apply_discovery_events(&mut self, events: Vec<(u64, DiscoveryEvent)>) {
+        let Some(discovery) = self.discovery.as_mut() else {
+            return;
+        };
 
-            match self
-                .credential_manager
-                .get_role_credentials(
-                    &instance.region,
-                    &token.access_token,
-                    &account.account_id,
-                    &account.role_name,
-                )
-                .await
-            {
-                Ok(creds) => {
-                    self.status_message = Some(format!(
-                        "Credentials cached for {} / {} (expires in {})",
-                        account.account_name,
-                        account.role_name,
-                        creds.expiration_display()
-                    ));
+        let mut arrived = Vec::new();
+        let mut stale = None;
+        let mut progress = None;
+        let mut finished = None;
+        let current = discovery.id;
+        let events = events
+            .into_iter()
+            .filter_map(|(id, event)| (id == current).then_some(event));
+        for event in events {
+            match event {
+                // Fresh cached rows replace the list when discovery finishes, like a reload;
+                // stale ones fill an empty list until the listing from AWS replaces them
+                DiscoveryEvent::Cached {
+                    session,
+                    roles,
+                    fresh,
+                } => {
+                    if discovery.live {
+                        // Rows an earlier session added live stay when the list is swapped
+                        discovery
+                            .roles
+                            .extend(self.accounts.items.iter().map(|account| {
+                                (account.session_name.clone(), account.account_role.clone())
+                            }));
+                        discovery.live = false;
+                    }
+                    discovery.from_cache |= fresh;
+                    let roles = roles.into_iter().map(|role| (session.clone(), role));
+                    if fresh {
+                        discovery.roles.extend(roles);
+                    } else {
+                        stale.get_or_insert_with(Vec::new).extend(roles);
+                    }
                 }
-                Err(e) => {
-                    self.status_message = Some(format!("Error: {}", e));
+                DiscoveryEvent::Roles {
+                    session,
+                    roles,
+                    done,
+                    total,
+                } => {
+                    arrived.extend(roles.into_iter().map(|role| (session.clone(), role)));
+                    progress = Some((session, done, total));
+                }
+                DiscoveryEvent::SessionFailed {
+                    session,
+                    error,
+                    offline,
+                } => {
+                    discovery.failed.push(format!("{}: {}", session, error));
+                    discovery.offline |= offline;
+                }
+                DiscoveryEvent::Finished(result) => {
+                    finished = Some(result);
+                    break;
                 }
             }
         }
-        Ok(())
-    }
 
-    /// Open AWS Console in browser for selected role
-    async fn open_console(&mut self) -> Result<()> {
-        if let Some(index) = self.accounts_list_state.selected() {
-            if let Some(account_with_status) = self.accounts.get(index).cloned() {
-                let account = account_with_status.account_role;
-
-                // Check if credentials are active
-                if !account_with_status.is_active {
-                    self.status_message = Some("No active credentials for this role. Press Enter to create credentials first.".to_string());
-                    return Ok(());
-                }
+        if let Some((session, done, total)) = progress {
+            let status = match session.filter(|_| self.config.ui.all_sessions) {
+                Some(session) => format!(
+                    "Loading roles of {}: {}/{} account(s)",
+                    session, done, total
+                ),
+                None => format!("Loading roles: {}/{} account(s)", done, total),
+            };
+            if self.status_message.as_deref() == Some(discovery.status.as_str()) {
+                self.status_message = Some(status.clone());
+            }
+            discovery.status = status;
+            discovery.progress = Some((done, total));
+        }
 
-                // Get credentials to open console
-                if let (Some(ref token), Some(ref instance)) = (&self.sso_token, &self.sso_instance)
-                {
-                    self.status_message = Some("Opening AWS Console in browser...".to_string());
+        if !discovery.live {
+            discovery.roles.extend(arrived);
+        } else if !arrived.is_empty() {
+            self.add_rows(arrived);
+        }
+        if let Some(roles) = stale.filter(|_| self.accounts.items.is_empty()) {
+            self.add_rows(roles);
+        }
 
-                    match self
-                        .credential_manager
-                        .get_role_credentials(
-                            &instance.region,
-                            &token.access_token,
-                            &account.account_id,
-                            &account.role_name,
-                        )
-                        .await
-                    {
-                        Ok(creds) => {
-                            // Use SSO region as default
-                            let region = Some(instance.region.as_str());
-
-                            match crate::console::open_console(&creds, region) {
-                                Ok(()) => {
-                                    self.status_message = Some(format!(
-                                        "✓ Opened AWS Console for {} / {}",
-                                        account.account_name, account.role_name
-                                    ));
-                                }
-                                Err(e) => {
-                                    self.status_message =
-                                        Some(format!("Error opening console: {}", e));
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            self.status_message = Some(format!("Error getting credentials: {}", e));
-                        }
-                    }
-                }
+        if let Some(result) = finished {
+            if let Some(discovery) = self.discovery.take() {
+                self.finish_discovery(discovery, result);
             }
-        } else {
-            self.status_message = Some("No role selected".to_string());
         }
-        Ok(())
     }
 
-    fn ui(&mut self, f: &mut Frame) {
-        // Note: draw_loading_screen needs &mut self to poll device_auth_info from Arc
-        match &self.state {
-            AppState::Main => self.draw_main_screen(f),
-            AppState::Help => self.draw_help_screen(f),
-            AppState::Loading => self.draw_loading_screen(f),
-            AppState::Error(msg) => self.draw_error_screen(f, msg.clone()),
-            AppState::ProfileInput => self.draw_profile_input_screen(f),
-            AppState::SsoConfigInput { step } => self.draw_sso_config_input_screen(f, step.clone()),
-            AppState::DefaultsConfigInput { step } => {
-                self.draw_defaults_config_input_screen(f, step.clone())
-            }
-            AppState::NewProfileConfigInput { step } => {
-                self.draw_new_profile_config_input_screen(f, step.clone())
+    /// Stop the running discovery, keeping the rows already on screen: those listed so far
+    /// on a first load, or the previous list on a reload
+    fn cancel_discovery(&mut self) {
+        // Dropping the discovery aborts its task
+        let Some(discovery) = self.discovery.take() else {
+            return;
+        };
+        self.load_profile_expirations();
+
+        let progress = discovery
+            .progress
+            .map(|(done, total)| format!(" after {}/{} account(s)", done, total))
+            .unwrap_or_default();
+        self.status_message = Some(format!(
+            "Loading cancelled{}; showing {} account/role combinations (r to reload)",
+            progress,
+            self.accounts.items.len()
+        ));
+    }
+
+    /// Add roles to the list; the first rows move the focus to the Accounts pane
+    fn add_rows(&mut self, roles: Vec<(Option<String>, AccountRole)>) {
+        let first_rows = self.accounts.items.is_empty();
+        let rows = self.with_status(roles);
+        self.accounts.items.extend(rows);
+        self.resort_accounts();
+        if first_rows && !self.accounts.items.is_empty() {
+            self.active_pane = ActivePane::Accounts;
+            self.accounts.state.select(Some(0));
+        }
+    }
+
+    fn finish_discovery(&mut self, mut discovery: Discovery, result: Result<()>) {
+        match result {
+            // Without a network the rows on screen (cached or listed earlier) are kept
+            Err(SsoError::NetworkError(e)) => {
+                tracing::warn!("Failed to list accounts, showing cached ones: {}", e);
+                self.offline = true;
+                self.load_profile_expirations();
+                self.status_message = Some(if self.accounts.items.is_empty() {
+                    "Offline: AWS is unreachable and no accounts are cached (r to retry)"
+                        .to_string()
+                } else {
+                    format!(
+                        "Offline: showing {} cached account/role combinations (r to retry)",
+                        self.accounts.items.len()
+                    )
+                });
+                return;
             }
-            AppState::ConfirmationDialog { title, message } => {
-                self.draw_confirmation_dialog(f, title.clone(), message.clone())
+            Err(e) => {
+                self.state = AppState::Error(format!("Failed to load accounts: {}", e));
+                return;
             }
+            Ok(()) => self.offline = discovery.offline,
         }
-    }
 
-    fn draw_main_screen(&mut self, f: &mut Frame) {
-        // Calculate dynamic sessions pane height
-        // Min 5 lines (1 border top + 1 header + 1 header margin + 1 content + 1 border bottom)
-        // Max 12 lines to avoid taking too much space
-        let sessions_count = self.sso_sessions.len();
-        let sessions_height = if sessions_count == 0 {
-            5 // Minimum height for empty pane
+        if !discovery.live {
+            self.accounts.items = self.with_status(std::mem::take(&mut discovery.roles));
+            self.resort_accounts();
+        }
+        self.load_profile_expirations();
+
+        // The session may belong to another organization now
+        self.accounts.org_tree = None;
+        let org_error = if self.config.ui.group_by == GroupBy::Ou {
+            self.load_org_tree(false).err()
         } else {
-            // 4 for borders + header + header margin, plus 1 line per session, max 12 total
-            std::cmp::min(sessions_count + 4, 12)
+            None
         };
 
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),                      // Header
-                Constraint::Min(10),                        // Accounts pane (flexible)
-                Constraint::Length(sessions_height as u16), // Sessions pane (dynamic)
-                Constraint::Length(2),                      // Help bar (2 lines)
-            ])
-            .split(f.area());
-
-        // Header
-        let header = Paragraph::new("awsom - AWS Organization Manager")
-            .style(
-                Style::default()
-                    .fg(catppuccin_color(self.theme.colors.blue))
-                    .add_modifier(Modifier::BOLD),
-            )
-            .block(Block::default().borders(Borders::ALL));
-        f.render_widget(header, chunks[0]);
-
-        // Account/Role table
-        let rows: Vec<Row> = self
+        // Leave messages set while discovery ran in the background alone
+        if self.status_message.as_deref() == Some(discovery.status.as_str()) {
+            let mut source = if discovery.from_cache {
+                " from cache (r to refresh)".to_string()
+            } else {
+                String::new()
+            };
+            if !discovery.failed.is_empty() {
+                source.push_str(&format!("; not listed: {}", discovery.failed.join(", ")));
+            }
+            self.status_message = Some(match org_error {
+                None => format!(
+                    "Loaded {} account/role combinations{}",
+                    self.accounts.items.len(),
+                    source
+                ),
+                Some(e) => format!(
+                    "Loaded {} account/role combinations{} (OU tree unavailable: {})",
+                    self.accounts.items.len(),
+                    source,
+                    e
+                ),
+            });
+        }
+
+        // Select first item if none selected
+        if self.accounts.state.selected().is_none() && !self.accounts.items.is_empty() {
+            self.accounts.state.select(Some(0));
+        }
+    }
+
+    /// Sort the account list, keeping the selected role selected
+    fn resort_accounts(&mut self) {
+        let selected = self
             .accounts
-            .iter()
-            .map(|account_with_status| {
-                let account = &account_with_status.account_role;
+            .selected_index(&self.config)
+            .and_then(|i| self.accounts.items.get(i))
+            .map(|a| a.account_role.clone());
+
+        sort_accounts(&mut self.accounts.items, &self.config.ui);
+
+        if let Some(selected) = selected {
+            let index = self
+                .accounts
+                .items
+                .iter()
+                .position(|a| a.account_role == selected)
+                .unwrap_or(0);
+            self.accounts.select(index, &self.config);
+        }
+    }
 
-                // Default marker
-                let default_mark = if account_with_status.is_default {
-                    "✓"
-                } else {
-                    ""
-                };
+    /// Attach profile, credential and favorite state to discovered roles, each with the
+    /// session it was listed from
+    fn with_status(&self, roles: Vec<(Option<String>, AccountRole)>) -> Vec<AccountRoleWithStatus> {
+        // Load credential statuses from AWS config
+        let statuses = crate::aws_config::list_profile_statuses().unwrap_or_default();
+
+        // Build a map from (account_id, role_name) to (is_active, expiration, is_default);
+        // with several profiles bound to a role, it's active and default if any of them is
+        #[allow(clippy::type_complexity)]
+        let mut profile_map: HashMap<
+            (String, String),
+            (bool, Option<chrono::DateTime<chrono::Utc>>, bool),
+        > = HashMap::new();
+
+        for status in statuses {
+            if status.has_credentials {
+                if let (Some(account_id), Some(role_name)) = (status.account_id, status.role_name) {
+                    // Check if this is the default profile
+                    let is_default = status.profile_name == "default";
+
+                    // Check if credentials are expired
+                    let is_active = if let Some(expiration) = status.expiration {
+                        !crate::expiry::is_expired(&expiration)
+                    } else {
+                        // No expiration info means credentials exist but we can't verify validity
+                        true
+                    };
 
-                // Calculate expiration status and actual active state
-                let (is_actually_active, expiration_status) = if account_with_status.is_active {
-                    if let Some(expiration) = account_with_status.expiration {
-                        let now = chrono::Utc::now();
-                        let remaining_secs = (expiration - now).num_seconds();
+                    // Match by account ID and role name from metadata
+                    let entry = profile_map
+                        .entry((account_id, role_name))
+                        .or_insert((false, None, false));
+                    entry.0 |= is_active;
+                    entry.1 = entry.1.max(status.expiration);
+                    entry.2 |= is_default;
+                }
+            }
+        }
 
-                        if remaining_secs > 0 {
-                            let hours = remaining_secs / 3600;
-                            let mins = (remaining_secs % 3600) / 60;
+        // Wrap roles with status, leaving out accounts outside the workspace or the session's
+        // account filter
+        let sso_native = self.config.profile_defaults.profile_mode == ProfileMode::SsoNative;
+        let last_used = crate::usage::last_used();
+        roles
+            .into_iter()
+            .filter(|(session_name, role)| self.config.shows_account(session_name.as_deref(), role))
+            .map(|(session_name, account_role)| {
+                // Match by account ID and role name
+                let key = (
+                    account_role.account_id.clone(),
+                    account_role.role_name.clone(),
+                );
 
-                            let display = if hours > 0 {
-                                format!("{}h {}m", hours, mins)
-                            } else {
-                                format!("{}m", mins)
-                            };
-                            (true, display)
-                        } else {
-                            (false, "EXPIRED".to_string())
-                        }
-                    } else {
-                        (true, "".to_string())
+                // Look up the profiles bound to the role using unified lookup
+                let mut other_profiles = match &session_name {
+                    Some(sess_name) => crate::aws_config::get_profiles_by_role(
+                        sess_name,
+                        &account_role.account_id,
+                        &account_role.role_name,
+                    )
+                    .unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                let profile_name = (!other_profiles.is_empty()).then(|| other_profiles.remove(0));
+
+                let (is_active, expiration, is_default) = match profile_map.get(&key) {
+                    Some(status) => *status,
+                    // An sso-native profile works for as long as its session's token does
+                    None if sso_native && profile_name.is_some() => {
+                        let token_expiration = self
+                            .sessions
+                            .items
+                            .iter()
+                            .find(|s| Some(&s.session_name) == session_name.as_ref())
+                            .and_then(|s| s.token_expiration);
+                        (
+                            token_expiration.is_some_and(|at| !crate::expiry::is_expired(&at)),
+                            token_expiration,
+                            profile_name.as_deref() == Some("default"),
+                        )
                     }
-                } else {
-                    (false, "".to_string())
+                    None => (false, None, false),
                 };
 
-                // Status indicator based on actual expiration state
-                let status = if is_actually_active { "🟢" } else { "🔴" };
-
-                // Profile name or "N/A"
-                let profile_display = account_with_status.profile_name.as_deref().unwrap_or("N/A");
-
-                Row::new(vec![
-                    Cell::new(Text::from(status).alignment(Alignment::Center)),
-                    Cell::new(Text::from(default_mark).alignment(Alignment::Center)),
-                    Cell::new(
-                        Text::from(account.account_name.clone()).alignment(Alignment::Center),
-                    ),
-                    Cell::new(Text::from(account.account_id.clone()).alignment(Alignment::Center)),
-                    Cell::new(Text::from(account.role_name.clone()).alignment(Alignment::Center)),
-                    Cell::new(Text::from(profile_display).alignment(Alignment::Center)),
-                    Cell::new(Text::from(expiration_status).alignment(Alignment::Center)),
-                ])
+                let is_favorite = self
+                    .config
+                    .is_favorite(&account_role.account_id, &account_role.role_name);
+                let last_used = last_used.get(&key).copied();
+
+                AccountRoleWithStatus {
+                    account_role,
+                    is_active,
+                    expiration,
+                    is_default,
+                    profile_name,
+                    other_profiles,
+                    is_favorite,
+                    session_name,
+                    last_used,
+                }
             })
-            .collect();
-
-        let header = Row::new(vec![
-            Cell::new(Text::from("Status").alignment(Alignment::Center)),
-            Cell::new(Text::from("Default").alignment(Alignment::Center)),
-            Cell::new(Text::from("Account").alignment(Alignment::Center)),
-            Cell::new(Text::from("Account ID").alignment(Alignment::Center)),
-            Cell::new(Text::from("Role").alignment(Alignment::Center)),
-            Cell::new(Text::from("Profile").alignment(Alignment::Center)),
-            Cell::new(Text::from("Expires").alignment(Alignment::Center)),
-        ])
-        .style(
-            Style::default()
-                .fg(catppuccin_color(self.theme.colors.blue))
-                .add_modifier(Modifier::BOLD),
-        )
-        .bottom_margin(1);
-
-        // Highlight accounts pane if it's active
-        let accounts_block_style = if self.active_pane == ActivePane::Accounts {
-            Style::default().fg(catppuccin_color(self.theme.colors.mauve))
-        } else {
-            Style::default().fg(catppuccin_color(self.theme.colors.surface0))
-        };
-
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Length(6),  // Status
-                Constraint::Length(7),  // Default (was 3, now wider for "Default")
-                Constraint::Min(15),    // Account Name
-                Constraint::Length(12), // Account ID
-                Constraint::Min(15),    // Role Name
-                Constraint::Min(15),    // Profile Name
-                Constraint::Length(10), // Expiration
-            ],
-        )
-        .header(header)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Accounts & Roles")
-                .border_style(accounts_block_style),
-        )
-        .row_highlight_style(
-            Style::default()
-                .bg(catppuccin_color(self.theme.colors.surface1))
-                .add_modifier(Modifier::BOLD),
-        );
+            .collect()
+    }
 
-        f.render_stateful_widget(table, chunks[1], &mut self.accounts_list_state);
+    /// Re-read credential expiry times for all profiles, not just the selected session's
+    pub(crate) fn load_profile_expirations(&mut self) {
+        self.profile_expirations = crate::aws_config::list_profile_statuses()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|status| status.has_credentials)
+            .filter_map(|status| Some((status.profile_name, status.expiration?)))
+            .collect();
+    }
 
-        // Render scrollbar for accounts pane
-        if !self.accounts.is_empty() {
-            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
-                .begin_symbol(Some("↑"))
-                .end_symbol(Some("↓"));
+    /// The profile whose credentials expire next, ignoring already expired ones
+    pub(crate) fn soonest_expiry(&self) -> Option<&(String, chrono::DateTime<chrono::Utc>)> {
+        self.profile_expirations
+            .iter()
+            .filter(|(_, expiration)| !crate::expiry::is_expired(expiration))
+            .min_by_key(|(_, expiration)| *expiration)
+    }
 
-            let mut scrollbar_state = ScrollbarState::new(self.accounts.len())
-                .position(self.accounts_list_state.selected().unwrap_or(0));
+    /// The selected session's active profiles with their roles; in the all-sessions view,
+    /// rows listed from other sessions are left alone
+    fn refresh_targets(&self) -> Vec<(AccountRole, String)> {
+        let current = self.sessions.selected().map(|s| s.session_name.clone());
+        // sso-native profiles have no credentials to refresh, only those from before do
+        let sso_native = self.config.profile_defaults.profile_mode == ProfileMode::SsoNative;
+        self.accounts
+            .items
+            .iter()
+            .filter(|account| account.is_active)
+            .filter(|account| account.session_name.is_none() || account.session_name == current)
+            .filter(|account| {
+                !sso_native
+                    || account.profile_name.as_deref().is_some_and(|profile| {
+                        crate::aws_config::has_session_credentials(profile).unwrap_or(false)
+                    })
+            })
+            .filter_map(|account| {
+                Some((account.account_role.clone(), account.profile_name.clone()?))
+            })
+            .collect()
+    }
 
-            f.render_stateful_widget(
-                scrollbar,
-                chunks[1].inner(ratatui::layout::Margin {
-                    vertical: 1,
-                    horizontal: 0,
-                }),
-                &mut scrollbar_state,
+    /// Re-fetch credentials for the selected session's active profiles in the background,
+    /// keeping each profile's region and output
+    fn refresh_all_profiles(&mut self) {
+        if self.refresh_all.is_some() {
+            self.status_message = Some("Profiles are still refreshing".to_string());
+            return;
+        }
+        let current = self.sessions.selected().map(|s| s.session_name.clone());
+        let Some((instance, token)) = self.session_login(current.as_deref()) else {
+            self.status_message = Some(
+                "Not logged in. Switch to Sessions pane (Tab) and press Enter to login."
+                    .to_string(),
             );
+            return;
+        };
+        let targets = self.refresh_targets();
+        if targets.is_empty() {
+            self.status_message = Some("No active profiles to refresh".to_string());
+            return;
         }
 
-        // Sessions pane
-        self.draw_sessions_pane(f, chunks[2]);
-
-        // Help bar (2 lines for better readability)
-        // Make Enter key description context-aware
-        let enter_action = match self.active_pane {
-            ActivePane::Sessions => "Enter:login/logout session",
-            ActivePane::Accounts => "Enter:activate/deactivate credentials",
-        };
+        let mut lines = Vec::new();
+        let mut fetches = Vec::new();
+        for (index, (account, profile_name)) in targets.into_iter().enumerate() {
+            match crate::aws_config::get_profile_details(&profile_name) {
+                Ok(details) => {
+                    let region = details
+                        .as_ref()
+                        .and_then(|d| d.region.clone())
+                        .unwrap_or_else(|| instance.region.clone());
+                    let output = details.and_then(|d| d.output);
+                    lines.push(None);
+                    fetches.push((
+                        index,
+                        StartTarget {
+                            account,
+                            profile_name,
+                            region,
+                            output,
+                        },
+                    ));
+                }
+                Err(e) => lines.push(Some(format!("✗ {}: {}", profile_name, e))),
+            }
+        }
+        self.refresh_all = Some(lines);
+        if fetches.is_empty() {
+            self.finish_refresh_all();
+            return;
+        }
 
-        let help_lines = vec![
-            Line::from(vec![Span::raw(format!(
-                "q:quit | ?:help | Tab:switch pane | ↑↓/jk:navigate | {}",
-                enter_action
-            ))]),
-            Line::from(vec![
-                Span::raw("Sessions: "),
-                Span::styled("a", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(":add "),
-                Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(":edit "),
-                Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(":delete | Accounts: "),
-                Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(":edit "),
-                Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(":make default "),
-                Span::styled("c", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(":console "),
-                Span::styled("r", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(":refresh"),
-            ]),
-        ];
-        let help_bar = Paragraph::new(help_lines)
-            .style(Style::default().fg(catppuccin_color(self.theme.colors.subtext0)));
-        f.render_widget(help_bar, chunks[3]);
-    }
-
-    fn draw_sessions_pane(&mut self, f: &mut Frame, area: ratatui::layout::Rect) {
-        let rows: Vec<Row> = self
-            .sso_sessions
-            .iter()
-            .map(|session| {
-                // Calculate expiration status first
-                let (is_actually_active, expiration_status) = if session.is_active {
-                    if let Some(expiration) = session.token_expiration {
-                        let now = chrono::Utc::now();
-                        let remaining_secs = (expiration - now).num_seconds();
-
-                        if remaining_secs > 0 {
-                            let hours = remaining_secs / 3600;
-                            let mins = (remaining_secs % 3600) / 60;
-
-                            let display = if hours > 0 {
-                                format!("{}h {}m", hours, mins)
-                            } else {
-                                format!("{}m", mins)
-                            };
-                            (true, display)
-                        } else {
-                            (false, "EXPIRED".to_string())
-                        }
-                    } else {
-                        (true, "".to_string())
+        self.status_message = Some(format!("Refreshing {} profile(s)...", fetches.len()));
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            for (index, target) in fetches {
+                let result = match CredentialManager::new() {
+                    Ok(manager) => {
+                        manager
+                            .refresh_credentials(
+                                &instance,
+                                &token.access_token,
+                                &target.account,
+                                None,
+                            )
+                            .await
                     }
-                } else {
-                    (false, "".to_string())
+                    Err(e) => Err(e),
                 };
+                let _ = events.send(AppEvent::ProfileRefreshed {
+                    index,
+                    target,
+                    result,
+                });
+            }
+        });
+    }
 
-                // Status indicator based on actual expiration state
-                let status = if is_actually_active { "🟢" } else { "🔴" };
+    /// Write credentials fetched by `refresh_all_profiles`, and show the summary once every
+    /// profile has reported
+    pub(crate) fn apply_profile_refreshed(
+        &mut self,
+        index: usize,
+        target: StartTarget,
+        result: Result<RoleCredentials>,
+    ) {
+        let written = result.and_then(|creds| {
+            crate::aws_config::write_credentials_with_metadata(
+                &target.profile_name,
+                &creds,
+                &target.region,
+                target.output.as_deref(),
+                Some(&target.account),
+            )?;
+            Ok(creds.expiration_display())
+        });
+        let Some(lines) = self.refresh_all.as_mut() else {
+            return;
+        };
+        lines[index] = Some(match written {
+            Ok(expires_in) => format!("✓ {} (expires in {})", target.profile_name, expires_in),
+            Err(e) => format!("✗ {}: {}", target.profile_name, e),
+        });
 
-                Row::new(vec![
-                    Cell::new(Text::from(status).alignment(Alignment::Center)),
-                    Cell::new(
-                        Text::from(session.session_name.clone()).alignment(Alignment::Center),
-                    ),
-                    Cell::new(Text::from(session.start_url.clone()).alignment(Alignment::Center)),
-                    Cell::new(Text::from(expiration_status).alignment(Alignment::Center)),
-                ])
-            })
-            .collect();
+        let pending = lines.iter().filter(|line| line.is_none()).count();
+        if pending > 0 {
+            self.status_message = Some(format!(
+                "Refreshing profiles: {} of {} done",
+                lines.len() - pending,
+                lines.len()
+            ));
+            self.load_profile_expirations();
+            self.restatus_rows();
+            return;
+        }
+        self.finish_refresh_all();
+    }
 
-        let header = Row::new(vec![
-            Cell::new(Text::from("Status").alignment(Alignment::Center)),
-            Cell::new(Text::from("Session Name").alignment(Alignment::Center)),
-            Cell::new(Text::from("Start URL").alignment(Alignment::Center)),
-            Cell::new(Text::from("Expires").alignment(Alignment::Center)),
-        ])
-        .style(
-            Style::default()
-                .fg(catppuccin_color(self.theme.colors.blue))
-                .add_modifier(Modifier::BOLD),
-        )
-        .bottom_margin(1);
+    fn finish_refresh_all(&mut self) {
+        let lines: Vec<String> = self
+            .refresh_all
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .collect();
+        let failed = lines.iter().filter(|line| line.starts_with('✗')).count();
+        self.load_accounts();
+        self.last_auto_refresh = Some(std::time::Instant::now());
+
+        self.status_message = Some(format!(
+            "Refreshed {} of {} profile(s)",
+            lines.len() - failed,
+            lines.len()
+        ));
+        // The summary doesn't cover a dialog opened while the profiles refreshed
+        if self.state == AppState::Main {
+            self.state = AppState::Summary {
+                title: "Refresh All Profiles".to_string(),
+                lines,
+            };
+        }
+    }
 
-        // Highlight sessions pane if it's active
-        let sessions_block_style = if self.active_pane == ActivePane::Sessions {
-            Style::default().fg(catppuccin_color(self.theme.colors.mauve))
-        } else {
-            Style::default().fg(catppuccin_color(self.theme.colors.surface0))
+    /// Instance and token of a logged-in session, or of the current one for rows without
+    /// a session
+    pub(crate) fn session_login(
+        &self,
+        session_name: Option<&str>,
+    ) -> Option<(SsoInstance, SsoToken)> {
+        let Some(session_name) = session_name else {
+            return Some((self.sso_instance.clone()?, self.sso_token.clone()?));
         };
+        let session = self
+            .sessions
+            .items
+            .iter()
+            .find(|session| session.session_name == session_name)?;
+        Some((session.instance.clone(), session.token.clone()?))
+    }
 
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Length(6),  // Status
-                Constraint::Min(20),    // Session Name
-                Constraint::Min(30),    // Start URL
-                Constraint::Length(10), // Expiration
-            ],
-        )
-        .header(header)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("SSO Sessions")
-                .border_style(sessions_block_style),
-        )
-        .row_highlight_style(
-            Style::default()
-                .bg(catppuccin_color(self.theme.colors.surface1))
-                .add_modifier(Modifier::BOLD),
-        );
+    async fn get_credentials_for_role(&mut self, account: &AccountRole) -> Result<()> {
+        if let (Some(ref token), Some(ref instance)) = (&self.sso_token, &self.sso_instance) {
+            self.status_message = Some(format!(
+                "Getting credentials for {} / {}...",
+                account.account_name, account.role_name
+            ));
 
-        f.render_stateful_widget(table, area, &mut self.sessions_list_state);
+            match self
+                .credential_manager
+                .get_role_credentials(instance, &token.access_token, account, None)
+                .await
+            {
+                Ok(creds) => {
+                    self.status_message = Some(format!(
+                        "Credentials cached for {} / {} (expires in {})",
+                        account.account_name,
+                        account.role_name,
+                        creds.expiration_display()
+                    ));
+                }
+                Err(e) => {
+                    self.status_message = Some(format!("Error: {}", e));
+                }
+            }
+        }
+        Ok(())
+    }
 
-        // Render scrollbar for sessions pane
-        if !self.sso_sessions.is_empty() {
-            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
-                .begin_symbol(Some("↑"))
-                .end_symbol(Some("↓"));
+    /// Profile name suggested for a role, from `[profile_defaults] name_template`
+    pub(crate) fn default_profile_name(&self, account: &AccountRole) -> String {
+        let session_name = self.sessions.selected().map(|s| s.session_name.clone());
+        let ctx = ProfileNameContext {
+            account_name: &account.account_name,
+            account_id: &account.account_id,
+            role_name: &account.role_name,
+            session_name: session_name.as_deref(),
+        };
 
-            let mut scrollbar_state = ScrollbarState::new(self.sso_sessions.len())
-                .position(self.sessions_list_state.selected().unwrap_or(0));
+        profile_template::render(&self.config.profile_defaults.name_template, &ctx).unwrap_or_else(
+            |e| {
+                tracing::warn!("{}; using the default profile name template", e);
+                profile_template::render(profile_template::DEFAULT_TEMPLATE, &ctx)
+                    .unwrap_or_default()
+            },
+        )
+    }
 
-            f.render_stateful_widget(
-                scrollbar,
-                area.inner(ratatui::layout::Margin {
-                    vertical: 1,
-                    horizontal: 0,
-                }),
-                &mut scrollbar_state,
-            );
+    /// Take the device code the background login published, copying its URL once if
+    /// --copy or `[login] copy_to_clipboard` asks for it
+    fn poll_device_auth(&mut self) {
+        let Some(published) = self
+            .device_auth_info_arc
+            .as_ref()
+            .and_then(|arc| arc.lock().ok().map(|guard| guard.clone()))
+        else {
+            return;
+        };
+        self.device_auth_info = published;
+        if self.device_auth_info.is_some()
+            && self.device_url_copy_status.is_none()
+            && crate::clipboard::should_copy_device_url(&self.config)
+        {
+            self.copy_device_url();
         }
     }
 
-    fn draw_help_screen(&self, f: &mut Frame) {
-        let help_text = vec![
-            Line::from(Span::styled(
-                "awsom - Help",
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )),
-            Line::from(""),
-            Line::from("Navigation:"),
-            Line::from("  Tab         - Switch between Sessions and Accounts panes"),
-            Line::from("  ↑, k        - Move selection up"),
-            Line::from("  ↓, j        - Move selection down"),
-            Line::from(""),
-            Line::from("Sessions Pane:"),
-            Line::from("  Enter       - Login/Logout selected SSO session"),
-            Line::from("  a           - Add new SSO session"),
-            Line::from("  e           - Edit selected SSO session"),
-            Line::from("  d           - Delete selected SSO session"),
-            Line::from(""),
-            Line::from("Accounts Pane:"),
-            Line::from("  Enter       - Start/stop session (activate/invalidate credentials)"),
-            Line::from("  e           - Edit profile (name, region, output) for selected role"),
-            Line::from("  d           - Make selected role's profile the default"),
-            Line::from("  c           - Open AWS Console in browser for selected role"),
-            Line::from("  r           - Refresh account/role list"),
-            Line::from(""),
-            Line::from("General:"),
-            Line::from("  q, Esc      - Quit application"),
-            Line::from("  ?, F1       - Show this help screen"),
-            Line::from(""),
-            Line::from(Span::styled(
-                "Press any key to return to main screen",
-                Style::default().fg(Color::Yellow),
-            )),
-        ];
-
-        let help = Paragraph::new(help_text)
-            .block(Block::default().borders(Borders::ALL).title("Help"))
-            .style(Style::default().fg(Color::White));
-        f.render_widget(help, f.area());
-    }
-
-    fn draw_loading_screen(&mut self, f: &mut Frame) {
-        // Poll device_auth_info from Arc if available
-        if let Some(ref arc) = self.device_auth_info_arc {
-            if let Ok(guard) = arc.lock() {
-                self.device_auth_info = guard.clone();
-            }
-        }
-
-        let mut loading_text = vec![];
-
-        // Check if we're showing device auth info
+    /// Copy the device login URL to the clipboard and remember the outcome for display
+    pub(crate) fn copy_device_url(&mut self) {
         if let Some(ref auth_info) = self.device_auth_info {
-            loading_text.push(Line::from(Span::styled(
-                "AWS SSO Login",
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )));
-            loading_text.push(Line::from(""));
-
-            // Use complete URL with code if available, otherwise show URL + code separately
-            if let Some(ref complete_url) = auth_info.verification_uri_complete {
-                // Show single URL with code embedded
-                let instruction_text = if crate::env::is_headless_environment() {
-                    "Copy and paste this URL (code is already included):"
-                } else {
-                    "Browser opened automatically. If not, copy this URL:"
-                };
-
-                loading_text.push(Line::from(Span::styled(
-                    instruction_text,
-                    Style::default().fg(Color::White),
-                )));
-                loading_text.push(Line::from(""));
-                loading_text.push(Line::from(Span::styled(
-                    complete_url,
-                    Style::default()
-                        .fg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                )));
-            } else {
-                // Fallback: show URL and code separately
-                let instruction_text = if crate::env::is_headless_environment() {
-                    "Open this URL in a browser (on another machine if needed):"
-                } else {
-                    "Browser opened automatically. If not, visit:"
-                };
+            self.device_url_copy_status = Some(match auth_info.copy_url() {
+                Ok(()) => "✓ URL copied to clipboard".to_string(),
+                Err(e) => format!("Could not copy URL: {}", e),
+            });
+        }
+    }
 
-                loading_text.push(Line::from(Span::styled(
-                    instruction_text,
-                    Style::default().fg(Color::White),
-                )));
-                loading_text.push(Line::from(""));
-                loading_text.push(Line::from(Span::styled(
-                    &auth_info.verification_uri,
-                    Style::default().fg(Color::Green),
-                )));
-                loading_text.push(Line::from(""));
-                loading_text.push(Line::from(Span::styled(
-                    "And enter code:",
-                    Style::default().fg(Color::White),
-                )));
-                loading_text.push(Line::from(""));
-                loading_text.push(Line::from(Span::styled(
-                    &auth_info.user_code,
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                )));
-            }
-            loading_text.push(Line::from(""));
-            loading_text.push(Line::from(Span::styled(
-                "Waiting for authorization...",
-                Style::default().fg(Color::Gray),
-            )));
-            loading_text.push(Line::from(""));
-            loading_text.push(Line::from(Span::styled(
-                "Press 'q' or 'Esc' to cancel",
-                Style::default().fg(Color::Yellow),
-            )));
-        } else {
-            // Generic loading message
-            loading_text.push(Line::from(""));
-            loading_text.push(Line::from(Span::styled(
-                "Loading...",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )));
-        }
-
-        let loading = Paragraph::new(loading_text)
-            .block(Block::default().borders(Borders::ALL))
-            .style(Style::default().fg(Color::White));
-        f.render_widget(loading, f.area());
-    }
-
-    fn draw_error_screen(&self, f: &mut Frame, message: String) {
-        let error_text = vec![
-            Line::from(Span::styled(
-                "Error",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            )),
-            Line::from(""),
-            Line::from(message),
-            Line::from(""),
-            Line::from(Span::styled(
-                "Press any key to continue",
-                Style::default().fg(Color::Yellow),
-            )),
-        ];
-
-        let error = Paragraph::new(error_text)
-            .block(Block::default().borders(Borders::ALL).title("Error"))
-            .style(Style::default().fg(Color::White));
-        f.render_widget(error, f.area());
-    }
-
-    fn draw_profile_input_screen(&self, f: &mut Frame) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3), // Title
-                Constraint::Length(5), // Info
-                Constraint::Length(3), // Input
-                Constraint::Min(0),    // Spacer
-                Constraint::Length(2), // Instructions
-            ])
-            .split(f.area());
-
-        // Title
-        let title = Paragraph::new("Save AWS Profile")
-            .style(
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .block(Block::default().borders(Borders::ALL));
-        f.render_widget(title, chunks[0]);
-
-        // Info about the role
-        let info_text = if let Some(ref role) = self.pending_role {
-            vec![
-                Line::from(format!(
-                    "Account: {} ({})",
-                    role.account_name, role.account_id
-                )),
-                Line::from(format!("Role: {}", role.role_name)),
-                Line::from(""),
-                Line::from("Enter a profile name (or press Enter to use default):"),
-            ]
-        } else {
-            vec![Line::from("No role selected")]
+    /// Copy the selected active role's `export` lines, JSON credentials or profile name
+    fn copy_credentials(&mut self) {
+        let Some(selected) = self
+            .accounts
+            .selected_index(&self.config)
+            .and_then(|index| self.accounts.items.get(index))
+        else {
+            self.status_message = Some("No role selected".to_string());
+            return;
         };
+        let account = selected.account_role.clone();
+        let profile_name = selected.profile_name.clone();
+        if !selected.is_active {
+            self.status_message = Some(format!(
+                "{} / {} has no active credentials; press Enter to start them",
+                account.account_name, account.role_name
+            ));
+            return;
+        }
 
-        let info = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
-        f.render_widget(info, chunks[1]);
+        let format = self.config.ui.copy_format;
+        if format == CopyFormat::Profile {
+            let text = profile_name.ok_or_else(|| {
+                SsoError::ConfigError("The role has no profile to copy".to_string())
+            });
+            self.apply_exported_credentials(&account, format, text);
+            return;
+        }
+        self.export_credentials(account, format);
+    }
 
-        // Input field with cursor at the correct position
-        let input_with_cursor = if self.profile_input.is_empty() {
-            "█".to_string()
-        } else {
-            // Split the string at cursor position and insert cursor character
-            let (before, after) = self.profile_input.split_at(self.profile_input_cursor);
-            format!("{}█{}", before, after)
+    /// Fetch the role's credentials in the background, rendered as `profile export` prints
+    /// them with the profile's region, for `apply_exported_credentials` to copy
+    fn export_credentials(&mut self, account: AccountRole, format: CopyFormat) {
+        let (Some(token), Some(instance)) = (self.sso_token.clone(), self.sso_instance.clone())
+        else {
+            self.apply_exported_credentials(&account, format, Err(SsoError::NoSessionFound));
+            return;
         };
-        let input = Paragraph::new(input_with_cursor.as_str())
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title("Profile Name"));
-        f.render_widget(input, chunks[2]);
-
-        // Instructions
-        let instructions = Paragraph::new(
-            "Enter: Save | Esc: Cancel | ←→: Move cursor | Home/End: Jump | Type to edit",
-        )
-        .style(Style::default().fg(Color::Gray));
-        f.render_widget(instructions, chunks[4]);
-    }
-
-    fn draw_sso_config_input_screen(&self, f: &mut Frame, step: SsoConfigStep) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),  // Title
-                Constraint::Length(10), // Instructions
-                Constraint::Length(3),  // Input
-                Constraint::Min(0),     // Spacer
-                Constraint::Length(2),  // Help
-            ])
-            .split(f.area());
-
-        // Title
-        let title = Paragraph::new("AWS SSO Configuration")
-            .style(
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .block(Block::default().borders(Borders::ALL));
-        f.render_widget(title, chunks[0]);
-
-        // Instructions based on current step
-        let (step_title, instructions, example) = match step {
-            SsoConfigStep::StartUrl => (
-                "Step 1 of 3: SSO Start URL",
-                "Enter your AWS SSO start URL (IAM Identity Center portal URL)",
-                "Example: https://my-org.awsapps.com/start",
-            ),
-            SsoConfigStep::Region => (
-                "Step 2 of 3: SSO Region",
-                "Enter the AWS Region where SSO is configured",
-                "Example: us-east-1",
-            ),
-            SsoConfigStep::SessionName => (
-                "Step 3 of 3: Session Name",
-                "Enter a name for this SSO session (optional)",
-                "Default: default-sso",
+        let region = crate::aws_config::get_existing_profile_name(&account)
+            .ok()
+            .flatten()
+            .and_then(|profile| {
+                crate::aws_config::get_profile_details(&profile)
+                    .ok()
+                    .flatten()
+            })
+            .and_then(|details| details.region)
+            .unwrap_or_else(|| instance.region.clone());
+        self.status_message = Some(format!(
+            "Reading credentials of {} / {}...",
+            account.account_name, account.role_name
+        ));
+
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = async {
+                let creds = CredentialManager::new()?
+                    .get_role_credentials(&instance, &token.access_token, &account, None)
+                    .await?;
+                let exported = ExportedCredentials::new(
+                    &account.account_id,
+                    &account.role_name,
+                    &region,
+                    &creds,
+                    None,
+                );
+                match format {
+                    CopyFormat::Json => OutputFormat::Json.render(&exported),
+                    _ => OutputFormat::Text.render(&exported),
+                }
+            }
+            .await;
+            let _ = events.send(AppEvent::ExportedCredentials {
+                account,
+                format,
+                result,
+            });
+        });
+    }
+
+    /// Copy what `y` copies for the role, or report why there's nothing to copy
+    pub(crate) fn apply_exported_credentials(
+        &mut self,
+        account: &AccountRole,
+        format: CopyFormat,
+        text: Result<String>,
+    ) {
+        let copied = text.and_then(|text| crate::clipboard::copy(&text));
+        self.status_message = Some(match copied {
+            Ok(()) => format!(
+                "✓ Copied {} of {} / {}",
+                match format {
+                    CopyFormat::Env => "export lines",
+                    CopyFormat::Json => "JSON credentials",
+                    CopyFormat::Profile => "the profile name",
+                },
+                account.account_name,
+                account.role_name
             ),
-        };
+            Err(e) => format!("Error copying credentials: {}", e),
+        });
+    }
 
-        let info_text = vec![
-            Line::from(Span::styled(
-                step_title,
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )),
-            Line::from(""),
-            Line::from(instructions),
-            Line::from(""),
-            Line::from(Span::styled(example, Style::default().fg(Color::Gray))),
-            Line::from(""),
-            Line::from("The configuration will be saved to ~/.aws/config"),
-            Line::from("as a [sso-session] section."),
-        ];
-
-        let info = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
-        f.render_widget(info, chunks[1]);
-
-        // Input field with cursor
-        let (current_input, field_label) = match step {
-            SsoConfigStep::StartUrl => (&self.sso_start_url_input, "SSO Start URL"),
-            SsoConfigStep::Region => (&self.sso_region_input, "SSO Region"),
-            SsoConfigStep::SessionName => (&self.sso_session_name_input, "Session Name"),
+    /// Open the console as `account` in the browser, or with `copy_url` put the sign-in URL
+    /// on the clipboard instead, for a browser on another machine
+    ///
+    /// The credentials and the sign-in token are fetched in the background, so a slow
+    /// federation endpoint doesn't freeze the screen.
+    pub(crate) fn open_console(&mut self, account: &AccountRole, region: &str, copy_url: bool) {
+        let (Some(token), Some(instance)) = (self.sso_token.clone(), self.sso_instance.clone())
+        else {
+            return;
         };
-
-        let input_with_cursor = if current_input.is_empty() {
-            "█".to_string()
+        self.status_message = Some(if copy_url {
+            "Creating AWS Console sign-in URL...".to_string()
         } else {
-            let (before, after) = current_input.split_at(self.sso_input_cursor);
-            format!("{}█{}", before, after)
-        };
+            "Opening AWS Console in browser...".to_string()
+        });
+
+        let events = self.events_tx.clone();
+        let account = account.clone();
+        let region = region.to_string();
+        let sign_in = SignIn::from_config(&self.config.console, None);
+        tokio::spawn(async move {
+            let result = match CredentialManager::new() {
+                Ok(manager) => {
+                    manager
+                        .get_role_credentials(&instance, &token.access_token, &account, None)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            let result = match result {
+                Ok(creds) => {
+                    crate::console::generate_console_url(&creds, Some(&region), &sign_in).await
+                }
+                Err(e) => Err(e),
+            };
+            let _ = events.send(AppEvent::ConsoleUrl {
+                instance,
+                account,
+                region,
+                copy_url,
+                result,
+            });
+        });
+    }
 
-        let input = Paragraph::new(input_with_cursor.as_str())
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title(field_label));
-        f.render_widget(input, chunks[2]);
-
-        // Help
-        let help = Paragraph::new("Enter: Next | Esc: Cancel | ←→: Move cursor | Type to edit")
-            .style(Style::default().fg(Color::Gray));
-        f.render_widget(help, chunks[4]);
-    }
-
-    fn draw_defaults_config_input_screen(&self, f: &mut Frame, step: DefaultsConfigStep) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),  // Title
-                Constraint::Length(10), // Instructions
-                Constraint::Length(3),  // Input
-                Constraint::Min(0),     // Spacer
-                Constraint::Length(2),  // Help
-            ])
-            .split(f.area());
-
-        // Title
-        let title = Paragraph::new("Configure Default Profile Settings")
-            .style(
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .block(Block::default().borders(Borders::ALL));
-        f.render_widget(title, chunks[0]);
-
-        // Instructions
-        let (step_title, instructions, example) = match step {
-            DefaultsConfigStep::Region => (
-                "Step 1 of 2: Default Region",
-                "Enter the default AWS region for new profiles",
-                "Example: us-east-1, eu-west-1, ap-southeast-2",
-            ),
-            DefaultsConfigStep::Output => (
-                "Step 2 of 2: Default Output Format",
-                "Enter the default output format for AWS CLI",
-                "Options: json, text, table, yaml",
-            ),
+    /// Show what the selected role grants: its permission set's description and its session
+    /// duration, read in the background
+    pub(crate) fn show_role_details(&mut self) {
+        let Some(account) = self
+            .accounts
+            .selected_index(&self.config)
+            .and_then(|index| self.accounts.items.get(index))
+            .map(|a| a.account_role.clone())
+        else {
+            self.status_message = Some("No role selected".to_string());
+            return;
         };
-
-        let info_text = vec![
-            Line::from(Span::styled(
-                step_title,
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )),
-            Line::from(""),
-            Line::from(instructions),
-            Line::from(""),
-            Line::from(Span::styled(example, Style::default().fg(Color::Gray))),
-            Line::from(""),
-            Line::from("These settings will be saved to ~/.aws/config as:"),
-            Line::from("[profile awsom-defaults]"),
-            Line::from("This allows awsom to provide defaults without interfering with"),
-            Line::from("your [default] profile."),
-        ];
-
-        let info = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
-        f.render_widget(info, chunks[1]);
-
-        // Input field
-        let (current_input, field_label) = match step {
-            DefaultsConfigStep::Region => (&self.default_region_input, "Default Region"),
-            DefaultsConfigStep::Output => (&self.default_output_input, "Default Output Format"),
+        let (Some(token), Some(instance)) = (self.sso_token.clone(), self.sso_instance.clone())
+        else {
+            self.status_message = Some("Log in to a session first".to_string());
+            return;
         };
-
-        let input_with_cursor = if current_input.is_empty() {
-            "█".to_string()
-        } else {
-            let (before, after) = current_input.split_at(self.default_input_cursor);
-            format!("{}█{}", before, after)
+        self.state = AppState::RoleDetails {
+            account: account.clone(),
+            details: None,
         };
 
-        let input = Paragraph::new(input_with_cursor.as_str())
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title(field_label));
-        f.render_widget(input, chunks[2]);
-
-        // Help
-        let help = Paragraph::new("Enter: Next | Esc: Cancel | ←→: Move cursor | Type to edit")
-            .style(Style::default().fg(Color::Gray));
-        f.render_widget(help, chunks[4]);
-    }
-
-    fn draw_new_profile_config_input_screen(&self, f: &mut Frame, step: NewProfileConfigStep) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),  // Title
-                Constraint::Length(12), // Instructions
-                Constraint::Length(3),  // Input
-                Constraint::Min(0),     // Spacer
-                Constraint::Length(2),  // Help
-            ])
-            .split(f.area());
-
-        // Title
-        let title = Paragraph::new("Configure New AWS Profile")
-            .style(
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .block(Block::default().borders(Borders::ALL));
-        f.render_widget(title, chunks[0]);
-
-        // Instructions
-        let (step_title, instructions, example) = match step {
-            NewProfileConfigStep::ProfileName => (
-                "Step 1 of 3: Profile Name",
-                "Enter a name for this AWS profile",
-                "Example: my-prod-account, dev-readonly",
-            ),
-            NewProfileConfigStep::Region => (
-                "Step 2 of 3: Region",
-                "Enter the AWS region for this profile",
-                "Example: us-east-1, eu-west-1, ap-southeast-2",
-            ),
-            NewProfileConfigStep::Output => (
-                "Step 3 of 3: Output Format",
-                "Enter the output format for AWS CLI",
-                "Options: json, text, table, yaml",
-            ),
-        };
+        let events = self.events_tx.clone();
+        let cached = self.permission_sets.get(&instance.start_url).cloned();
+        let admin_role = self.organizations_role();
+        tokio::spawn(async move {
+            let (permission_sets, fetched) = match cached {
+                Some(sets) => (Ok(sets), false),
+                None => (
+                    read_permission_sets(&instance, &token, admin_role).await,
+                    true,
+                ),
+            };
+            let permission_set = permission_sets
+                .as_ref()
+                .ok()
+                .and_then(|sets| sets.iter().find(|s| s.name == account.role_name));
+            let mut details = RoleDetails {
+                session_duration: permission_set.and_then(|s| s.session_duration),
+                description: permission_set.and_then(|s| s.description.clone()),
+                permission_set_error: match (&permission_sets, permission_set) {
+                    (Err(e), _) => Some(e.to_string()),
+                    (Ok(_), None) => Some(format!("no permission set named {}", account.role_name)),
+                    (Ok(_), Some(_)) => None,
+                },
+            };
 
-        let info_text = vec![
-            Line::from(Span::styled(
-                step_title,
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )),
-            Line::from(""),
-            Line::from(instructions),
-            Line::from(""),
-            Line::from(Span::styled(example, Style::default().fg(Color::Gray))),
-            Line::from(""),
-            Line::from("The profile will be saved to ~/.aws/config and"),
-            Line::from("credentials will be written to ~/.aws/credentials"),
-            Line::from(""),
-            Line::from(Span::styled(
-                "After configuration, credentials will be fetched automatically.",
-                Style::default().fg(Color::Green),
-            )),
-        ];
-
-        let info = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
-        f.render_widget(info, chunks[1]);
-
-        // Input field
-        let (current_input, field_label, cursor_pos) = match step {
-            NewProfileConfigStep::ProfileName => (
-                &self.new_profile_name_input,
-                "Profile Name",
-                self.new_profile_input_cursor,
-            ),
-            NewProfileConfigStep::Region => (
-                &self.new_profile_region_input,
-                "Region",
-                self.new_profile_input_cursor,
-            ),
-            NewProfileConfigStep::Output => (
-                &self.new_profile_output_input,
-                "Output Format",
-                self.new_profile_input_cursor,
-            ),
-        };
+            if details.session_duration.is_none() {
+                details.session_duration = cached_session_duration(&instance, &account);
+            }
 
-        let input_with_cursor = if current_input.is_empty() {
-            "█".to_string()
-        } else {
-            let (before, after) = current_input.split_at(cursor_pos);
-            format!("{}█{}", before, after)
-        };
+            let _ = events.send(AppEvent::RoleDetails {
+                instance,
+                account,
+                details,
+                permission_sets: permission_sets.ok().filter(|_| fetched),
+            });
+        });
+    }
 
-        let input = Paragraph::new(input_with_cursor.as_str())
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title(field_label));
-        f.render_widget(input, chunks[2]);
+    /// Show details read by `show_role_details`, if the popup is still open for the role
+    fn apply_role_details(
+        &mut self,
+        instance: SsoInstance,
+        account: AccountRole,
+        details: RoleDetails,
+        permission_sets: Option<Vec<PermissionSet>>,
+    ) {
+        if let Some(sets) = permission_sets {
+            self.permission_sets.insert(instance.start_url, sets);
+        }
+        if let AppState::RoleDetails {
+            account: shown,
+            details: shown_details,
+        } = &mut self.state
+        {
+            if *shown == account {
+                *shown_details = Some(details);
+            }
+        }
+    }
+
+    /// Open or copy a sign-in URL made by `open_console`
+    fn apply_console_url(
+        &mut self,
+        instance: &SsoInstance,
+        account: &AccountRole,
+        region: &str,
+        copy_url: bool,
+        result: Result<String>,
+    ) {
+        let result = result.and_then(|url| {
+            if copy_url {
+                crate::clipboard::copy(&url)
+            } else {
+                crate::console::open_url(&url)
+            }
+        });
+        if let Err(e) = result {
+            self.status_message = Some(format!("Error opening console: {}", e));
+            return;
+        }
+
+        crate::audit::record(
+            crate::audit::AuditAction::ConsoleOpened,
+            Some(&account.account_id),
+            Some(&account.role_name),
+            None,
+        );
+        crate::usage::record(
+            &self.config.stats,
+            &account.account_id,
+            &account.role_name,
+            None,
+        );
+        if copy_url {
+            self.status_message = Some(format!(
+                "✓ Copied the console sign-in URL for {} / {} in {} (valid for 15 minutes)",
+                account.account_name, account.role_name, region
+            ));
+            return;
+        }
+        hooks::run(
+            HookEvent::ConsoleOpen,
+            &HookContext {
+                session: instance.session_name.as_deref(),
+                start_url: Some(&instance.start_url),
+                account_id: Some(&account.account_id),
+                role_name: Some(&account.role_name),
+                region: Some(region),
+                ..HookContext::default()
+            },
+        );
+        self.status_message = Some(format!(
+            "✓ Opened AWS Console for {} / {} in {}",
+            account.account_name, account.role_name, region
+        ));
+    }
 
-        // Help
-        let help = Paragraph::new("Enter: Next | Esc: Cancel | ←→: Move cursor | Type to edit")
-            .style(Style::default().fg(Color::Gray));
-        f.render_widget(help, chunks[4]);
+    pub(crate) fn ui(&mut self, f: &mut Frame) {
+        match &self.state {
+            AppState::Main => self.draw_main_screen(f),
+            AppState::Help => self.draw_help_screen(f),
+            AppState::Loading => self.draw_loading_screen(f),
+            AppState::Error(msg) => self.draw_error_screen(f, msg.clone()),
+            AppState::ProfileInput => self.draw_profile_input_screen(f),
+            AppState::SsoConfigInput { step } => self.draw_sso_config_input_screen(f, step.clone()),
+            AppState::DefaultsConfigInput { step } => {
+                self.draw_defaults_config_input_screen(f, step.clone())
+            }
+            AppState::NewProfileConfigInput { step } => {
+                self.draw_new_profile_config_input_screen(f, step.clone())
+            }
+            AppState::ConfirmationDialog { title, message } => {
+                self.draw_confirmation_dialog(f, title.clone(), message.clone())
+            }
+            AppState::Summary { title, lines } => {
+                let (title, lines) = (title.clone(), lines.clone());
+                self.draw_main_screen(f);
+                self.draw_summary(f, &title, lines);
+            }
+            AppState::RegionPicker { account } => {
+                let title = format!(
+                    "Console Region - {} / {}",
+                    account.account_name, account.role_name
+                );
+                self.draw_main_screen(f);
+                self.draw_region_picker(f, title);
+            }
+            AppState::TagInput { account } => {
+                let title = format!("Tags - {} / {}", account.account_name, account.role_name);
+                let account_tags = self
+                    .config
+                    .tags_for(&account.account_id, &account.role_name)
+                    .into_iter()
+                    .filter(|tag| {
+                        !self
+                            .config
+                            .role_tags(&account.account_id, &account.role_name)
+                            .iter()
+                            .any(|t| t == tag)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                self.draw_main_screen(f);
+                self.draw_tag_input(f, title, account_tags);
+            }
+            AppState::ClusterInput { account, region } => {
+                let title = format!(
+                    "EKS Cluster - {} / {}",
+                    account.account_name, account.role_name
+                );
+                let region = region.clone();
+                self.draw_main_screen(f);
+                self.draw_cluster_input(f, title, &region);
+            }
+            AppState::WorkspacePicker => {
+                self.draw_main_screen(f);
+                self.draw_workspace_picker(f);
+            }
+            AppState::BulkMenu => {
+                self.draw_main_screen(f);
+                self.draw_bulk_menu(f);
+            }
+            AppState::ProfileConflict {
+                profile_name,
+                suffixed,
+                ..
+            } => {
+                let (profile_name, suffixed) = (profile_name.clone(), suffixed.clone());
+                self.draw_main_screen(f);
+                self.draw_profile_conflict(f, &profile_name, &suffixed);
+            }
+            AppState::CommandLine => {
+                self.draw_main_screen(f);
+                self.draw_command_palette(f);
+            }
+            AppState::Messages { log } => {
+                let log = log.clone();
+                self.draw_main_screen(f);
+                self.draw_messages(f, &log);
+            }
+            AppState::ProfileSettings { profile, field } => {
+                let (profile, field) = (profile.clone(), field.clone());
+                self.draw_main_screen(f);
+                self.draw_profile_settings(f, &profile, &field);
+            }
+            AppState::RoleDetails { account, details } => {
+                let (account, details) = (account.clone(), details.clone());
+                self.draw_main_screen(f);
+                self.draw_role_details(f, &account, details.as_ref());
+            }
+            AppState::Applications {
+                applications,
+                selected,
+            } => {
+                let (applications, selected) = (applications.clone(), *selected);
+                self.draw_main_screen(f);
+                self.draw_applications(f, applications.as_ref(), selected);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::snapshots;
+
+    #[test]
+    fn test_refresh_all_keeps_to_the_selected_session() {
+        let mut app = snapshots::app();
+        app.config.ui.all_sessions = true;
+        let staging = &mut app.accounts.items[2];
+        staging.is_active = true;
+        staging.profile_name = Some("staging-dev".to_string());
+        staging.session_name = Some("sandbox".to_string());
+
+        let profiles = |app: &super::App| -> Vec<String> {
+            let targets = app.refresh_targets();
+            targets.into_iter().map(|(_, profile)| profile).collect()
+        };
+        assert_eq!(profiles(&app), ["prod-admin"]);
+        app.sessions.state.select(Some(1));
+        assert_eq!(profiles(&app), ["staging-dev"]);
     }
 }
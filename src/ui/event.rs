@@ -0,0 +1,152 @@
+// Results of the TUI's background tasks
+//
+// Logins, account/role discovery, console sign-ins, credentials to copy, role details, the
+// application list, bulk starts, Refresh All, the OU tree and kubeconfig updates run as tokio
+// tasks and report back over one channel, which the event loop drains before drawing each
+// frame.
+use super::app::RoleDetails;
+use super::screens::StartTarget;
+use crate::applications::Application;
+use crate::config::CopyFormat;
+use crate::error::Result;
+use crate::models::{AccountRole, RoleCredentials, SsoInstance, SsoToken};
+use crate::orgs::OrgTree;
+use crate::permission_sets::PermissionSet;
+
+pub enum AppEvent {
+    Login(LoginResult),
+    /// From the discovery with this id; events of one that was replaced are dropped
+    Discovery {
+        id: u64,
+        event: DiscoveryEvent,
+    },
+    /// Credentials fetched for one of the favorites prefetched after a login; the event loop
+    /// writes them to the role's profile
+    Prefetched {
+        instance: SsoInstance,
+        role: AccountRole,
+        result: Result<RoleCredentials>,
+        done: usize,
+        total: usize,
+    },
+    /// A console sign-in URL, or why none could be made; the event loop opens or copies it
+    ConsoleUrl {
+        instance: SsoInstance,
+        account: AccountRole,
+        region: String,
+        copy_url: bool,
+        result: Result<String>,
+    },
+    /// What the details popup shows for a role, and the permission sets read for it, if
+    /// they weren't cached
+    RoleDetails {
+        instance: SsoInstance,
+        account: AccountRole,
+        details: RoleDetails,
+        permission_sets: Option<Vec<PermissionSet>>,
+    },
+    /// The applications assigned in the session, for the applications popup
+    Applications {
+        instance: SsoInstance,
+        result: Result<Vec<Application>>,
+    },
+    /// Credentials fetched for the marked role at `index` of a bulk start; the event loop
+    /// writes them to the target profile
+    BulkStarted {
+        index: usize,
+        target: StartTarget,
+        result: Result<RoleCredentials>,
+    },
+    /// New credentials for the profile at `index` of Refresh All; the event loop writes them
+    ProfileRefreshed {
+        index: usize,
+        target: StartTarget,
+        result: Result<RoleCredentials>,
+    },
+    /// A role's credentials rendered for the clipboard in `format`; the event loop copies
+    /// them
+    ExportedCredentials {
+        account: AccountRole,
+        format: CopyFormat,
+        result: Result<String>,
+    },
+    /// The organization read with the [organizations] role for the session of `instance`;
+    /// with `group`, the accounts pane is to be grouped by OU once it's there
+    OrgTree {
+        instance: SsoInstance,
+        group: bool,
+        result: Result<OrgTree>,
+    },
+    /// A cluster added to the kubeconfig as `account`: the context name, and whether
+    /// comments in the old file were dropped
+    Kubeconfig {
+        account: AccountRole,
+        result: Result<(String, bool)>,
+    },
+}
+
+/// Outcome of a background login
+pub enum LoginResult {
+    Success {
+        session_index: usize,
+        token: Box<SsoToken>,
+        instance: SsoInstance,
+        session_name: String,
+    },
+    Error {
+        message: String,
+    },
+    Cancelled,
+}
+
+/// Progress of the background account/role discovery task
+pub enum DiscoveryEvent {
+    /// A session's cached listing, sent before anything is fetched for it; `fresh` means
+    /// nothing will be
+    Cached {
+        session: Option<String>,
+        roles: Vec<AccountRole>,
+        fresh: bool,
+    },
+    /// One account's roles, and how many of the session's accounts are done out of the total
+    Roles {
+        session: Option<String>,
+        roles: Vec<AccountRole>,
+        done: usize,
+        total: usize,
+    },
+    /// Listing one of several sessions failed; the others are still listed
+    SessionFailed {
+        session: String,
+        error: String,
+        /// AWS couldn't be reached at all
+        offline: bool,
+    },
+    Finished(Result<()>),
+}
+
+/// A running account/role discovery
+pub struct Discovery {
+    pub id: u64,
+    pub task: tokio::task::JoinHandle<()>,
+    /// Roles received so far, with the session each was listed from
+    pub roles: Vec<(Option<String>, AccountRole)>,
+    /// Add rows to the list as they arrive instead of replacing it at the end
+    pub live: bool,
+    /// The rows are the cached listing, not a fresh one
+    pub from_cache: bool,
+    /// Progress text last put in the status bar
+    pub status: String,
+    /// Accounts whose roles are listed so far out of the session's total, for the gauge
+    pub progress: Option<(usize, usize)>,
+    /// "session: error" for each session that couldn't be listed
+    pub failed: Vec<String>,
+    /// A session couldn't be listed because AWS was unreachable
+    pub offline: bool,
+}
+
+impl Drop for Discovery {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
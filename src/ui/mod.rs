@@ -1,6 +1,13 @@
 // TUI module
+mod action;
 pub mod app;
+mod event;
+mod palette;
 pub mod screens;
+#[cfg(test)]
+mod snapshots;
+pub mod terminal;
+mod theme;
 pub mod widgets;
 
 pub use app::App;
@@ -0,0 +1,153 @@
+// Command palette: every main screen action, found by fuzzy search on the `:` line
+//
+// Typing after `:` (or Ctrl+P) narrows the list; Enter runs the highlighted entry unless the
+// line is one of the typed commands (`tag`, `group`), which still run as before.
+use super::action::Action;
+
+/// What picking a palette entry does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Carry out a main screen action
+    Run(Action),
+    /// Run a typed command, e.g. `group ou`
+    Line(&'static str),
+    /// Put the start of a command on the `:` line for the rest to be typed
+    Type(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub name: &'static str,
+    /// Key that does the same on the main screen, if any
+    pub key: &'static str,
+    pub command: Command,
+}
+
+const fn run(name: &'static str, key: &'static str, action: Action) -> Entry {
+    Entry {
+        name,
+        key,
+        command: Command::Run(action),
+    }
+}
+
+const fn line(name: &'static str, key: &'static str, line: &'static str) -> Entry {
+    Entry {
+        name,
+        key,
+        command: Command::Line(line),
+    }
+}
+
+pub const ENTRIES: &[Entry] = &[
+    run("Log in to session", "", Action::Login),
+    run("Log out of session", "", Action::Logout),
+    run("Add session", "a", Action::AddSession),
+    run("Edit session", "e", Action::EditSession),
+    run("Delete session", "d", Action::DeleteSession),
+    run("Start/stop role credentials", "Enter", Action::ToggleRole),
+    run("Refresh account list", "r", Action::RefreshAccounts),
+    run(
+        "Refresh all active profiles",
+        "R",
+        Action::RefreshAllProfiles,
+    ),
+    run("Open AWS console", "c", Action::OpenConsole),
+    run("Copy credentials", "y", Action::CopyCredentials),
+    run("Show role details", "i", Action::ShowRoleDetails),
+    run("Mark/unmark role", "Space", Action::ToggleMark),
+    run("Bulk actions on marked roles", "b", Action::BulkActions),
+    run("Edit profile", "e", Action::EditProfile),
+    run("Profile settings", "o", Action::ProfileSettings),
+    run("Make profile default", "d", Action::MakeDefault),
+    run("Pin/unpin favorite", "f", Action::ToggleFavorite),
+    run("Edit tags", "t", Action::EditTags),
+    Entry {
+        name: "Filter by tag",
+        key: ":tag",
+        command: Command::Type("tag "),
+    },
+    line("Clear tag filter", ":tag", "tag"),
+    line("Group by OU", ":group ou", "group ou"),
+    line("Group by tag", ":group tag", "group tag"),
+    line("Ungroup", ":group none", "group none"),
+    run("Cycle sort column", "s", Action::CycleSort),
+    run("Reverse sort order", "S", Action::ReverseSort),
+    run("Update kubeconfig (EKS)", "K", Action::UpdateKubeconfig),
+    run("Switch workspace", "w", Action::SwitchWorkspace),
+    run("Toggle all sessions' roles", "A", Action::ToggleAllSessions),
+    run("Pause/resume auto refresh", "P", Action::ToggleAutoRefresh),
+    run(
+        "Applications on the access portal",
+        "p",
+        Action::ShowApplications,
+    ),
+    run("Cycle theme", "T", Action::CycleTheme),
+    run("Show messages", "m", Action::ShowMessages),
+    run("Help", "?", Action::ShowHelp),
+    run("Quit", "q", Action::Quit),
+];
+
+/// Whether the `:` line is a typed command rather than a palette search
+pub fn is_command_line(input: &str) -> bool {
+    matches!(input.split_whitespace().next(), Some("tag" | "group"))
+}
+
+/// Entries matching `query`, best match first; all of them, in order, for an empty query
+pub fn search(query: &str) -> Vec<&'static Entry> {
+    let mut scored: Vec<(i32, usize, &Entry)> = ENTRIES
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| Some((fuzzy_score(query, entry.name)?, index, entry)))
+        .collect();
+    scored.sort_by_key(|&(score, index, _)| (-score, index));
+    scored.into_iter().map(|(_, _, entry)| entry).collect()
+}
+
+/// Score of `text` containing the letters of `query` in order, ignoring case and spaces;
+/// runs of consecutive letters and letters starting a word score higher
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 3;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        assert_eq!(search("").len(), ENTRIES.len());
+        assert_eq!(
+            search("console")[0].command,
+            Command::Run(Action::OpenConsole)
+        );
+        // Word starts beat letters scattered through other names
+        assert_eq!(search("lo")[0].command, Command::Run(Action::Login));
+        assert_eq!(
+            search("rap")[0].command,
+            Command::Run(Action::RefreshAllProfiles)
+        );
+        assert_eq!(search("GROUP OU")[0].command, Command::Line("group ou"));
+        assert!(search("zzz").is_empty());
+
+        assert!(is_command_line("tag prod"));
+        assert!(is_command_line(" group ou"));
+        assert!(!is_command_line("tags"));
+    }
+}
@@ -0,0 +1,165 @@
+// The applications assigned besides AWS accounts (SAML and other apps on the access
+// portal), listed in a popup and opened through the start page
+use crate::applications::{self, Application};
+use crate::error::Result;
+use crate::ui::app::{App, AppState};
+use crate::ui::event::AppEvent;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+impl App {
+    /// Open the applications popup for the selected session, reading the list in the
+    /// background
+    pub(crate) fn show_applications(&mut self) {
+        let (Some(token), Some(instance)) = (self.sso_token.clone(), self.sso_instance.clone())
+        else {
+            self.status_message = Some("Log in to a session first".to_string());
+            return;
+        };
+        self.state = AppState::Applications {
+            applications: None,
+            selected: 0,
+        };
+
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = applications::list(&instance.region, &token.access_token).await;
+            let _ = events.send(AppEvent::Applications { instance, result });
+        });
+    }
+
+    /// Show the list read by `show_applications`, if the popup is still open
+    pub(crate) fn apply_applications(&mut self, result: Result<Vec<Application>>) {
+        if let AppState::Applications { applications, .. } = &mut self.state {
+            *applications = Some(result.map_err(|e| e.to_string()));
+        }
+    }
+
+    pub(crate) fn handle_applications_key(&mut self, key: KeyCode) {
+        let AppState::Applications {
+            applications,
+            selected,
+        } = &mut self.state
+        else {
+            return;
+        };
+        let count = match applications {
+            Some(Ok(apps)) => apps.len(),
+            _ => 0,
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.state = AppState::Main,
+            KeyCode::Down | KeyCode::Char('j') if count > 0 => *selected = (*selected + 1) % count,
+            KeyCode::Up | KeyCode::Char('k') if count > 0 => {
+                *selected = (*selected + count - 1) % count
+            }
+            KeyCode::Enter => {
+                let app = match applications {
+                    Some(Ok(apps)) => apps.get(*selected).cloned(),
+                    _ => None,
+                };
+                let start_url = self.sso_instance.as_ref().map(|i| i.start_url.clone());
+                if let (Some(app), Some(start_url)) = (app, start_url) {
+                    self.state = AppState::Main;
+                    let url = applications::portal_url(&start_url, &app);
+                    self.status_message = Some(match crate::console::open_url(&url) {
+                        Ok(()) => format!("Opening {} in the browser...", app.name),
+                        Err(e) => format!("Error opening {}: {}", app.name, e),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Popup listing the applications, or why they couldn't be read
+    pub(crate) fn draw_applications(
+        &self,
+        f: &mut Frame,
+        applications: Option<&std::result::Result<Vec<Application>, String>>,
+        selected: usize,
+    ) {
+        let area = f.area();
+        let width = 70.min(area.width);
+        let rows = match applications {
+            Some(Ok(apps)) => apps.len().max(1),
+            _ => 1,
+        };
+        let height = (rows as u16 + 3).clamp(6, 20).min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.mauve))
+            .title("Applications")
+            .style(Style::default().bg(self.theme.base));
+        let inner = block.inner(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(block, popup);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+
+        let note = |text: String, color| {
+            Paragraph::new(text)
+                .style(Style::default().fg(color))
+                .wrap(ratatui::widgets::Wrap { trim: false })
+        };
+        match applications {
+            None => f.render_widget(
+                note("Reading applications...".to_string(), self.theme.yellow),
+                chunks[0],
+            ),
+            Some(Err(e)) => f.render_widget(
+                note(format!("Not available: {}", e), self.theme.red),
+                chunks[0],
+            ),
+            Some(Ok(apps)) if apps.is_empty() => f.render_widget(
+                note(
+                    "No applications assigned besides AWS accounts".to_string(),
+                    self.theme.subtext0,
+                ),
+                chunks[0],
+            ),
+            Some(Ok(apps)) => {
+                let items: Vec<ListItem> = apps
+                    .iter()
+                    .map(|app| {
+                        let mut spans = vec![Span::raw(app.name.clone())];
+                        if let Some(kind) = &app.application_type {
+                            spans.push(Span::styled(
+                                format!("  {}", kind),
+                                Style::default().fg(self.theme.subtext0),
+                            ));
+                        }
+                        ListItem::new(Line::from(spans))
+                    })
+                    .collect();
+                let list = List::new(items).highlight_style(
+                    Style::default()
+                        .bg(self.theme.surface1)
+                        .add_modifier(Modifier::BOLD),
+                );
+                let mut state = ListState::default().with_selected(Some(selected));
+                f.render_stateful_widget(list, chunks[0], &mut state);
+            }
+        }
+
+        let hints = Paragraph::new("↑↓:select | Enter:open in browser | Esc:close")
+            .style(Style::default().fg(self.theme.subtext0));
+        f.render_widget(hints, chunks[1]);
+    }
+}
@@ -0,0 +1,392 @@
+// Bulk actions on the roles marked with Space: open their consoles, or start or stop their
+// credentials, with a per-role summary
+use crate::aws_config::{self, SsoProfile};
+use crate::config::ProfileMode;
+use crate::credentials::CredentialManager;
+use crate::error::{Result, SsoError};
+use crate::models::{AccountRole, RoleCredentials, SsoInstance, SsoToken};
+use crate::sso_config;
+use crate::ui::app::{App, AppState, ConfirmAction};
+use crate::ui::event::AppEvent;
+use crate::ui::widgets::accounts_pane::AccountRoleWithStatus;
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// The profile a role's credentials are written to once fetched, for bulk starts and
+/// Refresh All
+pub(crate) struct StartTarget {
+    pub(crate) account: AccountRole,
+    pub(crate) profile_name: String,
+    pub(crate) region: String,
+    pub(crate) output: Option<String>,
+}
+
+/// What starting a marked role takes after its profile is resolved
+enum StartPlan {
+    /// Nothing more; the line for the summary
+    Done(String),
+    /// Credentials to fetch with the role's session
+    Fetch(Box<(SsoInstance, SsoToken, StartTarget)>),
+}
+
+impl App {
+    /// Open the bulk action menu, if any role is marked
+    pub(crate) fn open_bulk_menu(&mut self) {
+        if self.accounts.marked_items().is_empty() {
+            self.status_message = Some("Mark roles with Space first".to_string());
+            return;
+        }
+        self.state = AppState::BulkMenu;
+    }
+
+    pub(crate) fn handle_bulk_menu_key(&mut self, key: KeyCode) {
+        self.state = AppState::Main;
+        match key {
+            KeyCode::Char('c') => self.open_marked_consoles(),
+            KeyCode::Char('s') => self.start_marked_roles(),
+            KeyCode::Char('x') => self.stop_marked_roles(),
+            KeyCode::Char('u') => {
+                self.accounts.marked.clear();
+                self.status_message = Some("Cleared all marks".to_string());
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {}
+            _ => self.state = AppState::BulkMenu,
+        }
+    }
+
+    /// Open the console for every marked role, each in the region last picked for it or
+    /// the SSO region
+    fn open_marked_consoles(&mut self) {
+        let sso_region = self.sso_instance.as_ref().map(|i| i.region.clone());
+        let targets: Vec<(AccountRole, String)> = self
+            .accounts
+            .marked_items()
+            .into_iter()
+            .filter_map(|row| {
+                let account = row.account_role.clone();
+                let region = self
+                    .config
+                    .console_region(&account.account_id, &account.role_name)
+                    .map(str::to_string)
+                    .or_else(|| sso_region.clone())?;
+                Some((account, region))
+            })
+            .collect();
+
+        if self.config.console.require_confirmation {
+            let mut message = vec![format!(
+                "Sign in to the console as these {} roles?",
+                targets.len()
+            )];
+            message.extend(
+                targets
+                    .iter()
+                    .map(|(account, region)| format!("  {} in {}", account.full_display(), region)),
+            );
+            self.state = AppState::ConfirmationDialog {
+                title: "Open AWS Consoles".to_string(),
+                message,
+            };
+            self.pending_confirm_action = Some(ConfirmAction::OpenConsoles { targets });
+            return;
+        }
+        self.open_consoles(&targets);
+    }
+
+    /// Open the console as each role, one browser tab apiece
+    pub(crate) fn open_consoles(&mut self, targets: &[(AccountRole, String)]) {
+        for (account, region) in targets {
+            self.open_console(account, region, false);
+        }
+        self.status_message = Some(format!(
+            "Opening the AWS Console for {} role(s)...",
+            targets.len()
+        ));
+    }
+
+    /// Start every marked role that has no credentials, fetching them in the background
+    fn start_marked_roles(&mut self) {
+        if self.bulk_start.is_some() {
+            self.status_message = Some("Marked roles are still starting".to_string());
+            return;
+        }
+        let rows: Vec<AccountRoleWithStatus> = self
+            .accounts
+            .marked_items()
+            .into_iter()
+            .filter(|row| !row.is_active)
+            .cloned()
+            .collect();
+        if rows.is_empty() {
+            self.status_message = Some("Every marked role is already started".to_string());
+            return;
+        }
+
+        let mut lines = Vec::new();
+        let mut fetches = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            let name = row.account_role.display_name();
+            match self.prepare_start(row) {
+                Ok(StartPlan::Done(line)) => lines.push(Some(format!("✓ {}: {}", name, line))),
+                Ok(StartPlan::Fetch(fetch)) => {
+                    let (instance, token, target) = *fetch;
+                    lines.push(None);
+                    fetches.push((index, instance, token, target));
+                }
+                Err(e) => lines.push(Some(format!("✗ {}: {}", name, e))),
+            }
+        }
+        if fetches.is_empty() {
+            self.finish_bulk_action("Start Marked Roles", lines.into_iter().flatten().collect());
+            return;
+        }
+
+        self.status_message = Some(format!("Starting {} marked role(s)...", fetches.len()));
+        self.bulk_start = Some(lines);
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            for (index, instance, token, target) in fetches {
+                let result = match CredentialManager::new() {
+                    Ok(manager) => {
+                        manager
+                            .get_role_credentials(
+                                &instance,
+                                &token.access_token,
+                                &target.account,
+                                None,
+                            )
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                let _ = events.send(AppEvent::BulkStarted {
+                    index,
+                    target,
+                    result,
+                });
+            }
+        });
+    }
+
+    /// Write credentials fetched by `start_marked_roles`, and show the summary once every
+    /// role has reported
+    pub(crate) fn apply_bulk_started(
+        &mut self,
+        index: usize,
+        target: StartTarget,
+        result: Result<RoleCredentials>,
+    ) {
+        let written = result.and_then(|creds| {
+            aws_config::write_credentials_with_metadata(
+                &target.profile_name,
+                &creds,
+                &target.region,
+                target.output.as_deref(),
+                Some(&target.account),
+            )?;
+            Ok(format!(
+                "'{}' (expires in {})",
+                target.profile_name,
+                creds.expiration_display()
+            ))
+        });
+        let Some(lines) = self.bulk_start.as_mut() else {
+            return;
+        };
+        let name = target.account.display_name();
+        lines[index] = Some(match written {
+            Ok(line) => format!("✓ {}: {}", name, line),
+            Err(e) => format!("✗ {}: {}", name, e),
+        });
+
+        let pending = lines.iter().filter(|line| line.is_none()).count();
+        if pending > 0 {
+            self.status_message = Some(format!(
+                "Starting marked roles: {} of {} done",
+                lines.len() - pending,
+                lines.len()
+            ));
+            self.load_profile_expirations();
+            self.restatus_rows();
+            return;
+        }
+        let lines = self.bulk_start.take().unwrap_or_default();
+        self.finish_bulk_action("Start Marked Roles", lines.into_iter().flatten().collect());
+    }
+
+    /// Resolve the profile `row` starts in, or in a new one named by `[profile_defaults]
+    /// name_template` with awsom's default region and output
+    ///
+    /// An sso-native profile is written here, as it needs no credentials.
+    fn prepare_start(&self, row: &AccountRoleWithStatus) -> Result<StartPlan> {
+        let (instance, token) = self
+            .session_login(row.session_name.as_deref())
+            .ok_or(SsoError::NoSessionFound)?;
+        let account = &row.account_role;
+        let profile_name = match &row.profile_name {
+            Some(name) => name.clone(),
+            None => {
+                let name = self.default_profile_name(account);
+                if aws_config::get_profile_details(&name)?.is_some() {
+                    return Err(SsoError::ConfigError(format!(
+                        "profile '{}' already exists; start the role with Enter to name another",
+                        name
+                    )));
+                }
+                name
+            }
+        };
+
+        let details = aws_config::get_profile_details(&profile_name)?;
+        let defaults = aws_config::read_awsom_defaults()?;
+        let region = details
+            .as_ref()
+            .and_then(|d| d.region.clone())
+            .or_else(|| defaults.as_ref().map(|d| d.region.clone()))
+            .unwrap_or_else(|| instance.region.clone());
+        let output = details
+            .and_then(|d| d.output)
+            .or_else(|| defaults.map(|d| d.output))
+            .or_else(|| sso_config::get_default_output_format().map(str::to_string));
+
+        if self.config.profile_defaults.profile_mode == ProfileMode::SsoNative {
+            aws_config::write_sso_native_profile(&SsoProfile {
+                name: profile_name.clone(),
+                sso_session: aws_config::sso_native_session(&instance)?,
+                account_id: account.account_id.clone(),
+                role_name: account.role_name.clone(),
+                region,
+                output,
+                credential_process: false,
+                source_profile: None,
+            })?;
+            return Ok(StartPlan::Done(format!("SSO profile '{}'", profile_name)));
+        }
+
+        let target = StartTarget {
+            account: account.clone(),
+            profile_name,
+            region,
+            output,
+        };
+        Ok(StartPlan::Fetch(Box::new((instance, token, target))))
+    }
+
+    /// Remove the credentials of every marked role that has them, keeping the profiles
+    fn stop_marked_roles(&mut self) {
+        let rows: Vec<AccountRoleWithStatus> = self
+            .accounts
+            .marked_items()
+            .into_iter()
+            .filter(|row| row.is_active)
+            .cloned()
+            .collect();
+        if rows.is_empty() {
+            self.status_message = Some("No marked role is started".to_string());
+            return;
+        }
+
+        let lines = rows
+            .iter()
+            .map(|row| match self.stop_role(row) {
+                Ok(line) => format!("✓ {}: {}", row.account_role.display_name(), line),
+                Err(e) => format!("✗ {}: {}", row.account_role.display_name(), e),
+            })
+            .collect();
+        self.finish_bulk_action("Stop Marked Roles", lines);
+    }
+
+    /// Stop `row` the way Enter does: an sso-native profile is removed, session credentials
+    /// are removed from every profile bound to the role
+    fn stop_role(&self, row: &AccountRoleWithStatus) -> Result<String> {
+        if self.config.profile_defaults.profile_mode == ProfileMode::SsoNative {
+            let Some(profile) = &row.profile_name else {
+                return Err(SsoError::ConfigError("no profile to remove".to_string()));
+            };
+            if !aws_config::is_profile_in_awsom_section(profile)? {
+                return Err(SsoError::ConfigError(format!(
+                    "'{}' is user-managed; awsom leaves it alone",
+                    profile
+                )));
+            }
+            aws_config::delete_profile(profile)?;
+            return Ok(format!("removed SSO profile '{}'", profile));
+        }
+
+        let profiles = aws_config::get_existing_profile_names(&row.account_role)?;
+        for profile in &profiles {
+            aws_config::invalidate_profile(profile)?;
+        }
+        Ok(format!("stopped {}", profiles.join(", ")))
+    }
+
+    /// Show what a bulk action did and reload the rows it changed
+    fn finish_bulk_action(&mut self, title: &str, lines: Vec<String>) {
+        let failed = lines.iter().filter(|line| line.starts_with('✗')).count();
+        self.status_message = Some(format!(
+            "{}: {} of {} done",
+            title,
+            lines.len() - failed,
+            lines.len()
+        ));
+        self.load_accounts();
+        // A bulk start reports after the fact; it doesn't cover a dialog opened meanwhile
+        if self.state == AppState::Main {
+            self.state = AppState::Summary {
+                title: title.to_string(),
+                lines,
+            };
+        }
+    }
+
+    /// Popup listing the bulk actions, each run with one key
+    pub(crate) fn draw_bulk_menu(&self, f: &mut Frame) {
+        let marked = self.accounts.marked_items().len();
+        let entry = |key: &'static str, label: String| {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {}  ", key),
+                    Style::default()
+                        .fg(self.theme.blue)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(label),
+            ])
+        };
+        let text = vec![
+            entry("c", format!("Open the AWS Console for {} role(s)", marked)),
+            entry("s", "Start credentials of the marked roles".to_string()),
+            entry("x", "Stop credentials of the marked roles".to_string()),
+            entry("u", "Unmark all".to_string()),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Esc: cancel",
+                Style::default().fg(self.theme.subtext0),
+            )),
+        ];
+
+        let area = f.area();
+        let width = 50.min(area.width);
+        let height = (text.len() as u16 + 2).min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+        let menu = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.mauve))
+                .title(format!("{} Marked Role(s)", marked))
+                .style(Style::default().bg(self.theme.base)),
+        );
+        f.render_widget(Clear, popup);
+        f.render_widget(menu, popup);
+    }
+}
@@ -0,0 +1,1555 @@
+// Popups over the main screen: confirmations, pickers, editors, the command line and
+// the messages panel
+use crate::aws_config::{self, ConflictPolicy};
+use crate::config::GroupBy;
+use crate::credentials::CredentialManager;
+use crate::eks;
+use crate::error::Result;
+use crate::models::AccountRole;
+use crate::ui::app::{App, AppState, ConfirmAction, NewProfileConfigStep, RoleDetails};
+use crate::ui::event::AppEvent;
+use crate::ui::palette::{self, Command};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Lines of the log file shown in the messages panel, read from at most this many bytes
+const LOG_TAIL_LINES: usize = 200;
+const LOG_TAIL_BYTES: u64 = 64 * 1024;
+
+impl App {
+    pub(crate) async fn handle_confirmation_dialog_key(&mut self, key: KeyCode) -> Result<()> {
+        match key {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                // User confirmed - execute the pending action
+                if let Some(action) = self.pending_confirm_action.take() {
+                    match action {
+                        ConfirmAction::MakeProfileDefault {
+                            from_profile,
+                            account: _,
+                        } => {
+                            // Delete existing default profile
+                            tracing::info!("Deleting existing default profile");
+                            if let Err(e) = crate::aws_config::delete_profile("default") {
+                                tracing::debug!(
+                                    "No existing default profile to delete (or error): {}",
+                                    e
+                                );
+                            }
+
+                            // Rename the profile to default
+                            match crate::aws_config::rename_profile(&from_profile, "default") {
+                                Ok(()) => {
+                                    self.status_message = Some(format!(
+                                        "✓ Set '{}' as default profile",
+                                        from_profile
+                                    ));
+                                    // Reload accounts to update indicators
+                                    self.load_accounts();
+                                }
+                                Err(e) => {
+                                    self.status_message =
+                                        Some(format!("Error setting default profile: {}", e));
+                                }
+                            }
+                        }
+                        ConfirmAction::RenameProfile {
+                            old_name,
+                            new_name,
+                            account,
+                        } => {
+                            // Delete old profile if names differ
+                            if old_name != new_name {
+                                if let Err(e) = crate::aws_config::delete_profile(&old_name) {
+                                    tracing::warn!(
+                                        "Failed to delete old profile '{}': {}",
+                                        old_name,
+                                        e
+                                    );
+                                }
+                            }
+
+                            // Save the profile with new name and credentials
+                            self.state = AppState::Loading;
+                            self.save_profile_credentials(&account, &new_name).await?;
+                        }
+                        ConfirmAction::DeleteSession {
+                            session_index,
+                            session_name,
+                        } => {
+                            // Delete the session
+                            if let Some(session) = self.sessions.items.get(session_index).cloned() {
+                                // Logout if active
+                                if session.is_active {
+                                    self.logout_session(session_index).await?;
+                                }
+
+                                // Delete from config
+                                if let Err(e) = crate::aws_config::delete_sso_session(&session_name)
+                                {
+                                    self.status_message =
+                                        Some(format!("Error deleting session: {}", e));
+                                    self.state = AppState::Main;
+                                    return Ok(());
+                                }
+
+                                // Remove from list
+                                self.sessions.items.remove(session_index);
+
+                                // Update selection
+                                if self.sessions.items.is_empty() {
+                                    self.sessions.state.select(None);
+                                } else if session_index >= self.sessions.items.len() {
+                                    self.sessions
+                                        .state
+                                        .select(Some(self.sessions.items.len() - 1));
+                                }
+
+                                self.status_message =
+                                    Some(format!("✓ Deleted session '{}'", session_name));
+                            }
+                        }
+                        ConfirmAction::OpenConsole {
+                            account,
+                            region,
+                            copy_url,
+                        } => self.open_console(&account, &region, copy_url),
+                        ConfirmAction::OpenConsoles { targets } => self.open_consoles(&targets),
+                    }
+                }
+                self.state = AppState::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                // User cancelled - just return to main screen
+                self.pending_confirm_action = None;
+                self.state = AppState::Main;
+                self.status_message = Some("Action cancelled".to_string());
+            }
+            _ => {
+                // Ignore other keys
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn draw_confirmation_dialog(
+        &self,
+        f: &mut Frame,
+        title: String,
+        message: Vec<String>,
+    ) {
+        // Calculate dialog size with dynamic height
+        let dialog_width = 60;
+
+        // CRITICAL: Reserve space for essential elements
+        // - borders: 2 lines
+        // - title: 1 line
+        // - empty after title: 1 line
+        // - empty before buttons: 1 line
+        // - buttons (Y/N): 1 line
+        // MINIMUM dialog: 8 lines (6 fixed + at least 2 message lines)
+        let min_essential_height = 8u16;
+
+        // Get terminal dimensions
+        let area = f.area();
+
+        // Use most of the terminal height, leaving small margin
+        let max_height = area.height.saturating_sub(2);
+
+        // Calculate desired height
+        let content_height = message.len() as u16;
+        let desired_height = content_height + 6; // message + fixed elements
+
+        // Final dialog height (ensure minimum)
+        let dialog_height = std::cmp::max(
+            min_essential_height,
+            std::cmp::min(desired_height, max_height),
+        );
+
+        // Center the dialog
+        let dialog_x = (area.width.saturating_sub(dialog_width)) / 2;
+        let dialog_y = (area.height.saturating_sub(dialog_height)) / 2;
+
+        let dialog_area = ratatui::layout::Rect {
+            x: dialog_x,
+            y: dialog_y,
+            width: dialog_width,
+            height: dialog_height,
+        };
+
+        // Calculate available space for message content
+        // dialog_height - borders(2) - title(1) - empty(1) - empty(1) - buttons(1) = available
+        let available_message_lines = (dialog_height as usize).saturating_sub(6).max(1);
+
+        // Truncate message if needed - ALWAYS ensure Y/N buttons can be shown
+        let message_to_show = if message.len() > available_message_lines {
+            // Leave room for truncation indicator
+            let truncate_at = available_message_lines.saturating_sub(1).max(1);
+            let mut truncated = message[..truncate_at].to_vec();
+            truncated.push("...".to_string());
+            truncated
+        } else {
+            message
+        };
+
+        // Build dialog content
+        let mut dialog_text = vec![];
+        dialog_text.push(Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(self.theme.yellow)
+                .add_modifier(Modifier::BOLD),
+        )));
+        dialog_text.push(Line::from(""));
+
+        for msg in message_to_show {
+            dialog_text.push(Line::from(msg));
+        }
+
+        dialog_text.push(Line::from(""));
+        dialog_text.push(Line::from(vec![
+            Span::styled(
+                "Y",
+                Style::default()
+                    .fg(self.theme.green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(": Confirm | "),
+            Span::styled(
+                "N",
+                Style::default()
+                    .fg(self.theme.red)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(": Cancel"),
+        ]));
+
+        let dialog = Paragraph::new(dialog_text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.theme.yellow))
+                    .title("Confirmation"),
+            )
+            .wrap(ratatui::widgets::Wrap { trim: false });
+
+        // Clear the background by rendering a clear block first
+        let clear_block = Block::default().style(Style::default().bg(self.theme.base));
+        f.render_widget(clear_block, dialog_area);
+
+        // Render the dialog
+        f.render_widget(dialog, dialog_area);
+    }
+
+    /// Open AWS Console in browser for selected role
+    /// Show the console region picker for the selected role
+    pub(crate) fn open_region_picker(&mut self) {
+        let Some(account_with_status) = self
+            .accounts
+            .selected_index(&self.config)
+            .and_then(|index| self.accounts.items.get(index))
+        else {
+            self.status_message = Some("No role selected".to_string());
+            return;
+        };
+
+        if !account_with_status.is_active {
+            self.status_message = Some(
+                "No active credentials for this role. Press Enter to create credentials first."
+                    .to_string(),
+            );
+            return;
+        }
+
+        let account = account_with_status.account_role.clone();
+        self.remembered_region = self
+            .config
+            .console_region(&account.account_id, &account.role_name)
+            .map(str::to_string);
+        self.region_input.clear();
+
+        // Preselect the region used last time for this role, or else the SSO region
+        let sso_region = self.sso_instance.as_ref().map(|i| i.region.as_str());
+        let selected = match self.remembered_region {
+            Some(_) => 0,
+            None => self
+                .region_choices()
+                .iter()
+                .position(|r| Some(r.as_str()) == sso_region)
+                .unwrap_or(0),
+        };
+        self.region_list_state.select(Some(selected));
+        self.state = AppState::RegionPicker { account };
+    }
+
+    /// Regions listed in the picker: the remembered one first, then the common ones of the
+    /// session's partition, filtered by what has been typed
+    fn region_choices(&self) -> Vec<String> {
+        let filter = self.region_input.trim().to_lowercase();
+        let sso_region = self.sso_instance.as_ref().map_or("", |i| i.region.as_str());
+        self.remembered_region
+            .iter()
+            .map(String::as_str)
+            .chain(
+                crate::console::picker_regions(sso_region)
+                    .into_iter()
+                    .filter(|r| Some(*r) != self.remembered_region.as_deref()),
+            )
+            .filter(|r| r.contains(&filter))
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub(crate) async fn handle_region_picker_key(&mut self, key: KeyCode) -> Result<()> {
+        let AppState::RegionPicker { account } = self.state.clone() else {
+            return Ok(());
+        };
+        let choices = self.region_choices();
+
+        match key {
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.status_message = Some("Console launch cancelled".to_string());
+            }
+            KeyCode::Down if !choices.is_empty() => {
+                let next = self
+                    .region_list_state
+                    .selected()
+                    .map_or(0, |i| (i + 1) % choices.len());
+                self.region_list_state.select(Some(next));
+            }
+            KeyCode::Up if !choices.is_empty() => {
+                let previous = self
+                    .region_list_state
+                    .selected()
+                    .map_or(0, |i| (i + choices.len() - 1) % choices.len());
+                self.region_list_state.select(Some(previous));
+            }
+            KeyCode::Backspace => {
+                self.region_input.pop();
+                self.region_list_state.select(Some(0));
+            }
+            KeyCode::Char(c) if c.is_ascii_alphanumeric() || c == '-' => {
+                self.region_input.push(c.to_ascii_lowercase());
+                self.region_list_state.select(Some(0));
+            }
+            // Tab copies the sign-in URL instead of opening it
+            KeyCode::Enter | KeyCode::Tab => {
+                // A highlighted match wins; with no matches the typed text is used as-is
+                let region = match self.region_list_state.selected() {
+                    Some(index) if index < choices.len() => choices[index].clone(),
+                    _ => self.region_input.trim().to_string(),
+                };
+                if region.is_empty() {
+                    return Ok(());
+                }
+                if let Err(e) = crate::regions::check_region(&region) {
+                    self.status_message = Some(format!("✗ {}", e));
+                    return Ok(());
+                }
+                let warning = crate::regions::region_warning(&region);
+
+                self.state = AppState::Main;
+                self.config
+                    .set_console_region(&account.account_id, &account.role_name, &region);
+                if let Err(e) = self.config.save() {
+                    tracing::warn!("Failed to save console region: {}", e);
+                }
+                let copy_url = key == KeyCode::Tab;
+                if self.config.console.require_confirmation {
+                    let mut message = vec![
+                        format!("Sign in to the console as {}?", account.full_display()),
+                        format!("Region: {}", region),
+                    ];
+                    message.extend(warning.map(|warning| format!("⚠ {}", warning)));
+                    self.state = AppState::ConfirmationDialog {
+                        title: "Open AWS Console".to_string(),
+                        message,
+                    };
+                    self.pending_confirm_action = Some(ConfirmAction::OpenConsole {
+                        account,
+                        region,
+                        copy_url,
+                    });
+                    return Ok(());
+                }
+                self.open_console(&account, &region, copy_url);
+                if let Some(warning) = warning {
+                    self.status_message = Some(format!("⚠ {}", warning));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Popup with a region filter/free-text line above the matching regions
+    pub(crate) fn draw_region_picker(&mut self, f: &mut Frame, title: String) {
+        use ratatui::widgets::{Clear, List, ListItem};
+
+        let choices = self.region_choices();
+        let area = f.area();
+        let width = 50.min(area.width);
+        let height = (choices.len() as u16 + 7).clamp(9, 23).min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.mauve))
+            .title(title)
+            .style(Style::default().bg(self.theme.base));
+        let inner = block.inner(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(block, popup);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2), // Input
+                Constraint::Min(1),    // Regions
+                Constraint::Length(2), // Hints
+            ])
+            .split(inner);
+
+        let input = Paragraph::new(Line::from(vec![
+            Span::styled("Region: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{}█", self.region_input)),
+        ]));
+        f.render_widget(input, chunks[0]);
+
+        if choices.is_empty() {
+            let hint = Paragraph::new(format!("Press Enter to use '{}'", self.region_input))
+                .style(Style::default().fg(self.theme.subtext0));
+            f.render_widget(hint, chunks[1]);
+        } else {
+            let items: Vec<ListItem> = choices
+                .iter()
+                .map(|region| {
+                    if Some(region.as_str()) == self.remembered_region.as_deref() {
+                        ListItem::new(format!("{} (last used)", region))
+                    } else {
+                        ListItem::new(region.as_str())
+                    }
+                })
+                .collect();
+            let list = List::new(items).highlight_style(
+                Style::default()
+                    .bg(self.theme.surface1)
+                    .add_modifier(Modifier::BOLD),
+            );
+            f.render_stateful_widget(list, chunks[1], &mut self.region_list_state);
+        }
+
+        let hints = Paragraph::new(vec![
+            Line::from("↑↓:select | type to filter | Esc:cancel"),
+            Line::from("Enter:open | Tab:copy sign-in URL"),
+        ])
+        .style(Style::default().fg(self.theme.subtext0));
+        f.render_widget(hints, chunks[2]);
+    }
+
+    pub(crate) fn open_workspace_picker(&mut self) {
+        if self.config.workspaces.is_empty() {
+            self.status_message =
+                Some("No workspaces defined; add [[workspaces]] to config.toml".to_string());
+            return;
+        }
+
+        let selected = self
+            .workspace_choices()
+            .iter()
+            .position(|choice| *choice == self.config.active_workspace)
+            .unwrap_or(0);
+        self.workspace_list_state.select(Some(selected));
+        self.state = AppState::WorkspacePicker;
+    }
+
+    /// No workspace first, then the workspaces in config.toml order
+    fn workspace_choices(&self) -> Vec<Option<String>> {
+        std::iter::once(None)
+            .chain(self.config.workspaces.iter().map(|w| Some(w.name.clone())))
+            .collect()
+    }
+
+    pub(crate) async fn handle_workspace_picker_key(&mut self, key: KeyCode) {
+        let choices = self.workspace_choices();
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.state = AppState::Main,
+            KeyCode::Down | KeyCode::Char('j') => {
+                let next = self
+                    .workspace_list_state
+                    .selected()
+                    .map_or(0, |i| (i + 1) % choices.len());
+                self.workspace_list_state.select(Some(next));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let previous = self
+                    .workspace_list_state
+                    .selected()
+                    .map_or(0, |i| (i + choices.len() - 1) % choices.len());
+                self.workspace_list_state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                self.state = AppState::Main;
+                let index = self.workspace_list_state.selected().unwrap_or(0);
+                if let Some(choice) = choices.into_iter().nth(index) {
+                    self.switch_workspace(choice).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Popup listing the workspaces from config.toml, the active one marked
+    pub(crate) fn draw_workspace_picker(&mut self, f: &mut Frame) {
+        use ratatui::widgets::{Clear, List, ListItem};
+
+        let choices = self.workspace_choices();
+        let area = f.area();
+        let width = 50.min(area.width);
+        let height = (choices.len() as u16 + 3).clamp(6, 20).min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.mauve))
+            .title("Workspace")
+            .style(Style::default().bg(self.theme.base));
+        let inner = block.inner(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(block, popup);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+
+        let items: Vec<ListItem> = choices
+            .iter()
+            .map(|choice| {
+                let label = choice.as_deref().unwrap_or("(none - all sessions)");
+                if *choice == self.config.active_workspace {
+                    ListItem::new(format!("{} (active)", label))
+                } else {
+                    ListItem::new(label.to_string())
+                }
+            })
+            .collect();
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .bg(self.theme.surface1)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_stateful_widget(list, chunks[0], &mut self.workspace_list_state);
+
+        let hints = Paragraph::new("↑↓:select | Enter:switch | Esc:cancel")
+            .style(Style::default().fg(self.theme.subtext0));
+        f.render_widget(hints, chunks[1]);
+    }
+
+    /// Edit the selected role's own tags; account-wide tags are set in config.toml
+    pub(crate) fn open_tag_editor(&mut self) {
+        let Some(account) = self
+            .accounts
+            .selected_index(&self.config)
+            .and_then(|index| self.accounts.items.get(index))
+            .map(|a| a.account_role.clone())
+        else {
+            self.status_message = Some("No role selected".to_string());
+            return;
+        };
+
+        self.tag_input = self
+            .config
+            .role_tags(&account.account_id, &account.role_name)
+            .join(", ");
+        self.state = AppState::TagInput { account };
+    }
+
+    pub(crate) fn handle_tag_input_key(&mut self, key: KeyCode) {
+        let AppState::TagInput { account } = self.state.clone() else {
+            return;
+        };
+
+        match key {
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.status_message = Some("Tag edit cancelled".to_string());
+            }
+            KeyCode::Backspace => {
+                self.tag_input.pop();
+            }
+            KeyCode::Char(c) => self.tag_input.push(c),
+            KeyCode::Enter => {
+                let tags = parse_tags(&self.tag_input);
+                let summary = tags.join(", ");
+                self.config
+                    .set_role_tags(&account.account_id, &account.role_name, tags);
+                self.state = AppState::Main;
+                self.status_message = Some(match self.config.save() {
+                    Ok(()) if summary.is_empty() => format!(
+                        "Removed tags from {} / {}",
+                        account.account_name, account.role_name
+                    ),
+                    Ok(()) => format!(
+                        "Tagged {} / {}: {}",
+                        account.account_name, account.role_name, summary
+                    ),
+                    Err(e) => format!("Error saving tags: {}", e),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Popup with the role's own tags as an editable comma-separated list
+    pub(crate) fn draw_tag_input(&self, f: &mut Frame, title: String, account_tags: String) {
+        let area = f.area();
+        let width = 60.min(area.width);
+        let height = 7.min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let subtle = Style::default().fg(self.theme.subtext0);
+        let text = vec![
+            Line::from(vec![
+                Span::styled("Tags: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format!("{}█", self.tag_input)),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled(
+                if account_tags.is_empty() {
+                    "No account-wide tags".to_string()
+                } else {
+                    format!("Account-wide (config.toml): {}", account_tags)
+                },
+                subtle,
+            )),
+            Line::from(Span::styled(
+                "Separate with commas or spaces | Enter:save | Esc:cancel",
+                subtle,
+            )),
+        ];
+
+        let input = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.mauve))
+                .title(title)
+                .style(Style::default().bg(self.theme.base)),
+        );
+        f.render_widget(ratatui::widgets::Clear, popup);
+        f.render_widget(input, popup);
+    }
+
+    /// Ask for an EKS cluster to reach as the selected role
+    pub(crate) fn open_cluster_input(&mut self) {
+        let Some(selected) = self
+            .accounts
+            .selected_index(&self.config)
+            .and_then(|index| self.accounts.items.get(index))
+        else {
+            self.status_message = Some("No role selected".to_string());
+            return;
+        };
+        let Some(instance) = &self.sso_instance else {
+            self.status_message = Some("Log in to the role's session first".to_string());
+            return;
+        };
+
+        // The role's profile region, else the profile defaults, else the SSO region
+        let region = selected
+            .profile_name
+            .as_deref()
+            .and_then(|profile| {
+                crate::aws_config::get_profile_details(profile)
+                    .ok()
+                    .flatten()
+            })
+            .and_then(|details| details.region)
+            .or_else(|| {
+                crate::aws_config::read_awsom_defaults()
+                    .ok()
+                    .flatten()
+                    .map(|defaults| defaults.region)
+            })
+            .unwrap_or_else(|| instance.region.clone());
+
+        self.cluster_input.clear();
+        self.state = AppState::ClusterInput {
+            account: selected.account_role.clone(),
+            region,
+        };
+    }
+
+    pub(crate) fn handle_cluster_input_key(&mut self, key: KeyCode) {
+        let AppState::ClusterInput { account, region } = self.state.clone() else {
+            return;
+        };
+
+        match key {
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.status_message = Some("Kubeconfig update cancelled".to_string());
+            }
+            KeyCode::Backspace => {
+                self.cluster_input.pop();
+            }
+            KeyCode::Char(c) => self.cluster_input.push(c),
+            KeyCode::Enter => {
+                let input = self.cluster_input.trim().to_string();
+                if input.is_empty() {
+                    return;
+                }
+                let (cluster, region) = match input.split_once('@') {
+                    Some((cluster, region)) => (cluster.to_string(), region.to_string()),
+                    None => (input, region),
+                };
+                self.state = AppState::Main;
+                self.update_kubeconfig(account, cluster, region);
+            }
+            _ => {}
+        }
+    }
+
+    /// Look `cluster` up and write its kubeconfig entry as `account` in the background
+    fn update_kubeconfig(&mut self, account: AccountRole, cluster: String, region: String) {
+        let (Some(token), Some(instance)) = (self.sso_token.clone(), self.sso_instance.clone())
+        else {
+            self.status_message = Some("Log in to a session first".to_string());
+            return;
+        };
+        let Some(session) = instance.session_name.clone() else {
+            self.status_message =
+                Some("Error updating kubeconfig: the session has no name in ~/.aws/config".into());
+            return;
+        };
+        self.status_message = Some(format!("Looking up cluster {} in {}...", cluster, region));
+
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = async {
+                let creds = CredentialManager::new()?
+                    .get_role_credentials(&instance, &token.access_token, &account, None)
+                    .await?;
+                let cluster = eks::describe_cluster(&creds, &region, &cluster).await?;
+                let dropped_comments = eks::update_kubeconfig(
+                    &eks::kubeconfig_path()?,
+                    &cluster,
+                    &cluster.arn,
+                    &eks::get_token_args(&cluster.name, &region, &session, &account),
+                )?;
+                Ok((cluster.arn, dropped_comments))
+            }
+            .await;
+            let _ = events.send(AppEvent::Kubeconfig { account, result });
+        });
+    }
+
+    /// Report the kubeconfig update started by `update_kubeconfig`
+    pub(crate) fn apply_kubeconfig(
+        &mut self,
+        account: &AccountRole,
+        result: Result<(String, bool)>,
+    ) {
+        self.status_message = Some(match result {
+            Ok((context, dropped_comments)) => format!(
+                "✓ kubectl context {} uses {} / {}{}",
+                context,
+                account.account_name,
+                account.role_name,
+                if dropped_comments {
+                    " (comments in the kubeconfig were not kept)"
+                } else {
+                    ""
+                }
+            ),
+            Err(e) => format!("Error updating kubeconfig: {}", e),
+        });
+    }
+
+    /// Popup for the cluster name, with the region it is looked up in
+    pub(crate) fn draw_cluster_input(&self, f: &mut Frame, title: String, region: &str) {
+        let area = f.area();
+        let width = 60.min(area.width);
+        let height = 7.min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let subtle = Style::default().fg(self.theme.subtext0);
+        let text = vec![
+            Line::from(vec![
+                Span::styled("Cluster: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format!("{}█", self.cluster_input)),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("In {} (name@region for another region)", region),
+                subtle,
+            )),
+            Line::from(Span::styled(
+                "Enter:add to kubeconfig and switch context | Esc:cancel",
+                subtle,
+            )),
+        ];
+
+        let input = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.mauve))
+                .title(title)
+                .style(Style::default().bg(self.theme.base)),
+        );
+        f.render_widget(ratatui::widgets::Clear, popup);
+        f.render_widget(input, popup);
+    }
+
+    /// Edit the selected role's profile settings without fetching credentials
+    pub(crate) fn open_profile_settings(&mut self) {
+        let Some(profile) = self
+            .accounts
+            .selected_index(&self.config)
+            .and_then(|index| self.accounts.items.get(index))
+            .and_then(|a| a.profile_name.clone())
+        else {
+            self.status_message =
+                Some("Selected role has no profile yet - press Enter to create one".to_string());
+            return;
+        };
+
+        match crate::aws_config::is_profile_in_awsom_section(&profile) {
+            Ok(true) => {}
+            Ok(false) => {
+                self.status_message = Some(format!(
+                    "Profile '{}' is user-managed - run 'awsom import {}' to edit it here",
+                    profile, profile
+                ));
+                return;
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Error reading profile: {}", e));
+                return;
+            }
+        }
+
+        let details = crate::aws_config::get_profile_details(&profile)
+            .ok()
+            .flatten();
+        self.new_profile_name_input = profile.clone();
+        self.new_profile_region_input = details
+            .as_ref()
+            .and_then(|d| d.region.clone())
+            .unwrap_or_default();
+        self.new_profile_output_input = details
+            .and_then(|d| d.output)
+            .unwrap_or_else(|| "json".to_string());
+        self.state = AppState::ProfileSettings {
+            profile,
+            field: NewProfileConfigStep::ProfileName,
+        };
+    }
+
+    pub(crate) async fn handle_profile_settings_key(&mut self, key: KeyCode) {
+        let AppState::ProfileSettings { profile, field } = self.state.clone() else {
+            return;
+        };
+
+        let next = |field: &NewProfileConfigStep| match field {
+            NewProfileConfigStep::ProfileName => NewProfileConfigStep::Region,
+            NewProfileConfigStep::Region => NewProfileConfigStep::Output,
+            NewProfileConfigStep::Output => NewProfileConfigStep::ProfileName,
+        };
+        let input = match field {
+            NewProfileConfigStep::ProfileName => &mut self.new_profile_name_input,
+            NewProfileConfigStep::Region => &mut self.new_profile_region_input,
+            NewProfileConfigStep::Output => &mut self.new_profile_output_input,
+        };
+
+        match key {
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.status_message = Some("Profile settings unchanged".to_string());
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                self.state = AppState::ProfileSettings {
+                    profile,
+                    field: next(&field),
+                };
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.state = AppState::ProfileSettings {
+                    profile,
+                    field: next(&next(&field)),
+                };
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => {
+                let allowed = match field {
+                    NewProfileConfigStep::ProfileName => {
+                        c.is_alphanumeric() || c == '-' || c == '_'
+                    }
+                    NewProfileConfigStep::Region => {
+                        c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'
+                    }
+                    NewProfileConfigStep::Output => c.is_alphanumeric() || c == '-',
+                };
+                if allowed {
+                    input.push(c);
+                }
+            }
+            KeyCode::Enter => {
+                let new_name = self.new_profile_name_input.trim().to_string();
+                let region = self.new_profile_region_input.trim().to_string();
+                let output = self.new_profile_output_input.trim().to_string();
+                if new_name.is_empty() || region.is_empty() || output.is_empty() {
+                    self.status_message =
+                        Some("Profile name, region and output are required".to_string());
+                    return;
+                }
+                let checked = crate::regions::check_region(&region)
+                    .and_then(|()| crate::regions::check_output(&output));
+                if let Err(e) = checked {
+                    self.status_message = Some(format!("✗ {}", e));
+                    return;
+                }
+
+                match crate::aws_config::update_profile_settings(
+                    &profile, &new_name, &region, &output,
+                ) {
+                    Ok(()) => {
+                        self.state = AppState::Main;
+                        self.status_message = Some(match crate::regions::region_warning(&region) {
+                            Some(warning) => {
+                                format!("⚠ Updated profile '{}': {}", new_name, warning)
+                            }
+                            None => format!(
+                                "✓ Updated profile '{}' (region={}, output={})",
+                                new_name, region, output
+                            ),
+                        });
+                        if new_name != profile {
+                            self.load_accounts();
+                        }
+                    }
+                    // Stay in the popup so the input can be corrected
+                    Err(e) => self.status_message = Some(format!("Error: {}", e)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Popup with the three profile settings, the focused one editable
+    pub(crate) fn draw_profile_settings(
+        &self,
+        f: &mut Frame,
+        profile: &str,
+        field: &NewProfileConfigStep,
+    ) {
+        let area = f.area();
+        let width = 60.min(area.width);
+        let height = 9.min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let subtle = Style::default().fg(self.theme.subtext0);
+        let row = |label: &str, value: &str, focused: bool| {
+            let style = if focused {
+                Style::default()
+                    .fg(self.theme.yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(format!("{:<14}", label), style),
+                Span::styled(
+                    if focused {
+                        format!("{}█", value)
+                    } else {
+                        value.to_string()
+                    },
+                    style,
+                ),
+            ])
+        };
+
+        let text = vec![
+            row(
+                "Profile name",
+                &self.new_profile_name_input,
+                *field == NewProfileConfigStep::ProfileName,
+            ),
+            row(
+                "Region",
+                &self.new_profile_region_input,
+                *field == NewProfileConfigStep::Region,
+            ),
+            row(
+                "Output",
+                &self.new_profile_output_input,
+                *field == NewProfileConfigStep::Output,
+            ),
+            Line::from(""),
+            Line::from(Span::styled("Credentials are kept as they are", subtle)),
+            Line::from(Span::styled(
+                "Tab/↑↓:field | Enter:save | Esc:cancel",
+                subtle,
+            )),
+        ];
+
+        let popup_widget = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.mauve))
+                .title(format!("Profile settings - {}", profile))
+                .style(Style::default().bg(self.theme.base)),
+        );
+        f.render_widget(ratatui::widgets::Clear, popup);
+        f.render_widget(popup_widget, popup);
+    }
+
+    pub(crate) async fn handle_command_line_key(&mut self, key: KeyCode) {
+        let matches = palette::search(&self.command_input);
+        match key {
+            KeyCode::Esc => self.state = AppState::Main,
+            KeyCode::Backspace if self.command_input.is_empty() => self.state = AppState::Main,
+            KeyCode::Backspace => {
+                self.command_input.pop();
+                self.palette_state.select(Some(0));
+            }
+            KeyCode::Down if !matches.is_empty() => {
+                let next = self
+                    .palette_state
+                    .selected()
+                    .map_or(0, |i| (i + 1) % matches.len());
+                self.palette_state.select(Some(next));
+            }
+            KeyCode::Up if !matches.is_empty() => {
+                let previous = self
+                    .palette_state
+                    .selected()
+                    .map_or(0, |i| (i + matches.len() - 1) % matches.len());
+                self.palette_state.select(Some(previous));
+            }
+            KeyCode::Char(c) => {
+                self.command_input.push(c);
+                self.palette_state.select(Some(0));
+            }
+            KeyCode::Enter => {
+                // Typed commands and lines matching no entry run as typed
+                let picked = match self.palette_state.selected() {
+                    Some(index) if !palette::is_command_line(&self.command_input) => {
+                        matches.get(index).copied()
+                    }
+                    _ => None,
+                };
+                let input = std::mem::take(&mut self.command_input);
+                self.state = AppState::Main;
+                match picked.map(|entry| entry.command) {
+                    Some(Command::Run(action)) => {
+                        if let Err(e) = self.dispatch(action).await {
+                            self.status_message = Some(e.to_string());
+                        }
+                    }
+                    Some(Command::Line(line)) => self.run_command(line).await,
+                    Some(Command::Type(start)) => {
+                        self.command_input = start.to_string();
+                        self.state = AppState::CommandLine;
+                    }
+                    None => self.run_command(&input).await,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Palette entries matching the `:` line, above the status bar
+    pub(crate) fn draw_command_palette(&mut self, f: &mut Frame) {
+        use ratatui::widgets::{Clear, List, ListItem};
+
+        if palette::is_command_line(&self.command_input) {
+            return;
+        }
+        let matches = palette::search(&self.command_input);
+        let area = f.area();
+        let width = 56.min(area.width);
+        // Bottom edge on the status bar's top, above the 2-line help bar
+        let bottom = area.height.saturating_sub(3);
+        let height = (matches.len().max(1) as u16 + 2).min(14).min(bottom);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: bottom - height,
+            width,
+            height,
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.theme.mauve))
+            .title("Commands - ↑↓:select | Enter:run | Esc:cancel")
+            .style(Style::default().bg(self.theme.base));
+        let inner = block.inner(popup);
+        f.render_widget(Clear, popup);
+        f.render_widget(block, popup);
+
+        if matches.is_empty() {
+            let hint = Paragraph::new("No matching command; Enter runs the line as typed")
+                .style(Style::default().fg(self.theme.subtext0));
+            f.render_widget(hint, inner);
+            return;
+        }
+        let key_width = usize::from(inner.width).saturating_sub(1);
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|entry| {
+                let pad = key_width.saturating_sub(entry.name.chars().count());
+                ListItem::new(Line::from(vec![
+                    Span::raw(entry.name),
+                    Span::styled(
+                        format!("{:>pad$}", entry.key, pad = pad),
+                        Style::default().fg(self.theme.subtext0),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .bg(self.theme.surface1)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_stateful_widget(list, inner, &mut self.palette_state);
+    }
+
+    /// `:tag <name>` filters the accounts pane (`:tag` clears), `:group none|ou|tag` groups it
+    async fn run_command(&mut self, input: &str) {
+        let mut words = input.split_whitespace();
+        match (words.next(), words.next()) {
+            (None, _) => {}
+            (Some("tag"), tag) => {
+                let selected = self.accounts.selected_index(&self.config);
+                self.accounts.tag_filter = tag.map(str::to_string);
+                match selected {
+                    Some(index) => self.accounts.select(index, &self.config),
+                    None => self.accounts.state.select(Some(0)),
+                }
+                self.status_message = Some(match tag {
+                    Some(tag) => format!(
+                        "{} role(s) tagged '{}' (:tag clears the filter)",
+                        (0..self.accounts.items.len())
+                            .filter(|&index| self.accounts.matches_tag_filter(index, &self.config))
+                            .count(),
+                        tag
+                    ),
+                    None => "Tag filter cleared".to_string(),
+                });
+            }
+            (Some("group"), Some("none")) => self.set_grouping(GroupBy::None),
+            (Some("group"), Some("ou")) => self.set_grouping(GroupBy::Ou),
+            (Some("group"), Some("tag")) => self.set_grouping(GroupBy::Tag),
+            _ => {
+                self.status_message = Some(format!(
+                    "Unknown command ':{}' - try :tag <name> or :group none|ou|tag",
+                    input.trim()
+                ));
+            }
+        }
+    }
+
+    /// Popup listing what `R` or a bulk action did for each profile, and what failed
+    pub(crate) fn draw_summary(&self, f: &mut Frame, title: &str, lines: Vec<String>) {
+        let area = f.area();
+        let width = 70.min(area.width);
+        let height = (lines.len() as u16 + 4).min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let mut text: Vec<Line> = lines
+            .into_iter()
+            .map(|line| {
+                let color = if line.starts_with('✓') {
+                    self.theme.green
+                } else {
+                    self.theme.red
+                };
+                Line::from(Span::styled(line, Style::default().fg(color)))
+            })
+            .collect();
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            "Press any key to continue",
+            Style::default().fg(self.theme.yellow),
+        )));
+
+        let summary = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.theme.mauve))
+                    .title(title),
+            )
+            .style(Style::default().bg(self.theme.base))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+
+        f.render_widget(ratatui::widgets::Clear, popup);
+        f.render_widget(summary, popup);
+    }
+
+    /// Popup with a role's session duration and permission set description
+    pub(crate) fn draw_role_details(
+        &self,
+        f: &mut Frame,
+        account: &AccountRole,
+        details: Option<&RoleDetails>,
+    ) {
+        let label = |name: &'static str| {
+            Span::styled(
+                format!("{:<18}", name),
+                Style::default()
+                    .fg(self.theme.blue)
+                    .add_modifier(Modifier::BOLD),
+            )
+        };
+        let mut text = vec![
+            Line::from(vec![
+                label("Account"),
+                Span::raw(format!("{} ({})", account.account_name, account.account_id)),
+            ]),
+            Line::from(vec![label("Role"), Span::raw(account.role_name.clone())]),
+        ];
+        match details {
+            None => text.push(Line::from(Span::styled(
+                "Reading permission set...",
+                Style::default().fg(self.theme.yellow),
+            ))),
+            Some(details) => {
+                let duration = details.session_duration.map_or_else(
+                    || "unknown".to_string(),
+                    |duration| match (duration.num_hours(), duration.num_minutes() % 60) {
+                        (0, minutes) => format!("{}m", minutes),
+                        (hours, 0) => format!("{}h", hours),
+                        (hours, minutes) => format!("{}h {}m", hours, minutes),
+                    },
+                );
+                text.push(Line::from(vec![
+                    label("Session duration"),
+                    Span::raw(duration),
+                ]));
+                text.push(Line::from(label("Description")));
+                text.push(
+                    match (&details.description, &details.permission_set_error) {
+                        (Some(description), _) => Line::from(description.clone()),
+                        (None, Some(error)) => Line::from(Span::styled(
+                            format!("Not available: {}", error),
+                            Style::default().fg(self.theme.subtext0),
+                        )),
+                        (None, None) => Line::from(Span::styled(
+                            "The permission set has no description",
+                            Style::default().fg(self.theme.subtext0),
+                        )),
+                    },
+                );
+            }
+        }
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            "Press any key to close",
+            Style::default().fg(self.theme.yellow),
+        )));
+
+        let area = f.area();
+        let width = 70.min(area.width);
+        // Long descriptions wrap onto extra rows
+        let inner_width = usize::from(width.saturating_sub(2)).max(1);
+        let rows: usize = text
+            .iter()
+            .map(|line| (line.width().max(1) + inner_width - 1) / inner_width)
+            .sum();
+        let height = (rows as u16 + 2).min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+        let block = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.theme.mauve))
+                    .title("Role Details"),
+            )
+            .style(Style::default().bg(self.theme.base))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+
+        f.render_widget(ratatui::widgets::Clear, popup);
+        f.render_widget(block, popup);
+    }
+
+    /// Settle a clash with a user-managed profile: import it and save over it, save under
+    /// the suffixed name instead, or leave it alone
+    pub(crate) async fn handle_profile_conflict_key(&mut self, key: KeyCode) -> Result<()> {
+        let AppState::ProfileConflict {
+            account,
+            profile_name,
+            suffixed,
+        } = self.state.clone()
+        else {
+            return Ok(());
+        };
+
+        let target = match key {
+            KeyCode::Char('i') => {
+                match aws_config::resolve_profile_conflict(&profile_name, ConflictPolicy::Import) {
+                    Ok(name) => name,
+                    Err(e) => {
+                        self.state = AppState::Error(format!("Failed to import profile: {}", e));
+                        return Ok(());
+                    }
+                }
+            }
+            KeyCode::Char('s') => suffixed,
+            KeyCode::Char('a') | KeyCode::Char('q') | KeyCode::Esc => {
+                self.profile_input.clear();
+                self.profile_input_cursor = 0;
+                self.existing_profile_name = None;
+                self.state = AppState::Main;
+                self.status_message = Some(format!(
+                    "Left user-managed profile '{}' alone",
+                    profile_name
+                ));
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+
+        match self.existing_profile_name.clone() {
+            // Saving over the import was what the user asked for, so it isn't confirmed again
+            Some(old) if target == profile_name => {
+                if old != target {
+                    if let Err(e) = aws_config::delete_profile(&old) {
+                        tracing::warn!("Failed to delete old profile '{}': {}", old, e);
+                    }
+                }
+                self.existing_profile_name = Some(target.clone());
+            }
+            None if target == profile_name => self.existing_profile_name = Some(target.clone()),
+            // The user-managed profile stays as it is when writing the suffixed one
+            Some(old) if old == profile_name => self.existing_profile_name = None,
+            _ => {}
+        }
+        self.state = AppState::Loading;
+        self.save_profile_credentials(&account, &target).await
+    }
+
+    /// Popup offering the ways out of a clash with a user-managed profile
+    pub(crate) fn draw_profile_conflict(&self, f: &mut Frame, profile_name: &str, suffixed: &str) {
+        let entry = |key: &'static str, label: String| {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {}  ", key),
+                    Style::default()
+                        .fg(self.theme.blue)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(label),
+            ])
+        };
+        let text = vec![
+            Line::from(format!(
+                "'{}' is a user-managed profile in ~/.aws/config.",
+                profile_name
+            )),
+            Line::from(""),
+            entry("i", "Import it into awsom and overwrite it".to_string()),
+            entry("s", format!("Write '{}' instead", suffixed)),
+            entry("a", "Abort".to_string()),
+        ];
+
+        let area = f.area();
+        let width = 60.min(area.width);
+        let height = (text.len() as u16 + 2).min(area.height);
+        let popup = ratatui::layout::Rect {
+            x: (area.width - width) / 2,
+            y: (area.height - height) / 2,
+            width,
+            height,
+        };
+        let dialog = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.theme.yellow))
+                    .title("Profile Conflict"),
+            )
+            .style(Style::default().bg(self.theme.base))
+            .wrap(ratatui::widgets::Wrap { trim: false });
+
+        f.render_widget(ratatui::widgets::Clear, popup);
+        f.render_widget(dialog, popup);
+    }
+
+    /// Open the messages panel at the newest line, reading the log tail once
+    pub(crate) fn open_messages(&mut self) {
+        self.messages_scroll = 0;
+        self.state = AppState::Messages { log: log_tail() };
+    }
+
+    pub(crate) fn handle_messages_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('m') | KeyCode::Char('q') | KeyCode::Esc => {
+                self.state = AppState::Main;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.messages_scroll += 1,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.messages_scroll = self.messages_scroll.saturating_sub(1);
+            }
+            KeyCode::PageUp => self.messages_scroll += 10,
+            KeyCode::PageDown => self.messages_scroll = self.messages_scroll.saturating_sub(10),
+            KeyCode::Home | KeyCode::Char('g') => self.messages_scroll = usize::MAX,
+            KeyCode::End | KeyCode::Char('G') => self.messages_scroll = 0,
+            _ => {}
+        }
+    }
+
+    /// Popup with the status message history above the tail of the log file, scrolled to
+    /// the newest lines unless the user scrolled up
+    pub(crate) fn draw_messages(&mut self, f: &mut Frame, log: &[String]) {
+        let area = f.area();
+        let popup = ratatui::layout::Rect {
+            x: area.width / 20,
+            y: area.height / 10,
+            width: area.width - area.width / 10,
+            height: area.height - area.height / 5,
+        };
+        let heading = Style::default()
+            .fg(self.theme.mauve)
+            .add_modifier(Modifier::BOLD);
+        let dim = Style::default().fg(self.theme.subtext0);
+
+        let mut text = vec![Line::from(Span::styled("Status messages", heading))];
+        if self.messages.is_empty() {
+            text.push(Line::from(Span::styled("  (none yet)", dim)));
+        }
+        for (time, message) in &self.messages {
+            text.push(Line::from(vec![
+                Span::styled(format!("  {} ", time.format("%H:%M:%S")), dim),
+                Span::raw(message.clone()),
+            ]));
+        }
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            format!("Log: {}", crate::logging::log_file_path().display()),
+            heading,
+        )));
+        if log.is_empty() {
+            text.push(Line::from(Span::styled("  (empty)", dim)));
+        }
+        for line in log {
+            // Text lines have the level between spaces, JSON lines as a "level" value
+            let color = if line.contains(" ERROR ") || line.contains(r#""level":"ERROR""#) {
+                self.theme.red
+            } else if line.contains(" WARN ") || line.contains(r#""level":"WARN""#) {
+                self.theme.yellow
+            } else {
+                self.theme.text
+            };
+            text.push(Line::from(Span::styled(
+                format!("  {}", line),
+                Style::default().fg(color),
+            )));
+        }
+
+        // Keep the scroll position within the text so scrolling back down responds at once
+        let visible = usize::from(popup.height.saturating_sub(2));
+        let max_scroll = text.len().saturating_sub(visible);
+        self.messages_scroll = self.messages_scroll.min(max_scroll);
+        let top = max_scroll - self.messages_scroll;
+
+        let panel = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.theme.mauve))
+                    .title("Messages")
+                    .title_bottom(" ↑↓/jk:scroll  g/G:top/bottom  m/Esc:close "),
+            )
+            .style(Style::default().bg(self.theme.base))
+            .scroll((top.min(usize::from(u16::MAX)) as u16, 0));
+
+        f.render_widget(ratatui::widgets::Clear, popup);
+        f.render_widget(panel, popup);
+    }
+}
+
+/// The last lines of the TUI log file, empty if it can't be read
+fn log_tail() -> Vec<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let Ok(mut file) = std::fs::File::open(crate::logging::log_file_path()) else {
+        return Vec::new();
+    };
+    let start = file
+        .metadata()
+        .map_or(0, |metadata| metadata.len().saturating_sub(LOG_TAIL_BYTES));
+    let mut bytes = Vec::new();
+    if file
+        .seek(SeekFrom::Start(start))
+        .and_then(|_| file.read_to_end(&mut bytes))
+        .is_err()
+    {
+        return Vec::new();
+    }
+
+    // Reading from the middle of the file starts in the middle of a line
+    let text = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = text.lines().skip(usize::from(start > 0)).collect();
+    lines[lines.len().saturating_sub(LOG_TAIL_LINES)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Split tag editor input on commas and whitespace, dropping duplicates
+fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(|c: char| c == ',' || c.is_whitespace()) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
@@ -0,0 +1,903 @@
+// Full-screen forms for profile names, new SSO sessions and profile defaults
+use crate::error::Result;
+use crate::ui::app::{App, AppState, DefaultsConfigStep, NewProfileConfigStep, SsoConfigStep};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+impl App {
+    pub(crate) async fn handle_profile_input_key(&mut self, key: KeyCode) -> Result<()> {
+        match key {
+            KeyCode::Enter => {
+                // Save profile with entered name
+                if let Some(account) = self.pending_role.take() {
+                    self.state = AppState::Loading;
+                    self.save_profile_credentials(&account, &self.profile_input.clone())
+                        .await?;
+                }
+            }
+            KeyCode::Esc => {
+                // Cancel
+                self.state = AppState::Main;
+                self.profile_input.clear();
+                self.profile_input_cursor = 0;
+                self.pending_role = None;
+                self.existing_profile_name = None;
+            }
+            KeyCode::Left => {
+                // Move cursor left
+                if self.profile_input_cursor > 0 {
+                    self.profile_input_cursor -= 1;
+                }
+            }
+            KeyCode::Right => {
+                // Move cursor right
+                if self.profile_input_cursor < self.profile_input.len() {
+                    self.profile_input_cursor += 1;
+                }
+            }
+            KeyCode::Home => {
+                // Move cursor to beginning
+                self.profile_input_cursor = 0;
+            }
+            KeyCode::End => {
+                // Move cursor to end
+                self.profile_input_cursor = self.profile_input.len();
+            }
+            KeyCode::Backspace => {
+                // Delete character before cursor
+                if self.profile_input_cursor > 0 {
+                    self.profile_input.remove(self.profile_input_cursor - 1);
+                    self.profile_input_cursor -= 1;
+                }
+            }
+            KeyCode::Delete => {
+                // Delete character at cursor
+                if self.profile_input_cursor < self.profile_input.len() {
+                    self.profile_input.remove(self.profile_input_cursor);
+                }
+            }
+            KeyCode::Char(c) => {
+                // Only allow alphanumeric, dash, and underscore
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    self.profile_input.insert(self.profile_input_cursor, c);
+                    self.profile_input_cursor += 1;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub(crate) fn draw_profile_input_screen(&self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Title
+                Constraint::Length(5), // Info
+                Constraint::Length(3), // Input
+                Constraint::Min(0),    // Spacer
+                Constraint::Length(2), // Instructions
+            ])
+            .split(f.area());
+
+        // Title
+        let title = Paragraph::new("Save AWS Profile")
+            .style(
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(title, chunks[0]);
+
+        // Info about the role
+        let info_text = if let Some(ref role) = self.pending_role {
+            vec![
+                Line::from(format!(
+                    "Account: {} ({})",
+                    role.account_name, role.account_id
+                )),
+                Line::from(format!("Role: {}", role.role_name)),
+                Line::from(""),
+                Line::from("Enter a profile name (or press Enter to use default):"),
+            ]
+        } else {
+            vec![Line::from("No role selected")]
+        };
+
+        let info = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
+        f.render_widget(info, chunks[1]);
+
+        // Input field with cursor at the correct position
+        let input_with_cursor = if self.profile_input.is_empty() {
+            "█".to_string()
+        } else {
+            // Split the string at cursor position and insert cursor character
+            let (before, after) = self.profile_input.split_at(self.profile_input_cursor);
+            format!("{}█{}", before, after)
+        };
+        let input = Paragraph::new(input_with_cursor.as_str())
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL).title("Profile Name"));
+        f.render_widget(input, chunks[2]);
+
+        // Instructions
+        let instructions = Paragraph::new(
+            "Enter: Save | Esc: Cancel | ←→: Move cursor | Home/End: Jump | Type to edit",
+        )
+        .style(Style::default().fg(Color::Gray));
+        f.render_widget(instructions, chunks[4]);
+    }
+
+    pub(crate) async fn handle_sso_config_input_key(&mut self, key: KeyCode) -> Result<()> {
+        let current_step = if let AppState::SsoConfigInput { step } = &self.state {
+            step.clone()
+        } else {
+            return Ok(());
+        };
+
+        match key {
+            KeyCode::Enter => {
+                // Move to next step or save configuration
+                match current_step {
+                    SsoConfigStep::StartUrl => {
+                        if self.sso_start_url_input.trim().is_empty() {
+                            self.status_message = Some("SSO Start URL is required".to_string());
+                        } else {
+                            self.state = AppState::SsoConfigInput {
+                                step: SsoConfigStep::Region,
+                            };
+                            self.sso_input_cursor = self.sso_region_input.len();
+                        }
+                    }
+                    SsoConfigStep::Region => {
+                        if self.sso_region_input.trim().is_empty() {
+                            self.status_message = Some("SSO Region is required".to_string());
+                        } else if let Err(e) =
+                            crate::regions::check_region(self.sso_region_input.trim())
+                        {
+                            self.status_message = Some(format!("✗ {}", e));
+                        } else {
+                            self.status_message =
+                                crate::regions::region_warning(self.sso_region_input.trim())
+                                    .map(|warning| format!("⚠ {}", warning));
+                            self.state = AppState::SsoConfigInput {
+                                step: SsoConfigStep::SessionName,
+                            };
+                            self.sso_input_cursor = self.sso_session_name_input.len();
+                        }
+                    }
+                    SsoConfigStep::SessionName => {
+                        // Save configuration to ~/.aws/config
+                        let session_name = if self.sso_session_name_input.trim().is_empty() {
+                            "default-sso".to_string()
+                        } else {
+                            self.sso_session_name_input.trim().to_string()
+                        };
+
+                        let session = crate::aws_config::SsoSession {
+                            session_name: session_name.clone(),
+                            sso_start_url: self.sso_start_url_input.trim().to_string(),
+                            sso_region: self.sso_region_input.trim().to_string(),
+                            sso_registration_scopes: crate::aws_config::DEFAULT_REGISTRATION_SCOPES
+                                .to_string(),
+                        };
+
+                        match crate::aws_config::write_sso_session(&session) {
+                            Ok(()) => {
+                                self.status_message = Some(format!(
+                                    "✓ SSO session '{}' saved to ~/.aws/config",
+                                    session_name
+                                ));
+                                self.state = AppState::Main;
+
+                                // Clear input buffers
+                                self.sso_start_url_input.clear();
+                                self.sso_region_input.clear();
+                                self.sso_session_name_input = "default-sso".to_string();
+                                self.sso_input_cursor = 0;
+
+                                // Reload sessions list to show the new session
+                                self.load_all_sso_sessions().await;
+                            }
+                            Err(e) => {
+                                self.status_message =
+                                    Some(format!("Error saving configuration: {}", e));
+                            }
+                        }
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                // Cancel configuration
+                self.state = AppState::Main;
+                self.sso_start_url_input.clear();
+                self.sso_region_input.clear();
+                self.sso_session_name_input = "default-sso".to_string();
+                self.sso_input_cursor = 0;
+                self.status_message = Some("Configuration cancelled".to_string());
+            }
+            KeyCode::Left => {
+                if self.sso_input_cursor > 0 {
+                    self.sso_input_cursor -= 1;
+                }
+            }
+            KeyCode::Right => {
+                let max_len = match current_step {
+                    SsoConfigStep::StartUrl => self.sso_start_url_input.len(),
+                    SsoConfigStep::Region => self.sso_region_input.len(),
+                    SsoConfigStep::SessionName => self.sso_session_name_input.len(),
+                };
+                if self.sso_input_cursor < max_len {
+                    self.sso_input_cursor += 1;
+                }
+            }
+            KeyCode::Home => {
+                self.sso_input_cursor = 0;
+            }
+            KeyCode::End => {
+                self.sso_input_cursor = match current_step {
+                    SsoConfigStep::StartUrl => self.sso_start_url_input.len(),
+                    SsoConfigStep::Region => self.sso_region_input.len(),
+                    SsoConfigStep::SessionName => self.sso_session_name_input.len(),
+                };
+            }
+            KeyCode::Backspace => {
+                if self.sso_input_cursor > 0 {
+                    match current_step {
+                        SsoConfigStep::StartUrl => {
+                            self.sso_start_url_input.remove(self.sso_input_cursor - 1);
+                        }
+                        SsoConfigStep::Region => {
+                            self.sso_region_input.remove(self.sso_input_cursor - 1);
+                        }
+                        SsoConfigStep::SessionName => {
+                            self.sso_session_name_input
+                                .remove(self.sso_input_cursor - 1);
+                        }
+                    }
+                    self.sso_input_cursor -= 1;
+                }
+            }
+            KeyCode::Delete => match current_step {
+                SsoConfigStep::StartUrl => {
+                    if self.sso_input_cursor < self.sso_start_url_input.len() {
+                        self.sso_start_url_input.remove(self.sso_input_cursor);
+                    }
+                }
+                SsoConfigStep::Region => {
+                    if self.sso_input_cursor < self.sso_region_input.len() {
+                        self.sso_region_input.remove(self.sso_input_cursor);
+                    }
+                }
+                SsoConfigStep::SessionName => {
+                    if self.sso_input_cursor < self.sso_session_name_input.len() {
+                        self.sso_session_name_input.remove(self.sso_input_cursor);
+                    }
+                }
+            },
+            KeyCode::Char(c) => {
+                // Allow reasonable characters for URLs and region names
+                match current_step {
+                    SsoConfigStep::StartUrl => {
+                        self.sso_start_url_input.insert(self.sso_input_cursor, c);
+                        self.sso_input_cursor += 1;
+                    }
+                    SsoConfigStep::Region => {
+                        // Only allow lowercase letters, digits, and hyphens for region
+                        if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' {
+                            self.sso_region_input.insert(self.sso_input_cursor, c);
+                            self.sso_input_cursor += 1;
+                        }
+                    }
+                    SsoConfigStep::SessionName => {
+                        // Allow alphanumeric, dash, and underscore
+                        if c.is_alphanumeric() || c == '-' || c == '_' {
+                            self.sso_session_name_input.insert(self.sso_input_cursor, c);
+                            self.sso_input_cursor += 1;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub(crate) fn draw_sso_config_input_screen(&self, f: &mut Frame, step: SsoConfigStep) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),  // Title
+                Constraint::Length(10), // Instructions
+                Constraint::Length(3),  // Input
+                Constraint::Min(0),     // Spacer
+                Constraint::Length(2),  // Help
+            ])
+            .split(f.area());
+
+        // Title
+        let title = Paragraph::new("AWS SSO Configuration")
+            .style(
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(title, chunks[0]);
+
+        // Instructions based on current step
+        let (step_title, instructions, example) = match step {
+            SsoConfigStep::StartUrl => (
+                "Step 1 of 3: SSO Start URL",
+                "Enter your AWS SSO start URL (IAM Identity Center portal URL)",
+                "Example: https://my-org.awsapps.com/start",
+            ),
+            SsoConfigStep::Region => (
+                "Step 2 of 3: SSO Region",
+                "Enter the AWS Region where SSO is configured",
+                "Example: us-east-1",
+            ),
+            SsoConfigStep::SessionName => (
+                "Step 3 of 3: Session Name",
+                "Enter a name for this SSO session (optional)",
+                "Default: default-sso",
+            ),
+        };
+
+        let info_text = vec![
+            Line::from(Span::styled(
+                step_title,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(instructions),
+            Line::from(""),
+            Line::from(Span::styled(example, Style::default().fg(Color::Gray))),
+            Line::from(""),
+            Line::from("The configuration will be saved to ~/.aws/config"),
+            Line::from("as a [sso-session] section."),
+        ];
+
+        let info = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
+        f.render_widget(info, chunks[1]);
+
+        // Input field with cursor
+        let (current_input, field_label) = match step {
+            SsoConfigStep::StartUrl => (&self.sso_start_url_input, "SSO Start URL"),
+            SsoConfigStep::Region => (&self.sso_region_input, "SSO Region"),
+            SsoConfigStep::SessionName => (&self.sso_session_name_input, "Session Name"),
+        };
+
+        let input_with_cursor = if current_input.is_empty() {
+            "█".to_string()
+        } else {
+            let (before, after) = current_input.split_at(self.sso_input_cursor);
+            format!("{}█{}", before, after)
+        };
+
+        let input = Paragraph::new(input_with_cursor.as_str())
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL).title(field_label));
+        f.render_widget(input, chunks[2]);
+
+        // Help
+        let help = Paragraph::new("Enter: Next | Esc: Cancel | ←→: Move cursor | Type to edit")
+            .style(Style::default().fg(Color::Gray));
+        f.render_widget(help, chunks[4]);
+    }
+
+    pub(crate) async fn handle_defaults_config_input_key(&mut self, key: KeyCode) -> Result<()> {
+        let current_step = if let AppState::DefaultsConfigInput { step } = &self.state {
+            step.clone()
+        } else {
+            return Ok(());
+        };
+
+        match key {
+            KeyCode::Enter => {
+                match current_step {
+                    DefaultsConfigStep::Region => {
+                        if self.default_region_input.trim().is_empty() {
+                            self.status_message = Some("Region is required".to_string());
+                        } else if let Err(e) =
+                            crate::regions::check_region(self.default_region_input.trim())
+                        {
+                            self.status_message = Some(format!("✗ {}", e));
+                        } else {
+                            self.status_message =
+                                crate::regions::region_warning(self.default_region_input.trim())
+                                    .map(|warning| format!("⚠ {}", warning));
+                            self.state = AppState::DefaultsConfigInput {
+                                step: DefaultsConfigStep::Output,
+                            };
+                            self.default_input_cursor = self.default_output_input.len();
+                        }
+                    }
+                    DefaultsConfigStep::Output => {
+                        if let Err(e) =
+                            crate::regions::check_output(self.default_output_input.trim())
+                        {
+                            self.status_message = Some(format!("✗ {}", e));
+                            return Ok(());
+                        }
+
+                        // Save default configuration to [profile awsom-defaults]
+                        let config = crate::aws_config::DefaultConfig {
+                            region: self.default_region_input.trim().to_string(),
+                            output: self.default_output_input.trim().to_string(),
+                        };
+
+                        match crate::aws_config::write_awsom_defaults(&config) {
+                            Ok(()) => {
+                                self.status_message = Some(
+                                    "✓ Default settings saved to [profile awsom-defaults]"
+                                        .to_string(),
+                                );
+
+                                // Now proceed to new profile configuration
+                                if let Some(account) = &self.pending_role {
+                                    self.new_profile_name_input =
+                                        self.default_profile_name(account);
+                                    self.new_profile_region_input = config.region.clone();
+                                    self.new_profile_output_input = config.output.clone();
+                                    self.new_profile_input_cursor =
+                                        self.new_profile_name_input.len();
+                                    self.state = AppState::NewProfileConfigInput {
+                                        step: NewProfileConfigStep::ProfileName,
+                                    };
+                                }
+
+                                // Clear input buffers
+                                self.default_region_input = String::new();
+                                self.default_output_input = String::new();
+                                self.default_input_cursor = 0;
+                            }
+                            Err(e) => {
+                                self.status_message = Some(format!("Error saving defaults: {}", e));
+                            }
+                        }
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.default_region_input = String::new();
+                self.default_output_input = String::new();
+                self.default_input_cursor = 0;
+                self.pending_role = None;
+                self.status_message = Some("Configuration cancelled".to_string());
+            }
+            KeyCode::Left => {
+                if self.default_input_cursor > 0 {
+                    self.default_input_cursor -= 1;
+                }
+            }
+            KeyCode::Right => {
+                let max_len = match current_step {
+                    DefaultsConfigStep::Region => self.default_region_input.len(),
+                    DefaultsConfigStep::Output => self.default_output_input.len(),
+                };
+                if self.default_input_cursor < max_len {
+                    self.default_input_cursor += 1;
+                }
+            }
+            KeyCode::Home => {
+                self.default_input_cursor = 0;
+            }
+            KeyCode::End => {
+                self.default_input_cursor = match current_step {
+                    DefaultsConfigStep::Region => self.default_region_input.len(),
+                    DefaultsConfigStep::Output => self.default_output_input.len(),
+                };
+            }
+            KeyCode::Backspace => {
+                if self.default_input_cursor > 0 {
+                    match current_step {
+                        DefaultsConfigStep::Region => {
+                            self.default_region_input
+                                .remove(self.default_input_cursor - 1);
+                        }
+                        DefaultsConfigStep::Output => {
+                            self.default_output_input
+                                .remove(self.default_input_cursor - 1);
+                        }
+                    }
+                    self.default_input_cursor -= 1;
+                }
+            }
+            KeyCode::Delete => match current_step {
+                DefaultsConfigStep::Region => {
+                    if self.default_input_cursor < self.default_region_input.len() {
+                        self.default_region_input.remove(self.default_input_cursor);
+                    }
+                }
+                DefaultsConfigStep::Output => {
+                    if self.default_input_cursor < self.default_output_input.len() {
+                        self.default_output_input.remove(self.default_input_cursor);
+                    }
+                }
+            },
+            KeyCode::Char(c) => match current_step {
+                DefaultsConfigStep::Region => {
+                    if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' {
+                        self.default_region_input
+                            .insert(self.default_input_cursor, c);
+                        self.default_input_cursor += 1;
+                    }
+                }
+                DefaultsConfigStep::Output => {
+                    if c.is_alphanumeric() || c == '-' {
+                        self.default_output_input
+                            .insert(self.default_input_cursor, c);
+                        self.default_input_cursor += 1;
+                    }
+                }
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub(crate) fn draw_defaults_config_input_screen(
+        &self,
+        f: &mut Frame,
+        step: DefaultsConfigStep,
+    ) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),  // Title
+                Constraint::Length(10), // Instructions
+                Constraint::Length(3),  // Input
+                Constraint::Min(0),     // Spacer
+                Constraint::Length(2),  // Help
+            ])
+            .split(f.area());
+
+        // Title
+        let title = Paragraph::new("Configure Default Profile Settings")
+            .style(
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(title, chunks[0]);
+
+        // Instructions
+        let (step_title, instructions, example) = match step {
+            DefaultsConfigStep::Region => (
+                "Step 1 of 2: Default Region",
+                "Enter the default AWS region for new profiles",
+                "Example: us-east-1, eu-west-1, ap-southeast-2",
+            ),
+            DefaultsConfigStep::Output => (
+                "Step 2 of 2: Default Output Format",
+                "Enter the default output format for AWS CLI",
+                "Options: json, text, table, yaml",
+            ),
+        };
+
+        let info_text = vec![
+            Line::from(Span::styled(
+                step_title,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(instructions),
+            Line::from(""),
+            Line::from(Span::styled(example, Style::default().fg(Color::Gray))),
+            Line::from(""),
+            Line::from("These settings will be saved to ~/.aws/config as:"),
+            Line::from("[profile awsom-defaults]"),
+            Line::from("This allows awsom to provide defaults without interfering with"),
+            Line::from("your [default] profile."),
+        ];
+
+        let info = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
+        f.render_widget(info, chunks[1]);
+
+        // Input field
+        let (current_input, field_label) = match step {
+            DefaultsConfigStep::Region => (&self.default_region_input, "Default Region"),
+            DefaultsConfigStep::Output => (&self.default_output_input, "Default Output Format"),
+        };
+
+        let input_with_cursor = if current_input.is_empty() {
+            "█".to_string()
+        } else {
+            let (before, after) = current_input.split_at(self.default_input_cursor);
+            format!("{}█{}", before, after)
+        };
+
+        let input = Paragraph::new(input_with_cursor.as_str())
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL).title(field_label));
+        f.render_widget(input, chunks[2]);
+
+        // Help
+        let help = Paragraph::new("Enter: Next | Esc: Cancel | ←→: Move cursor | Type to edit")
+            .style(Style::default().fg(Color::Gray));
+        f.render_widget(help, chunks[4]);
+    }
+
+    pub(crate) async fn handle_new_profile_config_input_key(&mut self, key: KeyCode) -> Result<()> {
+        let current_step = if let AppState::NewProfileConfigInput { step } = &self.state {
+            step.clone()
+        } else {
+            return Ok(());
+        };
+
+        match key {
+            KeyCode::Enter => {
+                match current_step {
+                    NewProfileConfigStep::ProfileName => {
+                        if self.new_profile_name_input.trim().is_empty() {
+                            self.status_message = Some("Profile name is required".to_string());
+                        } else {
+                            self.state = AppState::NewProfileConfigInput {
+                                step: NewProfileConfigStep::Region,
+                            };
+                            self.new_profile_input_cursor = self.new_profile_region_input.len();
+                        }
+                    }
+                    NewProfileConfigStep::Region => {
+                        if self.new_profile_region_input.trim().is_empty() {
+                            self.status_message = Some("Region is required".to_string());
+                        } else if let Err(e) =
+                            crate::regions::check_region(self.new_profile_region_input.trim())
+                        {
+                            self.status_message = Some(format!("✗ {}", e));
+                        } else {
+                            self.status_message = crate::regions::region_warning(
+                                self.new_profile_region_input.trim(),
+                            )
+                            .map(|warning| format!("⚠ {}", warning));
+                            self.state = AppState::NewProfileConfigInput {
+                                step: NewProfileConfigStep::Output,
+                            };
+                            self.new_profile_input_cursor = self.new_profile_output_input.len();
+                        }
+                    }
+                    NewProfileConfigStep::Output => {
+                        if let Err(e) =
+                            crate::regions::check_output(self.new_profile_output_input.trim())
+                        {
+                            self.status_message = Some(format!("✗ {}", e));
+                            return Ok(());
+                        }
+
+                        // Save the profile with credentials
+                        if let Some(account) = self.pending_role.take() {
+                            let profile_name = self.new_profile_name_input.trim().to_string();
+                            self.state = AppState::Loading;
+                            self.save_profile_credentials(&account, &profile_name)
+                                .await?;
+
+                            // Clear input buffers
+                            self.new_profile_name_input.clear();
+                            self.new_profile_region_input.clear();
+                            self.new_profile_output_input.clear();
+                            self.new_profile_input_cursor = 0;
+                        }
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.new_profile_name_input.clear();
+                self.new_profile_region_input.clear();
+                self.new_profile_output_input.clear();
+                self.new_profile_input_cursor = 0;
+                self.pending_role = None;
+                self.status_message = Some("Profile configuration cancelled".to_string());
+            }
+            KeyCode::Left => {
+                if self.new_profile_input_cursor > 0 {
+                    self.new_profile_input_cursor -= 1;
+                }
+            }
+            KeyCode::Right => {
+                let max_len = match current_step {
+                    NewProfileConfigStep::ProfileName => self.new_profile_name_input.len(),
+                    NewProfileConfigStep::Region => self.new_profile_region_input.len(),
+                    NewProfileConfigStep::Output => self.new_profile_output_input.len(),
+                };
+                if self.new_profile_input_cursor < max_len {
+                    self.new_profile_input_cursor += 1;
+                }
+            }
+            KeyCode::Home => {
+                self.new_profile_input_cursor = 0;
+            }
+            KeyCode::End => {
+                self.new_profile_input_cursor = match current_step {
+                    NewProfileConfigStep::ProfileName => self.new_profile_name_input.len(),
+                    NewProfileConfigStep::Region => self.new_profile_region_input.len(),
+                    NewProfileConfigStep::Output => self.new_profile_output_input.len(),
+                };
+            }
+            KeyCode::Backspace => {
+                if self.new_profile_input_cursor > 0 {
+                    match current_step {
+                        NewProfileConfigStep::ProfileName => {
+                            self.new_profile_name_input
+                                .remove(self.new_profile_input_cursor - 1);
+                        }
+                        NewProfileConfigStep::Region => {
+                            self.new_profile_region_input
+                                .remove(self.new_profile_input_cursor - 1);
+                        }
+                        NewProfileConfigStep::Output => {
+                            self.new_profile_output_input
+                                .remove(self.new_profile_input_cursor - 1);
+                        }
+                    }
+                    self.new_profile_input_cursor -= 1;
+                }
+            }
+            KeyCode::Delete => match current_step {
+                NewProfileConfigStep::ProfileName => {
+                    if self.new_profile_input_cursor < self.new_profile_name_input.len() {
+                        self.new_profile_name_input
+                            .remove(self.new_profile_input_cursor);
+                    }
+                }
+                NewProfileConfigStep::Region => {
+                    if self.new_profile_input_cursor < self.new_profile_region_input.len() {
+                        self.new_profile_region_input
+                            .remove(self.new_profile_input_cursor);
+                    }
+                }
+                NewProfileConfigStep::Output => {
+                    if self.new_profile_input_cursor < self.new_profile_output_input.len() {
+                        self.new_profile_output_input
+                            .remove(self.new_profile_input_cursor);
+                    }
+                }
+            },
+            KeyCode::Char(c) => match current_step {
+                NewProfileConfigStep::ProfileName => {
+                    if c.is_alphanumeric() || c == '-' || c == '_' {
+                        self.new_profile_name_input
+                            .insert(self.new_profile_input_cursor, c);
+                        self.new_profile_input_cursor += 1;
+                    }
+                }
+                NewProfileConfigStep::Region => {
+                    if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' {
+                        self.new_profile_region_input
+                            .insert(self.new_profile_input_cursor, c);
+                        self.new_profile_input_cursor += 1;
+                    }
+                }
+                NewProfileConfigStep::Output => {
+                    if c.is_alphanumeric() || c == '-' {
+                        self.new_profile_output_input
+                            .insert(self.new_profile_input_cursor, c);
+                        self.new_profile_input_cursor += 1;
+                    }
+                }
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub(crate) fn draw_new_profile_config_input_screen(
+        &self,
+        f: &mut Frame,
+        step: NewProfileConfigStep,
+    ) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),  // Title
+                Constraint::Length(12), // Instructions
+                Constraint::Length(3),  // Input
+                Constraint::Min(0),     // Spacer
+                Constraint::Length(2),  // Help
+            ])
+            .split(f.area());
+
+        // Title
+        let title = Paragraph::new("Configure New AWS Profile")
+            .style(
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(title, chunks[0]);
+
+        // Instructions
+        let (step_title, instructions, example) = match step {
+            NewProfileConfigStep::ProfileName => (
+                "Step 1 of 3: Profile Name",
+                "Enter a name for this AWS profile",
+                "Example: my-prod-account, dev-readonly",
+            ),
+            NewProfileConfigStep::Region => (
+                "Step 2 of 3: Region",
+                "Enter the AWS region for this profile",
+                "Example: us-east-1, eu-west-1, ap-southeast-2",
+            ),
+            NewProfileConfigStep::Output => (
+                "Step 3 of 3: Output Format",
+                "Enter the output format for AWS CLI",
+                "Options: json, text, table, yaml",
+            ),
+        };
+
+        let info_text = vec![
+            Line::from(Span::styled(
+                step_title,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(instructions),
+            Line::from(""),
+            Line::from(Span::styled(example, Style::default().fg(Color::Gray))),
+            Line::from(""),
+            Line::from("The profile will be saved to ~/.aws/config and"),
+            Line::from("credentials will be written to ~/.aws/credentials"),
+            Line::from(""),
+            Line::from(Span::styled(
+                "After configuration, credentials will be fetched automatically.",
+                Style::default().fg(Color::Green),
+            )),
+        ];
+
+        let info = Paragraph::new(info_text).block(Block::default().borders(Borders::ALL));
+        f.render_widget(info, chunks[1]);
+
+        // Input field
+        let (current_input, field_label, cursor_pos) = match step {
+            NewProfileConfigStep::ProfileName => (
+                &self.new_profile_name_input,
+                "Profile Name",
+                self.new_profile_input_cursor,
+            ),
+            NewProfileConfigStep::Region => (
+                &self.new_profile_region_input,
+                "Region",
+                self.new_profile_input_cursor,
+            ),
+            NewProfileConfigStep::Output => (
+                &self.new_profile_output_input,
+                "Output Format",
+                self.new_profile_input_cursor,
+            ),
+        };
+
+        let input_with_cursor = if current_input.is_empty() {
+            "█".to_string()
+        } else {
+            let (before, after) = current_input.split_at(cursor_pos);
+            format!("{}█{}", before, after)
+        };
+
+        let input = Paragraph::new(input_with_cursor.as_str())
+            .style(Style::default().fg(Color::Yellow))
+            .block(Block::default().borders(Borders::ALL).title(field_label));
+        f.render_widget(input, chunks[2]);
+
+        // Help
+        let help = Paragraph::new("Enter: Next | Esc: Cancel | ←→: Move cursor | Type to edit")
+            .style(Style::default().fg(Color::Gray));
+        f.render_widget(help, chunks[4]);
+    }
+}
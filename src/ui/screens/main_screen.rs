@@ -0,0 +1,395 @@
+// Main screen with both panes, and the help, login and error screens
+use crate::expiry::{format_time_remaining, is_expiring_soon};
+use crate::ui::app::{ActivePane, App, AppState};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, LineGauge, Paragraph},
+    Frame,
+};
+
+impl App {
+    pub(crate) fn draw_main_screen(&mut self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),                      // Header
+                Constraint::Min(10),                        // Accounts pane (flexible)
+                Constraint::Length(self.sessions.height()), // Sessions pane (dynamic)
+                Constraint::Length(1),                      // Status bar
+                Constraint::Length(2),                      // Help bar (2 lines)
+            ])
+            .split(f.area());
+
+        // Header
+        let mut title = "awsom - AWS Organization Manager".to_string();
+        if let Some(session) = &self.config.current_session {
+            title.push_str(&format!(" - session: {}", session));
+        }
+        if self.config.read_only() {
+            title.push_str(" (read-only)");
+        }
+        let mut title = vec![Span::raw(title)];
+        if self.offline {
+            title.push(Span::styled(
+                "  OFFLINE - showing cached accounts (r to retry)",
+                Style::default().fg(self.theme.red),
+            ));
+        }
+        let header = Paragraph::new(Line::from(title))
+            .style(
+                Style::default()
+                    .fg(self.theme.blue)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(header, chunks[0]);
+
+        // Account/Role table, with OU header rows in the tree view
+        let accounts_focused = self.active_pane == ActivePane::Accounts;
+        self.accounts
+            .draw(f, chunks[1], &self.theme, accounts_focused, &self.config);
+
+        // Sessions pane
+        let sessions_focused = self.active_pane == ActivePane::Sessions;
+        self.sessions.draw(
+            f,
+            chunks[2],
+            &self.theme,
+            sessions_focused,
+            self.config.active_workspace.as_deref(),
+            self.config.ui.expiry_warning_minutes,
+        );
+
+        self.draw_status_bar(f, chunks[3]);
+
+        // Help bar (2 lines for better readability)
+        // Make Enter key description context-aware
+        let enter_action = match self.active_pane {
+            ActivePane::Sessions => "Enter:login/logout session",
+            ActivePane::Accounts => "Enter:activate/deactivate credentials",
+        };
+
+        let help_lines = vec![
+            Line::from(vec![Span::raw(format!(
+                "q:quit | ?:help | ::command | m:messages | T:theme | Tab:switch pane | ↑↓/jk:navigate | {}",
+                enter_action
+            ))]),
+            Line::from(vec![
+                Span::raw("Sessions: "),
+                Span::styled("a", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":add "),
+                Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":edit "),
+                Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":delete | Accounts: "),
+                Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":edit "),
+                Span::styled("o", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":settings "),
+                Span::styled("d", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":make default "),
+                Span::styled("c", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":console "),
+                Span::styled("f", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":favorite "),
+                Span::styled("s/S", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":sort "),
+                Span::styled("g", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":group "),
+                Span::styled("t", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":tags "),
+                Span::styled("r", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":refresh "),
+                Span::styled("R", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(":refresh all"),
+            ]),
+        ];
+        let help_bar = Paragraph::new(help_lines).style(Style::default().fg(self.theme.subtext0));
+        f.render_widget(help_bar, chunks[4]);
+    }
+
+    /// Last status message on the left, the next automatic refresh and the countdown to the
+    /// next credential expiry on the right, and a gauge in between while accounts are being
+    /// listed
+    fn draw_status_bar(&self, f: &mut Frame, area: ratatui::layout::Rect) {
+        let (countdown, color) = match self.soonest_expiry() {
+            Some((profile, expiration)) => {
+                let threshold = i64::from(self.config.ui.expiry_warning_minutes);
+                let color = if is_expiring_soon(expiration, threshold) {
+                    self.theme.yellow
+                } else {
+                    self.theme.green
+                };
+                (
+                    format!(
+                        "⏱ {} expires in {}",
+                        profile,
+                        format_time_remaining(expiration)
+                    ),
+                    color,
+                )
+            }
+            None => ("No active credentials".to_string(), self.theme.subtext0),
+        };
+
+        // Next automatic reload of the Accounts pane, as m:ss, once there is one to reload
+        let next_refresh = match self.next_auto_refresh() {
+            _ if self.accounts.items.is_empty() => String::new(),
+            Some(left) => format!("↻ {}:{:02}", left.as_secs() / 60, left.as_secs() % 60),
+            None => "↻ off".to_string(),
+        };
+
+        let progress = self
+            .discovery
+            .as_ref()
+            .and_then(|discovery| discovery.progress)
+            .filter(|_| self.state != AppState::CommandLine);
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(if progress.is_some() { 30 } else { 0 }),
+                Constraint::Length(next_refresh.chars().count() as u16 + 1),
+                Constraint::Length(countdown.chars().count() as u16 + 1),
+            ])
+            .split(area);
+
+        let message = if self.state == AppState::CommandLine {
+            format!(":{}█", self.command_input)
+        } else {
+            self.status_message.clone().unwrap_or_default()
+        };
+        let message = Paragraph::new(message).style(Style::default().fg(self.theme.text));
+        f.render_widget(message, chunks[0]);
+
+        if let Some((done, total)) = progress {
+            let gauge = LineGauge::default()
+                .ratio(if total == 0 {
+                    1.0
+                } else {
+                    done as f64 / total as f64
+                })
+                .label("Esc:stop")
+                .style(Style::default().fg(self.theme.subtext0))
+                .filled_style(Style::default().fg(self.theme.blue))
+                .unfilled_style(Style::default().fg(self.theme.surface1));
+            f.render_widget(gauge, chunks[1]);
+        }
+
+        let next_refresh = Paragraph::new(next_refresh)
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(self.theme.subtext0));
+        f.render_widget(next_refresh, chunks[2]);
+
+        let countdown = Paragraph::new(countdown)
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(color));
+        f.render_widget(countdown, chunks[3]);
+    }
+
+    pub(crate) fn draw_help_screen(&self, f: &mut Frame) {
+        let help_text = vec![
+            Line::from(Span::styled(
+                "awsom - Help",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from("Navigation:"),
+            Line::from("  Tab         - Switch between Sessions and Accounts panes"),
+            Line::from("  ↑, k        - Move selection up"),
+            Line::from("  ↓, j        - Move selection down"),
+            Line::from(""),
+            Line::from("Sessions Pane:"),
+            Line::from("  Enter       - Login/Logout selected SSO session"),
+            Line::from("  a           - Add new SSO session"),
+            Line::from("  e           - Edit selected SSO session"),
+            Line::from("  d           - Delete selected SSO session"),
+            Line::from(""),
+            Line::from("Accounts Pane:"),
+            Line::from("  Enter       - Start/stop session (activate/invalidate credentials)"),
+            Line::from("  e           - Edit profile (name, region, output) for selected role"),
+            Line::from("  o           - Change profile settings without fetching credentials"),
+            Line::from("  d           - Make selected role's profile the default"),
+            Line::from("  c           - Pick a region and open AWS Console for selected role"),
+            Line::from("  i           - Show session duration and permission set description"),
+            Line::from("  Space       - Mark/unmark selected role for a bulk action"),
+            Line::from("  b           - Bulk actions on marked roles: console, start, stop"),
+            Line::from("  f           - Pin/unpin selected role as a favorite (shown first)"),
+            Line::from(
+                "  s           - Cycle sort column (account, ID, role, expiry, status, recent)",
+            ),
+            Line::from("  S           - Reverse sort order"),
+            Line::from("  g           - Cycle grouping: none, Organizations OU, tag (Enter folds)"),
+            Line::from("  t           - Edit tags of selected role"),
+            Line::from("  K           - Add an EKS cluster to the kubeconfig as selected role"),
+            Line::from("  y           - Copy credentials: export lines, JSON or profile name"),
+            Line::from("                ([ui] copy_format)"),
+            Line::from("  r           - Refresh account/role list from AWS (skips the cache)"),
+            Line::from(
+                "  R           - Re-fetch credentials for all active profiles in the session",
+            ),
+            Line::from(""),
+            Line::from("General:"),
+            Line::from("  w           - Switch workspace (config.toml [[workspaces]])"),
+            Line::from(
+                "  A           - List the roles of all logged-in sessions, or the selected one's",
+            ),
+            Line::from(
+                "  P           - Pause/resume the automatic refresh ([ui] refresh_interval)",
+            ),
+            Line::from("  p           - Applications on the access portal besides AWS accounts"),
+            Line::from("  :, Ctrl+P   - Command palette: type to search all actions, Enter runs;"),
+            Line::from(
+                "                or tag <name> filters by tag (tag clears), group none|ou|tag",
+            ),
+            Line::from("  m           - Messages: earlier status messages and the log file"),
+            Line::from("  T           - Cycle theme: latte, frappe, macchiato, mocha, custom"),
+            Line::from("  q, Esc      - Quit application; Esc first stops loading accounts,"),
+            Line::from("                keeping the rows listed so far"),
+            Line::from("  ?, F1       - Show this help screen"),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Press any key to return to main screen",
+                Style::default().fg(Color::Yellow),
+            )),
+        ];
+
+        let help = Paragraph::new(help_text)
+            .block(Block::default().borders(Borders::ALL).title("Help"))
+            .style(Style::default().fg(Color::White));
+        f.render_widget(help, f.area());
+    }
+
+    pub(crate) fn draw_loading_screen(&self, f: &mut Frame) {
+        let mut loading_text = vec![];
+
+        // Check if we're showing device auth info
+        if let Some(ref auth_info) = self.device_auth_info {
+            loading_text.push(Line::from(Span::styled(
+                "AWS SSO Login",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            loading_text.push(Line::from(""));
+
+            // Use complete URL with code if available, otherwise show URL + code separately
+            if let Some(ref complete_url) = auth_info.verification_uri_complete {
+                // Show single URL with code embedded
+                let instruction_text = if crate::env::is_headless_environment() {
+                    "Copy and paste this URL (code is already included):"
+                } else {
+                    "Browser opened automatically. If not, copy this URL:"
+                };
+
+                loading_text.push(Line::from(Span::styled(
+                    instruction_text,
+                    Style::default().fg(Color::White),
+                )));
+                loading_text.push(Line::from(""));
+                loading_text.push(Line::from(Span::styled(
+                    complete_url,
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                // Fallback: show URL and code separately
+                let instruction_text = if crate::env::is_headless_environment() {
+                    "Open this URL in a browser (on another machine if needed):"
+                } else {
+                    "Browser opened automatically. If not, visit:"
+                };
+
+                loading_text.push(Line::from(Span::styled(
+                    instruction_text,
+                    Style::default().fg(Color::White),
+                )));
+                loading_text.push(Line::from(""));
+                loading_text.push(Line::from(Span::styled(
+                    &auth_info.verification_uri,
+                    Style::default().fg(Color::Green),
+                )));
+                loading_text.push(Line::from(""));
+                loading_text.push(Line::from(Span::styled(
+                    "And enter code:",
+                    Style::default().fg(Color::White),
+                )));
+                loading_text.push(Line::from(""));
+                loading_text.push(Line::from(Span::styled(
+                    &auth_info.user_code,
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            }
+            if let Some(ref copy_status) = self.device_url_copy_status {
+                loading_text.push(Line::from(""));
+                loading_text.push(Line::from(Span::styled(
+                    copy_status.as_str(),
+                    Style::default().fg(Color::Gray),
+                )));
+            }
+            loading_text.push(Line::from(""));
+            let seconds_left = auth_info.seconds_left();
+            loading_text.push(Line::from(Span::styled(
+                format!(
+                    "Waiting for authorization... the code expires in {}:{:02}",
+                    seconds_left / 60,
+                    seconds_left % 60
+                ),
+                // Hurry the user along in the last minute
+                Style::default().fg(if seconds_left < 60 {
+                    Color::Yellow
+                } else {
+                    Color::Gray
+                }),
+            )));
+            loading_text.push(Line::from(""));
+            loading_text.push(Line::from(Span::styled(
+                "Press 'c' to copy the URL, 'q' or 'Esc' to cancel",
+                Style::default().fg(Color::Yellow),
+            )));
+        } else {
+            // Generic loading message
+            loading_text.push(Line::from(""));
+            loading_text.push(Line::from(Span::styled(
+                "Loading...",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+
+        let loading = Paragraph::new(loading_text)
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::White));
+        f.render_widget(loading, f.area());
+    }
+
+    pub(crate) fn draw_error_screen(&self, f: &mut Frame, message: String) {
+        let error_text = vec![
+            Line::from(Span::styled(
+                "Error",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(message),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Press any key to continue",
+                Style::default().fg(Color::Yellow),
+            )),
+        ];
+
+        let error = Paragraph::new(error_text)
+            .block(Block::default().borders(Borders::ALL).title("Error"))
+            .style(Style::default().fg(Color::White));
+        f.render_widget(error, f.area());
+    }
+}
@@ -1 +1,8 @@
-// TUI screens
+// TUI screens, each drawing and handling keys for some of the app's states
+mod applications;
+mod bulk;
+mod dialogs;
+mod forms;
+mod main_screen;
+
+pub(crate) use bulk::StartTarget;
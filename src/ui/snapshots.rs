@@ -0,0 +1,284 @@
+// Snapshot tests of the TUI renderer
+//
+// Each test draws a screen from hand-built `App` state on a `TestBackend` and compares
+// the buffer, text and styles, with `src/ui/snapshots/<name>.snap`. After an intended
+// change to the UI, run `AWSOM_UPDATE_SNAPSHOTS=1 cargo test` and review the diff of the
+// snapshot files.
+use super::app::{ActivePane, App, AppState, ConfirmAction, RoleDetails};
+use super::event::Discovery;
+use super::widgets::accounts_pane::AccountRoleWithStatus;
+use super::widgets::sessions_pane::SsoSessionInfo;
+use crate::applications::Application;
+use crate::auth::DeviceAuthorizationInfo;
+use crate::config::{Config, CustomTheme, Theme};
+use crate::models::{AccountRole, SsoInstance};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::PathBuf;
+
+const WIDTH: u16 = 120;
+const HEIGHT: u16 = 32;
+
+/// Two sessions, the first logged in, and three roles of it with the second selected
+///
+/// The colors are set through a custom theme so the snapshots don't depend on the
+/// catppuccin version, and no expiry times are set so nothing depends on the clock.
+pub(super) fn app() -> App {
+    let mut config = Config::default();
+    config.ui.theme = Theme::Custom;
+    config.ui.custom_theme = CustomTheme {
+        base: Some("#000001".to_string()),
+        surface0: Some("#000002".to_string()),
+        surface1: Some("#000003".to_string()),
+        text: Some("#000004".to_string()),
+        subtext0: Some("#000005".to_string()),
+        blue: Some("#000006".to_string()),
+        green: Some("#000007".to_string()),
+        mauve: Some("#000008".to_string()),
+        red: Some("#000009".to_string()),
+        yellow: Some("#00000a".to_string()),
+    };
+    let mut app = App::with_config(config).unwrap();
+
+    app.sessions.items = ["corp", "sandbox"]
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let instance = SsoInstance {
+                start_url: format!("https://{}.awsapps.com/start", name),
+                region: "eu-west-1".to_string(),
+                session_name: Some(name.to_string()),
+            };
+            SsoSessionInfo {
+                session_name: name.to_string(),
+                start_url: instance.start_url.clone(),
+                region: instance.region.clone(),
+                is_active: i == 0,
+                token_expiration: None,
+                instance,
+                token: None,
+            }
+        })
+        .collect();
+    app.sessions.state.select(Some(0));
+
+    let role = |account_id: &str, account_name: &str, role_name: &str| AccountRoleWithStatus {
+        account_role: AccountRole {
+            account_id: account_id.to_string(),
+            account_name: account_name.to_string(),
+            role_name: role_name.to_string(),
+        },
+        is_active: false,
+        expiration: None,
+        is_default: false,
+        profile_name: None,
+        other_profiles: Vec::new(),
+        is_favorite: false,
+        session_name: Some("corp".to_string()),
+        last_used: None,
+    };
+    let mut prod = role("111111111111", "prod", "AdministratorAccess");
+    prod.is_active = true;
+    prod.is_default = true;
+    prod.is_favorite = true;
+    prod.profile_name = Some("prod-admin".to_string());
+    prod.other_profiles = vec!["default".to_string()];
+    app.accounts.items = vec![
+        prod,
+        role("111111111111", "prod", "ReadOnly"),
+        role("222222222222", "staging", "Developer"),
+    ];
+    app.accounts.state.select(Some(1));
+    app.active_pane = ActivePane::Accounts;
+    app.status_message = Some("Loaded 3 account/role combinations".to_string());
+    app
+}
+
+/// The screen as `Buffer`'s debug output: one string per row, then the style changes
+fn render(app: &mut App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal.draw(|f| app.ui(f)).unwrap();
+    format!("{:#?}\n", terminal.backend().buffer())
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/ui/snapshots")
+        .join(format!("{}.snap", name));
+    let expected = std::fs::read_to_string(&path).ok();
+    if std::env::var_os("AWSOM_UPDATE_SNAPSHOTS").is_some() || expected.is_none() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        assert!(
+            expected.is_some(),
+            "wrote new snapshot {}; review and commit it",
+            path.display()
+        );
+        return;
+    }
+    assert!(
+        expected.as_deref() == Some(actual),
+        "{} differs from the rendered screen (AWSOM_UPDATE_SNAPSHOTS=1 updates it):\n{}",
+        path.display(),
+        actual
+    );
+}
+
+#[test]
+fn test_main_screen() {
+    let mut app = app();
+    assert_snapshot("main_screen", &render(&mut app));
+
+    // Focus on the Sessions pane moves the highlighted border
+    app.active_pane = ActivePane::Sessions;
+    assert_snapshot("main_screen_sessions_focused", &render(&mut app));
+
+    // The all-sessions view adds a Session column
+    app.active_pane = ActivePane::Accounts;
+    app.config.ui.all_sessions = true;
+    app.accounts.items[2].session_name = Some("sandbox".to_string());
+    assert_snapshot("main_screen_all_sessions", &render(&mut app));
+}
+
+#[test]
+fn test_main_screen_loading_accounts() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _guard = runtime.enter();
+    let mut app = app();
+    app.status_message = Some("Loading roles: 3/8 account(s)".to_string());
+    app.discovery = Some(Discovery {
+        id: 0,
+        task: tokio::spawn(async {}),
+        roles: Vec::new(),
+        live: true,
+        from_cache: false,
+        status: "Loading roles: 3/8 account(s)".to_string(),
+        progress: Some((3, 8)),
+        failed: Vec::new(),
+        offline: false,
+    });
+    assert_snapshot("main_screen_loading_accounts", &render(&mut app));
+}
+
+#[test]
+fn test_main_screen_offline() {
+    let mut app = app();
+    app.offline = true;
+    app.status_message =
+        Some("Offline: showing 3 cached account/role combinations (r to retry)".to_string());
+    assert_snapshot("main_screen_offline", &render(&mut app));
+}
+
+#[test]
+fn test_help_screen() {
+    let mut app = app();
+    app.state = AppState::Help;
+    assert_snapshot("help_screen", &render(&mut app));
+}
+
+#[test]
+fn test_dialogs() {
+    let mut app = app();
+    let account = app.accounts.items[1].account_role.clone();
+    app.pending_confirm_action = Some(ConfirmAction::MakeProfileDefault {
+        from_profile: "prod-readonly".to_string(),
+        account: account.clone(),
+    });
+    app.state = AppState::ConfirmationDialog {
+        title: "Make Default".to_string(),
+        message: vec![
+            "Make 'prod-readonly' the default profile?".to_string(),
+            "".to_string(),
+            "y/Enter: confirm | n/Esc: cancel".to_string(),
+        ],
+    };
+    assert_snapshot("confirmation_dialog", &render(&mut app));
+
+    app.region_input.clear();
+    app.region_list_state.select(Some(0));
+    app.state = AppState::RegionPicker {
+        account: account.clone(),
+    };
+    assert_snapshot("region_picker", &render(&mut app));
+
+    app.state = AppState::RoleDetails {
+        account,
+        details: Some(RoleDetails {
+            session_duration: Some(chrono::Duration::minutes(90)),
+            description: Some("Read-only access to every service".to_string()),
+            permission_set_error: None,
+        }),
+    };
+    assert_snapshot("role_details", &render(&mut app));
+
+    app.state = AppState::Applications {
+        applications: Some(Ok(vec![
+            Application {
+                id: "ins-1111111111111111".to_string(),
+                name: "Salesforce".to_string(),
+                application_type: Some("Salesforce".to_string()),
+                description: None,
+            },
+            Application {
+                id: "ins-2222222222222222".to_string(),
+                name: "Team Wiki".to_string(),
+                application_type: Some("Custom SAML 2.0 application".to_string()),
+                description: None,
+            },
+        ])),
+        selected: 1,
+    };
+    assert_snapshot("applications", &render(&mut app));
+
+    app.accounts.marked = app.accounts.items[..2]
+        .iter()
+        .map(|a| a.account_role.clone())
+        .collect();
+    app.state = AppState::BulkMenu;
+    assert_snapshot("bulk_menu", &render(&mut app));
+    app.accounts.marked.clear();
+
+    app.state = AppState::ProfileConflict {
+        account: app.accounts.items[0].account_role.clone(),
+        profile_name: "prod".to_string(),
+        suffixed: "prod-awsom".to_string(),
+    };
+    assert_snapshot("profile_conflict", &render(&mut app));
+
+    app.state = AppState::Summary {
+        title: "Refresh All Profiles".to_string(),
+        lines: vec![
+            "✓ prod-admin".to_string(),
+            "✗ staging-dev: token expired".to_string(),
+        ],
+    };
+    assert_snapshot("refresh_summary", &render(&mut app));
+
+    app.command_input = "ref".to_string();
+    app.palette_state.select(Some(0));
+    app.state = AppState::CommandLine;
+    assert_snapshot("command_palette", &render(&mut app));
+}
+
+#[test]
+fn test_loading_screen() {
+    let mut app = app();
+    app.state = AppState::Loading;
+    app.status_message = Some("Logging in to sandbox...".to_string());
+    assert_snapshot("loading_screen", &render(&mut app));
+
+    // Once the login task publishes the device code
+    app.device_auth_info = Some(DeviceAuthorizationInfo {
+        device_code: "device-code".to_string(),
+        user_code: "ABCD-EFGH".to_string(),
+        verification_uri: "https://device.sso.eu-west-1.amazonaws.com/".to_string(),
+        verification_uri_complete: Some(
+            "https://device.sso.eu-west-1.amazonaws.com/?user_code=ABCD-EFGH".to_string(),
+        ),
+        expires_in: 600,
+        interval: Some(5),
+        // Half a second over, so the countdown reads 10:00 however long rendering takes
+        expires_at: chrono::Utc::now() + chrono::Duration::milliseconds(600_500),
+    });
+    app.device_url_copy_status = Some("✓ URL copied to clipboard".to_string());
+    assert_snapshot("loading_screen_device_code", &render(&mut app));
+}
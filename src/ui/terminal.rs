@@ -0,0 +1,132 @@
+// Raw mode and the alternate screen for the TUI, undone however the TUI ends
+//
+// A normal exit drops the guard. A panic on the TUI thread, or SIGTERM, restores the
+// terminal before the panic message is printed or the process exits, so the shell isn't
+// left without echo on a blank screen. `awsom --reset-terminal` fixes a terminal that was
+// left that way anyway (e.g. after SIGKILL).
+use crate::error::{Result, SsoError};
+use crate::logging;
+use crossterm::{
+    cursor, execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Once, OnceLock};
+use std::thread::{self, ThreadId};
+
+/// Set while the TUI owns the terminal
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Thread that draws the TUI; panics on other threads (background tasks) don't end it
+static TUI_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Holds the terminal in raw mode on the alternate screen until dropped
+pub struct TerminalGuard {
+    #[cfg(unix)]
+    signals: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl TerminalGuard {
+    /// Switch to raw mode and the alternate screen, restoring both on panic and SIGTERM
+    pub fn enter() -> Result<Self> {
+        let _ = TUI_THREAD.set(thread::current().id());
+        PANIC_HOOK.call_once(install_panic_hook);
+
+        terminal::enable_raw_mode().map_err(SsoError::Io)?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        if let Err(e) = execute!(io::stdout(), EnterAlternateScreen) {
+            restore();
+            return Err(SsoError::Io(e));
+        }
+
+        Ok(TerminalGuard {
+            #[cfg(unix)]
+            signals: watch_sigterm(),
+        })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(signals) = self.signals.take() {
+            signals.abort();
+        }
+        restore();
+    }
+}
+
+/// Leave raw mode and the alternate screen if the TUI still has them
+fn restore() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, cursor::Show);
+    }
+}
+
+/// Restore the terminal before the default hook prints a TUI panic. A panic in a
+/// background task only goes to the log, since printing it would garble the screen.
+fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !ACTIVE.load(Ordering::SeqCst) {
+            previous(info);
+            return;
+        }
+        tracing::error!("{}", info);
+        if TUI_THREAD.get() == Some(&thread::current().id()) {
+            restore();
+            logging::flush();
+            previous(info);
+        }
+    }));
+}
+
+/// Restore the terminal and exit with 128 + SIGTERM, like a shell reports it
+#[cfg(unix)]
+fn watch_sigterm() -> Option<tokio::task::JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(sigterm) => sigterm,
+        Err(e) => {
+            tracing::warn!("Can't watch for SIGTERM: {}", e);
+            return None;
+        }
+    };
+    Some(tokio::spawn(async move {
+        if sigterm.recv().await.is_some() {
+            tracing::info!("Received SIGTERM, exiting");
+            restore();
+            logging::flush();
+            std::process::exit(128 + 15);
+        }
+    }))
+}
+
+/// Put a terminal left in raw mode or on the alternate screen back to normal
+pub fn reset() -> Result<()> {
+    // Raw mode was switched on by another process, so there's no saved mode to go back to
+    #[cfg(unix)]
+    {
+        let status = std::process::Command::new("stty")
+            .arg("sane")
+            .stdin(std::process::Stdio::inherit())
+            .status()
+            .map_err(|e| SsoError::ConfigError(format!("Can't run stty: {}", e)))?;
+        if !status.success() {
+            return Err(SsoError::ConfigError(format!(
+                "stty sane failed ({})",
+                status
+            )));
+        }
+    }
+    #[cfg(not(unix))]
+    terminal::disable_raw_mode().map_err(SsoError::Io)?;
+
+    execute!(io::stdout(), LeaveAlternateScreen, cursor::Show).map_err(SsoError::Io)
+}
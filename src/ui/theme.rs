@@ -0,0 +1,128 @@
+// Colors of the TUI: one of the Catppuccin flavors, or `[ui.custom_theme]` over Mocha
+use crate::config::{CustomTheme, Theme};
+use crate::expiry::ExpiryState;
+use catppuccin::Flavor;
+use ratatui::style::{Color, Modifier, Style};
+
+/// The colors the TUI draws with, named after the Catppuccin colors they default to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub base: Color,
+    pub surface0: Color,
+    pub surface1: Color,
+    pub text: Color,
+    pub subtext0: Color,
+    pub blue: Color,
+    pub green: Color,
+    pub mauve: Color,
+    pub red: Color,
+    pub yellow: Color,
+}
+
+impl Palette {
+    pub fn new(theme: Theme, custom: &CustomTheme) -> Self {
+        match theme {
+            Theme::Latte => Self::from_flavor(&catppuccin::PALETTE.latte),
+            Theme::Frappe => Self::from_flavor(&catppuccin::PALETTE.frappe),
+            Theme::Macchiato => Self::from_flavor(&catppuccin::PALETTE.macchiato),
+            Theme::Mocha => Self::from_flavor(&catppuccin::PALETTE.mocha),
+            Theme::Custom => Self::from_flavor(&catppuccin::PALETTE.mocha).with_custom(custom),
+        }
+    }
+
+    fn from_flavor(flavor: &Flavor) -> Self {
+        let colors = &flavor.colors;
+        Self {
+            base: catppuccin_color(colors.base),
+            surface0: catppuccin_color(colors.surface0),
+            surface1: catppuccin_color(colors.surface1),
+            text: catppuccin_color(colors.text),
+            subtext0: catppuccin_color(colors.subtext0),
+            blue: catppuccin_color(colors.blue),
+            green: catppuccin_color(colors.green),
+            mauve: catppuccin_color(colors.mauve),
+            red: catppuccin_color(colors.red),
+            yellow: catppuccin_color(colors.yellow),
+        }
+    }
+
+    /// Style of a table row by how close its token or credentials are to expiring; None is
+    /// for rows with nothing active
+    pub fn expiry_style(&self, state: Option<ExpiryState>) -> Style {
+        match state {
+            Some(ExpiryState::Valid) => Style::default().fg(self.green),
+            Some(ExpiryState::ExpiringSoon) => Style::default().fg(self.yellow),
+            Some(ExpiryState::Expired) => Style::default().fg(self.red).add_modifier(Modifier::DIM),
+            None => Style::default(),
+        }
+    }
+
+    /// Replace the colors the custom theme sets; invalid ones are logged and skipped
+    fn with_custom(mut self, custom: &CustomTheme) -> Self {
+        let slots = [
+            ("base", &mut self.base, &custom.base),
+            ("surface0", &mut self.surface0, &custom.surface0),
+            ("surface1", &mut self.surface1, &custom.surface1),
+            ("text", &mut self.text, &custom.text),
+            ("subtext0", &mut self.subtext0, &custom.subtext0),
+            ("blue", &mut self.blue, &custom.blue),
+            ("green", &mut self.green, &custom.green),
+            ("mauve", &mut self.mauve, &custom.mauve),
+            ("red", &mut self.red, &custom.red),
+            ("yellow", &mut self.yellow, &custom.yellow),
+        ];
+        for (name, slot, value) in slots {
+            let Some(value) = value else {
+                continue;
+            };
+            match parse_hex(value) {
+                Some(color) => *slot = color,
+                None => tracing::warn!(
+                    "[ui.custom_theme] {} = \"{}\" is not a color like \"#1e1e2e\"",
+                    name,
+                    value
+                ),
+            }
+        }
+        self
+    }
+}
+
+/// Convert Catppuccin color to Ratatui Color
+fn catppuccin_color(color: catppuccin::Color) -> Color {
+    Color::Rgb(color.rgb.r, color.rgb.g, color.rgb.b)
+}
+
+/// Parse "#rrggbb" (the # is optional)
+fn parse_hex(value: &str) -> Option<Color> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_theme_over_mocha() {
+        let custom = CustomTheme {
+            base: Some("#000000".to_string()),
+            red: Some("ff5f5F".to_string()),
+            green: Some("green".to_string()),
+            ..CustomTheme::default()
+        };
+        let mocha = Palette::new(Theme::Mocha, &custom);
+        let palette = Palette::new(Theme::Custom, &custom);
+
+        assert_eq!(palette.base, Color::Rgb(0, 0, 0));
+        assert_eq!(palette.red, Color::Rgb(0xff, 0x5f, 0x5f));
+        // Invalid and unset colors stay Mocha's
+        assert_eq!(palette.green, mocha.green);
+        assert_eq!(palette.text, mocha.text);
+        assert_eq!(parse_hex("#12345"), None);
+    }
+}
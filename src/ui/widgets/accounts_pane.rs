@@ -0,0 +1,570 @@
+// Accounts pane: the roles of the selected session, or of every logged-in one, flat or
+// grouped by OU or tag
+use crate::config::{Config, GroupBy, SortColumn, UiConfig};
+use crate::models::AccountRole;
+use crate::orgs::{tree_rows, OrgTree, TreeRow};
+use crate::ui::action::Action;
+use crate::ui::theme::Palette;
+use crate::ui::widgets::expiry_columns;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Constraint, Rect},
+    style::{Modifier, Style},
+    text::Text,
+    widgets::{
+        Block, Borders, Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table,
+        TableState,
+    },
+    Frame,
+};
+use std::collections::HashSet;
+
+/// Tree view group for accounts the organization doesn't list
+const OUTSIDE_ORG: &str = "(outside organization)";
+
+/// Tag group for roles without tags
+const UNTAGGED: &str = "(untagged)";
+
+/// Wrapper for AccountRole with active status
+#[derive(Debug, Clone)]
+pub struct AccountRoleWithStatus {
+    pub account_role: AccountRole,
+    pub is_active: bool,
+    pub expiration: Option<chrono::DateTime<chrono::Utc>>,
+    pub is_default: bool,
+    pub profile_name: Option<String>,
+    /// Further profiles bound to the same role, e.g. `default` next to `prod-admin`
+    pub other_profiles: Vec<String>,
+    pub is_favorite: bool,
+    /// SSO session the role was listed from, whose token fetches its credentials
+    pub session_name: Option<String>,
+    /// Last activation in the usage stats, for the "recently used" sort
+    pub last_used: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Default)]
+pub struct AccountsPane {
+    /// Accounts and roles with their active status
+    pub items: Vec<AccountRoleWithStatus>,
+    /// Table selection state, an index into `rows()`
+    pub state: TableState,
+    /// OU structure for `group_by = "ou"`, reloaded with the accounts while grouped by OU
+    pub org_tree: Option<OrgTree>,
+    /// OUs (or tag groups) folded in the tree view
+    pub collapsed_units: HashSet<Vec<String>>,
+    /// Only show roles with this tag (`:tag <name>`)
+    pub tag_filter: Option<String>,
+    /// Roles marked with Space for a bulk action
+    pub marked: HashSet<AccountRole>,
+}
+
+impl AccountsPane {
+    /// What a key does while the pane has the focus
+    pub fn action(key: KeyCode) -> Option<Action> {
+        Some(match key {
+            KeyCode::Down | KeyCode::Char('j') => Action::NextRole,
+            KeyCode::Up | KeyCode::Char('k') => Action::PreviousRole,
+            KeyCode::Enter => Action::ToggleRole,
+            KeyCode::Char('e') => Action::EditProfile,
+            KeyCode::Char('d') => Action::MakeDefault,
+            KeyCode::Char('c') => Action::OpenConsole,
+            KeyCode::Char('f') => Action::ToggleFavorite,
+            KeyCode::Char('s') => Action::CycleSort,
+            KeyCode::Char('S') => Action::ReverseSort,
+            KeyCode::Char('g') => Action::CycleGrouping,
+            KeyCode::Char('t') => Action::EditTags,
+            KeyCode::Char('o') => Action::ProfileSettings,
+            KeyCode::Char('K') => Action::UpdateKubeconfig,
+            KeyCode::Char('y') => Action::CopyCredentials,
+            KeyCode::Char('i') => Action::ShowRoleDetails,
+            KeyCode::Char(' ') => Action::ToggleMark,
+            KeyCode::Char('b') => Action::BulkActions,
+            _ => return None,
+        })
+    }
+
+    /// Rows of the accounts pane after the tag filter: the flat list, or grouped by OU or tag
+    pub fn rows(&self, config: &Config) -> Vec<TreeRow> {
+        let visible = (0..self.items.len()).filter(|&index| self.matches_tag_filter(index, config));
+
+        let groups: Vec<(Vec<String>, usize)> = match (config.ui.group_by, &self.org_tree) {
+            (GroupBy::Ou, Some(tree)) => visible
+                .map(|index| {
+                    let path = tree
+                        .ou_path(&self.items[index].account_role.account_id)
+                        .map_or_else(|| vec![OUTSIDE_ORG.to_string()], <[String]>::to_vec);
+                    (path, index)
+                })
+                .collect(),
+            (GroupBy::Tag, _) => visible
+                .flat_map(|index| {
+                    let account = &self.items[index].account_role;
+                    let tags = config.tags_for(&account.account_id, &account.role_name);
+                    if tags.is_empty() {
+                        vec![(vec![UNTAGGED.to_string()], index)]
+                    } else {
+                        tags.into_iter()
+                            .map(|tag| (vec![tag.to_string()], index))
+                            .collect()
+                    }
+                })
+                .collect(),
+            // Without an OU tree (not loaded or failed to load) the list stays flat
+            (GroupBy::None, _) | (GroupBy::Ou, None) => {
+                return visible.map(TreeRow::Item).collect()
+            }
+        };
+
+        let entries: Vec<(&[String], usize)> = groups
+            .iter()
+            .map(|(path, index)| (path.as_slice(), *index))
+            .collect();
+        tree_rows(&entries, &self.collapsed_units)
+    }
+
+    /// Whether `items[index]` passes the `:tag` filter
+    pub fn matches_tag_filter(&self, index: usize, config: &Config) -> bool {
+        let account = &self.items[index].account_role;
+        self.tag_filter.as_deref().map_or(true, |tag| {
+            config.has_tag(&account.account_id, &account.role_name, tag)
+        })
+    }
+
+    /// Index into `items` of the selected row; None if nothing or an OU is selected
+    pub fn selected_index(&self, config: &Config) -> Option<usize> {
+        let row = self.state.selected()?;
+        match self.rows(config).get(row)? {
+            TreeRow::Item(index) => Some(*index),
+            TreeRow::Unit { .. } => None,
+        }
+    }
+
+    /// Select the row showing `items[index]`, or the first row if its OU is folded
+    pub fn select(&mut self, index: usize, config: &Config) {
+        let row = self
+            .rows(config)
+            .iter()
+            .position(|row| *row == TreeRow::Item(index))
+            .unwrap_or(0);
+        self.state.select(Some(row));
+    }
+
+    /// Fold or unfold the selected OU; false if the selection isn't an OU
+    pub fn toggle_selected_unit(&mut self, config: &Config) -> bool {
+        let Some(row) = self.state.selected() else {
+            return false;
+        };
+        let Some(TreeRow::Unit { path, .. }) = self.rows(config).into_iter().nth(row) else {
+            return false;
+        };
+
+        if !self.collapsed_units.remove(&path) {
+            self.collapsed_units.insert(path);
+        }
+        true
+    }
+
+    /// Mark or unmark the selected role and move to the next row; false if the selection
+    /// isn't a role
+    pub fn toggle_mark(&mut self, config: &Config) -> bool {
+        let Some(index) = self.selected_index(config) else {
+            return false;
+        };
+        let account = self.items[index].account_role.clone();
+        if !self.marked.remove(&account) {
+            self.marked.insert(account);
+        }
+        self.select_next(config);
+        true
+    }
+
+    /// Listed roles that are marked, in list order
+    pub fn marked_items(&self) -> Vec<&AccountRoleWithStatus> {
+        self.items
+            .iter()
+            .filter(|item| self.marked.contains(&item.account_role))
+            .collect()
+    }
+
+    /// Select the next row, wrapping around
+    pub fn select_next(&mut self, config: &Config) {
+        let rows = self.rows(config).len();
+        if rows == 0 {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= rows - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    /// Select the previous row, wrapping around
+    pub fn select_previous(&mut self, config: &Config) {
+        let rows = self.rows(config).len();
+        if rows == 0 {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    rows - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    /// Draw the table with the sort, grouping and tag filter in the title
+    pub fn draw(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        theme: &Palette,
+        focused: bool,
+        config: &Config,
+    ) {
+        let rows_shown = self.rows(config);
+        let grouping = match config.ui.group_by {
+            GroupBy::Ou if self.org_tree.is_some() => ", grouped by OU",
+            GroupBy::Tag => ", grouped by tag",
+            _ => "",
+        };
+        let filter = self
+            .tag_filter
+            .as_ref()
+            .map(|tag| format!(", tag: {}", tag))
+            .unwrap_or_default();
+        let all_sessions = config.ui.all_sessions;
+        let scope = if all_sessions { ", all sessions" } else { "" };
+        let marked = match self.marked_items().len() {
+            0 => String::new(),
+            count => format!(", {} marked", count),
+        };
+        let rows: Vec<Row> = rows_shown
+            .iter()
+            .map(|row| {
+                let index = match row {
+                    TreeRow::Item(index) => *index,
+                    TreeRow::Unit {
+                        path,
+                        depth,
+                        items,
+                        collapsed,
+                    } => {
+                        let label = format!(
+                            "{}{} {} ({})",
+                            "  ".repeat(*depth),
+                            if *collapsed { "▸" } else { "▾" },
+                            path.last().map(String::as_str).unwrap_or_default(),
+                            items
+                        );
+                        let mut cells = vec![Cell::new(""); if all_sessions { 4 } else { 3 }];
+                        cells.push(Cell::new(Text::from(label).style(
+                            Style::default().fg(theme.blue).add_modifier(Modifier::BOLD),
+                        )));
+                        return Row::new(cells);
+                    }
+                };
+                let account_with_status = &self.items[index];
+                let account = &account_with_status.account_role;
+
+                // Default marker
+                let default_mark = if account_with_status.is_default {
+                    "✓"
+                } else {
+                    ""
+                };
+
+                // Favorite marker
+                let favorite_mark = if account_with_status.is_favorite {
+                    "★"
+                } else {
+                    ""
+                };
+
+                let (status, expiration_status, style) = expiry_columns(
+                    account_with_status.is_active,
+                    account_with_status.expiration,
+                    config.ui.expiry_warning_minutes,
+                    theme,
+                );
+
+                // Every profile bound to the role, or "N/A"
+                let profile_display = match &account_with_status.profile_name {
+                    Some(name) => std::iter::once(name)
+                        .chain(&account_with_status.other_profiles)
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    None => "N/A".to_string(),
+                };
+
+                // Marked rows get a square before the status dot
+                let status = if self.marked.contains(account) {
+                    format!("■ {}", status)
+                } else {
+                    status.to_string()
+                };
+
+                let mut cells = vec![
+                    Cell::new(Text::from(status).alignment(Alignment::Center)),
+                    Cell::new(
+                        Text::from(favorite_mark)
+                            .alignment(Alignment::Center)
+                            .style(Style::default().fg(theme.yellow)),
+                    ),
+                    Cell::new(Text::from(default_mark).alignment(Alignment::Center)),
+                ];
+                if all_sessions {
+                    let session = account_with_status.session_name.as_deref().unwrap_or("-");
+                    cells.push(Cell::new(Text::from(session).alignment(Alignment::Center)));
+                }
+                cells.extend([
+                    Cell::new(
+                        Text::from(
+                            config
+                                .account_alias(&account.account_id)
+                                .unwrap_or(&account.account_name)
+                                .to_string(),
+                        )
+                        .alignment(Alignment::Center),
+                    ),
+                    Cell::new(Text::from(account.account_id.clone()).alignment(Alignment::Center)),
+                    Cell::new(Text::from(account.role_name.clone()).alignment(Alignment::Center)),
+                    Cell::new(Text::from(profile_display).alignment(Alignment::Center)),
+                    Cell::new(Text::from(expiration_status).alignment(Alignment::Center)),
+                ]);
+                Row::new(cells).style(style)
+            })
+            .collect();
+
+        let mut titles = vec!["Status", "★", "Default"];
+        if all_sessions {
+            titles.push("Session");
+        }
+        titles.extend(["Account", "Account ID", "Role", "Profile", "Expires"]);
+        let header = Row::new(
+            titles
+                .into_iter()
+                .map(|title| Cell::new(Text::from(title).alignment(Alignment::Center))),
+        )
+        .style(Style::default().fg(theme.blue).add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+        // Highlight accounts pane if it's active
+        let accounts_block_style = if focused {
+            Style::default().fg(theme.mauve)
+        } else {
+            Style::default().fg(theme.surface0)
+        };
+
+        let mut widths = vec![
+            Constraint::Length(6), // Status
+            Constraint::Length(3), // Favorite
+            Constraint::Length(7), // Default (was 3, now wider for "Default")
+        ];
+        if all_sessions {
+            widths.push(Constraint::Min(10)); // Session
+        }
+        widths.extend([
+            Constraint::Min(15),    // Account Name
+            Constraint::Length(12), // Account ID
+            Constraint::Min(15),    // Role Name
+            Constraint::Min(15),    // Profile Name
+            Constraint::Length(10), // Expiration
+        ]);
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Accounts & Roles (sort: {}{}{}{}{})",
+                        sort_description(&config.ui),
+                        grouping,
+                        filter,
+                        scope,
+                        marked
+                    ))
+                    .border_style(accounts_block_style),
+            )
+            .row_highlight_style(
+                Style::default()
+                    .bg(theme.surface1)
+                    .add_modifier(Modifier::BOLD),
+            );
+
+        f.render_stateful_widget(table, area, &mut self.state);
+
+        // Render scrollbar for accounts pane
+        if !rows_shown.is_empty() {
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(Some("↑"))
+                .end_symbol(Some("↓"));
+
+            let mut scrollbar_state =
+                ScrollbarState::new(rows_shown.len()).position(self.state.selected().unwrap_or(0));
+
+            f.render_stateful_widget(
+                scrollbar,
+                area.inner(ratatui::layout::Margin {
+                    vertical: 1,
+                    horizontal: 0,
+                }),
+                &mut scrollbar_state,
+            );
+        }
+    }
+}
+
+/// Current sort column and direction, e.g. "expiry ↓"
+pub fn sort_description(ui: &UiConfig) -> String {
+    format!(
+        "{} {}",
+        ui.sort_by.label(),
+        if ui.sort_descending { "↓" } else { "↑" }
+    )
+}
+
+/// Sort favorites first, then by the configured column, falling back to
+/// account name and role name so the order is stable
+pub fn sort_accounts(accounts: &mut [AccountRoleWithStatus], ui: &UiConfig) {
+    let is_live = |a: &AccountRoleWithStatus| {
+        a.is_active
+            && a.expiration
+                .map_or(true, |exp| !crate::expiry::is_expired(&exp))
+    };
+
+    accounts.sort_by(|a, b| {
+        let by_column = match ui.sort_by {
+            SortColumn::Account => a
+                .account_role
+                .account_name
+                .cmp(&b.account_role.account_name),
+            SortColumn::AccountId => a.account_role.account_id.cmp(&b.account_role.account_id),
+            SortColumn::Role => a.account_role.role_name.cmp(&b.account_role.role_name),
+            // Soonest expiry first, roles without credentials last
+            SortColumn::Expiry => match (a.expiration, b.expiration) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            },
+            // Active roles first
+            SortColumn::Status => is_live(b).cmp(&is_live(a)),
+            // Latest activation first, roles never activated last
+            SortColumn::Recent => match (a.last_used, b.last_used) {
+                (Some(x), Some(y)) => y.cmp(&x),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            },
+        };
+        let by_column = if ui.sort_descending {
+            by_column.reverse()
+        } else {
+            by_column
+        };
+
+        b.is_favorite
+            .cmp(&a.is_favorite)
+            .then(by_column)
+            .then_with(|| {
+                a.account_role
+                    .account_name
+                    .cmp(&b.account_role.account_name)
+            })
+            .then_with(|| a.account_role.role_name.cmp(&b.account_role.role_name))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn role(account_id: &str, role_name: &str) -> AccountRoleWithStatus {
+        AccountRoleWithStatus {
+            account_role: AccountRole {
+                account_id: account_id.to_string(),
+                account_name: format!("account-{}", account_id),
+                role_name: role_name.to_string(),
+            },
+            is_active: false,
+            expiration: None,
+            is_default: false,
+            profile_name: None,
+            other_profiles: Vec::new(),
+            is_favorite: false,
+            session_name: None,
+            last_used: None,
+        }
+    }
+
+    #[test]
+    fn test_tag_groups_fold_and_navigate() {
+        let mut config = Config::default();
+        config.set_role_tags("111", "Admin", vec!["prod".to_string()]);
+        config.ui.group_by = GroupBy::Tag;
+        let mut pane = AccountsPane {
+            items: vec![role("111", "Admin"), role("222", "ReadOnly")],
+            ..AccountsPane::default()
+        };
+
+        // "(untagged)" and "prod" headers, each with one role
+        assert_eq!(pane.rows(&config).len(), 4);
+        pane.select(1, &config);
+        assert_eq!(pane.selected_index(&config), Some(1));
+
+        // Folding the group hides its role; navigation wraps around the shorter list
+        pane.state.select(Some(0));
+        assert!(pane.toggle_selected_unit(&config));
+        assert_eq!(pane.rows(&config).len(), 3);
+        pane.select_previous(&config);
+        assert_eq!(pane.selected_index(&config), Some(0));
+        pane.select_next(&config);
+        assert_eq!(pane.selected_index(&config), None);
+
+        pane.tag_filter = Some("prod".to_string());
+        assert!(!pane.matches_tag_filter(1, &config));
+        assert_eq!(pane.rows(&config).len(), 2);
+    }
+
+    #[test]
+    fn test_marks_follow_the_role() {
+        let config = Config::default();
+        let mut pane = AccountsPane {
+            items: vec![role("111", "Admin"), role("222", "ReadOnly")],
+            ..AccountsPane::default()
+        };
+
+        // Marking moves down, so Space Space marks consecutive rows
+        pane.select(0, &config);
+        assert!(pane.toggle_mark(&config));
+        assert_eq!(pane.selected_index(&config), Some(1));
+        assert_eq!(pane.marked_items().len(), 1);
+
+        // A mark survives the list being re-sorted or reloaded
+        pane.items.reverse();
+        let marked: Vec<&str> = pane
+            .marked_items()
+            .iter()
+            .map(|row| row.account_role.account_id.as_str())
+            .collect();
+        assert_eq!(marked, vec!["111"]);
+
+        pane.select(1, &config);
+        pane.toggle_mark(&config);
+        assert!(pane.marked_items().is_empty());
+    }
+}
@@ -1 +1,41 @@
 // TUI widgets
+pub mod accounts_pane;
+pub mod sessions_pane;
+
+use crate::expiry::ExpiryState;
+use crate::ui::theme::Palette;
+use chrono::{DateTime, Utc};
+use ratatui::style::Style;
+
+/// Status symbol, Expires column text and row style of a session or role; rows are green,
+/// then yellow under `warning_minutes` left, then dimmed red once expired
+pub(crate) fn expiry_columns(
+    active: bool,
+    expiration: Option<DateTime<Utc>>,
+    warning_minutes: u32,
+    theme: &Palette,
+) -> (&'static str, String, Style) {
+    let state = match (active, expiration) {
+        (false, _) => None,
+        (true, None) => Some(ExpiryState::Valid),
+        (true, Some(at)) => Some(ExpiryState::of(&at, i64::from(warning_minutes))),
+    };
+    let remaining = match (state, expiration) {
+        (Some(ExpiryState::Expired), _) => "EXPIRED".to_string(),
+        (Some(_), Some(at)) => {
+            let remaining_secs = (at - Utc::now()).num_seconds();
+            let (hours, mins) = (remaining_secs / 3600, (remaining_secs % 3600) / 60);
+            if hours > 0 {
+                format!("{}h {}m", hours, mins)
+            } else {
+                format!("{}m", mins)
+            }
+        }
+        _ => String::new(),
+    };
+    let symbol = match state {
+        Some(ExpiryState::Valid | ExpiryState::ExpiringSoon) => "●",
+        _ => "○",
+    };
+    (symbol, remaining, theme.expiry_style(state))
+}
@@ -0,0 +1,199 @@
+// SSO sessions pane: the sessions in ~/.aws/config and whether each is logged in
+use crate::models::{SsoInstance, SsoToken};
+use crate::ui::action::Action;
+use crate::ui::theme::Palette;
+use crate::ui::widgets::expiry_columns;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Constraint, Rect},
+    style::{Modifier, Style},
+    text::Text,
+    widgets::{
+        Block, Borders, Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table,
+        TableState,
+    },
+    Frame,
+};
+
+/// SSO Session with its status
+#[derive(Debug, Clone)]
+pub struct SsoSessionInfo {
+    pub session_name: String,
+    pub start_url: String,
+    pub region: String,
+    pub is_active: bool,
+    pub token_expiration: Option<chrono::DateTime<chrono::Utc>>,
+    pub instance: SsoInstance,
+    pub token: Option<SsoToken>,
+}
+
+#[derive(Default)]
+pub struct SessionsPane {
+    /// SSO sessions with their status
+    pub items: Vec<SsoSessionInfo>,
+    /// Table selection state
+    pub state: TableState,
+}
+
+impl SessionsPane {
+    /// What a key does while the pane has the focus
+    pub fn action(key: KeyCode) -> Option<Action> {
+        Some(match key {
+            KeyCode::Down | KeyCode::Char('j') => Action::NextSession,
+            KeyCode::Up | KeyCode::Char('k') => Action::PreviousSession,
+            KeyCode::Enter => Action::ToggleSession,
+            KeyCode::Char('a') => Action::AddSession,
+            KeyCode::Char('e') => Action::EditSession,
+            KeyCode::Char('d') => Action::DeleteSession,
+            _ => return None,
+        })
+    }
+
+    pub fn selected(&self) -> Option<&SsoSessionInfo> {
+        self.state.selected().and_then(|idx| self.items.get(idx))
+    }
+
+    /// Select the next session, wrapping around
+    pub fn select_next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= self.items.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    /// Select the previous session, wrapping around
+    pub fn select_previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.items.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    /// Rows the pane takes on screen
+    pub fn height(&self) -> u16 {
+        // Min 5 lines (1 border top + 1 header + 1 header margin + 1 content + 1 border bottom)
+        // Max 12 lines to avoid taking too much space
+        if self.items.is_empty() {
+            5 // Minimum height for empty pane
+        } else {
+            // 4 for borders + header + header margin, plus 1 line per session, max 12 total
+            std::cmp::min(self.items.len() + 4, 12) as u16
+        }
+    }
+
+    /// Draw the table, naming the active workspace (if any) in the title
+    pub fn draw(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        theme: &Palette,
+        focused: bool,
+        workspace: Option<&str>,
+        warning_minutes: u32,
+    ) {
+        let rows: Vec<Row> = self
+            .items
+            .iter()
+            .map(|session| {
+                let (status, expiration_status, style) = expiry_columns(
+                    session.is_active,
+                    session.token_expiration,
+                    warning_minutes,
+                    theme,
+                );
+
+                Row::new(vec![
+                    Cell::new(Text::from(status).alignment(Alignment::Center)),
+                    Cell::new(
+                        Text::from(session.session_name.clone()).alignment(Alignment::Center),
+                    ),
+                    Cell::new(Text::from(session.start_url.clone()).alignment(Alignment::Center)),
+                    Cell::new(Text::from(expiration_status).alignment(Alignment::Center)),
+                ])
+                .style(style)
+            })
+            .collect();
+
+        let header = Row::new(vec![
+            Cell::new(Text::from("Status").alignment(Alignment::Center)),
+            Cell::new(Text::from("Session Name").alignment(Alignment::Center)),
+            Cell::new(Text::from("Start URL").alignment(Alignment::Center)),
+            Cell::new(Text::from("Expires").alignment(Alignment::Center)),
+        ])
+        .style(Style::default().fg(theme.blue).add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+        // Highlight sessions pane if it's active
+        let sessions_block_style = if focused {
+            Style::default().fg(theme.mauve)
+        } else {
+            Style::default().fg(theme.surface0)
+        };
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(6),  // Status
+                Constraint::Min(20),    // Session Name
+                Constraint::Min(30),    // Start URL
+                Constraint::Length(10), // Expiration
+            ],
+        )
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(match workspace {
+                    Some(name) => format!("SSO Sessions - workspace {}", name),
+                    None => "SSO Sessions".to_string(),
+                })
+                .border_style(sessions_block_style),
+        )
+        .row_highlight_style(
+            Style::default()
+                .bg(theme.surface1)
+                .add_modifier(Modifier::BOLD),
+        );
+
+        f.render_stateful_widget(table, area, &mut self.state);
+
+        // Render scrollbar for sessions pane
+        if !self.items.is_empty() {
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(Some("↑"))
+                .end_symbol(Some("↓"));
+
+            let mut scrollbar_state =
+                ScrollbarState::new(self.items.len()).position(self.state.selected().unwrap_or(0));
+
+            f.render_stateful_widget(
+                scrollbar,
+                area.inner(ratatui::layout::Margin {
+                    vertical: 1,
+                    horizontal: 0,
+                }),
+                &mut scrollbar_state,
+            );
+        }
+    }
+}
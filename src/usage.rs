@@ -0,0 +1,151 @@
+// Local usage stats: how often and when each role was activated
+//
+// Kept in <cache dir>/awsom/usage.json and never sent anywhere. An activation is something
+// the user asked for - starting a profile, exec/export, a console sign-in - not background
+// refreshes. `[stats] enabled = false` in config.toml stops recording.
+use crate::aws_config;
+use crate::config::{self, StatsConfig};
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleUsage {
+    pub account_id: String,
+    pub role_name: String,
+    pub count: u64,
+    pub first_used: DateTime<Utc>,
+    pub last_used: DateTime<Utc>,
+    /// Profile of the latest activation, if it went through one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+/// Path to usage.json, next to the audit log
+pub fn path() -> PathBuf {
+    config::cache_dir().join("usage.json")
+}
+
+fn key(account_id: &str, role_name: &str) -> String {
+    format!("{}/{}", account_id, role_name)
+}
+
+/// Count an activation of the role if `stats` enables it, and not in read-only mode
+///
+/// Failures are logged rather than returned, like the audit log's.
+pub fn record(stats: &StatsConfig, account_id: &str, role_name: &str, profile: Option<&str>) {
+    if !stats.enabled || config::is_read_only() {
+        return;
+    }
+    if let Err(e) = add(&path(), account_id, role_name, profile, Utc::now()) {
+        tracing::warn!("Failed to update usage stats: {}", e);
+    }
+}
+
+fn add(
+    path: &Path,
+    account_id: &str,
+    role_name: &str,
+    profile: Option<&str>,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Another awsom process may be counting an activation too
+    let _lock = aws_config::lock_file(path)?;
+    let mut usage = read(path)?;
+    let entry = usage
+        .entry(key(account_id, role_name))
+        .or_insert_with(|| RoleUsage {
+            account_id: account_id.to_string(),
+            role_name: role_name.to_string(),
+            count: 0,
+            first_used: at,
+            last_used: at,
+            profile: None,
+        });
+    entry.count += 1;
+    entry.last_used = at;
+    if profile.is_some() {
+        entry.profile = profile.map(str::to_string);
+    }
+
+    aws_config::write_atomic(path, serde_json::to_string_pretty(&usage)?)?;
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<BTreeMap<String, RoleUsage>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!(
+            "Ignoring unreadable usage stats in {}: {}",
+            path.display(),
+            e
+        );
+        BTreeMap::new()
+    }))
+}
+
+/// Every role used so far, most recently used first
+pub fn load() -> Result<Vec<RoleUsage>> {
+    Ok(sorted(read(&path())?))
+}
+
+fn sorted(usage: BTreeMap<String, RoleUsage>) -> Vec<RoleUsage> {
+    let mut usage: Vec<RoleUsage> = usage.into_values().collect();
+    usage.sort_by_key(|u| std::cmp::Reverse(u.last_used));
+    usage
+}
+
+/// Last activation of each role, keyed by (account ID, role name)
+pub fn last_used() -> BTreeMap<(String, String), DateTime<Utc>> {
+    read(&path())
+        .unwrap_or_default()
+        .into_values()
+        .map(|usage| ((usage.account_id, usage.role_name), usage.last_used))
+        .collect()
+}
+
+/// Forget all usage; returns whether there was any
+pub fn clear() -> Result<bool> {
+    let path = path();
+    if !path.exists() {
+        return Ok(false);
+    }
+    config::ensure_writable(&path)?;
+    fs::remove_file(path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_counts_activations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("awsom").join("usage.json");
+
+        let first = Utc::now() - chrono::Duration::days(2);
+        add(&path, "111111111111", "Admin", Some("prod-admin"), first).unwrap();
+        add(&path, "222222222222", "ReadOnly", None, first).unwrap();
+        add(&path, "111111111111", "Admin", None, Utc::now()).unwrap();
+
+        let usage = sorted(read(&path).unwrap());
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].role_name, "Admin");
+        assert_eq!(usage[0].count, 2);
+        assert_eq!(usage[0].first_used, first);
+        // An activation without a profile keeps the last one seen
+        assert_eq!(usage[0].profile.as_deref(), Some("prod-admin"));
+        assert_eq!(usage[1].count, 1);
+    }
+}
@@ -0,0 +1,110 @@
+// Change detection for ~/.aws/config and ~/.aws/credentials while the TUI is open
+//
+// Polls a hash of each file's content from the event loop, which wakes every 250ms anyway;
+// the files are small, and unlike modification times a hash can't miss two writes within
+// the filesystem's timestamp granularity. Polling also keeps working when an editor saves
+// by renaming a new file over the old one.
+use sha1::{Digest, Sha1};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long the files must stay unchanged before a change is reported, so a save that
+/// writes several times reloads once
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// SHA-1 of each watched file's content; None if it can't be read
+pub type Stamps = Vec<Option<[u8; 20]>>;
+
+pub struct FileWatcher {
+    paths: Vec<PathBuf>,
+    seen: Stamps,
+    /// When the last change not yet reported was seen
+    changed_at: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let seen = stamps(&paths);
+        Self {
+            paths,
+            seen,
+            changed_at: None,
+        }
+    }
+
+    /// The files' current state, to hand to `absorb` once awsom is done writing them
+    pub fn stamps(&self) -> Stamps {
+        stamps(&self.paths)
+    }
+
+    /// Take changes made since `before` as awsom's own, so they aren't reported
+    pub fn absorb(&mut self, before: Stamps) {
+        let current = self.stamps();
+        if current != before {
+            self.seen = current;
+        }
+    }
+
+    /// Whether the files changed and have since settled; true once per change
+    pub fn poll(&mut self) -> bool {
+        self.poll_at(Instant::now())
+    }
+
+    fn poll_at(&mut self, now: Instant) -> bool {
+        let current = self.stamps();
+        if current != self.seen {
+            self.seen = current;
+            self.changed_at = Some(now);
+        }
+
+        match self.changed_at {
+            Some(changed_at) if now.duration_since(changed_at) >= DEBOUNCE => {
+                self.changed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn stamps(paths: &[PathBuf]) -> Stamps {
+    paths
+        .iter()
+        .map(|path| {
+            let content = std::fs::read(path).ok()?;
+            Some(Sha1::digest(content).into())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_watcher_debounces_and_absorbs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config");
+        std::fs::write(&path, "[default]\n").unwrap();
+        let mut watcher = FileWatcher::new(vec![path.clone(), dir.path().join("credentials")]);
+        let start = Instant::now();
+        assert!(!watcher.poll_at(start));
+
+        std::fs::write(&path, "[default]\nregion = eu-west-1\n").unwrap();
+        assert!(!watcher.poll_at(start));
+        assert!(watcher.poll_at(start + DEBOUNCE));
+        assert!(!watcher.poll_at(start + DEBOUNCE * 2));
+
+        // Writes of awsom's own are not reported
+        let before = watcher.stamps();
+        std::fs::write(&path, "[default]\n").unwrap();
+        watcher.absorb(before);
+        assert!(!watcher.poll_at(start + DEBOUNCE * 3));
+        assert!(!watcher.poll_at(start + DEBOUNCE * 4));
+
+        // A same-size rewrite is seen whatever the timestamps say
+        std::fs::write(&path, "[dflt00]\n").unwrap();
+        assert!(!watcher.poll_at(start + DEBOUNCE * 5));
+        assert!(watcher.poll_at(start + DEBOUNCE * 6));
+    }
+}
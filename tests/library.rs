@@ -0,0 +1,81 @@
+// The library API used in-process against the mock SSO server, the way another tool would
+// embed awsom
+mod support;
+
+use awsom::{AccountRole, AuthManager, AwsConfigStore, CredentialManager, LoginOptions, SsoError};
+use support::{Home, MockSso};
+
+// One test per binary: the library reads HOME and AWS_ENDPOINT_URL from this process's
+// environment
+#[tokio::test]
+async fn test_library_login_credentials_and_profiles() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    std::env::set_var("HOME", home.path());
+    std::env::set_var("AWS_ENDPOINT_URL", sso.url());
+    std::env::set_var("AWS_EC2_METADATA_DISABLED", "true");
+    for var in [
+        "XDG_CONFIG_HOME",
+        "XDG_CACHE_HOME",
+        "AWS_CONFIG_FILE",
+        "AWS_SHARED_CREDENTIALS_FILE",
+        "AWS_PROFILE",
+    ] {
+        std::env::remove_var(var);
+    }
+
+    let store = AwsConfigStore::new();
+    let instance = store.sso_instance(Some("mock")).unwrap();
+    assert_eq!(instance.region, "us-east-1");
+    assert_eq!(instance.session_name.as_deref(), Some("mock"));
+    assert!(matches!(
+        store.sso_instance(Some("missing")),
+        Err(SsoError::ConfigError(_))
+    ));
+
+    let auth = AuthManager::new().unwrap();
+    let options = LoginOptions::new().headless(true);
+    let token = auth.login(&instance, options).await.unwrap();
+    assert_eq!(sso.state().tokens_issued, 1);
+    // The login is cached where the CLI looks for it
+    assert!(auth.get_cached_token(&instance).unwrap().is_some());
+    auth.login(&instance, options).await.unwrap();
+    assert_eq!(sso.state().tokens_issued, 1);
+
+    let role = AccountRole {
+        account_id: "111111111111".to_string(),
+        account_name: "prod".to_string(),
+        role_name: "ReadOnly".to_string(),
+    };
+    let creds = CredentialManager::new()
+        .unwrap()
+        .get_credentials(&instance, &token, &role)
+        .await
+        .unwrap();
+    store
+        .write_credentials("prod-readonly", &creds, "eu-west-1", Some("json"), &role)
+        .unwrap();
+
+    assert_eq!(
+        store.profile_for_role(&role).unwrap().as_deref(),
+        Some("prod-readonly")
+    );
+    let details = store.profile("prod-readonly").unwrap().unwrap();
+    assert_eq!(details.region.as_deref(), Some("eu-west-1"));
+    let profiles = store.profiles().unwrap();
+    let profile = profiles
+        .iter()
+        .find(|p| p.profile_name == "prod-readonly")
+        .unwrap();
+    assert!(profile.has_credentials);
+    assert_eq!(profile.role_name.as_deref(), Some("ReadOnly"));
+
+    // The CLI picks up the profile and the token the library wrote
+    let output = home.awsom(&["profile", "start", "prod-readonly"]);
+    support::assert_success(&output);
+    assert_eq!(sso.state().tokens_issued, 1);
+
+    store.delete_profile("prod-readonly").unwrap();
+    assert!(store.profile("prod-readonly").unwrap().is_none());
+    assert!(store.profile_for_role(&role).unwrap().is_none());
+}
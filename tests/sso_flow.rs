@@ -0,0 +1,784 @@
+// End-to-end runs of the awsom binary against the mock SSO/OIDC server in tests/support
+mod support;
+
+use support::{assert_success, stderr, stdout, Home, MockSso, USER_CODE};
+
+#[test]
+fn test_login_polls_until_authorized() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    sso.set_pending_polls(2);
+
+    let output = home.login();
+    assert!(stderr(&output).contains(USER_CODE));
+    assert_eq!(sso.count("POST /client/register"), 1);
+    assert_eq!(sso.count("POST /device_authorization"), 1);
+    assert_eq!(sso.count("POST /token"), 3);
+
+    // The cached token is used instead of logging in again
+    let output = home.awsom(&["session", "login"]);
+    assert_success(&output);
+    assert_eq!(sso.count("POST /token"), 3);
+    let output = home.awsom(&["session", "status", "--format", "json"]);
+    assert_success(&output);
+    assert!(stdout(&output).contains("mock"), "{}", stdout(&output));
+}
+
+#[test]
+fn test_profile_list_and_exec() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let output = home.awsom(&["profile", "list", "--format", "json"]);
+    assert_success(&output);
+    let list = stdout(&output);
+    for expected in ["111111111111", "222222222222", "Admin", "ReadOnly"] {
+        assert!(
+            list.contains(expected),
+            "{} missing from {}",
+            expected,
+            list
+        );
+    }
+
+    let output = home.awsom(&[
+        "profile",
+        "exec",
+        "--account-name",
+        "prod",
+        "--role-name",
+        "Admin",
+        "--",
+        "sh",
+        "-c",
+        "echo $AWS_ACCESS_KEY_ID $AWS_SESSION_TOKEN",
+    ]);
+    assert_success(&output);
+    assert_eq!(
+        stdout(&output).trim(),
+        "ASIA111111111111 session-111111111111-Admin-1"
+    );
+}
+
+#[test]
+fn test_exec_redact_masks_secrets() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let output = home.awsom(&[
+        "profile",
+        "exec",
+        "--account-name",
+        "prod",
+        "--role-name",
+        "Admin",
+        "--redact",
+        "--",
+        "sh",
+        "-c",
+        "echo $AWS_ACCESS_KEY_ID $AWS_SECRET_ACCESS_KEY; echo token=$AWS_SESSION_TOKEN >&2; exit 3",
+    ]);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout(&output).trim(), "ASIA111111111111 ****");
+    assert!(
+        stderr(&output).contains("token=****"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!stderr(&output).contains("session-111111111111"));
+}
+
+#[test]
+fn test_serve_refuses_remote_bind() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+
+    let output = home.awsom(&[
+        "serve",
+        "--account-name",
+        "prod",
+        "--role-name",
+        "Admin",
+        "--imds",
+        "--bind",
+        "0.0.0.0:0",
+    ]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("--allow-remote"),
+        "{}",
+        stderr(&output)
+    );
+    // Refused before logging in
+    assert_eq!(sso.state().tokens_issued, 0);
+}
+
+#[test]
+fn test_revoked_token_logs_in_again() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+    sso.revoke_tokens();
+
+    let output = home.awsom(&[
+        "profile",
+        "exec",
+        "--account-id",
+        "222222222222",
+        "--role-name",
+        "ReadOnly",
+        "--",
+        "sh",
+        "-c",
+        "echo $AWS_ACCESS_KEY_ID",
+    ]);
+    assert_success(&output);
+    assert_eq!(stdout(&output).trim(), "ASIA222222222222");
+    assert_eq!(sso.state().tokens_issued, 2);
+}
+
+#[test]
+fn test_account_alias_skips_the_account_list() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.write(
+        ".config/awsom/config.toml",
+        "[account_aliases]\n\"222222222222\" = \"Data Lake\"\n",
+    );
+    home.login();
+
+    let output = home.awsom(&[
+        "profile",
+        "exec",
+        "--account-name",
+        "data lake",
+        "--role-name",
+        "ReadOnly",
+        "--",
+        "sh",
+        "-c",
+        "echo $AWS_ACCESS_KEY_ID",
+    ]);
+    assert_success(&output);
+    assert_eq!(stdout(&output).trim(), "ASIA222222222222");
+    assert_eq!(sso.count("GET /assignment/accounts"), 0);
+}
+
+#[test]
+fn test_unassigned_role_fails() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let output = home.awsom(&[
+        "profile",
+        "exec",
+        "--account-id",
+        "222222222222",
+        "--role-name",
+        "Admin",
+        "--",
+        "true",
+    ]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Failed to get role credentials"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_profiles_bound_to_one_role_refresh_together() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    for name in ["prod-admin", "default"] {
+        let output = home.awsom(&[
+            "profile",
+            "create",
+            "--account-name",
+            "prod",
+            "--role-name",
+            "Admin",
+            "--name",
+            name,
+        ]);
+        assert_success(&output);
+    }
+    let output = home.awsom(&["profile", "start", "prod-admin"]);
+    assert_success(&output);
+
+    let credentials = home.read(".aws/credentials");
+    let latest = format!(
+        "session-111111111111-Admin-{}",
+        sso.state().credentials_issued
+    );
+    assert_eq!(credentials.matches(&latest).count(), 2, "{}", credentials);
+}
+
+#[test]
+fn test_session_list_shows_login_state() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+
+    let output = home.awsom(&["session", "list", "--format", "json"]);
+    assert_success(&output);
+    let list: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(list[0]["name"], "mock");
+    assert_eq!(list[0]["active"], false);
+    assert_eq!(list[0]["reason"], "no_session");
+
+    home.login();
+    let output = home.awsom(&[
+        "profile",
+        "create",
+        "--account-name",
+        "prod",
+        "--role-name",
+        "Admin",
+    ]);
+    assert_success(&output);
+
+    let output = home.awsom(&["session", "list", "--format", "json"]);
+    assert_success(&output);
+    let list: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(list[0]["active"], true);
+    assert!(list[0]["expires_in_minutes"].as_i64().unwrap() > 0);
+    assert_eq!(list[0]["profiles"], 1);
+}
+
+#[test]
+fn test_generate_role_arn_profiles() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let output = home.awsom(&[
+        "profile",
+        "create",
+        "--account-name",
+        "prod",
+        "--role-name",
+        "Admin",
+        "--name",
+        "hub",
+    ]);
+    assert_success(&output);
+    let output = home.awsom(&["profile", "start", "hub"]);
+    assert_success(&output);
+
+    let output = home.awsom(&[
+        "profile",
+        "generate",
+        "--all",
+        "--mode",
+        "role-arn",
+        "--source-profile",
+        "hub",
+    ]);
+    assert_success(&output);
+    assert!(
+        stderr(&output).contains("source profile 'hub' already holds"),
+        "{}",
+        stderr(&output)
+    );
+    let config = home.read(".aws/config");
+    assert!(
+        config.contains("role_arn = arn:aws:iam::222222222222:role/ReadOnly"),
+        "{}",
+        config
+    );
+    assert_eq!(
+        config.matches("source_profile = hub").count(),
+        2,
+        "{}",
+        config
+    );
+    assert!(config.contains("sso_role_name = Admin"), "{}", config);
+
+    // Generating SSO profiles again turns the same sections back
+    let output = home.awsom(&["profile", "generate", "--all"]);
+    assert_success(&output);
+    let config = home.read(".aws/config");
+    assert!(!config.contains("role_arn"), "{}", config);
+
+    let output = home.awsom(&[
+        "profile",
+        "generate",
+        "--all",
+        "--mode",
+        "role-arn",
+        "--source-profile",
+        "nope",
+    ]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_logout_forgets_the_token() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let output = home.awsom(&["session", "logout"]);
+    assert_success(&output);
+    let output = home.awsom(&["session", "login"]);
+    assert_success(&output);
+    assert_eq!(sso.count("POST /device_authorization"), 2);
+}
+
+#[test]
+fn test_exec_via_file() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let output = home.awsom(&[
+        "profile",
+        "exec",
+        "--account-name",
+        "dev",
+        "--role-name",
+        "ReadOnly",
+        "--via-file",
+        "--",
+        "sh",
+        "-c",
+        "echo $AWS_SHARED_CREDENTIALS_FILE; cat $AWS_SHARED_CREDENTIALS_FILE",
+    ]);
+    assert_success(&output);
+    let stdout = stdout(&output);
+    let (path, credentials) = stdout.split_once('\n').unwrap();
+    assert!(
+        credentials.contains("[default]\naws_access_key_id = ASIA222222222222"),
+        "{}",
+        credentials
+    );
+    assert!(
+        !std::path::Path::new(path).exists(),
+        "{} was left behind",
+        path
+    );
+    assert_eq!(home.read(".aws/credentials"), "");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_all_passes_on_every_line() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let output = home
+        .command()
+        .env("AWS_PROFILE", "stale")
+        .args(["exec-all", "--role-name", "Admin", "--", "sh", "-c"])
+        .arg("printf 'bad \\377 byte\\nprofile=%s\\n' \"$AWS_PROFILE\"; exit 3")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = stdout(&output);
+    assert!(stdout.contains("prod | bad \u{fffd} byte\n"), "{}", stdout);
+    assert!(stdout.contains("prod | profile=\n"), "{}", stdout);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_via_file_removes_credentials_on_sigterm() {
+    use std::os::unix::process::ExitStatusExt;
+    use std::time::{Duration, Instant};
+
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+    let tmp = home.path().join("tmp");
+    std::fs::create_dir(&tmp).unwrap();
+    let exec_dirs = || std::fs::read_dir(&tmp).unwrap().count();
+
+    let mut awsom = home
+        .command()
+        .env("TMPDIR", &tmp)
+        .args([
+            "profile",
+            "exec",
+            "--account-name",
+            "dev",
+            "--role-name",
+            "ReadOnly",
+        ])
+        .args(["--via-file", "--", "sleep", "30"])
+        .spawn()
+        .unwrap();
+    let started = Instant::now();
+    while exec_dirs() == 0 {
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "no credentials file"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let kill = std::process::Command::new("kill")
+        .args(["-TERM", &awsom.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(kill.success());
+    let status = awsom.wait().unwrap();
+    // The command got the signal and awsom passed on its exit code
+    assert_eq!(status.code(), Some(128 + 15), "{:?}", status.signal());
+    assert_eq!(exec_dirs(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_redact_passes_on_sighup() {
+    use std::time::{Duration, Instant};
+
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+    let started = home.path().join("started");
+
+    let mut awsom = home
+        .command()
+        .args([
+            "profile",
+            "exec",
+            "--account-name",
+            "dev",
+            "--role-name",
+            "ReadOnly",
+        ])
+        .args([
+            "--redact",
+            "--",
+            "sh",
+            "-c",
+            "touch \"$HOME/started\"; exec sleep 30",
+        ])
+        .spawn()
+        .unwrap();
+    let since = Instant::now();
+    while !started.exists() {
+        assert!(
+            since.elapsed() < Duration::from_secs(10),
+            "the command didn't start"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let kill = std::process::Command::new("kill")
+        .args(["-HUP", &awsom.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(kill.success());
+    assert_eq!(awsom.wait().unwrap().code(), Some(128 + 1));
+}
+
+#[test]
+fn test_console_confirmation_needs_a_terminal() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.write(
+        ".config/awsom/config.toml",
+        "[console]\nrequire_confirmation = true\n",
+    );
+    home.login();
+
+    let output = home.awsom(&[
+        "profile",
+        "console",
+        "--account-name",
+        "prod",
+        "--role-name",
+        "Admin",
+        "--url-only",
+    ]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("no terminal to confirm on"),
+        "{}",
+        stderr(&output)
+    );
+    assert_eq!(sso.count("GET /federation/credentials"), 0);
+}
+
+#[test]
+fn test_account_filter_hides_accounts() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.write(
+        ".config/awsom/config.toml",
+        "[account_filters.mock]\nexclude = [\"dev\"]\n",
+    );
+    home.login();
+
+    let output = home.awsom(&["profile", "list", "--format", "json"]);
+    assert_success(&output);
+    let list = stdout(&output);
+    assert!(list.contains("111111111111"), "{}", list);
+    assert!(!list.contains("222222222222"), "{}", list);
+}
+
+#[test]
+fn test_on_conflict_with_user_managed_profile() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    let config = home.read(".aws/config");
+    home.write(
+        ".aws/config",
+        &format!("[profile prod]\nregion = eu-west-1\n\n{}", config),
+    );
+    home.login();
+
+    let create = |on_conflict: Option<&str>| {
+        let mut args = vec![
+            "profile",
+            "create",
+            "--account-name",
+            "prod",
+            "--role-name",
+            "Admin",
+            "--name",
+            "prod",
+        ];
+        if let Some(policy) = on_conflict {
+            args.extend(["--on-conflict", policy]);
+        }
+        home.awsom(&args)
+    };
+
+    // Without a terminal to ask on, the profile is left alone
+    let output = create(None);
+    assert_eq!(output.status.code(), Some(6), "{}", stderr(&output));
+    assert!(!home.read(".aws/credentials").contains("[prod]"));
+
+    let output = create(Some("suffix"));
+    assert_success(&output);
+    assert!(
+        stdout(&output).contains("Created profile 'prod-awsom'"),
+        "{}",
+        stdout(&output)
+    );
+    let config = home.read(".aws/config");
+    assert!(
+        config.starts_with("[profile prod]\nregion = eu-west-1\n"),
+        "{}",
+        config
+    );
+
+    let output = create(Some("import"));
+    assert_success(&output);
+    let config = home.read(".aws/config");
+    assert_eq!(config.matches("[profile prod]").count(), 1, "{}", config);
+    assert!(
+        !config.starts_with("[profile prod]"),
+        "prod is still user-managed: {}",
+        config
+    );
+    assert!(home.read(".aws/credentials").contains("[prod]"));
+}
+
+#[test]
+fn test_config_set_and_get() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+
+    let output = home.awsom(&["config", "set", "ui.sort_by", "expiry"]);
+    assert_success(&output);
+    let output = home.awsom(&["config", "get", "ui.sort_by"]);
+    assert_success(&output);
+    assert_eq!(stdout(&output).trim(), "expiry");
+    assert!(home
+        .read(".config/awsom/config.toml")
+        .contains("sort_by = \"expiry\""));
+
+    let output = home.awsom(&["config", "set", "ui.notify_before_minutes", "soon"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Invalid value for 'ui.notify_before_minutes'"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_logins_reuse_the_client_registration() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+    assert_success(&home.awsom(&["session", "logout"]));
+    home.login();
+    assert_eq!(sso.count("POST /client/register"), 1);
+    assert_eq!(sso.count("POST /device_authorization"), 2);
+
+    let output = home.awsom(&["cache", "clear", "--registrations"]);
+    assert_success(&output);
+    assert!(
+        stderr(&output).contains("Removed 1 client registration(s)"),
+        "{}",
+        stderr(&output)
+    );
+    assert_success(&home.awsom(&["session", "logout"]));
+    home.login();
+    assert_eq!(sso.count("POST /client/register"), 2);
+}
+
+#[test]
+fn test_start_all_expired_profiles() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    for (account, role, name) in [("prod", "Admin", "prod-admin"), ("dev", "ReadOnly", "dev")] {
+        let output = home.awsom(&[
+            "profile",
+            "create",
+            "--account-name",
+            account,
+            "--role-name",
+            role,
+            "--name",
+            name,
+        ]);
+        assert_success(&output);
+    }
+    let output = home.awsom(&["profile", "start", "--all-expired"]);
+    assert_success(&output);
+    assert!(
+        stdout(&output).contains("No expired profiles"),
+        "{}",
+        stdout(&output)
+    );
+
+    let output = home.awsom(&["profile", "stop", "dev", "--force"]);
+    assert_success(&output);
+    let issued = sso.state().credentials_issued;
+    let output = home.awsom(&["profile", "start", "--all-expired"]);
+    assert_success(&output);
+    assert!(
+        stdout(&output).contains("1 refreshed, ✗ 0 failed, 0 skipped"),
+        "{}",
+        stdout(&output)
+    );
+    assert_eq!(sso.state().credentials_issued, issued + 1);
+    assert!(home
+        .read(".aws/credentials")
+        .contains("aws_access_key_id = ASIA222222222222"));
+
+    // Without a valid token the profiles are left for after the next login
+    home.awsom(&["profile", "stop", "dev", "--force"]);
+    assert_success(&home.awsom(&["session", "logout"]));
+    let output = home.awsom(&["profile", "start", "--all-expired"]);
+    assert_success(&output);
+    assert!(
+        stdout(&output).contains("dev: skipped, session 'mock' is not logged in"),
+        "{}",
+        stdout(&output)
+    );
+    assert_eq!(sso.count("POST /device_authorization"), 1);
+}
+
+#[test]
+fn test_apps_list_and_open() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let output = home.awsom(&["apps", "list", "--format", "json"]);
+    assert_success(&output);
+    let apps: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(apps[0]["name"], "Team Wiki");
+    assert_eq!(apps[0]["description"], serde_json::Value::Null);
+
+    let output = home.awsom(&["apps", "open", "team wiki", "--url-only"]);
+    assert_success(&output);
+    assert!(
+        stdout(&output)
+            .trim()
+            .ends_with("/#/saml/default/Team%20Wiki/ins-1111111111111111"),
+        "{}",
+        stdout(&output)
+    );
+
+    let output = home.awsom(&["apps", "open", "nope", "--url-only"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_stats_count_activations() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    home.login();
+
+    let exec = |account: &str, role: &str| {
+        let output = home.awsom(&[
+            "profile",
+            "exec",
+            "--account-name",
+            account,
+            "--role-name",
+            role,
+            "--",
+            "true",
+        ]);
+        assert_success(&output);
+    };
+    exec("prod", "Admin");
+    exec("prod", "Admin");
+    exec("dev", "ReadOnly");
+
+    let output = home.awsom(&["stats", "--format", "json"]);
+    assert_success(&output);
+    let stats: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    // Most recently used first
+    assert_eq!(stats[0]["accountId"], "222222222222");
+    assert_eq!(stats[0]["count"], 1);
+    assert_eq!(stats[1]["roleName"], "Admin");
+    assert_eq!(stats[1]["count"], 2);
+
+    // Nothing is counted with stats turned off
+    home.write(".config/awsom/config.toml", "[stats]\nenabled = false\n");
+    exec("prod", "Admin");
+    let output = home.awsom(&["stats", "--format", "json"]);
+    let stats: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(stats[1]["count"], 2);
+
+    assert_success(&home.awsom(&["stats", "--clear"]));
+    let output = home.awsom(&["stats", "--format", "json"]);
+    assert_eq!(stdout(&output).trim(), "[]");
+}
+
+#[test]
+fn test_run_logs_in_and_writes_no_profiles() {
+    let sso = MockSso::start();
+    let home = Home::new(&sso);
+    let config_before = home.read(".aws/config");
+
+    let output = home
+        .command()
+        .env("AWS_PROFILE", "stale")
+        .args([
+            "run",
+            "--account-name",
+            "prod",
+            "--role-name",
+            "Admin",
+            "--",
+            "sh",
+            "-c",
+            "echo $AWS_ACCESS_KEY_ID ${AWS_PROFILE:-none} $AWS_REGION",
+        ])
+        .output()
+        .unwrap();
+    assert_success(&output);
+    assert_eq!(stdout(&output).trim(), "ASIA111111111111 none us-east-1");
+    assert_eq!(sso.state().tokens_issued, 1);
+
+    assert_eq!(home.read(".aws/config"), config_before);
+    assert_eq!(home.read(".aws/credentials"), "");
+}
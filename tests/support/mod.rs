@@ -0,0 +1,436 @@
+// Test support: a local stand-in for the SSO-OIDC and SSO portal APIs, and the awsom binary
+// run against it in a throwaway home directory
+//
+// The AWS SDK clients awsom builds read AWS_ENDPOINT_URL, so pointing that at `MockSso`
+// sends the device flow (RegisterClient, StartDeviceAuthorization, CreateToken) and the
+// portal calls (ListAccounts, ListAccountRoles, GetRoleCredentials, Logout) here instead of
+// AWS. Each connection serves one request and is closed, which keeps the HTTP side small.
+#![allow(dead_code)]
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use tempfile::TempDir;
+
+/// Start URL of the session `Home::new` sets up
+pub const START_URL: &str = "https://mock.awsapps.com/start";
+/// User code shown by the device flow
+pub const USER_CODE: &str = "MOCK-CODE";
+
+pub struct MockAccount {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub roles: Vec<&'static str>,
+}
+
+/// What the mock serves and what it has been asked
+pub struct State {
+    pub accounts: Vec<MockAccount>,
+    /// CreateToken answers AuthorizationPendingException this many more times
+    pub pending_polls: u32,
+    /// Access tokens the portal APIs accept
+    pub valid_tokens: HashSet<String>,
+    pub tokens_issued: u32,
+    /// Role credentials handed out; the count ends each session token
+    pub credentials_issued: u32,
+    /// `METHOD /path` of every request, query left out
+    pub requests: Vec<String>,
+}
+
+pub struct MockSso {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockSso {
+    /// A mock with two accounts: prod (Admin, ReadOnly) and dev (ReadOnly)
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock SSO server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State {
+            accounts: vec![
+                MockAccount {
+                    id: "111111111111",
+                    name: "prod",
+                    roles: vec!["Admin", "ReadOnly"],
+                },
+                MockAccount {
+                    id: "222222222222",
+                    name: "dev",
+                    roles: vec!["ReadOnly"],
+                },
+            ],
+            pending_polls: 0,
+            valid_tokens: HashSet::new(),
+            tokens_issued: 0,
+            credentials_issued: 0,
+            requests: Vec::new(),
+        }));
+
+        let served = Arc::clone(&state);
+        let base_url = url.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // A client that hangs up mid-request only fails its own call
+                let _ = serve(stream, &served, &base_url);
+            }
+        });
+        Self { url, state }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Make CreateToken say the user hasn't approved the code yet `polls` times
+    pub fn set_pending_polls(&self, polls: u32) {
+        self.state().pending_polls = polls;
+    }
+
+    /// Reject every access token issued so far, as after a sign-out elsewhere
+    pub fn revoke_tokens(&self) {
+        self.state().valid_tokens.clear();
+    }
+
+    /// Number of requests made to `request`, e.g. `POST /token`
+    pub fn count(&self, request: &str) -> usize {
+        self.state()
+            .requests
+            .iter()
+            .filter(|r| *r == request)
+            .count()
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    bearer_token: Option<String>,
+    body: Value,
+}
+
+impl Request {
+    fn param(&self, name: &str) -> &str {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map_or("", |(_, value)| value.as_str())
+    }
+}
+
+fn serve(stream: TcpStream, state: &Mutex<State>, base_url: &str) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = read_request(&mut reader)?;
+    let (status, error_type, body) = {
+        let mut state = state.lock().unwrap();
+        state
+            .requests
+            .push(format!("{} {}", request.method, request.path));
+        respond(&mut state, &request, base_url)
+    };
+
+    let body = body.to_string();
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    if let Some(error_type) = error_type {
+        response.push_str(&format!("x-amzn-errortype: {}\r\n", error_type));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    let mut stream = stream;
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+fn read_request(reader: &mut impl BufRead) -> std::io::Result<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| {
+            let value = urlencoding::decode(value).map_or_else(|_| value.into(), |v| v.into());
+            (key.to_string(), value)
+        })
+        .collect();
+
+    let mut content_length = 0;
+    let mut bearer_token = None;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "x-amz-sso_bearer_token" => bearer_token = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query,
+        bearer_token,
+        body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+    })
+}
+
+/// Status line, x-amzn-errortype for errors, and JSON body
+fn respond(
+    state: &mut State,
+    request: &Request,
+    base_url: &str,
+) -> (&'static str, Option<&'static str>, Value) {
+    let now = Utc::now().timestamp();
+    let portal_call = request.path.starts_with("/assignment/")
+        || request.path.starts_with("/federation/")
+        || request.path.starts_with("/instance/")
+        || request.path == "/logout";
+    if portal_call
+        && !request
+            .bearer_token
+            .as_ref()
+            .is_some_and(|token| state.valid_tokens.contains(token))
+    {
+        return error(
+            "401 Unauthorized",
+            "UnauthorizedException",
+            "Session token not found or invalid",
+        );
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/client/register") => ok(json!({
+            "clientId": "mock-client-id",
+            "clientSecret": "mock-client-secret",
+            "clientIdIssuedAt": now,
+            "clientSecretExpiresAt": now + 90 * 24 * 3600,
+        })),
+        ("POST", "/device_authorization") => ok(json!({
+            "deviceCode": "mock-device-code",
+            "userCode": USER_CODE,
+            "verificationUri": format!("{}/device", base_url),
+            "verificationUriComplete": format!("{}/device?user_code={}", base_url, USER_CODE),
+            "expiresIn": 600,
+            "interval": 1,
+        })),
+        ("POST", "/token") => {
+            let refreshing = request.body["grantType"] == "refresh_token";
+            if !refreshing && state.pending_polls > 0 {
+                state.pending_polls -= 1;
+                return error(
+                    "400 Bad Request",
+                    "AuthorizationPendingException",
+                    "authorization_pending",
+                );
+            }
+            state.tokens_issued += 1;
+            let token = format!("mock-access-token-{}", state.tokens_issued);
+            state.valid_tokens.insert(token.clone());
+            ok(json!({
+                "accessToken": token,
+                "tokenType": "Bearer",
+                "expiresIn": 8 * 3600,
+                "refreshToken": "mock-refresh-token",
+            }))
+        }
+        ("GET", "/assignment/accounts") => {
+            let accounts: Vec<Value> = state
+                .accounts
+                .iter()
+                .map(|account| {
+                    json!({
+                        "accountId": account.id,
+                        "accountName": account.name,
+                        "emailAddress": format!("{}@example.com", account.name),
+                    })
+                })
+                .collect();
+            ok(json!({ "accountList": accounts }))
+        }
+        ("GET", "/assignment/roles") => {
+            let account_id = request.param("account_id");
+            let roles: Vec<Value> = state
+                .accounts
+                .iter()
+                .filter(|account| account.id == account_id)
+                .flat_map(|account| account.roles.iter())
+                .map(|role| json!({ "roleName": role, "accountId": account_id }))
+                .collect();
+            ok(json!({ "roleList": roles }))
+        }
+        ("GET", "/federation/credentials") => {
+            let account_id = request.param("account_id");
+            let role_name = request.param("role_name");
+            let assigned = state
+                .accounts
+                .iter()
+                .any(|account| account.id == account_id && account.roles.contains(&role_name));
+            if !assigned {
+                return error(
+                    "403 Forbidden",
+                    "ForbiddenException",
+                    "No access to this account and role",
+                );
+            }
+            state.credentials_issued += 1;
+            ok(json!({
+                "roleCredentials": {
+                    "accessKeyId": format!("ASIA{}", account_id),
+                    "secretAccessKey": format!("secret-{}", role_name),
+                    "sessionToken": format!(
+                        "session-{}-{}-{}",
+                        account_id, role_name, state.credentials_issued
+                    ),
+                    "expiration": (now + 3600) * 1000,
+                }
+            }))
+        }
+        // The access portal's own API, for applications other than AWS accounts
+        ("GET", "/instance/appinstances") => ok(json!({
+            "result": [{
+                "id": "ins-1111111111111111",
+                "name": "Team Wiki",
+                "applicationName": "Custom SAML 2.0 application",
+                "description": "",
+            }],
+            "paginationToken": null,
+        })),
+        ("POST", "/logout") => {
+            if let Some(token) = &request.bearer_token {
+                state.valid_tokens.remove(token);
+            }
+            ok(json!({}))
+        }
+        _ => error("404 Not Found", "ResourceNotFoundException", "No such API"),
+    }
+}
+
+fn ok(body: Value) -> (&'static str, Option<&'static str>, Value) {
+    ("200 OK", None, body)
+}
+
+fn error(
+    status: &'static str,
+    error_type: &'static str,
+    message: &str,
+) -> (&'static str, Option<&'static str>, Value) {
+    (
+        status,
+        Some(error_type),
+        json!({ "error": error_type, "message": message }),
+    )
+}
+
+/// A home directory for awsom with an SSO session named `mock` pointing at a `MockSso`
+pub struct Home {
+    dir: TempDir,
+    endpoint: String,
+}
+
+impl Home {
+    pub fn new(sso: &MockSso) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let home = Self {
+            dir,
+            endpoint: sso.url().to_string(),
+        };
+        let output = home.awsom(&[
+            "session",
+            "add",
+            "--name",
+            "mock",
+            "--start-url",
+            START_URL,
+            "--region",
+            "us-east-1",
+        ]);
+        assert_success(&output);
+        home
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn read(&self, relative: &str) -> String {
+        fs::read_to_string(self.path().join(relative)).unwrap_or_default()
+    }
+
+    /// Write a file under the home directory, creating its parent directories
+    pub fn write(&self, relative: &str, content: &str) {
+        let path = self.path().join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// The awsom binary with nothing from the caller's environment but PATH
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_awsom"));
+        command
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", self.path())
+            .env("AWS_ENDPOINT_URL", &self.endpoint)
+            .env("AWS_EC2_METADATA_DISABLED", "true")
+            .env("NO_COLOR", "1")
+            .arg("--headless");
+        command
+    }
+
+    pub fn awsom(&self, args: &[&str]) -> Output {
+        self.command().args(args).output().unwrap()
+    }
+
+    /// Log in to the `mock` session, failing the test if that doesn't work
+    pub fn login(&self) -> Output {
+        let output = self.awsom(&["session", "login"]);
+        assert_success(&output);
+        output
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+pub fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "awsom failed with {}\nstdout:\n{}\nstderr:\n{}",
+        output.status,
+        stdout(output),
+        stderr(output)
+    );
+}